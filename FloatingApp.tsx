@@ -51,6 +51,11 @@ interface AIAnalysisResult {
   usage?: string;
 }
 
+// Detached entry cards load this page with ?entry=<id>&language=<code>
+const entryParams = new URLSearchParams(window.location.search);
+const detachedEntryId = entryParams.get('entry');
+const detachedLanguage = entryParams.get('language');
+
 const FloatingApp: React.FC = () => {
   const [query, setQuery] = useState('');
   const [isLoading, setIsLoading] = useState(false);
@@ -58,8 +63,10 @@ const FloatingApp: React.FC = () => {
   const [error, setError] = useState<string | null>(null);
   const [selectedResult, setSelectedResult] = useState<DictionaryEntry | null>(null);
   const [showInfo, setShowInfo] = useState(false);
-  const [language, setLanguage] = useState<string>('de');
+  const [language, setLanguage] = useState<string>(detachedLanguage || 'de');
   const inputRef = useRef<HTMLInputElement>(null);
+  // Sentence the current query came from, saved with the term
+  const queryContextRef = useRef<{ query?: string; context?: string | null; source?: string | null }>({});
   
   const [isSaving, setIsSaving] = useState(false);
   const [saveMessage, setSaveMessage] = useState<string | null>(null);
@@ -76,8 +83,34 @@ const FloatingApp: React.FC = () => {
   useEffect(() => {
     inputRef.current?.focus();
 
-    const unlisten = listen<string>('new-query', (event) => {
-      const queryText = event.payload;
+    if (detachedEntryId) {
+      // Entry cards show one entry and never follow clipboard queries
+      invoke<DictionaryEntry | null>('get_dictionary_entry', {
+        entryId: detachedEntryId,
+        language: detachedLanguage || 'de',
+      })
+        .then((entry) => {
+          if (entry) {
+            setQuery(entry.text);
+            setResults([entry]);
+            setSelectedResult(entry);
+          } else {
+            setError('Entry not found');
+          }
+        })
+        .catch((err) => setError(String(err)));
+      return;
+    }
+
+    // The backend sends the extracted run plus its script segmentation;
+    // older builds sent the bare string
+    const unlisten = listen<string | { query: string; original: string; language?: string | null; prefetched?: boolean; context?: string | null; source?: string | null }>('new-query', (event) => {
+      const queryText = typeof event.payload === 'string' ? event.payload : event.payload.query;
+      queryContextRef.current = typeof event.payload === 'string'
+        ? {}
+        : { query: queryText, context: event.payload.context, source: event.payload.source };
+      // Searching in the routed language hits the backend prefetch cache
+      const routedLanguage = typeof event.payload === 'string' ? undefined : event.payload.language ?? undefined;
       
       if (!isWord(queryText)) {
         console.log('[FloatingApp] Ignored non-word:', queryText);
@@ -86,7 +119,10 @@ const FloatingApp: React.FC = () => {
       
       console.log('[FloatingApp] Received word:', queryText);
       setQuery(queryText);
-      handleSearch(queryText);
+      if (routedLanguage) {
+        setLanguage(routedLanguage);
+      }
+      handleSearch(queryText, routedLanguage);
     });
 
     return () => {
@@ -94,7 +130,7 @@ const FloatingApp: React.FC = () => {
     };
   }, []);
 
-  const handleSearch = async (searchQuery: string = query) => {
+  const handleSearch = async (searchQuery: string = query, searchLanguage: string = language) => {
     if (!searchQuery.trim()) return;
 
     setIsLoading(true);
@@ -105,13 +141,13 @@ const FloatingApp: React.FC = () => {
     try {
       const data = await invoke<SearchResult>('search_dictionary', {
         word: searchQuery,
-        language: language
+        language: searchLanguage
       });
       
       if (data.success && data.entries && data.entries.length > 0) {
         setResults(data.entries);
         setSelectedResult(data.entries[0]);
-      } else if (language === 'sa') {
+      } else if (searchLanguage === 'sa') {
         setError('Sanskrit queries require additional processing');
       } else {
         setResults([]);
@@ -147,6 +183,10 @@ const FloatingApp: React.FC = () => {
 
   const handleClose = async () => {
     try {
+      if (detachedEntryId) {
+        await getCurrentWindow().close();
+        return;
+      }
       await invoke('hide_floating_window');
     } catch (err) {
       console.error('[FloatingApp] Failed to hide window:', err);
@@ -165,7 +205,7 @@ const FloatingApp: React.FC = () => {
   const handleLanguageChange = (lang: string) => {
     setLanguage(lang);
     if (query) {
-      handleSearch(query);
+      handleSearch(query, lang);
     }
   };
 
@@ -195,12 +235,14 @@ const FloatingApp: React.FC = () => {
         nextReview: Date.now() + 24 * 60 * 60 * 1000,
         interval: 0,
         easeFactor: 2.5,
-        reps: 0
+        reps: 0,
+        context: queryContextRef.current.query === query ? queryContextRef.current.context ?? null : null,
+        source: queryContextRef.current.query === query ? queryContextRef.current.source ?? null : null
       });
       
       console.log('[FloatingApp] Save result:', result);
       
-      const savedTerms = Array.isArray(result) ? result : [result];
+      const savedTerms = Array.isArray(result?.terms) ? result.terms : [];
       
       // Also save to IndexedDB for local access
       for (const term of savedTerms) {
@@ -2,6 +2,9 @@
 """
 梵语处理命令行接口
 用于Node.js后端调用
+
+--server 模式下常驻运行：从 stdin 逐行读取 JSON 请求，
+向 stdout 逐行写出带相同 id 的 JSON 响应，避免每次调用重新启动解释器。
 """
 
 import sys
@@ -14,74 +17,222 @@ sys.path.insert(0, str(Path(__file__).parent))
 
 from sandhi_api import SanskritProcessor
 
+ACTIONS = ["split", "transliterate", "health", "process"]
+
+# 批量操作：对应的单项操作，以及列表所在的参数名和单项参数名
+BATCH_ACTIONS = {
+    "split_batch": ("split", "words", "word"),
+    "transliterate_batch": ("transliterate", "texts", "text"),
+}
+
+# 拆分模式及说明；sandhi 总有规则拆分兜底
+SPLIT_MODES = {
+    "sandhi": "Sandhi splitting of compounds and phrases",
+    "morpheme": "Morpheme segmentation for dictionary lookup",
+}
+
+# Dharma Mitra 分析器，首次 process 请求时加载
+_analyzer = None
+
+
+def get_analyzer():
+    global _analyzer
+    if _analyzer is None:
+        from enhanced_sanskrit_api import processor as analyzer
+
+        _analyzer = analyzer
+    return _analyzer
+
+
+def split_modes(processor):
+    """按已安装的库列出各拆分模式可用的后端，顺序即尝试顺序"""
+    backends = {
+        "sandhi": [
+            name
+            for name, present in (
+                ("vidyut_sandhi", processor.sandhi_splitter is not None),
+                ("vidyut", processor.chedaka is not None),
+                ("rules", True),
+            )
+            if present
+        ],
+        "morpheme": [
+            name
+            for name, present in (
+                ("sanskrit_parser", processor.sanskrit_parser is not None),
+                ("chedaka", processor.chedaka is not None),
+            )
+            if present
+        ],
+    }
+    return [
+        {
+            "id": mode,
+            "description": description,
+            "available": bool(backends[mode]),
+            "backends": backends[mode],
+        }
+        for mode, description in SPLIT_MODES.items()
+    ]
+
+
+def handle(processor, action, params):
+    """执行一个操作，返回结果字典"""
+    if action in BATCH_ACTIONS:
+        single, list_field, item_field = BATCH_ACTIONS[action]
+        items = params.get(list_field)
+        if not isinstance(items, list):
+            raise ValueError(f"{list_field} 必须是数组")
+        results = []
+        # 逐项处理，单项失败不影响其余各项；结果顺序与输入一致
+        for item in items:
+            try:
+                results.append(handle(processor, single, {**params, item_field: item}))
+            except Exception as e:
+                results.append({"success": False, "action": single, "error": str(e)})
+        return {"success": True, "action": action, "results": results}
+
+    if action == "split":
+        word = params.get("word")
+        if not word:
+            raise ValueError("--word 参数必需")
+        mode = params.get("mode") or "sandhi"
+        if mode not in SPLIT_MODES:
+            raise ValueError(f"未知拆分模式: {mode}，可用: {', '.join(SPLIT_MODES)}")
+        return {
+            "success": True,
+            "action": "split",
+            "mode": mode,
+            "word": word,
+            "result": processor.split_sandhi(word, mode=mode),
+        }
+
+    if action == "transliterate":
+        text = params.get("text")
+        if not text:
+            raise ValueError("--text 参数必需")
+        from_scheme = params.get("from_scheme") or "devanagari"
+        to_scheme = params.get("to_scheme") or "iast"
+        return {
+            "success": True,
+            "action": "transliterate",
+            "original": text,
+            "transliterated": processor.transliterate(text, from_scheme, to_scheme),
+            "from_scheme": from_scheme,
+            "to_scheme": to_scheme,
+        }
+
+    if action == "health":
+        return {
+            "success": True,
+            "action": "health",
+            "initialized": processor.initialized,
+            "has_chedaka": processor.chedaka is not None,
+            "vidyut_available": processor.initialized,
+            "sandhi_splitter_available": processor.sandhi_splitter is not None,
+            "chedaka_available": processor.chedaka is not None,
+            "modes": split_modes(processor),
+            "service": "sanskrit-processor",
+        }
+
+    if action == "process":
+        text = params.get("text")
+        if not text:
+            raise ValueError("--text 参数必需")
+        analysis = get_analyzer().analyze(text)
+        if not analysis.get("success"):
+            return {"success": False, "action": "process", "error": analysis.get("error")}
+        segments = [
+            {
+                "original": seg.get("unsandhied", ""),
+                "split": None,
+                "lemma": seg.get("lemma") or None,
+                "morphology": {"tag": seg.get("tag", ""), "meanings": seg.get("meanings", [])},
+            }
+            for seg in analysis.get("segments", [])
+        ]
+        return {
+            "success": True,
+            "action": "process",
+            "text": text,
+            "segments": segments,
+            "sandhi_rules": analysis.get("sandhi_rules", []),
+            "processing_time_ms": analysis.get("processing_time_ms"),
+        }
+
+    raise ValueError(f"未知操作: {action}")
+
+
+def serve(processor, out):
+    """常驻模式：一行一个请求，一行一个响应，写到 out"""
+    for line in sys.stdin:
+        line = line.strip()
+        if not line:
+            continue
+        try:
+            request = json.loads(line)
+        except ValueError as e:
+            response = {"id": None, "success": False, "error": f"无效请求: {e}"}
+        else:
+            action = request.get("action")
+            try:
+                response = handle(processor, action, request)
+            except Exception as e:
+                response = {"success": False, "action": action, "error": str(e)}
+            response["id"] = request.get("id")
+        out.write(json.dumps(response, ensure_ascii=False) + "\n")
+        out.flush()
+
 
 def main():
     parser = argparse.ArgumentParser(description="梵语Sandhi处理命令行接口")
     parser.add_argument(
         "--action",
-        required=True,
-        choices=["split", "transliterate", "health"],
+        choices=ACTIONS,
         help="操作类型",
     )
+    parser.add_argument("--server", action="store_true", help="常驻模式，通过 stdin/stdout 交换 JSON 行")
     parser.add_argument("--word", help="要拆分的梵语单词")
     parser.add_argument(
         "--mode",
         default="sandhi",
-        choices=["sandhi", "morpheme"],
+        choices=list(SPLIT_MODES),
         help="拆分模式: sandhi或morpheme",
     )
     parser.add_argument("--text", help="要转写的文本")
     parser.add_argument("--from-scheme", default="devanagari", help="源转写方案")
     parser.add_argument("--to-scheme", default="iast", help="目标转写方案")
     parser.add_argument("--json", action="store_true", help="输出JSON格式")
+    parser.add_argument(
+        "--stdin-json",
+        action="store_true",
+        help="从 stdin 读取 JSON 字符串数组，批量执行 split 或 transliterate",
+    )
 
     args = parser.parse_args()
+    if not args.server and not args.action:
+        parser.error("--action 或 --server 参数必需")
+    if args.stdin_json and args.action not in ("split", "transliterate"):
+        parser.error("--stdin-json 只支持 split 和 transliterate")
+
+    if args.server:
+        # 库的输出一律转到 stderr，stdout 只留给响应
+        out = sys.stdout
+        sys.stdout = sys.stderr
+        serve(SanskritProcessor(), out)
+        sys.exit(0)
 
     # 初始化处理器
     processor = SanskritProcessor()
 
-    result = {}
-
     try:
-        if args.action == "split":
-            if not args.word:
-                print("错误: --word 参数必需", file=sys.stderr)
-                sys.exit(1)
-
-            split_result = processor.split_sandhi(args.word, mode=args.mode)
-            result = {
-                "success": True,
-                "action": "split",
-                "mode": args.mode,
-                "word": args.word,
-                "result": split_result,
-            }
-
-        elif args.action == "transliterate":
-            if not args.text:
-                print("错误: --text 参数必需", file=sys.stderr)
-                sys.exit(1)
-
-            transliterated = processor.transliterate(
-                args.text, args.from_scheme, args.to_scheme
-            )
-            result = {
-                "success": True,
-                "action": "transliterate",
-                "original": args.text,
-                "transliterated": transliterated,
-                "from_scheme": args.from_scheme,
-                "to_scheme": args.to_scheme,
-            }
-
-        elif args.action == "health":
-            result = {
-                "success": True,
-                "action": "health",
-                "initialized": processor.initialized,
-                "has_chedaka": processor.chedaka is not None,
-                "service": "sanskrit-processor",
-            }
+        if args.stdin_json:
+            batch_action = args.action + "_batch"
+            params = vars(args)
+            params[BATCH_ACTIONS[batch_action][1]] = json.load(sys.stdin)
+            result = handle(processor, batch_action, params)
+        else:
+            result = handle(processor, args.action, vars(args))
 
         # 输出结果
         if args.json:
@@ -101,6 +252,17 @@ def main():
                 elif "initialized" in result:
                     print(f"  初始化: {result['initialized']}")
                     print(f"  分词器: {'可用' if result['has_chedaka'] else '不可用'}")
+                elif "segments" in result:
+                    for seg in result["segments"]:
+                        print(f"  {seg['original']} ({seg['lemma']})")
+                elif "results" in result:
+                    for item in result["results"]:
+                        if not item.get("success"):
+                            print(f"  失败: {item.get('error')}")
+                        elif "transliterated" in item:
+                            print(f"  {item['original']} → {item['transliterated']}")
+                        else:
+                            print(f"  {item['word']} → {item['result']['parts']}")
             else:
                 print(f"失败: {result.get('error', '未知错误')}")
 
@@ -110,7 +272,7 @@ def main():
         error_result = {
             "success": False,
             "error": str(e),
-            "action": args.action if "args" in locals() else "unknown",
+            "action": args.action,
         }
 
         if args.json:
@@ -16,6 +16,7 @@ import sqlite3
 import os
 import sys
 import re
+import unicodedata
 from pathlib import Path
 
 # ISO语言代码到语言名称的映射
@@ -48,48 +49,43 @@ ISO_TO_LANGUAGE_NAME = {
 }
 
 
-def normalize_word(word):
+# 与 src-tauri/src/normalize.rs 的 normalize_word 保持一致，否则查询会静默落空
+EDGE_PUNCTUATION = ".,;:!?\"'()[]{}"
+GERMAN_FOLDING = {"ä": "ae", "ö": "oe", "ü": "ue", "ß": "ss", "-": "", "/": ""}
+ACCENT_STRIPPING_LANGUAGES = {"fr", "es", "pt", "it"}
+STRESS_MARK_LANGUAGES = {"ru", "uk", "be"}
+
+
+def strip_marks(char):
+    """去掉组合附加符号，只保留基本字母"""
+    decomposed = unicodedata.normalize("NFD", char)
+    return "".join(c for c in decomposed if not unicodedata.combining(c))
+
+
+def normalize_word(word, lang_code):
     """
     规范化单词，用于查询和索引
-    - 转换为小写
-    - 移除变音符号（简单处理）
-    - 移除非字母字符（保留连字符、空格等）
+    - 所有语言: NFC、小写、去掉首尾标点
+    - de: ä→ae, ö→oe, ü→ue, ß→ss，去掉连字符和斜杠
+    - fr/es/pt/it: 去掉重音（西班牙语保留 ñ）
+    - ru/uk/be: 去掉重音符号，ё→е
+    - 其他语言（包括 sa）: 不做进一步折叠，IAST 附加符号是字母的一部分
     """
     if not word:
         return ""
 
-    # 转换为小写
-    normalized = word.lower()
-
-    # 移除变音符号（简单处理）
-    replacements = {
-        "ä": "a",
-        "ö": "o",
-        "ü": "u",
-        "ß": "ss",
-        "é": "e",
-        "è": "e",
-        "ê": "e",
-        "á": "a",
-        "à": "a",
-        "â": "a",
-        "ó": "o",
-        "ò": "o",
-        "ô": "o",
-        "ú": "u",
-        "ù": "u",
-        "û": "u",
-        "î": "i",
-        "ï": "i",
-        "ç": "c",
-        "ñ": "n",
-    }
-
-    for orig, repl in replacements.items():
-        normalized = normalized.replace(orig, repl)
-
-    # 移除尾部的标点
-    normalized = normalized.strip(".,;:!?\"'()[]{}")
+    normalized = unicodedata.normalize("NFC", word).lower()
+    normalized = normalized.strip(EDGE_PUNCTUATION + " \t\n")
+
+    if lang_code == "de":
+        normalized = "".join(GERMAN_FOLDING.get(c, c) for c in normalized)
+    elif lang_code in ACCENT_STRIPPING_LANGUAGES:
+        normalized = "".join(
+            c if (lang_code == "es" and c == "ñ") else strip_marks(c) for c in normalized
+        )
+    elif lang_code in STRESS_MARK_LANGUAGES:
+        normalized = normalized.replace("ё", "е")
+        normalized = "".join(c for c in normalized if c not in "\u0300\u0301")
 
     return normalized
 
@@ -126,6 +122,8 @@ def create_database_schema(db_path, language_code):
         sense_index INTEGER NOT NULL,
         gloss TEXT NOT NULL,
         example TEXT,
+        examples TEXT,  -- JSON数组: [{"text", "translation"}]
+        tags TEXT,
         FOREIGN KEY (dictionary_id) REFERENCES dictionary(id) ON DELETE CASCADE
     )
     """)
@@ -182,6 +180,12 @@ def create_database_schema(db_path, language_code):
         "CREATE INDEX IF NOT EXISTS idx_dictionary_lang ON dictionary(lang_code)"
     )
     cursor.execute("CREATE INDEX IF NOT EXISTS idx_forms_form ON forms(form)")
+    cursor.execute(
+        "CREATE INDEX IF NOT EXISTS idx_forms_dictionary ON forms(dictionary_id)"
+    )
+    cursor.execute(
+        "CREATE INDEX IF NOT EXISTS idx_senses_dictionary ON senses(dictionary_id)"
+    )
     cursor.execute(
         "CREATE INDEX IF NOT EXISTS idx_forms_normalized ON forms(normalized_form)"
     )
@@ -218,6 +222,9 @@ def extract_pos_from_entry(entry):
     return ""
 
 
+SENSE_STRUCTURAL_TAGS = {"form-of", "alt-of", "no-gloss", "empty-gloss"}
+
+
 def extract_senses(entry):
     """提取词义"""
     senses = []
@@ -235,12 +242,35 @@ def extract_senses(entry):
             if not example and "example" in sense and isinstance(sense["example"], str):
                 example = sense["example"]
 
+            # kaikki 的例句在 examples 列表里，译文字段是 english 或 translation
+            examples = []
+            for ex in sense.get("examples", []):
+                if isinstance(ex, dict) and ex.get("text"):
+                    examples.append({
+                        "text": ex["text"],
+                        "translation": ex.get("english") or ex.get("translation"),
+                    })
+            if not example and examples:
+                example = examples[0]["text"]
+
+            # 词义标签（语域、地区等），结构性标签不保留
+            labels = []
+            for tag in sense.get("tags", []) + sense.get("raw_tags", []):
+                if isinstance(tag, str) and tag not in SENSE_STRUCTURAL_TAGS and tag not in labels:
+                    labels.append(tag)
+
             if gloss:  # 只添加有词义的条目
-                senses.append({"sense_index": i, "gloss": gloss, "example": example})
+                senses.append({
+                    "sense_index": i,
+                    "gloss": gloss,
+                    "example": example,
+                    "examples": json.dumps(examples, ensure_ascii=False) if examples else None,
+                    "tags": json.dumps(labels, ensure_ascii=False) if labels else None,
+                })
     return senses
 
 
-def extract_forms(entry, base_word):
+def extract_forms(entry, base_word, lang_code):
     """提取词形变化"""
     forms = []
     if "forms" in entry:
@@ -254,26 +284,38 @@ def extract_forms(entry, base_word):
                 forms.append(
                     {
                         "form": form,
-                        "normalized_form": normalize_word(form),
+                        "normalized_form": normalize_word(form, lang_code),
                         "tags": tags_str,
                     }
                 )
     return forms
 
 
+def extract_relation_words(entry, field):
+    """提取词条及各词义下 field（synonyms/antonyms）中的词，去重，保持原顺序"""
+    words = []
+    groups = [entry.get(field, [])]
+    groups.extend(sense.get(field, []) for sense in entry.get("senses", []))
+    for group in groups:
+        for item in group:
+            word = item.get("word", "") if isinstance(item, dict) else ""
+            # "Thesaurus:xxx" 指向词库页面，查询时只需要词本身
+            if word.startswith("Thesaurus:"):
+                word = word[len("Thesaurus:"):]
+            word = word.strip()
+            if word and word not in words:
+                words.append(word)
+    return words
+
+
 def extract_synonyms(entry):
     """提取同义词"""
-    synonyms = []
-    # kaikki.org数据中同义词可能在不同的字段
-    # 这里简单实现，实际可能需要根据具体数据结构调整
-    return synonyms
+    return extract_relation_words(entry, "synonyms")
 
 
 def extract_antonyms(entry):
     """提取反义词"""
-    antonyms = []
-    # kaikki.org数据中反义词可能在不同的字段
-    return antonyms
+    return extract_relation_words(entry, "antonyms")
 
 
 def extract_sounds(entry):
@@ -343,7 +385,7 @@ def process_jsonl_file(jsonl_path, iso_code, output_path=None):
                     continue
 
                 # 规范化单词
-                normalized_word = normalize_word(word)
+                normalized_word = normalize_word(word, iso_code)
 
                 # 提取词性
                 pos = extract_pos_from_entry(entry)
@@ -355,7 +397,7 @@ def process_jsonl_file(jsonl_path, iso_code, output_path=None):
                     continue  # 跳过没有词义的条目
 
                 # 提取词形变化
-                forms = extract_forms(entry, word)
+                forms = extract_forms(entry, word, iso_code)
 
                 # 提取同义词和反义词
                 synonyms = extract_synonyms(entry)
@@ -395,14 +437,16 @@ def process_jsonl_file(jsonl_path, iso_code, output_path=None):
                 for sense in senses:
                     cursor.execute(
                         """
-                        INSERT INTO senses (dictionary_id, sense_index, gloss, example)
-                        VALUES (?, ?, ?, ?)
+                        INSERT INTO senses (dictionary_id, sense_index, gloss, example, examples, tags)
+                        VALUES (?, ?, ?, ?, ?, ?)
                     """,
                         (
                             dictionary_id,
                             sense["sense_index"],
                             sense["gloss"],
                             sense["example"],
+                            sense["examples"],
+                            sense["tags"],
                         ),
                     )
 
@@ -0,0 +1,300 @@
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+use tauri::AppHandle;
+
+pub type ActionHandler =
+    Arc<dyn Fn(&AppHandle, serde_json::Value) -> Result<serde_json::Value, String> + Send + Sync>;
+pub type EnabledFn = Arc<dyn Fn(&AppHandle) -> bool + Send + Sync>;
+
+/// An actionable entry for the command palette.
+#[derive(Clone)]
+pub struct AppAction {
+    pub id: String,
+    pub title: String,
+    pub keywords: Vec<String>,
+    pub category: String,
+    pub enabled_fn: EnabledFn,
+    pub handler: ActionHandler,
+}
+
+impl AppAction {
+    pub fn new(id: &str, title: &str, category: &str, handler: ActionHandler) -> Self {
+        Self {
+            id: id.to_string(),
+            title: title.to_string(),
+            keywords: Vec::new(),
+            category: category.to_string(),
+            enabled_fn: Arc::new(|_| true),
+            handler,
+        }
+    }
+
+    pub fn keywords(mut self, keywords: &[&str]) -> Self {
+        self.keywords = keywords.iter().map(|k| k.to_string()).collect();
+        self
+    }
+
+    pub fn enabled_when(mut self, enabled_fn: EnabledFn) -> Self {
+        self.enabled_fn = enabled_fn;
+        self
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ActionMatch {
+    pub id: String,
+    pub title: String,
+    pub category: String,
+    pub keywords: Vec<String>,
+    pub enabled: bool,
+    pub score: i64,
+}
+
+#[derive(Default)]
+pub struct ActionRegistry {
+    actions: Mutex<Vec<AppAction>>,
+}
+
+impl ActionRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register an action, replacing any previous action with the same id.
+    pub fn register(&self, action: AppAction) {
+        let mut actions = self.actions.lock().unwrap();
+        actions.retain(|a| a.id != action.id);
+        actions.push(action);
+    }
+
+    fn get(&self, id: &str) -> Option<AppAction> {
+        self.actions.lock().unwrap().iter().find(|a| a.id == id).cloned()
+    }
+
+    /// Rank registered actions against an optional query.
+    /// Without a query every action is returned, grouped by category.
+    pub fn list(&self, app: &AppHandle, query: Option<&str>) -> Vec<ActionMatch> {
+        self.rank(query, |action| (action.enabled_fn)(app))
+    }
+
+    fn rank(&self, query: Option<&str>, is_enabled: impl Fn(&AppAction) -> bool) -> Vec<ActionMatch> {
+        let actions: Vec<AppAction> = self.actions.lock().unwrap().clone();
+        let query = query.map(|q| q.trim()).filter(|q| !q.is_empty());
+
+        let mut matches: Vec<ActionMatch> = actions
+            .iter()
+            .filter_map(|action| {
+                let score = match query {
+                    Some(q) => action_score(q, action)?,
+                    None => 0,
+                };
+                Some(ActionMatch {
+                    id: action.id.clone(),
+                    title: action.title.clone(),
+                    category: action.category.clone(),
+                    keywords: action.keywords.clone(),
+                    enabled: is_enabled(action),
+                    score,
+                })
+            })
+            .collect();
+
+        matches.sort_by(|a, b| {
+            b.score
+                .cmp(&a.score)
+                .then_with(|| b.enabled.cmp(&a.enabled))
+                .then_with(|| a.category.cmp(&b.category))
+                .then_with(|| a.title.cmp(&b.title))
+        });
+        matches
+    }
+
+    /// Dispatch an action. Disabled actions are never run.
+    pub fn invoke(
+        &self,
+        app: &AppHandle,
+        id: &str,
+        args: serde_json::Value,
+    ) -> Result<serde_json::Value, String> {
+        self.dispatch(id, |action| (action.enabled_fn)(app), |action| (action.handler)(app, args))
+    }
+
+    fn dispatch(
+        &self,
+        id: &str,
+        is_enabled: impl Fn(&AppAction) -> bool,
+        run: impl FnOnce(&AppAction) -> Result<serde_json::Value, String>,
+    ) -> Result<serde_json::Value, String> {
+        let action = self
+            .get(id)
+            .ok_or_else(|| format!("Unknown action '{}'", id))?;
+        if !is_enabled(&action) {
+            return Err(format!("Action '{}' is currently disabled", id));
+        }
+        run(&action)
+    }
+}
+
+fn action_score(query: &str, action: &AppAction) -> Option<i64> {
+    // Title matches outrank keyword matches of the same quality
+    let title_score = fuzzy_score(query, &action.title).map(|s| s + 10);
+    let keyword_score = action
+        .keywords
+        .iter()
+        .filter_map(|k| fuzzy_score(query, k))
+        .max();
+    let id_score = fuzzy_score(query, &action.id).map(|s| s - 5);
+
+    [title_score, keyword_score, id_score].into_iter().flatten().max()
+}
+
+/// Subsequence scoring: every query character must appear in order in the
+/// candidate. Consecutive runs, word starts and an exact prefix earn bonuses,
+/// gaps and unmatched trailing characters cost points.
+pub fn fuzzy_score(query: &str, candidate: &str) -> Option<i64> {
+    let query: Vec<char> = query.to_lowercase().chars().filter(|c| !c.is_whitespace()).collect();
+    if query.is_empty() {
+        return Some(0);
+    }
+    let candidate: Vec<char> = candidate.to_lowercase().chars().collect();
+
+    let mut score: i64 = 0;
+    let mut qi = 0;
+    let mut prev_match: Option<usize> = None;
+
+    for (ci, c) in candidate.iter().enumerate() {
+        if qi == query.len() {
+            break;
+        }
+        if *c != query[qi] {
+            continue;
+        }
+
+        score += 1;
+        let word_start = ci == 0 || !candidate[ci - 1].is_alphanumeric();
+        if word_start {
+            score += 8;
+        }
+        match prev_match {
+            Some(p) if p + 1 == ci => score += 5,
+            Some(p) => score -= (ci - p - 1).min(5) as i64,
+            None => score -= ci.min(10) as i64,
+        }
+        prev_match = Some(ci);
+        qi += 1;
+    }
+
+    if qi < query.len() {
+        return None;
+    }
+
+    let query_str: String = query.iter().collect();
+    let candidate_str: String = candidate.iter().collect();
+    if candidate_str.starts_with(&query_str) {
+        score += 15;
+    }
+    if candidate_str == query_str {
+        score += 25;
+    }
+    score -= (candidate.len().saturating_sub(query.len()) / 4) as i64;
+
+    Some(score)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    fn action(id: &str, title: &str, category: &str) -> AppAction {
+        AppAction::new(id, title, category, Arc::new(|_, _| Ok(serde_json::Value::Null)))
+    }
+
+    fn registry() -> ActionRegistry {
+        let registry = ActionRegistry::new();
+        registry.register(action("settings.open", "Open settings", "Settings").keywords(&["preferences"]));
+        registry.register(action("review.start", "Start review session", "Vocabulary").keywords(&["srs", "study"]));
+        registry.register(action("clipboard.start", "Start clipboard monitoring", "Clipboard"));
+        registry.register(action("clipboard.stop", "Stop clipboard monitoring", "Clipboard"));
+        registry
+    }
+
+    #[test]
+    fn exact_beats_prefix_beats_word_start_beats_scattered() {
+        let exact = fuzzy_score("settings", "settings").unwrap();
+        let prefix = fuzzy_score("set", "settings").unwrap();
+        let word_start = fuzzy_score("set", "open settings").unwrap();
+        let scattered = fuzzy_score("set", "reset the timer").unwrap();
+        assert!(exact > prefix, "{} > {}", exact, prefix);
+        assert!(prefix > word_start, "{} > {}", prefix, word_start);
+        assert!(word_start > scattered, "{} > {}", word_start, scattered);
+    }
+
+    #[test]
+    fn query_must_be_a_subsequence() {
+        assert_eq!(fuzzy_score("xyz", "Open settings"), None);
+        assert_eq!(fuzzy_score("sto", "Open settings"), None);
+        assert_eq!(fuzzy_score("  ", "anything"), Some(0));
+        assert!(fuzzy_score("OPS", "open settings").is_some());
+    }
+
+    #[test]
+    fn title_match_outranks_keyword_match() {
+        let by_title = action("a", "Study", "X");
+        let by_keyword = action("b", "Review", "X").keywords(&["study"]);
+        assert!(action_score("study", &by_title) > action_score("study", &by_keyword));
+    }
+
+    #[test]
+    fn ranks_by_score_then_enabled() {
+        let registry = registry();
+        let ids: Vec<String> = registry.rank(Some("stop"), |_| true).into_iter().map(|m| m.id).collect();
+        assert_eq!(ids.first().map(String::as_str), Some("clipboard.stop"));
+
+        let ids: Vec<String> = registry.rank(Some("pref"), |_| true).into_iter().map(|m| m.id).collect();
+        assert_eq!(ids, vec!["settings.open"]);
+
+        // Equal scores: enabled actions come first
+        let matches = registry.rank(Some("start clipboard"), |a| a.id != "clipboard.start");
+        assert!(matches.iter().any(|m| m.id == "clipboard.start" && !m.enabled));
+
+        let all = registry.rank(None, |a| !a.id.starts_with("clipboard"));
+        assert_eq!(all.len(), 4);
+        assert!(all[0].enabled && all[1].enabled);
+        assert!(!all[2].enabled && !all[3].enabled);
+    }
+
+    #[test]
+    fn register_replaces_same_id() {
+        let registry = registry();
+        registry.register(action("settings.open", "Preferences", "Settings"));
+        let all = registry.rank(None, |_| true);
+        assert_eq!(all.len(), 4);
+        assert!(all.iter().any(|m| m.title == "Preferences"));
+    }
+
+    #[test]
+    fn disabled_action_is_not_run() {
+        let registry = registry();
+        let ran = Cell::new(false);
+        let result = registry.dispatch("clipboard.stop", |_| false, |_| {
+            ran.set(true);
+            Ok(serde_json::Value::Null)
+        });
+        assert_eq!(result, Err("Action 'clipboard.stop' is currently disabled".to_string()));
+        assert!(!ran.get());
+
+        let result = registry.dispatch("clipboard.stop", |_| true, |_| {
+            ran.set(true);
+            Ok(serde_json::json!("done"))
+        });
+        assert_eq!(result, Ok(serde_json::json!("done")));
+        assert!(ran.get());
+    }
+
+    #[test]
+    fn unknown_action_is_an_error() {
+        let result = registry().dispatch("nope", |_| true, |_| Ok(serde_json::Value::Null));
+        assert_eq!(result, Err("Unknown action 'nope'".to_string()));
+    }
+}
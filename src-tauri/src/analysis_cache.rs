@@ -0,0 +1,55 @@
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use crate::commands::dictionary::BatchQueryResult;
+use crate::commands::sanskrit::ProcessResult;
+
+const ANALYSIS_TTL: Duration = Duration::from_secs(30 * 60);
+const ANALYSIS_CAPACITY: usize = 32;
+
+/// A finished analysis kept around so it can be exported without re-running
+pub enum CachedAnalysis {
+    Text(ProcessResult),
+    Batch {
+        language: String,
+        /// Words in the order they were requested
+        words: Vec<String>,
+        result: BatchQueryResult,
+    },
+}
+
+/// Analyses by id, with when they were stored
+type AnalysisMap = HashMap<String, (Instant, Arc<CachedAnalysis>)>;
+
+static CACHE: Lazy<Mutex<AnalysisMap>> = Lazy::new(|| Mutex::new(HashMap::new()));
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Keep `analysis` and return the id to retrieve it with
+pub fn store(analysis: CachedAnalysis) -> String {
+    let prefix = match analysis {
+        CachedAnalysis::Text(_) => "text",
+        CachedAnalysis::Batch { .. } => "batch",
+    };
+    let id = format!("{}-{}", prefix, NEXT_ID.fetch_add(1, Ordering::SeqCst));
+
+    let mut cache = CACHE.lock().unwrap();
+    cache.retain(|_, (at, _)| at.elapsed() < ANALYSIS_TTL);
+    if cache.len() >= ANALYSIS_CAPACITY {
+        if let Some(oldest) = cache.iter().min_by_key(|(_, (at, _))| *at).map(|(k, _)| k.clone()) {
+            cache.remove(&oldest);
+        }
+    }
+    cache.insert(id.clone(), (Instant::now(), Arc::new(analysis)));
+    id
+}
+
+/// The analysis stored under `id`, unless it expired or was evicted
+pub fn get(id: &str) -> Option<Arc<CachedAnalysis>> {
+    let cache = CACHE.lock().unwrap();
+    cache
+        .get(id)
+        .filter(|(at, _)| at.elapsed() < ANALYSIS_TTL)
+        .map(|(_, analysis)| analysis.clone())
+}
@@ -0,0 +1,420 @@
+//! Vocabulary export for Anki: a tab-separated notes file with Anki's header
+//! directives, or an `.apkg` package (a legacy collection.anki2 SQLite file
+//! zipped with an empty media map) that can also carry review scheduling.
+
+use flate2::write::DeflateEncoder;
+use flate2::{Compression, Crc};
+use rusqlite::{params, Connection};
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+
+const DAY_SECS: i64 = 24 * 60 * 60;
+/// Ids of the deck and note type inside the package; Anki maps them to its
+/// own on import
+const DECK_ID: i64 = 1_700_000_000_001;
+const MODEL_ID: i64 = 1_700_000_000_002;
+
+/// One note: front, back, notes, Anki tags and the term's review state
+pub struct AnkiNote {
+    pub id: String,
+    pub front: String,
+    pub back: String,
+    pub notes: String,
+    pub tags: Vec<String>,
+    pub schedule: Option<AnkiSchedule>,
+}
+
+pub struct AnkiSchedule {
+    /// Epoch ms the card is next due
+    pub due_ms: i64,
+    pub interval_days: i32,
+    pub ease_factor: f64,
+    pub reps: i32,
+}
+
+/// An Anki tag for `tag`: Anki separates tags with spaces
+pub fn tag(tag: &str) -> String {
+    tag.split_whitespace().collect::<Vec<_>>().join("_")
+}
+
+fn html_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('\n', "<br>")
+}
+
+/// Notes as a tab-separated file Anki's "Import File" reads without asking
+/// for the separator or field mapping. Scheduling isn't part of this format.
+pub fn write_tsv(path: &Path, deck_name: &str, notes: &[AnkiNote]) -> Result<(), String> {
+    let mut out = String::new();
+    out.push_str("#separator:tab\n#html:true\n");
+    out.push_str(&format!("#deck:{}\n", deck_name.replace(['\n', '\r'], " ")));
+    out.push_str("#columns:Front\tBack\tNotes\tTags\n#tags column:4\n");
+    for note in notes {
+        let fields = [
+            html_escape(&note.front),
+            html_escape(&note.back),
+            html_escape(&note.notes),
+            note.tags.join(" "),
+        ];
+        let fields: Vec<String> = fields
+            .iter()
+            .map(|f| {
+                let f = f.replace(['\t', '\r'], " ");
+                if f.contains('"') {
+                    format!("\"{}\"", f.replace('"', "\"\""))
+                } else {
+                    f
+                }
+            })
+            .collect();
+        out.push_str(&fields.join("\t"));
+        out.push('\n');
+    }
+    fs::write(path, out).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+/// Notes as an `.apkg` package with one card each in `deck_name`
+pub fn write_apkg(path: &Path, deck_name: &str, notes: &[AnkiNote]) -> Result<(), String> {
+    let collection = std::env::temp_dir().join(format!(
+        "lumina-anki-{}-{}.anki2",
+        std::process::id(),
+        chrono::Utc::now().timestamp_millis()
+    ));
+    let built = build_collection(&collection, deck_name, notes)
+        .map_err(|e| format!("Failed to build Anki collection: {}", e))
+        .and_then(|()| fs::read(&collection).map_err(|e| format!("Failed to read Anki collection: {}", e)));
+    let _ = fs::remove_file(&collection);
+    let package = zip(&[("collection.anki2", &built?), ("media", b"{}")])?;
+    fs::write(path, package).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+fn build_collection(path: &Path, deck_name: &str, notes: &[AnkiNote]) -> rusqlite::Result<()> {
+    let conn = Connection::open(path)?;
+    conn.execute_batch(
+        "CREATE TABLE col (id integer primary key, crt integer not null, mod integer not null,
+            scm integer not null, ver integer not null, dty integer not null, usn integer not null,
+            ls integer not null, conf text not null, models text not null, decks text not null,
+            dconf text not null, tags text not null);
+         CREATE TABLE notes (id integer primary key, guid text not null, mid integer not null,
+            mod integer not null, usn integer not null, tags text not null, flds text not null,
+            sfld integer not null, csum integer not null, flags integer not null, data text not null);
+         CREATE TABLE cards (id integer primary key, nid integer not null, did integer not null,
+            ord integer not null, mod integer not null, usn integer not null, type integer not null,
+            queue integer not null, due integer not null, ivl integer not null, factor integer not null,
+            reps integer not null, lapses integer not null, left integer not null, odue integer not null,
+            odid integer not null, flags integer not null, data text not null);
+         CREATE TABLE revlog (id integer primary key, cid integer not null, usn integer not null,
+            ease integer not null, ivl integer not null, lastIvl integer not null, factor integer not null,
+            time integer not null, type integer not null);
+         CREATE TABLE graves (usn integer not null, oid integer not null, type integer not null);
+         CREATE INDEX ix_notes_usn ON notes (usn);
+         CREATE INDEX ix_cards_usn ON cards (usn);
+         CREATE INDEX ix_revlog_usn ON revlog (usn);
+         CREATE INDEX ix_cards_nid ON cards (nid);
+         CREATE INDEX ix_cards_sched ON cards (did, queue, due);
+         CREATE INDEX ix_revlog_cid ON revlog (cid);
+         CREATE INDEX ix_notes_csum ON notes (csum);",
+    )?;
+
+    let now_ms = chrono::Utc::now().timestamp_millis();
+    let now = now_ms / 1000;
+    // Review due dates are days since the collection was created
+    let created = now - now.rem_euclid(DAY_SECS);
+    conn.execute(
+        "INSERT INTO col VALUES (1, ?1, ?2, ?2, 11, 0, 0, 0, ?3, ?4, ?5, ?6, '{}')",
+        params![
+            created,
+            now_ms,
+            collection_conf().to_string(),
+            models(now).to_string(),
+            decks(deck_name, now).to_string(),
+            deck_conf().to_string(),
+        ],
+    )?;
+
+    let tx = conn.unchecked_transaction()?;
+    for (i, note) in notes.iter().enumerate() {
+        let id = now_ms + i as i64;
+        let front = html_escape(&note.front);
+        let fields = [front.as_str(), &html_escape(&note.back), &html_escape(&note.notes)].join("\u{1f}");
+        let tags = if note.tags.is_empty() {
+            String::new()
+        } else {
+            format!(" {} ", note.tags.join(" "))
+        };
+        tx.execute(
+            "INSERT INTO notes VALUES (?1, ?2, ?3, ?4, -1, ?5, ?6, ?7, ?8, 0, '')",
+            params![id, guid(&note.id), MODEL_ID, now, tags, fields, note.front, checksum(&note.front)],
+        )?;
+
+        let (card_type, due, interval, factor, reps) = match &note.schedule {
+            Some(s) => (
+                2,
+                ((s.due_ms / 1000 - created) / DAY_SECS).max(0),
+                s.interval_days.max(1),
+                (s.ease_factor * 1000.0).round() as i64,
+                s.reps,
+            ),
+            None => (0, i as i64 + 1, 0, 0, 0),
+        };
+        tx.execute(
+            "INSERT INTO cards VALUES (?1, ?1, ?2, 0, ?3, -1, ?4, ?4, ?5, ?6, ?7, ?8, 0, 0, 0, 0, 0, '')",
+            params![id, DECK_ID, now, card_type, due, interval, factor, reps],
+        )?;
+    }
+    tx.commit()
+}
+
+fn collection_conf() -> serde_json::Value {
+    serde_json::json!({
+        "activeDecks": [1],
+        "curDeck": 1,
+        "newSpread": 0,
+        "collapseTime": 1200,
+        "timeLim": 0,
+        "estTimes": true,
+        "dueCounts": true,
+        "curModel": MODEL_ID,
+        "nextPos": 1,
+        "sortType": "noteFld",
+        "sortBackwards": false,
+        "addToCur": true,
+    })
+}
+
+fn models(now: i64) -> serde_json::Value {
+    let field = |name: &str, ord: i32| {
+        serde_json::json!({
+            "name": name, "ord": ord, "sticky": false, "rtl": false,
+            "font": "Arial", "size": 20, "media": [],
+        })
+    };
+    serde_json::json!({
+        MODEL_ID.to_string(): {
+            "id": MODEL_ID,
+            "name": "Lumina Vocabulary",
+            "type": 0,
+            "mod": now,
+            "usn": -1,
+            "sortf": 0,
+            "did": DECK_ID,
+            "tags": [],
+            "vers": [],
+            "flds": [field("Front", 0), field("Back", 1), field("Notes", 2)],
+            "tmpls": [{
+                "name": "Card 1",
+                "ord": 0,
+                "qfmt": "{{Front}}",
+                "afmt": "{{FrontSide}}<hr id=answer>{{Back}}{{#Notes}}<br><br><small>{{Notes}}</small>{{/Notes}}",
+                "bqfmt": "",
+                "bafmt": "",
+                "did": null,
+            }],
+            "req": [[0, "any", [0]]],
+            "css": ".card { font-family: arial; font-size: 20px; text-align: center; }",
+            "latexPre": "\\documentclass[12pt]{article}\n\\special{papersize=3in,5in}\n\\usepackage[utf8]{inputenc}\n\\usepackage{amssymb,amsmath}\n\\pagestyle{empty}\n\\setlength{\\parindent}{0in}\n\\begin{document}\n",
+            "latexPost": "\\end{document}",
+        }
+    })
+}
+
+fn decks(deck_name: &str, now: i64) -> serde_json::Value {
+    let deck = |id: i64, name: &str| {
+        serde_json::json!({
+            "id": id, "name": name, "desc": "", "conf": 1, "dyn": 0,
+            "collapsed": false, "extendNew": 10, "extendRev": 50, "mod": now, "usn": -1,
+            "newToday": [0, 0], "revToday": [0, 0], "lrnToday": [0, 0], "timeToday": [0, 0],
+        })
+    };
+    serde_json::json!({
+        "1": deck(1, "Default"),
+        DECK_ID.to_string(): deck(DECK_ID, deck_name),
+    })
+}
+
+fn deck_conf() -> serde_json::Value {
+    serde_json::json!({
+        "1": {
+            "id": 1, "name": "Default", "mod": 0, "usn": 0,
+            "maxTaken": 60, "autoplay": true, "timer": 0, "replayq": true, "dyn": false,
+            "new": {"delays": [1, 10], "ints": [1, 4, 7], "initialFactor": 2500,
+                    "order": 1, "perDay": 20, "bury": true, "separate": true},
+            "rev": {"perDay": 200, "ease4": 1.3, "fuzz": 0.05, "ivlFct": 1,
+                    "maxIvl": 36500, "minSpace": 1, "bury": true},
+            "lapse": {"delays": [10], "mult": 0, "minInt": 1, "leechFails": 8, "leechAction": 0},
+        }
+    })
+}
+
+/// Stable note guid from the term id, so exporting again updates the same
+/// notes in Anki instead of adding copies. SHA-1 rather than `DefaultHasher`,
+/// whose output may change between Rust releases.
+fn guid(id: &str) -> String {
+    const BASE91: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789!#$%&()*+,-./:;<=>?@[]^_`{|}~";
+    let digest = sha1(id.as_bytes());
+    let mut n = u64::from_be_bytes([
+        digest[0], digest[1], digest[2], digest[3], digest[4], digest[5], digest[6], digest[7],
+    ]);
+    let mut out = String::new();
+    while n > 0 {
+        out.push(BASE91[(n % 91) as usize] as char);
+        n /= 91;
+    }
+    out
+}
+
+/// Anki's duplicate check: the first 8 hex digits of the SHA-1 of the
+/// sort field, as a number
+fn checksum(field: &str) -> i64 {
+    let digest = sha1(field.as_bytes());
+    i64::from(u32::from_be_bytes([digest[0], digest[1], digest[2], digest[3]]))
+}
+
+fn sha1(data: &[u8]) -> [u8; 20] {
+    let mut h: [u32; 5] = [0x67452301, 0xEFCDAB89, 0x98BADCFE, 0x10325476, 0xC3D2E1F0];
+    let mut message = data.to_vec();
+    message.push(0x80);
+    while message.len() % 64 != 56 {
+        message.push(0);
+    }
+    message.extend_from_slice(&((data.len() as u64) * 8).to_be_bytes());
+
+    for block in message.chunks(64) {
+        let mut w = [0u32; 80];
+        for (i, word) in block.chunks(4).enumerate() {
+            w[i] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+        }
+        for i in 16..80 {
+            w[i] = (w[i - 3] ^ w[i - 8] ^ w[i - 14] ^ w[i - 16]).rotate_left(1);
+        }
+        let [mut a, mut b, mut c, mut d, mut e] = h;
+        for (i, &word) in w.iter().enumerate() {
+            let (f, k) = match i {
+                0..=19 => ((b & c) | (!b & d), 0x5A827999),
+                20..=39 => (b ^ c ^ d, 0x6ED9EBA1),
+                40..=59 => ((b & c) | (b & d) | (c & d), 0x8F1BBCDC),
+                _ => (b ^ c ^ d, 0xCA62C1D6),
+            };
+            let temp = a.rotate_left(5).wrapping_add(f).wrapping_add(e).wrapping_add(k).wrapping_add(word);
+            e = d;
+            d = c;
+            c = b.rotate_left(30);
+            b = a;
+            a = temp;
+        }
+        for (state, value) in h.iter_mut().zip([a, b, c, d, e]) {
+            *state = state.wrapping_add(value);
+        }
+    }
+
+    let mut digest = [0u8; 20];
+    for (chunk, word) in digest.chunks_mut(4).zip(h) {
+        chunk.copy_from_slice(&word.to_be_bytes());
+    }
+    digest
+}
+
+/// A zip archive of deflated `files`
+fn zip(files: &[(&str, &[u8])]) -> Result<Vec<u8>, String> {
+    let mut out = Vec::new();
+    let mut central = Vec::new();
+    for (name, data) in files {
+        let mut crc = Crc::new();
+        crc.update(data);
+        let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(data).map_err(|e| format!("Failed to compress {}: {}", name, e))?;
+        let compressed = encoder.finish().map_err(|e| format!("Failed to compress {}: {}", name, e))?;
+        let offset = out.len() as u32;
+
+        // Version needed, flags, method (deflate), DOS time and date (1980-01-01)
+        let common: Vec<u8> = [
+            &20u16.to_le_bytes()[..],
+            &0u16.to_le_bytes(),
+            &8u16.to_le_bytes(),
+            &0u16.to_le_bytes(),
+            &0x21u16.to_le_bytes(),
+            &crc.sum().to_le_bytes(),
+            &(compressed.len() as u32).to_le_bytes(),
+            &(data.len() as u32).to_le_bytes(),
+            &(name.len() as u16).to_le_bytes(),
+            &0u16.to_le_bytes(),
+        ]
+        .concat();
+
+        out.extend_from_slice(&0x04034b50u32.to_le_bytes());
+        out.extend_from_slice(&common);
+        out.extend_from_slice(name.as_bytes());
+        out.extend_from_slice(&compressed);
+
+        central.extend_from_slice(&0x02014b50u32.to_le_bytes());
+        central.extend_from_slice(&20u16.to_le_bytes());
+        central.extend_from_slice(&common);
+        // Comment length, disk, internal and external attributes
+        central.extend_from_slice(&[0u8; 10]);
+        central.extend_from_slice(&offset.to_le_bytes());
+        central.extend_from_slice(name.as_bytes());
+    }
+
+    let central_offset = out.len() as u32;
+    out.extend_from_slice(&central);
+    out.extend_from_slice(&0x06054b50u32.to_le_bytes());
+    out.extend_from_slice(&[0u8; 4]);
+    out.extend_from_slice(&(files.len() as u16).to_le_bytes());
+    out.extend_from_slice(&(files.len() as u16).to_le_bytes());
+    out.extend_from_slice(&(central.len() as u32).to_le_bytes());
+    out.extend_from_slice(&central_offset.to_le_bytes());
+    out.extend_from_slice(&0u16.to_le_bytes());
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn note(id: &str, front: &str) -> AnkiNote {
+        AnkiNote {
+            id: id.to_string(),
+            front: front.to_string(),
+            back: "house".to_string(),
+            notes: String::new(),
+            tags: vec![tag("lumina de")],
+            schedule: None,
+        }
+    }
+
+    fn exported(path: &Path) -> Vec<(String, String, i64)> {
+        let conn = Connection::open(path).unwrap();
+        let mut stmt = conn.prepare("SELECT guid, sfld, csum FROM notes ORDER BY id").unwrap();
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?))).unwrap();
+        rows.map(Result::unwrap).collect()
+    }
+
+    #[test]
+    fn sha1_matches_the_reference_vectors() {
+        let hex = |d: [u8; 20]| d.iter().map(|b| format!("{:02x}", b)).collect::<String>();
+        assert_eq!(hex(sha1(b"abc")), "a9993e364706816aba3e25717850c26c9cd0d89d");
+        assert_eq!(hex(sha1(b"")), "da39a3ee5e6b4b0d3255bfef95601890afd80709");
+    }
+
+    #[test]
+    fn exporting_again_updates_the_same_notes() {
+        let dir = TempDir::new().unwrap();
+        let notes = [note("de:haus:1", "Haus"), note("de:baum:2", "Baum")];
+        let (first, second) = (dir.path().join("first.anki2"), dir.path().join("second.anki2"));
+        build_collection(&first, "Deutsch", &notes).unwrap();
+        build_collection(&second, "Deutsch", &notes).unwrap();
+
+        // Anki matches imported notes to existing ones by guid
+        let (first, second) = (exported(&first), exported(&second));
+        assert_eq!(first, second);
+        assert_eq!(first.len(), 2);
+        assert_ne!(first[0].0, first[1].0);
+        // Pinned so a change to the derivation can't silently duplicate notes
+        assert_eq!(guid("de:haus:1"), "i@TS-j9;!m");
+        assert_eq!(first[0].2, checksum("Haus"));
+    }
+}
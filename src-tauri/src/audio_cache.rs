@@ -0,0 +1,167 @@
+use once_cell::sync::Lazy;
+use std::collections::hash_map::DefaultHasher;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+use crate::offline_queue::{self, OperationError};
+
+/// Recordings kept on disk; the least recently played go first
+const MAX_CACHE_BYTES: u64 = 100 * 1024 * 1024;
+/// A single recording larger than this isn't a pronunciation clip
+const MAX_FILE_BYTES: usize = 10 * 1024 * 1024;
+const DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(15);
+
+static CLIENT: Lazy<reqwest::Client> = Lazy::new(|| {
+    reqwest::Client::builder()
+        .user_agent("LuminousLute/1.5.0")
+        .timeout(DOWNLOAD_TIMEOUT)
+        .build()
+        .unwrap_or_default()
+});
+
+/// Held while evicting, so two downloads don't both delete the same files
+static EVICTION: Mutex<()> = Mutex::new(());
+
+pub fn cache_dir(app: &tauri::AppHandle) -> PathBuf {
+    use tauri::Manager;
+    let base_dir = app.path()
+        .app_data_dir()
+        .unwrap_or_else(|_| std::env::current_dir().unwrap_or_else(|_| PathBuf::from(".")));
+    base_dir.join("data").join("audio_cache")
+}
+
+/// File a recording is cached as: a hash of its URL, keeping the extension
+/// so the webview knows the format
+fn cache_file(dir: &Path, url: &str) -> PathBuf {
+    let mut hasher = DefaultHasher::new();
+    url.hash(&mut hasher);
+    let extension = url
+        .rsplit('/')
+        .next()
+        .and_then(|name| name.rsplit_once('.'))
+        .map(|(_, ext)| ext.to_lowercase())
+        .filter(|ext| matches!(ext.as_str(), "ogg" | "oga" | "mp3" | "wav" | "opus" | "flac"))
+        .unwrap_or_else(|| "ogg".to_string());
+    dir.join(format!("{:016x}.{}", hasher.finish(), extension))
+}
+
+/// The cached copy of `url`, downloading it first if needed. Returns the
+/// path and whether it was already cached.
+pub async fn fetch(dir: &Path, url: &str) -> Result<(PathBuf, bool), OperationError> {
+    let path = cache_file(dir, url);
+    if path.is_file() {
+        // Playing a recording counts as a use for eviction
+        if let Err(e) = fs::File::options().append(true).open(&path).and_then(|f| f.set_modified(SystemTime::now())) {
+            eprintln!("[AUDIO] Could not touch {:?}: {}", path, e);
+        }
+        return Ok((path, true));
+    }
+
+    let response = CLIENT
+        .get(url)
+        .send()
+        .await
+        .map_err(|e| offline_queue::classify("Audio download failed", e))?;
+    if !response.status().is_success() {
+        return Err(format!("Audio download failed: HTTP {}", response.status()).into());
+    }
+    let bytes = response
+        .bytes()
+        .await
+        .map_err(|e| offline_queue::classify("Audio download failed", e))?;
+    if bytes.len() > MAX_FILE_BYTES {
+        return Err(format!("Audio file too large ({} bytes)", bytes.len()).into());
+    }
+
+    fs::create_dir_all(dir).map_err(|e| format!("Failed to create audio cache: {}", e))?;
+    // Written under a temporary name so a half-written file is never served
+    let partial = path.with_extension("part");
+    fs::write(&partial, &bytes).map_err(|e| format!("Failed to cache audio: {}", e))?;
+    fs::rename(&partial, &path).map_err(|e| format!("Failed to cache audio: {}", e))?;
+    eprintln!("[AUDIO] Cached {} ({} bytes)", url, bytes.len());
+
+    evict(dir, MAX_CACHE_BYTES, &path);
+    Ok((path, false))
+}
+
+/// Delete the least recently used recordings until the cache fits in
+/// `max_bytes`, never removing `keep`
+fn evict(dir: &Path, max_bytes: u64, keep: &Path) {
+    let _guard = EVICTION.lock().unwrap();
+    let mut files: Vec<(SystemTime, u64, PathBuf)> = fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|entry| {
+            let meta = entry.metadata().ok()?;
+            meta.is_file()
+                .then(|| (meta.modified().unwrap_or(SystemTime::UNIX_EPOCH), meta.len(), entry.path()))
+        })
+        .collect();
+    let mut total: u64 = files.iter().map(|(_, size, _)| size).sum();
+    if total <= max_bytes {
+        return;
+    }
+
+    files.sort();
+    for (_, size, path) in files {
+        if total <= max_bytes {
+            break;
+        }
+        if path == keep {
+            continue;
+        }
+        match fs::remove_file(&path) {
+            Ok(()) => total -= size,
+            Err(e) => eprintln!("[AUDIO] Could not evict {:?}: {}", path, e),
+        }
+    }
+    eprintln!("[AUDIO] Cache trimmed to {} bytes", total);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    /// Nothing listens on the discard port, so connecting fails at once
+    const UNREACHABLE: &str = "http://127.0.0.1:9/Hund.ogg";
+
+    #[tokio::test]
+    async fn cached_recording_is_not_downloaded_again() {
+        let dir = TempDir::new().unwrap();
+        let path = cache_file(dir.path(), UNREACHABLE);
+        fs::write(&path, b"OggS").unwrap();
+
+        // A retry after an earlier attempt succeeded is a no-op, even offline
+        for _ in 0..2 {
+            let (fetched, cached) = fetch(dir.path(), UNREACHABLE).await.unwrap();
+            assert_eq!((fetched, cached), (path.clone(), true));
+        }
+        assert_eq!(fs::read(&path).unwrap(), b"OggS");
+    }
+
+    #[tokio::test]
+    async fn half_written_recording_is_retried_as_offline() {
+        let dir = TempDir::new().unwrap();
+        let path = cache_file(dir.path(), UNREACHABLE);
+        fs::write(path.with_extension("part"), b"Og").unwrap();
+
+        match fetch(dir.path(), UNREACHABLE).await {
+            Err(OperationError::Offline(_)) => {}
+            other => panic!("expected an offline error, got {:?}", other),
+        }
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn cache_file_keeps_known_extensions() {
+        let dir = Path::new("/cache");
+        assert!(cache_file(dir, "https://example.org/De-Hund.ogg").to_string_lossy().ends_with(".ogg"));
+        assert!(cache_file(dir, "https://example.org/en-us-dog.MP3").to_string_lossy().ends_with(".mp3"));
+        assert!(cache_file(dir, "https://example.org/play?id=1").to_string_lossy().ends_with(".ogg"));
+        assert_ne!(cache_file(dir, "https://a/x.ogg"), cache_file(dir, "https://b/x.ogg"));
+    }
+}
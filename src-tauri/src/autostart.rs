@@ -0,0 +1,325 @@
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use crate::write_log;
+
+/// Passed by the login entry so the app starts in the tray, with no window
+pub const HIDDEN_FLAG: &str = "--hidden";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AutostartStatus {
+    /// Whether the OS will start Lumina at login
+    pub enabled: bool,
+    /// The executable the entry starts
+    pub executable: Option<String>,
+    /// False when the entry starts another copy, e.g. a portable exe that
+    /// has been moved since
+    pub current: bool,
+    /// Registry key or file holding the entry
+    pub location: String,
+}
+
+pub fn launched_hidden() -> bool {
+    std::env::args().skip(1).any(|arg| arg == HIDDEN_FLAG)
+}
+
+/// What the login entry should start: the AppImage rather than its
+/// temporary mount point, otherwise this executable
+fn executable() -> Result<PathBuf, String> {
+    if cfg!(target_os = "linux") {
+        if let Some(appimage) = std::env::var_os("APPIMAGE") {
+            return Ok(PathBuf::from(appimage));
+        }
+    }
+    std::env::current_exe().map_err(|e| format!("Cannot locate the executable: {}", e))
+}
+
+fn same_file(a: &Path, b: &Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+/// The entry as it is on disk (or in the registry) right now
+pub fn status() -> Result<AutostartStatus, String> {
+    let entry = platform::read()?;
+    let current = match &entry {
+        Some(path) => same_file(path, &executable()?),
+        None => false,
+    };
+    Ok(AutostartStatus {
+        enabled: entry.is_some(),
+        executable: entry.map(|path| path.to_string_lossy().into_owned()),
+        current,
+        location: platform::location(),
+    })
+}
+
+pub fn set(enabled: bool) -> Result<AutostartStatus, String> {
+    if enabled {
+        platform::write(&executable()?)?;
+    } else {
+        platform::remove()?;
+    }
+    status()
+}
+
+/// Point an existing entry at this executable if it was moved since the
+/// entry was written. Called at startup.
+pub fn refresh() {
+    match status() {
+        Ok(status) if status.enabled && !status.current => {
+            let previous = status.executable.unwrap_or_default();
+            match set(true) {
+                Ok(_) => write_log(&format!("[Autostart] Entry moved from {} to this executable", previous)),
+                Err(e) => write_log(&format!("[Autostart] Failed to update the entry: {}", e)),
+            }
+        }
+        Ok(_) => {}
+        Err(e) => write_log(&format!("[Autostart] Failed to read the entry: {}", e)),
+    }
+}
+
+#[tauri::command]
+pub fn get_autostart_enabled() -> Result<AutostartStatus, String> {
+    status()
+}
+
+/// Start Lumina (in the tray) at login, or stop doing so
+#[tauri::command]
+pub fn set_autostart_enabled(enabled: bool) -> Result<AutostartStatus, String> {
+    let status = set(enabled)?;
+    write_log(&format!("[Autostart] {}", if status.enabled { "Enabled" } else { "Disabled" }));
+    Ok(status)
+}
+
+/// A value under HKCU\...\Run, which needs no elevation
+#[cfg(windows)]
+mod platform {
+    use std::os::windows::process::CommandExt;
+    use std::path::{Path, PathBuf};
+    use std::process::Command;
+    use super::HIDDEN_FLAG;
+
+    const RUN_KEY: &str = r"HKCU\Software\Microsoft\Windows\CurrentVersion\Run";
+    const VALUE: &str = "Lumina";
+    const CREATE_NO_WINDOW: u32 = 0x08000000;
+
+    fn reg(args: &[&str]) -> Result<std::process::Output, String> {
+        Command::new("reg")
+            .args(args)
+            .creation_flags(CREATE_NO_WINDOW)
+            .output()
+            .map_err(|e| format!("Failed to run reg: {}", e))
+    }
+
+    pub fn location() -> String {
+        format!(r"{}\{}", RUN_KEY, VALUE)
+    }
+
+    /// The executable in `"C:\...\Lumina.exe" --hidden`
+    pub fn read() -> Result<Option<PathBuf>, String> {
+        let output = reg(&["query", RUN_KEY, "/v", VALUE])?;
+        if !output.status.success() {
+            // reg fails when the value doesn't exist
+            return Ok(None);
+        }
+        let text = String::from_utf8_lossy(&output.stdout);
+        let Some(data) = text.lines().find_map(|line| line.split_once("REG_SZ").map(|(_, data)| data.trim())) else {
+            return Ok(None);
+        };
+        let path = match data.strip_prefix('"') {
+            Some(rest) => rest.split('"').next().unwrap_or(rest),
+            None => data.split_whitespace().next().unwrap_or(data),
+        };
+        Ok(Some(PathBuf::from(path)))
+    }
+
+    pub fn write(executable: &Path) -> Result<(), String> {
+        let data = format!("\"{}\" {}", executable.display(), HIDDEN_FLAG);
+        let output = reg(&["add", RUN_KEY, "/v", VALUE, "/t", "REG_SZ", "/d", &data, "/f"])?;
+        if !output.status.success() {
+            return Err(format!("reg add failed: {}", String::from_utf8_lossy(&output.stderr).trim()));
+        }
+        Ok(())
+    }
+
+    pub fn remove() -> Result<(), String> {
+        if read()?.is_none() {
+            return Ok(());
+        }
+        let output = reg(&["delete", RUN_KEY, "/v", VALUE, "/f"])?;
+        if !output.status.success() {
+            return Err(format!("reg delete failed: {}", String::from_utf8_lossy(&output.stderr).trim()));
+        }
+        Ok(())
+    }
+}
+
+/// A LaunchAgent plist, loaded by launchd at the next login
+#[cfg(target_os = "macos")]
+mod platform {
+    use std::fs;
+    use std::path::{Path, PathBuf};
+    use super::HIDDEN_FLAG;
+
+    const LABEL: &str = "com.lumina.app";
+
+    fn plist_path() -> PathBuf {
+        let home = std::env::var_os("HOME").map(PathBuf::from).unwrap_or_default();
+        home.join("Library").join("LaunchAgents").join(format!("{}.plist", LABEL))
+    }
+
+    pub fn location() -> String {
+        plist_path().display().to_string()
+    }
+
+    fn escape(text: &str) -> String {
+        text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+    }
+
+    fn unescape(text: &str) -> String {
+        text.replace("&lt;", "<").replace("&gt;", ">").replace("&amp;", "&")
+    }
+
+    /// The first ProgramArguments string
+    pub fn read() -> Result<Option<PathBuf>, String> {
+        let path = plist_path();
+        if !path.exists() {
+            return Ok(None);
+        }
+        let content = fs::read_to_string(&path).map_err(|e| format!("Failed to read {:?}: {}", path, e))?;
+        let program = content
+            .split_once("<key>ProgramArguments</key>")
+            .and_then(|(_, rest)| rest.split_once("<string>"))
+            .and_then(|(_, rest)| rest.split_once("</string>"))
+            .map(|(program, _)| PathBuf::from(unescape(program)));
+        Ok(program)
+    }
+
+    pub fn write(executable: &Path) -> Result<(), String> {
+        let path = plist_path();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("Failed to create {:?}: {}", parent, e))?;
+        }
+        let content = format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+    <key>Label</key>
+    <string>{}</string>
+    <key>ProgramArguments</key>
+    <array>
+        <string>{}</string>
+        <string>{}</string>
+    </array>
+    <key>RunAtLoad</key>
+    <true/>
+</dict>
+</plist>
+"#,
+            LABEL,
+            escape(&executable.to_string_lossy()),
+            HIDDEN_FLAG
+        );
+        fs::write(&path, content).map_err(|e| format!("Failed to write {:?}: {}", path, e))
+    }
+
+    pub fn remove() -> Result<(), String> {
+        let path = plist_path();
+        match fs::remove_file(&path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(format!("Failed to remove {:?}: {}", path, e)),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// An XDG autostart .desktop file
+#[cfg(all(unix, not(target_os = "macos")))]
+mod platform {
+    use std::fs;
+    use std::path::{Path, PathBuf};
+    use super::HIDDEN_FLAG;
+
+    fn desktop_path() -> PathBuf {
+        let config = std::env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .filter(|dir| dir.is_absolute())
+            .unwrap_or_else(|| std::env::var_os("HOME").map(PathBuf::from).unwrap_or_default().join(".config"));
+        config.join("autostart").join("lumina.desktop")
+    }
+
+    pub fn location() -> String {
+        desktop_path().display().to_string()
+    }
+
+    /// Quote an Exec argument as the Desktop Entry spec asks
+    fn quote(arg: &str) -> String {
+        let mut quoted = String::from('"');
+        for c in arg.chars() {
+            if matches!(c, '"' | '`' | '$' | '\\') {
+                quoted.push('\\');
+            }
+            quoted.push(c);
+        }
+        quoted.push('"');
+        quoted
+    }
+
+    /// The program of an Exec line: quoted with backslash escapes, or up to
+    /// the first space
+    fn program(exec: &str) -> String {
+        let Some(rest) = exec.strip_prefix('"') else {
+            return exec.split_whitespace().next().unwrap_or_default().to_string();
+        };
+        let mut program = String::new();
+        let mut chars = rest.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => program.extend(chars.next()),
+                '"' => break,
+                c => program.push(c),
+            }
+        }
+        program
+    }
+
+    /// None when there is no file or it is switched off with Hidden=true
+    pub fn read() -> Result<Option<PathBuf>, String> {
+        let path = desktop_path();
+        if !path.exists() {
+            return Ok(None);
+        }
+        let content = fs::read_to_string(&path).map_err(|e| format!("Failed to read {:?}: {}", path, e))?;
+        let disabled = content
+            .lines()
+            .any(|line| matches!(line.trim(), "Hidden=true" | "X-GNOME-Autostart-enabled=false"));
+        if disabled {
+            return Ok(None);
+        }
+        let exec = content.lines().find_map(|line| line.trim().strip_prefix("Exec="));
+        Ok(exec.map(|exec| PathBuf::from(program(exec.trim()))))
+    }
+
+    pub fn write(executable: &Path) -> Result<(), String> {
+        let path = desktop_path();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("Failed to create {:?}: {}", parent, e))?;
+        }
+        let content = format!(
+            "[Desktop Entry]\nType=Application\nName=Lumina\nComment=Dictionary lookup in the tray\nExec={} {}\nTerminal=false\nX-GNOME-Autostart-enabled=true\n",
+            quote(&executable.to_string_lossy()),
+            HIDDEN_FLAG
+        );
+        fs::write(&path, content).map_err(|e| format!("Failed to write {:?}: {}", path, e))
+    }
+
+    pub fn remove() -> Result<(), String> {
+        let path = desktop_path();
+        match fs::remove_file(&path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(format!("Failed to remove {:?}: {}", path, e)),
+            _ => Ok(()),
+        }
+    }
+}
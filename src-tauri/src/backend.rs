@@ -0,0 +1,305 @@
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::io::{BufRead, BufReader, Read};
+use std::path::PathBuf;
+use std::process::{Child, ExitStatus, Stdio};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager};
+use crate::settings::SettingsState;
+use crate::{logging, python, recovery, write_log, write_service_log, AppState};
+
+/// How long a service gets to exit after the polite request before it is
+/// killed outright
+const STOP_GRACE: Duration = Duration::from_secs(3);
+/// Stderr lines kept per service, for reporting why it exited
+const STDERR_TAIL_LINES: usize = 20;
+/// Restarts in a row before a crashing service is given up on
+const MAX_RESTARTS: u32 = 5;
+/// First restart delay; doubles per attempt up to `MAX_RESTART_DELAY`
+const RESTART_DELAY: Duration = Duration::from_secs(1);
+const MAX_RESTART_DELAY: Duration = Duration::from_secs(30);
+/// A service that ran this long before exiting gets a fresh restart budget
+const HEALTHY_PERIOD: Duration = Duration::from_secs(60);
+
+/// What it takes to start one service, and to start it again
+#[derive(Debug, Clone)]
+pub struct ServiceSpec {
+    pub label: &'static str,
+    pub script: &'static str,
+    /// The endpoint that answers while the service is up
+    pub health_url: &'static str,
+    pub scripts_dir: PathBuf,
+}
+
+struct BackendService {
+    spec: ServiceSpec,
+    started: Instant,
+    stderr_tail: Arc<Mutex<VecDeque<String>>>,
+    child: Child,
+}
+
+/// A started service as it is right now
+pub struct ServiceProcess {
+    pub label: String,
+    pub script: String,
+    pub pid: u32,
+    pub health_url: Option<String>,
+    pub uptime: Duration,
+    /// Set once the process has exited
+    pub exit: Option<ExitStatus>,
+    pub stderr_tail: Vec<String>,
+}
+
+/// Payload of `service-started`, `service-restarting` and `service-failed`
+#[derive(Debug, Clone, Serialize)]
+pub struct ServiceEvent {
+    pub script: String,
+    pub label: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pid: Option<u32>,
+    /// Restart attempt, from 1
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub attempt: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub delay_ms: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exit_code: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl ServiceEvent {
+    fn new(spec: &ServiceSpec) -> Self {
+        ServiceEvent {
+            script: spec.script.to_string(),
+            label: spec.label.to_string(),
+            pid: None,
+            attempt: None,
+            delay_ms: None,
+            exit_code: None,
+            error: None,
+        }
+    }
+}
+
+/// The Python services started by `start_backend_services`, so they can be
+/// stopped again instead of outliving the app, and restarted when they crash
+#[derive(Default)]
+pub struct BackendServices {
+    running: Mutex<Vec<BackendService>>,
+    /// Restarts in a row per script
+    restarts: Mutex<HashMap<&'static str, u32>>,
+}
+
+impl BackendServices {
+    /// Launch a service and supervise it: its output is logged line by line
+    /// and an unexpected exit schedules a restart. Returns the PID.
+    pub fn start(&self, app: &AppHandle, spec: ServiceSpec) -> Result<u32, String> {
+        let mut child = python::resolve()?
+            .script(spec.script)
+            .current_dir(&spec.scripts_dir)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| e.to_string())?;
+        let pid = child.id();
+        write_log(&format!("✓ {} started (PID: {})", spec.label, pid));
+        recovery::record_backend_process(pid, spec.script);
+
+        let stderr_tail = Arc::new(Mutex::new(VecDeque::new()));
+        if let Some(stderr) = child.stderr.take() {
+            let tail = Arc::clone(&stderr_tail);
+            let label = spec.label;
+            std::thread::spawn(move || log_lines(stderr, label, "stderr", Some(&tail)));
+        }
+        if let Some(stdout) = child.stdout.take() {
+            let label = spec.label;
+            std::thread::spawn(move || log_lines(stdout, label, "stdout", None));
+        }
+        // Watched apart from the output: a reloader child of a Flask debug
+        // server can keep the pipes open after the service itself died
+        let supervised = spec.clone();
+        let handle = app.clone();
+        std::thread::spawn(move || supervise(&handle, supervised, pid));
+
+        let mut event = ServiceEvent::new(&spec);
+        event.pid = Some(pid);
+        self.running.lock().unwrap().push(BackendService {
+            spec,
+            started: Instant::now(),
+            stderr_tail,
+            child,
+        });
+        let _ = app.emit("service-started", event);
+        Ok(pid)
+    }
+
+    /// Whether any started service is still running
+    pub fn any_running(&self) -> bool {
+        let mut running = self.running.lock().unwrap();
+        running.iter_mut().any(|service| matches!(service.child.try_wait(), Ok(None)))
+    }
+
+    /// Every started service, including ones that have exited since
+    pub fn processes(&self) -> Vec<ServiceProcess> {
+        let mut running = self.running.lock().unwrap();
+        running
+            .iter_mut()
+            .map(|service| ServiceProcess {
+                label: service.spec.label.to_string(),
+                script: service.spec.script.to_string(),
+                pid: service.child.id(),
+                health_url: Some(service.spec.health_url.to_string()),
+                uptime: service.started.elapsed(),
+                exit: service.child.try_wait().ok().flatten(),
+                stderr_tail: service.stderr_tail.lock().unwrap().iter().cloned().collect(),
+            })
+            .collect()
+    }
+
+    /// Stop every service: ask first, kill after `STOP_GRACE`, then wait for
+    /// the exit. Returns how many were still running. Supervisors see their
+    /// service gone and don't restart it.
+    pub fn stop_all(&self) -> usize {
+        let services = std::mem::take(&mut *self.running.lock().unwrap());
+        self.restarts.lock().unwrap().clear();
+        let mut stopped = 0;
+        for mut service in services {
+            if !matches!(service.child.try_wait(), Ok(None)) {
+                recovery::forget_backend_process(service.spec.script);
+                continue;
+            }
+            let pid = service.child.id();
+            request_exit(pid);
+            let deadline = Instant::now() + STOP_GRACE;
+            let mut exited = false;
+            while Instant::now() < deadline {
+                if !matches!(service.child.try_wait(), Ok(None)) {
+                    exited = true;
+                    break;
+                }
+                std::thread::sleep(Duration::from_millis(50));
+            }
+            if !exited {
+                logging::warn(&format!("⚠ {} (PID: {}) ignored the stop request, killing it", service.spec.label, pid));
+                let _ = service.child.kill();
+            }
+            let _ = service.child.wait();
+            recovery::forget_backend_process(service.spec.script);
+            write_log(&format!("✓ {} stopped (PID: {})", service.spec.label, pid));
+            stopped += 1;
+        }
+        stopped
+    }
+
+    /// How the service with `pid` is doing: None once it was stopped or
+    /// replaced, otherwise its exit (if any), uptime and stderr tail
+    fn exit_of(&self, pid: u32) -> Option<(Option<ExitStatus>, Duration, Vec<String>)> {
+        let mut running = self.running.lock().unwrap();
+        let service = running.iter_mut().find(|s| s.child.id() == pid)?;
+        let exit = service.child.try_wait().ok().flatten();
+        let tail = service.stderr_tail.lock().unwrap().iter().cloned().collect();
+        Some((exit, service.started.elapsed(), tail))
+    }
+
+    /// Drop the exited service with `pid`; false when it is already gone
+    fn remove(&self, pid: u32) -> bool {
+        let mut running = self.running.lock().unwrap();
+        let before = running.len();
+        running.retain(|s| s.child.id() != pid);
+        running.len() != before
+    }
+}
+
+/// Wait for the service to exit; unless it was stopped on purpose, log why
+/// and start it again after a growing delay
+fn supervise(app: &AppHandle, spec: ServiceSpec, pid: u32) {
+    let (status, uptime, tail) = loop {
+        let Some((exit, uptime, tail)) = app.state::<AppState>().backend_services.exit_of(pid) else {
+            return;
+        };
+        if let Some(status) = exit {
+            break (status, uptime, tail);
+        }
+        std::thread::sleep(Duration::from_millis(200));
+    };
+    logging::error(&format!("✗ {} (PID: {}) exited with {}; see services.log", spec.label, pid, status));
+    write_service_log(&format!("[{}] exited with {}, last stderr lines:", spec.label, status));
+    for line in &tail {
+        write_service_log(&format!("[{}] [stderr] {}", spec.label, line));
+    }
+
+    let mut event = ServiceEvent::new(&spec);
+    event.pid = Some(pid);
+    event.exit_code = status.code();
+    if !app.state::<SettingsState>().get().auto_restart_services {
+        event.error = Some("Auto-restart is turned off".to_string());
+        let _ = app.emit("service-failed", event);
+        return;
+    }
+    let services = &app.state::<AppState>().backend_services;
+    let attempt = {
+        let mut restarts = services.restarts.lock().unwrap();
+        let count = restarts.entry(spec.script).or_insert(0);
+        if uptime >= HEALTHY_PERIOD {
+            *count = 0;
+        }
+        *count += 1;
+        *count
+    };
+    if attempt > MAX_RESTARTS {
+        logging::error(&format!("✗ {} keeps crashing, giving up after {} restarts", spec.label, MAX_RESTARTS));
+        event.error = Some(format!("Crashed {} times in a row", attempt));
+        let _ = app.emit("service-failed", event);
+        return;
+    }
+
+    let delay = RESTART_DELAY.saturating_mul(1 << (attempt - 1)).min(MAX_RESTART_DELAY);
+    write_log(&format!("{} restarting in {}s (attempt {})", spec.label, delay.as_secs(), attempt));
+    event.attempt = Some(attempt);
+    event.delay_ms = Some(delay.as_millis() as u64);
+    let _ = app.emit("service-restarting", event.clone());
+    std::thread::sleep(delay);
+
+    // Stopped or restarted by hand while waiting
+    if !services.remove(pid) {
+        return;
+    }
+    if let Err(e) = services.start(app, spec) {
+        logging::error(&format!("✗ Failed to restart {}: {}", event.label, e));
+        event.error = Some(e);
+        let _ = app.emit("service-failed", event);
+    }
+}
+
+/// Copy a service's output to services.log as it arrives, one line at a time
+fn log_lines(stream: impl Read, label: &str, marker: &str, tail: Option<&Mutex<VecDeque<String>>>) {
+    for line in BufReader::new(stream).lines().map_while(Result::ok) {
+        write_service_log(&format!("[{}] [{}] {}", label, marker, line));
+        if let Some(tail) = tail {
+            let mut tail = tail.lock().unwrap();
+            if tail.len() == STDERR_TAIL_LINES {
+                tail.pop_front();
+            }
+            tail.push_back(line);
+        }
+    }
+}
+
+/// SIGTERM, so Flask can shut down its server
+#[cfg(unix)]
+fn request_exit(pid: u32) {
+    let _ = std::process::Command::new("kill").args(["-TERM", &pid.to_string()]).status();
+}
+
+/// taskkill without /F asks the process to close
+#[cfg(windows)]
+fn request_exit(pid: u32) {
+    use std::os::windows::process::CommandExt;
+    const CREATE_NO_WINDOW: u32 = 0x08000000;
+    let _ = std::process::Command::new("taskkill")
+        .args(["/PID", &pid.to_string(), "/T"])
+        .creation_flags(CREATE_NO_WINDOW)
+        .status();
+}
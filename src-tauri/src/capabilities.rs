@@ -0,0 +1,30 @@
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::RwLock;
+
+/// Optional features whose tooling may be missing from an install
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Capabilities {
+    pub sanskrit_tools: bool,
+    pub tts: bool,
+}
+
+static CURRENT: Lazy<RwLock<Capabilities>> = Lazy::new(|| RwLock::new(Capabilities::default()));
+
+/// File-existence checks only; runs during the startup capabilities phase
+pub fn detect(base_path: &Path) -> Capabilities {
+    let scripts = base_path.join("scripts");
+    let detected = Capabilities {
+        sanskrit_tools: scripts.join("sanskrit_cli.py").exists(),
+        tts: scripts.join("tts_api.py").exists(),
+    };
+    *CURRENT.write().unwrap() = detected;
+    detected
+}
+
+/// Last detected capabilities; everything off until startup has probed
+pub fn current() -> Capabilities {
+    *CURRENT.read().unwrap()
+}
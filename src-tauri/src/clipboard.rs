@@ -0,0 +1,629 @@
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+use tauri::menu::CheckMenuItem;
+use tauri::{AppHandle, Emitter, Manager, Wry};
+use tauri_plugin_clipboard_manager::ClipboardExt;
+use crate::logging::{self, Level};
+use crate::script::{NewQueryPayload, Script};
+use crate::settings::SettingsState;
+use crate::{is_likely_word, lookup_targets, prefetch, script, usage, write_log};
+
+const MIN_POLL_INTERVAL_MS: u64 = 100;
+const MAX_POLL_INTERVAL_MS: u64 = 60_000;
+/// Both copies of a double copy must land within this
+const DOUBLE_COPY_WINDOW: Duration = Duration::from_secs(1);
+/// How long the texts of a selection lookup's copy stay ignored
+const SYNTHETIC_MEMORY: Duration = Duration::from_secs(3);
+
+/// What makes a copied word reach the floating window
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TriggerMode {
+    /// Every copy
+    Always,
+    /// The same text copied twice within `DOUBLE_COPY_WINDOW`
+    DoubleCopy,
+    /// The copy is kept until the global shortcut or a tray click sends it
+    Manual,
+}
+
+impl TriggerMode {
+    pub const ALL: [TriggerMode; 3] = [TriggerMode::Always, TriggerMode::DoubleCopy, TriggerMode::Manual];
+
+    /// Tray menu item id
+    pub fn menu_id(self) -> &'static str {
+        match self {
+            TriggerMode::Always => "clipboard_trigger:always",
+            TriggerMode::DoubleCopy => "clipboard_trigger:double-copy",
+            TriggerMode::Manual => "clipboard_trigger:manual",
+        }
+    }
+
+    pub fn from_menu_id(id: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|mode| mode.menu_id() == id)
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            TriggerMode::Always => "On every copy",
+            TriggerMode::DoubleCopy => "On double copy",
+            TriggerMode::Manual => "Manually",
+        }
+    }
+
+    /// Double copy needs the platform's clipboard write count
+    pub fn is_available(self) -> bool {
+        self != TriggerMode::DoubleCopy || sequence_number().is_some()
+    }
+}
+
+/// How the clipboard monitor behaves. The running monitor re-reads this
+/// every poll, so changes apply without restarting it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ClipboardMonitorConfig {
+    pub poll_interval_ms: u64,
+    /// Longer clipboard text is ignored (characters)
+    pub max_text_length: usize,
+    /// Show and focus the floating window on a copy; otherwise only the
+    /// `new-query` event is sent
+    pub auto_show_window: bool,
+    /// Start monitoring when the app launches
+    pub start_on_launch: bool,
+    pub trigger_mode: TriggerMode,
+    /// Skip URLs, email addresses, absolute paths and text that is mostly
+    /// digits or punctuation
+    pub builtin_filters: bool,
+    /// Dictionary language selected in the UI
+    pub language: Option<String>,
+    /// Skip copies not written in `language`'s script
+    pub require_matching_script: bool,
+    /// Regexes; a copy matching any of them is skipped
+    pub ignore_patterns: Vec<String>,
+    /// Copies equal to one of these (after trimming) are skipped
+    pub ignore_texts: Vec<String>,
+}
+
+impl Default for ClipboardMonitorConfig {
+    fn default() -> Self {
+        Self {
+            poll_interval_ms: 800,
+            max_text_length: 200,
+            auto_show_window: true,
+            start_on_launch: true,
+            trigger_mode: TriggerMode::Always,
+            builtin_filters: true,
+            language: None,
+            require_matching_script: false,
+            ignore_patterns: Vec::new(),
+            ignore_texts: Vec::new(),
+        }
+    }
+}
+
+impl ClipboardMonitorConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(MIN_POLL_INTERVAL_MS..=MAX_POLL_INTERVAL_MS).contains(&self.poll_interval_ms) {
+            return Err(format!(
+                "Poll interval must be between {} and {} ms",
+                MIN_POLL_INTERVAL_MS, MAX_POLL_INTERVAL_MS
+            ));
+        }
+        if self.max_text_length == 0 {
+            return Err("Max text length must be at least 1".to_string());
+        }
+        if !self.trigger_mode.is_available() {
+            return Err("Double copy isn't available: this system doesn't report repeated copies".to_string());
+        }
+        self.compile_patterns()?;
+        Ok(())
+    }
+
+    fn compile_patterns(&self) -> Result<Vec<Regex>, String> {
+        self.ignore_patterns
+            .iter()
+            .map(|p| Regex::new(p).map_err(|e| format!("Invalid ignore pattern \"{}\": {}", p, e)))
+            .collect()
+    }
+}
+
+/// Why a copy didn't trigger a lookup
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FilterReason {
+    TooLong,
+    Url,
+    Email,
+    Path,
+    Numeric,
+    Script,
+    Pattern,
+    IgnoreList,
+    NotAWord,
+}
+
+impl FilterReason {
+    fn as_str(self) -> &'static str {
+        match self {
+            FilterReason::TooLong => "too_long",
+            FilterReason::Url => "url",
+            FilterReason::Email => "email",
+            FilterReason::Path => "path",
+            FilterReason::Numeric => "numeric",
+            FilterReason::Script => "script",
+            FilterReason::Pattern => "pattern",
+            FilterReason::IgnoreList => "ignore_list",
+            FilterReason::NotAWord => "not_a_word",
+        }
+    }
+}
+
+/// What the monitor has seen since it was last started
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ClipboardMonitorStats {
+    pub running: bool,
+    /// Clipboard changes noticed
+    pub copies: u64,
+    /// Copies sent to the floating window (or held in manual mode)
+    pub lookups: u64,
+    /// Copies skipped, by reason ("url", "script", "pattern", ...)
+    pub filtered: BTreeMap<String, u64>,
+    pub last_copy_at: Option<String>,
+    pub last_filter_reason: Option<String>,
+}
+
+impl ClipboardMonitorStats {
+    fn copied(&mut self) {
+        self.copies += 1;
+        self.last_copy_at = Some(chrono::Local::now().to_rfc3339());
+    }
+
+    fn filtered(&mut self, reason: FilterReason) {
+        *self.filtered.entry(reason.as_str().to_string()).or_insert(0) += 1;
+        self.last_filter_reason = Some(reason.as_str().to_string());
+    }
+}
+
+/// The filter stage: None when the copy may go on to a lookup.
+/// `default_language` stands in for a language the UI hasn't set.
+fn filter(
+    text: &str,
+    config: &ClipboardMonitorConfig,
+    patterns: &[Regex],
+    default_language: Option<&str>,
+) -> Option<FilterReason> {
+    let trimmed = text.trim();
+    if config.ignore_texts.iter().any(|ignored| ignored.trim() == trimmed) {
+        return Some(FilterReason::IgnoreList);
+    }
+    if patterns.iter().any(|p| p.is_match(trimmed)) {
+        return Some(FilterReason::Pattern);
+    }
+    if config.builtin_filters {
+        if is_url(trimmed) {
+            return Some(FilterReason::Url);
+        }
+        if is_email(trimmed) {
+            return Some(FilterReason::Email);
+        }
+        if is_absolute_path(trimmed) {
+            return Some(FilterReason::Path);
+        }
+        if is_mostly_symbols(trimmed) {
+            return Some(FilterReason::Numeric);
+        }
+    }
+    if config.require_matching_script {
+        let wanted = config.language.as_deref().or(default_language).and_then(script::script_for_language);
+        if let Some(wanted) = wanted {
+            let matches = script::dominant_script(trimmed).is_some_and(|found| {
+                // Japanese text often starts with kanji
+                found == wanted || (wanted == Script::Kana && found == Script::Han)
+            });
+            if !matches {
+                return Some(FilterReason::Script);
+            }
+        }
+    }
+    None
+}
+
+fn is_url(text: &str) -> bool {
+    let lower = text.to_ascii_lowercase();
+    lower.starts_with("www.")
+        || lower.split_once("://").is_some_and(|(scheme, _)| {
+            !scheme.is_empty() && scheme.chars().all(|c| c.is_ascii_alphanumeric() || "+-.".contains(c))
+        })
+}
+
+fn is_email(text: &str) -> bool {
+    !text.contains(char::is_whitespace)
+        && text
+            .split_once('@')
+            .is_some_and(|(user, domain)| !user.is_empty() && domain.contains('.') && !domain.ends_with('.'))
+}
+
+fn is_absolute_path(text: &str) -> bool {
+    let bytes = text.as_bytes();
+    let drive = bytes.len() > 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':' && matches!(bytes[2], b'\\' | b'/');
+    drive || text.starts_with("\\\\") || text.starts_with("~/") || (text.starts_with('/') && text.len() > 1)
+}
+
+/// Fewer letters than digits and punctuation: numbers, codes, passwords
+fn is_mostly_symbols(text: &str) -> bool {
+    let (letters, others) = text
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .fold((0, 0), |(l, o), c| if c.is_alphabetic() { (l + 1, o) } else { (l, o + 1) });
+    letters <= others
+}
+
+/// The selection lookup's own copy, which must not trigger the monitor
+#[derive(Default)]
+struct SyntheticCopy {
+    /// The copy keystroke is in flight: every change is its doing
+    active: bool,
+    /// What it left on the clipboard (the selection, then the restored
+    /// original), ignored until `until`
+    texts: Vec<String>,
+    until: Option<Instant>,
+}
+
+/// The one clipboard polling thread. Starting it twice is a no-op, and
+/// stopping waits for the thread to finish, so a copy never produces two
+/// `new-query` events.
+#[derive(Default)]
+pub struct ClipboardMonitor {
+    running: Arc<AtomicBool>,
+    config: Arc<RwLock<ClipboardMonitorConfig>>,
+    thread: Mutex<Option<JoinHandle<()>>>,
+    /// The last copy in manual mode, waiting to be sent
+    pending: Arc<Mutex<Option<NewQueryPayload>>>,
+    stats: Arc<Mutex<ClipboardMonitorStats>>,
+    synthetic: Arc<Mutex<SyntheticCopy>>,
+    /// Tray items with a checkmark on the current trigger mode
+    trigger_items: Mutex<Vec<(TriggerMode, CheckMenuItem<Wry>)>>,
+}
+
+impl ClipboardMonitor {
+    /// Start polling; false when it was already running
+    pub fn start(&self, app: AppHandle) -> bool {
+        let mut thread = self.thread.lock().unwrap();
+        if thread.as_ref().is_some_and(|handle| !handle.is_finished()) {
+            return false;
+        }
+        self.running.store(true, Ordering::SeqCst);
+        *self.stats.lock().unwrap() = ClipboardMonitorStats::default();
+        let monitor = Shared {
+            running: Arc::clone(&self.running),
+            config: Arc::clone(&self.config),
+            pending: Arc::clone(&self.pending),
+            stats: Arc::clone(&self.stats),
+            synthetic: Arc::clone(&self.synthetic),
+        };
+        *thread = Some(std::thread::spawn(move || poll(app, monitor)));
+        write_log("[Clipboard] Monitor started");
+        true
+    }
+
+    /// Stop polling and wait for the thread; false when it wasn't running
+    pub fn stop(&self) -> bool {
+        let mut thread = self.thread.lock().unwrap();
+        let Some(handle) = thread.take() else {
+            return false;
+        };
+        self.running.store(false, Ordering::SeqCst);
+        // Cut the current poll interval short
+        handle.thread().unpark();
+        let _ = handle.join();
+        true
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running.load(Ordering::SeqCst)
+    }
+
+    pub fn stats(&self) -> ClipboardMonitorStats {
+        ClipboardMonitorStats {
+            running: self.is_running(),
+            ..self.stats.lock().unwrap().clone()
+        }
+    }
+
+    pub fn config(&self) -> ClipboardMonitorConfig {
+        self.config.read().unwrap().clone()
+    }
+
+    /// Takes effect on the next poll; the tray checkmark follows the mode
+    pub fn set_config(&self, config: ClipboardMonitorConfig) {
+        if config.trigger_mode != TriggerMode::Manual {
+            self.pending.lock().unwrap().take();
+        }
+        for (mode, item) in self.trigger_items.lock().unwrap().iter() {
+            let _ = item.set_checked(*mode == config.trigger_mode);
+        }
+        *self.config.write().unwrap() = config;
+    }
+
+    /// Ignore clipboard changes until `end_synthetic_copy`
+    pub fn begin_synthetic_copy(&self) {
+        self.synthetic.lock().unwrap().active = true;
+    }
+
+    /// Stop ignoring changes, except to `texts` for a little while
+    pub fn end_synthetic_copy(&self, texts: Vec<String>) {
+        *self.synthetic.lock().unwrap() = SyntheticCopy {
+            active: false,
+            texts,
+            until: Some(Instant::now() + SYNTHETIC_MEMORY),
+        };
+    }
+
+    pub fn set_trigger_items(&self, items: Vec<(TriggerMode, CheckMenuItem<Wry>)>) {
+        *self.trigger_items.lock().unwrap() = items;
+    }
+
+    /// Show the copy held back in manual mode; false when there is none
+    pub fn send_pending(&self, app: &AppHandle) -> bool {
+        let Some(payload) = self.pending.lock().unwrap().take() else {
+            return false;
+        };
+        let Some(window) = app.get_webview_window("floating") else {
+            return false;
+        };
+        let _ = window.show();
+        let _ = window.set_focus();
+        let _ = window.emit_to("floating", "new-query", payload);
+        true
+    }
+}
+
+/// A count of clipboard writes, which shows a copy of the text already on
+/// the clipboard. None where the platform keeps no such count (Wayland
+/// without XWayland, a failed X connection); double copy needs it.
+#[cfg(windows)]
+pub fn sequence_number() -> Option<u32> {
+    #[link(name = "user32")]
+    extern "system" {
+        fn GetClipboardSequenceNumber() -> u32;
+    }
+    // SAFETY: takes no arguments and only reads a counter
+    Some(unsafe { GetClipboardSequenceNumber() })
+}
+
+/// NSPasteboard's changeCount, read through the Objective-C runtime
+#[cfg(target_os = "macos")]
+pub fn sequence_number() -> Option<u32> {
+    use std::ffi::{c_char, c_void};
+
+    #[link(name = "AppKit", kind = "framework")]
+    extern "C" {}
+    #[link(name = "objc")]
+    extern "C" {
+        fn objc_getClass(name: *const c_char) -> *mut c_void;
+        fn sel_registerName(name: *const c_char) -> *mut c_void;
+        fn objc_msgSend();
+    }
+
+    // SAFETY: objc_msgSend is called through the signatures of the two
+    // methods sent: +[NSPasteboard generalPasteboard] returns an object
+    // and -[NSPasteboard changeCount] an NSInteger, neither takes arguments
+    unsafe {
+        let general: unsafe extern "C" fn(*mut c_void, *mut c_void) -> *mut c_void =
+            std::mem::transmute(objc_msgSend as unsafe extern "C" fn());
+        let change_count: unsafe extern "C" fn(*mut c_void, *mut c_void) -> isize =
+            std::mem::transmute(objc_msgSend as unsafe extern "C" fn());
+        let class = objc_getClass(c"NSPasteboard".as_ptr());
+        if class.is_null() {
+            return None;
+        }
+        let pasteboard = general(class, sel_registerName(c"generalPasteboard".as_ptr()));
+        if pasteboard.is_null() {
+            return None;
+        }
+        Some(change_count(pasteboard, sel_registerName(c"changeCount".as_ptr())) as u32)
+    }
+}
+
+/// X11 keeps no count, but XFixes reports each change of the CLIPBOARD
+/// selection's owner and apps take the selection again on every copy.
+/// The events are counted on a connection opened by the first call.
+#[cfg(target_os = "linux")]
+pub fn sequence_number() -> Option<u32> {
+    use std::sync::OnceLock;
+    use x11rb::connection::Connection;
+    use x11rb::protocol::xfixes::{ConnectionExt as _, SelectionEventMask};
+    use x11rb::protocol::xproto::ConnectionExt as _;
+    use x11rb::protocol::Event;
+    use x11rb::rust_connection::RustConnection;
+
+    struct OwnerChanges {
+        conn: RustConnection,
+        count: u32,
+    }
+
+    fn watch() -> Result<OwnerChanges, Box<dyn std::error::Error>> {
+        let (conn, screen) = x11rb::connect(None)?;
+        conn.xfixes_query_version(5, 0)?.reply()?;
+        let root = conn.setup().roots[screen].root;
+        let clipboard = conn.intern_atom(false, b"CLIPBOARD")?.reply()?.atom;
+        conn.xfixes_select_selection_input(root, clipboard, SelectionEventMask::SET_SELECTION_OWNER)?
+            .check()?;
+        Ok(OwnerChanges { conn, count: 0 })
+    }
+
+    static WATCH: OnceLock<Option<Mutex<OwnerChanges>>> = OnceLock::new();
+    let watch = WATCH.get_or_init(|| match watch() {
+        Ok(watch) => Some(Mutex::new(watch)),
+        Err(e) => {
+            write_log(&format!("[Clipboard] No X11 clipboard owner events, double copy unavailable: {}", e));
+            None
+        }
+    });
+    let mut changes = watch.as_ref()?.lock().unwrap();
+    while let Ok(Some(event)) = changes.conn.poll_for_event() {
+        if let Event::XfixesSelectionNotify(_) = event {
+            changes.count = changes.count.wrapping_add(1);
+        }
+    }
+    Some(changes.count)
+}
+
+#[cfg(not(any(windows, target_os = "macos", target_os = "linux")))]
+pub fn sequence_number() -> Option<u32> {
+    None
+}
+
+/// The parts of `ClipboardMonitor` the polling thread works with
+struct Shared {
+    running: Arc<AtomicBool>,
+    config: Arc<RwLock<ClipboardMonitorConfig>>,
+    pending: Arc<Mutex<Option<NewQueryPayload>>>,
+    stats: Arc<Mutex<ClipboardMonitorStats>>,
+    synthetic: Arc<Mutex<SyntheticCopy>>,
+}
+
+impl Shared {
+    /// Whether the selection lookup made this change; each remembered text
+    /// is matched once
+    fn is_synthetic(&self, text: &str) -> bool {
+        let mut synthetic = self.synthetic.lock().unwrap();
+        if synthetic.active {
+            return true;
+        }
+        if synthetic.until.is_some_and(|until| Instant::now() > until) {
+            *synthetic = SyntheticCopy::default();
+        }
+        match synthetic.texts.iter().position(|t| t == text) {
+            Some(i) => {
+                synthetic.texts.remove(i);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Count and log a skipped copy. The text isn't logged: it may be a
+    /// password.
+    fn skip(&self, reason: FilterReason) {
+        self.stats.lock().unwrap().filtered(reason);
+        logging::log(Level::Debug, &format!("[Clipboard] Skipped copy ({})", reason.as_str()));
+    }
+}
+
+fn poll(app: AppHandle, monitor: Shared) {
+    let mut last_text = String::new();
+    let mut last_sequence = sequence_number();
+    // Trimmed text and time of the last copy, for double-copy mode
+    let mut first_copy: Option<(String, Instant)> = None;
+    // Installed dictionaries rarely change; refresh the routing table
+    // occasionally, and when the default language changes
+    let mut default_language = app.state::<SettingsState>().get().default_language;
+    let mut targets = lookup_targets(&app);
+    let mut targets_loaded_at = Instant::now();
+    // Recompiled when the patterns change; invalid ones never get past
+    // `validate` but a hand-edited settings file could still hold them
+    let mut patterns: (Vec<String>, Vec<Regex>) = (Vec::new(), Vec::new());
+
+    while monitor.running.load(Ordering::SeqCst) {
+        let current = monitor.config.read().unwrap().clone();
+        let interval = Duration::from_millis(current.poll_interval_ms);
+        let Ok(text) = app.clipboard().read_text() else {
+            std::thread::park_timeout(interval);
+            continue;
+        };
+        let sequence = sequence_number();
+        let copied = text != last_text || sequence != last_sequence;
+        last_text = text.clone();
+        last_sequence = sequence;
+        if !copied || text.is_empty() || monitor.is_synthetic(&text) {
+            std::thread::park_timeout(interval);
+            continue;
+        }
+        monitor.stats.lock().unwrap().copied();
+        if patterns.0 != current.ignore_patterns {
+            let compiled = current.ignore_patterns.iter().filter_map(|p| Regex::new(p).ok()).collect();
+            patterns = (current.ignore_patterns.clone(), compiled);
+        }
+        let settings_language = app.state::<SettingsState>().get().default_language;
+        let skipped = if text.chars().count() > current.max_text_length {
+            Some(FilterReason::TooLong)
+        } else {
+            filter(&text, &current, &patterns.1, settings_language.as_deref())
+        };
+        if let Some(reason) = skipped {
+            monitor.skip(reason);
+            std::thread::park_timeout(interval);
+            continue;
+        }
+
+        if current.trigger_mode == TriggerMode::DoubleCopy {
+            let now = Instant::now();
+            let trimmed = text.trim();
+            let second = first_copy
+                .as_ref()
+                .is_some_and(|(first, at)| first == trimmed && now.duration_since(*at) <= DOUBLE_COPY_WINDOW);
+            if second {
+                first_copy = None;
+            } else {
+                first_copy = Some((trimmed.to_string(), now));
+                std::thread::park_timeout(interval);
+                continue;
+            }
+        }
+
+        if targets_loaded_at.elapsed() > Duration::from_secs(60) || settings_language != default_language {
+            default_language = settings_language;
+            targets = lookup_targets(&app);
+            targets_loaded_at = Instant::now();
+        }
+
+        // 按文字系统切分，取出可查询的主要片段
+        let routed = script::route_query(&text, &targets)
+            .filter(|payload| is_likely_word(&payload.query));
+        let Some(mut payload) = routed else {
+            monitor.skip(FilterReason::NotAWord);
+            std::thread::park_timeout(interval);
+            continue;
+        };
+
+        // Only the query is logged; the rest of the copy may be private
+        if payload.query != text.trim() {
+            write_log(&format!(
+                "[Clipboard] Extracted '{}' ({:?}) from a {}-character copy",
+                payload.query,
+                payload.script,
+                text.chars().count()
+            ));
+        } else {
+            write_log(&format!("[Clipboard] Detected word: '{}'", payload.query));
+        }
+
+        usage::record(usage::CLIPBOARD_TRIGGER, payload.language.as_deref());
+        payload.source = Some("clipboard".to_string());
+
+        // Start the lookup now so the popup's search is served from cache
+        if let Some(language) = payload.language.as_deref().filter(|l| *l != "sa") {
+            prefetch::start(&payload.query, language);
+            payload.prefetched = true;
+        }
+
+        monitor.stats.lock().unwrap().lookups += 1;
+        if current.trigger_mode == TriggerMode::Manual {
+            *monitor.pending.lock().unwrap() = Some(payload);
+        } else if let Some(window) = app.get_webview_window("floating") {
+            if current.auto_show_window {
+                let _ = window.show();
+                let _ = window.set_focus();
+            }
+            let _ = window.emit_to("floating", "new-query", payload);
+        }
+        // Woken early by `stop`; a spurious wakeup only polls sooner
+        std::thread::park_timeout(interval);
+    }
+    write_log("[Clipboard] Monitor stopped");
+}
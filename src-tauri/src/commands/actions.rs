@@ -0,0 +1,30 @@
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, State};
+use crate::actions::{ActionMatch, ActionRegistry};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ActionListResult {
+    pub actions: Vec<ActionMatch>,
+    pub total: usize,
+}
+
+#[tauri::command]
+pub async fn list_actions(
+    app: AppHandle,
+    registry: State<'_, ActionRegistry>,
+    query: Option<String>,
+) -> Result<ActionListResult, String> {
+    let actions = registry.list(&app, query.as_deref());
+    let total = actions.len();
+    Ok(ActionListResult { actions, total })
+}
+
+#[tauri::command]
+pub async fn invoke_action(
+    app: AppHandle,
+    registry: State<'_, ActionRegistry>,
+    id: String,
+    args: Option<serde_json::Value>,
+) -> Result<serde_json::Value, String> {
+    registry.invoke(&app, &id, args.unwrap_or(serde_json::Value::Null))
+}
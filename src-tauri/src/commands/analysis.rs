@@ -0,0 +1,327 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use tauri::State;
+use crate::analysis_cache::{self, CachedAnalysis};
+use crate::commands::vocabulary::{TermIndex, VocabularyState};
+
+/// Error prefix telling the UI to run the analysis again before exporting
+pub const ANALYSIS_EXPIRED: &str = "analysis_expired";
+
+// ============================================================================
+// Data Models
+// ============================================================================
+
+/// Which cached analysis to export: the `analysis_id` returned by
+/// `process_text` or `batch_query_dictionary`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum AnalysisRef {
+    Text { id: String },
+    Batch { id: String },
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Markdown,
+    Html,
+    Json,
+}
+
+/// One analyzed word. Vocabulary information is reduced to a status so a
+/// shared export never contains the learner's own notes or translations.
+#[derive(Debug, Clone, Serialize)]
+pub struct AnalysisRow {
+    pub word: String,
+    pub lemma: Option<String>,
+    pub split: Option<Vec<String>>,
+    pub gloss: Option<String>,
+    pub morphology: Vec<(String, String)>,
+    /// "new", "learning", "known", "ignored" or None when not in the vocabulary
+    pub vocabulary_status: Option<&'static str>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AnalysisDocument {
+    pub language: String,
+    pub original: String,
+    pub rows: Vec<AnalysisRow>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportAnalysisResult {
+    pub success: bool,
+    /// The rendered text when no path was given
+    pub content: Option<String>,
+    pub path: Option<String>,
+}
+
+// ============================================================================
+// Building
+// ============================================================================
+
+/// Bucket of a TermStatus, as `vocabulary_stats` counts them
+fn status_label(status: Option<i32>) -> Option<&'static str> {
+    match status? {
+        0 => Some("new"),
+        1..=4 => Some("learning"),
+        99 => Some("ignored"),
+        _ => Some("known"),
+    }
+}
+
+fn value_text(value: &serde_json::Value) -> Option<String> {
+    match value {
+        serde_json::Value::Null => None,
+        serde_json::Value::String(s) if s.is_empty() => None,
+        serde_json::Value::String(s) => Some(s.clone()),
+        serde_json::Value::Array(items) => {
+            let parts: Vec<String> = items.iter().filter_map(value_text).collect();
+            if parts.is_empty() { None } else { Some(parts.join(", ")) }
+        }
+        other => Some(other.to_string()),
+    }
+}
+
+/// Flatten the morphology object of a segment into table rows
+fn morphology_rows(morphology: Option<&serde_json::Value>) -> Vec<(String, String)> {
+    match morphology {
+        Some(serde_json::Value::Object(map)) => map
+            .iter()
+            .filter_map(|(key, value)| value_text(value).map(|text| (key.clone(), text)))
+            .collect(),
+        Some(other) => value_text(other).map(|t| vec![("analysis".to_string(), t)]).unwrap_or_default(),
+        None => Vec::new(),
+    }
+}
+
+fn build_document(analysis: &CachedAnalysis, index: &TermIndex) -> AnalysisDocument {
+    match analysis {
+        CachedAnalysis::Text(result) => {
+            let language = "sa".to_string();
+            let rows = result
+                .segments
+                .iter()
+                .map(|segment| {
+                    let morphology = morphology_rows(segment.morphology.as_ref());
+                    let gloss = morphology
+                        .iter()
+                        .find(|(k, _)| k == "meaning" || k == "gloss")
+                        .map(|(_, v)| v.clone());
+                    let lookup = segment.lemma.as_deref().unwrap_or(&segment.original);
+                    AnalysisRow {
+                        word: segment.original.clone(),
+                        lemma: segment.lemma.clone(),
+                        split: segment.split.clone(),
+                        gloss,
+                        vocabulary_status: status_label(index.status_for(&language, lookup)),
+                        morphology,
+                    }
+                })
+                .collect();
+            AnalysisDocument {
+                language,
+                original: result.text.clone(),
+                rows,
+            }
+        }
+        CachedAnalysis::Batch { language, words, result } => {
+            let rows = words
+                .iter()
+                .map(|word| {
+                    let entry = result.results.get(word).and_then(|entries| entries.first());
+                    let lemma = entry.map(|e| e.text.clone()).filter(|t| t != word);
+                    let gloss = entry.and_then(|e| {
+                        e.senses
+                            .as_ref()
+                            .map(|senses| senses.iter().take(3).map(|s| s.gloss.as_str()).collect::<Vec<_>>().join("; "))
+                            .or_else(|| e.definition.clone())
+                    });
+                    let mut morphology = Vec::new();
+                    if let Some(pos) = entry.and_then(|e| e.grammar.clone()) {
+                        morphology.push(("part of speech".to_string(), pos));
+                    }
+                    if let Some(tags) = entry.and_then(|e| e.matched_form_tags.clone()) {
+                        morphology.push(("form".to_string(), tags));
+                    }
+                    let status = index
+                        .status_for(language, word)
+                        .or_else(|| lemma.as_deref().and_then(|l| index.status_for(language, l)));
+                    AnalysisRow {
+                        word: word.clone(),
+                        lemma,
+                        split: None,
+                        gloss,
+                        morphology,
+                        vocabulary_status: status_label(status),
+                    }
+                })
+                .collect();
+            AnalysisDocument {
+                language: language.clone(),
+                original: words.join(" "),
+                rows,
+            }
+        }
+    }
+}
+
+// ============================================================================
+// Rendering
+// ============================================================================
+
+fn markdown_cell(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', " ")
+}
+
+fn render_markdown(doc: &AnalysisDocument) -> String {
+    let mut out = format!("# Analysis ({})\n\n> {}\n\n", doc.language, doc.original.replace('\n', "\n> "));
+    out.push_str("| Word | Lemma | Split | Gloss | Vocabulary |\n|---|---|---|---|---|\n");
+    for row in &doc.rows {
+        out.push_str(&format!(
+            "| {} | {} | {} | {} | {} |\n",
+            markdown_cell(&row.word),
+            markdown_cell(row.lemma.as_deref().unwrap_or("")),
+            markdown_cell(&row.split.as_ref().map(|s| s.join(" + ")).unwrap_or_default()),
+            markdown_cell(row.gloss.as_deref().unwrap_or("")),
+            row.vocabulary_status.unwrap_or("")
+        ));
+    }
+    for row in doc.rows.iter().filter(|r| !r.morphology.is_empty()) {
+        out.push_str(&format!("\n## {}\n\n| Feature | Value |\n|---|---|\n", markdown_cell(&row.word)));
+        for (feature, value) in &row.morphology {
+            out.push_str(&format!("| {} | {} |\n", markdown_cell(feature), markdown_cell(value)));
+        }
+    }
+    out
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// A single file with inline styles, nothing loaded from elsewhere
+fn render_html(doc: &AnalysisDocument) -> String {
+    let mut out = String::from(
+        "<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\"><title>Analysis</title><style>\
+         body{font-family:system-ui,sans-serif;max-width:50rem;margin:2rem auto;padding:0 1rem;color:#222}\
+         blockquote{font-size:1.3rem;border-left:4px solid #89a;margin:1rem 0;padding:.5rem 1rem;background:#f5f7fa}\
+         table{border-collapse:collapse;width:100%;margin:1rem 0}th,td{border:1px solid #ccd;padding:.3rem .5rem;text-align:left}\
+         th{background:#eef}.status{font-size:.8rem;border-radius:3px;padding:0 .3rem}\
+         .new{background:#dde}.learning{background:#fe9}.known{background:#beb}\
+         </style></head><body>\n",
+    );
+    out.push_str(&format!(
+        "<h1>Analysis ({})</h1>\n<blockquote>{}</blockquote>\n",
+        escape_html(&doc.language),
+        escape_html(&doc.original).replace('\n', "<br>")
+    ));
+    out.push_str("<table><tr><th>Word</th><th>Lemma</th><th>Split</th><th>Gloss</th><th>Vocabulary</th></tr>\n");
+    for row in &doc.rows {
+        let status = row
+            .vocabulary_status
+            .map(|s| format!("<span class=\"status {0}\">{0}</span>", s))
+            .unwrap_or_default();
+        out.push_str(&format!(
+            "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+            escape_html(&row.word),
+            escape_html(row.lemma.as_deref().unwrap_or("")),
+            escape_html(&row.split.as_ref().map(|s| s.join(" + ")).unwrap_or_default()),
+            escape_html(row.gloss.as_deref().unwrap_or("")),
+            status
+        ));
+    }
+    out.push_str("</table>\n");
+    for row in doc.rows.iter().filter(|r| !r.morphology.is_empty()) {
+        out.push_str(&format!("<h2>{}</h2>\n<table>", escape_html(&row.word)));
+        for (feature, value) in &row.morphology {
+            out.push_str(&format!(
+                "<tr><th>{}</th><td>{}</td></tr>",
+                escape_html(feature),
+                escape_html(value)
+            ));
+        }
+        out.push_str("</table>\n");
+    }
+    out.push_str("</body></html>\n");
+    out
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Render a cached analysis for sharing. Without `path` the rendered text
+/// is returned (for the clipboard); with it the file is written.
+#[tauri::command]
+pub async fn export_analysis(
+    vocabulary: State<'_, VocabularyState>,
+    payload: AnalysisRef,
+    format: ExportFormat,
+    path: Option<String>,
+) -> Result<ExportAnalysisResult, String> {
+    let (id, expect_text) = match &payload {
+        AnalysisRef::Text { id } => (id, true),
+        AnalysisRef::Batch { id } => (id, false),
+    };
+    let analysis = analysis_cache::get(id).ok_or_else(|| {
+        format!(
+            "{}: analysis '{}' is no longer cached, run the analysis again before exporting",
+            ANALYSIS_EXPIRED, id
+        )
+    })?;
+    if matches!(*analysis, CachedAnalysis::Text(_)) != expect_text {
+        return Err(format!("Analysis '{}' is not a {} analysis", id, if expect_text { "text" } else { "batch" }));
+    }
+
+    let doc = build_document(&analysis, &vocabulary.term_index.read().unwrap());
+    let content = match format {
+        ExportFormat::Markdown => render_markdown(&doc),
+        ExportFormat::Html => render_html(&doc),
+        ExportFormat::Json => serde_json::to_string_pretty(&doc)
+            .map_err(|e| format!("Failed to serialize analysis: {}", e))?,
+    };
+
+    match path {
+        Some(path) => {
+            let target = PathBuf::from(&path);
+            if let Some(parent) = target.parent().filter(|p| !p.as_os_str().is_empty()) {
+                fs::create_dir_all(parent)
+                    .map_err(|e| format!("Failed to create directory: {}", e))?;
+            }
+            fs::write(&target, content)
+                .map_err(|e| format!("Failed to write analysis: {}", e))?;
+            Ok(ExportAnalysisResult {
+                success: true,
+                content: None,
+                path: Some(path),
+            })
+        }
+        None => Ok(ExportAnalysisResult {
+            success: true,
+            content: Some(content),
+            path: None,
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn statuses_are_labelled_like_the_vocabulary_stats() {
+        let labels: Vec<_> = [None, Some(0), Some(1), Some(2), Some(3), Some(4), Some(5), Some(99)]
+            .into_iter()
+            .map(status_label)
+            .collect();
+        assert_eq!(
+            labels,
+            vec![None, Some("new"), Some("learning"), Some("learning"), Some("learning"), Some("learning"), Some("known"), Some("ignored")]
+        );
+    }
+}
@@ -2,21 +2,153 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
 use std::io::{Read as IoRead, Write as IoWrite};
-use std::path::PathBuf;
-use tauri::{AppHandle, Emitter};
-use crate::db::{self, DictionaryEntry, DictionaryStats, LanguageInfo};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
+use tauri::{AppHandle, Emitter, State};
+use crate::actions::{ActionRegistry, AppAction};
+use crate::{audio_cache, capabilities, entry_actions, import_jobs, keyboard, languages, limits, locale, offline_queue, online, prefetch, python, query_metrics, search_history, translit, usage};
+use crate::offline_queue::{OfflineQueue, OperationError};
+use crate::query_metrics::{QueryMetrics, QueryTimings};
+use crate::analysis_cache::{self, CachedAnalysis};
+use crate::languages::RegisteredLanguage;
+use futures_util::future::BoxFuture;
+use crate::commands::sanskrit;
+use crate::commands::vocabulary::{self, TermIndex, VocabularyState};
+use crate::AppState;
+use crate::settings::SettingsState;
+use crate::db::{self, metadata::DictionaryMetadata, DefinitionMatch, DictionaryEntry, DictionaryStats, EtymologyChain, LanguageInfo, OptimizeReport, ReverseMatch, SearchMode, SearchOptions, SenseFilter, SenseOrderHint};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SearchResult {
     pub success: bool,
     pub entries: Vec<DictionaryEntry>,
+    /// "local" for the default smart cascade, "local:<mode>" when another
+    /// mode was requested, "compound" for the parts of a split compound,
+    /// "lemma" for what the Sanskrit splitter made of the query,
+    /// "fuzzy" for near-miss spellings. Each entry's
+    /// `matched_via` says how that entry in particular was reached.
     pub source: String,
     pub query: String,
     pub language: String,
+    /// Digraph conversion or transliteration ("devanagari→iast") that
+    /// produced the hit when the literal query missed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transformation: Option<String>,
+    /// The converted spelling that matched; `query` keeps the original
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub matched_query: Option<String>,
+    /// Senses hidden by the label filter across all entries
+    #[serde(default)]
+    pub hidden_sense_count: usize,
+    /// Closest headword when the result came from the fuzzy fallback
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub matched_word: Option<String>,
+    /// Headwords a compound (or, for "lemma", a Sanskrit word) missing from
+    /// the dictionary was split into; `entries` then holds theirs, in the
+    /// same order
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compound_of: Option<Vec<String>>,
+    /// Time spent per lookup phase, when `debug` was passed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timings: Option<QueryTimings>,
+    /// Why the lookup failed, when `source` is "error". Starts with
+    /// `db_missing`, `db_locked` or `db_invalid` when the dictionary file
+    /// couldn't be opened.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Words offered when a lookup falls back to fuzzy matching
+const FUZZY_RESULT_LIMIT: usize = 5;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SenseLabel {
+    pub label: String,
+    pub count: i64,
+}
+
+/// First digraph variant of `query` that `lookup` finds anything for
+fn try_digraph_variants<T>(
+    language: &str,
+    query: &str,
+    lookup: impl Fn(&str) -> Result<Vec<T>, String>,
+) -> Option<(keyboard::InputVariant, Vec<T>)> {
+    keyboard::digraph_variants(language, query)
+        .into_iter()
+        .find_map(|variant| match lookup(&variant.text) {
+            Ok(found) if !found.is_empty() => Some((variant, found)),
+            _ => None,
+        })
+}
+
+/// Headwords sampled to find out which script a dictionary uses
+const SCRIPT_SAMPLE_SIZE: usize = 50;
+
+/// Look `query` up again transliterated into the script the language's
+/// dictionary is written in, when that differs from the query's
+fn try_other_script(
+    language: &str,
+    query: &str,
+    options: &SearchOptions,
+) -> Option<(keyboard::InputVariant, Vec<DictionaryEntry>)> {
+    let from = translit::detect(query)?;
+    let samples = db::sample_headwords(language, SCRIPT_SAMPLE_SIZE).ok()?;
+    let to = translit::dominant_scheme(&samples)?;
+    if from == to {
+        return None;
+    }
+    let converted = translit::convert(query, from, to);
+    eprintln!("[DICT] Transliterated {} ({}) to {} ({})", query, from.as_str(), converted, to.as_str());
+    match db::search_dictionary_with(&converted, language, options) {
+        Ok(found) if !found.is_empty() => Some((
+            keyboard::InputVariant {
+                text: converted,
+                transformation: format!("{}→{}", from.as_str(), to.as_str()),
+            },
+            found,
+        )),
+        _ => None,
+    }
+}
+
+/// Optional arguments of `search_dictionary`, passed as one `options` object
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct LookupOptions {
+    pub include_details: Option<bool>,
+    /// A `SearchMode` name; smart when absent
+    pub mode: Option<String>,
+    pub sense_ordering: Option<SenseOrderHint>,
+    pub include_hidden_senses: Option<bool>,
+    pub allow_online: Option<bool>,
+    pub limit: Option<usize>,
+    pub debug: Option<bool>,
 }
 
 #[tauri::command]
-pub async fn search_dictionary(word: String, language: String) -> Result<SearchResult, String> {
+pub async fn search_dictionary(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    settings: State<'_, SettingsState>,
+    vocabulary: State<'_, VocabularyState>,
+    word: String,
+    language: String,
+    options: Option<LookupOptions>,
+) -> Result<SearchResult, String> {
+    let LookupOptions { include_details, mode, sense_ordering, include_hidden_senses, allow_online, limit, debug } =
+        options.unwrap_or_default();
+    let allow_online = allow_online.unwrap_or(false);
+    let mode = match mode.as_deref() {
+        Some(m) => SearchMode::parse(m)?,
+        None => SearchMode::Smart,
+    };
+    let local_source = match mode {
+        SearchMode::Smart => "local".to_string(),
+        other => format!("local:{}", other.as_str()),
+    };
+
     if word.trim().is_empty() {
         return Ok(SearchResult {
             success: true,
@@ -24,42 +156,448 @@ pub async fn search_dictionary(word: String, language: String) -> Result<SearchR
             source: "local".to_string(),
             query: word,
             language: language.clone(),
+            transformation: None,
+            matched_query: None,
+            hidden_sense_count: 0,
+            matched_word: None,
+            compound_of: None,
+            timings: None,
+            error: None,
         });
     }
 
-    // Skip SQLite for Sanskrit - use only Sanskrit processing
-    if language == "sa" {
+    limits::check_text("query", &word, settings.get().limits.max_query_length)?;
+    usage::record(usage::LOOKUP, Some(&language));
+
+    // Without a local Sanskrit dictionary the Sanskrit processor is the
+    // only source; with one, the query is transliterated as needed below
+    if language == "sa" && db::get_connection(&language).is_err() {
         return Ok(SearchResult {
             success: true,
             entries: vec![],
             source: "sanskrit-only".to_string(),
             query: word,
             language,
+            transformation: None,
+            matched_query: None,
+            hidden_sense_count: 0,
+            matched_word: None,
+            compound_of: None,
+            timings: None,
+            error: None,
         });
     }
 
-    match db::search_dictionary(&word, &language) {
+    let started = Instant::now();
+    query_metrics::begin();
+    let options = SearchOptions {
+        include_details: include_details.unwrap_or(false),
+        mode,
+        sense_ordering,
+        // Every prefix hit is a full entry, so keep the page small
+        limit: limit.map(|l| l.clamp(1, 100)),
+    };
+
+    // Clipboard queries are looked up before the popup asks; only default
+    // options are prefetched
+    let prefetched = if !options.include_details && mode == SearchMode::Smart && options.sense_ordering.is_none() {
+        prefetch::take(&word, &language)
+    } else {
+        None
+    };
+    let result = match prefetched {
+        Some(result) => result,
+        None => db::search_dictionary_with(&word, &language, &options),
+    };
+    // Without a local dictionary the online lookup is the only source
+    let result = match result {
+        Err(e) if allow_online => {
+            eprintln!("[DICT] Local lookup failed, trying online: {}", e);
+            Ok(Vec::new())
+        }
+        other => other,
+    };
+
+    match result {
         Ok(entries) => {
+            // Only when the literal spelling found nothing
+            let converted = if entries.is_empty() && settings.get().smart_input {
+                try_digraph_variants(&language, &word, |variant| {
+                    db::search_dictionary_with(variant, &language, &options)
+                })
+            } else {
+                None
+            };
+            let (mut entries, mut transformation, mut matched_query) = match converted {
+                Some((variant, entries)) => (entries, Some(variant.transformation), Some(variant.text)),
+                None => (entries, None, None),
+            };
+            // A query typed in another script than the dictionary uses
+            if entries.is_empty() && translit::is_multi_script(&language) {
+                if let Some((variant, found)) = try_other_script(&language, &word, &options) {
+                    entries = found;
+                    transformation = Some(variant.transformation);
+                    matched_query = Some(variant.text);
+                }
+            }
+
+            // Near-miss spellings, only once every exact route came up empty
+            let mut source = local_source;
+            let mut matched_word = None;
+            let mut compound_of = None;
+            // Inflected or sandhied Sanskrit: look up what the splitter
+            // makes of it. Awaiting may resume on another thread, so the
+            // phases recorded so far are carried over.
+            if entries.is_empty() && mode == SearchMode::Smart && language == "sa" {
+                let phases = query_metrics::take();
+                let lemma_start = Instant::now();
+                let lemmas = sanskrit::lemmas_of(&state, &word, settings.get().persist_sanskrit_results).await;
+                query_metrics::begin();
+                for (phase, elapsed) in phases {
+                    query_metrics::record(phase, elapsed);
+                }
+                query_metrics::record("lemma", lemma_start.elapsed());
+                for lemma in &lemmas {
+                    let found = db::search_dictionary_with(lemma, &language, &options).unwrap_or_default();
+                    entries.extend(found.into_iter().map(|mut entry| {
+                        entry.matched_via = Some("lemma".to_string());
+                        entry
+                    }));
+                }
+                if !entries.is_empty() {
+                    source = "lemma".to_string();
+                    compound_of = Some(lemmas);
+                }
+            }
+            if entries.is_empty() && mode == SearchMode::Smart && db::splits_compounds(&language) {
+                match query_metrics::time("compound", || db::split_compound(&word, &language)) {
+                    Ok(Some(parts)) => {
+                        for part in &parts {
+                            let found = db::search_dictionary_with(part, &language, &options).unwrap_or_default();
+                            entries.extend(found.into_iter().map(|mut entry| {
+                                entry.matched_via = Some("compound".to_string());
+                                entry
+                            }));
+                        }
+                        source = "compound".to_string();
+                        compound_of = Some(parts);
+                    }
+                    Ok(None) => {}
+                    Err(e) => eprintln!("[DICT] Compound split failed: {}", e),
+                }
+            }
+            if entries.is_empty() && mode == SearchMode::Smart {
+                match query_metrics::time("fuzzy", || {
+                    db::search_dictionary_fuzzy(&word, &language, FUZZY_RESULT_LIMIT, &options)
+                }) {
+                    Ok(found) if !found.is_empty() => {
+                        matched_word = found.first().map(|(w, _, _)| w.clone());
+                        entries = found.into_iter().map(|(_, _, entry)| entry).collect();
+                        source = "fuzzy".to_string();
+                    }
+                    Ok(_) => {}
+                    Err(e) => eprintln!("[DICT] Fuzzy fallback failed: {}", e),
+                }
+            }
+            let mut phases = query_metrics::take();
+            if entries.is_empty() && allow_online {
+                let persist = settings.get().persist_online_lookups;
+                let online_start = Instant::now();
+                let found = online::lookup(&word, &language, persist).await;
+                phases.push(("online", online_start.elapsed()));
+                match found {
+                    Ok(found) if !found.is_empty() => {
+                        entries = found;
+                        source = "online".to_string();
+                    }
+                    Ok(_) => {}
+                    Err(e) => eprintln!("[DICT] Online fallback failed: {}", e),
+                }
+            }
+            search_history::record(&word, &language, !entries.is_empty());
+            if !entries.is_empty() {
+                // Clipboard lookups come through here too, from the popup.
+                // Prefix and compound hits are other words than the query.
+                let lemmas = entries
+                    .iter()
+                    .filter(|e| !matches!(e.matched_via.as_deref(), Some("prefix") | Some("compound")))
+                    .map(|e| e.text.clone());
+                let words: Vec<String> = std::iter::once(word.clone()).chain(lemmas).collect();
+                vocabulary::record_lookup(&app, &language, &words);
+            }
+            entry_actions::annotate(
+                &mut entries,
+                &vocabulary.term_index.read().unwrap(),
+                &capabilities::current(),
+            );
+
+            let current = settings.get();
+            let filter = SenseFilter {
+                hidden_labels: current.hidden_sense_labels,
+                preferred_region: current.preferred_region,
+            };
+            let hidden_sense_count =
+                db::filter_senses(&mut entries, &filter, include_hidden_senses.unwrap_or(false));
+
+            let timings = query_metrics::finish("search", &language, &word, started, phases);
             Ok(SearchResult {
                 success: true,
                 entries,
-                source: "local".to_string(),
+                source,
                 query: word,
                 language,
+                transformation,
+                matched_query,
+                hidden_sense_count,
+                matched_word,
+                compound_of,
+                timings: debug.unwrap_or(false).then_some(timings),
+                error: None,
             })
         }
-        Err(_e) => {
+        Err(e) => {
+            let timings = query_metrics::finish("search", &language, &word, started, query_metrics::take());
             Ok(SearchResult {
                 success: false,
                 entries: vec![],
                 source: "error".to_string(),
                 query: word,
                 language,
+                transformation: None,
+                matched_query: None,
+                hidden_sense_count: 0,
+                matched_word: None,
+                compound_of: None,
+                timings: debug.unwrap_or(false).then_some(timings),
+                error: Some(e),
             })
         }
     }
 }
 
+/// Look a word up on Wiktionary, skipping the local dictionaries. Fails
+/// with an `offline` error when there is no connection.
+#[tauri::command]
+pub async fn search_online(
+    settings: State<'_, SettingsState>,
+    word: String,
+    language: String,
+) -> Result<SearchResult, String> {
+    let current = settings.get();
+    limits::check_text("query", &word, current.limits.max_query_length)?;
+    let word = word.trim().to_string();
+    let entries = if word.is_empty() {
+        Vec::new()
+    } else {
+        online::lookup(&word, &language, current.persist_online_lookups)
+            .await
+            .map_err(online::error_message)?
+    };
+    Ok(SearchResult {
+        success: true,
+        entries,
+        source: "online".to_string(),
+        query: word,
+        language,
+        transformation: None,
+        matched_query: None,
+        hidden_sense_count: 0,
+        matched_word: None,
+        compound_of: None,
+        timings: None,
+        error: None,
+    })
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PronunciationAudioResult {
+    pub success: bool,
+    /// Local copy of the recording, readable by the webview
+    pub path: String,
+    pub audio_url: String,
+    /// Whether the file was already in the cache
+    pub cached: bool,
+}
+
+pub const AUDIO_OPERATION: &str = "audio.download";
+
+/// Download the first recording of `word` into the audio cache and return
+/// where it is. Fails with an `offline` error when it isn't cached and
+/// there is no connection, unless `enqueue_if_offline` queues the download.
+#[tauri::command]
+pub async fn get_pronunciation_audio(
+    app: AppHandle,
+    queue: State<'_, OfflineQueue>,
+    word: String,
+    language: String,
+    enqueue_if_offline: Option<bool>,
+) -> Result<PronunciationAudioResult, String> {
+    let urls = db::audio_urls(word.trim(), &language)?;
+    let Some(audio_url) = urls.into_iter().next() else {
+        return Err(format!("No recording of '{}' in the {} dictionary", word.trim(), language));
+    };
+    match audio_cache::fetch(&audio_cache::cache_dir(&app), &audio_url).await {
+        Ok((path, cached)) => Ok(PronunciationAudioResult {
+            success: true,
+            path: path.to_string_lossy().to_string(),
+            audio_url,
+            cached,
+        }),
+        Err(OperationError::Offline(e)) if enqueue_if_offline.unwrap_or(false) => {
+            queue.enqueue(AUDIO_OPERATION, serde_json::json!({ "url": audio_url }), &e);
+            Ok(PronunciationAudioResult {
+                success: false,
+                path: String::new(),
+                audio_url,
+                cached: false,
+            })
+        }
+        Err(e) => Err(online::error_message(e)),
+    }
+}
+
+/// Retry handler for queued recordings. Checks before writing: a recording
+/// that made it into the cache meanwhile isn't downloaded again, and a
+/// half-written earlier attempt is never mistaken for a cached file.
+pub fn retry_audio(app: AppHandle, args: serde_json::Value) -> BoxFuture<'static, Result<(), OperationError>> {
+    Box::pin(async move {
+        let url = args
+            .get("url")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| OperationError::Failed("Queued recording is missing 'url'".to_string()))?;
+        audio_cache::fetch(&audio_cache::cache_dir(&app), url).await.map(|_| ())
+    })
+}
+
+/// Find words whose glosses match `query`, best matches first. The first
+/// search in a dictionary builds its full-text index.
+#[tauri::command]
+pub async fn search_by_definition(
+    settings: State<'_, SettingsState>,
+    query: String,
+    language: String,
+    limit: Option<usize>,
+) -> Result<DefinitionSearchResult, String> {
+    limits::check_text("query", &query, settings.get().limits.max_query_length)?;
+    if query.trim().is_empty() {
+        return Ok(DefinitionSearchResult {
+            success: true,
+            query,
+            language,
+            results: vec![],
+            method: "none".to_string(),
+        });
+    }
+    let limit = limit.unwrap_or(20).clamp(1, 200);
+    let (results, method) = db::search_by_definition(&query, &language, limit)?;
+    Ok(DefinitionSearchResult {
+        success: true,
+        query,
+        language,
+        results,
+        method: method.to_string(),
+    })
+}
+
+/// Find dictionary words by their meaning ("what's the German for butterfly")
+#[tauri::command]
+pub async fn reverse_lookup(
+    settings: State<'_, SettingsState>,
+    text: String,
+    language: String,
+    limit: usize,
+) -> Result<ReverseLookupResult, String> {
+    limits::check_text("query", &text, settings.get().limits.max_query_length)?;
+    if text.trim().is_empty() {
+        return Ok(ReverseLookupResult {
+            success: true,
+            text,
+            language,
+            results: vec![],
+            has_more: false,
+        });
+    }
+    let (results, has_more) = db::reverse_lookup(&text, &language, limit.clamp(1, 200))?;
+    Ok(ReverseLookupResult {
+        success: true,
+        text,
+        language,
+        results,
+        has_more,
+    })
+}
+
+/// Crossword-style lookup: `*` matches any run of characters, `?` one
+#[tauri::command]
+pub async fn search_pattern(
+    settings: State<'_, SettingsState>,
+    pattern: String,
+    language: String,
+    limit: usize,
+) -> Result<PatternSearchResult, String> {
+    limits::check_text("pattern", &pattern, settings.get().limits.max_query_length)?;
+    let (matches, truncated) = db::search_pattern(&pattern, &language, limit.clamp(1, 500))?;
+    Ok(PatternSearchResult {
+        success: true,
+        pattern,
+        language,
+        results: matches
+            .into_iter()
+            .map(|(word, pos)| PatternMatch { word, pos })
+            .collect(),
+        truncated,
+    })
+}
+
+/// Sense labels present in a language's dictionary, most common first,
+/// for building the label filter settings
+#[tauri::command]
+pub async fn get_sense_labels(language: String) -> Result<Vec<SenseLabel>, String> {
+    Ok(db::get_sense_labels(&language)?
+        .into_iter()
+        .map(|(label, count)| SenseLabel { label, count })
+        .collect())
+}
+
+#[tauri::command]
+pub async fn get_dictionary_entry(
+    vocabulary: State<'_, VocabularyState>,
+    entry_id: String,
+    language: String,
+    include_details: Option<bool>,
+    sense_ordering: Option<SenseOrderHint>,
+) -> Result<Option<DictionaryEntry>, String> {
+    let id = entry_id
+        .parse::<i64>()
+        .map_err(|_| format!("Invalid entry id '{}'", entry_id))?;
+    let options = SearchOptions {
+        include_details: include_details.unwrap_or(false),
+        sense_ordering,
+        ..Default::default()
+    };
+    let mut entry = db::get_entry_by_id(id, &language, &options)?;
+    if let Some(entry) = entry.as_mut() {
+        entry_actions::annotate(
+            std::slice::from_mut(entry),
+            &vocabulary.term_index.read().unwrap(),
+            &capabilities::current(),
+        );
+    }
+    Ok(entry)
+}
+
+#[tauri::command]
+pub async fn get_etymology_chain(
+    entry_id: String,
+    language: String,
+    follow_local: bool,
+) -> Result<EtymologyChain, String> {
+    let id = entry_id
+        .parse::<i64>()
+        .map_err(|_| format!("Invalid entry id '{}'", entry_id))?;
+    db::get_etymology_chain(id, &language, follow_local)
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct StatsResult {
     pub success: bool,
@@ -83,6 +621,93 @@ pub async fn get_dictionary_stats(language: String) -> Result<StatsResult, Strin
     }
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OptimizeResult {
+    pub success: bool,
+    pub language: String,
+    pub report: OptimizeReport,
+}
+
+/// Add missing lookup indexes to a language's main dictionary and run
+/// ANALYZE; hand-built databases often have neither
+#[tauri::command]
+pub async fn optimize_dictionary(language: String) -> Result<OptimizeResult, String> {
+    let report = db::optimize_dictionary(&language)?;
+    db::invalidate_connections(Some(&language));
+    Ok(OptimizeResult {
+        success: true,
+        language,
+        report,
+    })
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NamedMetadata {
+    pub name: String,
+    pub metadata: DictionaryMetadata,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MetadataResult {
+    pub success: bool,
+    pub language: String,
+    /// Highest-priority dictionary; defaults when there is none
+    pub metadata: DictionaryMetadata,
+    pub dictionaries: Vec<NamedMetadata>,
+}
+
+/// Source, license and import date of a language's dictionaries
+#[tauri::command]
+pub async fn get_dictionary_metadata(language: String) -> Result<MetadataResult, String> {
+    let dictionaries: Vec<NamedMetadata> = db::get_dictionary_metadata(&language)?
+        .into_iter()
+        .map(|(name, metadata)| NamedMetadata { name, metadata })
+        .collect();
+    Ok(MetadataResult {
+        success: true,
+        metadata: dictionaries
+            .first()
+            .map(|d| d.metadata.clone())
+            .unwrap_or_default(),
+        language,
+        dictionaries,
+    })
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LanguageRegistryResult {
+    pub success: bool,
+    pub languages: Vec<RegisteredLanguage>,
+}
+
+/// Languages dictionaries can be installed for, built-in and user-added
+#[tauri::command]
+pub async fn get_registered_languages() -> Result<LanguageRegistryResult, String> {
+    Ok(LanguageRegistryResult {
+        success: true,
+        languages: languages::registered(),
+    })
+}
+
+/// Make a language known by code and name, so its dictionary directory
+/// may be named either way. Registering an existing code renames it.
+#[tauri::command]
+pub async fn register_language(code: String, name: String) -> Result<LanguageRegistryResult, String> {
+    Ok(LanguageRegistryResult {
+        success: true,
+        languages: languages::register(&code, &name)?,
+    })
+}
+
+/// Forget a language; its dictionary files are left alone
+#[tauri::command]
+pub async fn unregister_language(code: String) -> Result<LanguageRegistryResult, String> {
+    Ok(LanguageRegistryResult {
+        success: true,
+        languages: languages::unregister(&code)?,
+    })
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct LanguagesResult {
     pub success: bool,
@@ -114,83 +739,476 @@ pub async fn get_available_languages() -> Result<LanguagesResult, String> {
     }
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SuggestedLanguage {
+    pub code: String,
+    pub name: Option<String>,
+    pub rank: usize,
+    pub installed: bool,
+    pub downloadable: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SuggestedLanguagesResult {
+    pub primary: Option<String>,
+    pub languages: Vec<SuggestedLanguage>,
+}
+
+/// Languages from the OS locale preferences, for the first-run wizard
+#[tauri::command]
+pub async fn get_suggested_languages() -> Result<SuggestedLanguagesResult, String> {
+    let detected = locale::detected_languages();
+    let installed: Vec<String> = db::get_available_languages()
+        .map(|langs| langs.into_iter().map(|l| l.code).collect())
+        .unwrap_or_default();
+
+    let languages = detected
+        .iter()
+        .enumerate()
+        .map(|(rank, code)| SuggestedLanguage {
+            code: code.clone(),
+            name: languages::name_for_code(code).map(|n| n.to_string()),
+            rank,
+            installed: installed.contains(code),
+            downloadable: languages::has_download_pack(code),
+        })
+        .collect();
+
+    Ok(SuggestedLanguagesResult {
+        primary: detected.first().cloned(),
+        languages,
+    })
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Suggestion {
     pub word: String,
+    /// First part of speech, kept for callers that show only one
     pub pos: Option<String>,
+    /// Every part of speech the word has in the dictionary
+    #[serde(default)]
+    pub pos_list: Vec<String>,
+    /// Status of a saved term with this text, as the frontend's TermStatus:
+    /// 0 new, 1-4 learning, 5 well known, 99 ignored
+    pub term_status: Option<i32>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DefinitionSearchResult {
+    pub success: bool,
+    pub query: String,
+    pub language: String,
+    pub results: Vec<DefinitionMatch>,
+    /// "fts5", or "like" when the SQLite build lacks FTS5
+    pub method: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReverseLookupResult {
+    pub success: bool,
+    pub text: String,
+    pub language: String,
+    pub results: Vec<ReverseMatch>,
+    pub has_more: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PatternMatch {
+    pub word: String,
+    pub pos: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PatternSearchResult {
+    pub success: bool,
+    pub pattern: String,
+    pub language: String,
+    pub results: Vec<PatternMatch>,
+    /// More words matched than `limit`
+    pub truncated: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SuggestResult {
     pub suggestions: Vec<Suggestion>,
     pub source: String,
+    /// Another page is available at `offset + limit`
+    #[serde(default)]
+    pub has_more: bool,
+    /// Digraph conversion used when the literal prefix had no suggestions
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transformation: Option<String>,
+    /// Why the suggestions failed, when `source` is "error"; carries the same
+    /// `db_*` codes as `SearchResult::error`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SpecialCharactersResult {
+    pub language: String,
+    pub characters: Vec<String>,
 }
 
+/// Characters for the on-screen keyboard: built-in table plus any added in settings
 #[tauri::command]
-pub async fn get_dictionary_suggestions(prefix: String, language: String) -> Result<SuggestResult, String> {
-    match db::search_suggestions(&prefix, &language, 10) {
-        Ok(results) => Ok(SuggestResult {
-            suggestions: results.into_iter().map(|(word, pos)| Suggestion { word, pos }).collect(),
-            source: "local".to_string(),
-        }),
-        Err(_e) => Ok(SuggestResult {
+pub async fn get_special_characters(
+    settings: State<'_, SettingsState>,
+    language: String,
+) -> Result<SpecialCharactersResult, String> {
+    let custom = settings.get().special_characters.get(&language).cloned().unwrap_or_default();
+    Ok(SpecialCharactersResult {
+        characters: keyboard::special_characters(&language, &custom),
+        language,
+    })
+}
+
+const SUGGESTION_LIMIT: usize = 10;
+/// Extra candidates fetched so boosted terms past the current page can move up
+const SUGGESTION_LOOKAHEAD: usize = 20;
+
+/// Sort key for a suggestion: new and learning terms first, then plain
+/// dictionary words and ignored terms, then (optionally) well-known terms
+fn suggestion_rank(term_status: Option<i32>, prioritize_learning: bool) -> u8 {
+    match term_status {
+        Some(0..=4) => 0,
+        Some(5) if prioritize_learning => 2,
+        _ => 1,
+    }
+}
+
+/// Tag dictionary candidates with their saved-term status and move the
+/// words being learned to the front
+fn rank_suggestions(
+    results: Vec<(String, Vec<String>)>,
+    index: &TermIndex,
+    language: &str,
+    prioritize_learning: bool,
+) -> Vec<Suggestion> {
+    let mut suggestions: Vec<Suggestion> = results
+        .into_iter()
+        .map(|(word, pos_list)| Suggestion {
+            term_status: index.status_for(language, &word),
+            word,
+            pos: pos_list.first().cloned(),
+            pos_list,
+        })
+        .collect();
+    // Stable sort keeps the dictionary's ranking within a status
+    suggestions.sort_by_key(|s| suggestion_rank(s.term_status, prioritize_learning));
+    suggestions
+}
+
+#[tauri::command]
+pub async fn get_dictionary_suggestions(
+    vocabulary: State<'_, VocabularyState>,
+    settings: State<'_, SettingsState>,
+    prefix: String,
+    language: String,
+    limit: Option<usize>,
+    offset: Option<usize>,
+) -> Result<SuggestResult, String> {
+    let settings = settings.get();
+    limits::check_text("prefix", &prefix, settings.limits.max_prefix_length)?;
+    let limit = limit.unwrap_or(SUGGESTION_LIMIT).clamp(1, 100);
+    let offset = offset.unwrap_or(0);
+    // Every page is cut from the same ranked window starting at 0, so the
+    // vocabulary boost can't show a word on two pages
+    let window = offset + limit + SUGGESTION_LOOKAHEAD;
+
+    let literal = db::search_suggestions(&prefix, &language, window, 0);
+    let (literal, transformation) = match literal {
+        Ok(results) if results.is_empty() && settings.smart_input => {
+            match try_digraph_variants(&language, &prefix, |variant| {
+                db::search_suggestions(variant, &language, window, 0)
+            }) {
+                Some((variant, results)) => (Ok(results), Some(variant.transformation)),
+                None => (Ok(results), None),
+            }
+        }
+        other => (other, None),
+    };
+
+    match literal {
+        Ok(results) => {
+            let index = vocabulary.term_index.read().unwrap();
+            let suggestions = rank_suggestions(results, &index, &language, settings.prioritize_learning);
+            let has_more = suggestions.len() > offset + limit;
+            let suggestions = suggestions.into_iter().skip(offset).take(limit).collect();
+
+            Ok(SuggestResult {
+                suggestions,
+                source: "local".to_string(),
+                has_more,
+                transformation,
+                error: None,
+            })
+        }
+        Err(e) => Ok(SuggestResult {
             suggestions: vec![],
             source: "error".to_string(),
+            has_more: false,
+            transformation: None,
+            error: Some(e),
         }),
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BatchQueryResult {
     pub success: bool,
     pub results: HashMap<String, Vec<DictionaryEntry>>,
     pub found: usize,
     pub total: usize,
+    /// Requested words without any entry, in request order
+    #[serde(default)]
+    pub missing: Vec<String>,
+    /// Id for `export_analysis`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub analysis_id: Option<String>,
+    /// Time spent per lookup phase, when `debug` was passed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timings: Option<QueryTimings>,
+}
+
+/// Requested words without any entry, in request order and without repeats
+fn missing_words(words: &[String], results: &HashMap<String, Vec<DictionaryEntry>>) -> Vec<String> {
+    let mut missing: Vec<String> = Vec::new();
+    for word in words {
+        if !results.contains_key(word) && !missing.contains(word) {
+            missing.push(word.clone());
+        }
+    }
+    missing
 }
 
 #[tauri::command]
 pub async fn batch_query_dictionary(
+    state: State<'_, AppState>,
+    settings: State<'_, SettingsState>,
     words: Vec<String>,
     language: String,
+    debug: Option<bool>,
 ) -> Result<BatchQueryResult, String> {
-    if language == "sa" {
+    limits::check_batch(&words, &settings.get().limits)?;
+
+    if language == "sa" && db::get_connection(&language).is_err() {
+        let results = HashMap::new();
         return Ok(BatchQueryResult {
             success: true,
-            results: HashMap::new(),
+            missing: missing_words(&words, &results),
+            results,
             found: 0,
             total: words.len(),
+            analysis_id: None,
+            timings: None,
         });
     }
 
-    let mut results = HashMap::new();
-    let mut found = 0;
-
-    for word in &words {
-        match db::search_dictionary(word, &language) {
-            Ok(entries) => {
-                if !entries.is_empty() {
-                    found += 1;
-                    results.insert(word.clone(), entries);
-                }
+    let started = Instant::now();
+    query_metrics::begin();
+    let results = db::batch_search(&words, &language, &SearchOptions::default());
+    let timings = query_metrics::finish(
+        "batch",
+        &language,
+        &format!("{} words", words.len()),
+        started,
+        query_metrics::take(),
+    );
+    let mut results = results?;
+    // Inflected or sandhied Sanskrit words resolve through their lemmas
+    if language == "sa" {
+        let persist = settings.get().persist_sanskrit_results;
+        for word in &words {
+            if word.is_empty() || results.contains_key(word) {
+                continue;
+            }
+            let mut entries = Vec::new();
+            for lemma in sanskrit::lemmas_of(&state, word, persist).await {
+                let found = db::search_dictionary_with(&lemma, &language, &SearchOptions::default()).unwrap_or_default();
+                entries.extend(found.into_iter().map(|mut entry| {
+                    entry.matched_via = Some("lemma".to_string());
+                    entry
+                }));
+            }
+            if !entries.is_empty() {
+                results.insert(word.clone(), entries);
             }
-            Err(_) => {}
         }
     }
+    let missing = missing_words(&words, &results);
+    let found = results.len();
 
-    Ok(BatchQueryResult {
+    let mut result = BatchQueryResult {
         success: true,
         results,
         found,
         total: words.len(),
+        missing,
+        analysis_id: None,
+        timings: debug.unwrap_or(false).then_some(timings),
+    };
+    result.analysis_id = Some(analysis_cache::store(CachedAnalysis::Batch {
+        language,
+        words,
+        result: result.clone(),
+    }));
+    Ok(result)
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct QueryMetricsResult {
+    pub success: bool,
+    pub metrics: Vec<QueryMetrics>,
+}
+
+/// Lookup count, average and p95 time per language since the app started
+#[tauri::command]
+pub async fn get_query_metrics() -> Result<QueryMetricsResult, String> {
+    Ok(QueryMetricsResult {
+        success: true,
+        metrics: query_metrics::summary(),
     })
 }
 
 #[derive(Debug, Serialize, Deserialize)]
+pub struct ExportEntriesResult {
+    pub success: bool,
+    pub path: String,
+    pub format: String,
+    pub found: usize,
+    pub total: usize,
+    pub missing: Vec<String>,
+}
+
+/// One looked-up word in a JSON export
+#[derive(Debug, Serialize)]
+struct ExportedWord<'a> {
+    word: &'a str,
+    entries: &'a [DictionaryEntry],
+}
+
+/// Quote a CSV field when it contains a separator, quote or line break
+pub(crate) fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Look words up and write the entries to `output_path` as JSON (full
+/// entries) or CSV (word, pos, definition, etymology, ipa; one row per
+/// entry). An existing file is only replaced with `overwrite`.
+#[tauri::command]
+pub async fn export_entries(
+    settings: State<'_, SettingsState>,
+    words: Vec<String>,
+    language: String,
+    format: String,
+    output_path: String,
+    overwrite: Option<bool>,
+) -> Result<ExportEntriesResult, String> {
+    limits::check_batch(&words, &settings.get().limits)?;
+    let format = format.to_lowercase();
+    if format != "json" && format != "csv" {
+        return Err(format!("Unsupported export format '{}', expected json or csv", format));
+    }
+
+    let results = db::batch_search(&words, &language, &SearchOptions::default())?;
+    let (content, found, missing) = render_export(&words, &results, &format)?;
+    write_export(Path::new(&output_path), &content, overwrite.unwrap_or(false))?;
+
+    Ok(ExportEntriesResult {
+        success: true,
+        path: output_path,
+        format,
+        found,
+        total: words.len(),
+        missing,
+    })
+}
+
+/// Export file contents for the words `results` has, in input order, with
+/// how many words that was and the words it doesn't have
+fn render_export(
+    words: &[String],
+    results: &HashMap<String, Vec<DictionaryEntry>>,
+    format: &str,
+) -> Result<(String, usize, Vec<String>), String> {
+    let mut ordered: Vec<&String> = Vec::new();
+    let mut missing: Vec<String> = Vec::new();
+    for word in words {
+        if results.contains_key(word) {
+            if !ordered.contains(&word) {
+                ordered.push(word);
+            }
+        } else if !missing.contains(word) {
+            missing.push(word.clone());
+        }
+    }
+
+    let content = if format == "json" {
+        let exported: Vec<ExportedWord> = ordered
+            .iter()
+            .map(|word| ExportedWord { word, entries: &results[*word] })
+            .collect();
+        serde_json::to_string_pretty(&exported).map_err(|e| format!("Failed to serialize entries: {}", e))?
+    } else {
+        let mut csv = String::from("word,pos,definition,etymology,ipa\n");
+        for entry in ordered.iter().flat_map(|word| &results[*word]) {
+            let fields = [
+                entry.text.as_str(),
+                entry.grammar.as_deref().unwrap_or(""),
+                entry.definition.as_deref().unwrap_or(""),
+                entry.etymology.as_deref().unwrap_or(""),
+                entry.ipa.as_deref().unwrap_or(""),
+            ];
+            csv.push_str(&fields.map(csv_field).join(","));
+            csv.push('\n');
+        }
+        csv
+    };
+    Ok((content, ordered.len(), missing))
+}
+
+/// Write `content` to `target`, creating its directory; an existing file is
+/// only replaced with `overwrite`
+fn write_export(target: &Path, content: &str, overwrite: bool) -> Result<(), String> {
+    if let Some(parent) = target.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create directory: {}", e))?;
+    }
+    let mut file = if overwrite {
+        fs::File::create(target)
+    } else {
+        fs::OpenOptions::new().write(true).create_new(true).open(target)
+    }
+    .map_err(|e| match e.kind() {
+        std::io::ErrorKind::AlreadyExists => format!("{} already exists; set overwrite to replace it", target.display()),
+        _ => format!("Failed to create {}: {}", target.display(), e),
+    })?;
+    file.write_all(content.as_bytes())
+        .map_err(|e| format!("Failed to write export: {}", e))
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UploadResult {
     pub success: bool,
     pub message: String,
     pub file_path: Option<String>,
     pub file_type: Option<String>,
+    /// Required tables or "table.column"s the file lacks
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub missing: Vec<String>,
+    /// Optional parts the file lacks; the upload still went through
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+    /// Background import started by `upload_dictionary_file`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub job_id: Option<String>,
+    /// Name the dictionary was registered under in the language's manifest
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dictionary_name: Option<String>,
 }
 
 fn get_dict_dir() -> PathBuf {
@@ -245,13 +1263,292 @@ fn get_dict_dir() -> PathBuf {
     PathBuf::from("dict")
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportProgressEvent {
+    pub job_id: String,
+    pub language_code: String,
+    pub bytes_read: u64,
+    pub total_bytes: u64,
+    pub entries_imported: usize,
+    /// "validating", "copying", "importing", "indexing" or "converting"
+    pub stage: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportFinishedEvent {
+    pub job_id: String,
+    pub language_code: String,
+    pub result: UploadResult,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportFailedEvent {
+    pub job_id: String,
+    pub language_code: String,
+    pub error: String,
+    /// Required tables or columns the file lacks, when that was the cause
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub missing: Vec<String>,
+}
+
+const IMPORT_PROGRESS_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// Why a background upload failed
+struct UploadFailure {
+    error: String,
+    missing: Vec<String>,
+}
+
+impl From<String> for UploadFailure {
+    fn from(error: String) -> Self {
+        Self { error, missing: Vec::new() }
+    }
+}
+
+/// An upload running on its own thread
+struct UploadJob {
+    app: AppHandle,
+    job_id: String,
+    cancel: Arc<AtomicBool>,
+    src_path: PathBuf,
+    ext: String,
+    language_code: String,
+    language_name: String,
+    /// Manifest name; an upload under an existing name replaces that dictionary
+    dictionary_name: String,
+    priority: Option<i64>,
+    /// Recorded in the dictionary's metadata table
+    source: String,
+    license: Option<String>,
+    use_script: bool,
+    total_bytes: u64,
+}
+
+impl UploadJob {
+    fn emit_progress(&self, stage: &str, bytes_read: u64, entries_imported: usize) {
+        let _ = self.app.emit("dictionary-import-progress", ImportProgressEvent {
+            job_id: self.job_id.clone(),
+            language_code: self.language_code.clone(),
+            bytes_read,
+            total_bytes: self.total_bytes,
+            entries_imported,
+            stage: stage.to_string(),
+        });
+    }
+
+    /// Progress callback for the importers, at most one "importing" event
+    /// per interval; stage changes always go through
+    fn throttled_progress(&self) -> impl FnMut(&str, u64, usize) + '_ {
+        let mut last = std::time::Instant::now();
+        move |stage: &str, bytes_read: u64, entries: usize| {
+            if stage != "importing" || last.elapsed() >= IMPORT_PROGRESS_INTERVAL {
+                self.emit_progress(stage, bytes_read, entries);
+                last = std::time::Instant::now();
+            }
+        }
+    }
+
+    fn cancelled(&self) -> bool {
+        self.cancel.load(Ordering::Relaxed)
+    }
+
+    /// Copy a SQLite file next to `target` in chunks, then move it into place
+    fn copy_database(&self, target: &std::path::Path) -> Result<(), String> {
+        let building = target.with_extension("db.importing");
+        let result = (|| {
+            let mut input = fs::File::open(&self.src_path).map_err(|e| format!("Failed to open file: {}", e))?;
+            let mut output = fs::File::create(&building).map_err(|e| format!("Failed to copy file: {}", e))?;
+            let mut buffer = vec![0u8; 1 << 20];
+            let (mut copied, mut last) = (0u64, std::time::Instant::now());
+            loop {
+                if self.cancelled() {
+                    return Err(format!("{}: copy stopped", db::import::IMPORT_CANCELLED));
+                }
+                let read = input.read(&mut buffer).map_err(|e| format!("Failed to copy file: {}", e))?;
+                if read == 0 {
+                    break;
+                }
+                output.write_all(&buffer[..read]).map_err(|e| format!("Failed to copy file: {}", e))?;
+                copied += read as u64;
+                if last.elapsed() >= IMPORT_PROGRESS_INTERVAL {
+                    self.emit_progress("copying", copied, 0);
+                    last = std::time::Instant::now();
+                }
+            }
+            Ok(())
+        })();
+        if let Err(e) = result {
+            let _ = fs::remove_file(&building);
+            return Err(e);
+        }
+        if target.exists() {
+            fs::remove_file(target).map_err(|e| format!("Failed to remove existing file: {}", e))?;
+        }
+        fs::rename(&building, target).map_err(|e| format!("Failed to move file into place: {}", e))
+    }
+
+    /// The Python converter, polled so a cancel can kill it
+    fn run_script(&self, target: &std::path::Path) -> Result<(), String> {
+        use std::process::Stdio;
+        let launcher = python::resolve()?;
+        if !launcher.scripts_dir().join("convert_jsonl_to_sqlite.py").exists() {
+            return Err("JSONL conversion script not found".to_string());
+        }
+
+        let mut child = launcher
+            .script("convert_jsonl_to_sqlite.py")
+            .args([
+                "--input", &self.src_path.to_string_lossy(),
+                "--output", &target.to_string_lossy(),
+            ])
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("Failed to run conversion script: {}", e))?;
+        loop {
+            if self.cancelled() {
+                let _ = child.kill();
+                let _ = child.wait();
+                let _ = fs::remove_file(target);
+                return Err(format!("{}: conversion stopped", db::import::IMPORT_CANCELLED));
+            }
+            match child.try_wait() {
+                Ok(Some(status)) if status.success() => return Ok(()),
+                Ok(Some(_)) => {
+                    let mut stderr = String::new();
+                    if let Some(mut pipe) = child.stderr.take() {
+                        let _ = pipe.read_to_string(&mut stderr);
+                    }
+                    return Err(format!("Failed to convert JSONL: {}", stderr));
+                }
+                Ok(None) => {
+                    self.emit_progress("converting", 0, 0);
+                    std::thread::sleep(IMPORT_PROGRESS_INTERVAL);
+                }
+                Err(e) => return Err(format!("Failed to run conversion script: {}", e)),
+            }
+        }
+    }
+
+    fn run(&self) -> Result<UploadResult, UploadFailure> {
+        let is_sqlite = self.ext == "db" || self.ext == "sqlite";
+
+        // Refuse files every lookup would fail on, before anything is replaced
+        let mut warnings = Vec::new();
+        if is_sqlite {
+            self.emit_progress("validating", 0, 0);
+            let report = db::validate_dictionary_file(&self.src_path)?;
+            if let Some(corruption) = report.corruption {
+                return Err(format!("Dictionary file is corrupted: {}", corruption).into());
+            }
+            if !report.missing.is_empty() {
+                return Err(UploadFailure {
+                    error: format!("Dictionary file is missing {}", report.missing.join(", ")),
+                    missing: report.missing,
+                });
+            }
+            warnings = report.warnings;
+        }
+
+        let target_dir = db::find_language_dir(&self.language_code)
+            .unwrap_or_else(|| get_dict_dir().join(&self.language_name));
+        fs::create_dir_all(&target_dir)
+            .map_err(|e| format!("Failed to create language directory: {}", e))?;
+        let target_file_name =
+            db::manifest::target_file(&target_dir, &self.language_code, &self.dictionary_name);
+        let target_path = target_dir.join(&target_file_name);
+
+        let mut imported: Option<db::import::ImportStats> = None;
+        let file_type = if is_sqlite {
+            db::invalidate_connections(Some(&self.language_code));
+            self.copy_database(&target_path)?;
+            "sqlite"
+        } else if self.ext == "ifo" || self.ext == "zip" {
+            let mut report = self.throttled_progress();
+            let stats = db::stardict::import_stardict(
+                &self.src_path,
+                &target_path,
+                &self.language_code,
+                &self.language_name,
+                &self.cancel,
+                &mut report,
+            )?;
+            imported = Some(stats);
+            "stardict-imported"
+        } else if !self.use_script {
+            let mut report = self.throttled_progress();
+            // The importer builds a separate file; cached handles only
+            // need closing right before it replaces the old one
+            let stats = db::import::import_jsonl(
+                &self.src_path,
+                &target_path,
+                &self.language_code,
+                &self.language_name,
+                &self.cancel,
+                &mut report,
+            )?;
+            imported = Some(stats);
+            "jsonl-imported"
+        } else {
+            db::invalidate_connections(Some(&self.language_code));
+            self.run_script(&target_path)?;
+            "jsonl-converted"
+        };
+        let metadata = db::metadata::DictionaryMetadata::for_import(&self.source, self.license.clone());
+        if let Err(e) = db::metadata::write_file(&target_path, &metadata) {
+            eprintln!("[IMPORT] Could not record metadata: {}", e);
+        }
+        db::manifest::register(&target_dir, &target_file_name, &self.dictionary_name, self.priority)?;
+        db::invalidate_connections(Some(&self.language_code));
+
+        let message = match &imported {
+            Some(stats) => format!(
+                "Dictionary '{}' imported for {}: {} entries ({} malformed lines skipped)",
+                self.dictionary_name, self.language_name, stats.entries, stats.malformed
+            ),
+            None => format!(
+                "Dictionary '{}' uploaded successfully for {}",
+                self.dictionary_name, self.language_name
+            ),
+        };
+        Ok(UploadResult {
+            success: true,
+            message,
+            file_path: Some(target_path.to_string_lossy().to_string()),
+            file_type: Some(file_type.to_string()),
+            missing: Vec::new(),
+            warnings,
+            job_id: Some(self.job_id.clone()),
+            dictionary_name: Some(self.dictionary_name.clone()),
+        })
+    }
+}
+
+/// Optional arguments of `upload_dictionary_file`, passed as one `options` object
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct ImportOptions {
+    pub use_script: Option<bool>,
+    pub dictionary_name: Option<String>,
+    pub priority: Option<i64>,
+    pub source: Option<String>,
+    pub license: Option<String>,
+}
+
+/// Install a dictionary file in the background. SQLite files are validated
+/// and copied; Kaikki JSONL is imported natively unless `use_script` asks
+/// for the Python converter; StarDict comes as its .ifo file or a .zip. Returns a job id right away; progress and the
+/// outcome arrive as `dictionary-import-*` events.
 #[tauri::command]
 pub async fn upload_dictionary_file(
-    _app: AppHandle,
+    app: AppHandle,
+    settings: State<'_, SettingsState>,
     language_code: String,
     language_name: String,
     file_path: String,
+    options: Option<ImportOptions>,
 ) -> Result<UploadResult, String> {
+    let ImportOptions { use_script, dictionary_name, priority, source, license } = options.unwrap_or_default();
     if language_code.len() < 2 || language_code.len() > 3 {
         return Err("Valid language code (2-3 characters) is required".to_string());
     }
@@ -261,6 +1558,8 @@ pub async fn upload_dictionary_file(
     if !src_path.exists() {
         return Err("File not found".to_string());
     }
+    let file_size = fs::metadata(&src_path).map(|m| m.len()).unwrap_or(0);
+    limits::check_u64("dictionary file", file_size, settings.get().limits.max_import_file_size)?;
 
     let ext = src_path
         .extension()
@@ -268,83 +1567,85 @@ pub async fn upload_dictionary_file(
         .map(|e| e.to_lowercase())
         .ok_or("Invalid file extension")?;
 
-    if !["db", "sqlite", "jsonl", "json"].contains(&ext.as_str()) {
-        return Err("Only .db, .sqlite, .jsonl, and .json files are allowed".to_string());
+    if !["db", "sqlite", "jsonl", "json", "ifo", "zip"].contains(&ext.as_str()) {
+        return Err("Only .db, .sqlite, .jsonl, .json, StarDict .ifo and .zip files are allowed".to_string());
     }
 
-    let dict_dir = get_dict_dir();
-    if !dict_dir.exists() {
-        fs::create_dir_all(&dict_dir)
-            .map_err(|e| format!("Failed to create dict directory: {}", e))?;
-    }
-
-    let target_dir = dict_dir.join(&language_name);
-    if !target_dir.exists() {
-        fs::create_dir_all(&target_dir)
-            .map_err(|e| format!("Failed to create language directory: {}", e))?;
-    }
-
-    let (target_file_name, file_type) = if ext == "db" || ext == "sqlite" {
-        (format!("{}_dict.db", language_code), "sqlite".to_string())
-    } else {
-        let target_db_name = format!("{}_dict.db", language_code);
-        let target_db_path = target_dir.join(&target_db_name);
-
-        let base_path = std::env::current_exe()
-            .unwrap_or_default()
-            .parent()
-            .unwrap_or(std::path::Path::new("."))
-            .to_path_buf();
-        let script_path = base_path.join("scripts").join("convert_jsonl_to_sqlite.py");
-
-        if script_path.exists() {
-            use std::process::{Command, Stdio};
-            let output = Command::new("python")
-                .args(&[
-                    script_path.to_string_lossy().as_ref(),
-                    "--input", &file_path,
-                    "--output", &target_db_path.to_string_lossy(),
-                ])
-                .stdout(Stdio::piped())
-                .stderr(Stdio::piped())
-                .output();
-
-            match output {
-                Ok(out) => {
-                    if out.status.success() {
-                        (target_db_name, "jsonl-converted".to_string())
-                    } else {
-                        let stderr = String::from_utf8_lossy(&out.stderr);
-                        return Err(format!("Failed to convert JSONL: {}", stderr));
-                    }
-                }
-                Err(e) => return Err(format!("Failed to run conversion script: {}", e)),
-            }
-        } else {
-            return Err("JSONL conversion script not found".to_string());
-        }
+    // Without a name the source file's name identifies the dictionary
+    let dictionary_name = dictionary_name
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .or_else(|| src_path.file_stem().map(|s| s.to_string_lossy().to_string()))
+        .unwrap_or_else(|| language_name.clone());
+
+    let source = source
+        .filter(|s| !s.trim().is_empty())
+        .or_else(|| src_path.file_name().map(|n| n.to_string_lossy().to_string()))
+        .unwrap_or_else(|| file_path.clone());
+
+    languages::ensure_registered(&language_code, &language_name)?;
+
+    let (job_id, cancel) = import_jobs::start("import");
+    let job = UploadJob {
+        app,
+        job_id: job_id.clone(),
+        cancel,
+        src_path,
+        ext,
+        language_code,
+        language_name: language_name.clone(),
+        dictionary_name: dictionary_name.clone(),
+        priority,
+        source,
+        license,
+        use_script: use_script.unwrap_or(false),
+        total_bytes: file_size,
     };
-
-    if ext == "db" || ext == "sqlite" {
-        let target_path = target_dir.join(&target_file_name);
-        
-        if target_path.exists() {
-            fs::remove_file(&target_path)
-                .map_err(|e| format!("Failed to remove existing file: {}", e))?;
+    std::thread::spawn(move || {
+        let outcome = job.run();
+        import_jobs::finish(&job.job_id);
+        match outcome {
+            Ok(result) => {
+                let _ = job.app.emit("dictionary-import-finished", ImportFinishedEvent {
+                    job_id: job.job_id.clone(),
+                    language_code: job.language_code.clone(),
+                    result,
+                });
+            }
+            Err(failure) => {
+                eprintln!("[IMPORT] {} failed: {}", job.job_id, failure.error);
+                let _ = job.app.emit("dictionary-import-failed", ImportFailedEvent {
+                    job_id: job.job_id.clone(),
+                    language_code: job.language_code.clone(),
+                    error: failure.error,
+                    missing: failure.missing,
+                });
+            }
         }
-
-        fs::copy(&src_path, &target_path)
-            .map_err(|e| format!("Failed to copy file: {}", e))?;
-    }
+    });
 
     Ok(UploadResult {
         success: true,
-        message: format!("Dictionary uploaded successfully for {}", language_name),
-        file_path: Some(target_dir.join(&target_file_name).to_string_lossy().to_string()),
-        file_type: Some(file_type),
+        message: format!("Import started for {}", language_name),
+        file_path: None,
+        file_type: None,
+        missing: Vec::new(),
+        warnings: Vec::new(),
+        job_id: Some(job_id),
+        dictionary_name: Some(dictionary_name),
     })
 }
 
+/// Stop a running dictionary import; its partial database is removed
+#[tauri::command]
+pub async fn cancel_dictionary_import(job_id: String) -> Result<(), String> {
+    if import_jobs::cancel(&job_id) {
+        Ok(())
+    } else {
+        Err(format!("No running import with id {}", job_id))
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct RescanResult {
     pub success: bool,
@@ -355,12 +1656,14 @@ pub struct RescanResult {
 
 #[tauri::command]
 pub async fn rescan_dictionary() -> Result<RescanResult, String> {
+    let old_count = db::get_available_languages().map(|l| l.len()).unwrap_or(0);
+    db::invalidate_connections(None);
     match db::get_available_languages() {
         Ok(languages) => {
             let language_codes: Vec<String> = languages.iter().map(|l| l.code.clone()).collect();
             Ok(RescanResult {
                 success: true,
-                old_count: 0,
+                old_count,
                 new_count: languages.len(),
                 languages: language_codes,
             })
@@ -377,11 +1680,28 @@ pub struct RemoveResult {
 }
 
 #[tauri::command]
-pub async fn remove_dictionary(language_code: String) -> Result<RemoveResult, String> {
+pub async fn remove_dictionary(
+    language_code: String,
+    dictionary_name: Option<String>,
+) -> Result<RemoveResult, String> {
+    // One dictionary of the language; the rest stay installed
+    if let Some(name) = dictionary_name {
+        let language_dir = db::find_language_dir(&language_code)
+            .ok_or_else(|| format!("Dictionary for '{}' not found", language_code))?;
+        db::invalidate_connections(Some(&language_code));
+        db::manifest::unregister(&language_dir, &name)?;
+        return Ok(RemoveResult {
+            success: true,
+            language_code,
+            message: format!("Dictionary '{}' removed successfully", name),
+        });
+    }
+
     let dict_dir = get_dict_dir();
     let language_dir = dict_dir.join(&language_code);
     
     if language_dir.exists() {
+        db::invalidate_connections(Some(&language_code));
         fs::remove_dir_all(&language_dir)
             .map_err(|e| format!("Failed to remove dictionary directory: {}", e))?;
         
@@ -419,6 +1739,7 @@ pub async fn delete_dictionary_file(language_code: String) -> Result<DeleteResul
                         let file_path = file.path();
                         if let Some(file_name) = file_path.file_name().and_then(|n| n.to_str()) {
                             if file_name == pattern {
+                                db::invalidate_connections(Some(&language_code));
                                 fs::remove_file(&file_path)
                                     .map_err(|e| format!("Failed to delete file: {}", e))?;
                                 deleted_file = Some(file_path.to_string_lossy().to_string());
@@ -452,29 +1773,141 @@ pub struct DownloadProgress {
     pub progress: f64,
     pub message: String,
     pub language_code: String,
+    /// Pass to `cancel_download` to stop this download
+    pub job_id: String,
+    pub bytes_downloaded: u64,
+    /// 0 when the server didn't say
+    pub total_bytes: u64,
+}
+
+pub const DOWNLOAD_OPERATION: &str = "dictionary.download";
+
+/// Error prefix for a download stopped through `cancel_download`
+pub const DOWNLOAD_CANCELLED: &str = "download_cancelled";
+
+/// Manifest name of dictionaries downloaded from kaikki.org
+const KAIKKI_DICTIONARY_NAME: &str = "Wiktionary (kaikki.org)";
+
+/// The Kaikki JSONL extract for a language, and its English name
+fn kaikki_url(language_code: &str) -> Option<(String, &'static str)> {
+    let name = languages::name_for_code(language_code)?;
+    let url = format!(
+        "https://kaikki.org/dictionary/{}/kaikki.org-dictionary-{}.jsonl",
+        name.replace(' ', "%20"),
+        name.replace(' ', "")
+    );
+    Some((url, name))
 }
 
+/// Download a dictionary and install it. Without a `url` the language's
+/// Kaikki extract is used. Progress events carry a job id for
+/// `cancel_download`; an interrupted download resumes on the next attempt.
 #[tauri::command]
 pub async fn download_dictionary(
     app: AppHandle,
-    url: String,
+    queue: State<'_, OfflineQueue>,
     language_code: String,
-    language_name: String,
+    url: Option<String>,
+    language_name: Option<String>,
+    enqueue_if_offline: Option<bool>,
 ) -> Result<UploadResult, String> {
-    use flate2::read::GzDecoder;
+    let kaikki = kaikki_url(&language_code);
+    let url = match url.or_else(|| kaikki.as_ref().map(|(url, _)| url.clone())) {
+        Some(url) => url,
+        None => return Err(format!("No known download for language '{}'", language_code)),
+    };
+    let language_name = language_name
+        .or_else(|| kaikki.map(|(_, name)| name.to_lowercase()))
+        .unwrap_or_else(|| language_code.clone());
+    languages::ensure_registered(&language_code, &language_name)?;
+
+    match run_download(&app, &url, &language_code, &language_name).await {
+        Ok(result) => Ok(result),
+        Err(OperationError::Offline(e)) if enqueue_if_offline.unwrap_or(false) => {
+            let args = serde_json::json!({
+                "url": url,
+                "languageCode": language_code,
+                "languageName": language_name,
+            });
+            let id = queue.enqueue(DOWNLOAD_OPERATION, args, &e);
+            Ok(UploadResult {
+                success: false,
+                message: format!("Offline; download queued as {}", id),
+                file_path: None,
+                file_type: Some("queued".to_string()),
+                missing: Vec::new(),
+                warnings: Vec::new(),
+                job_id: None,
+                dictionary_name: None,
+            })
+        }
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// Stop a running download; the partial file is discarded
+#[tauri::command]
+pub async fn cancel_download(job_id: String) -> Result<bool, String> {
+    Ok(import_jobs::cancel(&job_id))
+}
+
+/// Retry handler for queued downloads. Idempotent: a retry resumes or
+/// restarts the download and replaces the same target database.
+pub fn retry_download(app: AppHandle, args: serde_json::Value) -> BoxFuture<'static, Result<(), OperationError>> {
+    Box::pin(async move {
+        let field = |name: &str| {
+            args.get(name)
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string())
+                .ok_or_else(|| OperationError::Failed(format!("Queued download is missing '{}'", name)))
+        };
+        let (url, code, name) = (field("url")?, field("languageCode")?, field("languageName")?);
+        run_download(&app, &url, &code, &name).await.map(|_| ())
+    })
+}
+
+async fn run_download(
+    app: &AppHandle,
+    url: &str,
+    language_code: &str,
+    language_name: &str,
+) -> Result<UploadResult, OperationError> {
+    let (job_id, cancel) = import_jobs::start("download");
+    let result = download_and_install(app, &job_id, cancel, url, language_code, language_name).await;
+    import_jobs::finish(&job_id);
+    result
+}
+
+async fn download_and_install(
+    app: &AppHandle,
+    job_id: &str,
+    cancel: Arc<AtomicBool>,
+    url: &str,
+    language_code: &str,
+    language_name: &str,
+) -> Result<UploadResult, OperationError> {
     use futures_util::StreamExt;
 
-    let emit_progress = |stage: &str, progress: f64, message: &str| {
+    let emit_progress = |stage: &str, progress: f64, message: &str, bytes_downloaded: u64, total_bytes: u64| {
         let _ = app.emit("dictionary-download-progress", DownloadProgress {
             stage: stage.to_string(),
             progress,
             message: message.to_string(),
-            language_code: language_code.clone(),
+            language_code: language_code.to_string(),
+            job_id: job_id.to_string(),
+            bytes_downloaded,
+            total_bytes,
         });
     };
 
-    // Step 1: Download
-    emit_progress("downloading", 0.0, "Starting download...");
+    // Step 1: Download, resuming a partial file left by an earlier attempt
+    emit_progress("downloading", 0.0, "Starting download...", 0, 0);
+
+    let temp_dir = std::env::temp_dir().join("luminous_lute_dict");
+    fs::create_dir_all(&temp_dir)
+        .map_err(|e| format!("Failed to create temp dir: {}", e))?;
+    let part_path = temp_dir.join(format!("{}_download.part", language_code));
+    let resume_from = fs::metadata(&part_path).map(|m| m.len()).unwrap_or(0);
 
     let client = reqwest::Client::builder()
         .user_agent("LuminousLute/1.5.0")
@@ -482,139 +1915,448 @@ pub async fn download_dictionary(
         .build()
         .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
 
-    let response = client.get(&url)
+    let mut request = client.get(url);
+    if resume_from > 0 {
+        eprintln!("[DOWNLOAD] Resuming {} from byte {}", url, resume_from);
+        request = request.header(reqwest::header::RANGE, format!("bytes={}-", resume_from));
+    }
+    let response = request
         .send()
         .await
-        .map_err(|e| format!("Download failed: {}", e))?;
+        .map_err(|e| offline_queue::classify("Download failed", e))?;
 
-    if !response.status().is_success() {
-        return Err(format!("Download failed: HTTP {}", response.status()));
+    let status = response.status();
+    let complete = status == reqwest::StatusCode::RANGE_NOT_SATISFIABLE && resume_from > 0;
+    if !status.is_success() && !complete {
+        return Err(format!("Download failed: HTTP {}", status).into());
+    }
+    let is_html = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.contains("text/html"));
+    if is_html {
+        return Err("Download failed: the server returned a web page instead of a dictionary".into());
     }
 
-    let total_size = response.content_length().unwrap_or(0);
-    let mut downloaded: u64 = 0;
-    let mut body_bytes: Vec<u8> = Vec::new();
+    // A 200 to a range request means the server starts over
+    let resuming = status == reqwest::StatusCode::PARTIAL_CONTENT;
+    let mut downloaded = if resuming || complete { resume_from } else { 0 };
+    let total_size = if complete {
+        resume_from
+    } else {
+        response.content_length().map(|len| len + downloaded).unwrap_or(0)
+    };
 
-    let mut stream = response.bytes_stream();
-    while let Some(chunk) = stream.next().await {
-        let chunk = chunk.map_err(|e| format!("Download error: {}", e))?;
-        body_bytes.extend_from_slice(&chunk);
-        downloaded += chunk.len() as u64;
-        if total_size > 0 {
-            let pct = (downloaded as f64 / total_size as f64).min(1.0);
-            let mb_done = downloaded as f64 / 1_048_576.0;
-            let mb_total = total_size as f64 / 1_048_576.0;
-            emit_progress("downloading", pct, &format!("{:.1} / {:.1} MB", mb_done, mb_total));
+    if !complete {
+        let mut outfile = fs::OpenOptions::new()
+            .create(true)
+            .write(true)
+            .append(resuming)
+            .truncate(!resuming)
+            .open(&part_path)
+            .map_err(|e| format!("Failed to create temp file: {}", e))?;
+        let mut last_event = std::time::Instant::now();
+        let mut stream = response.bytes_stream();
+        while let Some(chunk) = stream.next().await {
+            if cancel.load(Ordering::Relaxed) {
+                drop(outfile);
+                let _ = fs::remove_file(&part_path);
+                return Err(format!("{}: stopped after {} bytes", DOWNLOAD_CANCELLED, downloaded).into());
+            }
+            // The partial file stays for the next attempt to resume
+            let chunk = chunk.map_err(|e| offline_queue::classify("Download error", e))?;
+            outfile.write_all(&chunk)
+                .map_err(|e| format!("Write error: {}", e))?;
+            downloaded += chunk.len() as u64;
+            if last_event.elapsed() >= IMPORT_PROGRESS_INTERVAL {
+                last_event = std::time::Instant::now();
+                let pct = if total_size > 0 { (downloaded as f64 / total_size as f64).min(1.0) } else { 0.0 };
+                let mb_done = downloaded as f64 / 1_048_576.0;
+                let mb_total = total_size as f64 / 1_048_576.0;
+                emit_progress("downloading", pct, &format!("{:.1} / {:.1} MB", mb_done, mb_total), downloaded, total_size);
+            }
         }
     }
+    emit_progress("downloading", 1.0, "Download complete", downloaded, total_size);
 
-    // Step 2: Decompress (if gzip) or use raw JSONL
-    let temp_dir = std::env::temp_dir().join("luminous_lute_dict");
-    fs::create_dir_all(&temp_dir)
-        .map_err(|e| format!("Failed to create temp dir: {}", e))?;
+    // Step 2: Import (decompressing first if gzip) on a blocking thread
+    let target_dir = db::find_language_dir(language_code)
+        .unwrap_or_else(|| get_dict_dir().join(language_name));
+    fs::create_dir_all(&target_dir)
+        .map_err(|e| format!("Failed to create dict directory: {}", e))?;
+    let target_file_name = db::manifest::target_file(&target_dir, language_code, KAIKKI_DICTIONARY_NAME);
+    let target_db = target_dir.join(&target_file_name);
+
+    emit_progress("converting", 0.0, "Converting to SQLite database...", downloaded, total_size);
+    let stats = {
+        let app = app.clone();
+        let (job_id, language_code, language_name) =
+            (job_id.to_string(), language_code.to_string(), language_name.to_string());
+        let (part_path, temp_dir, target_db) = (part_path.clone(), temp_dir.clone(), target_db.clone());
+        tokio::task::spawn_blocking(move || -> Result<db::import::ImportStats, String> {
+            let jsonl_path = unpack_download(&part_path, &temp_dir, &language_code)?;
+            let mut last_event = std::time::Instant::now();
+            let mut report = |stage: &str, bytes_read: u64, entries: usize| {
+                if stage == "importing" && last_event.elapsed() < IMPORT_PROGRESS_INTERVAL {
+                    return;
+                }
+                last_event = std::time::Instant::now();
+                let _ = app.emit("dictionary-download-progress", DownloadProgress {
+                    stage: "converting".to_string(),
+                    progress: 0.0,
+                    message: format!("{} entries imported", entries),
+                    language_code: language_code.clone(),
+                    job_id: job_id.clone(),
+                    bytes_downloaded: bytes_read,
+                    total_bytes: 0,
+                });
+            };
+            let result = db::import::import_jsonl(
+                &jsonl_path,
+                &target_db,
+                &language_code,
+                &language_name,
+                &cancel,
+                &mut report,
+            );
+            if jsonl_path != part_path {
+                let _ = fs::remove_file(&jsonl_path);
+            }
+            // Keep the download only if the import itself can be retried
+            if result.is_ok() || result.as_ref().is_err_and(|e| e.starts_with(db::import::IMPORT_CANCELLED)) {
+                let _ = fs::remove_file(&part_path);
+            }
+            result
+        })
+        .await
+        .map_err(|e| format!("Import task failed: {}", e))??
+    };
+
+    let license = url.contains("kaikki.org").then(|| db::metadata::WIKTIONARY_LICENSE.to_string());
+    if let Err(e) = db::metadata::write_file(&target_db, &db::metadata::DictionaryMetadata::for_import(url, license)) {
+        eprintln!("[DOWNLOAD] Could not record metadata: {}", e);
+    }
+    db::manifest::register(&target_dir, &target_file_name, KAIKKI_DICTIONARY_NAME, None)?;
+    db::invalidate_connections(Some(language_code));
+
+    emit_progress("done", 1.0, "Dictionary installed successfully!", downloaded, total_size);
+
+    Ok(UploadResult {
+        success: true,
+        message: format!(
+            "Dictionary for {} downloaded and installed: {} entries",
+            language_name, stats.entries
+        ),
+        file_path: Some(target_db.to_string_lossy().to_string()),
+        file_type: Some("downloaded-jsonl-imported".to_string()),
+        missing: Vec::new(),
+        warnings: Vec::new(),
+        job_id: Some(job_id.to_string()),
+        dictionary_name: Some(KAIKKI_DICTIONARY_NAME.to_string()),
+    })
+}
 
-    let jsonl_path = temp_dir.join(format!("{}_extract.jsonl", language_code));
+/// The downloaded JSONL, decompressed next to it if it came gzipped.
+/// Refuses HTML error pages and anything else that doesn't start like JSON.
+fn unpack_download(part_path: &std::path::Path, temp_dir: &std::path::Path, language_code: &str) -> Result<PathBuf, String> {
+    use flate2::read::GzDecoder;
 
-    let is_gzip = body_bytes.len() >= 2 && body_bytes[0] == 0x1F && body_bytes[1] == 0x8B;
+    let mut head = [0u8; 512];
+    let read = fs::File::open(part_path)
+        .and_then(|mut f| f.read(&mut head))
+        .map_err(|e| format!("Failed to read download: {}", e))?;
+    let head = &head[..read];
 
-    if is_gzip {
-        emit_progress("decompressing", 0.0, "Decompressing .gz file...");
-        let mut decoder = GzDecoder::new(&body_bytes[..]);
+    let (jsonl_path, first) = if head.starts_with(&[0x1F, 0x8B]) {
+        let jsonl_path = temp_dir.join(format!("{}_extract.jsonl", language_code));
+        let input = fs::File::open(part_path).map_err(|e| format!("Failed to read download: {}", e))?;
+        let mut decoder = GzDecoder::new(std::io::BufReader::new(input));
         let mut outfile = fs::File::create(&jsonl_path)
             .map_err(|e| format!("Failed to create temp file: {}", e))?;
-        let mut buf = vec![0u8; 1_048_576];
-        loop {
-            let n = decoder.read(&mut buf)
-                .map_err(|e| format!("Decompress error: {}", e))?;
-            if n == 0 { break; }
-            outfile.write_all(&buf[..n])
-                .map_err(|e| format!("Write error: {}", e))?;
-        }
+        std::io::copy(&mut decoder, &mut outfile).map_err(|e| format!("Decompress error: {}", e))?;
+        let mut first = [0u8; 64];
+        let n = fs::File::open(&jsonl_path)
+            .and_then(|mut f| f.read(&mut first))
+            .map_err(|e| format!("Failed to read download: {}", e))?;
+        (jsonl_path, first[..n].to_vec())
     } else {
-        emit_progress("decompressing", 1.0, "File already decompressed, saving...");
-        fs::write(&jsonl_path, &body_bytes)
-            .map_err(|e| format!("Failed to write JSONL: {}", e))?;
+        (part_path.to_path_buf(), head.to_vec())
+    };
+
+    match first.iter().find(|b| !b.is_ascii_whitespace()) {
+        Some(b'{') => Ok(jsonl_path),
+        Some(b'<') => {
+            let _ = fs::remove_file(part_path);
+            Err("Download is an HTML page, not a dictionary; the URL may be wrong".to_string())
+        }
+        _ => {
+            let _ = fs::remove_file(part_path);
+            Err("Download is not a JSONL dictionary".to_string())
+        }
     }
-    drop(body_bytes);
+}
 
-    emit_progress("converting", 0.0, "Converting to SQLite database...");
+/// Command palette entries for dictionary management
+pub fn register_actions(registry: &ActionRegistry) {
+    registry.register(
+        AppAction::new(
+            "dictionary.import",
+            "Import dictionary",
+            "Dictionary",
+            Arc::new(|app, _args| {
+                app.emit_to("main", "open-settings-section", "dictionaries")
+                    .map_err(|e| e.to_string())?;
+                Ok(serde_json::Value::Null)
+            }),
+        )
+        .keywords(&["upload", "add dictionary", "jsonl", "sqlite"]),
+    );
+
+    registry.register(
+        AppAction::new(
+            "dictionary.rescan",
+            "Rescan dictionaries",
+            "Dictionary",
+            Arc::new(|_app, _args| {
+                let languages = db::get_available_languages()?;
+                let codes: Vec<String> = languages.into_iter().map(|l| l.code).collect();
+                Ok(serde_json::json!({ "languages": codes }))
+            }),
+        )
+        .keywords(&["refresh", "reload", "languages"]),
+    );
+
+    registry.register(
+        AppAction::new(
+            "language.switch",
+            "Switch dictionary language",
+            "Dictionary",
+            Arc::new(|app, args| {
+                let language = args
+                    .get("language")
+                    .and_then(|v| v.as_str())
+                    .ok_or("Missing 'language' argument")?
+                    .to_string();
+                app.emit("switch-language", &language).map_err(|e| e.to_string())?;
+                Ok(serde_json::json!({ "language": language }))
+            }),
+        )
+        .keywords(&["change language", "select language"]),
+    );
+}
 
-    // Step 3: Convert JSONL → SQLite
-    let dict_dir = get_dict_dir();
-    let target_dir = dict_dir.join(&language_name);
-    fs::create_dir_all(&target_dir)
-        .map_err(|e| format!("Failed to create dict directory: {}", e))?;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::vocabulary::{Term, TermUpdateEvent, TermsData};
+
+    fn term(id: &str, text: &str, status: i32) -> Term {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "text": text,
+            "languageId": "de",
+            "translation": "",
+            "notes": "",
+            "status": status,
+        }))
+        .unwrap()
+    }
 
-    let target_db = target_dir.join(format!("{}_dict.db", language_code));
-
-    let base_path = std::env::current_exe()
-        .unwrap_or_default()
-        .parent()
-        .unwrap_or(std::path::Path::new("."))
-        .to_path_buf();
-
-    // Try multiple script locations (exe dir, bundled, CWD, CWD parent for dev mode)
-    let cwd = std::env::current_dir().unwrap_or_default();
-    let script_candidates = vec![
-        base_path.join("scripts").join("convert_jsonl_to_sqlite.py"),
-        base_path.join("_up_").join("scripts").join("convert_jsonl_to_sqlite.py"),
-        cwd.join("scripts").join("convert_jsonl_to_sqlite.py"),
-        cwd.parent().unwrap_or(&cwd).join("scripts").join("convert_jsonl_to_sqlite.py"),
-    ];
-
-    let script_path = script_candidates.iter()
-        .find(|p| p.exists())
-        .ok_or("Conversion script not found. Please ensure convert_jsonl_to_sqlite.py is in scripts/")?;
-
-    let script_args = vec![
-        script_path.to_string_lossy().to_string(),
-        "--input".to_string(),
-        jsonl_path.to_string_lossy().to_string(),
-        "--output".to_string(),
-        target_db.to_string_lossy().to_string(),
-    ];
-
-    // Try uv first (works on this system), then python3, then python
-    let python_cmds = ["uv", "python3", "python"];
-    let mut output = None;
-    for cmd in &python_cmds {
-        let args: Vec<String> = if *cmd == "uv" {
-            let mut a = vec!["run".to_string(), "python".to_string()];
-            a.extend(script_args.clone());
-            a
-        } else {
-            script_args.clone()
-        };
-        if let Ok(o) = std::process::Command::new(cmd)
-            .args(&args)
-            .stdout(std::process::Stdio::piped())
-            .stderr(std::process::Stdio::piped())
-            .output()
-        {
-            if o.status.success() {
-                output = Some(o);
-                break;
+    fn candidates(words: &[&str]) -> Vec<(String, Vec<String>)> {
+        words.iter().map(|w| (w.to_string(), vec!["noun".to_string()])).collect()
+    }
+
+    fn words(suggestions: &[Suggestion]) -> Vec<&str> {
+        suggestions.iter().map(|s| s.word.as_str()).collect()
+    }
+
+    const HAUS: &[&str] = &["Haus", "Hausarzt", "Hausaufgabe", "Hausbau", "Haustier"];
+
+    #[test]
+    fn learning_terms_move_ahead_of_dictionary_words() {
+        let index = TermIndex::build(&TermsData {
+            terms: vec![term("a", "haustier", 1), term("b", "Hausbau", 0)],
+            version: String::new(),
+            updatedAt: 0,
+        });
+        let ranked = rank_suggestions(candidates(HAUS), &index, "de", false);
+        assert_eq!(words(&ranked), vec!["Hausbau", "Haustier", "Haus", "Hausarzt", "Hausaufgabe"]);
+        assert_eq!(ranked[1].term_status, Some(1));
+        assert_eq!(ranked[2].term_status, None);
+
+        // Another language's terms don't count
+        assert_eq!(words(&rank_suggestions(candidates(HAUS), &index, "nl", false)), HAUS.to_vec());
+    }
+
+    #[test]
+    fn known_terms_drop_behind_only_when_prioritizing_learning() {
+        let index = TermIndex::build(&TermsData {
+            terms: vec![term("a", "Haus", 5), term("b", "Haustier", 1)],
+            version: String::new(),
+            updatedAt: 0,
+        });
+        let ranked = rank_suggestions(candidates(HAUS), &index, "de", false);
+        assert_eq!(words(&ranked), vec!["Haustier", "Haus", "Hausarzt", "Hausaufgabe", "Hausbau"]);
+
+        let ranked = rank_suggestions(candidates(HAUS), &index, "de", true);
+        assert_eq!(words(&ranked), vec!["Haustier", "Hausarzt", "Hausaufgabe", "Hausbau", "Haus"]);
+        assert_eq!(ranked[4].term_status, Some(5));
+    }
+
+    #[test]
+    fn every_learning_stage_is_boosted_and_ignored_terms_are_not() {
+        let index = TermIndex::build(&TermsData {
+            terms: vec![
+                term("a", "Haus", 99),
+                term("b", "Hausarzt", 5),
+                term("c", "Hausaufgabe", 4),
+                term("d", "Hausbau", 3),
+                term("e", "Haustier", 2),
+            ],
+            version: String::new(),
+            updatedAt: 0,
+        });
+        let ranked = rank_suggestions(candidates(HAUS), &index, "de", true);
+        assert_eq!(words(&ranked), vec!["Hausaufgabe", "Hausbau", "Haustier", "Haus", "Hausarzt"]);
+        assert_eq!(
+            ranked.iter().map(|s| s.term_status).collect::<Vec<_>>(),
+            vec![Some(4), Some(3), Some(2), Some(99), Some(5)]
+        );
+
+        let ranked = rank_suggestions(candidates(HAUS), &index, "de", false);
+        assert_eq!(words(&ranked), vec!["Hausaufgabe", "Hausbau", "Haustier", "Haus", "Hausarzt"]);
+    }
+
+    #[test]
+    fn index_follows_terms_added_mid_session() {
+        let mut index = TermIndex::default();
+        assert_eq!(words(&rank_suggestions(candidates(HAUS), &index, "de", false)), HAUS.to_vec());
+
+        let update = |action: &str, term: Term, timestamp| TermUpdateEvent { action: action.to_string(), term, timestamp };
+        index.apply(&update("add", term("a", "Hausarzt", 0), 10));
+        assert_eq!(index.updated_at, 10);
+        assert_eq!(words(&rank_suggestions(candidates(HAUS), &index, "de", false))[0], "Hausarzt");
+
+        // Promoting it to well known and deleting it both take effect
+        index.apply(&update("update", term("a", "Hausarzt", 5), 20));
+        assert_eq!(words(&rank_suggestions(candidates(HAUS), &index, "de", true))[4], "Hausarzt");
+        index.apply(&update("delete", term("a", "Hausarzt", 5), 30));
+        assert_eq!(words(&rank_suggestions(candidates(HAUS), &index, "de", true)), HAUS.to_vec());
+        assert_eq!(index.updated_at, 30);
+    }
+
+    fn entry(text: &str, pos: &str, definition: &str) -> DictionaryEntry {
+        serde_json::from_value(serde_json::json!({
+            "entry_id": format!("de:{}", text),
+            "text": text,
+            "language": "de",
+            "grammar": pos,
+            "definition": definition,
+            "etymology": "From Middle High German.",
+            "ipa": "/haʊ̯s/",
+        }))
+        .unwrap()
+    }
+
+    /// Fields of each CSV record, following the quoting `csv_field` does
+    fn parse_csv(content: &str) -> Vec<Vec<String>> {
+        let mut records = Vec::new();
+        let (mut record, mut field) = (Vec::new(), String::new());
+        let mut chars = content.chars().peekable();
+        let mut quoted = false;
+        while let Some(c) = chars.next() {
+            match (quoted, c) {
+                (true, '"') if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                (true, '"') => quoted = false,
+                (true, c) => field.push(c),
+                (false, '"') => quoted = true,
+                (false, ',') => record.push(std::mem::take(&mut field)),
+                (false, '\n') => {
+                    record.push(std::mem::take(&mut field));
+                    records.push(std::mem::take(&mut record));
+                }
+                (false, c) => field.push(c),
             }
         }
+        records
     }
-    let output = output.ok_or("Failed to run conversion: no Python interpreter found (tried python, python3, uv run python)")?;
 
-    // Cleanup temp files
-    let _ = fs::remove_file(&jsonl_path);
-    let _ = fs::remove_dir(&temp_dir);
+    fn export_fixture() -> (Vec<String>, HashMap<String, Vec<DictionaryEntry>>) {
+        let words: Vec<String> = ["Haus", "gibtsnicht", "Bank", "Haus"].iter().map(|w| w.to_string()).collect();
+        let mut results = HashMap::new();
+        results.insert("Haus".to_string(), vec![entry("Haus", "noun", "house, home")]);
+        results.insert("Bank".to_string(), vec![
+            entry("Bank", "noun", "bench; \"seat\" for\nseveral people"),
+            entry("Bank", "noun", "bank (financial institution)"),
+        ]);
+        (words, results)
+    }
 
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        emit_progress("error", 0.0, &format!("Conversion failed: {}", stderr));
-        return Err(format!("Conversion failed: {}", stderr));
+    #[test]
+    fn json_export_round_trips_the_entries() {
+        let (words, results) = export_fixture();
+        let (content, found, missing) = render_export(&words, &results, "json").unwrap();
+        assert_eq!(found, 2);
+        assert_eq!(missing, vec!["gibtsnicht"]);
+
+        #[derive(Deserialize)]
+        struct Exported {
+            word: String,
+            entries: Vec<DictionaryEntry>,
+        }
+        let parsed: Vec<Exported> = serde_json::from_str(&content).unwrap();
+        let order: Vec<&str> = parsed.iter().map(|e| e.word.as_str()).collect();
+        assert_eq!(order, vec!["Haus", "Bank"]);
+        for exported in &parsed {
+            let original = serde_json::to_value(&results[&exported.word]).unwrap();
+            assert_eq!(serde_json::to_value(&exported.entries).unwrap(), original);
+        }
     }
 
-    emit_progress("done", 1.0, "Dictionary installed successfully!");
+    #[test]
+    fn csv_export_round_trips_escaped_fields() {
+        let (words, results) = export_fixture();
+        let (content, found, _) = render_export(&words, &results, "csv").unwrap();
+        assert_eq!(found, 2);
+        let records = parse_csv(&content);
+        assert_eq!(records[0], vec!["word", "pos", "definition", "etymology", "ipa"]);
+        let expected: Vec<Vec<String>> = ["Haus", "Bank"]
+            .iter()
+            .flat_map(|word| &results[*word])
+            .map(|e| vec![
+                e.text.clone(),
+                e.grammar.clone().unwrap(),
+                e.definition.clone().unwrap(),
+                e.etymology.clone().unwrap(),
+                e.ipa.clone().unwrap(),
+            ])
+            .collect();
+        assert_eq!(records[1..], expected[..]);
+    }
 
-    Ok(UploadResult {
-        success: true,
-        message: format!("Dictionary for {} downloaded and installed", language_name),
-        file_path: Some(target_db.to_string_lossy().to_string()),
-        file_type: Some("downloaded-jsonl-converted".to_string()),
-    })
+    #[test]
+    fn export_refuses_to_overwrite_without_the_flag() {
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("sheets").join("export.csv");
+        write_export(&target, "first", false).unwrap();
+        let err = write_export(&target, "second", false).unwrap_err();
+        assert!(err.contains("already exists"), "{err}");
+        assert_eq!(fs::read_to_string(&target).unwrap(), "first");
+        write_export(&target, "second", true).unwrap();
+        assert_eq!(fs::read_to_string(&target).unwrap(), "second");
+    }
+
+    #[test]
+    fn every_word_without_entries_is_missing_once() {
+        let words: Vec<String> = ["Haus", "gehen", "Haus", "", "ging"].iter().map(|w| w.to_string()).collect();
+        // No dictionary at all: every requested word is reported
+        assert_eq!(missing_words(&words, &HashMap::new()), ["Haus", "gehen", "", "ging"]);
+
+        let results = HashMap::from([("Haus".to_string(), vec![entry("Haus", "noun", "house")])]);
+        assert_eq!(missing_words(&words, &results), ["gehen", "", "ging"]);
+    }
 }
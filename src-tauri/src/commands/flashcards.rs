@@ -0,0 +1,602 @@
+use serde::{Deserialize, Serialize};
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tauri::State;
+use crate::commands::vocabulary::{load_terms, Term, VocabularyState};
+use crate::normalize;
+use crate::settings::SettingsState;
+use crate::truetype::Font;
+
+// ============================================================================
+// Data Models
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PageSize {
+    A4,
+    Letter,
+}
+
+impl PageSize {
+    /// Width and height in PDF points
+    fn dimensions(self) -> (f64, f64) {
+        match self {
+            PageSize::A4 => (595.28, 841.89),
+            PageSize::Letter => (612.0, 792.0),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct FlashcardLayout {
+    pub rows: u32,
+    pub cols: u32,
+    pub page: PageSize,
+    #[serde(default)]
+    pub double_sided: bool,
+}
+
+/// Which terms to print; every field is optional and combined with AND
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct FlashcardFilter {
+    #[serde(default)]
+    pub statuses: Option<Vec<i32>>,
+    /// Only terms whose review is due now
+    #[serde(default)]
+    pub due_only: bool,
+    /// Only root terms, not saved inflections
+    #[serde(default)]
+    pub roots_only: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkippedCard {
+    pub id: String,
+    pub text: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FlashcardExportResult {
+    pub success: bool,
+    pub path: String,
+    pub cards: usize,
+    pub pages: usize,
+    pub skipped: Vec<SkippedCard>,
+}
+
+// ============================================================================
+// Text Layout
+// ============================================================================
+
+const MARGIN: f64 = 36.0;
+const CELL_PADDING: f64 = 10.0;
+const LINE_HEIGHT: f64 = 1.25;
+const FRONT_SIZE: f64 = 20.0;
+const TRANSLATION_SIZE: f64 = 14.0;
+const CONTEXT_SIZE: f64 = 10.0;
+const MIN_SIZE: f64 = 7.0;
+
+/// Fonts tried when settings name none: ones covering Devanagari and IAST
+/// first, then Latin-only ones
+const SYSTEM_FONTS: &[&str] = &[
+    "C:\\Windows\\Fonts\\Nirmala.ttf",
+    "/System/Library/Fonts/Supplemental/Arial Unicode.ttf",
+    "/Library/Fonts/Arial Unicode.ttf",
+    "/usr/share/fonts/truetype/freefont/FreeSans.ttf",
+    "/usr/share/fonts/gnu-free/FreeSans.ttf",
+    "/usr/share/fonts/truetype/dejavu/DejaVuSans.ttf",
+    "/usr/share/fonts/TTF/DejaVuSans.ttf",
+    "C:\\Windows\\Fonts\\arial.ttf",
+];
+
+/// The font named in settings, or the first system font that loads
+fn card_font(configured: Option<&str>) -> Result<Font, String> {
+    if let Some(path) = configured {
+        return Font::load(Path::new(path));
+    }
+    SYSTEM_FONTS
+        .iter()
+        .map(Path::new)
+        .filter(|path| path.is_file())
+        .find_map(|path| Font::load(path).ok())
+        .ok_or_else(|| "No TrueType font found for flashcards; set flashcardFont in settings".to_string())
+}
+
+/// A glyph and the character it was drawn for
+type Glyph = (u16, char);
+
+const VOWEL_SIGN_I: char = 'ि';
+const VIRAMA: char = '्';
+const NUKTA: char = '़';
+
+fn is_devanagari_consonant(c: char) -> bool {
+    ('क'..='ह').contains(&c) || ('\u{958}'..='\u{95F}').contains(&c)
+}
+
+/// Glyphs for `text` in drawing order, or a character the font lacks.
+/// Whitespace becomes a space. There is no shaping engine, so conjuncts
+/// are drawn with a visible virama; the vowel sign ि is at least moved in
+/// front of the consonant cluster it follows, where it is written.
+fn glyphs(font: &Font, text: &str) -> Result<Vec<Glyph>, char> {
+    let mut out: Vec<Glyph> = Vec::with_capacity(text.len());
+    for c in normalize::compose(text).chars() {
+        let c = if c.is_whitespace() { ' ' } else { c };
+        if c == '\u{200C}' || c == '\u{200D}' {
+            continue;
+        }
+        let glyph = (font.glyph(c).ok_or(c)?, c);
+        if c != VOWEL_SIGN_I {
+            out.push(glyph);
+            continue;
+        }
+        let mut at = out.len();
+        if at > 0 && out[at - 1].1 == NUKTA {
+            at -= 1;
+        }
+        if at > 0 && is_devanagari_consonant(out[at - 1].1) {
+            at -= 1;
+            while at >= 2 && out[at - 1].1 == VIRAMA && is_devanagari_consonant(out[at - 2].1) {
+                at -= 2;
+            }
+        }
+        out.insert(at, glyph);
+    }
+    Ok(out)
+}
+
+fn text_width(font: &Font, glyphs: &[Glyph], size: f64) -> f64 {
+    glyphs.iter().map(|(g, _)| font.advance(*g)).sum::<f64>() * size / 1000.0
+}
+
+/// Greedy word wrap; a single word wider than the line is an error
+fn wrap(font: &Font, glyphs: &[Glyph], size: f64, max_width: f64) -> Option<Vec<Vec<Glyph>>> {
+    let mut lines: Vec<Vec<Glyph>> = Vec::new();
+    let mut current: Vec<Glyph> = Vec::new();
+    for word in glyphs.split(|(_, c)| *c == ' ').filter(|w| !w.is_empty()) {
+        if text_width(font, word, size) > max_width {
+            return None;
+        }
+        let mut candidate = current.clone();
+        if !candidate.is_empty() {
+            candidate.push((font.glyph(' ').unwrap_or(0), ' '));
+        }
+        candidate.extend_from_slice(word);
+        if text_width(font, &candidate, size) <= max_width {
+            current = candidate;
+        } else {
+            lines.push(std::mem::replace(&mut current, word.to_vec()));
+        }
+    }
+    if !current.is_empty() {
+        lines.push(current);
+    }
+    Some(lines)
+}
+
+/// One wrapped block of text in a single size
+struct Block {
+    size: f64,
+    /// Drawn filled and stroked, as the font has no bold face here
+    bold: bool,
+    lines: Vec<Vec<Glyph>>,
+}
+
+impl Block {
+    fn height(&self) -> f64 {
+        self.lines.len() as f64 * self.size * LINE_HEIGHT
+    }
+}
+
+/// Fit the given (preferred size, bold, glyphs) parts into a cell,
+/// shrinking all of them together until they fit or hit the minimum size
+fn fit(font: &Font, parts: &[(f64, bool, &[Glyph])], width: f64, height: f64) -> Option<Vec<Block>> {
+    let mut scale = 1.0;
+    loop {
+        let blocks: Option<Vec<Block>> = parts
+            .iter()
+            .filter(|(_, _, text)| !text.is_empty())
+            .map(|(size, bold, text)| {
+                let size = (size * scale).max(MIN_SIZE);
+                wrap(font, text, size, width).map(|lines| Block { size, bold: *bold, lines })
+            })
+            .collect();
+        if let Some(blocks) = blocks {
+            let total: f64 = blocks.iter().map(Block::height).sum::<f64>()
+                + CONTEXT_SIZE * (blocks.len().saturating_sub(1)) as f64;
+            if total <= height {
+                return Some(blocks);
+            }
+        }
+        if parts.iter().all(|(size, _, _)| size * scale <= MIN_SIZE) {
+            return None;
+        }
+        scale *= 0.9;
+    }
+}
+
+struct Card {
+    front: Vec<Block>,
+    back: Vec<Block>,
+}
+
+/// Glyph IDs as a hex string for the Identity-H encoded font
+fn pdf_string(glyphs: &[Glyph]) -> String {
+    let hex: String = glyphs.iter().map(|(g, _)| format!("{:04X}", g)).collect();
+    format!("<{}>", hex)
+}
+
+/// Draw a cell's cut border and its blocks centered inside it; (x, y) is
+/// the bottom-left corner
+fn draw_cell(ops: &mut String, font: &Font, blocks: &[Block], x: f64, y: f64, w: f64, h: f64) {
+    ops.push_str(&format!("0.8 G 0.5 w {:.2} {:.2} {:.2} {:.2} re S\n", x, y, w, h));
+    let gap = CONTEXT_SIZE;
+    let total: f64 = blocks.iter().map(Block::height).sum::<f64>() + gap * blocks.len().saturating_sub(1) as f64;
+    let mut cursor = y + (h + total) / 2.0;
+    for block in blocks {
+        let render = if block.bold {
+            format!("0 G {:.2} w 2 Tr", block.size * 0.03)
+        } else {
+            "0 Tr".to_string()
+        };
+        for line in &block.lines {
+            cursor -= block.size * LINE_HEIGHT;
+            let line_x = x + (w - text_width(font, line, block.size)) / 2.0;
+            // Baseline sits a little above the bottom of the line box
+            let baseline = cursor + block.size * (LINE_HEIGHT - 1.0) / 2.0 + block.size * 0.2;
+            ops.push_str(&format!(
+                "BT {} /F1 {:.2} Tf {:.2} {:.2} Td {} Tj ET\n",
+                render,
+                block.size,
+                line_x,
+                baseline,
+                pdf_string(line)
+            ));
+        }
+        cursor -= gap;
+    }
+}
+
+/// ToUnicode CMap so text copied or searched in the PDF reads as the
+/// characters the glyphs were drawn for
+fn to_unicode(used: &BTreeMap<u16, char>) -> String {
+    let mut cmap = String::from(
+        "/CIDInit /ProcSet findresource begin\n12 dict begin\nbegincmap\n\
+         /CIDSystemInfo << /Registry (Adobe) /Ordering (UCS) /Supplement 0 >> def\n\
+         /CMapName /Adobe-Identity-UCS def\n/CMapType 2 def\n\
+         1 begincodespacerange\n<0000> <FFFF>\nendcodespacerange\n",
+    );
+    let entries: Vec<_> = used.iter().collect();
+    // A bfchar section holds at most 100 entries
+    for chunk in entries.chunks(100) {
+        cmap.push_str(&format!("{} beginbfchar\n", chunk.len()));
+        for (glyph, c) in chunk {
+            let utf16: String = c.encode_utf16(&mut [0; 2]).iter().map(|u| format!("{:04X}", u)).collect();
+            cmap.push_str(&format!("<{:04X}> <{}>\n", glyph, utf16));
+        }
+        cmap.push_str("endbfchar\n");
+    }
+    cmap.push_str("endcmap\nCMapName currentdict /CMap defineresource pop\nend\nend\n");
+    cmap
+}
+
+fn stream(dictionary: &str, content: &[u8]) -> Vec<u8> {
+    let mut object = format!("<< /Length {}{} >>\nstream\n", content.len(), dictionary).into_bytes();
+    object.extend_from_slice(content);
+    object.extend_from_slice(b"\nendstream");
+    object
+}
+
+/// Minimal PDF 1.4 writer: one embedded TrueType font addressed by glyph
+/// ID, one content stream per page, only the font file compressed
+fn write_pdf(pages: &[String], page_size: (f64, f64), font: &Font, used: &BTreeMap<u16, char>) -> Result<Vec<u8>, String> {
+    let mut objects: Vec<Vec<u8>> = Vec::new();
+    // 1: catalog, 2: pages, 3-7: font, then page/content pairs
+    let first_page_obj = 8;
+    let kids: Vec<String> = (0..pages.len())
+        .map(|i| format!("{} 0 R", first_page_obj + i * 2))
+        .collect();
+    objects.push(b"<< /Type /Catalog /Pages 2 0 R >>".to_vec());
+    objects.push(format!("<< /Type /Pages /Kids [{}] /Count {} >>", kids.join(" "), pages.len()).into_bytes());
+
+    let widths: Vec<String> = used.keys().map(|g| format!("{} [{:.0}]", g, font.advance(*g))).collect();
+    let [x_min, y_min, x_max, y_max] = font.bbox();
+    let (ascent, descent) = font.vertical_metrics();
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    encoder
+        .write_all(&font.data)
+        .and_then(|_| encoder.flush())
+        .map_err(|e| format!("Failed to compress font: {}", e))?;
+    let font_file = encoder.finish().map_err(|e| format!("Failed to compress font: {}", e))?;
+    objects.push(format!(
+        "<< /Type /Font /Subtype /Type0 /BaseFont /{} /Encoding /Identity-H \
+         /DescendantFonts [4 0 R] /ToUnicode 7 0 R >>",
+        font.name
+    ).into_bytes());
+    objects.push(format!(
+        "<< /Type /Font /Subtype /CIDFontType2 /BaseFont /{} \
+         /CIDSystemInfo << /Registry (Adobe) /Ordering (Identity) /Supplement 0 >> \
+         /FontDescriptor 5 0 R /W [{}] /CIDToGIDMap /Identity >>",
+        font.name,
+        widths.join(" ")
+    ).into_bytes());
+    objects.push(format!(
+        "<< /Type /FontDescriptor /FontName /{} /Flags 32 /FontBBox [{} {} {} {}] \
+         /ItalicAngle 0 /Ascent {} /Descent {} /CapHeight {} /StemV 80 /FontFile2 6 0 R >>",
+        font.name, x_min, y_min, x_max, y_max, ascent, descent, ascent
+    ).into_bytes());
+    objects.push(stream(&format!(" /Length1 {} /Filter /FlateDecode", font.data.len()), &font_file));
+    objects.push(stream("", to_unicode(used).as_bytes()));
+
+    for (i, content) in pages.iter().enumerate() {
+        let content_obj = first_page_obj + i * 2 + 1;
+        objects.push(format!(
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {:.2} {:.2}] \
+             /Resources << /Font << /F1 3 0 R >> >> /Contents {} 0 R >>",
+            page_size.0, page_size.1, content_obj
+        ).into_bytes());
+        objects.push(stream("", content.as_bytes()));
+    }
+
+    let mut out: Vec<u8> = b"%PDF-1.4\n%\xE2\xE3\xCF\xD3\n".to_vec();
+    let mut offsets = Vec::with_capacity(objects.len());
+    for (i, object) in objects.iter().enumerate() {
+        offsets.push(out.len());
+        out.extend_from_slice(format!("{} 0 obj\n", i + 1).as_bytes());
+        out.extend_from_slice(object);
+        out.extend_from_slice(b"\nendobj\n");
+    }
+    let xref_offset = out.len();
+    out.extend_from_slice(format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1).as_bytes());
+    for offset in offsets {
+        out.extend_from_slice(format!("{:010} 00000 n \n", offset).as_bytes());
+    }
+    out.extend_from_slice(
+        format!(
+            "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+            objects.len() + 1,
+            xref_offset
+        )
+        .as_bytes(),
+    );
+    Ok(out)
+}
+
+/// The sentence printed under the translation: the term's latest context
+fn context_sentence(term: &Term) -> &str {
+    term.contexts.last().map(|c| c.text.as_str()).unwrap_or("")
+}
+
+fn matches_filter(term: &Term, language_id: &str, filter: &FlashcardFilter, now: i64) -> bool {
+    term.languageId == language_id
+        && filter.statuses.as_ref().is_none_or(|s| s.contains(&term.status))
+        && (!filter.due_only || term.nextReview <= now)
+        && (!filter.roots_only || term.parentId.is_none())
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Print terms as cut-out flashcards: term on the front, translation and
+/// the term's latest context sentence on the back.
+///
+/// With `double_sided`, every front page is followed by its back page with
+/// columns mirrored, so cards line up when the sheet is flipped on the long
+/// edge. Single-sided output interleaves unmirrored back pages instead.
+///
+/// Embeds the TrueType font set as `flashcardFont`, or else a system font
+/// (Nirmala UI, Arial Unicode, FreeSans, DejaVu Sans), whole. Terms with a
+/// character the font lacks are skipped and reported rather than printed
+/// as missing glyphs.
+#[tauri::command]
+pub async fn export_flashcards_pdf(
+    state: State<'_, VocabularyState>,
+    settings: State<'_, SettingsState>,
+    language_id: String,
+    filter: Option<FlashcardFilter>,
+    layout: FlashcardLayout,
+    output_path: String,
+) -> Result<FlashcardExportResult, String> {
+    if layout.rows == 0 || layout.cols == 0 || layout.rows > 10 || layout.cols > 6 {
+        return Err("Layout must have 1-10 rows and 1-6 columns".to_string());
+    }
+    let filter = filter.unwrap_or_default();
+    let font = card_font(settings.get().flashcard_font.as_deref())?;
+    let terms_path = state.terms_path.lock().unwrap().clone();
+    let data = load_terms(&terms_path);
+    let now = chrono::Utc::now().timestamp_millis();
+
+    let (page_w, page_h) = layout.page.dimensions();
+    let cell_w = (page_w - 2.0 * MARGIN) / layout.cols as f64;
+    let cell_h = (page_h - 2.0 * MARGIN) / layout.rows as f64;
+    let inner_w = cell_w - 2.0 * CELL_PADDING;
+    let inner_h = cell_h - 2.0 * CELL_PADDING;
+
+    let mut cards = Vec::new();
+    let mut skipped = Vec::new();
+    // Every glyph drawn, with its character for the ToUnicode map
+    let mut used: BTreeMap<u16, char> = BTreeMap::new();
+    for term in data.terms.iter().filter(|t| matches_filter(t, &language_id, &filter, now)) {
+        let skip = |reason: String| SkippedCard {
+            id: term.id.clone(),
+            text: term.text.clone(),
+            reason,
+        };
+        let shaped = [term.text.as_str(), term.translation.as_str(), context_sentence(term)]
+            .iter()
+            .map(|s| glyphs(&font, s.trim()))
+            .collect::<Result<Vec<_>, _>>();
+        let parts = match shaped {
+            Ok(parts) => parts,
+            Err(c) => {
+                skipped.push(skip(format!("Character '{}' is not in the font {}", c, font.name)));
+                continue;
+            }
+        };
+        let front = fit(&font, &[(FRONT_SIZE, true, &parts[0])], inner_w, inner_h);
+        let back = fit(
+            &font,
+            &[(TRANSLATION_SIZE, false, &parts[1]), (CONTEXT_SIZE, false, &parts[2])],
+            inner_w,
+            inner_h,
+        );
+        match (front, back) {
+            (Some(front), Some(back)) => {
+                used.extend(parts.iter().flatten().copied());
+                used.extend(font.glyph(' ').map(|g| (g, ' ')));
+                cards.push(Card { front, back });
+            }
+            (None, _) => skipped.push(skip("Term text does not fit on a card".to_string())),
+            (_, None) => skipped.push(skip("Translation and context do not fit on a card".to_string())),
+        }
+    }
+
+    if cards.is_empty() {
+        return Ok(FlashcardExportResult {
+            success: false,
+            path: output_path,
+            cards: 0,
+            pages: 0,
+            skipped,
+        });
+    }
+
+    let per_page = (layout.rows * layout.cols) as usize;
+    let mut pages = Vec::new();
+    for chunk in cards.chunks(per_page) {
+        let mut front_ops = String::new();
+        let mut back_ops = String::new();
+        for (i, card) in chunk.iter().enumerate() {
+            let row = (i / layout.cols as usize) as f64;
+            let col = i % layout.cols as usize;
+            let back_col = if layout.double_sided { layout.cols as usize - 1 - col } else { col };
+            let y = page_h - MARGIN - (row + 1.0) * cell_h;
+            let front_x = MARGIN + col as f64 * cell_w;
+            let back_x = MARGIN + back_col as f64 * cell_w;
+            draw_cell(&mut front_ops, &font, &card.front, front_x, y, cell_w, cell_h);
+            draw_cell(&mut back_ops, &font, &card.back, back_x, y, cell_w, cell_h);
+        }
+        pages.push(front_ops);
+        pages.push(back_ops);
+    }
+
+    let path = PathBuf::from(&output_path);
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create directory: {}", e))?;
+    }
+    fs::write(&path, write_pdf(&pages, (page_w, page_h), &font, &used)?)
+        .map_err(|e| format!("Failed to write PDF: {}", e))?;
+
+    eprintln!(
+        "[FLASHCARDS] Wrote {} cards on {} pages to {} ({} skipped)",
+        cards.len(),
+        pages.len(),
+        output_path,
+        skipped.len()
+    );
+    Ok(FlashcardExportResult {
+        success: true,
+        path: output_path,
+        cards: cards.len(),
+        pages: pages.len(),
+        skipped,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::truetype;
+
+    fn font() -> Font {
+        Font::parse(truetype::test_font(&['a', 'ā', 'r', 'm', ' ', 'क', 'ष', 'त', '्', 'ि', 'ा']), "Test").unwrap()
+    }
+
+    fn chars(glyphs: &[Glyph]) -> String {
+        glyphs.iter().map(|(_, c)| c).collect()
+    }
+
+    #[test]
+    fn iast_and_devanagari_map_to_the_embedded_font() {
+        let font = font();
+        assert_eq!(chars(&glyphs(&font, "rāma\tmā").unwrap()), "rāma mā");
+        // Decomposed IAST is composed first
+        assert_eq!(chars(&glyphs(&font, "ra\u{304}").unwrap()), "rā");
+        assert_eq!(chars(&glyphs(&font, "कता").unwrap()), "कता");
+        assert_eq!(glyphs(&font, "dharma"), Err('d'));
+    }
+
+    #[test]
+    fn short_i_is_drawn_before_its_consonant_cluster() {
+        let font = font();
+        assert_eq!(chars(&glyphs(&font, "ति").unwrap()), "ित");
+        assert_eq!(chars(&glyphs(&font, "क्षि").unwrap()), "िक्ष");
+        assert_eq!(chars(&glyphs(&font, "कक्षि").unwrap()), "किक्ष");
+    }
+
+    #[test]
+    fn pdf_embeds_the_font_with_a_unicode_map() {
+        let font = font();
+        let used: BTreeMap<u16, char> = glyphs(&font, "क a").unwrap().into_iter().collect();
+        let pdf = write_pdf(&["BT /F1 12 Tf <0006> Tj ET\n".to_string()], PageSize::A4.dimensions(), &font, &used).unwrap();
+        let text = String::from_utf8_lossy(&pdf);
+        assert!(text.contains("/Subtype /CIDFontType2 /BaseFont /Test"));
+        assert!(text.contains("/FontFile2 6 0 R"));
+        assert!(text.contains(&format!("/Length1 {}", font.data.len())));
+        assert!(text.contains("/W [1 [500] 5 [500] 6 [500]]"));
+        assert!(text.contains("3 beginbfchar\n<0001> <0061>\n<0005> <0020>\n<0006> <0915>\nendbfchar"));
+
+        // Every xref entry points at its object
+        let xref = text.rfind("xref\n").unwrap();
+        for (i, line) in text[xref..].lines().skip(3).take(9).enumerate() {
+            let offset: usize = line[..10].parse().unwrap();
+            assert!(text[offset..].starts_with(&format!("{} 0 obj", i + 1)));
+        }
+    }
+
+    #[test]
+    fn card_back_shows_the_latest_context() {
+        let term: Term = serde_json::from_value(serde_json::json!({
+            "id": "a", "text": "Haus", "languageId": "de", "translation": "house",
+            "notes": "a note", "status": 1, "nextReview": 0, "contexts": [
+                { "text": "Das Haus ist alt.", "addedAt": 1 },
+                { "text": "Ein großes Haus.", "addedAt": 2 },
+            ],
+        }))
+        .unwrap();
+        assert_eq!(context_sentence(&term), "Ein großes Haus.");
+        let bare = Term { contexts: Vec::new(), ..term };
+        assert_eq!(context_sentence(&bare), "");
+    }
+
+    #[test]
+    fn filter_matches_statuses_due_terms_and_roots() {
+        let term: Term = serde_json::from_value(serde_json::json!({
+            "id": "a", "text": "Haus", "languageId": "de", "translation": "house",
+            "notes": "", "status": 1, "nextReview": 100, "parentId": "root",
+        }))
+        .unwrap();
+        let all = FlashcardFilter::default();
+        assert!(matches_filter(&term, "de", &all, 0));
+        assert!(!matches_filter(&term, "fr", &all, 0));
+
+        let learning = FlashcardFilter { statuses: Some(vec![1]), ..Default::default() };
+        let mastered = FlashcardFilter { statuses: Some(vec![2]), ..Default::default() };
+        assert!(matches_filter(&term, "de", &learning, 0));
+        assert!(!matches_filter(&term, "de", &mastered, 0));
+
+        let due = FlashcardFilter { due_only: true, ..Default::default() };
+        assert!(!matches_filter(&term, "de", &due, 99));
+        assert!(matches_filter(&term, "de", &due, 100));
+
+        let roots = FlashcardFilter { roots_only: true, ..Default::default() };
+        assert!(!matches_filter(&term, "de", &roots, 0));
+    }
+}
@@ -0,0 +1,30 @@
+use crate::search_history::{self, HistoryEntry};
+
+/// Past lookups, newest first
+#[tauri::command]
+pub async fn get_search_history(
+    language: Option<String>,
+    limit: Option<usize>,
+    offset: Option<usize>,
+) -> Result<Vec<HistoryEntry>, String> {
+    Ok(search_history::list(
+        language.as_deref(),
+        limit.unwrap_or(50).clamp(1, 1000),
+        offset.unwrap_or(0),
+    ))
+}
+
+#[tauri::command]
+pub async fn clear_search_history() -> Result<(), String> {
+    search_history::clear();
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn delete_history_entry(id: u64) -> Result<(), String> {
+    if search_history::delete(id) {
+        Ok(())
+    } else {
+        Err(format!("History entry {} not found", id))
+    }
+}
@@ -0,0 +1,615 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager};
+use crate::commands::vocabulary::{self, TermsData, VocabularyState};
+use crate::db;
+use crate::settings::{self, Settings, SettingsState};
+
+// ============================================================================
+// Data Models
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IntegrityIssue {
+    /// Stable id, passed back to `repair_data`: "<category>.<kind>[:<subject>]"
+    pub id: String,
+    pub category: String,
+    pub message: String,
+    /// Safe to fix automatically; nothing irreversible happens unless listed
+    pub repairable: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IntegrityCategory {
+    pub name: String,
+    pub checked: usize,
+    pub issues: Vec<IntegrityIssue>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IntegrityReport {
+    pub success: bool,
+    pub categories: Vec<IntegrityCategory>,
+    pub total_issues: usize,
+    pub repairable_issues: usize,
+    pub checked_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IdRemap {
+    pub old_id: String,
+    pub new_id: String,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RepairResult {
+    pub success: bool,
+    pub repaired: Vec<String>,
+    /// Listed ids that were unknown, no longer present or not repairable
+    pub skipped: Vec<String>,
+    pub id_mapping: Vec<IdRemap>,
+    pub error: Option<String>,
+}
+
+/// Paths of every store the checks look at
+struct DataPaths {
+    terms: PathBuf,
+    media_dir: PathBuf,
+    settings: PathBuf,
+    dict_dir: PathBuf,
+}
+
+impl DataPaths {
+    fn resolve(app: &AppHandle) -> Self {
+        let terms = app.state::<VocabularyState>().terms_path.lock().unwrap().clone();
+        let data_dir = terms.parent().map(Path::to_path_buf).unwrap_or_default();
+        Self {
+            media_dir: data_dir.join("images"),
+            terms,
+            settings: settings::get_settings_path(app),
+            dict_dir: db::get_dict_dir(),
+        }
+    }
+}
+
+fn issue(category: &str, kind: &str, subject: Option<&str>, message: String, repairable: bool) -> IntegrityIssue {
+    let id = match subject {
+        Some(s) => format!("{}.{}:{}", category, kind, s),
+        None => format!("{}.{}", category, kind),
+    };
+    IntegrityIssue {
+        id,
+        category: category.to_string(),
+        message,
+        repairable,
+    }
+}
+
+// ============================================================================
+// Checks
+// ============================================================================
+
+/// Parse every language's terms store without the silent fallbacks of
+/// `load_terms`
+fn read_terms_strict(terms_path: &Path) -> Result<Option<TermsData>, String> {
+    let stores = vocabulary::language_stores(terms_path);
+    if stores.is_empty() {
+        return Ok(None);
+    }
+    let mut data = TermsData {
+        terms: Vec::new(),
+        version: vocabulary::TERMS_VERSION.to_string(),
+        updatedAt: 0,
+    };
+    for path in stores {
+        let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        let content = fs::read_to_string(&path).map_err(|e| format!("Cannot read {}: {}", name, e))?;
+        let (store, _, _) = vocabulary::parse_terms(&content).map_err(|e| format!("{} does not parse: {}", name, e))?;
+        data.terms.extend(store.terms);
+    }
+    Ok(Some(data))
+}
+
+/// Image references that point at local files (data URLs and remote images are skipped)
+fn local_image_path(image: &str, media_dir: &Path) -> Option<PathBuf> {
+    if image.is_empty() || image.starts_with("data:") || image.starts_with("http://") || image.starts_with("https://") {
+        return None;
+    }
+    let path = PathBuf::from(image);
+    Some(if path.is_absolute() { path } else { media_dir.join(path) })
+}
+
+fn check_terms(paths: &DataPaths) -> (IntegrityCategory, IntegrityCategory) {
+    let mut terms_cat = IntegrityCategory { name: "terms".to_string(), checked: 0, issues: Vec::new() };
+    let mut media_cat = IntegrityCategory { name: "media".to_string(), checked: 0, issues: Vec::new() };
+
+    let data = match read_terms_strict(&paths.terms) {
+        Ok(Some(data)) => data,
+        Ok(None) => return (terms_cat, media_cat),
+        Err(e) => {
+            // Rewriting would lose every term; only a human can decide that
+            terms_cat.issues.push(issue("terms", "unparseable", None, e, false));
+            return (terms_cat, media_cat);
+        }
+    };
+    terms_cat.checked = data.terms.len();
+
+    let mut seen: HashMap<&str, usize> = HashMap::new();
+    for term in &data.terms {
+        *seen.entry(term.id.as_str()).or_insert(0) += 1;
+    }
+    let mut duplicates: Vec<(&str, usize)> = seen.iter().filter(|(_, n)| **n > 1).map(|(id, n)| (*id, *n)).collect();
+    duplicates.sort();
+    for (id, count) in duplicates {
+        terms_cat.issues.push(issue(
+            "terms",
+            "duplicate_id",
+            Some(id),
+            format!("{} terms share id '{}'", count, id),
+            true,
+        ));
+    }
+
+    for term in &data.terms {
+        if let Some(parent) = term.parentId.as_deref() {
+            if !seen.contains_key(parent) {
+                terms_cat.issues.push(issue(
+                    "terms",
+                    "dangling_parent",
+                    Some(&term.id),
+                    format!("'{}' points at missing parent '{}'", term.text, parent),
+                    true,
+                ));
+            }
+        }
+    }
+
+    // Media referenced by terms but missing on disk, and files nobody references
+    let mut referenced: HashSet<PathBuf> = HashSet::new();
+    for term in &data.terms {
+        let Some(path) = term.image.as_deref().and_then(|i| local_image_path(i, &paths.media_dir)) else {
+            continue;
+        };
+        media_cat.checked += 1;
+        if !path.exists() {
+            media_cat.issues.push(issue(
+                "media",
+                "missing_file",
+                Some(&term.id),
+                format!("Image for '{}' not found: {}", term.text, path.display()),
+                true,
+            ));
+        }
+        referenced.insert(path);
+    }
+
+    if let Ok(entries) = fs::read_dir(&paths.media_dir) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_file() && !referenced.contains(&path) {
+                media_cat.checked += 1;
+                let name = entry.file_name().to_string_lossy().to_string();
+                // Deleting user files is irreversible; report only
+                media_cat.issues.push(issue(
+                    "media",
+                    "orphan_file",
+                    Some(&name),
+                    format!("No term references {}", path.display()),
+                    false,
+                ));
+            }
+        }
+    }
+
+    (terms_cat, media_cat)
+}
+
+fn check_settings(paths: &DataPaths) -> IntegrityCategory {
+    let mut cat = IntegrityCategory { name: "settings".to_string(), checked: 0, issues: Vec::new() };
+    let Ok(content) = fs::read_to_string(&paths.settings) else {
+        return cat;
+    };
+    cat.checked = 1;
+
+    let settings = match serde_json::from_str::<Settings>(&content) {
+        Ok(s) => s,
+        Err(e) => {
+            cat.issues.push(issue(
+                "settings",
+                "unparseable",
+                None,
+                format!("settings.json does not parse ({}); repair backs it up and restores defaults", e),
+                true,
+            ));
+            return cat;
+        }
+    };
+
+    let current = Settings::default().version;
+    if settings.version > current {
+        cat.issues.push(issue(
+            "settings",
+            "newer_version",
+            None,
+            format!("settings.json is version {}, this build understands {}", settings.version, current),
+            false,
+        ));
+    }
+    for (field, value) in [("uiLanguage", &settings.ui_language), ("glossLanguage", &settings.gloss_language)] {
+        if let Some(code) = value {
+            if crate::locale::normalize_locale(code).as_deref() != Some(code.as_str()) {
+                cat.issues.push(issue(
+                    "settings",
+                    "invalid_language",
+                    Some(field),
+                    format!("{} '{}' is not a language code", field, code),
+                    true,
+                ));
+            }
+        }
+    }
+    cat
+}
+
+fn check_dictionaries(paths: &DataPaths) -> IntegrityCategory {
+    let mut cat = IntegrityCategory { name: "dictionaries".to_string(), checked: 0, issues: Vec::new() };
+    let Ok(entries) = fs::read_dir(&paths.dict_dir) else {
+        return cat;
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("db") {
+            continue;
+        }
+        cat.checked += 1;
+        let name = entry.file_name().to_string_lossy().to_string();
+
+        let result = rusqlite::Connection::open_with_flags(&path, rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY)
+            .and_then(|conn| {
+                conn.query_row(
+                    "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name IN ('dictionary', 'senses', 'forms')",
+                    [],
+                    |row| row.get::<_, i64>(0),
+                )
+            });
+        match result {
+            Ok(3) => {}
+            Ok(_) => cat.issues.push(issue(
+                "dictionaries",
+                "missing_tables",
+                Some(&name),
+                format!("{} lacks the dictionary/senses/forms tables", name),
+                false,
+            )),
+            Err(e) => cat.issues.push(issue(
+                "dictionaries",
+                "unreadable",
+                Some(&name),
+                format!("{} cannot be opened: {}", name, e),
+                false,
+            )),
+        }
+    }
+    cat
+}
+
+/// Run every check. Read-only; safe to call at startup.
+pub fn run_integrity_checks(app: &AppHandle) -> IntegrityReport {
+    let paths = DataPaths::resolve(app);
+    let (terms, media) = check_terms(&paths);
+    let categories = vec![terms, media, check_settings(&paths), check_dictionaries(&paths)];
+
+    let total_issues = categories.iter().map(|c| c.issues.len()).sum();
+    let repairable_issues = categories
+        .iter()
+        .flat_map(|c| c.issues.iter())
+        .filter(|i| i.repairable)
+        .count();
+
+    IntegrityReport {
+        success: true,
+        categories,
+        total_issues,
+        repairable_issues,
+        checked_at: chrono::Utc::now().timestamp_millis(),
+    }
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Verify terms, media references, settings and dictionary files
+#[tauri::command]
+pub async fn verify_data_integrity(app: AppHandle) -> Result<IntegrityReport, String> {
+    Ok(run_integrity_checks(&app))
+}
+
+/// Fix the listed issues. Only ids reported as repairable by the latest
+/// check are acted on; everything else ends up in `skipped`.
+#[tauri::command]
+pub async fn repair_data(app: AppHandle, issues: Vec<String>) -> Result<RepairResult, String> {
+    vocabulary::spawn_write(app, move |app, state| {
+        repair_data_blocking(app, state, &app.state::<SettingsState>(), issues)
+    })
+    .await
+}
+
+fn repair_data_blocking(
+    app: &AppHandle,
+    state: &VocabularyState,
+    settings_state: &SettingsState,
+    issues: Vec<String>,
+) -> Result<RepairResult, String> {
+    let report = run_integrity_checks(app);
+    let repairable: HashSet<String> = report
+        .categories
+        .iter()
+        .flat_map(|c| c.issues.iter())
+        .filter(|i| i.repairable)
+        .map(|i| i.id.clone())
+        .collect();
+
+    let mut result = RepairResult {
+        success: true,
+        repaired: Vec::new(),
+        skipped: Vec::new(),
+        id_mapping: Vec::new(),
+        error: None,
+    };
+    let (todo, skipped): (Vec<String>, Vec<String>) = issues.into_iter().partition(|id| repairable.contains(id));
+    result.skipped = skipped;
+
+    // Terms and media fixes share one load/save of the store
+    let term_fixes: Vec<&String> = todo.iter().filter(|id| id.starts_with("terms.") || id.starts_with("media.")).collect();
+    if !term_fixes.is_empty() {
+        let terms_path = state.terms_path.lock().unwrap().clone();
+        let _guard = state.lock_all_languages();
+        let mut data = match read_terms_strict(&terms_path) {
+            Ok(Some(data)) => data,
+            Ok(None) => return Err("Terms store disappeared during repair".to_string()),
+            Err(e) => return Err(e),
+        };
+        let now = chrono::Utc::now().timestamp_millis();
+
+        for fix in term_fixes {
+            let (kind, subject) = fix.split_once(':').unwrap_or((fix.as_str(), ""));
+            match kind {
+                "terms.duplicate_id" => {
+                    // First occurrence keeps the id, later ones get a fresh suffix
+                    let mut n = 0;
+                    for term in data.terms.iter_mut().filter(|t| t.id == subject) {
+                        n += 1;
+                        if n == 1 {
+                            continue;
+                        }
+                        let new_id = format!("{}#{}", subject, n);
+                        result.id_mapping.push(IdRemap {
+                            old_id: term.id.clone(),
+                            new_id: new_id.clone(),
+                            text: term.text.clone(),
+                        });
+                        term.id = new_id;
+                        term.updatedAt = now;
+                    }
+                }
+                "terms.dangling_parent" => {
+                    if let Some(term) = data.terms.iter_mut().find(|t| t.id == subject) {
+                        term.parentId = None;
+                        term.updatedAt = now;
+                    }
+                }
+                "media.missing_file" => {
+                    if let Some(term) = data.terms.iter_mut().find(|t| t.id == subject) {
+                        term.image = None;
+                        term.updatedAt = now;
+                    }
+                }
+                _ => {
+                    result.skipped.push(fix.clone());
+                    continue;
+                }
+            }
+            result.repaired.push(fix.clone());
+        }
+
+        data.updatedAt = now;
+        vocabulary::save_terms(&terms_path, &data)?;
+    }
+
+    for fix in todo.iter().filter(|id| id.starts_with("settings.")) {
+        let repaired = match fix.as_str() {
+            "settings.unparseable" => {
+                let path = settings::get_settings_path(app);
+                let backup = path.with_extension(format!("json.corrupt-{}", chrono::Utc::now().timestamp()));
+                fs::copy(&path, &backup).map_err(|e| format!("Failed to back up settings: {}", e))?;
+                // The in-memory copy already fell back to defaults on load
+                settings_state.update(|_| {})
+            }
+            "settings.invalid_language:uiLanguage" => settings_state.update(|s| s.ui_language = None),
+            "settings.invalid_language:glossLanguage" => settings_state.update(|s| s.gloss_language = None),
+            _ => {
+                result.skipped.push(fix.clone());
+                continue;
+            }
+        };
+        match repaired {
+            Ok(_) => result.repaired.push(fix.clone()),
+            Err(e) => {
+                result.success = false;
+                result.error = Some(e);
+            }
+        }
+    }
+
+    eprintln!(
+        "[MAINTENANCE] Repaired {} issue(s), skipped {}",
+        result.repaired.len(),
+        result.skipped.len()
+    );
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    /// Empty data dir laid out like the app's: data/, data/images, dictionaries/
+    fn fixture() -> (TempDir, DataPaths) {
+        let dir = TempDir::new().unwrap();
+        let data = dir.path().join("data");
+        fs::create_dir_all(data.join("images")).unwrap();
+        fs::create_dir_all(dir.path().join("dictionaries")).unwrap();
+        let paths = DataPaths {
+            terms: data.join("terms.json"),
+            media_dir: data.join("images"),
+            settings: dir.path().join("settings.json"),
+            dict_dir: dir.path().join("dictionaries"),
+        };
+        (dir, paths)
+    }
+
+    fn write_store(paths: &DataPaths, language: &str, terms: serde_json::Value) {
+        fs::write(vocabulary::language_path(&paths.terms, language), terms.to_string()).unwrap();
+    }
+
+    fn ids(cat: &IntegrityCategory) -> Vec<(&str, bool)> {
+        cat.issues.iter().map(|i| (i.id.as_str(), i.repairable)).collect()
+    }
+
+    #[test]
+    fn clean_data_has_no_issues() {
+        let (_dir, paths) = fixture();
+        write_store(&paths, "de", serde_json::json!([{ "id": "a", "text": "Haus", "languageId": "de" }]));
+        fs::write(&paths.settings, r#"{"uiLanguage": "de"}"#).unwrap();
+
+        let (terms, media) = check_terms(&paths);
+        assert_eq!((terms.checked, terms.issues.len()), (1, 0));
+        assert!(media.issues.is_empty());
+        assert!(check_settings(&paths).issues.is_empty());
+        assert!(check_dictionaries(&paths).issues.is_empty());
+    }
+
+    #[test]
+    fn unparseable_store_is_reported_but_not_repairable() {
+        let (_dir, paths) = fixture();
+        fs::write(vocabulary::language_path(&paths.terms, "de"), "{\"terms\": [").unwrap();
+
+        let (terms, media) = check_terms(&paths);
+        assert_eq!(ids(&terms), vec![("terms.unparseable", false)]);
+        assert!(terms.issues[0].message.contains("terms_de.json"));
+        assert_eq!(media.checked, 0);
+    }
+
+    #[test]
+    fn duplicate_ids_across_stores_are_reported() {
+        let (_dir, paths) = fixture();
+        write_store(&paths, "de", serde_json::json!([{ "id": "x", "text": "Haus", "languageId": "de" }]));
+        write_store(&paths, "fr", serde_json::json!([{ "id": "x", "text": "maison", "languageId": "fr" }]));
+
+        let (terms, _) = check_terms(&paths);
+        assert_eq!(ids(&terms), vec![("terms.duplicate_id:x", true)]);
+        assert!(terms.issues[0].message.starts_with("2 terms"));
+    }
+
+    #[test]
+    fn dangling_parent_is_reported() {
+        let (_dir, paths) = fixture();
+        write_store(
+            &paths,
+            "de",
+            serde_json::json!([
+                { "id": "a", "text": "Haus", "languageId": "de" },
+                { "id": "b", "text": "Häuser", "languageId": "de", "parentId": "a" },
+                { "id": "c", "text": "Hauses", "languageId": "de", "parentId": "gone" },
+            ]),
+        );
+
+        let (terms, _) = check_terms(&paths);
+        assert_eq!(ids(&terms), vec![("terms.dangling_parent:c", true)]);
+    }
+
+    #[test]
+    fn missing_and_orphaned_media_are_reported() {
+        let (_dir, paths) = fixture();
+        fs::write(paths.media_dir.join("kept.png"), b"png").unwrap();
+        fs::write(paths.media_dir.join("stray.png"), b"png").unwrap();
+        write_store(
+            &paths,
+            "de",
+            serde_json::json!([
+                { "id": "a", "text": "Haus", "languageId": "de", "image": "kept.png" },
+                { "id": "b", "text": "Baum", "languageId": "de", "image": "lost.png" },
+                { "id": "c", "text": "Hund", "languageId": "de", "image": "https://example.org/dog.png" },
+            ]),
+        );
+
+        let (_, media) = check_terms(&paths);
+        assert_eq!(media.checked, 3);
+        assert_eq!(
+            ids(&media),
+            vec![("media.missing_file:b", true), ("media.orphan_file:stray.png", false)]
+        );
+    }
+
+    #[test]
+    fn unparseable_settings_are_repairable() {
+        let (_dir, paths) = fixture();
+        fs::write(&paths.settings, "{ not json").unwrap();
+
+        let cat = check_settings(&paths);
+        assert_eq!(ids(&cat), vec![("settings.unparseable", true)]);
+    }
+
+    #[test]
+    fn settings_from_a_newer_build_are_left_alone() {
+        let (_dir, paths) = fixture();
+        let newer = settings::SETTINGS_VERSION + 1;
+        fs::write(&paths.settings, format!(r#"{{"version": {}}}"#, newer)).unwrap();
+
+        let cat = check_settings(&paths);
+        assert_eq!(ids(&cat), vec![("settings.newer_version", false)]);
+    }
+
+    #[test]
+    fn invalid_language_codes_are_reported_per_field() {
+        let (_dir, paths) = fixture();
+        fs::write(&paths.settings, r#"{"uiLanguage": "English", "glossLanguage": "de"}"#).unwrap();
+
+        let cat = check_settings(&paths);
+        assert_eq!(ids(&cat), vec![("settings.invalid_language:uiLanguage", true)]);
+    }
+
+    #[test]
+    fn dictionary_without_tables_is_reported() {
+        let (_dir, paths) = fixture();
+        let conn = rusqlite::Connection::open(paths.dict_dir.join("de.db")).unwrap();
+        conn.execute_batch("CREATE TABLE dictionary (id INTEGER PRIMARY KEY)").unwrap();
+        drop(conn);
+
+        let cat = check_dictionaries(&paths);
+        assert_eq!(cat.checked, 1);
+        assert_eq!(ids(&cat), vec![("dictionaries.missing_tables:de.db", false)]);
+    }
+
+    #[test]
+    fn garbage_dictionary_is_unreadable() {
+        let (_dir, paths) = fixture();
+        fs::write(paths.dict_dir.join("fr.db"), vec![0x42u8; 4096]).unwrap();
+        fs::write(paths.dict_dir.join("notes.txt"), "not a dictionary").unwrap();
+
+        let cat = check_dictionaries(&paths);
+        assert_eq!(cat.checked, 1);
+        assert_eq!(ids(&cat), vec![("dictionaries.unreadable:fr.db", false)]);
+    }
+}
@@ -1,3 +1,11 @@
+pub mod actions;
+pub mod analysis;
 pub mod dictionary;
+pub mod flashcards;
+pub mod history;
+pub mod maintenance;
+pub mod queue;
 pub mod sanskrit;
+pub mod texts;
+pub mod usage;
 pub mod vocabulary;
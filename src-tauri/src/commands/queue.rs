@@ -0,0 +1,29 @@
+use serde::{Deserialize, Serialize};
+use tauri::State;
+use crate::offline_queue::{OfflineQueue, PendingOperation};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PendingOperationsResult {
+    pub operations: Vec<PendingOperation>,
+    pub total: usize,
+}
+
+/// Network operations waiting for connectivity, with status and last error
+#[tauri::command]
+pub async fn get_pending_operations(queue: State<'_, OfflineQueue>) -> Result<PendingOperationsResult, String> {
+    let operations = queue.list();
+    Ok(PendingOperationsResult {
+        total: operations.len(),
+        operations,
+    })
+}
+
+/// Drop a queued operation. Operations currently being retried can't be cancelled.
+#[tauri::command]
+pub async fn cancel_pending_operation(queue: State<'_, OfflineQueue>, id: String) -> Result<(), String> {
+    if queue.cancel(&id) {
+        Ok(())
+    } else {
+        Err(format!("No cancellable operation '{}'", id))
+    }
+}
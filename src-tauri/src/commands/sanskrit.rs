@@ -1,5 +1,61 @@
 use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::io::{BufRead, BufReader};
 use std::process::{Command, Stdio};
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, State};
+use crate::actions::{ActionRegistry, AppAction};
+use crate::python::{self, PythonStrategy};
+use crate::settings::SettingsState;
+use crate::commands::vocabulary::VocabularyState;
+use crate::entry_actions::{self, EntryAction};
+use crate::capabilities;
+use crate::limits::{self, Truncation};
+use crate::analysis_cache::{self, CachedAnalysis};
+use crate::import_jobs;
+use crate::db::{self, DictionaryEntry, SearchOptions};
+use crate::sanskrit_cache;
+use crate::translit;
+use crate::sanskrit_worker::{SanskritWorkerStatus, WorkerError};
+use crate::AppState;
+
+/// Default time limits; every command takes a `timeout_ms` override
+const SPLIT_TIMEOUT: Duration = Duration::from_secs(10);
+const TRANSLITERATE_TIMEOUT: Duration = Duration::from_secs(10);
+const HEALTH_TIMEOUT: Duration = Duration::from_secs(10);
+const PROCESS_TIMEOUT: Duration = Duration::from_secs(30);
+const BATCH_TIMEOUT: Duration = Duration::from_secs(60);
+/// Texts longer than this (in characters) are processed as a background job
+const BACKGROUND_TEXT_CHARS: usize = 1000;
+/// Target size of the pieces a background job sends to the worker
+const JOB_CHUNK_CHARS: usize = 400;
+
+fn timeout_or(timeout_ms: Option<u64>, default: Duration) -> Duration {
+    timeout_ms.map(Duration::from_millis).unwrap_or(default)
+}
+
+/// Send one request to the Sanskrit worker. A response with `success: false`
+/// comes back as `WorkerError::Failed` with its message.
+async fn ask_worker(
+    state: &AppState,
+    request: serde_json::Value,
+    timeout: Duration,
+    cancel: Option<&AtomicBool>,
+) -> Result<serde_json::Value, WorkerError> {
+    let response = state.sanskrit_worker.request(request, timeout, cancel).await?;
+    if response.get("success").and_then(|v| v.as_bool()) == Some(false) {
+        return Err(WorkerError::Failed(
+            response
+                .get("error")
+                .and_then(|v| v.as_str())
+                .unwrap_or("Sanskrit worker reported a failure")
+                .to_string(),
+        ));
+    }
+    Ok(response)
+}
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SanskritSplitResult {
@@ -9,10 +65,32 @@ pub struct SanskritSplitResult {
     pub word: String,
     pub result: Option<serde_json::Value>,
     pub error: Option<String>,
+    /// What went wrong, see `WorkerError::kind`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error_kind: Option<String>,
+    /// Answered from the result cache without asking the worker
+    #[serde(default)]
+    pub cached: bool,
+    /// Library that produced the split ("vidyut_sandhi", "chedaka", "rules"…)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub backend: Option<String>,
+}
+
+fn split_backend(result: Option<&serde_json::Value>) -> Option<String> {
+    result?.get("source")?.as_str().map(str::to_string)
 }
 
 #[tauri::command]
-pub async fn sanskrit_split(word: String, mode: String) -> Result<SanskritSplitResult, String> {
+pub async fn sanskrit_split(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    settings: State<'_, SettingsState>,
+    word: String,
+    mode: String,
+    timeout_ms: Option<u64>,
+) -> Result<SanskritSplitResult, String> {
+    let current = settings.get();
+    limits::check_text("word", &word, current.limits.max_query_length)?;
     if word.trim().is_empty() {
         return Ok(SanskritSplitResult {
             success: false,
@@ -21,67 +99,98 @@ pub async fn sanskrit_split(word: String, mode: String) -> Result<SanskritSplitR
             word,
             result: None,
             error: Some("Empty word".to_string()),
+            error_kind: None,
+            cached: false,
+            backend: None,
+        });
+    }
+    let health = cached_health(&app, &state, false, HEALTH_TIMEOUT).await;
+    if let Err(e) = check_mode(&health, &mode) {
+        return Ok(SanskritSplitResult {
+            success: false,
+            action: "split".to_string(),
+            mode,
+            word,
+            result: None,
+            error: Some(e),
+            error_kind: Some("invalid_mode".to_string()),
+            cached: false,
+            backend: None,
         });
     }
 
-    let output = Command::new("python")
-        .args(&[
-            "scripts/sanskrit_cli.py",
-            "--action", "split",
-            "--word", &word,
-            "--mode", &mode,
-            "--json"
-        ])
-        .current_dir(std::env::current_exe().unwrap_or_default().parent().unwrap_or(std::path::Path::new(".")))
-        .stdout(Stdio::piped())
-        .stderr(Stdio::piped())
-        .output();
-
-    match output {
-        Ok(output) => {
-            if output.status.success() {
-                let stdout = String::from_utf8_lossy(&output.stdout);
-                match serde_json::from_str::<serde_json::Value>(&stdout) {
-                    Ok(result) => Ok(SanskritSplitResult {
-                        success: true,
-                        action: "split".to_string(),
-                        mode,
-                        word,
-                        result: Some(result),
-                        error: None,
-                    }),
-                    Err(e) => Ok(SanskritSplitResult {
-                        success: false,
-                        action: "split".to_string(),
-                        mode,
-                        word,
-                        result: None,
-                        error: Some(format!("Failed to parse result: {}", e)),
-                    }),
-                }
-            } else {
-                let stderr = String::from_utf8_lossy(&output.stderr);
-                Ok(SanskritSplitResult {
-                    success: false,
-                    action: "split".to_string(),
-                    mode,
-                    word,
-                    result: None,
-                    error: Some(stderr.to_string()),
-                })
-            }
-        }
+    match split_word(&state, &word, &mode, current.persist_sanskrit_results, timeout_or(timeout_ms, SPLIT_TIMEOUT)).await {
+        Ok((result, cached)) => Ok(SanskritSplitResult {
+            success: true,
+            action: "split".to_string(),
+            mode,
+            word,
+            backend: split_backend(result.as_ref()),
+            result,
+            error: None,
+            error_kind: None,
+            cached,
+        }),
         Err(e) => Ok(SanskritSplitResult {
             success: false,
             action: "split".to_string(),
             mode,
             word,
             result: None,
-            error: Some(format!("Failed to run Python: {}", e)),
-        })
+            error: Some(e.to_string()),
+            error_kind: Some(e.kind().to_string()),
+            cached: false,
+            backend: None,
+        }),
     }
 }
 
+/// The splitter's result for `word`, from the cache when possible; the flag
+/// says whether it was cached
+async fn split_word(
+    state: &AppState,
+    word: &str,
+    mode: &str,
+    persist: bool,
+    timeout: Duration,
+) -> Result<(Option<serde_json::Value>, bool), WorkerError> {
+    let cache_key = sanskrit_cache::key("split", &[mode, word]);
+    if let Some(result) = sanskrit_cache::get(&cache_key, persist) {
+        return Ok((Some(result), true));
+    }
+    let request = json!({ "action": "split", "word": word, "mode": mode });
+    let mut response = ask_worker(state, request, timeout, None).await?;
+    let result = response.get_mut("result").map(serde_json::Value::take);
+    if let Some(result) = &result {
+        sanskrit_cache::put(cache_key, result.clone(), persist);
+    }
+    Ok((result, false))
+}
+
+/// Dictionary forms for an inflected or sandhied `word`: the parts the
+/// splitter finds, or nothing when it is unavailable or leaves the word as is
+pub async fn lemmas_of(state: &AppState, word: &str, persist: bool) -> Vec<String> {
+    if !capabilities::current().sanskrit_tools {
+        return Vec::new();
+    }
+    let result = match split_word(state, word, "sandhi", persist, SPLIT_TIMEOUT).await {
+        Ok((Some(result), _)) => result,
+        Ok((None, _)) => return Vec::new(),
+        Err(e) => {
+            eprintln!("[SANSKRIT] Split for lookup failed: {}", e);
+            return Vec::new();
+        }
+    };
+    let mut lemmas: Vec<String> = Vec::new();
+    let parts = result.get("parts").and_then(|v| v.as_array()).into_iter().flatten();
+    for part in parts.filter_map(|p| p.as_str()).map(str::trim) {
+        if !part.is_empty() && part != word && !lemmas.iter().any(|l| l == part) {
+            lemmas.push(part.to_string());
+        }
+    }
+    lemmas
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct TransliterateResult {
     pub success: bool,
@@ -91,10 +200,34 @@ pub struct TransliterateResult {
     pub from_scheme: String,
     pub to_scheme: String,
     pub error: Option<String>,
+    /// What went wrong, see `WorkerError::kind`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error_kind: Option<String>,
+    /// Answered from the result cache without asking the worker
+    #[serde(default)]
+    pub cached: bool,
+    /// Converted in-process by `translit`; the worker is only asked for
+    /// schemes it doesn't know
+    #[serde(default)]
+    pub native: bool,
+}
+
+/// Both schemes, when `translit` handles them natively
+fn native_schemes(from_scheme: &str, to_scheme: &str) -> Option<(translit::Scheme, translit::Scheme)> {
+    Some((translit::Scheme::parse(from_scheme)?, translit::Scheme::parse(to_scheme)?))
 }
 
 #[tauri::command]
-pub async fn sanskrit_transliterate(text: String, from_scheme: String, to_scheme: String) -> Result<TransliterateResult, String> {
+pub async fn sanskrit_transliterate(
+    state: State<'_, AppState>,
+    settings: State<'_, SettingsState>,
+    text: String,
+    from_scheme: String,
+    to_scheme: String,
+    timeout_ms: Option<u64>,
+) -> Result<TransliterateResult, String> {
+    let current = settings.get();
+    limits::check_text("text", &text, current.limits.max_text_length)?;
     if text.trim().is_empty() {
         return Ok(TransliterateResult {
             success: false,
@@ -104,65 +237,68 @@ pub async fn sanskrit_transliterate(text: String, from_scheme: String, to_scheme
             from_scheme: from_scheme.clone(),
             to_scheme: to_scheme.clone(),
             error: Some("Empty text".to_string()),
+            error_kind: None,
+            cached: false,
+            native: false,
         });
     }
 
-    let output = Command::new("python")
-        .args(&[
-            "scripts/sanskrit_cli.py",
-            "--action", "transliterate",
-            "--text", &text,
-            "--from-scheme", &from_scheme,
-            "--to-scheme", &to_scheme,
-            "--json"
-        ])
-        .current_dir(std::env::current_exe().unwrap_or_default().parent().unwrap_or(std::path::Path::new(".")))
-        .stdout(Stdio::piped())
-        .stderr(Stdio::piped())
-        .output();
-
-    match output {
-        Ok(output) => {
-            if output.status.success() {
-                let stdout = String::from_utf8_lossy(&output.stdout);
-                match serde_json::from_str::<serde_json::Value>(&stdout) {
-                    Ok(result) => {
-                        let transliterated = result.get("transliterated")
-                            .and_then(|v| v.as_str())
-                            .map(|s| s.to_string());
-                        
-                        Ok(TransliterateResult {
-                            success: result.get("success").and_then(|v| v.as_bool()).unwrap_or(true),
-                            action: "transliterate".to_string(),
-                            original: text,
-                            transliterated,
-                            from_scheme,
-                            to_scheme,
-                            error: None,
-                        })
-                    }
-                    Err(e) => Ok(TransliterateResult {
-                        success: false,
-                        action: "transliterate".to_string(),
-                        original: text,
-                        transliterated: None,
-                        from_scheme,
-                        to_scheme,
-                        error: Some(format!("Failed to parse result: {}", e)),
-                    }),
-                }
-            } else {
-                let stderr = String::from_utf8_lossy(&output.stderr);
-                Ok(TransliterateResult {
-                    success: false,
-                    action: "transliterate".to_string(),
-                    original: text,
-                    transliterated: None,
-                    from_scheme,
-                    to_scheme,
-                    error: Some(stderr.to_string()),
-                })
+    if let Some((from, to)) = native_schemes(&from_scheme, &to_scheme) {
+        return Ok(TransliterateResult {
+            success: true,
+            action: "transliterate".to_string(),
+            transliterated: Some(translit::convert(&text, from, to)),
+            original: text,
+            from_scheme,
+            to_scheme,
+            error: None,
+            error_kind: None,
+            cached: false,
+            native: true,
+        });
+    }
+
+    let cache_key = sanskrit_cache::key("transliterate", &[&from_scheme, &to_scheme, &text]);
+    if let Some(cached) = sanskrit_cache::get(&cache_key, current.persist_sanskrit_results) {
+        return Ok(TransliterateResult {
+            success: true,
+            action: "transliterate".to_string(),
+            original: text,
+            transliterated: cached.as_str().map(|s| s.to_string()),
+            from_scheme,
+            to_scheme,
+            error: None,
+            error_kind: None,
+            cached: true,
+            native: false,
+        });
+    }
+
+    let request = json!({
+        "action": "transliterate",
+        "text": text,
+        "from_scheme": from_scheme,
+        "to_scheme": to_scheme,
+    });
+    let response = ask_worker(&state, request, timeout_or(timeout_ms, TRANSLITERATE_TIMEOUT), None).await;
+    match response {
+        Ok(response) => {
+            let transliterated = response.get("transliterated").and_then(|v| v.as_str()).map(|s| s.to_string());
+            if let Some(transliterated) = &transliterated {
+                sanskrit_cache::put(cache_key, json!(transliterated), current.persist_sanskrit_results);
             }
+            Ok(TransliterateResult {
+                success: true,
+                action: "transliterate".to_string(),
+                original: text,
+                transliterated,
+                from_scheme,
+                to_scheme,
+                error: None,
+                error_kind: None,
+                cached: false,
+                native: false,
+            })
         }
         Err(e) => Ok(TransliterateResult {
             success: false,
@@ -171,12 +307,15 @@ pub async fn sanskrit_transliterate(text: String, from_scheme: String, to_scheme
             transliterated: None,
             from_scheme,
             to_scheme,
-            error: Some(format!("Failed to run Python: {}", e)),
-        })
+            error: Some(e.to_string()),
+            error_kind: Some(e.kind().to_string()),
+            cached: false,
+            native: false,
+        }),
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SanskritHealthResult {
     pub success: bool,
     pub action: String,
@@ -184,232 +323,1288 @@ pub struct SanskritHealthResult {
     pub sandhi_splitter_available: bool,
     pub chedaka_available: bool,
     pub error: Option<String>,
+    /// What went wrong, see `WorkerError::kind`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error_kind: Option<String>,
+    /// When the probe behind this result ran (RFC 3339)
+    #[serde(default)]
+    pub checked_at: String,
+    /// How long that probe took
+    #[serde(default)]
+    pub probe_ms: u64,
+    /// Served from the cache rather than probed for this call
+    #[serde(default)]
+    pub cached: bool,
+    /// Split modes and the backends each would use; empty when the probe failed
+    #[serde(default)]
+    pub modes: Vec<SanskritMode>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SanskritMode {
+    pub id: String,
+    pub description: String,
+    /// False when none of the mode's backends is installed
+    pub available: bool,
+    /// In the order they are tried
+    #[serde(default)]
+    pub backends: Vec<String>,
+}
+
+impl SanskritHealthResult {
+    /// The flags the UI enables features by
+    fn availability(&self) -> (bool, bool, bool, bool) {
+        (self.success, self.vidyut_available, self.sandhi_splitter_available, self.chedaka_available)
+    }
 }
 
+/// A health result is reused this long before `sanskrit_health` probes again
+const HEALTH_TTL: Duration = Duration::from_secs(5 * 60);
+
+/// Last health probe, so polling doesn't keep the worker busy
+#[derive(Default)]
+pub struct HealthCache {
+    last: std::sync::Mutex<Option<(std::time::Instant, SanskritHealthResult)>>,
+}
+
+/// Ask the worker what is installed, cache the answer and emit
+/// `sanskrit-availability-changed` when the first probe completes or any
+/// availability flag differs from the previous probe
+pub async fn refresh_health(app: &AppHandle, timeout: Duration) -> SanskritHealthResult {
+    let state = app.state::<AppState>();
+    let started = std::time::Instant::now();
+    let answer = ask_worker(&state, json!({ "action": "health" }), timeout, None).await;
+    let flag = |result: &serde_json::Value, name: &str| result.get(name).and_then(|v| v.as_bool()).unwrap_or(false);
+    let mut health = SanskritHealthResult {
+        success: answer.is_ok(),
+        action: "health".to_string(),
+        vidyut_available: false,
+        sandhi_splitter_available: false,
+        chedaka_available: false,
+        error: None,
+        error_kind: None,
+        checked_at: chrono::Utc::now().to_rfc3339(),
+        probe_ms: started.elapsed().as_millis() as u64,
+        cached: false,
+        modes: Vec::new(),
+    };
+    match answer {
+        Ok(result) => {
+            health.vidyut_available = flag(&result, "vidyut_available");
+            health.sandhi_splitter_available = flag(&result, "sandhi_splitter_available");
+            health.chedaka_available = flag(&result, "chedaka_available");
+            health.modes = result
+                .get("modes")
+                .and_then(|v| serde_json::from_value(v.clone()).ok())
+                .unwrap_or_default();
+        }
+        Err(e) => {
+            health.error = Some(e.to_string());
+            health.error_kind = Some(e.kind().to_string());
+        }
+    }
+
+    let previous = state
+        .sanskrit_health
+        .last
+        .lock()
+        .unwrap()
+        .replace((std::time::Instant::now(), health.clone()));
+    if previous.map(|(_, p)| p.availability()) != Some(health.availability()) {
+        eprintln!(
+            "[SANSKRIT] Availability: tools {}, vidyut {}, splitter {}, chedaka {}",
+            health.success, health.vidyut_available, health.sandhi_splitter_available, health.chedaka_available
+        );
+        let _ = app.emit("sanskrit-availability-changed", &health);
+    }
+    health
+}
+
+/// What the Sanskrit tooling can do. Answers from a cache for up to five
+/// minutes unless `force` is set; listen to `sanskrit-availability-changed`
+/// instead of polling.
 #[tauri::command]
-pub async fn sanskrit_health() -> Result<SanskritHealthResult, String> {
-    let output = Command::new("python")
-        .args(&[
-            "scripts/sanskrit_cli.py",
-            "--action", "health",
-            "--json"
-        ])
-        .current_dir(std::env::current_exe().unwrap_or_default().parent().unwrap_or(std::path::Path::new(".")))
-        .stdout(Stdio::piped())
-        .stderr(Stdio::piped())
-        .output();
-
-    match output {
-        Ok(output) => {
-            if output.status.success() {
-                let stdout = String::from_utf8_lossy(&output.stdout);
-                match serde_json::from_str::<serde_json::Value>(&stdout) {
-                    Ok(result) => {
-                        Ok(SanskritHealthResult {
-                            success: result.get("success").and_then(|v| v.as_bool()).unwrap_or(true),
-                            action: "health".to_string(),
-                            vidyut_available: result.get("vidyut_available").and_then(|v| v.as_bool()).unwrap_or(false),
-                            sandhi_splitter_available: result.get("sandhi_splitter_available").and_then(|v| v.as_bool()).unwrap_or(false),
-                            chedaka_available: result.get("chedaka_available").and_then(|v| v.as_bool()).unwrap_or(false),
-                            error: None,
-                        })
-                    }
-                    Err(_) => Ok(SanskritHealthResult {
-                        success: false,
-                        action: "health".to_string(),
-                        vidyut_available: false,
-                        sandhi_splitter_available: false,
-                        chedaka_available: false,
-                        error: Some("Failed to parse health result".to_string()),
-                    }),
+pub async fn sanskrit_health(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    force: Option<bool>,
+    timeout_ms: Option<u64>,
+) -> Result<SanskritHealthResult, String> {
+    Ok(cached_health(&app, &state, force.unwrap_or(false), timeout_or(timeout_ms, HEALTH_TIMEOUT)).await)
+}
+
+/// The cached health result while it is fresh, otherwise a new probe
+async fn cached_health(app: &AppHandle, state: &AppState, force: bool, timeout: Duration) -> SanskritHealthResult {
+    if !force {
+        let cached = state.sanskrit_health.last.lock().unwrap().clone();
+        if let Some((_, mut health)) = cached.filter(|(at, _)| at.elapsed() < HEALTH_TTL) {
+            health.cached = true;
+            return health;
+        }
+    }
+    refresh_health(app, timeout).await
+}
+
+/// Reject a split mode the worker doesn't offer. Without a health answer
+/// there is nothing to check against, and the worker's own error surfaces.
+fn check_mode(health: &SanskritHealthResult, mode: &str) -> Result<(), String> {
+    if health.modes.is_empty() || health.modes.iter().any(|m| m.id == mode && m.available) {
+        return Ok(());
+    }
+    let available: Vec<&str> = health.modes.iter().filter(|m| m.available).map(|m| m.id.as_str()).collect();
+    let problem = if health.modes.iter().any(|m| m.id == mode) {
+        format!("Split mode '{}' needs a backend that isn't installed", mode)
+    } else {
+        format!("Unknown split mode '{}'", mode)
+    };
+    Err(format!("{}, available: {}", problem, available.join(", ")))
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SanskritModesResult {
+    pub success: bool,
+    pub modes: Vec<SanskritMode>,
+    pub error: Option<String>,
+    /// What went wrong, see `WorkerError::kind`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error_kind: Option<String>,
+    #[serde(default)]
+    pub cached: bool,
+}
+
+/// Split modes the installed packages support, for `sanskrit_split`'s
+/// `mode`. Shares the health check's cache.
+#[tauri::command]
+pub async fn get_sanskrit_modes(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    force: Option<bool>,
+    timeout_ms: Option<u64>,
+) -> Result<SanskritModesResult, String> {
+    let health = cached_health(&app, &state, force.unwrap_or(false), timeout_or(timeout_ms, HEALTH_TIMEOUT)).await;
+    Ok(SanskritModesResult {
+        success: health.success,
+        modes: health.modes,
+        error: health.error,
+        error_kind: health.error_kind,
+        cached: health.cached,
+    })
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ClearSanskritCacheResult {
+    pub success: bool,
+    pub memory_entries: usize,
+    pub disk_entries: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SanskritBatchResult<T> {
+    /// False when the worker couldn't be asked at all; each item still
+    /// carries its own error
+    pub success: bool,
+    pub action: String,
+    /// One per input, in input order
+    pub items: Vec<T>,
+    pub succeeded: usize,
+    pub failed: usize,
+    pub error: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error_kind: Option<String>,
+}
+
+/// Outcome of one batch item: the answer and whether it came from the cache
+type BatchItem = Result<(serde_json::Value, bool), WorkerError>;
+
+/// How a batch command talks to the worker
+struct BatchRequest<'a> {
+    action: &'a str,
+    /// Request field the inputs go in
+    list_field: &'a str,
+    /// The other request fields (mode, schemes)
+    params: serde_json::Value,
+    /// Per-item response field holding the answer
+    answer_field: &'a str,
+    max_item_length: usize,
+    persist: bool,
+    timeout: Duration,
+}
+
+/// Shared part of the batch commands. Items are answered from the cache
+/// where possible; the rest go to the worker in a single request and each
+/// successful answer is cached.
+async fn run_batch(
+    state: &AppState,
+    request: BatchRequest<'_>,
+    inputs: &[String],
+    cache_keys: Vec<String>,
+) -> (Vec<BatchItem>, Option<WorkerError>) {
+    let BatchRequest { action, list_field, mut params, answer_field, max_item_length, persist, timeout } = request;
+    let mut items: Vec<Option<BatchItem>> = Vec::with_capacity(inputs.len());
+    let mut misses = Vec::new();
+    for (i, input) in inputs.iter().enumerate() {
+        if input.trim().is_empty() {
+            items.push(Some(Err(WorkerError::Failed("Empty input".to_string()))));
+        } else if let Err(e) = limits::check_text("item", input, max_item_length) {
+            items.push(Some(Err(WorkerError::Failed(e.to_string()))));
+        } else if let Some(cached) = sanskrit_cache::get(&cache_keys[i], persist) {
+            items.push(Some(Ok((cached, true))));
+        } else {
+            items.push(None);
+            misses.push(i);
+        }
+    }
+
+    let mut failure = None;
+    if !misses.is_empty() {
+        params["action"] = json!(action);
+        params[list_field] = json!(misses.iter().map(|&i| &inputs[i]).collect::<Vec<_>>());
+        match ask_worker(state, params, timeout, None).await {
+            Ok(mut response) => {
+                let mut answers = match response.get_mut("results").map(serde_json::Value::take) {
+                    Some(serde_json::Value::Array(answers)) => answers.into_iter(),
+                    _ => Vec::new().into_iter(),
+                };
+                for &i in &misses {
+                    let item = match answers.next() {
+                        Some(answer) if answer.get("success").and_then(|v| v.as_bool()) == Some(true) => {
+                            let value = answer.get(answer_field).cloned().unwrap_or(serde_json::Value::Null);
+                            sanskrit_cache::put(cache_keys[i].clone(), value.clone(), persist);
+                            Ok((value, false))
+                        }
+                        Some(answer) => Err(WorkerError::Failed(
+                            answer.get("error").and_then(|v| v.as_str()).unwrap_or("Failed").to_string(),
+                        )),
+                        None => Err(WorkerError::Failed("No answer for this item".to_string())),
+                    };
+                    items[i] = Some(item);
                 }
-            } else {
-                Ok(SanskritHealthResult {
-                    success: false,
-                    action: "health".to_string(),
-                    vidyut_available: false,
-                    sandhi_splitter_available: false,
-                    chedaka_available: false,
-                    error: Some("Python script failed".to_string()),
-                })
+            }
+            Err(e) => {
+                for &i in &misses {
+                    items[i] = Some(Err(e.clone()));
+                }
+                failure = Some(e);
             }
         }
-        Err(e) => Ok(SanskritHealthResult {
-            success: false,
-            action: "health".to_string(),
-            vidyut_available: false,
-            sandhi_splitter_available: false,
-            chedaka_available: false,
-            error: Some(format!("Failed to run Python: {}", e)),
+    }
+    (items.into_iter().map(|item| item.expect("every item answered")).collect(), failure)
+}
+
+fn batch_result<T>(
+    action: &str,
+    items: Vec<T>,
+    succeeded: usize,
+    failure: Option<WorkerError>,
+) -> SanskritBatchResult<T> {
+    SanskritBatchResult {
+        success: failure.is_none(),
+        action: action.to_string(),
+        failed: items.len() - succeeded,
+        items,
+        succeeded,
+        error: failure.as_ref().map(|e| e.to_string()),
+        error_kind: failure.as_ref().map(|e| e.kind().to_string()),
+    }
+}
+
+/// Split every word in one worker request. Order is kept and a bad word
+/// only fails its own item.
+#[tauri::command]
+pub async fn sanskrit_split_batch(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    settings: State<'_, SettingsState>,
+    words: Vec<String>,
+    mode: String,
+    timeout_ms: Option<u64>,
+) -> Result<SanskritBatchResult<SanskritSplitResult>, String> {
+    let current = settings.get();
+    limits::check("batch", words.len(), current.limits.max_sanskrit_batch_size)?;
+    check_mode(&cached_health(&app, &state, false, HEALTH_TIMEOUT).await, &mode)?;
+
+    let keys = words.iter().map(|word| sanskrit_cache::key("split", &[&mode, word])).collect();
+    let request = BatchRequest {
+        action: "split_batch",
+        list_field: "words",
+        params: json!({ "mode": mode }),
+        answer_field: "result",
+        max_item_length: current.limits.max_query_length,
+        persist: current.persist_sanskrit_results,
+        timeout: timeout_or(timeout_ms, BATCH_TIMEOUT),
+    };
+    let (answers, failure) = run_batch(&state, request, &words, keys).await;
+
+    let succeeded = answers.iter().filter(|a| a.is_ok()).count();
+    let items = words
+        .into_iter()
+        .zip(answers)
+        .map(|(word, answer)| match answer {
+            Ok((result, cached)) => SanskritSplitResult {
+                success: true,
+                action: "split".to_string(),
+                mode: mode.clone(),
+                word,
+                backend: split_backend(Some(&result)),
+                result: Some(result),
+                error: None,
+                error_kind: None,
+                cached,
+            },
+            Err(e) => SanskritSplitResult {
+                success: false,
+                action: "split".to_string(),
+                mode: mode.clone(),
+                word,
+                result: None,
+                error: Some(e.to_string()),
+                error_kind: Some(e.kind().to_string()),
+                cached: false,
+                backend: None,
+            },
         })
+        .collect();
+    Ok(batch_result("split_batch", items, succeeded, failure))
+}
+
+/// Transliterate every text in one worker request. Order is kept and a bad
+/// text only fails its own item.
+#[tauri::command]
+pub async fn sanskrit_transliterate_batch(
+    state: State<'_, AppState>,
+    settings: State<'_, SettingsState>,
+    texts: Vec<String>,
+    from_scheme: String,
+    to_scheme: String,
+    timeout_ms: Option<u64>,
+) -> Result<SanskritBatchResult<TransliterateResult>, String> {
+    let current = settings.get();
+    limits::check("batch", texts.len(), current.limits.max_sanskrit_batch_size)?;
+
+    if let Some((from, to)) = native_schemes(&from_scheme, &to_scheme) {
+        let items: Vec<TransliterateResult> = texts
+            .into_iter()
+            .map(|text| {
+                let checked = limits::check_text("item", &text, current.limits.max_text_length);
+                TransliterateResult {
+                    success: checked.is_ok(),
+                    action: "transliterate".to_string(),
+                    transliterated: checked.is_ok().then(|| translit::convert(&text, from, to)),
+                    original: text,
+                    from_scheme: from_scheme.clone(),
+                    to_scheme: to_scheme.clone(),
+                    error: checked.as_ref().err().map(|e| e.to_string()),
+                    error_kind: checked.as_ref().err().map(|_| "failed".to_string()),
+                    cached: false,
+                    native: true,
+                }
+            })
+            .collect();
+        let succeeded = items.iter().filter(|item| item.success).count();
+        return Ok(batch_result("transliterate_batch", items, succeeded, None));
     }
+
+    let keys = texts
+        .iter()
+        .map(|text| sanskrit_cache::key("transliterate", &[&from_scheme, &to_scheme, text]))
+        .collect();
+    let request = BatchRequest {
+        action: "transliterate_batch",
+        list_field: "texts",
+        params: json!({ "from_scheme": from_scheme, "to_scheme": to_scheme }),
+        answer_field: "transliterated",
+        max_item_length: current.limits.max_text_length,
+        persist: current.persist_sanskrit_results,
+        timeout: timeout_or(timeout_ms, BATCH_TIMEOUT),
+    };
+    let (answers, failure) = run_batch(&state, request, &texts, keys).await;
+
+    let succeeded = answers.iter().filter(|a| a.is_ok()).count();
+    let items = texts
+        .into_iter()
+        .zip(answers)
+        .map(|(text, answer)| match answer {
+            Ok((transliterated, cached)) => TransliterateResult {
+                success: true,
+                action: "transliterate".to_string(),
+                original: text,
+                transliterated: transliterated.as_str().map(|s| s.to_string()),
+                from_scheme: from_scheme.clone(),
+                to_scheme: to_scheme.clone(),
+                error: None,
+                error_kind: None,
+                cached,
+                native: false,
+            },
+            Err(e) => TransliterateResult {
+                success: false,
+                action: "transliterate".to_string(),
+                original: text,
+                transliterated: None,
+                from_scheme: from_scheme.clone(),
+                to_scheme: to_scheme.clone(),
+                error: Some(e.to_string()),
+                error_kind: Some(e.kind().to_string()),
+                cached: false,
+                native: false,
+            },
+        })
+        .collect();
+    Ok(batch_result("transliterate_batch", items, succeeded, failure))
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TransliterationScheme {
+    pub id: String,
+    pub name: String,
+    /// Converted in-process; otherwise the Python worker is needed
+    pub native: bool,
+}
+
+/// Schemes only the Python side (vidyut) knows
+const PYTHON_SCHEMES: &[(&str, &str)] = &[("itrans", "ITRANS")];
+
+/// Every scheme `sanskrit_transliterate` accepts. Conversions between
+/// native schemes work without Python.
+#[tauri::command]
+pub async fn list_transliteration_schemes() -> Result<Vec<TransliterationScheme>, String> {
+    let native = translit::Scheme::ALL.iter().map(|scheme| TransliterationScheme {
+        id: scheme.as_str().to_string(),
+        name: scheme.display_name().to_string(),
+        native: true,
+    });
+    let python = PYTHON_SCHEMES.iter().map(|(id, name)| TransliterationScheme {
+        id: id.to_string(),
+        name: name.to_string(),
+        native: false,
+    });
+    Ok(native.chain(python).collect())
+}
+
+/// Forget cached split and transliteration results, in memory and on disk
+#[tauri::command]
+pub async fn clear_sanskrit_cache() -> Result<ClearSanskritCacheResult, String> {
+    let (memory_entries, disk_entries) = sanskrit_cache::clear()?;
+    eprintln!("[SANSKRIT] Cache cleared: {} in memory, {} on disk", memory_entries, disk_entries);
+    Ok(ClearSanskritCacheResult {
+        success: true,
+        memory_entries,
+        disk_entries,
+    })
+}
+
+/// Whether the Sanskrit worker process is up and how much it has done
+#[tauri::command]
+pub async fn sanskrit_worker_status(state: State<'_, AppState>) -> Result<SanskritWorkerStatus, String> {
+    Ok(state.sanskrit_worker.status())
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct PythonEnvironmentCheck {
     pub available: bool,
     pub version: Option<String>,
+    /// Launch strategy in use: "uv", "venv", "system" or "custom"
+    pub strategy: Option<String>,
+    /// The command line scripts are started with, e.g. "python3"
+    pub interpreter: Option<String>,
     pub vidyut_available: bool,
     pub sandhi_splitter_available: bool,
     pub chedaka_available: bool,
 }
 
+fn can_import(launcher: &python::PythonLauncher, module: &str) -> bool {
+    launcher
+        .python()
+        .args(["-c", &format!("import {}", module)])
+        .output()
+        .map(|o| o.status.success())
+        .unwrap_or(false)
+}
+
 #[tauri::command]
 pub async fn check_python_environment() -> Result<PythonEnvironmentCheck, String> {
-    let python_check = Command::new("python")
-        .arg("--version")
-        .output();
+    let launcher = match python::resolve() {
+        Ok(launcher) => launcher,
+        Err(_) => {
+            return Ok(PythonEnvironmentCheck {
+                available: false,
+                version: None,
+                strategy: None,
+                interpreter: None,
+                vidyut_available: false,
+                sandhi_splitter_available: false,
+                chedaka_available: false,
+            })
+        }
+    };
 
-    let version = match &python_check {
-        Ok(output) => {
-            if output.status.success() {
-                Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    let version = launcher
+        .python()
+        .arg("--version")
+        .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .map(|o| {
+            // Older interpreters print the version on stderr
+            let stdout = String::from_utf8_lossy(&o.stdout).trim().to_string();
+            if stdout.is_empty() {
+                String::from_utf8_lossy(&o.stderr).trim().to_string()
             } else {
-                None
+                stdout
+            }
+        });
+    let available = version.is_some();
+
+    Ok(PythonEnvironmentCheck {
+        available,
+        version,
+        strategy: Some(launcher.strategy.as_str().to_string()),
+        interpreter: Some(launcher.describe()),
+        vidyut_available: available && can_import(&launcher, "vidyut"),
+        // Both ship inside vidyut; these are the modules sandhi_api.py loads
+        sandhi_splitter_available: available && can_import(&launcher, "vidyut.sandhi"),
+        chedaka_available: available && can_import(&launcher, "vidyut.cheda"),
+    })
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PythonStrategyResult {
+    pub success: bool,
+    pub strategy: Option<String>,
+    pub command: Option<String>,
+    pub error: Option<String>,
+}
+
+fn strategy_result(resolved: Result<std::sync::Arc<python::PythonLauncher>, String>) -> PythonStrategyResult {
+    match resolved {
+        Ok(launcher) => PythonStrategyResult {
+            success: true,
+            strategy: Some(launcher.strategy.as_str().to_string()),
+            command: Some(launcher.describe()),
+            error: None,
+        },
+        Err(e) => PythonStrategyResult {
+            success: false,
+            strategy: None,
+            command: None,
+            error: Some(e),
+        },
+    }
+}
+
+/// Probe the Python launch strategies again, e.g. after installing uv
+#[tauri::command]
+pub async fn reprobe_python(state: State<'_, AppState>) -> Result<PythonStrategyResult, String> {
+    let result = strategy_result(python::reprobe());
+    state.sanskrit_worker.restart("Python was probed again");
+    Ok(result)
+}
+
+/// Prefer `strategy` ("uv", "venv", "system", "custom") over the default probe order;
+/// None clears the preference. Persisted in settings.
+#[tauri::command]
+pub async fn set_python_strategy(
+    state: State<'_, AppState>,
+    settings: State<'_, SettingsState>,
+    strategy: Option<String>,
+) -> Result<PythonStrategyResult, String> {
+    let parsed = strategy.as_deref().map(PythonStrategy::parse).transpose()?;
+    settings.update(|s| s.python_strategy = parsed.map(|p| p.as_str().to_string()))?;
+    python::set_override(parsed);
+    let result = strategy_result(python::reprobe());
+    state.sanskrit_worker.restart("Python strategy changed");
+    Ok(result)
+}
+
+/// Use the interpreter at `path` instead of probing; None goes back to
+/// probing. Persisted in settings. An interpreter that doesn't run is
+/// reported and skipped, so the result shows what is actually used.
+#[tauri::command]
+pub async fn set_python_path(
+    state: State<'_, AppState>,
+    settings: State<'_, SettingsState>,
+    path: Option<String>,
+) -> Result<PythonStrategyResult, String> {
+    let path = path.map(|p| p.trim().to_string()).filter(|p| !p.is_empty());
+    settings.update(|s| s.python_path = path.clone())?;
+    python::set_custom_path(path.map(std::path::PathBuf::from));
+    let result = strategy_result(python::reprobe());
+    state.sanskrit_worker.restart("Python interpreter changed");
+    Ok(result)
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct InstallDependenciesResult {
+    pub success: bool,
+    pub strategy: Option<String>,
+    pub command: String,
+    pub exit_code: Option<i32>,
+    pub error: Option<String>,
+}
+
+/// Install the scripts' dependencies with whatever the resolved strategy
+/// uses: `uv sync` for uv projects, pip from requirements.txt otherwise.
+/// Output lines are streamed to the service log as they arrive.
+#[tauri::command]
+pub async fn install_python_dependencies() -> Result<InstallDependenciesResult, String> {
+    let launcher = python::resolve()?;
+    let scripts_dir = launcher.scripts_dir();
+
+    let mut cmd = match launcher.strategy {
+        PythonStrategy::Uv => {
+            let mut cmd = Command::new("uv");
+            cmd.arg("sync").arg("--project").arg(&scripts_dir);
+            cmd
+        }
+        PythonStrategy::Venv | PythonStrategy::System | PythonStrategy::Custom => {
+            let requirements = scripts_dir.join("requirements.txt");
+            if !requirements.exists() {
+                return Ok(InstallDependenciesResult {
+                    success: false,
+                    strategy: Some(launcher.strategy.as_str().to_string()),
+                    command: String::new(),
+                    exit_code: None,
+                    error: Some(format!("No requirements.txt in {}", scripts_dir.display())),
+                });
             }
+            let mut cmd = launcher.python();
+            cmd.args(["-m", "pip", "install", "-r"]).arg(&requirements);
+            cmd
         }
-        Err(_) => None,
     };
+    cmd.current_dir(&scripts_dir);
+    let description = format!("{:?}", cmd);
+    crate::write_service_log(&format!("[deps] Running {}", description));
+
+    let mut child = match cmd.stdout(Stdio::piped()).stderr(Stdio::piped()).spawn() {
+        Ok(child) => child,
+        Err(e) => {
+            return Ok(InstallDependenciesResult {
+                success: false,
+                strategy: Some(launcher.strategy.as_str().to_string()),
+                command: description,
+                exit_code: None,
+                error: Some(format!("Failed to start installer: {}", e)),
+            })
+        }
+    };
+
+    // pip writes progress to stdout and warnings to stderr; drain both
+    let stderr = child.stderr.take();
+    let stderr_thread = std::thread::spawn(move || {
+        if let Some(stderr) = stderr {
+            for line in BufReader::new(stderr).lines().map_while(Result::ok) {
+                crate::write_service_log(&format!("[deps err] {}", line));
+            }
+        }
+    });
+    if let Some(stdout) = child.stdout.take() {
+        for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+            crate::write_service_log(&format!("[deps] {}", line));
+        }
+    }
+    let _ = stderr_thread.join();
 
-    let available = python_check.is_ok() && version.is_some();
-
-    let mut vidyut_available = false;
-    let mut sandhi_splitter_available = false;
-    let mut chedaka_available = false;
-
-    if available {
-        let packages_check = Command::new("python")
-            .args(&["-c", "import vidyut; import sandhi_splitter; import chedaka; print('ok')"])
-            .output();
-
-        if let Ok(output) = packages_check {
-            let stdout = String::from_utf8_lossy(&output.stdout);
-            vidyut_available = stdout.contains("ok") || Command::new("python")
-                .args(&["-c", "import vidyut"])
-                .output()
-                .map(|o| o.status.success())
-                .unwrap_or(false);
-
-            sandhi_splitter_available = Command::new("python")
-                .args(&["-c", "import sandhi_splitter"])
-                .output()
-                .map(|o| o.status.success())
-                .unwrap_or(false);
-
-            chedaka_available = Command::new("python")
-                .args(&["-c", "import chedaka"])
-                .output()
-                .map(|o| o.status.success())
-                .unwrap_or(false);
+    let status = child.wait().map_err(|e| e.to_string())?;
+    crate::write_service_log(&format!("[deps] Finished with {}", status));
+
+    // A fresh .venv or uv environment may change which strategy wins
+    let strategy = python::reprobe().ok().map(|l| l.strategy.as_str().to_string());
+
+    Ok(InstallDependenciesResult {
+        success: status.success(),
+        strategy,
+        command: description,
+        exit_code: status.code(),
+        error: if status.success() { None } else { Some(format!("Installer exited with {}", status)) },
+    })
+}
+
+/// What `install_sanskrit_dependencies` installs, matching requirements.txt
+/// plus the parser behind the "morpheme" split mode
+const SANSKRIT_PACKAGES: &[&str] = &["vidyut<=0.4.0", "sanskrit_parser", "dharmamitra-sanskrit-grammar>=0.1.0"];
+
+/// Installer output that means the package index couldn't be reached
+const NETWORK_ERRORS: &[&str] = &[
+    "Could not resolve host",
+    "Temporary failure in name resolution",
+    "Failed to establish a new connection",
+    "Network is unreachable",
+    "No matching distribution found",
+    "error sending request",
+    "dns error",
+];
+/// Installer output that means the target environment isn't writable
+const PERMISSION_ERRORS: &[&str] = &["Permission denied", "[Errno 13]", "Access is denied"];
+
+#[derive(Debug)]
+pub enum InstallError {
+    /// Another install is still running
+    AlreadyRunning,
+    PythonNotFound(String),
+    /// The package index couldn't be reached
+    Network(String),
+    /// The interpreter's site-packages aren't writable
+    Permission(String),
+    Cancelled,
+    Failed(String),
+}
+
+impl InstallError {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            InstallError::AlreadyRunning => "already_running",
+            InstallError::PythonNotFound(_) => "python_not_found",
+            InstallError::Network(_) => "network",
+            InstallError::Permission(_) => "permission",
+            InstallError::Cancelled => "cancelled",
+            InstallError::Failed(_) => "failed",
         }
     }
+}
 
-    Ok(PythonEnvironmentCheck {
-        available,
-        version,
-        vidyut_available,
-        sandhi_splitter_available,
-        chedaka_available,
+impl std::fmt::Display for InstallError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InstallError::AlreadyRunning => write!(f, "An install is already running"),
+            InstallError::PythonNotFound(e) => write!(f, "Python not found: {}", e),
+            InstallError::Network(line) => write!(f, "Package index unreachable: {}", line),
+            InstallError::Permission(line) => write!(f, "No permission to install: {}", line),
+            InstallError::Cancelled => write!(f, "Install cancelled"),
+            InstallError::Failed(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PythonInstallProgressEvent {
+    /// "stdout" or "stderr"
+    pub stream: String,
+    pub line: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct InstallSanskritDependenciesResult {
+    pub success: bool,
+    pub command: Option<String>,
+    pub exit_code: Option<i32>,
+    pub error: Option<String>,
+    /// What went wrong, see `InstallError::kind`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error_kind: Option<String>,
+    /// The environment check after the install, whether or not it succeeded
+    pub environment: Option<PythonEnvironmentCheck>,
+}
+
+/// Install vidyut and the other Sanskrit packages into the interpreter the
+/// scripts run with: `uv pip install` when uv is on PATH, otherwise pip
+/// (with `--user` outside a virtualenv). Output is streamed as
+/// `python-install-progress` events; `cancel_sanskrit_install` stops it.
+#[tauri::command]
+pub async fn install_sanskrit_dependencies(
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<InstallSanskritDependenciesResult, String> {
+    let cancel = {
+        let mut running = state.python_install.lock().unwrap();
+        if running.is_some() {
+            return Ok(install_failed(None, &InstallError::AlreadyRunning, None));
+        }
+        running.insert(Arc::new(AtomicBool::new(false))).clone()
+    };
+    let installer = app.clone();
+    let outcome = tauri::async_runtime::spawn_blocking(move || run_install(&installer, &cancel))
+        .await
+        .unwrap_or_else(|e| Err((None, InstallError::Failed(e.to_string()))));
+    *state.python_install.lock().unwrap() = None;
+
+    if outcome.is_ok() {
+        // The worker imported the old packages; its next health answer
+        // tells listeners what became available
+        let _ = python::reprobe();
+        state.sanskrit_worker.restart("Sanskrit packages installed");
+        tauri::async_runtime::spawn(async move {
+            refresh_health(&app, PROCESS_TIMEOUT).await;
+        });
+    }
+    let environment = check_python_environment().await.ok();
+    Ok(match outcome {
+        Ok((command, exit_code)) => InstallSanskritDependenciesResult {
+            success: true,
+            command: Some(command),
+            exit_code,
+            error: None,
+            error_kind: None,
+            environment,
+        },
+        Err((command, e)) => install_failed(command, &e, environment),
     })
 }
 
+fn install_failed(
+    command: Option<String>,
+    error: &InstallError,
+    environment: Option<PythonEnvironmentCheck>,
+) -> InstallSanskritDependenciesResult {
+    InstallSanskritDependenciesResult {
+        success: false,
+        command,
+        exit_code: None,
+        error: Some(error.to_string()),
+        error_kind: Some(error.kind().to_string()),
+        environment,
+    }
+}
+
+/// Stop a running `install_sanskrit_dependencies`; false when none is running
+#[tauri::command]
+pub async fn cancel_sanskrit_install(state: State<'_, AppState>) -> Result<bool, String> {
+    let running = state.python_install.lock().unwrap();
+    if let Some(cancel) = running.as_ref() {
+        cancel.store(true, std::sync::atomic::Ordering::Relaxed);
+    }
+    Ok(running.is_some())
+}
+
+type InstallOutcome = Result<(String, Option<i32>), (Option<String>, InstallError)>;
+
+/// The blocking part of the install: build the command, stream its output
+/// and classify a failure by the lines it printed
+fn run_install(app: &AppHandle, cancel: &AtomicBool) -> InstallOutcome {
+    let launcher = python::resolve().map_err(|e| (None, InstallError::PythonNotFound(e)))?;
+    // The interpreter behind the launcher (uv and venv strategies wrap it)
+    let probe = launcher
+        .python()
+        .args(["-c", "import sys; print(sys.executable); print(sys.prefix != sys.base_prefix)"])
+        .output()
+        .map_err(|e| (None, InstallError::PythonNotFound(e.to_string())))?;
+    let probe = String::from_utf8_lossy(&probe.stdout).to_string();
+    let mut lines = probe.lines();
+    let executable = lines.next().unwrap_or_default().trim().to_string();
+    let in_venv = lines.next().map(str::trim) == Some("True");
+    if executable.is_empty() {
+        return Err((None, InstallError::PythonNotFound(launcher.describe())));
+    }
+
+    let uv_available = Command::new("uv").arg("--version").output().is_ok_and(|o| o.status.success());
+    let mut cmd = if uv_available {
+        let mut cmd = Command::new("uv");
+        cmd.args(["pip", "install", "--python", &executable]);
+        cmd
+    } else {
+        let mut cmd = Command::new(&executable);
+        cmd.args(["-m", "pip", "install"]);
+        if !in_venv {
+            cmd.arg("--user");
+        }
+        cmd
+    };
+    cmd.args(SANSKRIT_PACKAGES);
+    let description = format!("{:?}", cmd);
+    crate::write_service_log(&format!("[deps] Running {}", description));
+
+    let mut child = cmd
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| (Some(description.clone()), InstallError::Failed(format!("Failed to start installer: {}", e))))?;
+
+    let (sender, receiver) = std::sync::mpsc::channel::<(&'static str, String)>();
+    let readers: Vec<_> = [
+        child.stdout.take().map(|out| ("stdout", Box::new(out) as Box<dyn std::io::Read + Send>)),
+        child.stderr.take().map(|err| ("stderr", Box::new(err) as Box<dyn std::io::Read + Send>)),
+    ]
+    .into_iter()
+    .flatten()
+    .map(|(stream, reader)| {
+        let sender = sender.clone();
+        std::thread::spawn(move || {
+            for line in BufReader::new(reader).lines().map_while(Result::ok) {
+                let _ = sender.send((stream, line));
+            }
+        })
+    })
+    .collect();
+    drop(sender);
+
+    let mut network = None;
+    let mut permission = None;
+    let mut cancelled = false;
+    loop {
+        match receiver.recv_timeout(Duration::from_millis(100)) {
+            Ok((stream, line)) => {
+                crate::write_service_log(&format!("[deps] {}", line));
+                if network.is_none() && NETWORK_ERRORS.iter().any(|p| line.contains(p)) {
+                    network = Some(line.clone());
+                }
+                if permission.is_none() && PERMISSION_ERRORS.iter().any(|p| line.contains(p)) {
+                    permission = Some(line.clone());
+                }
+                let _ = app.emit("python-install-progress", PythonInstallProgressEvent {
+                    stream: stream.to_string(),
+                    line,
+                });
+            }
+            Err(std::sync::mpsc::RecvTimeoutError::Timeout) => {}
+            Err(std::sync::mpsc::RecvTimeoutError::Disconnected) => break,
+        }
+        if !cancelled && cancel.load(std::sync::atomic::Ordering::Relaxed) {
+            cancelled = true;
+            let _ = child.kill();
+        }
+    }
+    for reader in readers {
+        let _ = reader.join();
+    }
+
+    let status = child.wait().map_err(|e| (Some(description.clone()), InstallError::Failed(e.to_string())))?;
+    crate::write_service_log(&format!("[deps] Finished with {}", status));
+    if cancelled {
+        return Err((Some(description), InstallError::Cancelled));
+    }
+    if status.success() {
+        return Ok((description, status.code()));
+    }
+    let error = match (network, permission) {
+        (Some(line), _) => InstallError::Network(line),
+        (None, Some(line)) => InstallError::Permission(line),
+        (None, None) => InstallError::Failed(format!("Installer exited with {}", status)),
+    };
+    Err((Some(description), error))
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Segment {
     pub original: String,
     pub split: Option<Vec<String>>,
     pub lemma: Option<String>,
     pub morphology: Option<serde_json::Value>,
+    /// Quick actions, computed after the Python output is parsed
+    #[serde(default)]
+    pub actions: Vec<EntryAction>,
+    /// Local dictionary entries for the lemma, then the original form;
+    /// None when the language has no local dictionary
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dictionary: Option<Vec<DictionaryEntry>>,
+    /// Status of the saved term for this word (new/learning/mastered)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub term_status: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub term_id: Option<String>,
+    /// Character offset of `original` in the processed text; None when the
+    /// analyzer's form doesn't occur there verbatim (resolved sandhi)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub offset: Option<usize>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProcessResult {
     pub success: bool,
     pub text: String,
     pub segments: Vec<Segment>,
     pub analysis: Option<serde_json::Value>,
     pub error: Option<String>,
+    /// What went wrong, see `WorkerError::kind`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error_kind: Option<String>,
+    /// Id for `export_analysis`; set on success
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub analysis_id: Option<String>,
+    /// Set when `truncate` cut the text to fit the length limit
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub truncation: Option<Truncation>,
+    /// Set when the text went to a background job; the analysis arrives
+    /// with `sanskrit-process-done`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub job_id: Option<String>,
 }
 
-#[tauri::command]
-pub async fn process_text(text: String) -> Result<ProcessResult, String> {
-    if text.trim().is_empty() {
-        return Ok(ProcessResult {
+impl ProcessResult {
+    fn failed(text: String, error: &WorkerError, truncation: Option<Truncation>) -> Self {
+        ProcessResult {
             success: false,
             text,
             segments: vec![],
             analysis: None,
-            error: Some("Empty text".to_string()),
+            error: Some(error.to_string()),
+            error_kind: Some(error.kind().to_string()),
+            analysis_id: None,
+            truncation,
+            job_id: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SanskritProcessProgressEvent {
+    pub job_id: String,
+    pub chunk_index: usize,
+    pub total_chunks: usize,
+    /// The finished chunk's segments, annotated like the final result's
+    pub segments: Vec<Segment>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SanskritProcessDoneEvent {
+    pub job_id: String,
+    pub result: ProcessResult,
+}
+
+/// Optional arguments of `process_text`, passed as one `options` object
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct ProcessOptions {
+    /// Dictionary and vocabulary the segments are matched against; sa when absent
+    pub language: Option<String>,
+    /// Shorten over-long text instead of rejecting it
+    pub truncate: Option<bool>,
+    pub timeout_ms: Option<u64>,
+}
+
+/// Analyse `text`. Texts over `BACKGROUND_TEXT_CHARS` return a `job_id`
+/// straight away and are processed sentence-aligned chunk by chunk, with
+/// `sanskrit-process-progress` after each chunk and `sanskrit-process-done`
+/// carrying the merged result; `cancel_sanskrit_process` stops them.
+/// `timeout_ms` limits the whole call, or each chunk of a background job.
+#[tauri::command]
+pub async fn process_text(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    vocabulary: State<'_, VocabularyState>,
+    settings: State<'_, SettingsState>,
+    text: String,
+    options: Option<ProcessOptions>,
+) -> Result<ProcessResult, String> {
+    let ProcessOptions { language, truncate, timeout_ms } = options.unwrap_or_default();
+    let language = language.unwrap_or_else(|| "sa".to_string());
+    // Over-long text is an error unless the caller accepts a shortened analysis
+    let max_length = settings.get().limits.max_text_length;
+    let (text, truncation) = limits::fit_text(text, max_length, truncate.unwrap_or(false))?;
+
+    if text.trim().is_empty() {
+        return Ok(ProcessResult::failed(text, &WorkerError::Failed("Empty text".to_string()), truncation));
+    }
+
+    let timeout = timeout_or(timeout_ms, PROCESS_TIMEOUT);
+    if text.chars().count() <= BACKGROUND_TEXT_CHARS {
+        return Ok(match ask_worker(&state, json!({ "action": "process", "text": text }), timeout, None).await {
+            Ok(result) => {
+                let mut segments = parse_segments(&result, &text, 0);
+                annotate_segments(&vocabulary, &language, &mut segments);
+                analysed(text, segments, vec![result], truncation)
+            }
+            Err(e) => ProcessResult::failed(text, &e, truncation),
         });
     }
 
-    let script_path = std::path::PathBuf::from("scripts/enhanced_sanskrit_api.py");
-    
-    if !script_path.exists() {
-        return Err("Enhanced Sanskrit API script not found".to_string());
+    let (job_id, cancel) = import_jobs::start("sanskrit");
+    let job = job_id.clone();
+    let queued_text = text.clone();
+    let queued_truncation = truncation.clone();
+    tauri::async_runtime::spawn(async move {
+        let state = app.state::<AppState>();
+        let vocabulary = app.state::<VocabularyState>();
+        let chunks = text_chunks(&text, JOB_CHUNK_CHARS);
+        let mut results = Vec::with_capacity(chunks.len());
+        let mut segments = Vec::new();
+        let mut failure = None;
+        for (chunk_index, (start, chunk)) in chunks.iter().enumerate() {
+            let request = json!({ "action": "process", "text": chunk });
+            let result = match ask_worker(&state, request, timeout, Some(&cancel)).await {
+                Ok(result) => result,
+                Err(e) => {
+                    failure = Some(e);
+                    break;
+                }
+            };
+            let mut found = parse_segments(&result, chunk, *start);
+            annotate_segments(&vocabulary, &language, &mut found);
+            let _ = app.emit("sanskrit-process-progress", SanskritProcessProgressEvent {
+                job_id: job.clone(),
+                chunk_index,
+                total_chunks: chunks.len(),
+                segments: found.clone(),
+            });
+            segments.extend(found);
+            results.push(result);
+        }
+        import_jobs::finish(&job);
+
+        let result = match failure {
+            Some(e) => {
+                eprintln!("[SANSKRIT] {} failed: {}", job, e);
+                ProcessResult::failed(text, &e, truncation)
+            }
+            None => analysed(text, segments, results, truncation),
+        };
+        let _ = app.emit("sanskrit-process-done", SanskritProcessDoneEvent { job_id: job, result });
+    });
+
+    Ok(ProcessResult {
+        success: true,
+        text: queued_text,
+        segments: vec![],
+        analysis: None,
+        error: None,
+        error_kind: None,
+        analysis_id: None,
+        truncation: queued_truncation,
+        job_id: Some(job_id),
+    })
+}
+
+/// Stop a background `process_text` job, killing the worker if it is busy
+/// with it; false when no such job is running
+#[tauri::command]
+pub async fn cancel_sanskrit_process(job_id: String) -> Result<bool, String> {
+    Ok(job_id.starts_with("sanskrit-") && import_jobs::cancel(&job_id))
+}
+
+/// The segments of the worker's answer for `chunk`, which starts `start`
+/// characters into the text. Offsets are found by searching forward from
+/// the previous segment, so repeated words map to successive occurrences.
+fn parse_segments(result: &serde_json::Value, chunk: &str, start: usize) -> Vec<Segment> {
+    let mut cursor = 0;
+    result
+        .get("segments")
+        .and_then(|v| v.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|item| serde_json::from_value::<Segment>(item.clone()).ok())
+        .map(|mut segment| {
+            let found = chunk[cursor..].find(segment.original.as_str()).filter(|_| !segment.original.is_empty());
+            if let Some(found) = found {
+                let at = cursor + found;
+                segment.offset = Some(start + chunk[..at].chars().count());
+                cursor = at + segment.original.len();
+            }
+            segment
+        })
+        .collect()
+}
+
+/// Fill in each segment's dictionary entries, saved-term status and quick
+/// actions
+fn annotate_segments(vocabulary: &VocabularyState, language: &str, segments: &mut [Segment]) {
+    // One batch query for every lemma and surface form in the text
+    let mut words: Vec<String> = Vec::new();
+    for segment in segments.iter() {
+        words.extend(segment.lemma.iter().cloned());
+        words.push(segment.original.clone());
     }
+    let found = if db::get_connection(language).is_ok() {
+        db::batch_search(&words, language, &SearchOptions::default())
+            .map_err(|e| eprintln!("[SANSKRIT] Segment lookup failed: {}", e))
+            .ok()
+    } else {
+        None
+    };
 
-    let output = Command::new("python")
-        .args(&[
-            "scripts/enhanced_sanskrit_api.py",
-            "--action", "process",
-            "--text", &text,
-            "--json"
-        ])
-        .current_dir(std::env::current_exe().unwrap_or_default().parent().unwrap_or(std::path::Path::new(".")))
-        .stdout(Stdio::piped())
-        .stderr(Stdio::piped())
-        .output();
-
-    match output {
-        Ok(output) => {
-            if output.status.success() {
-                let stdout = String::from_utf8_lossy(&output.stdout);
-                match serde_json::from_str::<serde_json::Value>(&stdout) {
-                    Ok(result) => {
-                        let segments = result.get("segments")
-                            .and_then(|v| v.as_array())
-                            .map(|arr| {
-                                arr.iter()
-                                    .filter_map(|item| {
-                                        serde_json::from_value::<Segment>(item.clone()).ok()
-                                    })
-                                    .collect()
-                            })
-                            .unwrap_or_default();
-
-                        Ok(ProcessResult {
-                            success: result.get("success").and_then(|v| v.as_bool()).unwrap_or(true),
-                            text,
-                            segments,
-                            analysis: Some(result),
-                            error: None,
-                        })
-                    }
-                    Err(e) => Ok(ProcessResult {
-                        success: false,
-                        text,
-                        segments: vec![],
-                        analysis: None,
-                        error: Some(format!("Failed to parse result: {}", e)),
-                    }),
+    let caps = capabilities::current();
+    let index = vocabulary.term_index.read().unwrap();
+    for segment in segments.iter_mut() {
+        let forms: Vec<String> = segment
+            .lemma
+            .iter()
+            .chain(std::iter::once(&segment.original))
+            .filter(|w| !w.is_empty())
+            .cloned()
+            .collect();
+        if let Some(found) = &found {
+            let mut entries: Vec<DictionaryEntry> = Vec::new();
+            for (i, form) in forms.iter().enumerate() {
+                if forms[..i].contains(form) {
+                    continue;
                 }
-            } else {
-                let stderr = String::from_utf8_lossy(&output.stderr);
-                Ok(ProcessResult {
-                    success: false,
-                    text,
-                    segments: vec![],
-                    analysis: None,
-                    error: Some(stderr.to_string()),
-                })
+                entries.extend(found.get(form).into_iter().flatten().cloned());
             }
+            entry_actions::annotate(&mut entries, &index, &caps);
+            segment.dictionary = Some(entries);
         }
-        Err(e) => Ok(ProcessResult {
-            success: false,
-            text,
-            segments: vec![],
-            analysis: None,
-            error: Some(format!("Failed to run Python: {}", e)),
-        }),
+        if let Some(form) = forms.iter().find(|f| index.status_for(language, f).is_some()) {
+            segment.term_status = index.status_for(language, form);
+            segment.term_id = index.ids_for(language, form).into_iter().min();
+        }
+        segment.actions = entry_actions::for_segment(segment, language, &index, &caps);
+    }
+}
+
+/// Build the result from the annotated segments and the worker's answers
+/// for the pieces of `text`, and cache the analysis for export
+fn analysed(
+    text: String,
+    segments: Vec<Segment>,
+    mut results: Vec<serde_json::Value>,
+    truncation: Option<Truncation>,
+) -> ProcessResult {
+    let analysis = if results.len() == 1 {
+        results.remove(0)
+    } else {
+        json!({ "success": true, "action": "process", "chunks": results })
+    };
+    let mut processed = ProcessResult {
+        success: true,
+        text,
+        segments,
+        analysis: Some(analysis),
+        error: None,
+        error_kind: None,
+        analysis_id: None,
+        truncation,
+        job_id: None,
+    };
+    processed.analysis_id = Some(analysis_cache::store(CachedAnalysis::Text(processed.clone())));
+    processed
+}
+
+/// Split `text` after sentence ends (daṇḍa, full stop, line break) into
+/// pieces of about `target` characters, each with its character offset.
+/// A sentence end only counts before whitespace or the end of the text, so
+/// "1.5" or "ŚB.2" never splits; a sentence longer than `target` stays whole.
+fn text_chunks(text: &str, target: usize) -> Vec<(usize, String)> {
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    let mut current = String::new();
+    let mut sentence = String::new();
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        sentence.push(c);
+        let ends = matches!(c, '।' | '॥' | '.' | '?' | '!' | '\n')
+            && chars.peek().is_none_or(|next| next.is_whitespace());
+        if ends {
+            if !current.is_empty() && current.chars().count() + sentence.chars().count() > target {
+                let length = current.chars().count();
+                chunks.push((start, std::mem::take(&mut current)));
+                start += length;
+            }
+            current.push_str(&std::mem::take(&mut sentence));
+        }
+    }
+    if !current.is_empty() && current.chars().count() + sentence.chars().count() > target {
+        let length = current.chars().count();
+        chunks.push((start, std::mem::take(&mut current)));
+        start += length;
     }
+    current.push_str(&sentence);
+    chunks.push((start, current));
+    chunks.retain(|(_, chunk)| !chunk.trim().is_empty());
+    chunks
+}
+
+/// Command palette entries for the Sanskrit tools. Only registered when the
+/// Python tooling is present.
+pub fn register_actions(registry: &ActionRegistry) {
+    registry.register(
+        AppAction::new(
+            "sanskrit.toolkit",
+            "Open Sanskrit toolkit",
+            "Sanskrit",
+            Arc::new(|app, _args| {
+                app.emit_to("main", "open-sanskrit-toolkit", ())
+                    .map_err(|e| e.to_string())?;
+                Ok(serde_json::Value::Null)
+            }),
+        )
+        .keywords(&["sandhi", "split", "transliterate", "vidyut"]),
+    );
 }
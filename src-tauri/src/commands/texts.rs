@@ -0,0 +1,397 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager, State};
+use crate::commands::vocabulary::{TermIndex, VocabularyState};
+
+// ============================================================================
+// Data Models
+// ============================================================================
+
+/// A reading text as the backend keeps it: metadata plus the distinct
+/// normalized tokens, so coverage is a set lookup instead of a re-tokenize.
+/// The content itself stays in the frontend's document store.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StoredText {
+    pub id: String,
+    pub title: String,
+    pub language_id: String,
+    pub created_at: i64,
+    pub tokens: Vec<String>,
+    #[serde(default)]
+    pub coverage: f64,
+    #[serde(default)]
+    pub coverage_computed_at: i64,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct TextsData {
+    pub texts: Vec<StoredText>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TextSummary {
+    pub id: String,
+    pub title: String,
+    pub language_id: String,
+    pub created_at: i64,
+    pub token_count: usize,
+    /// Share of distinct tokens that are saved terms, as last computed
+    pub coverage: f64,
+    pub coverage_computed_at: i64,
+    /// Vocabulary changed since `coverage` was computed
+    pub stale: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TextCoverageEvent {
+    pub text_id: String,
+    pub coverage: f64,
+    pub coverage_computed_at: i64,
+}
+
+pub struct TextsState {
+    pub texts_path: PathBuf,
+    /// Serializes read-modify-write cycles of the texts file
+    lock: Mutex<()>,
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphabetic()
+        // Combining diacritics and Devanagari signs (virama etc.), but not the dandas
+        || ('\u{0300}'..='\u{036F}').contains(&c)
+        || ('\u{0900}'..='\u{0963}').contains(&c)
+}
+
+/// Distinct lowercase words of `content`, sorted. Splits on anything that
+/// isn't part of a word, which suits space-delimited scripts.
+pub fn tokenize(content: &str) -> Vec<String> {
+    content
+        .split(|c: char| !is_word_char(c))
+        .filter(|w| !w.is_empty())
+        .map(TermIndex::normalize)
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect()
+}
+
+fn compute_coverage(text: &StoredText, index: &TermIndex) -> f64 {
+    if text.tokens.is_empty() {
+        return 0.0;
+    }
+    let known = text
+        .tokens
+        .iter()
+        .filter(|t| index.contains(&text.language_id, t))
+        .count();
+    known as f64 / text.tokens.len() as f64
+}
+
+fn load_texts(path: &PathBuf) -> TextsData {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|content| serde_json::from_str::<TextsData>(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save_texts(path: &PathBuf, data: &TextsData) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create directory: {}", e))?;
+    }
+    let content = serde_json::to_string(data)
+        .map_err(|e| format!("Failed to serialize texts: {}", e))?;
+    fs::write(path, content)
+        .map_err(|e| format!("Failed to write texts file: {}", e))
+}
+
+/// Recompute texts selected by `filter` and return a `text-coverage-updated`
+/// event for each. Unselected texts that were current as of `current_since`
+/// are marked current again, since the change being processed can't affect them.
+fn recompute_texts(
+    data: &mut TextsData,
+    index: &TermIndex,
+    filter: impl Fn(&StoredText) -> bool,
+    current_since: Option<i64>,
+) -> Vec<TextCoverageEvent> {
+    let computed_at = index.updated_at;
+    let mut events = Vec::new();
+    for text in data.texts.iter_mut() {
+        if filter(text) {
+            text.coverage = compute_coverage(text, index);
+            events.push(TextCoverageEvent {
+                text_id: text.id.clone(),
+                coverage: text.coverage,
+                coverage_computed_at: computed_at,
+            });
+            text.coverage_computed_at = computed_at;
+        } else if current_since.is_some_and(|since| text.coverage_computed_at >= since) {
+            text.coverage_computed_at = computed_at;
+        }
+    }
+    events
+}
+
+/// `recompute_texts` on the stored texts, saving and emitting the results
+fn recompute(app: &AppHandle, filter: impl Fn(&StoredText) -> bool, current_since: Option<i64>) {
+    let state = app.state::<TextsState>();
+    let vocabulary = app.state::<VocabularyState>();
+    let _guard = state.lock.lock().unwrap();
+
+    let mut data = load_texts(&state.texts_path);
+    if data.texts.is_empty() {
+        return;
+    }
+    let events = recompute_texts(&mut data, &vocabulary.term_index.read().unwrap(), filter, current_since);
+
+    if let Err(e) = save_texts(&state.texts_path, &data) {
+        eprintln!("[TEXTS] Failed to save coverage: {}", e);
+        return;
+    }
+    for event in events {
+        let _ = app.emit("text-coverage-updated", event);
+    }
+}
+
+/// Whether a change to the term `token` (normalized) in `language` can
+/// change this text's coverage
+fn contains_token(text: &StoredText, language: &str, token: &str) -> bool {
+    text.language_id == language && text.tokens.binary_search_by(|t| t.as_str().cmp(token)).is_ok()
+}
+
+/// A term was added, changed or removed: only texts in its language that
+/// contain its text can change coverage. `previous_updated_at` is the
+/// vocabulary timestamp before this change.
+pub fn refresh_for_term(app: &AppHandle, language: &str, term_text: &str, previous_updated_at: i64) {
+    let app = app.clone();
+    let language = language.to_string();
+    let token = TermIndex::normalize(term_text);
+    std::thread::spawn(move || {
+        recompute(&app, |text| contains_token(text, &language, &token), Some(previous_updated_at));
+    });
+}
+
+/// Recompute every text computed before the latest vocabulary change
+pub fn refresh_stale(app: &AppHandle) {
+    let app = app.clone();
+    std::thread::spawn(move || {
+        let updated_at = app.state::<VocabularyState>().term_index.read().unwrap().updated_at;
+        recompute(&app, |text| text.coverage_computed_at < updated_at, None);
+    });
+}
+
+pub fn get_texts_path(app: &AppHandle) -> PathBuf {
+    let base_dir = app.path()
+        .app_data_dir()
+        .unwrap_or_else(|_| std::env::current_dir().unwrap_or_else(|_| PathBuf::from(".")));
+    base_dir.join("data").join("texts.json")
+}
+
+pub fn texts_state_at(texts_path: PathBuf) -> TextsState {
+    TextsState {
+        texts_path,
+        lock: Mutex::new(()),
+    }
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Register (or replace) a text and persist its token set
+#[tauri::command]
+pub async fn import_text(
+    app: AppHandle,
+    state: State<'_, TextsState>,
+    vocabulary: State<'_, VocabularyState>,
+    id: String,
+    title: String,
+    language_id: String,
+    content: String,
+) -> Result<TextSummary, String> {
+    let mut text = StoredText {
+        id,
+        title,
+        language_id,
+        created_at: chrono::Utc::now().timestamp_millis(),
+        tokens: tokenize(&content),
+        coverage: 0.0,
+        coverage_computed_at: 0,
+    };
+    {
+        let index = vocabulary.term_index.read().unwrap();
+        text.coverage = compute_coverage(&text, &index);
+        text.coverage_computed_at = index.updated_at;
+    }
+
+    let _guard = state.lock.lock().unwrap();
+    let mut data = load_texts(&state.texts_path);
+    if let Some(existing) = data.texts.iter_mut().find(|t| t.id == text.id) {
+        text.created_at = existing.created_at;
+        *existing = text.clone();
+    } else {
+        data.texts.push(text.clone());
+    }
+    save_texts(&state.texts_path, &data)?;
+
+    let _ = app.emit("text-coverage-updated", TextCoverageEvent {
+        text_id: text.id.clone(),
+        coverage: text.coverage,
+        coverage_computed_at: text.coverage_computed_at,
+    });
+    Ok(summarize(&text, text.coverage_computed_at))
+}
+
+fn summarize(text: &StoredText, vocabulary_updated_at: i64) -> TextSummary {
+    TextSummary {
+        id: text.id.clone(),
+        title: text.title.clone(),
+        language_id: text.language_id.clone(),
+        created_at: text.created_at,
+        token_count: text.tokens.len(),
+        coverage: text.coverage,
+        coverage_computed_at: text.coverage_computed_at,
+        stale: text.coverage_computed_at < vocabulary_updated_at,
+    }
+}
+
+/// All texts with their last computed coverage and whether it is stale
+#[tauri::command]
+pub async fn list_texts(
+    state: State<'_, TextsState>,
+    vocabulary: State<'_, VocabularyState>,
+) -> Result<Vec<TextSummary>, String> {
+    let updated_at = vocabulary.term_index.read().unwrap().updated_at;
+    let data = load_texts(&state.texts_path);
+    Ok(data.texts.iter().map(|t| summarize(t, updated_at)).collect())
+}
+
+/// Recompute one text in the background; the result arrives as a
+/// `text-coverage-updated` event
+#[tauri::command]
+pub async fn refresh_coverage(app: AppHandle, text_id: String) -> Result<(), String> {
+    std::thread::spawn(move || {
+        recompute(&app, |text| text.id == text_id, None);
+    });
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn delete_text(state: State<'_, TextsState>, id: String) -> Result<(), String> {
+    let _guard = state.lock.lock().unwrap();
+    let mut data = load_texts(&state.texts_path);
+    let before = data.texts.len();
+    data.texts.retain(|t| t.id != id);
+    if data.texts.len() == before {
+        return Err("Text not found".to_string());
+    }
+    save_texts(&state.texts_path, &data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::vocabulary::{Term, TermUpdateEvent};
+
+    fn text(id: &str, language: &str, content: &str) -> StoredText {
+        StoredText {
+            id: id.to_string(),
+            title: id.to_string(),
+            language_id: language.to_string(),
+            created_at: 0,
+            tokens: tokenize(content),
+            coverage: 0.0,
+            coverage_computed_at: 0,
+        }
+    }
+
+    fn learn(index: &mut TermIndex, language: &str, word: &str, timestamp: i64) {
+        let term: Term = serde_json::from_value(serde_json::json!({
+            "id": format!("{}:{}", language, word),
+            "text": word,
+            "languageId": language,
+            "translation": "",
+            "notes": "",
+            "status": 1,
+        }))
+        .unwrap();
+        index.apply(&TermUpdateEvent { action: "add".to_string(), term, timestamp });
+    }
+
+    #[test]
+    fn tokens_are_distinct_lowercase_words() {
+        assert_eq!(tokenize("Der Hund, der Katze!  Hund?"), vec!["der", "hund", "katze"]);
+        // Virama and vowel signs stay inside the word, the danda doesn't
+        assert_eq!(tokenize("धर्मक्षेत्रे कुरुक्षेत्रे।"), vec!["कुरुक्षेत्रे", "धर्मक्षेत्रे"]);
+    }
+
+    #[test]
+    fn texts_keep_their_camel_case_keys_on_disk_and_in_events() {
+        let mut stored = text("t1", "de", "Der Hund");
+        stored.coverage_computed_at = 7;
+        let json = serde_json::to_value(&stored).unwrap();
+        assert_eq!(json["languageId"], "de");
+        assert_eq!(json["coverageComputedAt"], 7);
+        assert!(json.get("createdAt").is_some());
+        assert_eq!(serde_json::from_value::<StoredText>(json).unwrap().language_id, "de");
+
+        let summary = serde_json::to_value(summarize(&stored, 7)).unwrap();
+        assert_eq!(summary["tokenCount"], 2);
+        let event = TextCoverageEvent { text_id: "t1".to_string(), coverage: 0.5, coverage_computed_at: 7 };
+        assert_eq!(serde_json::to_value(event).unwrap()["textId"], "t1");
+    }
+
+    #[test]
+    fn learning_a_word_bumps_exactly_the_texts_containing_it() {
+        let mut data = TextsData {
+            texts: vec![
+                text("fable", "de", "Der Hund und die Katze"),
+                text("letter", "de", "Liebe Anna, der Hund schläft"),
+                text("recipe", "de", "Mehl und Zucker"),
+                text("french", "fr", "Le hund"),
+            ],
+        };
+        let mut index = TermIndex::default();
+        learn(&mut index, "de", "und", 10);
+        recompute_texts(&mut data, &index, |_| true, None);
+        let coverage: Vec<f64> = data.texts.iter().map(|t| t.coverage).collect();
+        assert_eq!(coverage, vec![0.2, 0.0, 1.0 / 3.0, 0.0]);
+
+        let previous = index.updated_at;
+        learn(&mut index, "de", "Hund", 20);
+        let token = TermIndex::normalize("Hund");
+        let events = recompute_texts(&mut data, &index, |t| contains_token(t, "de", &token), Some(previous));
+
+        let updated: Vec<&str> = events.iter().map(|e| e.text_id.as_str()).collect();
+        assert_eq!(updated, vec!["fable", "letter"]);
+        assert_eq!(data.texts[0].coverage, 0.4);
+        assert_eq!(data.texts[1].coverage, 0.2);
+        assert_eq!(data.texts[2].coverage, 1.0 / 3.0);
+        assert_eq!(data.texts[3].coverage, 0.0);
+        // Texts the change couldn't affect are current again without a recompute
+        assert!(data.texts.iter().all(|t| t.coverage_computed_at == 20));
+    }
+
+    #[test]
+    fn stale_texts_stay_stale_when_skipped() {
+        let mut data = TextsData { texts: vec![text("a", "de", "Hund"), text("b", "de", "Katze")] };
+        let mut index = TermIndex::default();
+        learn(&mut index, "de", "hund", 10);
+        // "b" was computed before the previous change, so it isn't current
+        data.texts[1].coverage_computed_at = 5;
+        data.texts[0].coverage_computed_at = 5;
+        recompute_texts(&mut data, &index, |t| t.id == "a", Some(10));
+        assert_eq!(data.texts[0].coverage_computed_at, 10);
+        assert!(summarize(&data.texts[1], index.updated_at).stale);
+        assert!(!summarize(&data.texts[0], index.updated_at).stale);
+    }
+}
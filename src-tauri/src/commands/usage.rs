@@ -0,0 +1,22 @@
+use tauri::State;
+use crate::settings::SettingsState;
+use crate::usage::{self, UsageSummary};
+
+/// Local usage statistics for the last `since_days` days (default 30)
+#[tauri::command]
+pub async fn get_usage_summary(since_days: Option<u32>) -> Result<UsageSummary, String> {
+    Ok(usage::summary(since_days.unwrap_or(30).max(1)))
+}
+
+#[tauri::command]
+pub async fn reset_usage_data() -> Result<(), String> {
+    usage::reset();
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn set_usage_privacy_mode(settings: State<'_, SettingsState>, enabled: bool) -> Result<(), String> {
+    settings.update(|s| s.privacy_mode = enabled)?;
+    usage::set_privacy_mode(enabled);
+    Ok(())
+}
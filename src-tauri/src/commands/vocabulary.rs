@@ -1,8 +1,17 @@
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs;
-use std::path::PathBuf;
-use std::sync::Mutex;
-use tauri::{AppHandle, Emitter, Manager, State};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Condvar, Mutex, RwLock, RwLockReadGuard, RwLockWriteGuard};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Listener, Manager, State};
+use crate::commands::dictionary::csv_field;
+use crate::commands::texts;
+use crate::{anki, normalize, review_log, usage};
+use crate::review_log::{DailyReviews, ReviewEvent};
+use crate::settings::{DailyGoal, SettingsState};
+use crate::db::{self, DictionaryEntry, SearchOptions, SenseOrderHint};
 
 // ============================================================================
 // Data Models
@@ -21,6 +30,12 @@ pub struct Term {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub image: Option<String>,
     
+    // Dictionary sense this term was learned with
+    #[serde(rename = "senseId", default, skip_serializing_if = "Option::is_none")]
+    pub sense_id: Option<String>,
+    #[serde(rename = "senseFingerprint", default, skip_serializing_if = "Option::is_none")]
+    pub sense_fingerprint: Option<String>,
+    
     // SRS fields
     #[serde(default)]
     pub nextReview: i64,
@@ -44,6 +59,26 @@ pub struct Term {
     pub queryCount: i32,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub lastQueriedAt: Option<i64>,
+
+    // Source text or topic labels, compared case-insensitively
+    #[serde(default)]
+    pub tags: Vec<String>,
+
+    // Where the term was first saved from ("clipboard", "floating", "reader", ...)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    // Sentences the term was met in, oldest first
+    #[serde(default)]
+    pub contexts: Vec<TermContext>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TermContext {
+    pub text: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    pub added_at: i64,
 }
 
 fn default_ease_factor() -> f64 {
@@ -65,6 +100,10 @@ pub struct TermInput {
     pub parentId: Option<String>,
     #[serde(default)]
     pub image: Option<String>,
+    #[serde(rename = "senseId", default)]
+    pub sense_id: Option<String>,
+    #[serde(rename = "senseFingerprint", default)]
+    pub sense_fingerprint: Option<String>,
     #[serde(default)]
     pub status: Option<i32>,
     #[serde(default)]
@@ -75,6 +114,23 @@ pub struct TermInput {
     pub easeFactor: Option<f64>,
     #[serde(default)]
     pub reps: Option<i32>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Inflected forms saved as children of this term
+    #[serde(default)]
+    pub inflections: Vec<InflectionInput>,
+    /// Sentence the term was met in
+    #[serde(default)]
+    pub context: Option<String>,
+    #[serde(default)]
+    pub source: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct InflectionInput {
+    pub text: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -83,6 +139,10 @@ pub struct TermUpdates {
     pub translation: Option<String>,
     #[serde(default)]
     pub notes: Option<String>,
+    #[serde(rename = "senseId", default)]
+    pub sense_id: Option<String>,
+    #[serde(rename = "senseFingerprint", default)]
+    pub sense_fingerprint: Option<String>,
     #[serde(default)]
     pub status: Option<i32>,
     #[serde(default)]
@@ -93,6 +153,9 @@ pub struct TermUpdates {
     pub easeFactor: Option<f64>,
     #[serde(default)]
     pub reps: Option<i32>,
+    /// Replaces the term's tags
+    #[serde(default)]
+    pub tags: Option<Vec<String>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -102,6 +165,29 @@ pub struct TermUpdateEvent {
     pub timestamp: i64,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TermsBulkProgressEvent {
+    pub action: String,
+    pub processed: usize,
+    pub failed: usize,
+    pub total: Option<usize>,
+    pub timestamp: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TermsBulkUpdateEvent {
+    pub action: String,
+    pub success: bool,
+    pub affected: usize,
+    pub failed: usize,
+    /// Affected ids, empty when `refetch` is set
+    pub ids: Vec<String>,
+    /// Too many terms changed to list; the frontend should reload the store
+    pub refetch: bool,
+    pub error: Option<String>,
+    pub timestamp: i64,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct TermsData {
     pub terms: Vec<Term>,
@@ -114,14 +200,268 @@ pub struct TermsData {
 // ============================================================================
 
 pub struct VocabularyState {
+    /// The combined store of earlier versions; language stores live next to it
     pub terms_path: Mutex<PathBuf>,
+    pub term_index: RwLock<TermIndex>,
+    /// Languages whose store is being written
+    busy_languages: Mutex<HashSet<String>>,
+    language_released: Condvar,
+    /// Held shared while writing one language, exclusively while writing
+    /// several at once
+    all_languages: RwLock<()>,
+}
+
+impl VocabularyState {
+    fn new(terms_path: PathBuf, index: TermIndex) -> Self {
+        Self {
+            terms_path: Mutex::new(terms_path),
+            term_index: RwLock::new(index),
+            busy_languages: Mutex::new(HashSet::new()),
+            language_released: Condvar::new(),
+            all_languages: RwLock::new(()),
+        }
+    }
+
+    /// Exclusive write access to one language's store. Writes to other
+    /// languages go ahead; ones spanning languages wait.
+    pub fn lock_language(&self, language: &str) -> LanguageGuard<'_> {
+        let shared = self.all_languages.read().unwrap();
+        let mut busy = self.busy_languages.lock().unwrap();
+        while busy.contains(language) {
+            busy = self.language_released.wait(busy).unwrap();
+        }
+        busy.insert(language.to_string());
+        LanguageGuard {
+            state: self,
+            language: language.to_string(),
+            _shared: shared,
+        }
+    }
+
+    /// Exclusive write access to every language's store
+    pub fn lock_all_languages(&self) -> RwLockWriteGuard<'_, ()> {
+        self.all_languages.write().unwrap()
+    }
+}
+
+/// Run a store write on a blocking thread: taking the store locks waits
+/// for other writers, which must not hold up an async runtime worker
+pub(crate) async fn spawn_write<T: Send + 'static>(
+    app: AppHandle,
+    write: impl FnOnce(&AppHandle, &VocabularyState) -> Result<T, String> + Send + 'static,
+) -> Result<T, String> {
+    tauri::async_runtime::spawn_blocking(move || write(&app, &app.state::<VocabularyState>()))
+        .await
+        .map_err(|e| format!("Vocabulary write failed: {}", e))?
+}
+
+pub struct LanguageGuard<'a> {
+    state: &'a VocabularyState,
+    language: String,
+    _shared: RwLockReadGuard<'a, ()>,
+}
+
+impl Drop for LanguageGuard<'_> {
+    fn drop(&mut self) {
+        self.state.busy_languages.lock().unwrap().remove(&self.language);
+        self.state.language_released.notify_all();
+    }
+}
+
+/// Normalized term texts per language with their statuses, so suggestion
+/// ranking never has to read the store. Kept current by `watch_term_updates`.
+#[derive(Debug, Default)]
+pub struct TermIndex {
+    by_id: HashMap<String, (String, String)>,
+    by_text: HashMap<(String, String), HashMap<String, i32>>,
+    /// Last change to the store this index reflects (ms)
+    pub updated_at: i64,
+}
+
+impl TermIndex {
+    pub fn build(data: &TermsData) -> Self {
+        let mut index = Self::default();
+        for term in &data.terms {
+            index.insert(term);
+        }
+        index.updated_at = data.updatedAt;
+        index
+    }
+
+    pub fn normalize(text: &str) -> String {
+        text.trim().to_lowercase()
+    }
+
+    pub fn insert(&mut self, term: &Term) {
+        self.remove(&term.id);
+        let key = (term.languageId.clone(), Self::normalize(&term.text));
+        self.by_id.insert(term.id.clone(), key.clone());
+        self.by_text.entry(key).or_default().insert(term.id.clone(), term.status);
+    }
+
+    pub fn remove(&mut self, id: &str) {
+        if let Some(key) = self.by_id.remove(id) {
+            if let Some(ids) = self.by_text.get_mut(&key) {
+                ids.remove(id);
+                if ids.is_empty() {
+                    self.by_text.remove(&key);
+                }
+            }
+        }
+    }
+
+    /// Follow one `term-update` event
+    pub fn apply(&mut self, update: &TermUpdateEvent) {
+        match update.action.as_str() {
+            "delete" => self.remove(&update.term.id),
+            _ => self.insert(&update.term),
+        }
+        self.updated_at = self.updated_at.max(update.timestamp);
+    }
+
+    /// Whether any saved term has this already-normalized text
+    pub fn contains(&self, language: &str, normalized: &str) -> bool {
+        self.by_text.contains_key(&(language.to_string(), normalized.to_string()))
+    }
+
+    /// Ids of saved terms with this text
+    pub fn ids_for(&self, language: &str, text: &str) -> Vec<String> {
+        self.by_text
+            .get(&(language.to_string(), Self::normalize(text)))
+            .map(|ids| ids.keys().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Least advanced status among saved terms with this text, if any
+    pub fn status_for(&self, language: &str, text: &str) -> Option<i32> {
+        self.by_text
+            .get(&(language.to_string(), Self::normalize(text)))
+            .and_then(|ids| ids.values().min().copied())
+    }
+}
+
+// ============================================================================
+// Bulk event batching
+// ============================================================================
+
+const BULK_PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+const BULK_PROGRESS_ITEMS: usize = 200;
+const BULK_MAX_LISTED_IDS: usize = 500;
+
+/// Where a batch's events go: the app, or a recorder in tests
+pub trait BulkEventSink {
+    fn progress(&self, event: TermsBulkProgressEvent);
+    fn update(&self, event: TermsBulkUpdateEvent);
+}
+
+impl BulkEventSink for AppHandle {
+    fn progress(&self, event: TermsBulkProgressEvent) {
+        let _ = self.emit("terms-bulk-progress", event);
+    }
+
+    fn update(&self, event: TermsBulkUpdateEvent) {
+        let _ = self.emit("terms-bulk-update", event);
+    }
+}
+
+/// Collects per-term changes of a bulk operation so the webview gets periodic
+/// `terms-bulk-progress` events and one terminal `terms-bulk-update` instead of
+/// one `term-update` per term. If dropped without `finish` (early return or
+/// panic) it still emits a failed terminal event.
+pub struct BulkEventBatch<S: BulkEventSink = AppHandle> {
+    sink: S,
+    action: String,
+    total: Option<usize>,
+    ids: Vec<String>,
+    failed: usize,
+    since_emit: usize,
+    last_emit: Instant,
+    finished: bool,
+}
+
+impl BulkEventBatch {
+    pub fn new(app: &AppHandle, action: &str, total: Option<usize>) -> Self {
+        Self::with_sink(app.clone(), action, total)
+    }
+}
+
+impl<S: BulkEventSink> BulkEventBatch<S> {
+    pub fn with_sink(sink: S, action: &str, total: Option<usize>) -> Self {
+        Self {
+            sink,
+            action: action.to_string(),
+            total,
+            ids: Vec::new(),
+            failed: 0,
+            since_emit: 0,
+            last_emit: Instant::now(),
+            finished: false,
+        }
+    }
+
+    pub fn record(&mut self, id: &str) {
+        self.ids.push(id.to_string());
+        self.tick();
+    }
+
+    pub fn record_failure(&mut self) {
+        self.failed += 1;
+        self.tick();
+    }
+
+    fn tick(&mut self) {
+        self.since_emit += 1;
+        if self.since_emit >= BULK_PROGRESS_ITEMS || self.last_emit.elapsed() >= BULK_PROGRESS_INTERVAL {
+            self.sink.progress(TermsBulkProgressEvent {
+                action: self.action.clone(),
+                processed: self.ids.len() + self.failed,
+                failed: self.failed,
+                total: self.total,
+                timestamp: chrono::Utc::now().timestamp_millis(),
+            });
+            self.since_emit = 0;
+            self.last_emit = Instant::now();
+        }
+    }
+
+    fn emit_terminal(&mut self, error: Option<String>) {
+        self.finished = true;
+        let ids = std::mem::take(&mut self.ids);
+        let refetch = ids.len() > BULK_MAX_LISTED_IDS;
+        self.sink.update(TermsBulkUpdateEvent {
+            action: self.action.clone(),
+            success: error.is_none(),
+            affected: ids.len(),
+            failed: self.failed,
+            ids: if refetch { Vec::new() } else { ids },
+            refetch,
+            error,
+            timestamp: chrono::Utc::now().timestamp_millis(),
+        });
+    }
+
+    pub fn finish(mut self) {
+        self.emit_terminal(None);
+    }
+
+    pub fn fail(mut self, error: &str) {
+        self.emit_terminal(Some(error.to_string()));
+    }
+}
+
+impl<S: BulkEventSink> Drop for BulkEventBatch<S> {
+    fn drop(&mut self) {
+        if !self.finished {
+            self.emit_terminal(Some("Bulk operation aborted".to_string()));
+        }
+    }
 }
 
 // ============================================================================
 // Helper Functions
 // ============================================================================
 
-fn get_terms_path(app: &AppHandle) -> PathBuf {
+pub fn get_terms_path(app: &AppHandle) -> PathBuf {
     // Try to get the data directory from Tauri
     let base_dir = app.path()
         .app_data_dir()
@@ -130,48 +470,227 @@ fn get_terms_path(app: &AppHandle) -> PathBuf {
     base_dir.join("data").join("terms.json")
 }
 
-fn load_terms(terms_path: &PathBuf) -> TermsData {
-    if terms_path.exists() {
-        match fs::read_to_string(terms_path) {
-            Ok(content) => {
-                match serde_json::from_str::<TermsData>(&content) {
-                    Ok(data) => data,
-                    Err(_) => {
-                        // Try old format (just array)
-                        if let Ok(terms) = serde_json::from_str::<Vec<Term>>(&content) {
-                            TermsData {
-                                terms,
-                                version: "1.0".to_string(),
-                                updatedAt: chrono::Utc::now().timestamp_millis(),
-                            }
-                        } else {
-                            TermsData {
-                                terms: Vec::new(),
-                                version: "1.0".to_string(),
-                                updatedAt: chrono::Utc::now().timestamp_millis(),
-                            }
-                        }
-                    }
-                }
+/// Schema written to stores and exports. "1.0" stores had no tags,
+/// contexts or sources; bare arrays of terms predate the version field.
+pub const TERMS_VERSION: &str = "2.0";
+
+/// Previous versions of each store kept as terms_de.json.1, .2, ...
+const TERMS_GENERATIONS: usize = 2;
+
+/// Set when a store had to fall back to an older generation
+static TERMS_RECOVERY: Mutex<Option<TermsRecovery>> = Mutex::new(None);
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TermsRecovery {
+    /// Why the store couldn't be used
+    pub error: String,
+    /// The generation that was loaded instead, None if none was readable
+    pub recovered_from: Option<String>,
+    pub at: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct VocabularyHealth {
+    pub success: bool,
+    /// Whether the last load had to recover from a damaged store
+    pub recovered: bool,
+    pub recovery: Option<TermsRecovery>,
+    /// Previous generations on disk, newest first per store
+    pub generations: Vec<String>,
+}
+
+fn generation_path(terms_path: &Path, generation: usize) -> PathBuf {
+    let mut name = terms_path.as_os_str().to_owned();
+    name.push(format!(".{}", generation));
+    PathBuf::from(name)
+}
+
+/// The store of one language, terms_<language>.json next to the old
+/// combined terms.json
+pub(crate) fn language_path(terms_path: &Path, language: &str) -> PathBuf {
+    let mut code: String = language
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect();
+    if code.is_empty() {
+        code = "unknown".to_string();
+    }
+    terms_path.with_file_name(format!("terms_{}.json", code))
+}
+
+/// Every language store on disk, sorted by file name. A store only present
+/// as previous generations (terms_de.json.1) counts too, so a language
+/// whose file is missing is still loaded, from those.
+pub(crate) fn language_stores(terms_path: &Path) -> Vec<PathBuf> {
+    let Some(dir) = terms_path.parent() else {
+        return Vec::new();
+    };
+    let mut paths: Vec<PathBuf> = fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let store = match name.rsplit_once('.') {
+                Some((store, generation)) if generation.parse::<usize>().is_ok() => store,
+                _ => name.as_str(),
+            };
+            (store.len() > "terms_.json".len() && store.starts_with("terms_") && store.ends_with(".json"))
+                .then(|| dir.join(store))
+        })
+        .collect();
+    paths.sort();
+    paths.dedup();
+    paths
+}
+
+fn empty_terms() -> TermsData {
+    TermsData {
+        terms: Vec::new(),
+        version: TERMS_VERSION.to_string(),
+        updatedAt: chrono::Utc::now().timestamp_millis(),
+    }
+}
+
+/// Field names of older builds (and their exports) for `Term` fields
+const LEGACY_TERM_FIELDS: &[(&str, &str)] = &[
+    ("language_id", "languageId"),
+    ("language", "languageId"),
+    ("parent_id", "parentId"),
+    ("sense_id", "senseId"),
+    ("sense_fingerprint", "senseFingerprint"),
+    ("next_review", "nextReview"),
+    ("last_review", "lastReview"),
+    ("ease_factor", "easeFactor"),
+    ("created_at", "createdAt"),
+    ("updated_at", "updatedAt"),
+    ("query_count", "queryCount"),
+    ("last_queried_at", "lastQueriedAt"),
+];
+
+/// Bring a term of an older schema up to date: legacy field names, fields
+/// that had no default yet, and ids of builds that didn't store them
+fn migrate_term(mut value: serde_json::Value) -> Result<Term, String> {
+    use serde_json::Value;
+
+    let term = value.as_object_mut().ok_or_else(|| "Term is not an object".to_string())?;
+    for (old, new) in LEGACY_TERM_FIELDS {
+        if let Some(v) = term.remove(*old) {
+            term.entry(*new).or_insert(v);
+        }
+    }
+    for field in ["translation", "notes"] {
+        if term.get(field).is_none_or(Value::is_null) {
+            term.insert(field.to_string(), Value::from(""));
+        }
+    }
+    if term.get("status").is_none_or(Value::is_null) {
+        term.insert("status".to_string(), Value::from(0));
+    }
+    if term.get("id").is_none_or(Value::is_null) {
+        let text = term.get("text").and_then(Value::as_str).unwrap_or_default().to_lowercase();
+        let language = term.get("languageId").and_then(Value::as_str).unwrap_or_default().to_string();
+        let created = term.get("createdAt").and_then(Value::as_i64).unwrap_or(0);
+        term.insert("id".to_string(), Value::from(format!("{}:{}:{}", language, text, created)));
+    }
+    serde_json::from_value(value).map_err(|e| format!("Term does not parse: {}", e))
+}
+
+/// Parse a store or export of any known schema: a bare array of terms (the
+/// oldest format), "1.0", or `TERMS_VERSION`. Returns the data at
+/// `TERMS_VERSION`, the version it was read at (None for a bare array) and
+/// how many terms had to be migrated.
+pub(crate) fn parse_terms(content: &str) -> Result<(TermsData, Option<String>, usize), String> {
+    use serde_json::Value;
+
+    let (version, updated_at, terms) = match serde_json::from_str::<Value>(content).map_err(|e| e.to_string())? {
+        Value::Array(terms) => (None, None, terms),
+        Value::Object(mut data) => {
+            let version = Some(data.get("version").and_then(Value::as_str).unwrap_or("1.0").to_string());
+            let updated_at = data.get("updatedAt").and_then(Value::as_i64);
+            match data.remove("terms") {
+                Some(Value::Array(terms)) => (version, updated_at, terms),
+                _ => return Err("No terms list".to_string()),
             }
-            Err(_) => TermsData {
-                terms: Vec::new(),
-                version: "1.0".to_string(),
-                updatedAt: chrono::Utc::now().timestamp_millis(),
-            },
         }
-    } else {
+        _ => return Err("Not a vocabulary file".to_string()),
+    };
+
+    let (terms, migrated) = match version.as_deref() {
+        Some(TERMS_VERSION) => (
+            serde_json::from_value::<Vec<Term>>(Value::Array(terms)).map_err(|e| e.to_string())?,
+            0,
+        ),
+        None | Some("1.0") => {
+            let terms = terms.into_iter().map(migrate_term).collect::<Result<Vec<Term>, String>>()?;
+            let migrated = terms.len();
+            (terms, migrated)
+        }
+        Some(other) => return Err(format!("Unknown vocabulary format version {} (newer Lumina?)", other)),
+    };
+    Ok((
         TermsData {
-            terms: Vec::new(),
-            version: "1.0".to_string(),
-            updatedAt: chrono::Utc::now().timestamp_millis(),
+            terms,
+            version: TERMS_VERSION.to_string(),
+            updatedAt: updated_at.unwrap_or_else(|| chrono::Utc::now().timestamp_millis()),
+        },
+        version,
+        migrated,
+    ))
+}
+
+/// A terms file of any known schema, see `parse_terms`
+fn read_terms_file(path: &Path) -> Result<TermsData, String> {
+    let content = fs::read_to_string(path).map_err(|e| format!("Failed to read {:?}: {}", path, e))?;
+    parse_terms(&content)
+        .map(|(data, _, _)| data)
+        .map_err(|e| format!("Failed to parse {:?}: {}", path, e))
+}
+
+/// One store file, falling back to its previous generations when damaged
+/// or missing
+fn load_store(path: &Path) -> TermsData {
+    let error = if path.exists() {
+        match read_terms_file(path) {
+            Ok(data) => return data,
+            Err(e) => e,
+        }
+    } else if (1..=TERMS_GENERATIONS).any(|generation| generation_path(path, generation).exists()) {
+        format!("{:?} is missing", path)
+    } else {
+        return empty_terms();
+    };
+
+    eprintln!("[VOCAB] {}; trying previous versions", error);
+    for generation in 1..=TERMS_GENERATIONS {
+        let older = generation_path(path, generation);
+        if let Ok(data) = read_terms_file(&older) {
+            eprintln!("[VOCAB] Recovered {} terms from {:?}", data.terms.len(), older);
+            *TERMS_RECOVERY.lock().unwrap() = Some(TermsRecovery {
+                error,
+                recovered_from: older.file_name().map(|n| n.to_string_lossy().to_string()),
+                at: chrono::Utc::now().timestamp_millis(),
+            });
+            return data;
         }
     }
+    eprintln!("[VOCAB] No readable previous version of {:?}", path);
+    *TERMS_RECOVERY.lock().unwrap() = Some(TermsRecovery {
+        error,
+        recovered_from: None,
+        at: chrono::Utc::now().timestamp_millis(),
+    });
+    empty_terms()
 }
 
-fn save_terms(terms_path: &PathBuf, data: &TermsData) -> Result<(), String> {
+/// Write one store file durably: to a temporary file that is synced and
+/// then renamed over it, after shifting older generations up and linking
+/// (or copying) the current file to `.1`, keeping `TERMS_GENERATIONS`. The
+/// current file stays in place until the rename replaces it, so a crash at
+/// any point leaves the new or the previous version under its own name.
+fn save_store(path: &Path, data: &TermsData) -> Result<(), String> {
     // Ensure directory exists
-    if let Some(parent) = terms_path.parent() {
+    if let Some(parent) = path.parent() {
         fs::create_dir_all(parent)
             .map_err(|e| format!("Failed to create directory: {}", e))?;
     }
@@ -179,40 +698,162 @@ fn save_terms(terms_path: &PathBuf, data: &TermsData) -> Result<(), String> {
     let content = serde_json::to_string_pretty(data)
         .map_err(|e| format!("Failed to serialize terms: {}", e))?;
     
-    fs::write(terms_path, content)
+    let tmp_path = path.with_extension("json.tmp");
+    let mut file = fs::File::create(&tmp_path)
         .map_err(|e| format!("Failed to write terms file: {}", e))?;
+    file.write_all(content.as_bytes())
+        .and_then(|()| file.sync_all())
+        .map_err(|e| format!("Failed to write terms file: {}", e))?;
+    drop(file);
+
+    if path.exists() {
+        for generation in (1..TERMS_GENERATIONS).rev() {
+            let older = generation_path(path, generation);
+            if older.exists() {
+                let _ = fs::rename(&older, generation_path(path, generation + 1));
+            }
+        }
+        let previous = generation_path(path, 1);
+        let _ = fs::remove_file(&previous);
+        fs::hard_link(path, &previous)
+            .or_else(|_| fs::copy(path, &previous).map(|_| ()))
+            .map_err(|e| format!("Failed to keep previous terms file: {}", e))?;
+    }
+    fs::rename(&tmp_path, path)
+        .map_err(|e| format!("Failed to replace terms file: {}", e))?;
+    // Make the renames themselves durable
+    #[cfg(unix)]
+    if let Some(parent) = path.parent() {
+        let _ = fs::File::open(parent).and_then(|dir| dir.sync_all());
+    }
     
     Ok(())
 }
 
-// ============================================================================
-// Tauri Commands
-// ============================================================================
+/// The terms of one language
+pub fn load_language(terms_path: &Path, language: &str) -> TermsData {
+    load_store(&language_path(terms_path, language))
+}
 
-/// Save a new term (supports root + inflection)
-#[tauri::command]
-pub async fn save_term(
-    app: AppHandle,
-    state: State<'_, VocabularyState>,
-    input: TermInput,
-) -> Result<Vec<Term>, String> {
-    let terms_path = state.terms_path.lock().unwrap().clone();
-    let mut data = load_terms(&terms_path);
-    
-    let now = chrono::Utc::now().timestamp_millis();
-    let mut saved_terms = Vec::new();
-    
-    // 1. Save main term (root form)
-    let main_id = format!("{}:{}:{}", input.languageId, input.text.to_lowercase(), now);
-    let main_term = Term {
-        id: main_id.clone(),
-        text: input.text.clone(),
-        languageId: input.languageId.clone(),
-        translation: input.translation.clone(),
+/// Replace the store of one language; `data` must only hold its terms
+pub fn save_language(terms_path: &Path, language: &str, data: &TermsData) -> Result<(), String> {
+    save_store(&language_path(terms_path, language), data)
+}
+
+/// One language's terms, or every language's without one
+fn load_scope(terms_path: &Path, language: Option<&str>) -> TermsData {
+    match language {
+        Some(language) => load_language(terms_path, language),
+        None => load_terms(terms_path),
+    }
+}
+
+/// The terms of every language, store by store
+pub fn load_terms(terms_path: &Path) -> TermsData {
+    let mut data = empty_terms();
+    data.updatedAt = 0;
+    for path in language_stores(terms_path) {
+        let store = load_store(&path);
+        data.terms.extend(store.terms);
+        data.updatedAt = data.updatedAt.max(store.updatedAt);
+    }
+    data
+}
+
+/// Save terms of any languages. Only stores whose terms changed are
+/// rewritten; stores of languages `data` no longer has any terms in are
+/// emptied.
+pub fn save_terms(terms_path: &Path, data: &TermsData) -> Result<(), String> {
+    let mut stores: HashMap<PathBuf, Vec<&Term>> = language_stores(terms_path)
+        .into_iter()
+        .map(|path| (path, Vec::new()))
+        .collect();
+    for term in &data.terms {
+        stores.entry(language_path(terms_path, &term.languageId)).or_default().push(term);
+    }
+
+    for (path, terms) in stores {
+        let content = serde_json::to_string(&terms).map_err(|e| format!("Failed to serialize terms: {}", e))?;
+        let unchanged = path.exists()
+            && read_terms_file(&path)
+                .ok()
+                .and_then(|stored| serde_json::to_string(&stored.terms).ok())
+                .is_some_and(|stored| stored == content);
+        if unchanged {
+            continue;
+        }
+        save_store(&path, &TermsData {
+            terms: terms.into_iter().cloned().collect(),
+            version: TERMS_VERSION.to_string(),
+            updatedAt: data.updatedAt,
+        })?;
+    }
+    Ok(())
+}
+
+/// Language of the store holding term `id`. Ids start with the language,
+/// so that store is tried first; other stores are searched after it.
+fn term_language(terms_path: &Path, id: &str) -> Option<String> {
+    if let Some((language, _)) = id.split_once(':') {
+        if load_language(terms_path, language).terms.iter().any(|t| t.id == id) {
+            return Some(language.to_string());
+        }
+    }
+    language_stores(terms_path)
+        .into_iter()
+        .filter_map(|path| load_store(&path).terms.into_iter().find(|t| t.id == id))
+        .map(|term| term.languageId)
+        .next()
+}
+
+/// Split the combined terms.json of earlier versions into language stores.
+/// It's kept as terms.json.migrated; reruns after a crash redo the split.
+fn migrate_combined_store(terms_path: &Path) {
+    if !terms_path.exists() {
+        return;
+    }
+    let data = load_store(terms_path);
+    if let Err(e) = save_terms(terms_path, &data) {
+        eprintln!("[VOCAB] Could not split {:?} by language: {}", terms_path, e);
+        return;
+    }
+    let migrated = terms_path.with_extension("json.migrated");
+    match fs::rename(terms_path, &migrated) {
+        Ok(()) => eprintln!("[VOCAB] Split {} terms into per-language stores", data.terms.len()),
+        Err(e) => eprintln!("[VOCAB] Could not move {:?} aside: {}", terms_path, e),
+    }
+}
+
+/// `existing` followed by the new tags in `added`, trimmed, without
+/// empty ones and without case-insensitive duplicates (first spelling wins)
+fn merge_tags(existing: &[String], added: &[String]) -> Vec<String> {
+    let mut tags: Vec<String> = Vec::new();
+    for tag in existing.iter().chain(added).map(|t| t.trim()) {
+        if !tag.is_empty() && !tags.iter().any(|t| t.to_lowercase() == tag.to_lowercase()) {
+            tags.push(tag.to_string());
+        }
+    }
+    tags
+}
+
+/// A term for `input`, created at `now`
+fn new_term(input: TermInput, now: i64) -> Term {
+    let mut contexts = Vec::new();
+    if let Some(context) = input.context.as_deref() {
+        add_context(&mut contexts, context, input.source.as_deref(), now);
+    }
+    Term {
+        id: format!("{}:{}:{}", input.languageId, input.text.to_lowercase(), now),
+        tags: merge_tags(&[], &input.tags),
+        text: input.text,
+        languageId: input.languageId,
+        translation: input.translation,
         status: input.status.unwrap_or(0),
-        notes: input.notes.clone(),
-        parentId: input.parentId.clone(),
-        image: input.image.clone(),
+        notes: input.notes,
+        parentId: input.parentId,
+        image: input.image,
+        sense_id: input.sense_id,
+        sense_fingerprint: input.sense_fingerprint,
         nextReview: input.nextReview.unwrap_or(now + 24 * 60 * 60 * 1000),
         lastReview: 0,
         interval: input.interval.unwrap_or(0),
@@ -222,86 +863,580 @@ pub async fn save_term(
         updatedAt: now,
         queryCount: 0,
         lastQueriedAt: None,
-    };
-    
-    data.terms.push(main_term.clone());
-    saved_terms.push(main_term.clone());
-    
-    // 2. Broadcast update
-    let _ = app.emit("term-update", TermUpdateEvent {
-        action: "add".to_string(),
-        term: main_term,
-        timestamp: now,
-    });
-    
-    // Save to file
-    data.updatedAt = now;
-    save_terms(&terms_path, &data)?;
-    
-    Ok(saved_terms)
+        source: input.source,
+        contexts,
+    }
 }
 
-/// Get all terms
-#[tauri::command]
-pub async fn get_all_terms(
-    state: State<'_, VocabularyState>,
-) -> Result<Vec<Term>, String> {
-    let terms_path = state.terms_path.lock().unwrap().clone();
-    let data = load_terms(&terms_path);
-    Ok(data.terms)
+/// Add a child of `parent` for each inflection whose text isn't saved in
+/// the language yet. Children share the parent's language and translation.
+fn add_inflections(data: &mut TermsData, parent: &Term, inflections: &[InflectionInput], now: i64) -> Vec<Term> {
+    let mut children = Vec::new();
+    for inflection in inflections {
+        let normalized = TermIndex::normalize(&inflection.text);
+        if normalized.is_empty()
+            || data.terms.iter().any(|t| t.languageId == parent.languageId && TermIndex::normalize(&t.text) == normalized)
+        {
+            continue;
+        }
+        let child = new_term(TermInput {
+            text: inflection.text.trim().to_string(),
+            languageId: parent.languageId.clone(),
+            translation: parent.translation.clone(),
+            notes: String::new(),
+            parentId: Some(parent.id.clone()),
+            image: None,
+            sense_id: None,
+            sense_fingerprint: None,
+            status: None,
+            nextReview: None,
+            interval: None,
+            easeFactor: None,
+            reps: None,
+            tags: inflection.tags.clone(),
+            inflections: Vec::new(),
+            context: None,
+            source: parent.source.clone(),
+        }, now);
+        data.terms.push(child.clone());
+        children.push(child);
+    }
+    children
 }
 
-/// Delete a term by ID
-#[tauri::command]
-pub async fn delete_term(
-    app: AppHandle,
-    state: State<'_, VocabularyState>,
-    id: String,
-) -> Result<(), String> {
-    let terms_path = state.terms_path.lock().unwrap().clone();
-    let mut data = load_terms(&terms_path);
-    
-    let index = data.terms.iter().position(|t| t.id == id)
-        .ok_or_else(|| "Term not found".to_string())?;
-    
-    let term = data.terms.remove(index);
-    
-    // Broadcast update
-    let _ = app.emit("term-update", TermUpdateEvent {
-        action: "delete".to_string(),
-        term,
-        timestamp: chrono::Utc::now().timestamp_millis(),
-    });
-    
-    data.updatedAt = chrono::Utc::now().timestamp_millis();
-    save_terms(&terms_path, &data)?;
-    
-    Ok(())
+/// `addition` appended to `existing` with `separator`, unless it is empty
+/// or already part of it
+fn append_field(existing: &str, addition: &str, separator: &str) -> String {
+    let addition = addition.trim();
+    if addition.is_empty() || existing.to_lowercase().contains(&addition.to_lowercase()) {
+        existing.to_string()
+    } else if existing.trim().is_empty() {
+        addition.to_string()
+    } else {
+        format!("{}{}{}", existing, separator, addition)
+    }
 }
 
-/// Update a term
-#[tauri::command]
-pub async fn update_term(
-    app: AppHandle,
-    state: State<'_, VocabularyState>,
-    id: String,
-    updates: TermUpdates,
-) -> Result<Term, String> {
+/// Fold another copy's translation, notes and tags into `term`
+fn merge_term_fields(term: &mut Term, translation: &str, notes: &str, tags: &[String]) {
+    term.translation = append_field(&term.translation, translation, "; ");
+    term.notes = append_field(&term.notes, notes, "\n");
+    term.tags = merge_tags(&term.tags, tags);
+}
+
+/// Fold a duplicate `other` into `term`: its translation, notes, tags and
+/// contexts are added, lookup counts summed, and the review state of
+/// whichever copy got further is kept
+fn merge_duplicate(term: &mut Term, other: &Term) {
+    merge_term_fields(term, &other.translation, &other.notes, &other.tags);
+    term.status = term.status.max(other.status);
+    term.queryCount += other.queryCount;
+    term.lastQueriedAt = term.lastQueriedAt.max(other.lastQueriedAt);
+    if term.image.is_none() {
+        term.image = other.image.clone();
+    }
+    if term.source.is_none() {
+        term.source = other.source.clone();
+    }
+    for context in &other.contexts {
+        add_context(&mut term.contexts, &context.text, context.source.as_deref(), context.added_at);
+    }
+    term.contexts.sort_by_key(|c| c.added_at);
+    if (other.reps, other.lastReview) > (term.reps, term.lastReview) {
+        term.nextReview = other.nextReview;
+        term.lastReview = other.lastReview;
+        term.interval = other.interval;
+        term.easeFactor = other.easeFactor;
+        term.reps = other.reps;
+    }
+}
+
+/// Context sentences kept per term; the oldest go first
+const MAX_TERM_CONTEXTS: usize = 5;
+/// Longer context is cut to this many characters
+const MAX_CONTEXT_CHARS: usize = 300;
+
+/// Append a context sentence unless it is empty or already kept (ignoring
+/// case and spacing). Returns whether anything was added.
+fn add_context(contexts: &mut Vec<TermContext>, text: &str, source: Option<&str>, now: i64) -> bool {
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    let text: String = text.chars().take(MAX_CONTEXT_CHARS).collect();
+    if text.is_empty() {
+        return false;
+    }
+    let key = text.to_lowercase();
+    if contexts.iter().any(|c| c.text.to_lowercase() == key) {
+        return false;
+    }
+    contexts.push(TermContext {
+        text,
+        source: source.map(str::to_string),
+        added_at: now,
+    });
+    if contexts.len() > MAX_TERM_CONTEXTS {
+        contexts.drain(..contexts.len() - MAX_TERM_CONTEXTS);
+    }
+    true
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct TermFilter {
+    pub language_id: Option<String>,
+    pub status: Option<i32>,
+    /// Case-insensitive substring of the text or translation
+    pub text: Option<String>,
+    /// Case-insensitive tag
+    pub tag: Option<String>,
+    /// Inclusive bounds on createdAt (ms)
+    pub created_after: Option<i64>,
+    pub created_before: Option<i64>,
+    /// Only terms due for review before this (ms)
+    pub due_before: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TermQueryResult {
+    pub terms: Vec<Term>,
+    /// Matching terms before `limit` and `offset`
+    pub total: usize,
+}
+
+/// The page of `terms` matching `filter`, see `query_terms`
+fn filter_terms(
+    terms: Vec<Term>,
+    filter: &TermFilter,
+    sort: Option<&str>,
+    limit: usize,
+    offset: usize,
+) -> Result<TermQueryResult, String> {
+    let text = filter.text.as_deref().map(|t| t.trim().to_lowercase()).filter(|t| !t.is_empty());
+    let tag = filter.tag.as_deref().map(|t| t.trim().to_lowercase());
+    let mut terms: Vec<Term> = terms
+        .into_iter()
+        .filter(|t| filter.language_id.as_deref().is_none_or(|lang| t.languageId == lang))
+        .filter(|t| filter.status.is_none_or(|status| t.status == status))
+        .filter(|t| {
+            text.as_deref().is_none_or(|needle| {
+                t.text.to_lowercase().contains(needle) || t.translation.to_lowercase().contains(needle)
+            })
+        })
+        .filter(|t| tag.as_deref().is_none_or(|tag| t.tags.iter().any(|own| own.to_lowercase() == tag)))
+        .filter(|t| filter.created_after.is_none_or(|after| t.createdAt >= after))
+        .filter(|t| filter.created_before.is_none_or(|before| t.createdAt <= before))
+        .filter(|t| filter.due_before.is_none_or(|before| t.nextReview < before))
+        .collect();
+
+    if let Some(sort) = sort {
+        let (key, direction) = sort.split_once(':').unwrap_or((sort, "asc"));
+        let descending = match direction {
+            "asc" => false,
+            "desc" => true,
+            other => return Err(format!("Unknown sort direction '{}', expected asc or desc", other)),
+        };
+        match key {
+            "createdAt" => terms.sort_by_key(|t| t.createdAt),
+            "updatedAt" => terms.sort_by_key(|t| t.updatedAt),
+            "nextReview" => terms.sort_by_key(|t| t.nextReview),
+            "text" => terms.sort_by_cached_key(|t| t.text.to_lowercase()),
+            "queryCount" => terms.sort_by_key(|t| t.queryCount),
+            other => return Err(format!("Unknown sort key '{}'", other)),
+        }
+        if descending {
+            terms.reverse();
+        }
+    }
+
+    let total = terms.len();
+    let terms = terms.into_iter().skip(offset).take(limit).collect();
+    Ok(TermQueryResult { terms, total })
+}
+
+#[derive(Debug, Serialize)]
+pub struct SaveTermResult {
+    pub success: bool,
+    pub terms: Vec<Term>,
+    /// A term with the same text already existed in this language
+    pub duplicate: bool,
+    /// The input was merged into that existing term
+    pub merged: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DedupeResult {
+    pub success: bool,
+    /// Terms folded into another and removed
+    pub merged: usize,
+    /// Ids of the terms that were kept
+    pub kept_ids: Vec<String>,
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Save a new term (supports root + inflection). A term whose text is
+/// already saved in the language is returned as a duplicate instead, or with
+/// `upsert` gets the new translation, notes and tags appended.
+#[tauri::command]
+pub async fn save_term(
+    app: AppHandle,
+    input: TermInput,
+    upsert: Option<bool>,
+) -> Result<SaveTermResult, String> {
+    spawn_write(app, move |app, state| save_term_blocking(app, state, input, upsert)).await
+}
+
+fn save_term_blocking(
+    app: &AppHandle,
+    state: &VocabularyState,
+    mut input: TermInput,
+    upsert: Option<bool>,
+) -> Result<SaveTermResult, String> {
+    let terms_path = state.terms_path.lock().unwrap().clone();
+    let language = input.languageId.clone();
+    let _guard = state.lock_language(&language);
+    let mut data = load_language(&terms_path, &language);
+    
+    let now = chrono::Utc::now().timestamp_millis();
+    let mut saved_terms = Vec::new();
+    let inflections = std::mem::take(&mut input.inflections);
+
+    let normalized = TermIndex::normalize(&input.text);
+    if let Some(existing) = data.terms.iter_mut()
+        .filter(|t| t.languageId == input.languageId && TermIndex::normalize(&t.text) == normalized)
+        .min_by_key(|t| t.createdAt)
+    {
+        // Meeting a saved word again in a new sentence is worth keeping
+        // even when nothing else is merged
+        let new_context = input.context.as_deref()
+            .is_some_and(|context| add_context(&mut existing.contexts, context, input.source.as_deref(), now));
+        if !upsert.unwrap_or(false) {
+            if new_context {
+                existing.updatedAt = now;
+                let term = existing.clone();
+                data.updatedAt = now;
+                save_language(&terms_path, &language, &data)?;
+                let _ = app.emit("term-update", TermUpdateEvent {
+                    action: "update".to_string(),
+                    term: term.clone(),
+                    timestamp: now,
+                });
+                return Ok(SaveTermResult {
+                    success: true,
+                    terms: vec![term],
+                    duplicate: true,
+                    merged: false,
+                });
+            }
+            return Ok(SaveTermResult {
+                success: true,
+                terms: vec![existing.clone()],
+                duplicate: true,
+                merged: false,
+            });
+        }
+
+        merge_term_fields(existing, &input.translation, &input.notes, &input.tags);
+        existing.updatedAt = now;
+        let term = existing.clone();
+        let children = add_inflections(&mut data, &term, &inflections, now);
+        data.updatedAt = now;
+        save_language(&terms_path, &language, &data)?;
+
+        let _ = app.emit("term-update", TermUpdateEvent {
+            action: "update".to_string(),
+            term: term.clone(),
+            timestamp: now,
+        });
+        for child in &children {
+            let _ = app.emit("term-update", TermUpdateEvent {
+                action: "add".to_string(),
+                term: child.clone(),
+                timestamp: now,
+            });
+        }
+        return Ok(SaveTermResult {
+            success: true,
+            terms: std::iter::once(term).chain(children).collect(),
+            duplicate: true,
+            merged: true,
+        });
+    }
+    
+    // 1. Save main term (root form), then its inflections
+    let mut main_term = new_term(input, now);
+    if let Some(source) = main_term.image.as_deref().and_then(local_image_source) {
+        main_term.image = Some(store_term_image(&terms_path, &main_term.id, &source)?);
+    }
+    data.terms.push(main_term.clone());
+    let children = add_inflections(&mut data, &main_term, &inflections, now);
+    saved_terms.push(main_term);
+    saved_terms.extend(children);
+    usage::record(usage::TERM_SAVED, Some(&saved_terms[0].languageId));
+    
+    // 2. Broadcast update
+    for term in &saved_terms {
+        let _ = app.emit("term-update", TermUpdateEvent {
+            action: "add".to_string(),
+            term: term.clone(),
+            timestamp: now,
+        });
+    }
+    
+    // Save to file
+    data.updatedAt = now;
+    save_language(&terms_path, &language, &data)?;
+    
+    Ok(SaveTermResult {
+        success: true,
+        terms: saved_terms,
+        duplicate: false,
+        merged: false,
+    })
+}
+
+/// Merge terms saved more than once (same language, same text ignoring
+/// case), optionally in one language. The oldest copy is kept; it gets the
+/// others' translations, notes and tags, the review state of whichever copy
+/// got furthest, and their lookup counts. Children are re-pointed to it.
+#[tauri::command]
+pub async fn dedupe_terms(
+    app: AppHandle,
+    language: Option<String>,
+) -> Result<DedupeResult, String> {
+    spawn_write(app, move |app, state| dedupe_terms_blocking(app, state, language)).await
+}
+
+fn dedupe_terms_blocking(
+    app: &AppHandle,
+    state: &VocabularyState,
+    language: Option<String>,
+) -> Result<DedupeResult, String> {
     let terms_path = state.terms_path.lock().unwrap().clone();
+    let _guard = state.lock_all_languages();
     let mut data = load_terms(&terms_path);
+    let now = chrono::Utc::now().timestamp_millis();
+
+    let mut groups: HashMap<(String, String), Vec<usize>> = HashMap::new();
+    for (i, term) in data.terms.iter().enumerate() {
+        if language.as_deref().is_none_or(|lang| term.languageId == lang) {
+            groups
+                .entry((term.languageId.clone(), TermIndex::normalize(&term.text)))
+                .or_default()
+                .push(i);
+        }
+    }
+
+    let mut batch = BulkEventBatch::new(app, "dedupe", None);
+    let mut removed: HashMap<String, String> = HashMap::new();
+    let mut kept_ids = Vec::new();
+    for mut indices in groups.into_values().filter(|g| g.len() > 1) {
+        indices.sort_by_key(|&i| (data.terms[i].createdAt, i));
+        let keep = indices[0];
+        for &i in &indices[1..] {
+            let other = data.terms[i].clone();
+            merge_duplicate(&mut data.terms[keep], &other);
+            removed.insert(other.id.clone(), data.terms[keep].id.clone());
+            batch.record(&other.id);
+        }
+
+        let term = &mut data.terms[keep];
+        term.updatedAt = now;
+        kept_ids.push(term.id.clone());
+        batch.record(&term.id);
+    }
+
+    if removed.is_empty() {
+        batch.finish();
+        return Ok(DedupeResult { success: true, merged: 0, kept_ids });
+    }
+
+    data.terms.retain(|t| !removed.contains_key(&t.id));
+    for term in &mut data.terms {
+        if let Some(kept) = term.parentId.as_ref().and_then(|parent| removed.get(parent)) {
+            term.parentId = Some(kept.clone());
+            term.updatedAt = now;
+        }
+    }
+    data.updatedAt = now;
+    if let Err(e) = save_terms(&terms_path, &data) {
+        batch.fail(&e);
+        return Err(e);
+    }
+    batch.finish();
+
+    eprintln!("[VOCAB] Merged {} duplicate terms into {}", removed.len(), kept_ids.len());
+    Ok(DedupeResult {
+        success: true,
+        merged: removed.len(),
+        kept_ids,
+    })
+}
+
+/// Get all terms
+#[tauri::command]
+pub async fn get_all_terms(
+    state: State<'_, VocabularyState>,
+) -> Result<Vec<Term>, String> {
+    let terms_path = state.terms_path.lock().unwrap().clone();
+    let data = load_terms(&terms_path);
+    Ok(filter_terms(data.terms, &TermFilter::default(), None, usize::MAX, 0)?.terms)
+}
+
+/// One page of terms matching `filter`, sorted by `sort` ("createdAt",
+/// "updatedAt", "nextReview", "text" or "queryCount", optionally followed
+/// by ":asc" or ":desc"; stored order when absent)
+#[tauri::command]
+pub async fn query_terms(
+    state: State<'_, VocabularyState>,
+    filter: TermFilter,
+    sort: Option<String>,
+    limit: usize,
+    offset: usize,
+) -> Result<TermQueryResult, String> {
+    let terms_path = state.terms_path.lock().unwrap().clone();
+    let data = load_scope(&terms_path, filter.language_id.as_deref());
+    filter_terms(data.terms, &filter, sort.as_deref(), limit, offset)
+}
+
+/// Delete a term by ID, together with its inflection children, or with
+/// `orphan_children` leaving them as roots of their own. Review history of
+/// deleted terms is dropped unless `keep_history` keeps it anonymized.
+#[tauri::command]
+pub async fn delete_term(
+    app: AppHandle,
+    id: String,
+    orphan_children: Option<bool>,
+    keep_history: Option<bool>,
+) -> Result<(), String> {
+    spawn_write(app, move |app, state| delete_term_blocking(app, state, id, orphan_children, keep_history)).await
+}
+
+fn delete_term_blocking(
+    app: &AppHandle,
+    state: &VocabularyState,
+    id: String,
+    orphan_children: Option<bool>,
+    keep_history: Option<bool>,
+) -> Result<(), String> {
+    let terms_path = state.terms_path.lock().unwrap().clone();
+    let language = term_language(&terms_path, &id).ok_or_else(|| "Term not found".to_string())?;
+    let _guard = state.lock_language(&language);
+    let mut data = load_language(&terms_path, &language);
+    let now = chrono::Utc::now().timestamp_millis();
     
-    let index = data.terms.iter_mut()
-        .position(|t| t.id == id)
+    let index = data.terms.iter().position(|t| t.id == id)
         .ok_or_else(|| "Term not found".to_string())?;
     
-    let term = &mut data.terms[index];
+    let term = data.terms.remove(index);
+    let mut events = vec![("delete", term)];
+
+    if orphan_children.unwrap_or(false) {
+        for child in data.terms.iter_mut().filter(|t| t.parentId.as_deref() == Some(id.as_str())) {
+            child.parentId = None;
+            child.updatedAt = now;
+            events.push(("update", child.clone()));
+        }
+    } else {
+        // Children of children go too
+        let mut removed = vec![id];
+        while let Some(parent) = removed.pop() {
+            while let Some(i) = data.terms.iter().position(|t| t.parentId.as_deref() == Some(parent.as_str())) {
+                let child = data.terms.remove(i);
+                removed.push(child.id.clone());
+                events.push(("delete", child));
+            }
+        }
+    }
+    
+    data.updatedAt = now;
+    save_language(&terms_path, &language, &data)?;
+    for (_, term) in events.iter().filter(|(action, _)| *action == "delete") {
+        remove_term_image(&terms_path, term);
+        review_log::forget_term(&term.id, keep_history.unwrap_or(false));
+    }
+
+    // Broadcast update
+    for (action, term) in events {
+        let _ = app.emit("term-update", TermUpdateEvent {
+            action: action.to_string(),
+            term,
+            timestamp: now,
+        });
+    }
     
-    // Apply updates
-    if let Some(translation) = updates.translation {
-        term.translation = translation;
+    Ok(())
+}
+
+#[derive(Debug, Serialize)]
+pub struct TermFamily {
+    pub root: Term,
+    /// Inflections of the root, oldest first
+    pub children: Vec<Term>,
+}
+
+/// The root of the term's word family with its inflections. Works from the
+/// root or from any of its children.
+#[tauri::command]
+pub async fn get_term_family(
+    state: State<'_, VocabularyState>,
+    id: String,
+) -> Result<TermFamily, String> {
+    let terms_path = state.terms_path.lock().unwrap().clone();
+    let language = term_language(&terms_path, &id).ok_or_else(|| "Term not found".to_string())?;
+    let data = load_language(&terms_path, &language);
+    let by_id: HashMap<&str, &Term> = data.terms.iter().map(|t| (t.id.as_str(), t)).collect();
+
+    let mut root = *by_id.get(id.as_str()).ok_or_else(|| "Term not found".to_string())?;
+    // Bounded in case a broken store has a parent cycle
+    for _ in 0..by_id.len() {
+        match root.parentId.as_deref().and_then(|parent| by_id.get(parent)) {
+            Some(parent) => root = parent,
+            None => break,
+        }
     }
-    if let Some(notes) = updates.notes {
-        term.notes = notes;
+
+    let mut children: Vec<Term> = data.terms.iter()
+        .filter(|t| t.parentId.as_deref() == Some(root.id.as_str()))
+        .cloned()
+        .collect();
+    children.sort_by_key(|t| t.createdAt);
+    Ok(TermFamily {
+        root: root.clone(),
+        children,
+    })
+}
+
+/// Sentences the term was met in, oldest first
+#[tauri::command]
+pub async fn get_term_contexts(
+    state: State<'_, VocabularyState>,
+    id: String,
+) -> Result<Vec<TermContext>, String> {
+    let terms_path = state.terms_path.lock().unwrap().clone();
+    let language = term_language(&terms_path, &id).ok_or_else(|| "Term not found".to_string())?;
+    load_language(&terms_path, &language)
+        .terms
+        .into_iter()
+        .find(|t| t.id == id)
+        .map(|t| t.contexts)
+        .ok_or_else(|| "Term not found".to_string())
+}
+
+/// Set the fields `updates` carries
+fn apply_updates(term: &mut Term, updates: &TermUpdates) {
+    if let Some(translation) = &updates.translation {
+        term.translation = translation.clone();
+    }
+    if let Some(notes) = &updates.notes {
+        term.notes = notes.clone();
+    }
+    if let Some(sense_id) = &updates.sense_id {
+        term.sense_id = Some(sense_id.clone());
+    }
+    if let Some(fingerprint) = &updates.sense_fingerprint {
+        term.sense_fingerprint = Some(fingerprint.clone());
     }
     if let Some(status) = updates.status {
         term.status = status;
@@ -317,9 +1452,54 @@ pub async fn update_term(
     }
     if let Some(reps) = updates.reps {
         term.reps = reps;
+        usage::record(usage::REVIEW_ANSWERED, Some(&term.languageId));
     }
+    if let Some(tags) = &updates.tags {
+        term.tags = merge_tags(&[], tags);
+    }
+}
+
+/// Update a term
+#[tauri::command]
+pub async fn update_term(
+    app: AppHandle,
+    id: String,
+    updates: TermUpdates,
+) -> Result<Term, String> {
+    spawn_write(app, move |app, state| update_term_blocking(app, state, id, updates)).await
+}
+
+fn update_term_blocking(
+    app: &AppHandle,
+    state: &VocabularyState,
+    id: String,
+    updates: TermUpdates,
+) -> Result<Term, String> {
+    let terms_path = state.terms_path.lock().unwrap().clone();
+    let language = term_language(&terms_path, &id).ok_or_else(|| "Term not found".to_string())?;
+    let _guard = state.lock_language(&language);
+    let mut data = load_language(&terms_path, &language);
+    
+    let index = data.terms.iter_mut()
+        .position(|t| t.id == id)
+        .ok_or_else(|| "Term not found".to_string())?;
     
+    let term = &mut data.terms[index];
+    let schedule = (term.interval, term.easeFactor, term.nextReview, term.reps);
+    apply_updates(term, &updates);
     term.updatedAt = chrono::Utc::now().timestamp_millis();
+    let rescheduled = schedule != (term.interval, term.easeFactor, term.nextReview, term.reps);
+    if rescheduled {
+        review_log::record(ReviewEvent {
+            term_id: term.id.clone(),
+            language: term.languageId.clone(),
+            timestamp: term.updatedAt,
+            grade: None,
+            previous_interval: schedule.0,
+            interval: term.interval,
+            ease_factor: term.easeFactor,
+        });
+    }
     let term_clone = term.clone();
     
     // Broadcast update
@@ -330,15 +1510,2214 @@ pub async fn update_term(
     });
     
     data.updatedAt = chrono::Utc::now().timestamp_millis();
-    save_terms(&terms_path, &data)?;
+    save_language(&terms_path, &language, &data)?;
+    if rescheduled {
+        check_daily_goal(app, "reviews");
+    }
     
     Ok(term_clone)
 }
 
-/// Initialize vocabulary state
-pub fn init_vocabulary_state(app: &AppHandle) -> VocabularyState {
-    let terms_path = get_terms_path(app);
-    VocabularyState {
-        terms_path: Mutex::new(terms_path),
+#[derive(Debug, Serialize)]
+pub struct BulkUpdateResult {
+    pub success: bool,
+    /// The terms after the update
+    pub terms: Vec<Term>,
+    pub not_found: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BulkDeleteResult {
+    pub success: bool,
+    pub deleted: usize,
+    pub not_found: Vec<String>,
+    /// Surviving children of deleted terms, now roots of their own
+    pub orphaned: Vec<String>,
+}
+
+/// Apply the same `updates` to every term in `ids` with one save and one
+/// `terms-bulk-update` event. Unknown ids are reported, not fatal.
+#[tauri::command]
+pub async fn bulk_update_terms(
+    app: AppHandle,
+    ids: Vec<String>,
+    updates: TermUpdates,
+) -> Result<BulkUpdateResult, String> {
+    spawn_write(app, move |app, state| bulk_update_terms_blocking(app, state, ids, updates)).await
+}
+
+fn bulk_update_terms_blocking(
+    app: &AppHandle,
+    state: &VocabularyState,
+    ids: Vec<String>,
+    updates: TermUpdates,
+) -> Result<BulkUpdateResult, String> {
+    let terms_path = state.terms_path.lock().unwrap().clone();
+    let _guard = state.lock_all_languages();
+    let mut data = load_terms(&terms_path);
+    let now = chrono::Utc::now().timestamp_millis();
+
+    let mut batch = BulkEventBatch::new(app, "update", Some(ids.len()));
+    let positions: HashMap<&str, usize> = data.terms.iter()
+        .enumerate()
+        .map(|(i, t)| (t.id.as_str(), i))
+        .collect();
+    let mut found = Vec::new();
+    let mut not_found = Vec::new();
+    for id in &ids {
+        match positions.get(id.as_str()) {
+            Some(&i) if !found.contains(&i) => found.push(i),
+            Some(_) => {}
+            None => not_found.push(id.clone()),
+        }
+    }
+
+    let mut terms = Vec::with_capacity(found.len());
+    for i in found {
+        let term = &mut data.terms[i];
+        apply_updates(term, &updates);
+        term.updatedAt = now;
+        batch.record(&term.id);
+        terms.push(term.clone());
+    }
+
+    if !terms.is_empty() {
+        data.updatedAt = now;
+        if let Err(e) = save_terms(&terms_path, &data) {
+            batch.fail(&e);
+            return Err(e);
+        }
+    }
+    batch.finish();
+
+    Ok(BulkUpdateResult {
+        success: true,
+        terms,
+        not_found,
+    })
+}
+
+/// Delete every term in `ids` with one save and one `terms-bulk-update`
+/// event. Unknown ids are reported, not fatal. Children of deleted terms
+/// that weren't deleted themselves lose their `parentId` in the same save.
+#[tauri::command]
+pub async fn bulk_delete_terms(
+    app: AppHandle,
+    ids: Vec<String>,
+) -> Result<BulkDeleteResult, String> {
+    spawn_write(app, move |app, state| bulk_delete_terms_blocking(app, state, ids)).await
+}
+
+fn bulk_delete_terms_blocking(
+    app: &AppHandle,
+    state: &VocabularyState,
+    ids: Vec<String>,
+) -> Result<BulkDeleteResult, String> {
+    let terms_path = state.terms_path.lock().unwrap().clone();
+    let _guard = state.lock_all_languages();
+    let mut data = load_terms(&terms_path);
+
+    let mut batch = BulkEventBatch::new(app, "delete", Some(ids.len()));
+    let not_found: Vec<String> = {
+        let existing: std::collections::HashSet<&str> = data.terms.iter().map(|t| t.id.as_str()).collect();
+        ids.iter().filter(|id| !existing.contains(id.as_str())).cloned().collect()
+    };
+    let wanted: std::collections::HashSet<&str> = ids.iter().map(String::as_str).collect();
+    let (removed, kept): (Vec<Term>, Vec<Term>) = std::mem::take(&mut data.terms)
+        .into_iter()
+        .partition(|t| wanted.contains(t.id.as_str()));
+    data.terms = kept;
+    let deleted = removed.len();
+    let now = chrono::Utc::now().timestamp_millis();
+    let orphaned = detach_children(&mut data.terms, &wanted, now);
+
+    if deleted > 0 {
+        data.updatedAt = now;
+        if let Err(e) = save_terms(&terms_path, &data) {
+            batch.fail(&e);
+            return Err(e);
+        }
+    }
+    for term in &removed {
+        remove_term_image(&terms_path, term);
+        review_log::forget_term(&term.id, false);
+        batch.record(&term.id);
+    }
+    batch.finish();
+    let orphaned_ids = orphaned.iter().map(|t| t.id.clone()).collect();
+    for term in orphaned {
+        let _ = app.emit("term-update", TermUpdateEvent {
+            action: "update".to_string(),
+            term,
+            timestamp: now,
+        });
+    }
+
+    Ok(BulkDeleteResult {
+        success: true,
+        deleted,
+        not_found,
+        orphaned: orphaned_ids,
+    })
+}
+
+/// Clear `parentId` on terms whose parent is in `deleted`, returning the
+/// updated terms
+fn detach_children(terms: &mut [Term], deleted: &HashSet<&str>, now: i64) -> Vec<Term> {
+    terms
+        .iter_mut()
+        .filter(|t| t.parentId.as_deref().is_some_and(|parent| deleted.contains(parent)))
+        .map(|child| {
+            child.parentId = None;
+            child.updatedAt = now;
+            child.clone()
+        })
+        .collect()
+}
+
+/// Change a term's tags with `edit`, save, and broadcast the term
+fn edit_term_tags(
+    app: &AppHandle,
+    state: &VocabularyState,
+    id: &str,
+    edit: impl FnOnce(&[String]) -> Vec<String>,
+) -> Result<Term, String> {
+    let terms_path = state.terms_path.lock().unwrap().clone();
+    let language = term_language(&terms_path, id).ok_or_else(|| "Term not found".to_string())?;
+    let _guard = state.lock_language(&language);
+    let mut data = load_language(&terms_path, &language);
+    let term = data.terms.iter_mut()
+        .find(|t| t.id == id)
+        .ok_or_else(|| "Term not found".to_string())?;
+
+    let now = chrono::Utc::now().timestamp_millis();
+    term.tags = edit(&term.tags);
+    term.updatedAt = now;
+    let term_clone = term.clone();
+
+    data.updatedAt = now;
+    save_language(&terms_path, &language, &data)?;
+    let _ = app.emit("term-update", TermUpdateEvent {
+        action: "update".to_string(),
+        term: term_clone.clone(),
+        timestamp: now,
+    });
+    Ok(term_clone)
+}
+
+/// Add tags to a term; ones it already has (in any case) are skipped
+#[tauri::command]
+pub async fn add_term_tags(
+    app: AppHandle,
+    id: String,
+    tags: Vec<String>,
+) -> Result<Term, String> {
+    spawn_write(app, move |app, state| add_term_tags_blocking(app, state, id, tags)).await
+}
+
+fn add_term_tags_blocking(
+    app: &AppHandle,
+    state: &VocabularyState,
+    id: String,
+    tags: Vec<String>,
+) -> Result<Term, String> {
+    edit_term_tags(app, state, &id, |existing| merge_tags(existing, &tags))
+}
+
+/// Remove a tag from a term, matching case-insensitively
+#[tauri::command]
+pub async fn remove_term_tag(
+    app: AppHandle,
+    id: String,
+    tag: String,
+) -> Result<Term, String> {
+    spawn_write(app, move |app, state| remove_term_tag_blocking(app, state, id, tag)).await
+}
+
+fn remove_term_tag_blocking(
+    app: &AppHandle,
+    state: &VocabularyState,
+    id: String,
+    tag: String,
+) -> Result<Term, String> {
+    let tag = tag.trim().to_lowercase();
+    edit_term_tags(app, state, &id, |existing| {
+        existing.iter().filter(|t| t.to_lowercase() != tag).cloned().collect()
+    })
+}
+
+/// Most hits `search_terms` returns
+const SEARCH_RESULT_LIMIT: usize = 100;
+const SEARCH_FIELDS: &[&str] = &["text", "translation", "notes", "tags"];
+
+#[derive(Debug, Serialize)]
+pub struct TermSearchHit {
+    pub term: Term,
+    /// "text", "translation", "notes" or "tags"
+    pub matched_field: String,
+    /// "exact", "prefix" or "substring"
+    pub match_kind: String,
+}
+
+/// `value` and `query` compared under the language's dictionary
+/// normalization and with all diacritics folded: 0 for equal, 1 for a
+/// prefix, 2 for a substring
+fn match_rank(value: &str, query: &str, folded_query: &str, language: &str) -> Option<u8> {
+    let keyed = normalize::normalize_word(value, language);
+    let folded = normalize::fold_marks(value);
+    let query = normalize::normalize_word(query, language);
+    [(keyed, query.as_str()), (folded, folded_query)]
+        .iter()
+        .filter(|(_, q)| !q.is_empty())
+        .filter_map(|(v, q)| {
+            if v == q {
+                Some(0)
+            } else if v.starts_with(q) {
+                Some(1)
+            } else if v.contains(q) {
+                Some(2)
+            } else {
+                None
+            }
+        })
+        .min()
+}
+
+/// Search saved terms' text, translation, notes and tags (or only `fields`)
+/// for `query`. Exact text matches come first, then prefixes, then
+/// substrings; at most 100 hits.
+#[tauri::command]
+pub async fn search_terms(
+    state: State<'_, VocabularyState>,
+    query: String,
+    language: Option<String>,
+    fields: Option<Vec<String>>,
+) -> Result<Vec<TermSearchHit>, String> {
+    let fields: Vec<&str> = match &fields {
+        Some(fields) => {
+            if let Some(unknown) = fields.iter().find(|f| !SEARCH_FIELDS.contains(&f.as_str())) {
+                return Err(format!("Unknown field '{}', expected one of {}", unknown, SEARCH_FIELDS.join(", ")));
+            }
+            fields.iter().map(String::as_str).collect()
+        }
+        None => SEARCH_FIELDS.to_vec(),
+    };
+    let query = query.trim();
+    if query.is_empty() {
+        return Ok(Vec::new());
+    }
+    let folded_query = normalize::fold_marks(query);
+
+    let terms_path = state.terms_path.lock().unwrap().clone();
+    let data = load_scope(&terms_path, language.as_deref());
+    let mut hits: Vec<(u8, usize, TermSearchHit)> = Vec::new();
+    for term in data.terms.into_iter()
+        .filter(|t| language.as_deref().is_none_or(|lang| t.languageId == lang))
+    {
+        let lang = term.languageId.as_str();
+        // Whole-value matches only rank above substrings for the text itself
+        let best = fields
+            .iter()
+            .enumerate()
+            .filter_map(|(order, &field)| {
+                let rank = match field {
+                    "text" => match_rank(&term.text, query, &folded_query, lang),
+                    "translation" => match_rank(&term.translation, query, &folded_query, lang).map(|_| 2),
+                    "notes" => match_rank(&term.notes, query, &folded_query, lang).map(|_| 2),
+                    _ => term.tags.iter().filter_map(|t| match_rank(t, query, &folded_query, lang)).min().map(|_| 2),
+                }?;
+                Some((rank, order, field))
+            })
+            .min();
+        if let Some((rank, order, field)) = best {
+            let match_kind = match (field, rank) {
+                ("text", 0) => "exact",
+                ("text", 1) => "prefix",
+                _ => "substring",
+            };
+            hits.push((rank, order, TermSearchHit {
+                term,
+                matched_field: field.to_string(),
+                match_kind: match_kind.to_string(),
+            }));
+        }
+    }
+
+    hits.sort_by(|(rank_a, order_a, a), (rank_b, order_b, b)| {
+        (rank_a, order_a, a.term.text.chars().count(), a.term.text.to_lowercase())
+            .cmp(&(rank_b, order_b, b.term.text.chars().count(), b.term.text.to_lowercase()))
+    });
+    Ok(hits.into_iter().take(SEARCH_RESULT_LIMIT).map(|(_, _, hit)| hit).collect())
+}
+
+/// Terms carrying `tag` (case-insensitively), optionally in one language
+#[tauri::command]
+pub async fn get_terms_by_tag(
+    state: State<'_, VocabularyState>,
+    tag: String,
+    language: Option<String>,
+) -> Result<Vec<Term>, String> {
+    let terms_path = state.terms_path.lock().unwrap().clone();
+    let tag = tag.trim().to_lowercase();
+    Ok(load_scope(&terms_path, language.as_deref())
+        .terms
+        .into_iter()
+        .filter(|t| t.tags.iter().any(|own| own.to_lowercase() == tag))
+        .filter(|t| language.as_deref().is_none_or(|lang| t.languageId == lang))
+        .collect())
+}
+
+const DAY_MS: i64 = 24 * 60 * 60 * 1000;
+/// SM-2 never lets a card get easier to forget than this
+const MIN_EASE_FACTOR: f64 = 1.3;
+
+/// Apply one SM-2 review with quality `grade` (0–5) at `now`. A grade below
+/// 3 starts the card over at one day; otherwise the interval goes 1, 6,
+/// then grows by the ease factor. Returns the new interval in days.
+fn apply_sm2(term: &mut Term, grade: u8, now: i64) -> i32 {
+    let q = grade as f64;
+    term.easeFactor = (term.easeFactor + (0.1 - (5.0 - q) * (0.08 + (5.0 - q) * 0.02))).max(MIN_EASE_FACTOR);
+    term.interval = if grade < 3 {
+        1
+    } else {
+        match term.interval {
+            i if i < 1 => 1,
+            1 => 6,
+            i => (i as f64 * term.easeFactor).round() as i32,
+        }
+    };
+    term.reps += 1;
+    term.lastReview = now;
+    term.nextReview = now + term.interval as i64 * DAY_MS;
+    term.updatedAt = now;
+    term.interval
+}
+
+/// Terms whose review is due, most overdue first
+#[tauri::command]
+pub async fn get_due_terms(
+    state: State<'_, VocabularyState>,
+    language: Option<String>,
+    limit: usize,
+) -> Result<Vec<Term>, String> {
+    let terms_path = state.terms_path.lock().unwrap().clone();
+    let now = chrono::Utc::now().timestamp_millis();
+    let mut due: Vec<Term> = load_scope(&terms_path, language.as_deref())
+        .terms
+        .into_iter()
+        .filter(|t| t.nextReview <= now)
+        .filter(|t| language.as_deref().is_none_or(|lang| t.languageId == lang))
+        .collect();
+    due.sort_by_key(|t| t.nextReview);
+    due.truncate(limit);
+    Ok(due)
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GradeResult {
+    pub term: Term,
+    /// Days until the next review
+    pub interval_days: i32,
+    pub next_review: i64,
+}
+
+/// Record a review of a term graded 0 (blackout) to 5 (perfect) and
+/// schedule the next one with SM-2. Terms that aren't due can be graded too.
+#[tauri::command]
+pub async fn grade_term(
+    app: AppHandle,
+    id: String,
+    grade: u8,
+) -> Result<GradeResult, String> {
+    spawn_write(app, move |app, state| grade_term_blocking(app, state, id, grade)).await
+}
+
+fn grade_term_blocking(
+    app: &AppHandle,
+    state: &VocabularyState,
+    id: String,
+    grade: u8,
+) -> Result<GradeResult, String> {
+    if grade > 5 {
+        return Err(format!("Grade must be between 0 and 5, got {}", grade));
+    }
+    let terms_path = state.terms_path.lock().unwrap().clone();
+    let language = term_language(&terms_path, &id).ok_or_else(|| "Term not found".to_string())?;
+    let _guard = state.lock_language(&language);
+    let mut data = load_language(&terms_path, &language);
+
+    let term = data.terms.iter_mut()
+        .find(|t| t.id == id)
+        .ok_or_else(|| "Term not found".to_string())?;
+    let now = chrono::Utc::now().timestamp_millis();
+    let previous_interval = term.interval;
+    let interval_days = apply_sm2(term, grade, now);
+    usage::record(usage::REVIEW_ANSWERED, Some(&term.languageId));
+    review_log::record(ReviewEvent {
+        term_id: term.id.clone(),
+        language: term.languageId.clone(),
+        timestamp: now,
+        grade: Some(grade),
+        previous_interval,
+        interval: term.interval,
+        ease_factor: term.easeFactor,
+    });
+    let term_clone = term.clone();
+
+    data.updatedAt = now;
+    save_language(&terms_path, &language, &data)?;
+
+    let _ = app.emit("term-update", TermUpdateEvent {
+        action: "update".to_string(),
+        term: term_clone.clone(),
+        timestamp: now,
+    });
+    check_daily_goal(app, "reviews");
+
+    Ok(GradeResult {
+        next_review: term_clone.nextReview,
+        term: term_clone,
+        interval_days,
+    })
+}
+
+/// Days of review forecast after today
+const FORECAST_DAYS: i64 = 7;
+/// Days of "terms added" history, today included
+const ADDED_HISTORY_DAYS: i64 = 30;
+
+#[derive(Debug, Default, Serialize)]
+pub struct StatusCounts {
+    /// Status 0
+    pub new: usize,
+    /// Statuses 1-4
+    pub learning: usize,
+    /// Status 5
+    pub mastered: usize,
+    /// Status 99
+    pub ignored: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DailyCount {
+    /// Local calendar day, "YYYY-MM-DD"
+    pub date: String,
+    pub count: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct VocabularyStats {
+    pub success: bool,
+    pub total: usize,
+    pub by_status: StatusCounts,
+    pub by_language: std::collections::BTreeMap<String, usize>,
+    /// Due by the end of today, overdue included
+    pub due_today: usize,
+    /// Today (overdue included) and the following days
+    pub forecast: Vec<DailyCount>,
+    /// Oldest day first, ending today
+    pub added_per_day: Vec<DailyCount>,
+    /// Over terms reviewed at least once
+    pub average_ease_factor: Option<f64>,
+}
+
+/// The calendar day `ms` falls on in `tz`
+fn day_in<Tz: chrono::TimeZone>(ms: i64, tz: &Tz) -> Option<chrono::NaiveDate> {
+    chrono::DateTime::from_timestamp_millis(ms).map(|t| t.with_timezone(tz).date_naive())
+}
+
+/// Counts, review forecast and recent additions for the dashboard,
+/// optionally for one language. Days are local calendar days.
+#[tauri::command]
+pub async fn get_vocabulary_stats(
+    state: State<'_, VocabularyState>,
+    language: Option<String>,
+) -> Result<VocabularyStats, String> {
+    let terms_path = state.terms_path.lock().unwrap().clone();
+    let data = load_scope(&terms_path, language.as_deref());
+    Ok(vocabulary_stats(&data.terms, language.as_deref(), &chrono::Local::now()))
+}
+
+/// `get_vocabulary_stats` for `terms` as of `now`, days taken in `now`'s timezone
+fn vocabulary_stats<Tz: chrono::TimeZone>(
+    terms: &[Term],
+    language: Option<&str>,
+    now: &chrono::DateTime<Tz>,
+) -> VocabularyStats {
+    let tz = now.timezone();
+    let today = now.date_naive();
+
+    let mut stats = VocabularyStats {
+        success: true,
+        total: 0,
+        by_status: StatusCounts::default(),
+        by_language: std::collections::BTreeMap::new(),
+        due_today: 0,
+        forecast: Vec::new(),
+        added_per_day: Vec::new(),
+        average_ease_factor: None,
+    };
+    let mut due = vec![0usize; FORECAST_DAYS as usize + 1];
+    let mut added = vec![0usize; ADDED_HISTORY_DAYS as usize];
+    let mut ease_sum = 0.0;
+    let mut reviewed = 0;
+
+    for term in terms.iter().filter(|t| language.is_none_or(|lang| t.languageId == lang)) {
+        stats.total += 1;
+        *stats.by_language.entry(term.languageId.clone()).or_insert(0) += 1;
+        match term.status {
+            0 => stats.by_status.new += 1,
+            1..=4 => stats.by_status.learning += 1,
+            99 => stats.by_status.ignored += 1,
+            _ => stats.by_status.mastered += 1,
+        }
+
+        if term.status != 99 {
+            if let Some(day) = day_in(term.nextReview, &tz) {
+                // Overdue terms are due today
+                let offset = (day - today).num_days().max(0);
+                if offset <= FORECAST_DAYS {
+                    due[offset as usize] += 1;
+                }
+            }
+        }
+        if let Some(day) = day_in(term.createdAt, &tz) {
+            let age = (today - day).num_days();
+            if (0..ADDED_HISTORY_DAYS).contains(&age) {
+                added[(ADDED_HISTORY_DAYS - 1 - age) as usize] += 1;
+            }
+        }
+        if term.reps > 0 {
+            ease_sum += term.easeFactor;
+            reviewed += 1;
+        }
+    }
+
+    let date = |offset: i64| (today + chrono::Duration::days(offset)).format("%Y-%m-%d").to_string();
+    stats.due_today = due[0];
+    stats.forecast = due
+        .into_iter()
+        .enumerate()
+        .map(|(i, count)| DailyCount { date: date(i as i64), count })
+        .collect();
+    stats.added_per_day = added
+        .into_iter()
+        .enumerate()
+        .map(|(i, count)| DailyCount { date: date(i as i64 - (ADDED_HISTORY_DAYS - 1)), count })
+        .collect();
+    stats.average_ease_factor = (reviewed > 0).then(|| ease_sum / reviewed as f64);
+    stats
+}
+
+/// Review events, newest first, optionally for one term and from `since`
+/// (ms) on. Raw events go back a year.
+#[tauri::command]
+pub async fn get_review_log(
+    term_id: Option<String>,
+    since: Option<i64>,
+    limit: Option<usize>,
+) -> Result<Vec<ReviewEvent>, String> {
+    Ok(review_log::list(
+        term_id.as_deref(),
+        since,
+        limit.unwrap_or(100).clamp(1, 10_000),
+    ))
+}
+
+/// Reviews and accuracy per local day for the last `days` days, oldest first
+#[tauri::command]
+pub async fn get_reviews_per_day(days: Option<u32>) -> Result<Vec<DailyReviews>, String> {
+    Ok(review_log::per_day(days.unwrap_or(30).clamp(1, 3660)))
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReviewCard {
+    pub term: Term,
+    pub entries: Vec<DictionaryEntry>,
+    /// Whether the term's linked sense was found and moved to the front
+    pub hint_applied: bool,
+}
+
+/// Get a term together with its dictionary entries for review, leading with
+/// the sense the term was learned with
+#[tauri::command]
+pub async fn get_review_card(
+    state: State<'_, VocabularyState>,
+    id: String,
+) -> Result<ReviewCard, String> {
+    let terms_path = state.terms_path.lock().unwrap().clone();
+    let language = term_language(&terms_path, &id).ok_or_else(|| "Term not found".to_string())?;
+    let data = load_language(&terms_path, &language);
+    let term = data.terms.into_iter()
+        .find(|t| t.id == id)
+        .ok_or_else(|| "Term not found".to_string())?;
+
+    let sense_ordering = if term.sense_id.is_some() || term.sense_fingerprint.is_some() {
+        Some(SenseOrderHint {
+            sense_id: term.sense_id.clone(),
+            gloss_fingerprint: term.sense_fingerprint.clone(),
+        })
+    } else {
+        None
+    };
+    let options = SearchOptions {
+        sense_ordering,
+        ..Default::default()
+    };
+
+    let entries = db::search_dictionary_with(&term.text, &term.languageId, &options)
+        .unwrap_or_default();
+    let hint_applied = entries.iter().any(|e| e.hint_applied == Some(true));
+
+    Ok(ReviewCard {
+        term,
+        entries,
+        hint_applied,
+    })
+}
+
+// ============================================================================
+// Streaks and daily goal
+// ============================================================================
+
+#[derive(Debug, Serialize)]
+pub struct StreakInfo {
+    pub success: bool,
+    /// Consecutive local days with a review or a saved term, up to today;
+    /// still running when only yesterday was active so far
+    pub current_streak: u32,
+    pub longest_streak: u32,
+    /// Local calendar day the counts are for, "YYYY-MM-DD"
+    pub today: String,
+    pub new_terms_today: u32,
+    pub reviews_today: u32,
+    pub goal: DailyGoal,
+    pub goal_reached: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DailyGoalReachedEvent {
+    pub date: String,
+    /// "newTerms" or "reviews"
+    pub kind: String,
+    pub count: u32,
+    pub goal: u32,
+}
+
+/// Goal halves already announced, for the day they were announced on
+static GOALS_ANNOUNCED: Mutex<Option<(chrono::NaiveDate, Vec<&'static str>)>> = Mutex::new(None);
+
+/// Current and longest runs of consecutive days in `active`
+fn streaks(active: &std::collections::BTreeSet<chrono::NaiveDate>, today: chrono::NaiveDate) -> (u32, u32) {
+    let mut longest = 0;
+    let mut run = 0;
+    let mut previous: Option<chrono::NaiveDate> = None;
+    for &day in active {
+        run = if previous.is_some_and(|p| p.succ_opt() == Some(day)) { run + 1 } else { 1 };
+        longest = longest.max(run);
+        previous = Some(day);
+    }
+
+    let mut day = if active.contains(&today) { today } else { today - chrono::Duration::days(1) };
+    let mut current = 0;
+    while active.contains(&day) {
+        current += 1;
+        day -= chrono::Duration::days(1);
+    }
+    (current, longest)
+}
+
+/// Terms created on each day in `tz`
+fn terms_by_day<Tz: chrono::TimeZone>(terms: &[Term], tz: &Tz) -> BTreeMap<chrono::NaiveDate, u32> {
+    let mut days = BTreeMap::new();
+    for day in terms.iter().filter_map(|t| day_in(t.createdAt, tz)) {
+        *days.entry(day).or_insert(0) += 1;
+    }
+    days
+}
+
+fn goal_reached(goal: &DailyGoal, new_terms: u32, reviews: u32) -> bool {
+    (goal.new_terms > 0 || goal.reviews > 0) && new_terms >= goal.new_terms && reviews >= goal.reviews
+}
+
+/// Streaks and today's progress towards the daily goal, all derived from
+/// term creation times and the review log
+#[tauri::command]
+pub async fn get_streak_info(
+    state: State<'_, VocabularyState>,
+    settings: State<'_, SettingsState>,
+) -> Result<StreakInfo, String> {
+    let terms_path = state.terms_path.lock().unwrap().clone();
+    let created = terms_by_day(&load_terms(&terms_path).terms, &chrono::Local);
+    let reviewed = review_log::reviews_by_day();
+    let today = chrono::Local::now().date_naive();
+    Ok(streak_info(&created, &reviewed, today, settings.get().daily_goal))
+}
+
+/// `get_streak_info` from per-day term and review counts
+fn streak_info(
+    created: &BTreeMap<chrono::NaiveDate, u32>,
+    reviewed: &BTreeMap<chrono::NaiveDate, u64>,
+    today: chrono::NaiveDate,
+    goal: DailyGoal,
+) -> StreakInfo {
+    let active = created.keys().chain(reviewed.keys()).copied().collect();
+    let (current_streak, longest_streak) = streaks(&active, today);
+    let new_terms_today = created.get(&today).copied().unwrap_or(0);
+    let reviews_today = reviewed.get(&today).copied().unwrap_or(0) as u32;
+    StreakInfo {
+        success: true,
+        current_streak,
+        longest_streak,
+        today: today.format("%Y-%m-%d").to_string(),
+        new_terms_today,
+        reviews_today,
+        goal,
+        goal_reached: goal_reached(&goal, new_terms_today, reviews_today),
+    }
+}
+
+#[tauri::command]
+pub async fn set_daily_goal(
+    settings: State<'_, SettingsState>,
+    new_terms: u32,
+    reviews: u32,
+) -> Result<DailyGoal, String> {
+    let settings = settings.update(|s| s.daily_goal = DailyGoal { new_terms, reviews })?;
+    Ok(settings.daily_goal)
+}
+
+/// Emit `daily-goal-reached` the first time today's count of `kind`
+/// ("newTerms" or "reviews") reaches its goal
+fn check_daily_goal(app: &AppHandle, kind: &'static str) {
+    let goal = app.state::<SettingsState>().get().daily_goal;
+    let target = if kind == "reviews" { goal.reviews } else { goal.new_terms };
+    if target == 0 {
+        return;
+    }
+    let today = chrono::Local::now().date_naive();
+    {
+        let announced = GOALS_ANNOUNCED.lock().unwrap();
+        if announced.as_ref().is_some_and(|(day, kinds)| *day == today && kinds.contains(&kind)) {
+            return;
+        }
+    }
+
+    let count = if kind == "reviews" {
+        review_log::reviews_by_day().get(&today).copied().unwrap_or(0) as u32
+    } else {
+        let terms_path = app.state::<VocabularyState>().terms_path.lock().unwrap().clone();
+        terms_by_day(&load_terms(&terms_path).terms, &chrono::Local).get(&today).copied().unwrap_or(0)
+    };
+    if count < target {
+        return;
+    }
+
+    {
+        let mut announced = GOALS_ANNOUNCED.lock().unwrap();
+        match announced.as_mut() {
+            Some((day, kinds)) if *day == today => {
+                if kinds.contains(&kind) {
+                    return;
+                }
+                kinds.push(kind);
+            }
+            _ => *announced = Some((today, vec![kind])),
+        }
+    }
+    eprintln!("[VOCAB] Daily {} goal reached: {}/{}", kind, count, target);
+    let _ = app.emit("daily-goal-reached", DailyGoalReachedEvent {
+        date: today.format("%Y-%m-%d").to_string(),
+        kind: kind.to_string(),
+        count,
+        goal: target,
+    });
+}
+
+// ============================================================================
+// Vocabulary export / import
+// ============================================================================
+
+#[derive(Debug, Serialize)]
+pub struct VocabularyExportResult {
+    pub success: bool,
+    pub path: String,
+    pub terms: usize,
+    pub version: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct VocabularyImportResult {
+    pub success: bool,
+    /// "replace" or "merge"
+    pub mode: String,
+    /// Format version of the file, None for a bare array of terms
+    pub source_version: Option<String>,
+    /// Terms added as new
+    pub imported: usize,
+    /// Terms folded into one already saved (or earlier in the file)
+    pub merged: usize,
+    /// Terms without text or language
+    pub skipped: usize,
+    /// Terms read from an older format
+    pub migrated: usize,
+}
+
+/// Write every language's terms to `path` as one file at `TERMS_VERSION`,
+/// for `import_vocabulary` on this or another machine
+#[tauri::command]
+pub async fn export_vocabulary(
+    state: State<'_, VocabularyState>,
+    path: String,
+) -> Result<VocabularyExportResult, String> {
+    let terms_path = state.terms_path.lock().unwrap().clone();
+    let mut data = load_terms(&terms_path);
+    data.version = TERMS_VERSION.to_string();
+    data.updatedAt = chrono::Utc::now().timestamp_millis();
+    let content = serde_json::to_string_pretty(&data)
+        .map_err(|e| format!("Failed to serialize terms: {}", e))?;
+    fs::write(&path, content).map_err(|e| format!("Failed to write {}: {}", path, e))?;
+
+    eprintln!("[VOCAB] Exported {} terms to {}", data.terms.len(), path);
+    Ok(VocabularyExportResult {
+        success: true,
+        path,
+        terms: data.terms.len(),
+        version: TERMS_VERSION.to_string(),
+    })
+}
+
+/// Import a file from `export_vocabulary`, a backup or an older build's
+/// terms.json. "replace" swaps the whole vocabulary for it (backing the
+/// current one up first); "merge" adds its terms, folding ones already
+/// saved (same id, or same text in the language) into the saved copy the
+/// way `dedupe_terms` does.
+#[tauri::command]
+pub async fn import_vocabulary(
+    app: AppHandle,
+    path: String,
+    mode: String,
+) -> Result<VocabularyImportResult, String> {
+    spawn_write(app, move |app, state| import_vocabulary_blocking(app, state, path, mode)).await
+}
+
+fn import_vocabulary_blocking(
+    app: &AppHandle,
+    state: &VocabularyState,
+    path: String,
+    mode: String,
+) -> Result<VocabularyImportResult, String> {
+    if mode != "replace" && mode != "merge" {
+        return Err(format!("Unknown import mode '{}', expected replace or merge", mode));
+    }
+    let content = fs::read_to_string(&path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    let (incoming, source_version, migrated) = parse_terms(&content)
+        .map_err(|e| format!("{} is not a vocabulary file: {}", path, e))?;
+    let terms_path = state.terms_path.lock().unwrap().clone();
+    let now = chrono::Utc::now().timestamp_millis();
+
+    let mut result = VocabularyImportResult {
+        success: true,
+        mode: mode.clone(),
+        source_version,
+        imported: 0,
+        merged: 0,
+        skipped: 0,
+        migrated,
+    };
+    let replace = mode == "replace";
+    if replace && !language_stores(&terms_path).is_empty() {
+        write_backup(app)?;
+    }
+
+    let _guard = state.lock_all_languages();
+    let mut data = if replace { empty_terms() } else { load_terms(&terms_path) };
+    let existing = data.terms.len();
+    let mut by_id: HashMap<String, usize> = HashMap::new();
+    let mut by_text: HashMap<(String, String), usize> = HashMap::new();
+    for (i, term) in data.terms.iter().enumerate() {
+        by_id.insert(term.id.clone(), i);
+        by_text.entry((term.languageId.clone(), TermIndex::normalize(&term.text))).or_insert(i);
+    }
+
+    // Replacing reloads every window instead of reporting term by term
+    let mut batch = if replace { None } else { Some(BulkEventBatch::new(app, "import", Some(incoming.terms.len()))) };
+    let mut remapped: HashMap<String, String> = HashMap::new();
+    for term in incoming.terms {
+        if term.text.trim().is_empty() || term.languageId.trim().is_empty() {
+            result.skipped += 1;
+            continue;
+        }
+        let key = (term.languageId.clone(), TermIndex::normalize(&term.text));
+        match by_id.get(&term.id).or_else(|| by_text.get(&key)).copied() {
+            Some(i) => {
+                let saved = &mut data.terms[i];
+                let same_term = saved.id == term.id;
+                let query_count = saved.queryCount;
+                merge_duplicate(saved, &term);
+                // Another copy of the same term, not a second lookup history
+                if same_term {
+                    saved.queryCount = query_count.max(term.queryCount);
+                } else {
+                    remapped.insert(term.id.clone(), saved.id.clone());
+                }
+                saved.updatedAt = now;
+                if let Some(batch) = batch.as_mut() {
+                    batch.record(&saved.id);
+                }
+                result.merged += 1;
+            }
+            None => {
+                by_id.insert(term.id.clone(), data.terms.len());
+                by_text.insert(key, data.terms.len());
+                if let Some(batch) = batch.as_mut() {
+                    batch.record(&term.id);
+                }
+                data.terms.push(term);
+                result.imported += 1;
+            }
+        }
+    }
+    // Children of terms that were merged away follow them
+    for term in data.terms.iter_mut().skip(existing) {
+        if let Some(kept) = term.parentId.as_ref().and_then(|parent| remapped.get(parent)) {
+            term.parentId = Some(kept.clone());
+        }
+    }
+
+    data.updatedAt = now;
+    if let Err(e) = save_terms(&terms_path, &data) {
+        if let Some(batch) = batch {
+            batch.fail(&e);
+        }
+        return Err(e);
+    }
+    match batch {
+        Some(batch) => batch.finish(),
+        None => {
+            *state.term_index.write().unwrap() = TermIndex::build(&data);
+            texts::refresh_stale(app);
+            let _ = app.emit("terms-reloaded", serde_json::json!({ "import": path, "timestamp": now }));
+        }
+    }
+
+    eprintln!(
+        "[VOCAB] Imported {} ({}): {} new, {} merged, {} skipped, {} migrated",
+        path, mode, result.imported, result.merged, result.skipped, result.migrated
+    );
+    Ok(result)
+}
+
+// ============================================================================
+// CSV import / export
+// ============================================================================
+
+const CSV_COLUMNS: &[&str] = &["text", "translation", "notes", "status", "tags", "createdAt"];
+/// Separates tags inside the tags column
+const CSV_TAG_SEPARATOR: char = ';';
+
+#[derive(Debug, Serialize)]
+pub struct CsvExportResult {
+    pub success: bool,
+    pub path: String,
+    pub exported: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CsvRowError {
+    /// 1-based line the row starts on
+    pub line: usize,
+    pub message: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CsvImportResult {
+    pub success: bool,
+    pub imported: usize,
+    /// Rows whose term is already saved (or appeared earlier in the file)
+    pub skipped: usize,
+    /// Rows that couldn't be read; the rest of the file is still imported
+    pub errors: Vec<CsvRowError>,
+}
+
+/// Split CSV `content` into records with the line each starts on. Quoted
+/// fields may contain separators, doubled quotes and line breaks; a record
+/// whose quote is never closed is returned as an error.
+fn parse_csv(content: &str) -> Vec<(usize, Result<Vec<String>, String>)> {
+    let mut records = Vec::new();
+    let mut chars = content.trim_start_matches('\u{feff}').chars().peekable();
+    let mut line = 1;
+    while chars.peek().is_some() {
+        let start = line;
+        let mut fields = Vec::new();
+        let mut field = String::new();
+        let mut quoted = false;
+        let mut closed = true;
+        loop {
+            match chars.next() {
+                None => break,
+                Some('"') if quoted => {
+                    if chars.peek() == Some(&'"') {
+                        chars.next();
+                        field.push('"');
+                    } else {
+                        quoted = false;
+                        closed = true;
+                    }
+                }
+                Some('"') if field.is_empty() => {
+                    quoted = true;
+                    closed = false;
+                }
+                Some(c) if quoted => {
+                    if c == '\n' {
+                        line += 1;
+                    }
+                    field.push(c);
+                }
+                Some(',') => fields.push(std::mem::take(&mut field)),
+                Some('\r') if chars.peek() == Some(&'\n') => {}
+                Some('\n') | Some('\r') => {
+                    line += 1;
+                    break;
+                }
+                Some(c) => field.push(c),
+            }
+        }
+        fields.push(field);
+        if !closed {
+            records.push((start, Err("Unterminated quoted field".to_string())));
+        } else if fields.iter().any(|f| !f.trim().is_empty()) {
+            records.push((start, Ok(fields)));
+        }
+    }
+    records
+}
+
+/// Column positions of a header row, if `fields` is one
+fn csv_header(fields: &[String]) -> Option<HashMap<String, usize>> {
+    if !fields.first().is_some_and(|f| f.trim().eq_ignore_ascii_case("text")) {
+        return None;
+    }
+    Some(
+        fields
+            .iter()
+            .enumerate()
+            .map(|(i, name)| (name.trim().to_lowercase(), i))
+            .collect(),
+    )
+}
+
+/// Accepts the export's RFC 3339 timestamps as well as epoch milliseconds
+fn parse_created_at(value: &str) -> Option<i64> {
+    value.parse::<i64>().ok().or_else(|| {
+        chrono::DateTime::parse_from_rfc3339(value)
+            .ok()
+            .map(|t| t.timestamp_millis())
+    })
+}
+
+/// Write saved terms, optionally of one language and status, as CSV with a
+/// header row
+#[tauri::command]
+pub async fn export_terms_csv(
+    state: State<'_, VocabularyState>,
+    path: String,
+    language: Option<String>,
+    status: Option<i32>,
+) -> Result<CsvExportResult, String> {
+    let terms_path = state.terms_path.lock().unwrap().clone();
+    let data = load_scope(&terms_path, language.as_deref());
+
+    let mut csv = CSV_COLUMNS.join(",");
+    csv.push('\n');
+    let mut exported = 0;
+    for term in data.terms.iter()
+        .filter(|t| language.as_deref().is_none_or(|lang| t.languageId == lang))
+        .filter(|t| status.is_none_or(|status| t.status == status))
+    {
+        let created_at = chrono::DateTime::from_timestamp_millis(term.createdAt)
+            .map(|t| t.to_rfc3339())
+            .unwrap_or_else(|| term.createdAt.to_string());
+        let fields = [
+            term.text.clone(),
+            term.translation.clone(),
+            term.notes.clone(),
+            term.status.to_string(),
+            term.tags.join(&CSV_TAG_SEPARATOR.to_string()),
+            created_at,
+        ];
+        csv.push_str(&fields.iter().map(|f| csv_field(f)).collect::<Vec<_>>().join(","));
+        csv.push('\n');
+        exported += 1;
+    }
+
+    let target = PathBuf::from(&path);
+    if let Some(parent) = target.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create directory: {}", e))?;
+    }
+    fs::write(&target, csv).map_err(|e| format!("Failed to write {}: {}", path, e))?;
+
+    Ok(CsvExportResult {
+        success: true,
+        path,
+        exported,
+    })
+}
+
+/// Import terms into `language_id` from a CSV written by `export_terms_csv`,
+/// or from plain text,translation rows. Terms already saved are skipped, so
+/// importing the same file twice adds nothing.
+#[tauri::command]
+pub async fn import_terms_csv(
+    app: AppHandle,
+    path: String,
+    language_id: String,
+) -> Result<CsvImportResult, String> {
+    spawn_write(app, move |app, state| import_terms_csv_blocking(app, state, path, language_id)).await
+}
+
+fn import_terms_csv_blocking(
+    app: &AppHandle,
+    state: &VocabularyState,
+    path: String,
+    language_id: String,
+) -> Result<CsvImportResult, String> {
+    let content = fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    let terms_path = state.terms_path.lock().unwrap().clone();
+    let _guard = state.lock_language(&language_id);
+    let mut data = load_language(&terms_path, &language_id);
+    let now = chrono::Utc::now().timestamp_millis();
+
+    let mut known: std::collections::HashSet<String> = data.terms.iter()
+        .filter(|t| t.languageId == language_id)
+        .map(|t| TermIndex::normalize(&t.text))
+        .collect();
+
+    let mut records = parse_csv(&content).into_iter().peekable();
+    let header = match records.peek() {
+        Some((_, Ok(fields))) => csv_header(fields),
+        _ => None,
+    };
+    if header.is_some() {
+        records.next();
+    }
+    // Without a header, columns are in export order
+    let columns: HashMap<String, usize> = header.unwrap_or_else(|| {
+        CSV_COLUMNS.iter().enumerate().map(|(i, name)| (name.to_lowercase(), i)).collect()
+    });
+    let column = |fields: &[String], name: &str| -> String {
+        columns
+            .get(name)
+            .and_then(|&i| fields.get(i))
+            .map(|f| f.trim().to_string())
+            .unwrap_or_default()
+    };
+
+    let mut batch = BulkEventBatch::new(app, "import", None);
+    let mut result = CsvImportResult {
+        success: true,
+        imported: 0,
+        skipped: 0,
+        errors: Vec::new(),
+    };
+    for (line, record) in records {
+        let fields = match record {
+            Ok(fields) => fields,
+            Err(message) => {
+                result.errors.push(CsvRowError { line, message });
+                batch.record_failure();
+                continue;
+            }
+        };
+        let text = column(&fields, "text");
+        if text.is_empty() {
+            result.errors.push(CsvRowError { line, message: "Missing text".to_string() });
+            batch.record_failure();
+            continue;
+        }
+        let status = match column(&fields, "status") {
+            s if s.is_empty() => None,
+            s => match s.parse::<i32>() {
+                Ok(status) => Some(status),
+                Err(_) => {
+                    result.errors.push(CsvRowError { line, message: format!("Invalid status '{}'", s) });
+                    batch.record_failure();
+                    continue;
+                }
+            },
+        };
+        let created_at = match column(&fields, "createdat") {
+            s if s.is_empty() => None,
+            s => match parse_created_at(&s) {
+                Some(t) => Some(t),
+                None => {
+                    result.errors.push(CsvRowError { line, message: format!("Invalid createdAt '{}'", s) });
+                    batch.record_failure();
+                    continue;
+                }
+            },
+        };
+        if !known.insert(TermIndex::normalize(&text)) {
+            result.skipped += 1;
+            continue;
+        }
+
+        let mut term = new_term(TermInput {
+            text,
+            languageId: language_id.clone(),
+            translation: column(&fields, "translation"),
+            notes: column(&fields, "notes"),
+            parentId: None,
+            image: None,
+            sense_id: None,
+            sense_fingerprint: None,
+            status,
+            nextReview: None,
+            interval: None,
+            easeFactor: None,
+            reps: None,
+            tags: column(&fields, "tags").split(CSV_TAG_SEPARATOR).map(str::to_string).collect(),
+            inflections: Vec::new(),
+            context: None,
+            source: Some("csv".to_string()),
+        }, now);
+        if let Some(created_at) = created_at {
+            term.createdAt = created_at;
+        }
+        batch.record(&term.id);
+        data.terms.push(term);
+        result.imported += 1;
+    }
+
+    if result.imported > 0 {
+        data.updatedAt = now;
+        if let Err(e) = save_language(&terms_path, &language_id, &data) {
+            batch.fail(&e);
+            return Err(e);
+        }
+    }
+    batch.finish();
+
+    eprintln!(
+        "[VOCAB] CSV import from {}: {} imported, {} skipped, {} errors",
+        path, result.imported, result.skipped, result.errors.len()
+    );
+    Ok(result)
+}
+
+// ============================================================================
+// Anki export
+// ============================================================================
+
+#[derive(Debug, Serialize)]
+pub struct AnkiExportResult {
+    pub success: bool,
+    pub path: String,
+    /// "apkg" or "tsv"
+    pub format: String,
+    pub notes: usize,
+}
+
+/// Export terms, optionally of one language, for Anki: an `.apkg` package
+/// when `path` ends in .apkg, otherwise a tab-separated notes file. With
+/// `include_scheduling`, reviewed terms keep their due date, interval and
+/// ease in the package; the text format can't carry them.
+#[tauri::command]
+pub async fn export_terms_anki(
+    state: State<'_, VocabularyState>,
+    path: String,
+    language: Option<String>,
+    deck_name: String,
+    include_scheduling: Option<bool>,
+) -> Result<AnkiExportResult, String> {
+    let target = PathBuf::from(&path);
+    let apkg = target
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("apkg"));
+    let include_scheduling = include_scheduling.unwrap_or(false);
+    if include_scheduling && !apkg {
+        return Err("Scheduling can only be exported to an .apkg package".to_string());
+    }
+    let deck_name = deck_name.trim();
+    if deck_name.is_empty() {
+        return Err("Deck name is empty".to_string());
+    }
+
+    let terms_path = state.terms_path.lock().unwrap().clone();
+    let data = load_scope(&terms_path, language.as_deref());
+    let notes: Vec<anki::AnkiNote> = data.terms.into_iter()
+        .filter(|t| language.as_deref().is_none_or(|lang| t.languageId == lang))
+        .map(|t| {
+            let mut tags: Vec<String> = t.tags.iter().map(|tag| anki::tag(tag)).collect();
+            tags.push(format!("lumina::status::{}", t.status));
+            tags.push(format!("lumina::{}", anki::tag(&t.languageId)));
+            anki::AnkiNote {
+                schedule: (include_scheduling && t.reps > 0).then_some(anki::AnkiSchedule {
+                    due_ms: t.nextReview,
+                    interval_days: t.interval,
+                    ease_factor: t.easeFactor,
+                    reps: t.reps,
+                }),
+                id: t.id,
+                front: t.text,
+                back: t.translation,
+                notes: t.notes,
+                tags,
+            }
+        })
+        .collect();
+
+    if let Some(parent) = target.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create directory: {}", e))?;
+    }
+    if apkg {
+        anki::write_apkg(&target, deck_name, &notes)?;
+    } else {
+        anki::write_tsv(&target, deck_name, &notes)?;
+    }
+
+    Ok(AnkiExportResult {
+        success: true,
+        path,
+        format: if apkg { "apkg" } else { "tsv" }.to_string(),
+        notes: notes.len(),
+    })
+}
+
+/// Repeat lookups of a term within this window count once
+const QUERY_COOLDOWN_MS: i64 = 60 * 1000;
+
+/// Count a dictionary lookup against the saved terms whose text is one of
+/// `words` (the query and the lemmas it found). Matching uses the in-memory
+/// index; the store is only read and written, off this thread, on a hit.
+pub fn record_lookup(app: &AppHandle, language: &str, words: &[String]) {
+    let state = app.state::<VocabularyState>();
+    let ids: std::collections::HashSet<String> = {
+        let index = state.term_index.read().unwrap();
+        words.iter().flat_map(|w| index.ids_for(language, w)).collect()
+    };
+    if ids.is_empty() {
+        return;
+    }
+
+    let app = app.clone();
+    let language = language.to_string();
+    std::thread::spawn(move || {
+        let state = app.state::<VocabularyState>();
+        let terms_path = state.terms_path.lock().unwrap().clone();
+        let _guard = state.lock_language(&language);
+        let mut data = load_language(&terms_path, &language);
+        let now = chrono::Utc::now().timestamp_millis();
+        let mut queried = Vec::new();
+        for term in data.terms.iter_mut().filter(|t| ids.contains(&t.id)) {
+            if term.lastQueriedAt.is_some_and(|at| now - at < QUERY_COOLDOWN_MS) {
+                continue;
+            }
+            term.queryCount += 1;
+            term.lastQueriedAt = Some(now);
+            queried.push(term.clone());
+        }
+        if queried.is_empty() {
+            return;
+        }
+        if let Err(e) = save_language(&terms_path, &language, &data) {
+            eprintln!("[VOCAB] Failed to record lookup: {}", e);
+            return;
+        }
+        for term in queried {
+            let _ = app.emit("term-update", TermUpdateEvent {
+                action: "queried".to_string(),
+                term,
+                timestamp: now,
+            });
+        }
+    });
+}
+
+/// Saved terms looked up most often, most recently looked up first on ties
+#[tauri::command]
+pub async fn get_most_queried_terms(
+    state: State<'_, VocabularyState>,
+    limit: usize,
+) -> Result<Vec<Term>, String> {
+    let terms_path = state.terms_path.lock().unwrap().clone();
+    let mut terms: Vec<Term> = load_terms(&terms_path)
+        .terms
+        .into_iter()
+        .filter(|t| t.queryCount > 0)
+        .collect();
+    terms.sort_by(|a, b| {
+        b.queryCount
+            .cmp(&a.queryCount)
+            .then(b.lastQueriedAt.cmp(&a.lastQueriedAt))
+    });
+    terms.truncate(limit);
+    Ok(terms)
+}
+
+/// Whether the vocabulary store had to be recovered from a previous version
+#[tauri::command]
+pub async fn get_vocabulary_health(
+    state: State<'_, VocabularyState>,
+) -> Result<VocabularyHealth, String> {
+    let terms_path = state.terms_path.lock().unwrap().clone();
+    let recovery = TERMS_RECOVERY.lock().unwrap().clone();
+    let generations = language_stores(&terms_path)
+        .iter()
+        .flat_map(|store| (1..=TERMS_GENERATIONS).map(|generation| generation_path(store, generation)))
+        .filter(|path| path.exists())
+        .filter_map(|path| path.file_name().map(|n| n.to_string_lossy().to_string()))
+        .collect();
+    Ok(VocabularyHealth {
+        success: true,
+        recovered: recovery.is_some(),
+        recovery,
+        generations,
+    })
+}
+
+// ============================================================================
+// Backups
+// ============================================================================
+
+/// At most one automatic backup per this interval
+const BACKUP_INTERVAL: Duration = Duration::from_secs(10 * 60);
+const BACKUP_PREFIX: &str = "terms-";
+const BACKUP_SUFFIX: &str = ".json.gz";
+
+static LAST_BACKUP: Mutex<Option<Instant>> = Mutex::new(None);
+
+#[derive(Debug, Clone, Serialize)]
+pub struct VocabularyBackup {
+    pub name: String,
+    /// Compressed size
+    pub bytes: u64,
+    pub created_at: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RestoreBackupResult {
+    pub success: bool,
+    pub restored: String,
+    pub terms: usize,
+    /// Backup of the vocabulary as it was before the restore, if there was one
+    pub previous: Option<VocabularyBackup>,
+}
+
+fn backups_dir(app: &AppHandle) -> PathBuf {
+    let base_dir = app.path()
+        .app_data_dir()
+        .unwrap_or_else(|_| std::env::current_dir().unwrap_or_else(|_| PathBuf::from(".")));
+    base_dir.join("backups")
+}
+
+/// Back the store up after a change, unless one was made within
+/// `BACKUP_INTERVAL`
+fn schedule_backup(app: &AppHandle) {
+    {
+        let mut last = LAST_BACKUP.lock().unwrap();
+        if last.is_some_and(|at| at.elapsed() < BACKUP_INTERVAL) {
+            return;
+        }
+        *last = Some(Instant::now());
+    }
+    let app = app.clone();
+    std::thread::spawn(move || {
+        if let Err(e) = write_backup(&app) {
+            eprintln!("[VOCAB] Backup failed: {}", e);
+        }
+    });
+}
+
+/// Write a gzipped copy of every language's terms, in the combined
+/// terms.json format, to the backups folder and drop the oldest beyond the
+/// configured count
+fn write_backup(app: &AppHandle) -> Result<VocabularyBackup, String> {
+    use flate2::{write::GzEncoder, Compression};
+
+    let terms_path = app.state::<VocabularyState>().terms_path.lock().unwrap().clone();
+    let content = serde_json::to_vec_pretty(&load_terms(&terms_path))
+        .map_err(|e| format!("Failed to serialize terms: {}", e))?;
+    let dir = backups_dir(app);
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create backups folder: {}", e))?;
+
+    let now = chrono::Local::now();
+    let name = format!("{}{}{}", BACKUP_PREFIX, now.format("%Y%m%d-%H%M%S%3f"), BACKUP_SUFFIX);
+    let path = dir.join(&name);
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(&content).map_err(|e| format!("Failed to compress backup: {}", e))?;
+    let compressed = encoder.finish().map_err(|e| format!("Failed to compress backup: {}", e))?;
+    let tmp_path = path.with_extension("tmp");
+    fs::write(&tmp_path, &compressed).map_err(|e| format!("Failed to write backup: {}", e))?;
+    fs::rename(&tmp_path, &path).map_err(|e| format!("Failed to write backup: {}", e))?;
+
+    let keep = app.state::<SettingsState>().get().vocabulary_backup_count.max(1);
+    let backups = list_backups(&dir);
+    for old in backups.iter().skip(keep) {
+        if let Err(e) = fs::remove_file(dir.join(&old.name)) {
+            eprintln!("[VOCAB] Could not remove old backup {}: {}", old.name, e);
+        }
+    }
+    Ok(VocabularyBackup {
+        name,
+        bytes: compressed.len() as u64,
+        created_at: now.timestamp_millis(),
+    })
+}
+
+/// Backups in `dir`, newest first
+fn list_backups(dir: &Path) -> Vec<VocabularyBackup> {
+    let mut backups: Vec<VocabularyBackup> = fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            if !name.starts_with(BACKUP_PREFIX) || !name.ends_with(BACKUP_SUFFIX) {
+                return None;
+            }
+            let meta = entry.metadata().ok()?;
+            let created_at = meta
+                .modified()
+                .ok()
+                .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+                .map(|d| d.as_millis() as i64)
+                .unwrap_or(0);
+            Some(VocabularyBackup { name, bytes: meta.len(), created_at })
+        })
+        .collect();
+    // Names carry the timestamp, so they sort chronologically
+    backups.sort_by(|a, b| b.name.cmp(&a.name));
+    backups
+}
+
+#[tauri::command]
+pub async fn list_vocabulary_backups(app: AppHandle) -> Result<Vec<VocabularyBackup>, String> {
+    Ok(list_backups(&backups_dir(&app)))
+}
+
+/// Back the vocabulary up now, regardless of when the last backup was made
+#[tauri::command]
+pub async fn create_vocabulary_backup(app: AppHandle) -> Result<VocabularyBackup, String> {
+    let backup = write_backup(&app)?;
+    *LAST_BACKUP.lock().unwrap() = Some(Instant::now());
+    Ok(backup)
+}
+
+/// Replace the vocabulary with backup `name`. The current state is backed
+/// up first; open windows get `terms-reloaded`.
+#[tauri::command]
+pub async fn restore_vocabulary_backup(
+    app: AppHandle,
+    name: String,
+) -> Result<RestoreBackupResult, String> {
+    spawn_write(app, move |app, state| restore_vocabulary_backup_blocking(app, state, name)).await
+}
+
+fn restore_vocabulary_backup_blocking(
+    app: &AppHandle,
+    state: &VocabularyState,
+    name: String,
+) -> Result<RestoreBackupResult, String> {
+    use flate2::read::GzDecoder;
+    use std::io::Read;
+
+    if name.contains(['/', '\\']) || !name.starts_with(BACKUP_PREFIX) || !name.ends_with(BACKUP_SUFFIX) {
+        return Err(format!("Not a vocabulary backup: {}", name));
+    }
+    let path = backups_dir(app).join(&name);
+    let compressed = fs::read(&path).map_err(|e| format!("Failed to read backup {}: {}", name, e))?;
+    let mut content = String::new();
+    GzDecoder::new(&compressed[..])
+        .read_to_string(&mut content)
+        .map_err(|e| format!("Failed to decompress backup {}: {}", name, e))?;
+    let (mut data, _, _) = parse_terms(&content)
+        .map_err(|e| format!("Backup {} is not a vocabulary file: {}", name, e))?;
+
+    let terms_path = state.terms_path.lock().unwrap().clone();
+    let previous = if language_stores(&terms_path).is_empty() { None } else { Some(write_backup(app)?) };
+    let now = chrono::Utc::now().timestamp_millis();
+    data.updatedAt = now;
+    {
+        let _guard = state.lock_all_languages();
+        save_terms(&terms_path, &data)?;
+    }
+    *state.term_index.write().unwrap() = TermIndex::build(&data);
+    texts::refresh_stale(app);
+
+    eprintln!("[VOCAB] Restored {} terms from {}", data.terms.len(), name);
+    let _ = app.emit("terms-reloaded", serde_json::json!({ "backup": name, "timestamp": now }));
+    Ok(RestoreBackupResult {
+        success: true,
+        restored: name,
+        terms: data.terms.len(),
+        previous,
+    })
+}
+
+// ============================================================================
+// Term images
+// ============================================================================
+
+/// Larger images are refused rather than stored
+const MAX_IMAGE_BYTES: u64 = 1024 * 1024;
+
+#[derive(Debug, Serialize)]
+pub struct TermImage {
+    pub success: bool,
+    pub mime: String,
+    pub base64: String,
+    /// Absolute path of the stored file
+    pub path: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ImageCleanupResult {
+    pub success: bool,
+    pub removed: usize,
+    pub bytes_freed: u64,
+}
+
+/// Term images live next to the term stores, in images/; `Term.image` holds the
+/// file name relative to it
+fn images_dir(terms_path: &Path) -> PathBuf {
+    terms_path.parent().map(Path::to_path_buf).unwrap_or_default().join("images")
+}
+
+/// `image` as a local file to copy in: an absolute path to an existing file,
+/// not a data or remote URL or an already stored name
+fn local_image_source(image: &str) -> Option<PathBuf> {
+    let path = PathBuf::from(image.strip_prefix("file://").unwrap_or(image));
+    (path.is_absolute() && path.is_file()).then_some(path)
+}
+
+/// Extension and MIME type of an image, from its magic bytes
+fn image_kind(bytes: &[u8]) -> Option<(&'static str, &'static str)> {
+    if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        Some(("png", "image/png"))
+    } else if bytes.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        Some(("jpg", "image/jpeg"))
+    } else if bytes.starts_with(b"GIF87a") || bytes.starts_with(b"GIF89a") {
+        Some(("gif", "image/gif"))
+    } else if bytes.len() >= 12 && &bytes[..4] == b"RIFF" && &bytes[8..12] == b"WEBP" {
+        Some(("webp", "image/webp"))
+    } else if bytes.starts_with(b"BM") {
+        Some(("bmp", "image/bmp"))
+    } else {
+        None
+    }
+}
+
+/// Copy `source` into the images folder as `<term id>.<ext>` and return the
+/// stored name
+fn store_term_image(terms_path: &Path, term_id: &str, source: &Path) -> Result<String, String> {
+    let size = fs::metadata(source)
+        .map_err(|e| format!("Failed to read image {}: {}", source.display(), e))?
+        .len();
+    if size > MAX_IMAGE_BYTES {
+        return Err(format!("Image is larger than {} KB: {}", MAX_IMAGE_BYTES / 1024, source.display()));
+    }
+    let bytes = fs::read(source).map_err(|e| format!("Failed to read image {}: {}", source.display(), e))?;
+    let (extension, _) = image_kind(&bytes).ok_or_else(|| format!("Not a supported image: {}", source.display()))?;
+
+    let stem: String = term_id
+        .chars()
+        .map(|c| if c.is_alphanumeric() || c == '-' { c } else { '_' })
+        .collect();
+    let name = format!("{}.{}", stem, extension);
+    let dir = images_dir(terms_path);
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create images folder: {}", e))?;
+    fs::write(dir.join(&name), &bytes).map_err(|e| format!("Failed to store image: {}", e))?;
+    Ok(name)
+}
+
+/// Delete the stored image of a term that is gone
+fn remove_term_image(terms_path: &Path, term: &Term) {
+    let Some(name) = term.image.as_deref().filter(|i| !i.is_empty() && !Path::new(i).is_absolute() && !i.contains(':')) else {
+        return;
+    };
+    let path = images_dir(terms_path).join(name);
+    if path.is_file() {
+        if let Err(e) = fs::remove_file(&path) {
+            eprintln!("[VOCAB] Could not remove image {:?}: {}", path, e);
+        }
+    }
+}
+
+fn base64_encode(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk.iter().enumerate().fold(0u32, |n, (i, &b)| n | ((b as u32) << (16 - 8 * i)));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[((n >> (18 - 6 * i)) & 0x3F) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+/// A term's stored image, base64-encoded for a data URL
+#[tauri::command]
+pub async fn get_term_image(
+    state: State<'_, VocabularyState>,
+    id: String,
+) -> Result<TermImage, String> {
+    let terms_path = state.terms_path.lock().unwrap().clone();
+    let language = term_language(&terms_path, &id).ok_or_else(|| "Term not found".to_string())?;
+    let data = load_language(&terms_path, &language);
+    let term = data.terms.iter()
+        .find(|t| t.id == id)
+        .ok_or_else(|| "Term not found".to_string())?;
+    let image = term.image.as_deref().filter(|i| !i.is_empty()).ok_or_else(|| "Term has no image".to_string())?;
+
+    let path = match PathBuf::from(image) {
+        path if path.is_absolute() => path,
+        name => images_dir(&terms_path).join(name),
+    };
+    let bytes = fs::read(&path).map_err(|e| format!("Failed to read image {}: {}", path.display(), e))?;
+    let (_, mime) = image_kind(&bytes).ok_or_else(|| format!("Not a supported image: {}", path.display()))?;
+    Ok(TermImage {
+        success: true,
+        mime: mime.to_string(),
+        base64: base64_encode(&bytes),
+        path: path.to_string_lossy().to_string(),
+    })
+}
+
+/// Delete files in the images folder that no term refers to
+#[tauri::command]
+pub async fn cleanup_term_images(
+    state: State<'_, VocabularyState>,
+) -> Result<ImageCleanupResult, String> {
+    let terms_path = state.terms_path.lock().unwrap().clone();
+    let data = load_terms(&terms_path);
+    let referenced: std::collections::HashSet<&str> = data.terms.iter()
+        .filter_map(|t| t.image.as_deref())
+        .collect();
+
+    let mut result = ImageCleanupResult { success: true, removed: 0, bytes_freed: 0 };
+    for entry in fs::read_dir(images_dir(&terms_path)).into_iter().flatten().flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        let Ok(meta) = entry.metadata() else { continue };
+        if !meta.is_file() || referenced.contains(name.as_str()) {
+            continue;
+        }
+        match fs::remove_file(entry.path()) {
+            Ok(()) => {
+                result.removed += 1;
+                result.bytes_freed += meta.len();
+            }
+            Err(e) => eprintln!("[VOCAB] Could not remove image {}: {}", name, e),
+        }
+    }
+    eprintln!("[VOCAB] Removed {} unused images ({} bytes)", result.removed, result.bytes_freed);
+    Ok(result)
+}
+
+/// Vocabulary state for the stores at `terms_path`
+pub fn vocabulary_state_at(terms_path: PathBuf) -> VocabularyState {
+    migrate_combined_store(&terms_path);
+    let index = TermIndex::build(&load_terms(&terms_path));
+    VocabularyState::new(terms_path, index)
+}
+
+/// Keep the term index in step with the store by following the same events
+/// the webviews get. Bulk operations rebuild it from disk.
+pub fn watch_term_updates(app: &AppHandle) {
+    let handle = app.clone();
+    app.listen("term-update", move |event| {
+        let Ok(update) = serde_json::from_str::<TermUpdateEvent>(event.payload()) else {
+            return;
+        };
+        schedule_backup(&handle);
+        // Lookup counts don't change what the index or text coverage uses
+        if update.action == "queried" {
+            return;
+        }
+        if update.action == "add" {
+            let app = handle.clone();
+            std::thread::spawn(move || check_daily_goal(&app, "newTerms"));
+        }
+        let state = handle.state::<VocabularyState>();
+        let previous_updated_at = {
+            let mut index = state.term_index.write().unwrap();
+            let previous = index.updated_at;
+            index.apply(&update);
+            previous
+        };
+        texts::refresh_for_term(&handle, &update.term.languageId, &update.term.text, previous_updated_at);
+    });
+
+    let handle = app.clone();
+    app.listen("terms-bulk-update", move |_event| {
+        schedule_backup(&handle);
+        let state = handle.state::<VocabularyState>();
+        let terms_path = state.terms_path.lock().unwrap().clone();
+        *state.term_index.write().unwrap() = TermIndex::build(&load_terms(&terms_path));
+        texts::refresh_stale(&handle);
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[derive(Clone, Default)]
+    struct Recorder {
+        progress: Arc<Mutex<Vec<TermsBulkProgressEvent>>>,
+        updates: Arc<Mutex<Vec<TermsBulkUpdateEvent>>>,
+    }
+
+    impl BulkEventSink for Recorder {
+        fn progress(&self, event: TermsBulkProgressEvent) {
+            self.progress.lock().unwrap().push(event);
+        }
+
+        fn update(&self, event: TermsBulkUpdateEvent) {
+            self.updates.lock().unwrap().push(event);
+        }
+    }
+
+    #[test]
+    fn sense_fields_keep_their_camel_case_names() {
+        let term = migrate_term(serde_json::json!({
+            "text": "Bank",
+            "language_id": "de",
+            "sense_id": "17",
+            "senseFingerprint": "a1b2",
+        }))
+        .unwrap();
+        assert_eq!(term.sense_id.as_deref(), Some("17"));
+        assert_eq!(term.sense_fingerprint.as_deref(), Some("a1b2"));
+
+        let json = serde_json::to_value(&term).unwrap();
+        assert_eq!(json["senseId"], "17");
+        assert_eq!(json["senseFingerprint"], "a1b2");
+        assert!(json.get("sense_id").is_none());
+    }
+
+    #[test]
+    fn deleting_parents_detaches_their_surviving_children() {
+        let term = |id: &str, parent: Option<&str>| {
+            migrate_term(serde_json::json!({ "id": id, "text": id, "languageId": "de", "parentId": parent }))
+                .unwrap()
+        };
+        let mut terms = vec![
+            term("gehen", None),
+            term("ging", Some("gehen")),
+            term("gegangen", Some("gehen")),
+            term("Haus", None),
+            term("Häuser", Some("Haus")),
+        ];
+        let deleted: HashSet<&str> = ["gehen", "Haus", "Häuser"].into_iter().collect();
+        terms.retain(|t| !deleted.contains(t.id.as_str()));
+
+        let orphaned = detach_children(&mut terms, &deleted, 42);
+        let ids: Vec<&str> = orphaned.iter().map(|t| t.id.as_str()).collect();
+        assert_eq!(ids, ["ging", "gegangen"]);
+        assert!(terms.iter().all(|t| t.parentId.is_none() && t.updatedAt == 42));
+    }
+
+    #[test]
+    fn thousand_item_import_emits_a_handful_of_events() {
+        let recorder = Recorder::default();
+        let started = Instant::now();
+        let mut batch = BulkEventBatch::with_sink(recorder.clone(), "import", Some(1000));
+        for i in 0..1000 {
+            if i % 100 == 99 {
+                batch.record_failure();
+            } else {
+                batch.record(&format!("term-{}", i));
+            }
+        }
+        batch.finish();
+
+        let progress = recorder.progress.lock().unwrap();
+        let updates = recorder.updates.lock().unwrap();
+        // One progress event per 200 items, plus any the 250 ms timer adds
+        // on a slow machine
+        let timer_events = (started.elapsed().as_millis() / BULK_PROGRESS_INTERVAL.as_millis()) as usize;
+        assert!(progress.len() >= 1000 / BULK_PROGRESS_ITEMS);
+        assert!(progress.len() <= 1000 / BULK_PROGRESS_ITEMS + timer_events);
+        assert!(progress.len() + updates.len() <= 10);
+        assert!(progress.windows(2).all(|w| w[0].processed < w[1].processed));
+
+        assert_eq!(updates.len(), 1);
+        let update = &updates[0];
+        assert!(update.success);
+        assert_eq!(update.affected, 990);
+        assert_eq!(update.failed, 10);
+        // Too many ids to list
+        assert!(update.refetch);
+        assert!(update.ids.is_empty());
+    }
+
+    #[test]
+    fn small_batch_lists_ids() {
+        let recorder = Recorder::default();
+        let mut batch = BulkEventBatch::with_sink(recorder.clone(), "delete", Some(2));
+        batch.record("a");
+        batch.record("b");
+        batch.finish();
+
+        let updates = recorder.updates.lock().unwrap();
+        assert_eq!(updates.len(), 1);
+        assert_eq!(updates[0].ids, vec!["a", "b"]);
+        assert!(!updates[0].refetch);
+    }
+
+    #[test]
+    fn panicking_import_still_emits_a_terminal_event() {
+        let recorder = Recorder::default();
+        let sink = recorder.clone();
+        let result = std::panic::catch_unwind(move || {
+            let mut batch = BulkEventBatch::with_sink(sink, "import", None);
+            batch.record("a");
+            panic!("import failed");
+        });
+        assert!(result.is_err());
+
+        let updates = recorder.updates.lock().unwrap();
+        assert_eq!(updates.len(), 1);
+        assert!(!updates[0].success);
+        assert_eq!(updates[0].affected, 1);
+        assert_eq!(updates[0].error.as_deref(), Some("Bulk operation aborted"));
+    }
+
+    #[test]
+    fn failed_batch_reports_its_error_once() {
+        let recorder = Recorder::default();
+        let batch = BulkEventBatch::with_sink(recorder.clone(), "update", None);
+        batch.fail("disk full");
+
+        let updates = recorder.updates.lock().unwrap();
+        assert_eq!(updates.len(), 1);
+        assert_eq!(updates[0].error.as_deref(), Some("disk full"));
+    }
+
+    /// Milliseconds of the wall-clock time `local` ("YYYY-MM-DD HH:MM:SS") in `tz`
+    fn at(tz: &chrono::FixedOffset, local: &str) -> i64 {
+        let naive = chrono::NaiveDateTime::parse_from_str(local, "%Y-%m-%d %H:%M:%S").unwrap();
+        naive.and_local_timezone(*tz).unwrap().timestamp_millis()
+    }
+
+    fn dated_term(id: &str, status: i32, created_at: i64, next_review: i64) -> Term {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "text": id,
+            "languageId": "de",
+            "translation": "",
+            "notes": "",
+            "status": status,
+            "createdAt": created_at,
+            "nextReview": next_review,
+        }))
+        .unwrap()
+    }
+
+    fn count_on(days: &[DailyCount], date: &str) -> usize {
+        days.iter().find(|d| d.date == date).map_or(0, |d| d.count)
+    }
+
+    #[test]
+    fn stats_bucket_by_local_day_around_midnight() {
+        let berlin = chrono::FixedOffset::east_opt(3600).unwrap();
+        let long_ago = at(&berlin, "2026-01-01 12:00:00");
+        let next_year = at(&berlin, "2027-01-01 12:00:00");
+        let terms = vec![
+            dated_term("just-after-midnight", 0, at(&berlin, "2026-03-10 00:00:00"), next_year),
+            dated_term("just-before-midnight", 0, at(&berlin, "2026-03-09 23:59:59"), next_year),
+            // 22:59 and 23:00 UTC: the same UTC day, different local days
+            dated_term("due-tonight", 1, long_ago, at(&berlin, "2026-03-10 23:59:59")),
+            dated_term("due-tomorrow", 1, long_ago, at(&berlin, "2026-03-11 00:00:00")),
+            dated_term("overdue", 2, long_ago, at(&berlin, "2026-03-01 09:00:00")),
+            dated_term("ignored", 99, long_ago, at(&berlin, "2026-03-10 08:00:00")),
+        ];
+        let now = chrono::DateTime::from_timestamp_millis(at(&berlin, "2026-03-10 12:00:00"))
+            .unwrap()
+            .with_timezone(&berlin);
+
+        let stats = vocabulary_stats(&terms, None, &now);
+        assert_eq!(stats.due_today, 2);
+        assert_eq!(stats.forecast[0].date, "2026-03-10");
+        assert_eq!(count_on(&stats.forecast, "2026-03-11"), 1);
+        assert_eq!(stats.forecast.len(), FORECAST_DAYS as usize + 1);
+        assert_eq!(stats.added_per_day.last().unwrap().date, "2026-03-10");
+        assert_eq!(count_on(&stats.added_per_day, "2026-03-10"), 1);
+        assert_eq!(count_on(&stats.added_per_day, "2026-03-09"), 1);
+        assert_eq!(stats.added_per_day.len(), ADDED_HISTORY_DAYS as usize);
+
+        // A minute before midnight the early-morning term is in the future
+        // and yesterday's term is today's
+        let late = chrono::DateTime::from_timestamp_millis(at(&berlin, "2026-03-09 23:59:59"))
+            .unwrap()
+            .with_timezone(&berlin);
+        let stats = vocabulary_stats(&terms, None, &late);
+        assert_eq!(stats.added_per_day.last().unwrap().date, "2026-03-09");
+        assert_eq!(count_on(&stats.added_per_day, "2026-03-09"), 1);
+        assert_eq!(count_on(&stats.forecast, "2026-03-10"), 1);
+    }
+
+    #[test]
+    fn stats_follow_a_timezone_change() {
+        let utc = chrono::FixedOffset::east_opt(0).unwrap();
+        let berlin = chrono::FixedOffset::east_opt(3600).unwrap();
+        let new_york = chrono::FixedOffset::west_opt(5 * 3600).unwrap();
+        // 03:00 UTC: the 10th in Berlin, still the 9th in New York
+        let terms = vec![dated_term("early", 0, at(&utc, "2026-03-10 03:00:00"), at(&utc, "2026-03-11 03:00:00"))];
+        let instant = chrono::DateTime::from_timestamp_millis(at(&utc, "2026-03-10 12:00:00")).unwrap();
+
+        let stats = vocabulary_stats(&terms, None, &instant.with_timezone(&berlin));
+        assert_eq!(count_on(&stats.added_per_day, "2026-03-10"), 1);
+        assert_eq!(count_on(&stats.forecast, "2026-03-11"), 1);
+
+        let stats = vocabulary_stats(&terms, None, &instant.with_timezone(&new_york));
+        assert_eq!(count_on(&stats.added_per_day, "2026-03-09"), 1);
+        assert_eq!(count_on(&stats.added_per_day, "2026-03-10"), 0);
+        assert_eq!(count_on(&stats.forecast, "2026-03-10"), 1);
+    }
+
+    #[test]
+    fn streak_runs_across_local_midnight() {
+        let berlin = chrono::FixedOffset::east_opt(3600).unwrap();
+        let day = |d: &str| chrono::NaiveDate::parse_from_str(d, "%Y-%m-%d").unwrap();
+        let terms = vec![
+            dated_term("late", 0, at(&berlin, "2026-03-08 23:59:59"), 0),
+            dated_term("early", 0, at(&berlin, "2026-03-09 00:00:00"), 0),
+        ];
+        let created = terms_by_day(&terms, &berlin);
+        assert_eq!(created.get(&day("2026-03-08")), Some(&1));
+        assert_eq!(created.get(&day("2026-03-09")), Some(&1));
+        let reviewed = BTreeMap::from([(day("2026-03-10"), 3)]);
+        let goal = DailyGoal { new_terms: 1, reviews: 3 };
+
+        let info = streak_info(&created, &reviewed, day("2026-03-10"), goal);
+        assert_eq!((info.current_streak, info.longest_streak), (3, 3));
+        assert_eq!((info.new_terms_today, info.reviews_today), (0, 3));
+        assert!(!info.goal_reached);
+
+        // Nothing yet today: yesterday's run still counts
+        let info = streak_info(&created, &BTreeMap::new(), day("2026-03-10"), goal);
+        assert_eq!((info.current_streak, info.longest_streak), (2, 2));
+        // A day skipped ends it
+        let info = streak_info(&created, &BTreeMap::new(), day("2026-03-11"), goal);
+        assert_eq!((info.current_streak, info.longest_streak), (0, 2));
+        let info = streak_info(&created, &BTreeMap::new(), day("2026-03-09"), goal);
+        assert_eq!(info.today, "2026-03-09");
+        // Both halves of the goal have to be met
+        assert_eq!((info.new_terms_today, info.goal_reached), (1, false));
+    }
+
+    #[test]
+    fn streak_follows_a_timezone_change() {
+        let utc = chrono::FixedOffset::east_opt(0).unwrap();
+        let tokyo = chrono::FixedOffset::east_opt(9 * 3600).unwrap();
+        let terms = vec![
+            dated_term("first", 0, at(&utc, "2026-03-09 01:00:00"), 0),
+            dated_term("second", 0, at(&utc, "2026-03-10 23:00:00"), 0),
+        ];
+        let instant = chrono::DateTime::from_timestamp_millis(at(&utc, "2026-03-11 00:30:00")).unwrap();
+        let goal = DailyGoal { new_terms: 1, reviews: 0 };
+
+        // Consecutive days in UTC; the 9th and the 11th in Tokyo
+        let today = instant.with_timezone(&utc).date_naive();
+        let info = streak_info(&terms_by_day(&terms, &utc), &BTreeMap::new(), today, goal);
+        assert_eq!(info.today, "2026-03-11");
+        assert_eq!((info.current_streak, info.longest_streak), (2, 2));
+        assert_eq!(info.new_terms_today, 0);
+        assert!(!info.goal_reached);
+
+        let today = instant.with_timezone(&tokyo).date_naive();
+        let info = streak_info(&terms_by_day(&terms, &tokyo), &BTreeMap::new(), today, goal);
+        assert_eq!(info.today, "2026-03-11");
+        assert_eq!((info.current_streak, info.longest_streak), (1, 1));
+        assert_eq!(info.new_terms_today, 1);
+        assert!(info.goal_reached);
+    }
+
+    fn store_with(ids: &[&str]) -> TermsData {
+        TermsData {
+            terms: ids.iter().map(|id| dated_term(id, 0, 1, 1)).collect(),
+            version: TERMS_VERSION.to_string(),
+            updatedAt: 1,
+        }
+    }
+
+    fn ids(data: &TermsData) -> Vec<&str> {
+        data.terms.iter().map(|t| t.id.as_str()).collect()
+    }
+
+    #[test]
+    fn saving_keeps_previous_generations() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("terms_de.json");
+        for (i, version) in [&["a"][..], &["a", "b"], &["a", "b", "c"]].into_iter().enumerate() {
+            save_store(&path, &store_with(version)).unwrap();
+            assert!(path.exists(), "save {}", i);
+        }
+        assert_eq!(ids(&read_terms_file(&path).unwrap()), vec!["a", "b", "c"]);
+        assert_eq!(ids(&read_terms_file(&generation_path(&path, 1)).unwrap()), vec!["a", "b"]);
+        assert_eq!(ids(&read_terms_file(&generation_path(&path, 2)).unwrap()), vec!["a"]);
+        assert!(!generation_path(&path, 3).exists());
+        assert!(!path.with_extension("json.tmp").exists());
+    }
+
+    #[test]
+    fn truncated_store_is_recovered_from_the_previous_generation() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("terms_de.json");
+        save_store(&path, &store_with(&["a"])).unwrap();
+        save_store(&path, &store_with(&["a", "b"])).unwrap();
+        save_store(&path, &store_with(&["a", "b", "c"])).unwrap();
+
+        // A write cut off halfway
+        let content = fs::read(&path).unwrap();
+        fs::write(&path, &content[..content.len() / 2]).unwrap();
+        assert_eq!(ids(&load_store(&path)), vec!["a", "b"]);
+
+        // The previous generation damaged too: the one before it
+        fs::write(generation_path(&path, 1), "").unwrap();
+        assert_eq!(ids(&load_store(&path)), vec!["a"]);
+
+        // Nothing readable left
+        fs::write(generation_path(&path, 2), "[").unwrap();
+        assert!(ids(&load_store(&path)).is_empty());
+    }
+
+    #[test]
+    fn missing_store_is_recovered_from_a_generation() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("terms_de.json");
+        assert!(ids(&load_store(&path)).is_empty());
+
+        save_store(&path, &store_with(&["a"])).unwrap();
+        save_store(&path, &store_with(&["a", "b"])).unwrap();
+        // As a crash mid-rotation under the old scheme left it
+        fs::remove_file(&path).unwrap();
+        assert_eq!(ids(&load_store(&path)), vec!["a"]);
+    }
+
+    #[test]
+    fn stores_mid_rotation_are_still_listed() {
+        let dir = tempfile::tempdir().unwrap();
+        let terms_path = dir.path().join("terms.json");
+        let de = language_path(&terms_path, "de");
+        let fr = language_path(&terms_path, "fr");
+        save_store(&de, &store_with(&["de:a"])).unwrap();
+        save_store(&fr, &store_with(&["fr:a"])).unwrap();
+        save_store(&fr, &store_with(&["fr:a", "fr:b"])).unwrap();
+        fs::remove_file(&fr).unwrap();
+        fs::write(dir.path().join("terms_it.json.tmp"), "[]").unwrap();
+        fs::write(dir.path().join("terms.json.migrated"), "[]").unwrap();
+
+        assert_eq!(language_stores(&terms_path), vec![de, fr]);
+        assert_eq!(ids(&load_terms(&terms_path)), vec!["de:a", "fr:a"]);
     }
 }
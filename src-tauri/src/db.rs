@@ -1,6 +1,21 @@
-use rusqlite::{params, Connection};
+use once_cell::sync::Lazy;
+use rusqlite::{params, Connection, ErrorCode, OpenFlags};
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+use crate::normalize::normalize_word;
+use crate::query_metrics;
+
+pub mod import;
+pub mod manifest;
+pub mod metadata;
+pub mod stardict;
+
+use manifest::DictionaryFile;
+use metadata::DictionaryMetadata;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct DictionaryEntry {
@@ -15,6 +30,107 @@ pub struct DictionaryEntry {
     pub link_part: Option<String>,
     pub inflections: Option<Vec<Inflection>>,
     pub etymology: Option<String>,
+    /// IPA transcriptions, "; "-separated
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ipa: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub etymology_chain: Option<EtymologyChain>,
+    /// Tags of the form row the query matched, when reached through `forms`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub matched_form_tags: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub senses: Option<Vec<Sense>>,
+    /// Usage examples, None when the dictionary was imported without them
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub examples: Option<Vec<Example>>,
+    /// Set when a sense ordering hint was passed: whether the hinted sense was found
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hint_applied: Option<bool>,
+    /// Whether any pronunciation row carries an audio recording
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub has_audio: Option<bool>,
+    /// Every IPA variant and recording, in dictionary order
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pronunciations: Option<Vec<Pronunciation>>,
+    /// Senses removed by the label filter
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hidden_sense_count: Option<usize>,
+    /// Quick actions for this entry, filled in by the command layer
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub actions: Option<Vec<crate::entry_actions::EntryAction>>,
+    /// Dictionary the entry came from, when the language has several
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dictionary_name: Option<String>,
+    /// Synonyms, antonyms and related words, None when the dictionary has none
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub relations: Option<Vec<Relation>>,
+    /// How the query reached this entry: "headword", "form", "prefix",
+    /// "link" (followed from a spelling variant), "compound" (a part of the
+    /// query), "lemma" (a Sanskrit splitter result for the query), "fuzzy"
+    /// or "online"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub matched_via: Option<String>,
+}
+
+/// One row of `sounds`: a transcription, a recording, or both
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Pronunciation {
+    pub ipa: Option<String>,
+    /// Accent or region, e.g. "UK", "US", "Received-Pronunciation"
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Wiktionary ogg/mp3 link, absolute
+    pub audio_url: Option<String>,
+}
+
+/// A word related to an entry, as a plain headword that can be looked up
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Relation {
+    /// "synonym", "antonym" or "related"
+    pub rel_type: String,
+    pub word: String,
+    /// Sense the relation belongs to; None when it's about the whole entry
+    pub sense_index: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Sense {
+    /// Row id in `senses`; changes when the dictionary is re-imported
+    pub id: String,
+    pub index: i64,
+    pub gloss: String,
+    /// Stable identity derived from the gloss text, survives re-imports
+    pub fingerprint: String,
+    #[serde(default)]
+    pub promoted: bool,
+    /// Register, usage and regional labels ("colloquial", "archaic", "Austria")
+    #[serde(default)]
+    pub labels: Vec<String>,
+    /// Matches a hidden label but was kept because it is the entry's only sense
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub filtered: bool,
+}
+
+/// A usage example attached to one of an entry's senses
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Example {
+    pub text: String,
+    pub translation: Option<String>,
+    pub sense_index: i64,
+}
+
+/// Which senses to hide or move up, from the user's settings
+#[derive(Debug, Clone, Default)]
+pub struct SenseFilter {
+    pub hidden_labels: Vec<String>,
+    pub preferred_region: Option<String>,
+}
+
+/// Identifies the sense a learner studied so it can be shown first
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct SenseOrderHint {
+    pub sense_id: Option<String>,
+    pub gloss_fingerprint: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -22,6 +138,85 @@ pub struct Inflection {
     pub form: String,
     pub tags: Option<String>,
     pub normalized_form: Option<String>,
+    /// The form the user searched for
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub matched: bool,
+}
+
+/// One ancestor in an etymology, parsed from a Kaikki etymology template
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct EtymologyStep {
+    pub relation: String,
+    pub language_code: Option<String>,
+    pub language: Option<String>,
+    pub word: Option<String>,
+    pub notes: Option<String>,
+    /// Entry id of the ancestor word in another installed dictionary
+    pub linked_entry_id: Option<String>,
+    pub linked_language: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct EtymologyChain {
+    pub parsed: bool,
+    pub steps: Vec<EtymologyStep>,
+    pub text: Option<String>,
+}
+
+/// How a query is matched against the dictionary
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SearchMode {
+    /// Forms first, then headwords, each with normalization; first hit wins.
+    /// Also accepted as "auto".
+    #[default]
+    Smart,
+    /// Only the literal headword, no normalization, case or forms fallback
+    Exact,
+    /// Every entry reachable through any headword or form match
+    AllForms,
+    /// Only lemmas that list the query among their forms
+    Lemma,
+    /// Headwords starting with the query, ranked like suggestions
+    Prefix,
+}
+
+impl SearchMode {
+    pub fn parse(mode: &str) -> Result<Self, String> {
+        match mode {
+            "smart" | "auto" => Ok(SearchMode::Smart),
+            "exact" => Ok(SearchMode::Exact),
+            "all_forms" => Ok(SearchMode::AllForms),
+            "lemma" => Ok(SearchMode::Lemma),
+            "prefix" => Ok(SearchMode::Prefix),
+            other => Err(format!(
+                "Unknown search mode '{}', expected auto, exact, lemma, prefix or all_forms",
+                other
+            )),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SearchMode::Smart => "smart",
+            SearchMode::Exact => "exact",
+            SearchMode::AllForms => "all_forms",
+            SearchMode::Lemma => "lemma",
+            SearchMode::Prefix => "prefix",
+        }
+    }
+}
+
+/// Headwords a prefix search returns per dictionary when no limit is given
+pub const PREFIX_RESULT_LIMIT: usize = 20;
+
+/// Optional sections of a dictionary lookup
+#[derive(Debug, Clone, Default)]
+pub struct SearchOptions {
+    pub include_details: bool,
+    pub mode: SearchMode,
+    pub sense_ordering: Option<SenseOrderHint>,
+    /// Headwords for `SearchMode::Prefix`, `PREFIX_RESULT_LIMIT` when None
+    pub limit: Option<usize>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -31,6 +226,31 @@ pub struct DictionaryStats {
     pub sense_count: i64,
     pub form_count: i64,
     pub synonym_count: i64,
+    /// Entries per part of speech, most common first
+    pub pos_counts: Vec<(String, i64)>,
+    /// Size of the database file in bytes
+    pub file_size: u64,
+    /// The columns lookups search, and whether an index covers each
+    pub indexes: Vec<IndexStatus>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexStatus {
+    pub table: String,
+    pub column: String,
+    pub indexed: bool,
+}
+
+/// What `optimize_dictionary` changed, with the time of a sample lookup
+/// before and after
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct OptimizeReport {
+    pub created_indexes: Vec<String>,
+    pub sample_word: Option<String>,
+    pub before_ms: f64,
+    pub after_ms: f64,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -42,10 +262,30 @@ pub struct LanguageInfo {
     pub word_count: i64,
     pub sense_count: i64,
     pub form_count: i64,
+    /// Highest-priority dictionary file
     pub path: Option<String>,
+    /// One entry per dictionary in priority order; the counts above are their sums
+    #[serde(default)]
+    pub dictionaries: Vec<DictionarySummary>,
+    /// Source and import date of the highest-priority dictionary
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<DictionaryMetadata>,
+}
+
+/// One dictionary of a language, as listed by `get_available_languages`
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DictionarySummary {
+    pub name: String,
+    pub priority: i64,
+    pub path: String,
+    pub word_count: i64,
+    pub sense_count: i64,
+    pub form_count: i64,
+    pub metadata: DictionaryMetadata,
 }
 
-fn get_dict_dir() -> PathBuf {
+pub fn get_dict_dir() -> PathBuf {
     // Try multiple locations in order:
     // 1. Executable directory (for production builds)
     // 2. Executable _up_ directory (for bundled builds)
@@ -122,512 +362,1912 @@ fn get_dict_dir() -> PathBuf {
     PathBuf::from("dict")
 }
 
-pub fn get_connection(lang_code: &str) -> Result<Connection, String> {
-    eprintln!("[CONN] Getting connection for language: {}", lang_code);
-
-    let dict_dir = get_dict_dir();
-    eprintln!("[CONN] dict_dir: {:?}", dict_dir);
-
-    if !dict_dir.exists() {
-        eprintln!("[CONN] ✗ Dictionary directory does not exist");
-        return Err(format!(
-            "Dictionary directory not found: {}",
-            dict_dir.display()
-        ));
+/// FNV-1a over the whitespace- and case-normalized gloss
+pub fn gloss_fingerprint(gloss: &str) -> String {
+    let normalized = gloss
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase();
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in normalized.bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
     }
-    eprintln!("[CONN] ✓ Dictionary directory exists");
-
-    // Map language names to codes for directory matching
-    let name_to_code = [
-        ("german", "de"),
-        ("sanskrit", "sa"),
-        ("english", "en"),
-        ("french", "fr"),
-        ("spanish", "es"),
-        ("italian", "it"),
-        ("portuguese", "pt"),
-        ("russian", "ru"),
-        ("chinese", "zh"),
-        ("japanese", "ja"),
-        ("korean", "ko"),
-        ("arabic", "ar"),
-    ];
-
-    let mut db_path: Option<PathBuf> = None;
-
-    if let Ok(entries) = std::fs::read_dir(&dict_dir) {
-        for entry in entries.flatten() {
-            let path = entry.path();
-            if path.is_dir() {
-                let dir_name = path
-                    .file_name()
-                    .and_then(|n| n.to_str())
-                    .unwrap_or("")
-                    .to_lowercase();
+    format!("{:016x}", hash)
+}
 
-                // Check if directory name matches language code or name
-                let matches = dir_name == lang_code
-                    || name_to_code.iter().any(|(name, code)| {
-                        (dir_name == *name && lang_code == *code)
-                            || (dir_name == *code && lang_code == *code)
-                    });
-
-                if matches {
-                    // Support both naming conventions
-                    let patterns = vec![
-                        format!("{}_dict.db", lang_code),
-                        "dictionary.db".to_string(),
-                        format!("{}_dict.db", dir_name),
-                    ];
-
-                    if let Ok(files) = std::fs::read_dir(&path) {
-                        for file in files.flatten() {
-                            let file_path = file.path();
-                            if let Some(file_name) = file_path.file_name().and_then(|n| n.to_str())
-                            {
-                                if patterns.iter().any(|p| p == file_name) {
-                                    db_path = Some(file_path);
-                                    break;
-                                }
-                            }
-                        }
-                    }
-                }
-            }
-            if db_path.is_some() {
-                break;
-            }
-        }
+/// Labels an older import left in the gloss itself: "(colloquial, Austria) to eat"
+pub fn parse_gloss_labels(gloss: &str) -> Vec<String> {
+    let Some(rest) = gloss.trim_start().strip_prefix('(') else {
+        return Vec::new();
+    };
+    let Some(end) = rest.find(')') else {
+        return Vec::new();
+    };
+    let labels: Vec<String> = rest[..end]
+        .split(',')
+        .map(|l| l.trim().to_string())
+        .collect();
+    // Parenthesized explanations aren't labels; labels are a word or two
+    if labels.iter().any(|l| l.is_empty() || l.split_whitespace().count() > 3) {
+        return Vec::new();
     }
+    labels
+}
 
-    let db_path = db_path.ok_or_else(|| {
-        format!(
-            "Dictionary not found for language '{}'. Searched in {}",
-            lang_code,
-            dict_dir.display()
-        )
-    })?;
-
-    Connection::open(&db_path).map_err(|e| format!("Failed to open database: {}", e))
+/// Labels from the `tags` column (a JSON array written by the importer),
+/// falling back to a parenthesized gloss prefix
+fn sense_labels(tags: Option<&str>, gloss: &str) -> Vec<String> {
+    match tags.and_then(|t| serde_json::from_str::<Vec<String>>(t).ok()) {
+        Some(labels) if !labels.is_empty() => labels,
+        _ => parse_gloss_labels(gloss),
+    }
 }
 
-fn normalize_word(word: &str) -> String {
-    let mut normalized = word.to_string();
+fn has_column(conn: &Connection, table: &str, column: &str) -> bool {
+    conn.prepare(&format!("SELECT {} FROM {} LIMIT 0", column, table)).is_ok()
+}
 
-    let replacements = [
-        ("ä", "ae"),
-        ("Ä", "Ae"),
-        ("ö", "oe"),
-        ("Ö", "Oe"),
-        ("ü", "ue"),
-        ("Ü", "Ue"),
-        ("ß", "ss"),
-        ("ẞ", "Ss"),
-        ("-", ""),
-        ("/", ""),
-    ];
+fn load_senses(conn: &Connection, entry_id: i64) -> Vec<Sense> {
+    let sql = if has_column(conn, "senses", "tags") {
+        "SELECT id, sense_index, gloss, tags FROM senses WHERE dictionary_id = ?1 ORDER BY sense_index, id"
+    } else {
+        "SELECT id, sense_index, gloss, NULL FROM senses WHERE dictionary_id = ?1 ORDER BY sense_index, id"
+    };
+    let mut stmt = match conn.prepare(sql) {
+        Ok(stmt) => stmt,
+        Err(_) => return Vec::new(),
+    };
+    stmt.query_map(params![entry_id], |r| {
+        let gloss: String = r.get(2)?;
+        let tags: Option<String> = r.get(3)?;
+        Ok(Sense {
+            id: r.get::<_, i64>(0)?.to_string(),
+            index: r.get(1)?,
+            fingerprint: gloss_fingerprint(&gloss),
+            labels: sense_labels(tags.as_deref(), &gloss),
+            gloss,
+            promoted: false,
+            filtered: false,
+        })
+    })
+    .map(|rows| rows.filter_map(|r| r.ok()).collect())
+    .unwrap_or_default()
+}
 
-    for (from, to) in replacements {
-        normalized = normalized.replace(from, to);
+/// Examples shown per entry
+const MAX_EXAMPLES: usize = 5;
+/// Related words shown per entry
+const MAX_RELATIONS: usize = 50;
+/// Pronunciation rows returned per entry
+const MAX_PRONUNCIATIONS: usize = 20;
+/// IPA variants joined into `DictionaryEntry::ipa`
+const MAX_JOINED_IPA: usize = 5;
+
+/// Wiktionary links are often protocol-relative ("//upload.wikimedia.org/...")
+fn absolute_audio_url(url: &str) -> Option<String> {
+    let url = url.trim();
+    if url.is_empty() {
+        None
+    } else if url.starts_with("//") {
+        Some(format!("https:{}", url))
+    } else {
+        Some(url.to_string())
     }
+}
 
-    normalized.to_lowercase()
+/// Pronunciation rows of an entry. `tags` is a JSON array from the importer;
+/// dictionaries from before the column have none.
+fn load_pronunciations(conn: &Connection, entry_id: i64) -> Vec<Pronunciation> {
+    let sql = if has_column(conn, "sounds", "tags") {
+        "SELECT ipa, audio_url, tags FROM sounds WHERE dictionary_id = ?1 ORDER BY id LIMIT ?2"
+    } else {
+        "SELECT ipa, audio_url, NULL FROM sounds WHERE dictionary_id = ?1 ORDER BY id LIMIT ?2"
+    };
+    let Ok(mut stmt) = conn.prepare(sql) else {
+        return Vec::new();
+    };
+    stmt.query_map(params![entry_id, MAX_PRONUNCIATIONS as i64], |r| {
+        let ipa: Option<String> = r.get(0)?;
+        let audio_url: Option<String> = r.get(1)?;
+        let tags: Option<String> = r.get(2)?;
+        Ok(Pronunciation {
+            ipa: ipa.map(|i| i.trim().to_string()).filter(|i| !i.is_empty()),
+            tags: tags.and_then(|t| serde_json::from_str(&t).ok()).unwrap_or_default(),
+            audio_url: audio_url.as_deref().and_then(absolute_audio_url),
+        })
+    })
+    .map(|rows| {
+        rows.filter_map(|r| r.ok())
+            .filter(|p| p.ipa.is_some() || p.audio_url.is_some())
+            .collect()
+    })
+    .unwrap_or_default()
 }
 
-fn extract_link_part(details: &Option<serde_json::Value>) -> Option<String> {
-    if let Some(d) = details {
-        if let Some(obj) = d.as_object() {
-            if let Some(senses) = obj.get("senses") {
-                if let Some(senses_arr) = senses.as_array() {
-                    for sense in senses_arr {
-                        if let Some(sense_obj) = sense.as_object() {
-                            if let Some(parts) = sense_obj.get("partsOfSpeech") {
-                                if let Some(parts_arr) = parts.as_array() {
-                                    for part in parts_arr {
-                                        if let Some(part_obj) = part.as_object() {
-                                            if let Some(rels) = part_obj.get("relations") {
-                                                if let Some(rels_arr) = rels.as_array() {
-                                                    for rel in rels_arr {
-                                                        if let Some(rel_obj) = rel.as_object() {
-                                                            if let Some(rel_type) =
-                                                                rel_obj.get("relType")
-                                                            {
-                                                                if rel_type == "linkedForm" {
-                                                                    if let Some(targets) =
-                                                                        rel_obj.get("targets")
-                                                                    {
-                                                                        if let Some(targets_arr) =
-                                                                            targets.as_array()
-                                                                        {
-                                                                            if let Some(first) =
-                                                                                targets_arr.first()
-                                                                            {
-                                                                                if let Some(
-                                                                                    target_obj,
-                                                                                ) = first
-                                                                                    .as_object()
-                                                                                {
-                                                                                    if let Some(w) =
-                                                                                        target_obj
-                                                                                            .get(
-                                                                                            "word",
-                                                                                        )
-                                                                                    {
-                                                                                        return Some(w.to_string().trim_matches('"').to_string());
-                                                                                    }
-                                                                                }
-                                                                            }
-                                                                        }
-                                                                    }
-                                                                }
-                                                            }
-                                                        }
-                                                    }
-                                                }
-                                            }
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
+/// Recordings of `word` across the language's dictionaries, in priority
+/// order, for playing without a full lookup
+pub fn audio_urls(word: &str, lang_code: &str) -> Result<Vec<String>, String> {
+    let mut urls: Vec<String> = Vec::new();
+    for (_, conn) in get_connections(lang_code)? {
+        let Ok(mut stmt) = conn.prepare(
+            "SELECT s.audio_url FROM sounds s
+             JOIN dictionary d ON d.id = s.dictionary_id
+             WHERE d.word = ?1 AND s.audio_url IS NOT NULL AND s.audio_url != ''
+             ORDER BY d.id, s.id",
+        ) else {
+            continue;
+        };
+        let found: Vec<String> = stmt
+            .query_map(params![word], |r| r.get::<_, String>(0))
+            .map(|rows| rows.filter_map(|r| r.ok()).collect())
+            .unwrap_or_default();
+        for url in found.iter().filter_map(|u| absolute_audio_url(u)) {
+            if !urls.contains(&url) {
+                urls.push(url);
             }
         }
     }
-    None
+    Ok(urls)
 }
 
-fn extract_etymology(details: &Option<serde_json::Value>) -> Option<String> {
-    if let Some(d) = details {
-        if let Some(obj) = d.as_object() {
-            if let Some(etymology) = obj.get("etymology") {
-                return Some(etymology.to_string().trim_matches('"').to_string());
-            }
-            if let Some(etymologies) = obj.get("etymologies") {
-                if let Some(arr) = etymologies.as_array() {
-                    if !arr.is_empty() {
-                        return Some(arr[0].to_string().trim_matches('"').to_string());
-                    }
-                }
+/// Relations from the importer's `relations` table, or the `synonyms` and
+/// `antonyms` tables of the Python converter
+fn load_relations(conn: &Connection, entry_id: i64) -> Option<Vec<Relation>> {
+    let mut relations: Vec<Relation> = Vec::new();
+    if has_column(conn, "relations", "rel_type") {
+        let mut stmt = conn
+            .prepare(
+                "SELECT rel_type, word, sense_index FROM relations
+                 WHERE dictionary_id = ?1 ORDER BY id LIMIT ?2",
+            )
+            .ok()?;
+        relations = stmt
+            .query_map(params![entry_id, MAX_RELATIONS as i64], |r| {
+                Ok(Relation { rel_type: r.get(0)?, word: r.get(1)?, sense_index: r.get(2)? })
+            })
+            .ok()?
+            .filter_map(|r| r.ok())
+            .collect();
+    } else {
+        for (table, column, rel_type) in [("synonyms", "synonym", "synonym"), ("antonyms", "antonym", "antonym")] {
+            if !has_column(conn, table, column) {
+                continue;
             }
+            let sql = format!("SELECT {} FROM {} WHERE dictionary_id = ?1 ORDER BY id", column, table);
+            let Ok(mut stmt) = conn.prepare(&sql) else {
+                continue;
+            };
+            let words: Vec<String> = stmt
+                .query_map(params![entry_id], |r| r.get(0))
+                .map(|rows| rows.filter_map(|r| r.ok()).collect())
+                .unwrap_or_default();
+            relations.extend(
+                words
+                    .iter()
+                    .filter_map(|w| import::relation_headword(w))
+                    .map(|word| Relation { rel_type: rel_type.to_string(), word, sense_index: None }),
+            );
         }
+        relations.truncate(MAX_RELATIONS);
+    }
+    if relations.is_empty() {
+        None
+    } else {
+        Some(relations)
     }
-    None
 }
 
-pub fn search_dictionary(word: &str, lang_code: &str) -> Result<Vec<DictionaryEntry>, String> {
-    let conn = get_connection(lang_code)?;
-    let normalized = normalize_word(word);
-    let mut results: Vec<DictionaryEntry> = Vec::new();
-    let mut seen_texts: std::collections::HashSet<String> = std::collections::HashSet::new();
+/// Examples from the `examples` JSON column of `senses`, or the plain
+/// `example` text column of dictionaries imported before it existed
+fn load_examples(conn: &Connection, entry_id: i64) -> Option<Vec<Example>> {
+    let structured = has_column(conn, "senses", "examples");
+    let sql = if structured {
+        "SELECT sense_index, examples FROM senses WHERE dictionary_id = ?1 AND examples IS NOT NULL ORDER BY sense_index, id"
+    } else if has_column(conn, "senses", "example") {
+        "SELECT sense_index, example FROM senses WHERE dictionary_id = ?1 AND example != '' ORDER BY sense_index, id"
+    } else {
+        return None;
+    };
+    let mut stmt = conn.prepare(sql).ok()?;
+    let rows: Vec<(i64, String)> = stmt
+        .query_map(params![entry_id], |r| Ok((r.get(0)?, r.get(1)?)))
+        .ok()?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    let mut examples = Vec::new();
+    for (sense_index, value) in rows {
+        if structured {
+            let parsed: Vec<serde_json::Value> = serde_json::from_str(&value).unwrap_or_default();
+            examples.extend(parsed.iter().filter_map(|e| {
+                let text = e.get("text")?.as_str()?.trim();
+                (!text.is_empty()).then(|| Example {
+                    text: text.to_string(),
+                    translation: e.get("translation").and_then(|t| t.as_str()).map(str::to_string),
+                    sense_index,
+                })
+            }));
+        } else {
+            examples.push(Example { text: value, translation: None, sense_index });
+        }
+        if examples.len() >= MAX_EXAMPLES {
+            break;
+        }
+    }
+    examples.truncate(MAX_EXAMPLES);
+    if examples.is_empty() { None } else { Some(examples) }
+}
 
-    // Step 1: Check forms table FIRST to find if the word is an inflection
-    let mut root_entry_id: Option<i64> = None;
-    let mut inflection_tags: Vec<String> = Vec::new();
+/// Labels Kaikki uses for regional varieties
+const REGION_LABELS: &[&str] = &[
+    "Austria", "Switzerland", "Germany", "Northern Germany", "Southern Germany", "Liechtenstein",
+    "US", "UK", "British", "American", "Australia", "Canada", "Ireland", "Scotland", "New Zealand",
+    "India", "South Africa", "Spain", "Latin America", "Mexico", "Argentina", "Brazil", "Portugal",
+    "France", "Belgium", "Quebec",
+];
 
-    eprintln!("[DICT] Step 1: Checking forms table for inflections...");
+fn is_region_label(label: &str) -> bool {
+    REGION_LABELS.iter().any(|r| r.eq_ignore_ascii_case(label))
+}
 
-    // Query forms table for exact form match (excluding error tags) - case insensitive
-    if let Ok(mut forms_stmt) = conn.prepare(
-        "SELECT dictionary_id, tags FROM forms 
-         WHERE LOWER(form) = LOWER(?1) AND (tags IS NULL OR tags NOT LIKE '%error%')
-         LIMIT 1",
-    ) {
-        if let Ok(row) = forms_stmt.query_row(params![word], |r| {
-            Ok((r.get::<_, i64>(0)?, r.get::<_, Option<String>>(1)?))
-        }) {
-            root_entry_id = Some(row.0);
-            eprintln!("[DICT] Found in forms table: dictionary_id={}", row.0);
-            if let Some(tags) = row.1 {
-                inflection_tags.push(tags.clone());
-                eprintln!("[DICT] inflection_tags: {}", tags);
-            }
-        } else {
-            eprintln!("[DICT] Not found in forms table (exact match)");
+/// Hide senses carrying a hidden label and move senses of the preferred
+/// region ahead of other regions' senses. An entry never loses all of its
+/// senses: if every one would be hidden, the first is kept and flagged.
+/// Returns how many senses were hidden.
+pub fn apply_sense_filter(entry: &mut DictionaryEntry, filter: &SenseFilter) -> usize {
+    let Some(senses) = entry.senses.as_mut() else {
+        return 0;
+    };
+    let is_hidden = |sense: &Sense| {
+        sense.labels.iter().any(|l| filter.hidden_labels.iter().any(|h| h.eq_ignore_ascii_case(l)))
+    };
+
+    let before = senses.len();
+    let mut hidden = 0;
+    if senses.iter().all(is_hidden) {
+        if let Some(first) = senses.first_mut() {
+            first.filtered = true;
+            hidden = before - 1;
+            senses.truncate(1);
         }
+    } else {
+        senses.retain(|s| !is_hidden(s));
+        hidden = before - senses.len();
     }
 
-    // If not found in forms, try normalized_form - case insensitive
-    if root_entry_id.is_none() {
-        if let Ok(mut forms_stmt) = conn.prepare(
-            "SELECT dictionary_id, tags FROM forms 
-             WHERE LOWER(normalized_form) = LOWER(?1) AND (tags IS NULL OR tags NOT LIKE '%error%')
-             LIMIT 1",
-        ) {
-            if let Ok(row) = forms_stmt.query_row(params![normalized], |r| {
-                Ok((r.get::<_, i64>(0)?, r.get::<_, Option<String>>(1)?))
-            }) {
-                root_entry_id = Some(row.0);
-                eprintln!(
-                    "[DICT] Found in forms table (normalized): dictionary_id={}",
-                    row.0
-                );
-                if let Some(tags) = row.1 {
-                    inflection_tags.push(tags);
-                }
+    if let Some(region) = filter.preferred_region.as_deref() {
+        // 0: preferred region, 1: unmarked, 2: some other region. A promoted
+        // (hinted) sense keeps its place at the front.
+        let rank = |sense: &Sense| {
+            if sense.promoted || sense.labels.iter().any(|l| l.eq_ignore_ascii_case(region)) {
+                0
+            } else if sense.labels.iter().any(|l| is_region_label(l)) {
+                2
             } else {
-                eprintln!("[DICT] Not found in forms table (normalized)");
+                1
             }
-        }
+        };
+        senses.sort_by_key(rank);
+    }
+
+    if hidden > 0 || filter.preferred_region.is_some() {
+        entry.definition = Some(senses.iter().map(|s| s.gloss.as_str()).collect::<Vec<_>>().join(" | "));
     }
+    entry.hidden_sense_count = Some(hidden);
+    hidden
+}
 
-    // Step 2: If forms table has the word, use the root entry from forms
-    // Otherwise, query dictionary table for direct match
-    let mut dictionary_id: Option<i64> = None;
+/// Apply the sense filter to every entry unless the caller asked to see
+/// hidden senses, returning the total number hidden
+pub fn filter_senses(entries: &mut [DictionaryEntry], filter: &SenseFilter, include_hidden: bool) -> usize {
+    if include_hidden {
+        return 0;
+    }
+    entries.iter_mut().map(|e| apply_sense_filter(e, filter)).sum()
+}
 
-    if root_entry_id.is_some() {
-        eprintln!("[DICT] Using root_entry_id from forms table");
-        dictionary_id = root_entry_id;
+/// Every sense label in a language's dictionary with its number of senses,
+/// most common first
+pub fn get_sense_labels(lang_code: &str) -> Result<Vec<(String, i64)>, String> {
+    let conn = get_connection(lang_code)?;
+    let mut counts: HashMap<String, i64> = HashMap::new();
+    if has_column(&conn, "senses", "tags") {
+        let mut stmt = conn
+            .prepare("SELECT tags, COUNT(*) FROM senses WHERE tags IS NOT NULL AND tags != '[]' GROUP BY tags")
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([], |r| Ok((r.get::<_, String>(0)?, r.get::<_, i64>(1)?)))
+            .map_err(|e| e.to_string())?;
+        for (tags, count) in rows.filter_map(|r| r.ok()) {
+            for label in serde_json::from_str::<Vec<String>>(&tags).unwrap_or_default() {
+                *counts.entry(label).or_insert(0) += count;
+            }
+        }
     } else {
-        eprintln!("[DICT] Step 2: Querying dictionary table for direct match...");
-        // Query dictionary table for exact match
-        if let Ok(id) = conn.query_row(
-            "SELECT id FROM dictionary WHERE word = ?1 LIMIT 1",
-            params![word],
-            |r| r.get::<_, i64>(0),
-        ) {
-            dictionary_id = Some(id);
-            eprintln!("[DICT] Found in dictionary table: id={}", id);
-        } else {
-            eprintln!("[DICT] Not found in dictionary table (exact)");
+        let mut stmt = conn
+            .prepare("SELECT gloss FROM senses WHERE gloss LIKE '(%'")
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([], |r| r.get::<_, String>(0))
+            .map_err(|e| e.to_string())?;
+        for gloss in rows.filter_map(|r| r.ok()) {
+            for label in parse_gloss_labels(&gloss) {
+                *counts.entry(label).or_insert(0) += 1;
+            }
         }
+    }
+    let mut labels: Vec<(String, i64)> = counts.into_iter().collect();
+    labels.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    Ok(labels)
+}
 
-        // If not found, try normalized_word
-        if dictionary_id.is_none() {
-            if let Ok(id) = conn.query_row(
-                "SELECT id FROM dictionary WHERE normalized_word = ?1 LIMIT 1",
-                params![normalized],
-                |r| r.get::<_, i64>(0),
-            ) {
-                dictionary_id = Some(id);
-                eprintln!("[DICT] Found in dictionary table (normalized): id={}", id);
-            } else {
-                eprintln!("[DICT] Not found in dictionary table (normalized)");
-            }
+/// Move the hinted sense to the front. The fingerprint wins over the row id
+/// because ids are reassigned when a dictionary is re-imported.
+pub fn apply_sense_hint(senses: &mut Vec<Sense>, hint: &SenseOrderHint) -> bool {
+    let position = hint
+        .gloss_fingerprint
+        .as_ref()
+        .and_then(|fp| senses.iter().position(|s| &s.fingerprint == fp))
+        .or_else(|| {
+            hint.sense_id
+                .as_ref()
+                .and_then(|id| senses.iter().position(|s| &s.id == id))
+        });
+
+    match position {
+        Some(pos) => {
+            let mut sense = senses.remove(pos);
+            sense.promoted = true;
+            senses.insert(0, sense);
+            true
         }
+        None => false,
     }
+}
 
-    eprintln!("[DICT] Final dictionary_id: {:?}", dictionary_id);
-    eprintln!("[DICT] Final root_entry_id: {:?}", root_entry_id);
+// ============================================================================
+// Connection cache
+// ============================================================================
 
-    // 步骤 4: 获取词条完整信息
-    if let Some(entry_id) = dictionary_id {
-        eprintln!("[DICT] ========== Fetching entry details ==========");
-        eprintln!("[DICT] entry_id: {}", entry_id);
-        eprintln!("[DICT] query_word: {}", word);
-        eprintln!("[DICT] root_entry_id: {:?}", root_entry_id);
+/// Idle connections kept per dictionary file; more than this are simply closed
+const MAX_IDLE_PER_DICTIONARY: usize = 4;
 
-        let mut stmt = conn
-            .prepare(
-                "SELECT d.id, d.word, d.lang, d.lang_code, d.pos, d.etymology_text, d.pronunciation,
-                        (SELECT GROUP_CONCAT(s.gloss, ' | ') FROM senses s WHERE s.dictionary_id = d.id) as definition,
-                        d.normalized_word
-                 FROM dictionary d
-                 WHERE d.id = ?1",
-            )
-            .map_err(|e| e.to_string())?;
+/// How long a connection waits on another process's lock, e.g. an import
+/// script writing the same file, before giving up
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
 
-        let entries = stmt
-            .query_map(params![entry_id], |row| {
-                let dict_word: String = row.get(1)?;
-                let normalized_word: Option<String> = row.get(8)?;
-
-                eprintln!("[DICT] dict_word from DB: {}", dict_word);
-                eprintln!("[DICT] normalized_word: {:?}", normalized_word);
-
-                // 获取 IPA
-                let ipa_from_sounds: Option<String> =
-                    match conn.prepare("SELECT ipa FROM sounds WHERE dictionary_id = ?1 LIMIT 5") {
-                        Ok(mut sounds_stmt) => sounds_stmt
-                            .query_map(params![entry_id], |row| row.get::<_, Option<String>>(0))
-                            .map(|rows| {
-                                let ipa_list: Vec<String> =
-                                    rows.filter_map(|r| r.ok().flatten()).collect();
-                                if ipa_list.is_empty() {
-                                    None
-                                } else {
-                                    Some(ipa_list.join("; "))
-                                }
-                            })
-                            .unwrap_or_default(),
-                        Err(_) => None,
-                    };
-
-                let ipa = ipa_from_sounds.or(row.get::<_, Option<String>>(6).unwrap_or(None));
-
-                // 获取原形词（如果是屈折形式）
-                let root_form_word: Option<String> = if root_entry_id.is_some() && dict_word != word
-                {
-                    // 从 dictionary 表获取原形词
-                    match conn.query_row(
-                        "SELECT word FROM dictionary WHERE id = ?1",
-                        params![entry_id],
-                        |r| r.get::<_, String>(0),
-                    ) {
-                        Ok(w) => {
-                            eprintln!("[DICT] root_form_word: {}", w);
-                            Some(w)
-                        }
-                        Err(e) => {
-                            eprintln!("[DICT] Failed to get root_form: {}", e);
-                            None
-                        }
-                    }
-                } else {
-                    eprintln!("[DICT] Not an inflection, root_form_word: None");
-                    None
-                };
+/// Error prefixes for a dictionary file that couldn't be opened, so the UI
+/// can tell the user what to do about it
+pub const DB_MISSING: &str = "db_missing";
+pub const DB_LOCKED: &str = "db_locked";
+pub const DB_INVALID: &str = "db_invalid";
 
-                // 构建屈折信息（如果查询的词是屈折形式）
-                let mut inflections_for_this: Option<Vec<Inflection>> = None;
+/// Error string for a failed open of or first read from `path`
+fn open_error(path: &Path, e: rusqlite::Error) -> String {
+    let prefix = if !path.exists() {
+        DB_MISSING
+    } else {
+        match e.sqlite_error_code() {
+            Some(ErrorCode::DatabaseBusy) | Some(ErrorCode::DatabaseLocked) => DB_LOCKED,
+            Some(ErrorCode::NotADatabase) => DB_INVALID,
+            _ => return format!("Failed to open database {}: {}", path.display(), e),
+        }
+    };
+    format!("{}: {}: {}", prefix, path.display(), e)
+}
 
-                if root_entry_id.is_some() {
-                    eprintln!(
-                        "[DICT] Fetching all inflected forms for dictionary_id={}",
-                        entry_id
-                    );
+/// Open a dictionary for lookups. The app never writes a dictionary after
+/// import, so the connection is read-only and query-only; the header is
+/// read right away so a locked or foreign file fails here, not mid-search.
+fn open_read_only(path: &Path) -> Result<Connection, String> {
+    if !path.exists() {
+        return Err(format!("{}: {}", DB_MISSING, path.display()));
+    }
+    let conn = Connection::open_with_flags(
+        path,
+        OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+    )
+    .map_err(|e| open_error(path, e))?;
+    conn.busy_timeout(BUSY_TIMEOUT).map_err(|e| open_error(path, e))?;
+    conn.pragma_update(None, "query_only", true)
+        .map_err(|e| open_error(path, e))?;
+    conn.query_row("PRAGMA schema_version", [], |r| r.get::<_, i64>(0))
+        .map_err(|e| open_error(path, e))?;
+    Ok(conn)
+}
 
-                    // Query ALL forms from forms table for this lemma
-                    let mut forms_stmt = conn
-                        .prepare(
-                            "SELECT form, tags, normalized_form FROM forms 
-                             WHERE dictionary_id = ?1 AND (tags IS NULL OR tags NOT LIKE '%error%')
-                             ORDER BY form
-                             LIMIT 50",
-                        )
-                        .map_err(|e| e.to_string());
-
-                    if let Ok(mut stmt) = forms_stmt {
-                        match stmt.query_map(params![entry_id], |row| {
-                            Ok(Inflection {
-                                form: row.get(0)?,
-                                tags: row.get(1)?,
-                                normalized_form: row.get(2)?,
-                            })
-                        }) {
-                            Ok(mapped_rows) => {
-                                let all_forms: Vec<Inflection> =
-                                    mapped_rows.filter_map(|r| r.ok()).collect();
-
-                                eprintln!(
-                                    "[DICT] Found {} inflected forms for entry_id={}",
-                                    all_forms.len(),
-                                    entry_id
-                                );
-
-                                // Debug: print first few forms
-                                for (i, form) in all_forms.iter().take(5).enumerate() {
-                                    eprintln!(
-                                        "[DICT] Form {}: form='{}', tags='{:?}', normalized='{:?}'",
-                                        i, form.form, form.tags, form.normalized_form
-                                    );
-                                }
-
-                                if !all_forms.is_empty() {
-                                    inflections_for_this = Some(all_forms);
-                                }
-                            }
-                            Err(e) => {
-                                eprintln!("[DICT] Error querying forms: {}", e);
-                            }
-                        }
-                    }
-                }
+/// Open a database to change it: importers, index creation and metadata.
+/// A file that already exists is switched to WAL so lookups reading it
+/// don't block the writer; a new one keeps the default journal for the
+/// importer to configure.
+pub fn open_for_write(path: &Path) -> Result<Connection, String> {
+    let existed = path.exists();
+    let conn = Connection::open(path).map_err(|e| open_error(path, e))?;
+    conn.busy_timeout(BUSY_TIMEOUT).map_err(|e| open_error(path, e))?;
+    if existed {
+        conn.query_row("PRAGMA journal_mode = WAL", [], |r| r.get::<_, String>(0))
+            .map_err(|e| open_error(path, e))?;
+    }
+    Ok(conn)
+}
 
-                Ok(DictionaryEntry {
-                    entry_id: Some(entry_id.to_string()),
-                    text: dict_word,
-                    language: row.get(2)?,
-                    translation: None,
-                    root_form: root_form_word,
-                    grammar: row.get::<_, Option<String>>(4)?,
-                    definition: row.get::<_, Option<String>>(7)?,
-                    details: None,
-                    link_part: None,
-                    inflections: inflections_for_this,
-                    etymology: row.get::<_, Option<String>>(5)?,
-                })
-            })
-            .map_err(|e| e.to_string())?;
+/// Resolved dictionaries and idle connections, so a lookup doesn't scan
+/// `dict/` and reopen the files every time. `generation` changes on every
+/// invalidation; connections checked out before that are closed instead
+/// of being returned.
+struct ConnectionCache {
+    /// Each language's dictionaries in priority order
+    dictionaries: HashMap<String, Vec<DictionaryFile>>,
+    idle: HashMap<PathBuf, Vec<Connection>>,
+    /// Last `get_available_languages` result; counting every database is slow
+    languages: Option<Vec<LanguageInfo>>,
+    generation: u64,
+}
 
-        for entry in entries.filter_map(|e| e.ok()) {
-            eprintln!(
-                "[DICT] Entry: text={}, root_form={:?}",
-                entry.text, entry.root_form
-            );
-            if !seen_texts.contains(&entry.text) {
-                seen_texts.insert(entry.text.clone());
-                results.push(entry);
+static CONNECTIONS: Lazy<Mutex<ConnectionCache>> = Lazy::new(|| {
+    Mutex::new(ConnectionCache {
+        dictionaries: HashMap::new(),
+        idle: HashMap::new(),
+        languages: None,
+        generation: 0,
+    })
+});
+
+/// A cached connection; goes back to the cache when dropped
+pub struct PooledConnection {
+    path: PathBuf,
+    generation: u64,
+    conn: Option<Connection>,
+}
+
+impl Deref for PooledConnection {
+    type Target = Connection;
+
+    fn deref(&self) -> &Connection {
+        self.conn.as_ref().expect("connection present until drop")
+    }
+}
+
+impl Drop for PooledConnection {
+    fn drop(&mut self) {
+        let Some(conn) = self.conn.take() else {
+            return;
+        };
+        let mut cache = CONNECTIONS.lock().unwrap();
+        let known = cache
+            .dictionaries
+            .values()
+            .flatten()
+            .any(|file| file.path == self.path);
+        if cache.generation != self.generation || !known {
+            return;
+        }
+        let idle = cache.idle.entry(self.path.clone()).or_default();
+        if idle.len() < MAX_IDLE_PER_DICTIONARY {
+            idle.push(conn);
+        }
+    }
+}
+
+/// Forget cached dictionaries and connections for one language, or for all
+/// of them, and the language list. Call after anything adds, replaces or
+/// deletes dictionary files.
+pub fn invalidate_connections(lang_code: Option<&str>) {
+    let mut cache = CONNECTIONS.lock().unwrap();
+    cache.languages = None;
+    match lang_code {
+        Some(code) => {
+            for file in cache.dictionaries.remove(code).unwrap_or_default() {
+                cache.idle.remove(&file.path);
             }
         }
+        None => {
+            cache.dictionaries.clear();
+            cache.idle.clear();
+        }
+    }
+    cache.generation += 1;
+}
 
-        eprintln!("[DICT] Total results before return: {}", results.len());
-        for (i, r) in results.iter().enumerate() {
-            eprintln!(
-                "[DICT] Result {}: text={}, root_form={:?}",
-                i, r.text, r.root_form
-            );
+/// The language's dictionaries in priority order, with the cache generation
+/// they were resolved under. A cached list whose files have disappeared
+/// from disk is evicted and resolved again.
+fn cached_dictionaries(lang_code: &str) -> Result<(Vec<DictionaryFile>, u64), String> {
+    let generation = {
+        let mut cache = CONNECTIONS.lock().unwrap();
+        let generation = cache.generation;
+        match cache.dictionaries.get(lang_code) {
+            Some(files) if files.iter().all(|file| file.path.exists()) => {
+                return Ok((files.clone(), generation));
+            }
+            Some(_) => {
+                eprintln!("[CONN] Cached dictionaries for {} changed on disk, evicting", lang_code);
+                for file in cache.dictionaries.remove(lang_code).unwrap_or_default() {
+                    cache.idle.remove(&file.path);
+                }
+            }
+            None => {}
+        }
+        generation
+    };
+
+    let files = resolve_dictionaries(lang_code)?;
+    for file in &files {
+        match open_for_write(&file.path) {
+            Ok(conn) => ensure_lookup_indexes(&conn),
+            Err(e) => eprintln!("[CONN] Could not create lookup indexes: {}", e),
         }
-        eprintln!("[DICT] ========== End search_dictionary ==========");
     }
 
-    Ok(results)
+    let mut cache = CONNECTIONS.lock().unwrap();
+    if cache.generation == generation {
+        cache.dictionaries.insert(lang_code.to_string(), files.clone());
+    }
+    Ok((files, generation))
 }
 
-fn search_inflections(
-    conn: &Connection,
-    word: &str,
-    normalized: &str,
-) -> Result<Vec<Inflection>, String> {
-    let mut stmt = conn
-        .prepare(
-            "SELECT form, tags, normalized_form FROM forms 
-             WHERE form = ?1 OR normalized_form = ?1 OR form = ?2 OR normalized_form = ?2
-             LIMIT 20",
-        )
-        .map_err(|e| e.to_string())?;
+fn checkout(path: &Path, generation: u64) -> Result<PooledConnection, String> {
+    let idle = CONNECTIONS
+        .lock()
+        .unwrap()
+        .idle
+        .get_mut(path)
+        .and_then(|idle| idle.pop());
+    let conn = match idle {
+        Some(conn) => conn,
+        None => open_read_only(path)?,
+    };
+    Ok(PooledConnection {
+        path: path.to_path_buf(),
+        generation,
+        conn: Some(conn),
+    })
+}
 
-    let inflections = stmt
-        .query_map(params![word, normalized], |row| {
-            Ok(Inflection {
-                form: row.get(0)?,
-                tags: row.get(1)?,
-                normalized_form: row.get(2)?,
-            })
-        })
-        .map_err(|e| e.to_string())?;
+/// A connection to the language's highest-priority dictionary, reused
+/// when possible
+pub fn get_connection(lang_code: &str) -> Result<PooledConnection, String> {
+    let (files, generation) = query_metrics::time("resolve", || cached_dictionaries(lang_code))?;
+    let primary = files
+        .first()
+        .ok_or_else(|| format!("Dictionary not found for language '{}'", lang_code))?;
+    query_metrics::time("connect", || checkout(&primary.path, generation))
+}
 
-    Ok(inflections.filter_map(|i| i.ok()).collect())
+/// Connections to every dictionary of the language in priority order. A
+/// file that fails to open is skipped so it doesn't hide the others.
+pub fn get_connections(lang_code: &str) -> Result<Vec<(DictionaryFile, PooledConnection)>, String> {
+    let (files, generation) = query_metrics::time("resolve", || cached_dictionaries(lang_code))?;
+    let mut connections = Vec::new();
+    for file in files {
+        match query_metrics::time("connect", || checkout(&file.path, generation)) {
+            Ok(conn) => connections.push((file, conn)),
+            Err(e) => eprintln!("[CONN] Skipping dictionary {}: {}", file.name, e),
+        }
+    }
+    if connections.is_empty() {
+        return Err(format!("No dictionary could be opened for language '{}'", lang_code));
+    }
+    Ok(connections)
 }
 
-pub fn get_language_stats(lang_code: &str) -> Result<DictionaryStats, String> {
-    let conn = get_connection(lang_code)?;
+/// Columns lookups can't work without, per table
+const REQUIRED_COLUMNS: &[(&str, &[&str])] = &[
+    ("dictionary", &["id", "word", "normalized_word", "pos", "lang", "lang_code"]),
+    ("senses", &["dictionary_id", "gloss"]),
+    ("forms", &["dictionary_id", "form", "normalized_form", "tags"]),
+];
+/// Tables whose absence only loses a feature
+const OPTIONAL_TABLES: &[(&str, &str)] = &[("sounds", "pronunciations and audio")];
+
+/// What's wrong with a dictionary file before it is installed
+#[derive(Debug, Clone, Default)]
+pub struct DictionaryFileReport {
+    /// "table" or "table.column" entries the app needs
+    pub missing: Vec<String>,
+    /// Optional parts that are absent
+    pub warnings: Vec<String>,
+    /// Problems found by `PRAGMA integrity_check`
+    pub corruption: Option<String>,
+}
 
-    // Kaikki format
-    let word_count: i64 = conn
-        .query_row("SELECT COUNT(DISTINCT word) FROM dictionary", [], |row| {
-            row.get(0)
+/// Check a dictionary file's schema and integrity without modifying it
+pub fn validate_dictionary_file(path: &std::path::Path) -> Result<DictionaryFileReport, String> {
+    let conn = Connection::open_with_flags(path, rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .map_err(|e| format!("Not a readable SQLite database: {}", e))?;
+    let mut report = DictionaryFileReport::default();
+
+    let integrity: Vec<String> = conn
+        .prepare("PRAGMA integrity_check")
+        .and_then(|mut stmt| {
+            stmt.query_map([], |r| r.get::<_, String>(0))
+                .map(|rows| rows.filter_map(|r| r.ok()).collect())
         })
-        .unwrap_or(0);
-
-    let sense_count: i64 = conn
-        .query_row("SELECT COUNT(*) FROM senses", [], |row| row.get(0))
-        .unwrap_or(0);
+        .map_err(|e| format!("Not a readable SQLite database: {}", e))?;
+    if integrity.first().map(String::as_str) != Some("ok") {
+        report.corruption = Some(integrity.into_iter().take(5).collect::<Vec<_>>().join("; "));
+        return Ok(report);
+    }
 
-    let form_count: i64 = conn
-        .query_row("SELECT COUNT(*) FROM forms", [], |row| row.get(0))
+    for (table, columns) in REQUIRED_COLUMNS {
+        if !has_column(&conn, table, "*") {
+            report.missing.push(table.to_string());
+            continue;
+        }
+        for column in columns.iter() {
+            if !has_column(&conn, table, column) {
+                report.missing.push(format!("{}.{}", table, column));
+            }
+        }
+    }
+    for (table, feature) in OPTIONAL_TABLES {
+        if !has_column(&conn, table, "*") {
+            report.warnings.push(format!("No {} table: {} unavailable", table, feature));
+        }
+    }
+    Ok(report)
+}
+
+/// Indexes on the foreign keys of `senses` and `forms`, which older
+/// importers didn't create. Runs once per dictionary file when it is first
+/// resolved; a read-only file just keeps scanning.
+fn ensure_lookup_indexes(conn: &Connection) {
+    if let Err(e) = conn.execute_batch(
+        "CREATE INDEX IF NOT EXISTS idx_senses_dictionary ON senses(dictionary_id);
+         CREATE INDEX IF NOT EXISTS idx_forms_dictionary ON forms(dictionary_id);",
+    ) {
+        eprintln!("[CONN] Could not create lookup indexes: {}", e);
+    }
+}
+
+/// The language's directory under `dict/`, named by code or by language name
+pub fn find_language_dir(lang_code: &str) -> Option<PathBuf> {
+    let dict_dir = get_dict_dir();
+
+    std::fs::read_dir(&dict_dir)
+        .ok()?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir())
+        .find(|path| {
+            let dir_name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+            crate::languages::dir_matches(dir_name, lang_code)
+        })
+}
+
+/// Find the dictionaries for a language by scanning `dict/`
+fn resolve_dictionaries(lang_code: &str) -> Result<Vec<DictionaryFile>, String> {
+    eprintln!("[CONN] Resolving dictionaries for language: {}", lang_code);
+
+    let dict_dir = get_dict_dir();
+    if !dict_dir.exists() {
+        eprintln!("[CONN] ✗ Dictionary directory does not exist");
+        return Err(format!(
+            "Dictionary directory not found: {}",
+            dict_dir.display()
+        ));
+    }
+
+    let files = find_language_dir(lang_code)
+        .map(|dir| manifest::scan(&dir))
+        .unwrap_or_default();
+    if files.is_empty() {
+        return Err(format!(
+            "Dictionary not found for language '{}'. Searched in {}",
+            lang_code,
+            dict_dir.display()
+        ));
+    }
+    for file in &files {
+        eprintln!("[CONN] ✓ {} (priority {}): {:?}", file.name, file.priority, file.path);
+    }
+    Ok(files)
+}
+
+/// Target of the first `linkedForm` relation in a details blob
+/// (senses → partsOfSpeech → relations)
+fn extract_link_part(details: &Option<serde_json::Value>) -> Option<String> {
+    let senses = details.as_ref()?.get("senses")?.as_array()?;
+    senses
+        .iter()
+        .filter_map(|sense| sense.get("partsOfSpeech")?.as_array())
+        .flatten()
+        .filter_map(|part| part.get("relations")?.as_array())
+        .flatten()
+        .filter(|rel| rel.get("relType").and_then(|t| t.as_str()) == Some("linkedForm"))
+        .find_map(|rel| {
+            let word = rel.get("targets")?.as_array()?.first()?.get("word")?;
+            Some(word.as_str().map(str::to_string).unwrap_or_else(|| word.to_string()))
+        })
+}
+
+/// Kaikki glosses that only point at another spelling
+const LINK_GLOSS_MARKERS: &[&str] = &[
+    "alternative spelling of ",
+    "alternative form of ",
+    "obsolete spelling of ",
+    "archaic spelling of ",
+    "dated spelling of ",
+    "nonstandard spelling of ",
+    "pre-reform spelling of ",
+    "former spelling of ",
+    "misspelling of ",
+];
+
+/// The word a gloss like "Alternative spelling of color (US)" points to
+fn gloss_link_target(gloss: &str) -> Option<String> {
+    let lower = gloss.to_lowercase();
+    let (start, marker) = LINK_GLOSS_MARKERS
+        .iter()
+        .find_map(|m| lower.find(m).map(|pos| (pos, *m)))?;
+    // Markers are ASCII, so offsets in the lowercased text line up only if
+    // everything before them is too
+    if !gloss.is_char_boundary(start + marker.len()) || !gloss[..start].is_ascii() {
+        return None;
+    }
+    let target: String = gloss[start + marker.len()..]
+        .split(['(', ',', ';', ':', '.', '“', '"'])
+        .next()?
+        .trim()
+        .to_string();
+    if target.is_empty() || target.split_whitespace().count() > 3 {
+        None
+    } else {
+        Some(target)
+    }
+}
+
+fn load_details(conn: &Connection, entry_id: i64) -> Option<serde_json::Value> {
+    let details: Option<String> = conn
+        .query_row(
+            "SELECT details FROM dictionary WHERE id = ?1",
+            params![entry_id],
+            |r| r.get(0),
+        )
+        .ok()
+        .flatten();
+    details.and_then(|d| serde_json::from_str::<serde_json::Value>(&d).ok())
+}
+
+/// Where an entry links to: a `linkedForm` relation, or else a sense whose
+/// gloss is just "alternative spelling of …"
+fn link_target(conn: &Connection, entry: &DictionaryEntry) -> Option<String> {
+    let entry_id = entry.entry_id.as_deref()?.parse::<i64>().ok()?;
+    extract_link_part(&load_details(conn, entry_id)).or_else(|| {
+        entry
+            .senses
+            .as_ref()?
+            .iter()
+            .find_map(|sense| gloss_link_target(&sense.gloss))
+    })
+}
+
+/// Linked entries are chased at most this many hops from a search result
+const MAX_LINK_DEPTH: usize = 2;
+
+/// Fill in `link_part` on results that point at another spelling and
+/// append the entries they point to. Visited words stop A → B → A cycles.
+fn follow_links(conn: &Connection, results: &mut Vec<DictionaryEntry>, options: &SearchOptions) {
+    let mut visited: std::collections::HashSet<String> =
+        results.iter().map(|e| e.text.to_lowercase()).collect();
+    let mut seen_ids: std::collections::HashSet<String> =
+        results.iter().filter_map(|e| e.entry_id.clone()).collect();
+    let mut frontier: Vec<usize> = (0..results.len()).collect();
+
+    for _ in 0..MAX_LINK_DEPTH {
+        let mut next = Vec::new();
+        for index in frontier {
+            let Some(target) = link_target(conn, &results[index]) else {
+                continue;
+            };
+            results[index].link_part = Some(target.clone());
+            if !visited.insert(target.to_lowercase()) {
+                continue;
+            }
+
+            let ids: Vec<i64> = conn
+                .prepare("SELECT id FROM dictionary WHERE word = ?1 ORDER BY id")
+                .and_then(|mut stmt| {
+                    stmt.query_map(params![target], |r| r.get::<_, i64>(0))
+                        .map(|rows| rows.filter_map(|r| r.ok()).collect())
+                })
+                .unwrap_or_default();
+            for id in ids {
+                if !seen_ids.insert(id.to_string()) {
+                    continue;
+                }
+                let candidate = EntryCandidate { id, via_form: false, form_tags: None };
+                if let Ok(Some(mut entry)) = fetch_entry(conn, &candidate, &target, options) {
+                    eprintln!("[DICT] Followed link to {} (id={})", target, id);
+                    entry.matched_via = Some("link".to_string());
+                    results.push(entry);
+                    next.push(results.len() - 1);
+                }
+            }
+        }
+        if next.is_empty() {
+            break;
+        }
+        frontier = next;
+    }
+}
+
+fn extract_etymology(details: &Option<serde_json::Value>) -> Option<String> {
+    if let Some(d) = details {
+        if let Some(obj) = d.as_object() {
+            if let Some(etymology) = obj.get("etymology") {
+                return Some(etymology.to_string().trim_matches('"').to_string());
+            }
+            if let Some(etymologies) = obj.get("etymologies") {
+                if let Some(arr) = etymologies.as_array() {
+                    if !arr.is_empty() {
+                        return Some(arr[0].to_string().trim_matches('"').to_string());
+                    }
+                }
+            }
+        }
+    }
+    None
+}
+
+fn etymology_relation(template_name: &str) -> Option<&'static str> {
+    match template_name {
+        "inh" | "inh+" | "inherited" => Some("inherited"),
+        "bor" | "bor+" | "borrowed" => Some("borrowed"),
+        "lbor" | "learned borrowing" => Some("learned_borrowing"),
+        "ubor" | "unadapted borrowing" => Some("unadapted_borrowing"),
+        "slbor" | "semi-learned borrowing" => Some("semi_learned_borrowing"),
+        "der" | "der+" | "derived" => Some("derived"),
+        "cal" | "calque" => Some("calque"),
+        "sl" | "semantic loan" => Some("semantic_loan"),
+        "psm" | "phono-semantic matching" => Some("phono_semantic_matching"),
+        _ => None,
+    }
+}
+
+/// Parse Kaikki `etymology_templates` into an ancestry chain. Templates that
+/// don't describe ancestry (cognates, mentions, affixes) are skipped.
+pub fn parse_etymology_templates(templates: &serde_json::Value) -> Vec<EtymologyStep> {
+    let mut steps = Vec::new();
+    let Some(arr) = templates.as_array() else {
+        return steps;
+    };
+
+    for template in arr {
+        let Some(name) = template.get("name").and_then(|v| v.as_str()) else {
+            continue;
+        };
+        let Some(relation) = etymology_relation(name) else {
+            continue;
+        };
+        let args = template.get("args");
+        let arg = |key: &str| -> Option<String> {
+            args.and_then(|a| a.get(key))
+                .and_then(|v| v.as_str())
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty() && v != "-")
+        };
+
+        // {{inh|<target lang>|<source lang>|<word>|<alt>|<gloss>}}
+        let language_code = arg("2");
+        let word = arg("3").or_else(|| arg("4"));
+        let notes = arg("t").or_else(|| arg("gloss")).or_else(|| arg("5"));
+
+        // The expansion reads "Middle High German liebe"; the language name is what precedes the word
+        let expansion = template.get("expansion").and_then(|v| v.as_str()).unwrap_or("");
+        let language = match &word {
+            Some(w) if expansion.contains(w.as_str()) => {
+                let name = expansion[..expansion.find(w.as_str()).unwrap_or(0)].trim();
+                if name.is_empty() { None } else { Some(name.to_string()) }
+            }
+            _ if !expansion.is_empty() => Some(expansion.trim().to_string()),
+            _ => None,
+        };
+
+        if language_code.is_none() && word.is_none() {
+            continue;
+        }
+
+        steps.push(EtymologyStep {
+            relation: relation.to_string(),
+            language_code,
+            language,
+            word,
+            notes,
+            linked_entry_id: None,
+            linked_language: None,
+        });
+    }
+
+    steps
+}
+
+fn load_etymology_templates(conn: &Connection, entry_id: i64) -> Option<serde_json::Value> {
+    // Newer imports keep the raw templates; older ones may only have a details blob
+    let raw: Option<String> = conn
+        .query_row(
+            "SELECT etymology_templates FROM dictionary WHERE id = ?1",
+            params![entry_id],
+            |r| r.get(0),
+        )
+        .ok()
+        .flatten();
+    if let Some(templates) = raw.and_then(|r| serde_json::from_str::<serde_json::Value>(&r).ok()) {
+        return Some(templates);
+    }
+
+    load_details(conn, entry_id).and_then(|d| d.get("etymology_templates").cloned())
+}
+
+fn build_etymology_chain(conn: &Connection, entry_id: i64, text: Option<String>) -> EtymologyChain {
+    let steps = load_etymology_templates(conn, entry_id)
+        .map(|t| parse_etymology_templates(&t))
+        .unwrap_or_default();
+
+    EtymologyChain {
+        parsed: !steps.is_empty(),
+        steps,
+        text,
+    }
+}
+
+/// Build the etymology chain for an entry. With `follow_local`, each ancestor
+/// is looked up in the installed dictionary for its language and linked.
+pub fn get_etymology_chain(
+    entry_id: i64,
+    lang_code: &str,
+    follow_local: bool,
+) -> Result<EtymologyChain, String> {
+    let conn = get_connection(lang_code)?;
+    let text: Option<String> = conn
+        .query_row(
+            "SELECT etymology_text FROM dictionary WHERE id = ?1",
+            params![entry_id],
+            |r| r.get(0),
+        )
+        .map_err(|e| format!("Entry {} not found: {}", entry_id, e))?;
+
+    let mut chain = build_etymology_chain(&conn, entry_id, text);
+
+    if follow_local {
+        for step in chain.steps.iter_mut() {
+            let (Some(code), Some(word)) = (&step.language_code, &step.word) else {
+                continue;
+            };
+            if code == lang_code {
+                continue;
+            }
+            let Ok(ancestor_conn) = get_connection(code) else {
+                continue;
+            };
+            if let Ok(id) = ancestor_conn.query_row(
+                "SELECT id FROM dictionary WHERE word = ?1 LIMIT 1",
+                params![word],
+                |r| r.get::<_, i64>(0),
+            ) {
+                step.linked_entry_id = Some(id.to_string());
+                step.linked_language = Some(code.clone());
+            }
+        }
+    }
+
+    Ok(chain)
+}
+
+/// Look a word up in every dictionary of the language, in priority order.
+/// Each entry is tagged with the dictionary it came from.
+pub fn search_dictionary_with(
+    word: &str,
+    lang_code: &str,
+    options: &SearchOptions,
+) -> Result<Vec<DictionaryEntry>, String> {
+    let mut results: Vec<DictionaryEntry> = Vec::new();
+    for (dictionary, conn) in get_connections(lang_code)? {
+        let mut found = search_connection(&conn, word, lang_code, options)?;
+        eprintln!("[DICT] {}: {} results", dictionary.name, found.len());
+        for entry in found.iter_mut() {
+            entry.dictionary_name = Some(dictionary.name.clone());
+        }
+        results.extend(found);
+    }
+
+    eprintln!("[DICT] Total results before return: {}", results.len());
+    Ok(results)
+}
+
+fn search_connection(
+    conn: &Connection,
+    word: &str,
+    lang_code: &str,
+    options: &SearchOptions,
+) -> Result<Vec<DictionaryEntry>, String> {
+    let normalized = normalize_word(word, lang_code);
+    let mut results: Vec<DictionaryEntry> = Vec::new();
+    let mut seen_ids: std::collections::HashSet<i64> = std::collections::HashSet::new();
+
+    let candidates = query_metrics::time("candidates", || match options.mode {
+        SearchMode::Smart => Ok(smart_candidates(conn, word, &normalized)),
+        SearchMode::Exact => Ok(exact_candidates(conn, word)),
+        SearchMode::AllForms => Ok(all_forms_candidates(conn, word, &normalized)),
+        SearchMode::Lemma => Ok(form_candidates(conn, word, &normalized)),
+        SearchMode::Prefix => prefix_candidates(conn, word, options.limit.unwrap_or(PREFIX_RESULT_LIMIT)),
+    })?;
+
+    eprintln!(
+        "[DICT] mode={:?}, candidates: {:?}",
+        options.mode,
+        candidates.iter().map(|c| c.id).collect::<Vec<_>>()
+    );
+
+    for candidate in &candidates {
+        // 步骤 4: 获取词条完整信息
+        if let Some(mut entry) = query_metrics::time("entries", || fetch_entry(conn, candidate, word, options))? {
+            eprintln!(
+                "[DICT] Entry: text={}, root_form={:?}",
+                entry.text, entry.root_form
+            );
+            if options.mode == SearchMode::Prefix {
+                entry.matched_via = Some("prefix".to_string());
+            }
+            // Homographs share their text, so only the id identifies a duplicate
+            if seen_ids.insert(candidate.id) {
+                results.push(entry);
+            }
+        }
+    }
+
+    // Exact mode means the literal headword only, and a prefix search
+    // lists headwords rather than resolving one
+    if !matches!(options.mode, SearchMode::Exact | SearchMode::Prefix) {
+        query_metrics::time("links", || follow_links(conn, &mut results, options));
+    }
+    Ok(results)
+}
+
+/// Escape `%`, `_` and `\\` for a LIKE pattern using `ESCAPE '\\'`
+fn escape_like(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '%' | '_' | '\\') {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// Words scored per fuzzy lookup; the SQL prefix/length filter picks them
+const FUZZY_CANDIDATE_CAP: usize = 50_000;
+
+/// Edit distance with adjacent transpositions counted as one edit
+/// (optimal string alignment), over characters
+fn damerau_levenshtein(a: &[char], b: &[char]) -> usize {
+    let (n, m) = (a.len(), b.len());
+    let mut d = vec![vec![0usize; m + 1]; n + 1];
+    for (i, row) in d.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in d[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+    for i in 1..=n {
+        for j in 1..=m {
+            let cost = if a[i - 1] == b[j - 1] { 0 } else { 1 };
+            d[i][j] = (d[i - 1][j] + 1).min(d[i][j - 1] + 1).min(d[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
+            }
+        }
+    }
+    d[n][m]
+}
+
+/// Edits tolerated for a query of `len` characters
+fn fuzzy_threshold(len: usize) -> usize {
+    match len {
+        0..=4 => 1,
+        5..=8 => 2,
+        _ => 3,
+    }
+}
+
+/// Languages whose compounds are split on a miss, with their linking
+/// elements (Fugenelemente), longest first
+const COMPOUND_LINKS: &[(&str, &[&str])] = &[("de", &["es", "s", "n", "e"])];
+/// Shortest constituent; shorter ones are mostly abbreviations and letters
+const COMPOUND_MIN_PART_CHARS: usize = 3;
+const COMPOUND_MAX_PARTS: usize = 4;
+/// Distinct dictionary probes per split, to bound worst-case latency
+const COMPOUND_MAX_LOOKUPS: usize = 200;
+/// Complete segmentations collected before picking the best one
+const COMPOUND_MAX_SPLITS: usize = 10;
+
+pub fn splits_compounds(lang_code: &str) -> bool {
+    COMPOUND_LINKS.iter().any(|(code, _)| *code == lang_code)
+}
+
+/// Memoized "is this a headword" probes for one split
+struct CompoundProbe<'a> {
+    conn: &'a Connection,
+    lang_code: &'a str,
+    /// (piece, last part) → headword it resolves to
+    known: HashMap<(String, bool), Option<String>>,
+}
+
+impl CompoundProbe<'_> {
+    /// The headword `piece` stands for: a word of its own, or for the last
+    /// part also an inflected form ("Fahrten" → "Fahrt"). Affix entries
+    /// don't count, or every "-ung" would become a constituent.
+    fn headword(&mut self, piece: &str, last: bool) -> Option<String> {
+        let key = (piece.to_string(), last);
+        if let Some(found) = self.known.get(&key) {
+            return found.clone();
+        }
+        if self.known.len() >= COMPOUND_MAX_LOOKUPS {
+            return None;
+        }
+        let normalized = normalize_word(piece, self.lang_code);
+        let mut found: Option<String> = self
+            .conn
+            .query_row(
+                "SELECT word FROM dictionary
+                 WHERE normalized_word = ?1 AND word NOT LIKE '-%' AND word NOT LIKE '%-'
+                   AND COALESCE(pos, '') NOT IN ('prefix', 'suffix', 'infix', 'interfix', 'affix', 'letter', 'character', 'symbol')
+                 ORDER BY LENGTH(word) = LENGTH(?2) DESC, id
+                 LIMIT 1",
+                params![normalized, piece],
+                |r| r.get(0),
+            )
+            .ok();
+        if found.is_none() && last {
+            found = self
+                .conn
+                .query_row(
+                    "SELECT d.word FROM forms f JOIN dictionary d ON d.id = f.dictionary_id
+                     WHERE f.normalized_form = ?1 AND (f.tags IS NULL OR f.tags NOT LIKE '%error%')
+                     ORDER BY d.id LIMIT 1",
+                    params![normalized],
+                    |r| r.get(0),
+                )
+                .ok();
+        }
+        self.known.insert(key, found.clone());
+        found
+    }
+}
+
+/// Complete segmentations of `chars[start..]`, longest first piece first,
+/// appended to `splits` until enough are found
+fn compound_splits(
+    probe: &mut CompoundProbe,
+    chars: &[char],
+    start: usize,
+    links: &[&str],
+    parts: &mut Vec<String>,
+    splits: &mut Vec<Vec<String>>,
+) {
+    if parts.len() >= COMPOUND_MAX_PARTS {
+        return;
+    }
+    for end in (start + COMPOUND_MIN_PART_CHARS..=chars.len()).rev() {
+        if splits.len() >= COMPOUND_MAX_SPLITS {
+            return;
+        }
+        let last = end == chars.len();
+        // A single-part split is the word itself, which already missed
+        if last && start == 0 {
+            continue;
+        }
+        let piece: String = chars[start..end].iter().collect();
+        let Some(headword) = probe.headword(&piece, last) else {
+            continue;
+        };
+        parts.push(headword);
+        if last {
+            splits.push(parts.clone());
+        } else {
+            compound_splits(probe, chars, end, links, parts, splits);
+            let rest: String = chars[end..].iter().collect();
+            for link in links {
+                let link_len = link.chars().count();
+                if rest.starts_with(link) && end + link_len < chars.len() {
+                    compound_splits(probe, chars, end + link_len, links, parts, splits);
+                }
+            }
+        }
+        parts.pop();
+    }
+}
+
+/// Split a compound that isn't a headword into known headwords, e.g.
+/// "Donaudampfschifffahrt" → Donau, Dampf, Schiff, Fahrt, allowing linking
+/// elements between parts ("Arbeitsamt" → Arbeit, Amt). Of the splits
+/// found, the one with the fewest parts wins, then the one whose parts are
+/// most even. None for a word that is a headword itself or has no split.
+pub fn split_compound(word: &str, lang_code: &str) -> Result<Option<Vec<String>>, String> {
+    if !splits_compounds(lang_code) {
+        return Ok(None);
+    }
+    let conn = get_connection(lang_code)?;
+    Ok(split_compound_on(&conn, word, lang_code))
+}
+
+fn split_compound_on(conn: &Connection, word: &str, lang_code: &str) -> Option<Vec<String>> {
+    let (_, links) = COMPOUND_LINKS.iter().find(|(code, _)| *code == lang_code)?;
+    let word = word.trim();
+    let chars: Vec<char> = word.to_lowercase().chars().collect();
+    if chars.len() < COMPOUND_MIN_PART_CHARS * 2 || !chars.iter().all(|c| c.is_alphabetic()) {
+        return None;
+    }
+
+    let mut probe = CompoundProbe { conn, lang_code, known: HashMap::new() };
+    let lowered: String = chars.iter().collect();
+    if probe.headword(&lowered, false).is_some() {
+        return None;
+    }
+
+    let mut splits = Vec::new();
+    compound_splits(&mut probe, &chars, 0, links, &mut Vec::new(), &mut splits);
+    eprintln!(
+        "[DICT] Compound '{}': {} split(s) after {} probes",
+        word,
+        splits.len(),
+        probe.known.len()
+    );
+    // Fewer parts first; among equals, the longest shortest part
+    splits.into_iter().min_by_key(|parts| {
+        let shortest = parts.iter().map(|p| p.chars().count()).min().unwrap_or(0);
+        (parts.len(), std::cmp::Reverse(shortest))
+    })
+}
+
+/// "Did you mean" lookup for a query that found nothing. Headwords sharing
+/// the first letter and within the allowed length difference are scored by
+/// edit distance; the closest `limit` words are returned with their entries
+/// and distance. Only meant to run after a miss, it scans rather than seeks.
+pub fn search_dictionary_fuzzy(
+    word: &str,
+    lang_code: &str,
+    limit: usize,
+    options: &SearchOptions,
+) -> Result<Vec<(String, usize, DictionaryEntry)>, String> {
+    let query: Vec<char> = word.trim().to_lowercase().chars().collect();
+    let Some(first) = query.first().copied() else {
+        return Ok(Vec::new());
+    };
+    let threshold = fuzzy_threshold(query.len());
+    let conn = get_connection(lang_code)?;
+
+    // LIKE only folds ASCII case, so ask for both spellings of the first letter
+    let lower_prefix = format!("{}%", escape_like(&first.to_string()));
+    let upper_prefix = format!("{}%", escape_like(&first.to_uppercase().to_string()));
+    let min_len = query.len().saturating_sub(threshold) as i64;
+    let max_len = (query.len() + threshold) as i64;
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT DISTINCT word FROM dictionary
+             WHERE (word LIKE ?1 ESCAPE '\\' OR word LIKE ?2 ESCAPE '\\')
+               AND length(word) BETWEEN ?3 AND ?4
+             LIMIT ?5",
+        )
+        .map_err(|e| format!("Failed to prepare fuzzy query: {}", e))?;
+    let words: Vec<String> = stmt
+        .query_map(
+            params![lower_prefix, upper_prefix, min_len, max_len, FUZZY_CANDIDATE_CAP as i64],
+            |r| r.get::<_, String>(0),
+        )
+        .map_err(|e| format!("Failed to run fuzzy query: {}", e))?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    let mut scored: Vec<(usize, String)> = words
+        .into_iter()
+        .filter_map(|candidate| {
+            let chars: Vec<char> = candidate.to_lowercase().chars().collect();
+            let distance = damerau_levenshtein(&query, &chars);
+            (distance <= threshold).then_some((distance, candidate))
+        })
+        .collect();
+    scored.sort();
+    eprintln!(
+        "[DICT] Fuzzy '{}' (threshold {}): {} close word(s)",
+        word,
+        threshold,
+        scored.len()
+    );
+
+    let mut results = Vec::new();
+    for (distance, matched) in scored.into_iter().take(limit) {
+        for candidate in exact_candidates(&conn, &matched) {
+            if let Some(mut entry) = fetch_entry(&conn, &candidate, &matched, options)? {
+                entry.matched_via = Some("fuzzy".to_string());
+                results.push((matched.clone(), distance, entry));
+            }
+        }
+    }
+    Ok(results)
+}
+
+/// Words per `IN (...)` list, well under SQLite's bound parameter limit
+const BATCH_CHUNK: usize = 400;
+
+/// `(dictionary_id, matched text, form tags)` for every row of `sql` whose
+/// placeholder list is filled with `values`, chunked
+fn query_in_chunks(
+    conn: &Connection,
+    sql: &str,
+    values: &[String],
+) -> Result<Vec<(i64, String, Option<String>)>, String> {
+    let mut rows = Vec::new();
+    for chunk in values.chunks(BATCH_CHUNK) {
+        let placeholders = vec!["?"; chunk.len()].join(",");
+        let mut stmt = conn
+            .prepare(&sql.replace("{}", &placeholders))
+            .map_err(|e| e.to_string())?;
+        let mapped = stmt
+            .query_map(rusqlite::params_from_iter(chunk.iter()), |r| {
+                Ok((r.get::<_, i64>(0)?, r.get::<_, String>(1)?, r.get::<_, Option<String>>(2)?))
+            })
+            .map_err(|e| e.to_string())?;
+        rows.extend(mapped.filter_map(|r| r.ok()));
+    }
+    Ok(rows)
+}
+
+/// Look up many words on one connection: headwords in chunked `IN` lists,
+/// then `forms` (as written and lowercased) for the words that missed.
+/// Words without any entry are absent from the map.
+pub fn batch_search(
+    words: &[String],
+    lang_code: &str,
+    options: &SearchOptions,
+) -> Result<HashMap<String, Vec<DictionaryEntry>>, String> {
+    let mut unique: Vec<String> = Vec::new();
+    for word in words {
+        if !word.is_empty() && !unique.contains(word) {
+            unique.push(word.clone());
+        }
+    }
+
+    let mut results: HashMap<String, Vec<DictionaryEntry>> = HashMap::new();
+    for (dictionary, conn) in get_connections(lang_code)? {
+        for (word, entries) in batch_search_connection(&conn, &unique, options)? {
+            let merged = results.entry(word).or_default();
+            merged.extend(entries.into_iter().map(|mut entry| {
+                entry.dictionary_name = Some(dictionary.name.clone());
+                entry
+            }));
+        }
+    }
+    eprintln!("[DICT] Batch: {}/{} words found", results.len(), unique.len());
+    Ok(results)
+}
+
+fn batch_search_connection(
+    conn: &Connection,
+    unique: &[String],
+    options: &SearchOptions,
+) -> Result<HashMap<String, Vec<DictionaryEntry>>, String> {
+    let phase_start = std::time::Instant::now();
+    let mut candidates: HashMap<String, Vec<EntryCandidate>> = HashMap::new();
+    let headwords = query_in_chunks(
+        conn,
+        "SELECT id, word, NULL FROM dictionary WHERE word IN ({}) ORDER BY id",
+        unique,
+    )?;
+    for (id, word, _) in headwords {
+        push_candidate(
+            candidates.entry(word).or_default(),
+            EntryCandidate { id, via_form: false, form_tags: None },
+        );
+    }
+
+    let misses: Vec<&String> = unique.iter().filter(|w| !candidates.contains_key(*w)).collect();
+    if !misses.is_empty() {
+        // Sentence-initial capitals: "Ging" should still find the form "ging"
+        let mut by_form: HashMap<String, Vec<&String>> = HashMap::new();
+        for word in &misses {
+            by_form.entry(word.to_string()).or_default().push(word);
+            let lower = word.to_lowercase();
+            if lower != **word {
+                by_form.entry(lower).or_default().push(word);
+            }
+        }
+        let forms: Vec<String> = by_form.keys().cloned().collect();
+        let rows = query_in_chunks(
+            conn,
+            "SELECT dictionary_id, form, tags FROM forms
+             WHERE form IN ({}) AND (tags IS NULL OR tags NOT LIKE '%error%')
+             ORDER BY dictionary_id",
+            &forms,
+        )?;
+        for (id, form, tags) in rows {
+            for word in by_form.get(&form).into_iter().flatten() {
+                push_candidate(
+                    candidates.entry(word.to_string()).or_default(),
+                    EntryCandidate { id, via_form: true, form_tags: tags.clone() },
+                );
+            }
+        }
+    }
+
+    query_metrics::record("candidates", phase_start.elapsed());
+
+    let mut results = HashMap::new();
+    for (word, word_candidates) in candidates {
+        let mut entries = Vec::new();
+        for candidate in &word_candidates {
+            if let Some(entry) = query_metrics::time("entries", || fetch_entry(conn, candidate, &word, options))? {
+                entries.push(entry);
+            }
+        }
+        if !entries.is_empty() {
+            results.insert(word, entries);
+        }
+    }
+    Ok(results)
+}
+
+/// A dictionary row reached from the query, either directly or through `forms`
+#[derive(Debug, Clone)]
+struct EntryCandidate {
+    id: i64,
+    via_form: bool,
+    form_tags: Option<String>,
+}
+
+fn push_candidate(candidates: &mut Vec<EntryCandidate>, candidate: EntryCandidate) {
+    if !candidates.iter().any(|c| c.id == candidate.id) {
+        candidates.push(candidate);
+    }
+}
+
+/// The "do what I mean" cascade: forms (exact, then normalized), then
+/// headwords (exact and normalized together). The first step with a hit
+/// wins, but every entry that step reaches is returned, so homographs and
+/// forms shared by several lemmas all show up.
+fn smart_candidates(conn: &Connection, word: &str, normalized: &str) -> Vec<EntryCandidate> {
+    // Step 1: Check forms table FIRST to find if the word is an inflection
+    let mut candidates = form_candidates(conn, word, normalized);
+    if !candidates.is_empty() {
+        return candidates;
+    }
+
+    // Step 2: query dictionary table for direct match
+    if let Ok(mut stmt) = conn.prepare(
+        "SELECT id FROM dictionary WHERE word = ?1 OR normalized_word = ?2
+         ORDER BY (word = ?1) DESC, id",
+    ) {
+        if let Ok(rows) = stmt.query_map(params![word, normalized], |r| r.get::<_, i64>(0)) {
+            for id in rows.filter_map(|r| r.ok()) {
+                push_candidate(&mut candidates, EntryCandidate { id, via_form: false, form_tags: None });
+            }
+        }
+    }
+    if candidates.is_empty() {
+        eprintln!("[DICT] Not found in forms or dictionary table");
+    } else {
+        eprintln!("[DICT] Found in dictionary table: {} entr(ies)", candidates.len());
+    }
+    candidates
+}
+
+/// Lemmas listing the query as a form: the exact spelling (any case) if
+/// that finds any, otherwise the normalized one
+fn form_candidates(conn: &Connection, word: &str, normalized: &str) -> Vec<EntryCandidate> {
+    let mut candidates: Vec<EntryCandidate> = Vec::new();
+    let form_queries = [
+        ("SELECT dictionary_id, tags FROM forms
+          WHERE LOWER(form) = LOWER(?1) AND (tags IS NULL OR tags NOT LIKE '%error%')
+          ORDER BY dictionary_id", word),
+        ("SELECT dictionary_id, tags FROM forms
+          WHERE LOWER(normalized_form) = LOWER(?1) AND (tags IS NULL OR tags NOT LIKE '%error%')
+          ORDER BY dictionary_id", normalized),
+    ];
+    for (sql, value) in form_queries {
+        if let Ok(mut stmt) = conn.prepare(sql) {
+            if let Ok(rows) = stmt.query_map(params![value], |r| {
+                Ok((r.get::<_, i64>(0)?, r.get::<_, Option<String>>(1)?))
+            }) {
+                for (id, tags) in rows.filter_map(|r| r.ok()) {
+                    push_candidate(&mut candidates, EntryCandidate { id, via_form: true, form_tags: tags });
+                }
+            }
+        }
+        if !candidates.is_empty() {
+            eprintln!("[DICT] Found in forms table: {} lemma(s)", candidates.len());
+            return candidates;
+        }
+    }
+    candidates
+}
+
+/// Every entry of the first `limit` headwords starting with `prefix`, in
+/// suggestion order
+fn prefix_candidates(conn: &Connection, prefix: &str, limit: usize) -> Result<Vec<EntryCandidate>, String> {
+    let mut candidates = Vec::new();
+    for (headword, _) in prefix_headwords(conn, prefix, limit, 0)? {
+        for candidate in exact_candidates(conn, &headword) {
+            push_candidate(&mut candidates, candidate);
+        }
+    }
+    Ok(candidates)
+}
+
+/// Exact headword only: no normalization, case folding or forms fallback
+fn exact_candidates(conn: &Connection, word: &str) -> Vec<EntryCandidate> {
+    let mut stmt = match conn.prepare("SELECT id FROM dictionary WHERE word = ?1 ORDER BY id") {
+        Ok(stmt) => stmt,
+        Err(_) => return Vec::new(),
+    };
+    stmt.query_map(params![word], |r| r.get::<_, i64>(0))
+        .map(|rows| {
+            rows.filter_map(|r| r.ok())
+                .map(|id| EntryCandidate { id, via_form: false, form_tags: None })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Every entry reachable from the query: all headwords matching it case- or
+/// normalization-insensitively, plus every lemma that lists it as a form.
+fn all_forms_candidates(conn: &Connection, word: &str, normalized: &str) -> Vec<EntryCandidate> {
+    let mut candidates: Vec<EntryCandidate> = Vec::new();
+
+    if let Ok(mut stmt) = conn.prepare(
+        "SELECT id FROM dictionary
+         WHERE word = ?1 OR LOWER(word) = LOWER(?1) OR normalized_word = ?2
+         ORDER BY (word = ?1) DESC, id",
+    ) {
+        if let Ok(rows) = stmt.query_map(params![word, normalized], |r| r.get::<_, i64>(0)) {
+            for id in rows.filter_map(|r| r.ok()) {
+                push_candidate(&mut candidates, EntryCandidate { id, via_form: false, form_tags: None });
+            }
+        }
+    }
+
+    if let Ok(mut stmt) = conn.prepare(
+        "SELECT dictionary_id, tags FROM forms
+         WHERE (LOWER(form) = LOWER(?1) OR LOWER(normalized_form) = LOWER(?2))
+           AND (tags IS NULL OR tags NOT LIKE '%error%')
+         ORDER BY dictionary_id",
+    ) {
+        if let Ok(rows) = stmt.query_map(params![word, normalized], |r| {
+            Ok((r.get::<_, i64>(0)?, r.get::<_, Option<String>>(1)?))
+        }) {
+            for (id, tags) in rows.filter_map(|r| r.ok()) {
+                push_candidate(&mut candidates, EntryCandidate { id, via_form: true, form_tags: tags });
+            }
+        }
+    }
+
+    candidates
+}
+
+/// Fetch a single entry by its id
+pub fn get_entry_by_id(
+    entry_id: i64,
+    lang_code: &str,
+    options: &SearchOptions,
+) -> Result<Option<DictionaryEntry>, String> {
+    let conn = get_connection(lang_code)?;
+    let candidate = EntryCandidate { id: entry_id, via_form: false, form_tags: None };
+    fetch_entry(&conn, &candidate, "", options)
+}
+
+fn fetch_entry(
+    conn: &Connection,
+    candidate: &EntryCandidate,
+    word: &str,
+    options: &SearchOptions,
+) -> Result<Option<DictionaryEntry>, String> {
+    let entry_id = candidate.id;
+    let via_form = candidate.via_form;
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT d.id, d.word, d.lang, d.lang_code, d.pos, d.etymology_text, d.pronunciation,
+                    (SELECT GROUP_CONCAT(s.gloss, ' | ') FROM senses s WHERE s.dictionary_id = d.id) as definition,
+                    d.normalized_word
+             FROM dictionary d
+             WHERE d.id = ?1",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let entries = stmt
+        .query_map(params![entry_id], |row| {
+            let dict_word: String = row.get(1)?;
+
+            // 获取 IPA（以及是否有录音）
+            let pronunciations = load_pronunciations(conn, entry_id);
+            let mut ipa_list: Vec<&str> = Vec::new();
+            for ipa in pronunciations.iter().filter_map(|p| p.ipa.as_deref()) {
+                if ipa_list.len() < MAX_JOINED_IPA && !ipa_list.contains(&ipa) {
+                    ipa_list.push(ipa);
+                }
+            }
+            let ipa_from_sounds = if ipa_list.is_empty() { None } else { Some(ipa_list.join("; ")) };
+            let has_audio = pronunciations.iter().any(|p| p.audio_url.is_some());
+
+            let ipa = ipa_from_sounds.or(row.get::<_, Option<String>>(6).unwrap_or(None));
+
+            // 获取原形词（如果是屈折形式）
+            let root_form_word: Option<String> = if via_form && dict_word != word {
+                Some(dict_word.clone())
+            } else {
+                None
+            };
+
+            // 完整的屈折表
+            let lang_code: Option<String> = row.get(3)?;
+            let inflections = search_inflections(conn, entry_id, word, lang_code.as_deref().unwrap_or(""))
+                .unwrap_or_else(|e| {
+                    eprintln!("[DICT] Error querying forms: {}", e);
+                    Vec::new()
+                });
+
+            let mut senses = load_senses(conn, entry_id);
+            let mut definition = row.get::<_, Option<String>>(7)?;
+            let hint_applied = options.sense_ordering.as_ref().map(|hint| {
+                let applied = apply_sense_hint(&mut senses, hint);
+                if applied {
+                    definition = Some(
+                        senses.iter().map(|s| s.gloss.as_str()).collect::<Vec<_>>().join(" | "),
+                    );
+                }
+                applied
+            });
+
+            let etymology = row.get::<_, Option<String>>(5)?;
+            let etymology_chain = if options.include_details {
+                Some(build_etymology_chain(conn, entry_id, etymology.clone()))
+            } else {
+                None
+            };
+
+            Ok(DictionaryEntry {
+                entry_id: Some(entry_id.to_string()),
+                text: dict_word,
+                language: row.get(2)?,
+                translation: None,
+                root_form: root_form_word,
+                grammar: row.get::<_, Option<String>>(4)?,
+                definition,
+                details: None,
+                link_part: None,
+                inflections: if inflections.is_empty() { None } else { Some(inflections) },
+                etymology,
+                ipa,
+                etymology_chain,
+                matched_form_tags: candidate.form_tags.clone(),
+                senses: if senses.is_empty() { None } else { Some(senses) },
+                examples: load_examples(conn, entry_id),
+                hint_applied,
+                has_audio: Some(has_audio),
+                pronunciations: if pronunciations.is_empty() { None } else { Some(pronunciations) },
+                hidden_sense_count: None,
+                actions: None,
+                dictionary_name: None,
+                relations: load_relations(conn, entry_id),
+                matched_via: Some(if via_form { "form" } else { "headword" }.to_string()),
+            })
+        })
+        .map_err(|e| e.to_string())?;
+
+    let entry = entries.filter_map(|e| e.ok()).next();
+    Ok(entry)
+}
+
+/// Forms returned per entry; a few verbs list far more than anyone reads
+const MAX_INFLECTIONS: usize = 300;
+
+/// Every form of an entry, grouped by tags (groups in the order the
+/// dictionary first lists them), with the one matching `word` marked
+fn search_inflections(
+    conn: &Connection,
+    dictionary_id: i64,
+    word: &str,
+    lang_code: &str,
+) -> Result<Vec<Inflection>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT form, tags, normalized_form FROM forms
+             WHERE dictionary_id = ?1 AND (tags IS NULL OR tags NOT LIKE '%error%')
+             ORDER BY id
+             LIMIT ?2",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let lowered = word.to_lowercase();
+    let normalized = normalize_word(word, lang_code);
+    let rows = stmt
+        .query_map(params![dictionary_id, MAX_INFLECTIONS as i64], |row| {
+            let form: String = row.get(0)?;
+            let normalized_form: Option<String> = row.get(2)?;
+            let matched = !word.is_empty()
+                && (form.to_lowercase() == lowered || normalized_form.as_deref() == Some(normalized.as_str()));
+            Ok(Inflection {
+                form,
+                tags: row.get(1)?,
+                normalized_form,
+                matched,
+            })
+        })
+        .map_err(|e| e.to_string())?;
+
+    let mut groups: Vec<(Option<String>, Vec<Inflection>)> = Vec::new();
+    for inflection in rows.filter_map(|i| i.ok()) {
+        match groups.iter_mut().find(|(tags, _)| *tags == inflection.tags) {
+            Some((_, forms)) => forms.push(inflection),
+            None => groups.push((inflection.tags.clone(), vec![inflection])),
+        }
+    }
+    Ok(groups.into_iter().flat_map(|(_, forms)| forms).collect())
+}
+
+pub fn get_language_stats(lang_code: &str) -> Result<DictionaryStats, String> {
+    let conn = get_connection(lang_code)?;
+
+    // Kaikki format
+    let word_count: i64 = conn
+        .query_row("SELECT COUNT(DISTINCT word) FROM dictionary", [], |row| {
+            row.get(0)
+        })
+        .unwrap_or(0);
+
+    let sense_count: i64 = conn
+        .query_row("SELECT COUNT(*) FROM senses", [], |row| row.get(0))
+        .unwrap_or(0);
+
+    let form_count: i64 = conn
+        .query_row("SELECT COUNT(*) FROM forms", [], |row| row.get(0))
         .unwrap_or(0);
 
+    // Relations as stored by the importer, or a table of synonyms
+    let synonym_count: i64 = if has_column(&conn, "relations", "rel_type") {
+        conn.query_row("SELECT COUNT(*) FROM relations WHERE rel_type = 'synonym'", [], |row| row.get(0))
+            .unwrap_or(0)
+    } else if has_column(&conn, "synonyms", "*") {
+        conn.query_row("SELECT COUNT(*) FROM synonyms", [], |row| row.get(0))
+            .unwrap_or(0)
+    } else {
+        0
+    };
+
+    let pos_counts: Vec<(String, i64)> = conn
+        .prepare(
+            "SELECT COALESCE(pos, 'unknown'), COUNT(*) FROM dictionary
+             GROUP BY 1 ORDER BY 2 DESC, 1",
+        )
+        .and_then(|mut stmt| {
+            stmt.query_map([], |r| Ok((r.get(0)?, r.get(1)?)))
+                .map(|rows| rows.filter_map(|r| r.ok()).collect())
+        })
+        .unwrap_or_default();
+
+    let file_size = std::fs::metadata(&conn.path).map(|m| m.len()).unwrap_or(0);
+    let indexes = LOOKUP_INDEXES
+        .iter()
+        .map(|(table, column, _)| IndexStatus {
+            table: table.to_string(),
+            column: column.to_string(),
+            indexed: column_indexed(&conn, table, column),
+        })
+        .collect();
+
     Ok(DictionaryStats {
         word_count,
         sense_count,
         form_count,
-        synonym_count: 0,
+        synonym_count,
+        pos_counts,
+        file_size,
+        indexes,
+    })
+}
+
+/// Columns the lookups filter on, with the index the importer gives each
+const LOOKUP_INDEXES: &[(&str, &str, &str)] = &[
+    ("dictionary", "word", "idx_dictionary_word"),
+    ("dictionary", "normalized_word", "idx_dictionary_normalized"),
+    ("forms", "form", "idx_forms_form"),
+    ("forms", "normalized_form", "idx_forms_normalized"),
+];
+
+/// Lookups timed per measurement in `optimize_dictionary`
+const SAMPLE_RUNS: u32 = 5;
+
+/// Whether some index on `table` starts with `column`, so SQLite can seek on it
+fn column_indexed(conn: &Connection, table: &str, column: &str) -> bool {
+    let names: Vec<String> = conn
+        .prepare(&format!("PRAGMA index_list({})", table))
+        .and_then(|mut stmt| {
+            stmt.query_map([], |r| r.get::<_, String>(1))
+                .map(|rows| rows.filter_map(|r| r.ok()).collect())
+        })
+        .unwrap_or_default();
+    names.iter().any(|name| {
+        conn.query_row(
+            &format!("SELECT name FROM pragma_index_info('{}') WHERE seqno = 0", name.replace('\'', "''")),
+            [],
+            |r| r.get::<_, String>(0),
+        )
+        .is_ok_and(|first| first == column)
+    })
+}
+
+/// Average milliseconds of a smart lookup of `word`
+fn time_lookup(conn: &Connection, word: &str, lang_code: &str) -> f64 {
+    let normalized = normalize_word(word, lang_code);
+    let start = std::time::Instant::now();
+    for _ in 0..SAMPLE_RUNS {
+        smart_candidates(conn, word, &normalized);
+    }
+    start.elapsed().as_secs_f64() * 1000.0 / SAMPLE_RUNS as f64
+}
+
+/// Create the lookup indexes the language's main dictionary lacks and
+/// refresh the query planner's statistics
+pub fn optimize_dictionary(lang_code: &str) -> Result<OptimizeReport, String> {
+    let path = get_connection(lang_code)?.path.clone();
+    let conn = open_for_write(&path)?;
+
+    let sample_word: Option<String> = conn
+        .query_row(
+            "SELECT word FROM dictionary LIMIT 1 OFFSET (SELECT COUNT(*) / 2 FROM dictionary)",
+            [],
+            |r| r.get(0),
+        )
+        .ok();
+    let before_ms = sample_word.as_deref().map_or(0.0, |w| time_lookup(&conn, w, lang_code));
+
+    let mut created_indexes = Vec::new();
+    for (table, column, name) in LOOKUP_INDEXES {
+        if column_indexed(&conn, table, column) {
+            continue;
+        }
+        conn.execute_batch(&format!("CREATE INDEX IF NOT EXISTS {} ON {}({})", name, table, column))
+            .map_err(|e| format!("Failed to create index {}: {}", name, e))?;
+        created_indexes.push(name.to_string());
+    }
+    ensure_lookup_indexes(&conn);
+    conn.execute_batch("ANALYZE").map_err(|e| format!("Failed to analyze: {}", e))?;
+
+    let after_ms = sample_word.as_deref().map_or(0.0, |w| time_lookup(&conn, w, lang_code));
+    eprintln!(
+        "[DICT] Optimized {}: created {:?}, sample lookup {:.2} ms -> {:.2} ms",
+        lang_code, created_indexes, before_ms, after_ms
+    );
+    Ok(OptimizeReport {
+        created_indexes,
+        sample_word,
+        before_ms,
+        after_ms,
     })
 }
 
+/// Installed languages with per-dictionary counts, cached until the next
+/// `invalidate_connections`
 pub fn get_available_languages() -> Result<Vec<LanguageInfo>, String> {
+    let generation = {
+        let cache = CONNECTIONS.lock().unwrap();
+        if let Some(languages) = &cache.languages {
+            return Ok(languages.clone());
+        }
+        cache.generation
+    };
+    let languages = scan_available_languages()?;
+    let mut cache = CONNECTIONS.lock().unwrap();
+    if cache.generation == generation {
+        cache.languages = Some(languages.clone());
+    }
+    Ok(languages)
+}
+
+fn scan_available_languages() -> Result<Vec<LanguageInfo>, String> {
     let dict_dir = get_dict_dir();
     let mut languages = Vec::new();
 
@@ -641,22 +2281,6 @@ pub fn get_available_languages() -> Result<Vec<LanguageInfo>, String> {
         return Ok(languages);
     }
 
-    // Map directory names to language codes
-    let name_to_code = [
-        ("german", "de"),
-        ("sanskrit", "sa"),
-        ("english", "en"),
-        ("french", "fr"),
-        ("spanish", "es"),
-        ("italian", "it"),
-        ("portuguese", "pt"),
-        ("russian", "ru"),
-        ("chinese", "zh"),
-        ("japanese", "ja"),
-        ("korean", "ko"),
-        ("arabic", "ar"),
-    ];
-
     eprintln!("[DICT] Reading directory entries...");
     if let Ok(entries) = std::fs::read_dir(&dict_dir) {
         eprintln!("[DICT] Found entries in dict_dir");
@@ -673,70 +2297,62 @@ pub fn get_available_languages() -> Result<Vec<LanguageInfo>, String> {
 
                 eprintln!("[DICT] Directory name: {}", dir_name);
 
-                // Check if directory name matches language code or name
-                let (lang_code, lang_name) = name_to_code
-                    .iter()
-                    .find(|(name, code)| dir_name == *name || dir_name == *code)
-                    .map(|(name, code)| (*code, *name))
-                    .unwrap_or((&dir_name, &dir_name));
+                // The registry maps directory names to codes
+                let (lang_code, lang_name) = crate::languages::resolve_dir(&dir_name);
+                let (lang_code, lang_name) = (lang_code.as_str(), lang_name.as_str());
 
                 eprintln!("[DICT] Matched: code={}, name={}", lang_code, lang_name);
 
-                // Look for database files in the language directory
-                let db_files = ["{}_dict.db", "{}_dict.sqlite", "dict.db", "dict.sqlite"];
-                let mut db_path: Option<String> = None;
-
-                for pattern in &db_files {
-                    let file_name = pattern.replace("{}", lang_code);
-                    let potential_path = path.join(&file_name);
-                    eprintln!("[DICT] Checking DB file: {:?}", potential_path);
-
-                    if potential_path.exists() {
-                        db_path = Some(potential_path.to_string_lossy().to_string());
-                        eprintln!("[DICT] ✓ Found database: {:?}", potential_path);
-                        break;
-                    }
-                }
-
-                if let Some(db) = db_path {
-                    // Get stats from database
-                    if let Ok(conn) = get_connection(lang_code) {
-                        let word_count: i64 = conn
-                            .query_row("SELECT COUNT(DISTINCT word) FROM dictionary", [], |row| {
-                                row.get(0)
-                            })
-                            .unwrap_or(0);
-
-                        let sense_count: i64 = conn
-                            .query_row("SELECT COUNT(*) FROM senses", [], |row| row.get(0))
-                            .unwrap_or(0);
-
-                        let form_count: i64 = conn
-                            .query_row("SELECT COUNT(*) FROM forms", [], |row| row.get(0))
-                            .unwrap_or(0);
-
-                        eprintln!(
-                            "[DICT] Stats for {}: words={}, senses={}, forms={}",
-                            lang_code, word_count, sense_count, form_count
-                        );
+                // Every dictionary of the language, with its own counts
+                match get_connections(lang_code) {
+                    Ok(connections) => {
+                        let mut dictionaries = Vec::new();
+                        for (dictionary, conn) in connections {
+                            let word_count: i64 = conn
+                                .query_row("SELECT COUNT(DISTINCT word) FROM dictionary", [], |row| {
+                                    row.get(0)
+                                })
+                                .unwrap_or(0);
+
+                            let sense_count: i64 = conn
+                                .query_row("SELECT COUNT(*) FROM senses", [], |row| row.get(0))
+                                .unwrap_or(0);
+
+                            let form_count: i64 = conn
+                                .query_row("SELECT COUNT(*) FROM forms", [], |row| row.get(0))
+                                .unwrap_or(0);
+
+                            eprintln!(
+                                "[DICT] Stats for {}/{}: words={}, senses={}, forms={}",
+                                lang_code, dictionary.name, word_count, sense_count, form_count
+                            );
+
+                            dictionaries.push(DictionarySummary {
+                                name: dictionary.name,
+                                priority: dictionary.priority,
+                                path: dictionary.path.to_string_lossy().to_string(),
+                                word_count,
+                                sense_count,
+                                form_count,
+                                metadata: metadata::read(&conn, &dictionary.path),
+                            });
+                        }
 
                         languages.push(LanguageInfo {
                             code: lang_code.to_string(),
                             name: lang_name.to_string(),
                             has_local: true,
-                            word_count,
-                            sense_count,
-                            form_count,
-                            path: Some(db),
+                            word_count: dictionaries.iter().map(|d| d.word_count).sum(),
+                            sense_count: dictionaries.iter().map(|d| d.sense_count).sum(),
+                            form_count: dictionaries.iter().map(|d| d.form_count).sum(),
+                            path: dictionaries.first().map(|d| d.path.clone()),
+                            metadata: dictionaries.first().map(|d| d.metadata.clone()),
+                            dictionaries,
                         });
-                    } else {
-                        eprintln!(
-                            "[DICT] ✗ Could not open database connection for {}",
-                            lang_code
-                        );
                     }
-                } else {
-                    eprintln!("[DICT] ✗ No database file found in {:?}", path);
+                    Err(e) => {
+                        eprintln!("[DICT] ✗ No usable dictionary in {:?}: {}", path, e);
+                    }
                 }
             }
         }
@@ -756,29 +2372,945 @@ pub fn get_available_languages() -> Result<Vec<LanguageInfo>, String> {
     Ok(languages)
 }
 
+/// Metadata of each dictionary of the language, in priority order
+pub fn get_dictionary_metadata(lang_code: &str) -> Result<Vec<(String, DictionaryMetadata)>, String> {
+    Ok(get_connections(lang_code)?
+        .into_iter()
+        .map(|(dictionary, conn)| {
+            let metadata = metadata::read(&conn, &dictionary.path);
+            (dictionary.name, metadata)
+        })
+        .collect())
+}
+
+/// A spread of headwords from the language's main dictionary, for telling
+/// which script it is written in
+pub fn sample_headwords(lang_code: &str, count: usize) -> Result<Vec<String>, String> {
+    let conn = get_connection(lang_code)?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT word FROM dictionary
+             WHERE id % MAX(1, (SELECT MAX(id) FROM dictionary) / ?1) = 0
+             LIMIT ?1",
+        )
+        .map_err(|e| e.to_string())?;
+    let words = stmt
+        .query_map(params![count as i64], |r| r.get::<_, String>(0))
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+    Ok(words)
+}
+
+/// Headwords starting with `prefix`, one row per word with all of its
+/// parts of speech. Words starting with the prefix as typed (case included)
+/// come first, then a `frequency` column when the dictionary has one, then
+/// shorter words, then words with more senses as a stand-in for frequency.
 pub fn search_suggestions(
     prefix: &str,
     lang_code: &str,
     limit: usize,
-) -> Result<Vec<(String, Option<String>)>, String> {
+    offset: usize,
+) -> Result<Vec<(String, Vec<String>)>, String> {
     let conn = get_connection(lang_code)?;
+    prefix_headwords(&conn, prefix, limit, offset)
+}
 
+fn prefix_headwords(
+    conn: &Connection,
+    prefix: &str,
+    limit: usize,
+    offset: usize,
+) -> Result<Vec<(String, Vec<String>)>, String> {
+    let frequency_order = if has_column(conn, "dictionary", "frequency") {
+        "MAX(d.frequency) DESC,"
+    } else {
+        ""
+    };
     // Kaikki format: dictionary table has 'word' and 'pos' columns
+    let sql = format!(
+        "SELECT d.word, GROUP_CONCAT(DISTINCT d.pos), COUNT(s.id) AS sense_count
+         FROM dictionary d
+         LEFT JOIN senses s ON s.dictionary_id = d.id
+         WHERE d.word LIKE ?1 ESCAPE '\\'
+         GROUP BY d.word
+         ORDER BY substr(d.word, 1, ?2) = ?3 DESC, {} LENGTH(d.word), sense_count DESC, d.word
+         LIMIT ?4 OFFSET ?5",
+        frequency_order
+    );
+    let mut stmt = conn.prepare(&sql).map_err(|e| e.to_string())?;
+
+    let search_pattern = format!("{}%", escape_like(prefix));
+    let results = stmt
+        .query_map(
+            params![
+                search_pattern,
+                prefix.chars().count() as i64,
+                prefix,
+                limit as i64,
+                offset as i64
+            ],
+            |row| {
+                let pos: Option<String> = row.get(1)?;
+                let pos_list = pos
+                    .map(|p| p.split(',').map(str::to_string).collect())
+                    .unwrap_or_default();
+                Ok((row.get::<_, String>(0)?, pos_list))
+            },
+        )
+        .map_err(|e| e.to_string())?;
+
+    Ok(results.filter_map(|r| r.ok()).collect())
+}
+
+/// Literal characters a pattern needs so it can't turn into a full scan
+const PATTERN_MIN_LITERALS: usize = 2;
+
+/// LIKE pattern for a crossword pattern: `*` is any run of characters,
+/// `?` exactly one, and `%` / `_` typed by the user stay literal
+fn pattern_to_like(pattern: &str) -> Result<String, String> {
+    let literals = pattern.chars().filter(|c| !matches!(c, '*' | '?')).count();
+    if literals == 0 {
+        return Err("Pattern contains only wildcards; add at least two letters".to_string());
+    }
+    if literals < PATTERN_MIN_LITERALS {
+        return Err(format!(
+            "Pattern needs at least {} letters besides wildcards",
+            PATTERN_MIN_LITERALS
+        ));
+    }
+    Ok(pattern
+        .chars()
+        .map(|c| match c {
+            '*' => "%".to_string(),
+            '?' => "_".to_string(),
+            other => escape_like(&other.to_string()),
+        })
+        .collect())
+}
+
+/// (headword, part of speech) pairs matched by `search_pattern`
+pub type PatternHits = Vec<(String, Option<String>)>;
+
+/// Headwords matching a wildcard pattern, with their part of speech.
+/// Returns at most `limit` pairs and whether more matched.
+pub fn search_pattern(pattern: &str, lang_code: &str, limit: usize) -> Result<(PatternHits, bool), String> {
+    let like = pattern_to_like(pattern.trim())?;
+    let conn = get_connection(lang_code)?;
     let mut stmt = conn
         .prepare(
-            "SELECT DISTINCT word, pos FROM dictionary 
-             WHERE word LIKE ?1 
-             ORDER BY word 
+            "SELECT DISTINCT word, pos FROM dictionary
+             WHERE word LIKE ?1 ESCAPE '\\'
+             ORDER BY LENGTH(word), word
              LIMIT ?2",
         )
         .map_err(|e| e.to_string())?;
+    let mut results: PatternHits = stmt
+        .query_map(params![like, (limit + 1) as i64], |row| Ok((row.get(0)?, row.get(1)?)))
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+    let truncated = results.len() > limit;
+    results.truncate(limit);
+    Ok((results, truncated))
+}
 
-    let search_pattern = format!("{}%", prefix);
-    let results = stmt
-        .query_map(params![search_pattern, limit as i64], |row| {
-            Ok((row.get::<_, String>(0)?, row.get::<_, Option<String>>(1)?))
+// ============================================================================
+// Definition search
+// ============================================================================
+
+/// Rows indexed per transaction while building the gloss index
+const GLOSS_INDEX_BATCH: i64 = 50_000;
+
+/// Snippet highlight markers, replaced after HTML-escaping the snippet
+const HIGHLIGHT_START: char = '\u{2}';
+const HIGHLIGHT_END: char = '\u{3}';
+
+/// A sense whose gloss matched a definition search
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DefinitionMatch {
+    pub entry_id: String,
+    pub word: String,
+    pub pos: Option<String>,
+    pub gloss: String,
+    /// Excerpt of the gloss, HTML-escaped, with matches in `<mark>`
+    pub snippet: String,
+}
+
+fn meta_value(conn: &Connection, key: &str) -> Option<String> {
+    conn.query_row("SELECT value FROM lumina_meta WHERE key = ?1", params![key], |r| r.get(0))
+        .ok()
+}
+
+fn set_meta_value(conn: &Connection, key: &str, value: &str) -> Result<(), String> {
+    conn.execute(
+        "INSERT INTO lumina_meta (key, value) VALUES (?1, ?2)
+         ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+        params![key, value],
+    )
+    .map(|_| ())
+    .map_err(|e| e.to_string())
+}
+
+/// Whether the FTS5 gloss index was finished, so lookups can use it
+/// without opening the file for writing
+fn gloss_index_complete(conn: &Connection) -> bool {
+    meta_value(conn, "gloss_fts_complete").as_deref() == Some("1")
+}
+
+/// Build the FTS5 index over `senses.gloss` inside the dictionary file;
+/// `conn` must be writable. Progress is recorded after every batch, so an
+/// interrupted build resumes where it stopped and a finished one is
+/// skipped. Returns false when this SQLite build has no FTS5.
+pub fn ensure_gloss_index(conn: &Connection) -> Result<bool, String> {
+    conn.execute_batch("CREATE TABLE IF NOT EXISTS lumina_meta (key TEXT PRIMARY KEY, value TEXT)")
+        .map_err(|e| e.to_string())?;
+    if gloss_index_complete(conn) {
+        return Ok(true);
+    }
+    if conn
+        .execute_batch(
+            "CREATE VIRTUAL TABLE IF NOT EXISTS senses_fts
+             USING fts5(gloss, content='senses', content_rowid='id')",
+        )
+        .is_err()
+    {
+        eprintln!("[FTS] FTS5 unavailable, definition search falls back to LIKE");
+        return Ok(false);
+    }
+
+    let mut indexed_upto: i64 = meta_value(conn, "gloss_fts_indexed_upto")
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(0);
+    let max_id: i64 = conn
+        .query_row("SELECT COALESCE(MAX(id), 0) FROM senses", [], |r| r.get(0))
+        .map_err(|e| e.to_string())?;
+    eprintln!("[FTS] Building gloss index from id {} to {}", indexed_upto, max_id);
+
+    while indexed_upto < max_id {
+        let batch_end = (indexed_upto + GLOSS_INDEX_BATCH).min(max_id);
+        conn.execute_batch("BEGIN").map_err(|e| e.to_string())?;
+        let result = conn
+            .execute(
+                "INSERT INTO senses_fts (rowid, gloss)
+                 SELECT id, gloss FROM senses WHERE id > ?1 AND id <= ?2",
+                params![indexed_upto, batch_end],
+            )
+            .map_err(|e| e.to_string())
+            .and_then(|_| set_meta_value(conn, "gloss_fts_indexed_upto", &batch_end.to_string()));
+        match result {
+            Ok(()) => conn.execute_batch("COMMIT").map_err(|e| e.to_string())?,
+            Err(e) => {
+                let _ = conn.execute_batch("ROLLBACK");
+                return Err(format!("Failed to build gloss index: {}", e));
+            }
+        }
+        indexed_upto = batch_end;
+    }
+    set_meta_value(conn, "gloss_fts_complete", "1")?;
+    Ok(true)
+}
+
+/// Quote every word so user input can't be parsed as FTS5 query syntax
+fn fts_query(query: &str) -> String {
+    query
+        .split_whitespace()
+        .map(|word| format!("\"{}\"", word.replace('"', "\"\"")))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+fn render_snippet(marked: &str) -> String {
+    escape_html(marked)
+        .replace(HIGHLIGHT_START, "<mark>")
+        .replace(HIGHLIGHT_END, "</mark>")
+}
+
+/// Mark the first case-insensitive occurrence of `query` in `gloss`
+fn highlight_first(gloss: &str, query: &str) -> String {
+    let lower = gloss.to_lowercase();
+    // Lowercasing can change byte lengths; only highlight when it didn't
+    match lower.find(&query.to_lowercase()) {
+        Some(start) if lower.len() == gloss.len() => {
+            let end = start + query.len();
+            if gloss.is_char_boundary(start) && gloss.is_char_boundary(end) {
+                return render_snippet(&format!(
+                    "{}{}{}{}{}",
+                    &gloss[..start],
+                    HIGHLIGHT_START,
+                    &gloss[start..end],
+                    HIGHLIGHT_END,
+                    &gloss[end..]
+                ));
+            }
+            escape_html(gloss)
+        }
+        _ => escape_html(gloss),
+    }
+}
+
+/// Find words by meaning: ranked FTS5 matches over glosses, or a plain
+/// substring scan when FTS5 is unavailable. Returns the matches and which
+/// method produced them ("fts5" or "like").
+pub fn search_by_definition(
+    query: &str,
+    lang_code: &str,
+    limit: usize,
+) -> Result<(Vec<DefinitionMatch>, &'static str), String> {
+    let conn = get_connection(lang_code)?;
+    let map_row = |r: &rusqlite::Row| -> rusqlite::Result<(i64, String, Option<String>, String, String)> {
+        Ok((r.get(0)?, r.get(1)?, r.get(2)?, r.get(3)?, r.get(4)?))
+    };
+
+    let has_fts = gloss_index_complete(&conn) || match open_for_write(&conn.path) {
+        Ok(writer) => ensure_gloss_index(&writer)?,
+        Err(e) => {
+            eprintln!("[FTS] Cannot build gloss index, falling back to LIKE: {}", e);
+            false
+        }
+    };
+    if has_fts {
+        let sql = format!(
+            "SELECT s.dictionary_id, d.word, d.pos, s.gloss,
+                    snippet(senses_fts, 0, '{}', '{}', '…', 16)
+             FROM senses_fts
+             JOIN senses s ON s.id = senses_fts.rowid
+             JOIN dictionary d ON d.id = s.dictionary_id
+             WHERE senses_fts MATCH ?1
+             ORDER BY rank
+             LIMIT ?2",
+            HIGHLIGHT_START, HIGHLIGHT_END
+        );
+        let mut stmt = conn.prepare(&sql).map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map(params![fts_query(query), limit as i64], map_row)
+            .map_err(|e| e.to_string())?;
+        let matches = rows
+            .filter_map(|r| r.ok())
+            .map(|(id, word, pos, gloss, snippet)| DefinitionMatch {
+                entry_id: id.to_string(),
+                word,
+                pos,
+                gloss,
+                snippet: render_snippet(&snippet),
+            })
+            .collect();
+        return Ok((matches, "fts5"));
+    }
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT s.dictionary_id, d.word, d.pos, s.gloss, s.gloss
+             FROM senses s
+             JOIN dictionary d ON d.id = s.dictionary_id
+             WHERE s.gloss LIKE ?1
+             ORDER BY LENGTH(s.gloss), s.id
+             LIMIT ?2",
+        )
+        .map_err(|e| e.to_string())?;
+    let pattern = format!("%{}%", query.trim());
+    let rows = stmt
+        .query_map(params![pattern, limit as i64], map_row)
+        .map_err(|e| e.to_string())?;
+    let matches = rows
+        .filter_map(|r| r.ok())
+        .map(|(id, word, pos, gloss, _)| DefinitionMatch {
+            entry_id: id.to_string(),
+            word,
+            pos,
+            snippet: highlight_first(&gloss, query.trim()),
+            gloss,
         })
+        .collect();
+    Ok((matches, "like"))
+}
+
+/// How a reverse lookup gloss matched the query, best first
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum GlossMatch {
+    Exact,
+    Prefix,
+    Contains,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ReverseMatch {
+    pub entry_id: String,
+    pub word: String,
+    pub pos: Option<String>,
+    pub gloss: String,
+    pub matched: GlossMatch,
+}
+
+/// Words whose glosses contain `text` ("butterfly" → Schmetterling).
+/// Whole-gloss matches come first, then glosses starting with the text,
+/// then the rest, shorter glosses first within each group. Returns at most
+/// `limit` matches and whether more exist.
+pub fn reverse_lookup(
+    text: &str,
+    lang_code: &str,
+    limit: usize,
+) -> Result<(Vec<ReverseMatch>, bool), String> {
+    let conn = get_connection(lang_code)?;
+    let text = text.trim();
+    let escaped = escape_like(text);
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT s.dictionary_id, d.word, d.pos, s.gloss,
+                    CASE WHEN LOWER(s.gloss) = LOWER(?1) THEN 0
+                         WHEN s.gloss LIKE ?2 ESCAPE '\\' THEN 1
+                         ELSE 2 END AS rank
+             FROM senses s
+             JOIN dictionary d ON d.id = s.dictionary_id
+             WHERE s.gloss LIKE ?3 ESCAPE '\\'
+             ORDER BY rank, LENGTH(s.gloss), s.id
+             LIMIT ?4",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(
+            params![
+                text,
+                format!("{}%", escaped),
+                format!("%{}%", escaped),
+                // One extra row tells whether there are more
+                (limit + 1) as i64
+            ],
+            |r| {
+                Ok(ReverseMatch {
+                    entry_id: r.get::<_, i64>(0)?.to_string(),
+                    word: r.get(1)?,
+                    pos: r.get(2)?,
+                    gloss: r.get(3)?,
+                    matched: match r.get::<_, i64>(4)? {
+                        0 => GlossMatch::Exact,
+                        1 => GlossMatch::Prefix,
+                        _ => GlossMatch::Contains,
+                    },
+                })
+            },
+        )
         .map_err(|e| e.to_string())?;
 
-    Ok(results.filter_map(|r| r.ok()).collect())
+    let mut matches: Vec<ReverseMatch> = rows.filter_map(|r| r.ok()).collect();
+    let has_more = matches.len() > limit;
+    matches.truncate(limit);
+    Ok((matches, has_more))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::sync::atomic::AtomicBool;
+
+    /// Import Kaikki-style entries into a fresh dictionary database
+    fn fixture(lang_code: &str, entries: &[serde_json::Value]) -> (tempfile::TempDir, Connection) {
+        let dir = tempfile::tempdir().unwrap();
+        let conn = import_into(dir.path(), lang_code, entries);
+        (dir, conn)
+    }
+
+    /// (Re-)import entries as `<dir>/<lang_code>.db`, replacing any earlier import
+    fn import_into(dir: &Path, lang_code: &str, entries: &[serde_json::Value]) -> Connection {
+        let source = dir.join("entries.jsonl");
+        let lines: Vec<String> = entries.iter().map(|e| e.to_string()).collect();
+        std::fs::write(&source, lines.join("\n")).unwrap();
+        let target = dir.join(format!("{}.db", lang_code));
+        import::import_jsonl(&source, &target, lang_code, lang_code, &AtomicBool::new(false), &mut |_, _, _| {})
+            .unwrap();
+        Connection::open(&target).unwrap()
+    }
+
+    fn entry_id(conn: &Connection, word: &str) -> i64 {
+        conn.query_row("SELECT id FROM dictionary WHERE word = ?1", params![word], |r| r.get(0))
+            .unwrap()
+    }
+
+    #[test]
+    fn etymology_of_a_borrowed_word() {
+        let (_dir, conn) = fixture("en", &[json!({
+            "word": "café",
+            "pos": "noun",
+            "senses": [{ "glosses": ["A coffee shop"] }],
+            "etymology_text": "Borrowed from French café (“coffee”).",
+            "etymology_templates": [
+                { "name": "bor", "args": { "1": "en", "2": "fr", "3": "café", "4": "", "5": "coffee" }, "expansion": "French café" },
+                { "name": "cog", "args": { "1": "it", "2": "caffè" }, "expansion": "Italian caffè" }
+            ]
+        })]);
+        let id = entry_id(&conn, "café");
+        let chain = build_etymology_chain(&conn, id, Some("Borrowed from French café".to_string()));
+
+        assert!(chain.parsed);
+        assert_eq!(chain.steps.len(), 1, "the cognate is not an ancestor");
+        let step = &chain.steps[0];
+        assert_eq!(step.relation, "borrowed");
+        assert_eq!(step.language_code.as_deref(), Some("fr"));
+        assert_eq!(step.language.as_deref(), Some("French"));
+        assert_eq!(step.word.as_deref(), Some("café"));
+        assert_eq!(step.notes.as_deref(), Some("coffee"));
+        assert!(step.linked_entry_id.is_none());
+    }
+
+    #[test]
+    fn etymology_of_an_inherited_word() {
+        let (_dir, conn) = fixture("fr", &[json!({
+            "word": "amour",
+            "pos": "noun",
+            "senses": [{ "glosses": ["love"] }],
+            "etymology_templates": [
+                { "name": "inh", "args": { "1": "fr", "2": "fro", "3": "amor" }, "expansion": "Old French amor" },
+                { "name": "inh", "args": { "1": "fr", "2": "la", "3": "amor", "t": "love" }, "expansion": "Latin amor (“love”)" },
+                { "name": "m", "args": { "1": "la", "2": "amō" }, "expansion": "amō" }
+            ]
+        })]);
+        let id = entry_id(&conn, "amour");
+        let chain = build_etymology_chain(&conn, id, None);
+
+        assert!(chain.parsed);
+        let steps: Vec<_> = chain
+            .steps
+            .iter()
+            .map(|s| (s.relation.as_str(), s.language_code.as_deref(), s.language.as_deref(), s.word.as_deref()))
+            .collect();
+        assert_eq!(
+            steps,
+            vec![
+                ("inherited", Some("fro"), Some("Old French"), Some("amor")),
+                ("inherited", Some("la"), Some("Latin"), Some("amor")),
+            ]
+        );
+        assert_eq!(chain.steps[1].notes.as_deref(), Some("love"));
+    }
+
+    #[test]
+    fn unparseable_etymology_falls_back_to_text() {
+        let (_dir, conn) = fixture("en", &[json!({
+            "word": "quark",
+            "pos": "noun",
+            "senses": [{ "glosses": ["A fundamental particle"] }],
+            "etymology_text": "Coined by Murray Gell-Mann.",
+            "etymology_templates": [{ "name": "coinage", "args": { "1": "en", "2": "Murray Gell-Mann" } }]
+        })]);
+        let id = entry_id(&conn, "quark");
+        let chain = build_etymology_chain(&conn, id, Some("Coined by Murray Gell-Mann.".to_string()));
+
+        assert!(!chain.parsed);
+        assert!(chain.steps.is_empty());
+        assert_eq!(chain.text.as_deref(), Some("Coined by Murray Gell-Mann."));
+    }
+
+    fn german_liebe() -> (tempfile::TempDir, Connection) {
+        fixture("de", &[
+            json!({ "word": "Liebe", "pos": "noun", "senses": [{ "glosses": ["love"] }],
+                    "forms": [{ "form": "Lieben", "tags": ["plural"] }] }),
+            json!({ "word": "lieben", "pos": "verb", "senses": [{ "glosses": ["to love"] }],
+                    "forms": [{ "form": "liebe", "tags": ["first-person", "singular", "present"] },
+                              { "form": "liebt", "tags": ["third-person", "singular", "present"] }] }),
+            json!({ "word": "lieb", "pos": "adj", "senses": [{ "glosses": ["dear"] }],
+                    "forms": [{ "form": "liebe", "tags": ["feminine", "nominative", "strong"] }] }),
+        ])
+    }
+
+    /// English entries, each a noun with one gloss
+    fn english(words: &[(&str, &str)]) -> (tempfile::TempDir, Connection) {
+        let entries: Vec<_> = words
+            .iter()
+            .map(|(word, gloss)| json!({ "word": word, "pos": "noun", "senses": [{ "glosses": [gloss] }] }))
+            .collect();
+        fixture("en", &entries)
+    }
+
+    /// Give `word` a `linkedForm` relation to `target`, as converted dumps store it
+    fn link(conn: &Connection, word: &str, target: &str) {
+        let details = json!({ "senses": [{ "partsOfSpeech": [{ "relations": [
+            { "relType": "linkedForm", "targets": [{ "word": target }] }
+        ] }] }] });
+        conn.execute("UPDATE dictionary SET details = ?1 WHERE word = ?2", params![details.to_string(), word])
+            .unwrap();
+    }
+
+    /// (text, link_part, matched_via) of each result for `word`
+    fn linked(conn: &Connection, word: &str) -> Vec<(String, Option<String>, Option<String>)> {
+        search_connection(conn, word, "en", &SearchOptions::default())
+            .unwrap()
+            .into_iter()
+            .map(|e| (e.text, e.link_part, e.matched_via))
+            .collect()
+    }
+
+    fn some(s: &str) -> Option<String> {
+        Some(s.to_string())
+    }
+
+    #[test]
+    fn linked_form_brings_in_its_main_entry() {
+        let (_dir, conn) = english(&[("colour", "A spelling variant"), ("color", "The property of light")]);
+        link(&conn, "colour", "color");
+
+        let results = search_connection(&conn, "colour", "en", &SearchOptions::default()).unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].link_part.as_deref(), Some("color"));
+        assert_eq!((results[1].text.as_str(), results[1].matched_via.as_deref()), ("color", Some("link")));
+        assert_eq!(glosses(&results[1]), vec!["The property of light"]);
+
+        // The main entry itself links nowhere
+        assert_eq!(linked(&conn, "color"), vec![("color".to_string(), None, some("headword"))]);
+    }
+
+    #[test]
+    fn alternative_spelling_gloss_counts_as_a_link() {
+        let (_dir, conn) = english(&[("colour", "Alternative spelling of color (UK)"), ("color", "The property of light")]);
+        let results = linked(&conn, "colour");
+        assert_eq!(results[0].1, some("color"));
+        assert_eq!(results[1], ("color".to_string(), None, some("link")));
+    }
+
+    #[test]
+    fn link_cycles_stop_at_visited_words() {
+        let (_dir, conn) = english(&[("grey", "A colour"), ("gray", "A color")]);
+        link(&conn, "grey", "gray");
+        link(&conn, "gray", "grey");
+
+        let results = linked(&conn, "grey");
+        assert_eq!(
+            results,
+            vec![
+                ("grey".to_string(), some("gray"), some("headword")),
+                ("gray".to_string(), some("grey"), some("link")),
+            ]
+        );
+    }
+
+    #[test]
+    fn links_are_followed_at_most_two_hops() {
+        let (_dir, conn) = english(&[("a", "first"), ("b", "second"), ("c", "third"), ("d", "fourth")]);
+        link(&conn, "a", "b");
+        link(&conn, "b", "c");
+        link(&conn, "c", "d");
+
+        let texts: Vec<String> = linked(&conn, "a").into_iter().map(|(text, _, _)| text).collect();
+        assert_eq!(texts, vec!["a", "b", "c"]);
+    }
+
+    fn search(conn: &Connection, word: &str, mode: SearchMode) -> Vec<DictionaryEntry> {
+        let options = SearchOptions { mode, ..Default::default() };
+        search_connection(conn, word, "de", &options).unwrap()
+    }
+
+    #[test]
+    fn all_forms_returns_every_lemma_of_an_ambiguous_form() {
+        let (_dir, conn) = german_liebe();
+        let results = search(&conn, "liebe", SearchMode::AllForms);
+        let found: Vec<(&str, Option<&str>, Option<&str>)> = results
+            .iter()
+            .map(|e| (e.text.as_str(), e.grammar.as_deref(), e.matched_via.as_deref()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("Liebe", Some("noun"), Some("headword")),
+                ("lieben", Some("verb"), Some("form")),
+                ("lieb", Some("adj"), Some("form")),
+            ]
+        );
+        let adjective = results.iter().find(|e| e.text == "lieb").unwrap();
+        assert!(adjective.matched_form_tags.as_deref().is_some_and(|t| t.contains("feminine")));
+    }
+
+    #[test]
+    fn smart_mode_stops_at_the_first_step_with_a_hit() {
+        let (_dir, conn) = german_liebe();
+        let texts: Vec<String> = search(&conn, "liebe", SearchMode::Smart).into_iter().map(|e| e.text).collect();
+        // Both lemmas listing the form, but not the headword step
+        assert_eq!(texts, vec!["lieben", "lieb"]);
+    }
+
+    #[test]
+    fn exact_mode_matches_only_the_literal_headword() {
+        let (_dir, conn) = german_liebe();
+        assert!(search(&conn, "liebe", SearchMode::Exact).is_empty());
+        let texts: Vec<String> = search(&conn, "Liebe", SearchMode::Exact).into_iter().map(|e| e.text).collect();
+        assert_eq!(texts, vec!["Liebe"]);
+        assert!(search(&conn, "liebt", SearchMode::Exact).is_empty());
+    }
+
+    fn bank(glosses: &[&str]) -> serde_json::Value {
+        let senses: Vec<serde_json::Value> = glosses.iter().map(|g| json!({ "glosses": [g] })).collect();
+        json!({ "word": "Bank", "pos": "noun", "senses": senses })
+    }
+
+    fn search_with_hint(conn: &Connection, hint: &SenseOrderHint) -> DictionaryEntry {
+        let options = SearchOptions { sense_ordering: Some(hint.clone()), ..Default::default() };
+        search_connection(conn, "Bank", "de", &options).unwrap().remove(0)
+    }
+
+    fn glosses(entry: &DictionaryEntry) -> Vec<&str> {
+        entry.senses.iter().flatten().map(|s| s.gloss.as_str()).collect()
+    }
+
+    #[test]
+    fn known_sense_stays_first_across_a_reimport() {
+        let dir = tempfile::tempdir().unwrap();
+        let conn = import_into(dir.path(), "de", &[bank(&["bench", "bank (financial institution)"])]);
+        let learned = search_with_hint(&conn, &SenseOrderHint::default());
+        let studied = learned.senses.as_ref().unwrap()[1].clone();
+        let hint = SenseOrderHint {
+            sense_id: Some(studied.id.clone()),
+            gloss_fingerprint: Some(studied.fingerprint.clone()),
+        };
+
+        let first = search_with_hint(&conn, &hint);
+        assert_eq!(first.hint_applied, Some(true));
+        assert_eq!(glosses(&first), vec!["bank (financial institution)", "bench"]);
+        assert!(first.senses.as_ref().unwrap()[0].promoted);
+        drop(conn);
+
+        // The new edition adds an entry and a sense, so every row id moves
+        // and the stale id now belongs to another sense
+        let conn = import_into(dir.path(), "de", &[
+            json!({ "word": "Bankett", "pos": "noun", "senses": [{ "glosses": ["banquet"] }] }),
+            bank(&["bench", "sandbank", "bank (financial institution)"]),
+        ]);
+        let after = search_with_hint(&conn, &hint);
+        assert_eq!(after.hint_applied, Some(true));
+        assert_eq!(glosses(&after), vec!["bank (financial institution)", "bench", "sandbank"]);
+        assert_ne!(after.senses.as_ref().unwrap()[0].id, studied.id);
+        assert_eq!(after.definition.as_deref(), Some("bank (financial institution) | bench | sandbank"));
+        drop(conn);
+
+        // The studied sense was removed: natural order, hint not applied
+        let conn = import_into(dir.path(), "de", &[bank(&["bench", "sandbank"])]);
+        let gone = search_with_hint(&conn, &SenseOrderHint { sense_id: None, ..hint });
+        assert_eq!(gone.hint_applied, Some(false));
+        assert_eq!(glosses(&gone), vec!["bench", "sandbank"]);
+        assert!(gone.senses.as_ref().unwrap().iter().all(|s| !s.promoted));
+    }
+
+    #[test]
+    fn sense_id_is_used_without_a_fingerprint() {
+        let (_dir, conn) = fixture("de", &[bank(&["bench", "bank (financial institution)"])]);
+        let natural = search_with_hint(&conn, &SenseOrderHint::default());
+        let id = natural.senses.as_ref().unwrap()[1].id.clone();
+        let entry = search_with_hint(&conn, &SenseOrderHint { sense_id: Some(id), gloss_fingerprint: None });
+        assert_eq!(entry.hint_applied, Some(true));
+        assert_eq!(glosses(&entry)[0], "bank (financial institution)");
+    }
+
+    #[test]
+    fn examples_survive_a_serde_round_trip() {
+        let (_dir, conn) = fixture("de", &[
+            json!({ "word": "Haus", "pos": "noun", "senses": [
+                { "glosses": ["house"], "examples": [
+                    { "text": "Das Haus ist groß.", "english": "The house is big." },
+                    { "text": "Wir bleiben zu Hause." },
+                ] },
+                { "glosses": ["household"], "examples": [{ "text": "ein Haus führen", "translation": "to run a household" }] },
+            ] }),
+            json!({ "word": "Maus", "pos": "noun", "senses": [{ "glosses": ["mouse"] }] }),
+        ]);
+
+        let haus = search(&conn, "Haus", SearchMode::Exact).remove(0);
+        let examples = haus.examples.clone().unwrap();
+        assert_eq!(examples, vec![
+            Example { text: "Das Haus ist groß.".into(), translation: Some("The house is big.".into()), sense_index: 0 },
+            Example { text: "Wir bleiben zu Hause.".into(), translation: None, sense_index: 0 },
+            Example { text: "ein Haus führen".into(), translation: Some("to run a household".into()), sense_index: 1 },
+        ]);
+
+        let json = serde_json::to_value(&haus).unwrap();
+        assert_eq!(json["examples"][0], json!({ "text": "Das Haus ist groß.", "translation": "The house is big.", "sense_index": 0 }));
+        let back: DictionaryEntry = serde_json::from_value(json).unwrap();
+        assert_eq!(back.examples, Some(examples));
+
+        // No examples: the field is left out and reads back as None
+        let maus = search(&conn, "Maus", SearchMode::Exact).remove(0);
+        assert_eq!(maus.examples, None);
+        let json = serde_json::to_value(&maus).unwrap();
+        assert!(json.get("examples").is_none());
+        let back: DictionaryEntry = serde_json::from_value(json).unwrap();
+        assert_eq!(back.examples, None);
+    }
+
+    #[test]
+    fn batch_lookup_of_a_reading_text() {
+        let entries: Vec<_> = (0..600)
+            .map(|i| json!({ "word": format!("wort{i}"), "pos": "noun", "senses": [{ "glosses": [format!("word {i}")] }],
+                             "forms": [{ "form": format!("worte{i}"), "tags": ["plural"] }] }))
+            .collect();
+        let (_dir, conn) = fixture("de", &entries);
+
+        // 450 headwords, 100 plural forms (every other one sentence-initial)
+        // and 50 unknown words: more than one IN chunk of each kind
+        let mut words: Vec<String> = (0..450).map(|i| format!("wort{i}")).collect();
+        words.extend((450..550).map(|i| if i % 2 == 0 { format!("Worte{i}") } else { format!("worte{i}") }));
+        words.extend((0..50).map(|i| format!("unbekannt{i}")));
+        words.extend(words[..100].to_vec());
+        let options = SearchOptions::default();
+
+        let started = std::time::Instant::now();
+        let batch = batch_search_connection(&conn, &words[..600], &options).unwrap();
+        let batch_time = started.elapsed();
+
+        let started = std::time::Instant::now();
+        let one_by_one: Vec<(String, Vec<DictionaryEntry>)> = words
+            .iter()
+            .map(|w| (w.clone(), search_connection(&conn, w, "de", &options).unwrap()))
+            .collect();
+        let loop_time = started.elapsed();
+        eprintln!("[DICT] {} words: batch {:?}, one query per word {:?}", words.len(), batch_time, loop_time);
+
+        assert_eq!(batch.len(), 550);
+        assert_eq!(batch["wort7"][0].text, "wort7");
+        assert_eq!(batch["Worte450"][0].text, "wort450");
+        assert!(batch["worte451"][0].matched_form_tags.as_deref().is_some_and(|t| t.contains("plural")));
+        assert!(!batch.contains_key("unbekannt3"));
+        // Same entries the per-word lookup finds
+        for (word, found) in one_by_one {
+            let texts: Vec<&str> = found.iter().map(|e| e.text.as_str()).collect();
+            let batched: Vec<&str> = batch.get(&word).into_iter().flatten().map(|e| e.text.as_str()).collect();
+            assert_eq!(batched, texts, "{word}");
+        }
+    }
+
+    /// German nouns, each with one gloss
+    fn german_nouns(words: &[&str]) -> (tempfile::TempDir, Connection) {
+        let entries: Vec<_> = words
+            .iter()
+            .map(|word| json!({ "word": word, "pos": "noun", "senses": [{ "glosses": [word.to_lowercase()] }] }))
+            .collect();
+        fixture("de", &entries)
+    }
+
+    fn split(conn: &Connection, word: &str) -> Option<Vec<String>> {
+        split_compound_on(conn, word, "de")
+    }
+
+    fn parts(words: &[&str]) -> Option<Vec<String>> {
+        Some(words.iter().map(|w| w.to_string()).collect())
+    }
+
+    #[test]
+    fn compounds_split_across_a_fugen_s() {
+        let (_dir, conn) = german_nouns(&["Arbeit", "Amt", "Liebe", "Brief", "Geburt", "Tag", "Hilfe", "Mittel"]);
+        assert_eq!(split(&conn, "Arbeitsamt"), parts(&["Arbeit", "Amt"]));
+        assert_eq!(split(&conn, "Geburtstag"), parts(&["Geburt", "Tag"]));
+        // -s- after a noun ending in -e
+        assert_eq!(split(&conn, "Liebesbrief"), parts(&["Liebe", "Brief"]));
+        // -s- in front of the last part only
+        assert_eq!(split(&conn, "Hilfsmittel"), None);
+    }
+
+    #[test]
+    fn compounds_split_into_the_fewest_parts() {
+        let (_dir, conn) = german_nouns(&["Donau", "Dampf", "Schiff", "Fahrt"]);
+        assert_eq!(split(&conn, "Donaudampfschifffahrt"), parts(&["Donau", "Dampf", "Schiff", "Fahrt"]));
+        let (_dir, conn) = german_nouns(&["Donau", "Dampf", "Schiff", "Fahrt", "Schifffahrt"]);
+        assert_eq!(split(&conn, "Donaudampfschifffahrt"), parts(&["Donau", "Dampf", "Schifffahrt"]));
+    }
+
+    #[test]
+    fn headwords_are_not_split() {
+        let (_dir, conn) = german_nouns(&["Hand", "Schuh", "Handschuh", "Haus", "Tür", "Arbeit", "Arbeitsamt", "Amt"]);
+        assert_eq!(split(&conn, "Handschuh"), None);
+        assert_eq!(split(&conn, "handschuh"), None);
+        assert_eq!(split(&conn, "Arbeitsamt"), None);
+        assert_eq!(split(&conn, "Haustür"), parts(&["Haus", "Tür"]));
+        // No known parts, too short, or not a word
+        assert_eq!(split(&conn, "Xylofonklang"), None);
+        assert_eq!(split(&conn, "Amt"), None);
+        assert_eq!(split(&conn, "Haus-Tür"), None);
+        assert_eq!(split_compound_on(&conn, "Haustür", "en"), None);
+    }
+
+    /// An entry whose senses carry the given labels, glossed "g0", "g1", ...
+    fn labeled_entry(labels: &[&[&str]]) -> DictionaryEntry {
+        let senses: Vec<serde_json::Value> = labels
+            .iter()
+            .enumerate()
+            .map(|(i, l)| {
+                json!({
+                    "id": i.to_string(),
+                    "index": i,
+                    "gloss": format!("g{}", i),
+                    "fingerprint": format!("f{}", i),
+                    "labels": l,
+                })
+            })
+            .collect();
+        serde_json::from_value(json!({
+            "entry_id": "de:Semmel",
+            "text": "Semmel",
+            "language": "de",
+            "definition": "original",
+            "senses": senses,
+        }))
+        .unwrap()
+    }
+
+    fn hiding(labels: &[&str]) -> SenseFilter {
+        SenseFilter { hidden_labels: labels.iter().map(|l| l.to_string()).collect(), preferred_region: None }
+    }
+
+    #[test]
+    fn senses_with_a_hidden_label_are_removed() {
+        let mut entry = labeled_entry(&[&["vulgar"], &[], &["archaic", "Austria"], &["Colloquial"]]);
+        let hidden = apply_sense_filter(&mut entry, &hiding(&["Vulgar", "colloquial"]));
+
+        assert_eq!(hidden, 2);
+        assert_eq!(glosses(&entry), ["g1", "g2"]);
+        assert_eq!(entry.definition.as_deref(), Some("g1 | g2"));
+        assert_eq!(entry.hidden_sense_count, Some(2));
+        assert!(entry.senses.as_ref().unwrap().iter().all(|s| !s.filtered));
+    }
+
+    #[test]
+    fn including_hidden_senses_leaves_every_sense_in_place() {
+        let mut entries = vec![labeled_entry(&[&["vulgar"], &[], &["colloquial"]])];
+        let hidden = filter_senses(&mut entries, &hiding(&["vulgar", "colloquial"]), true);
+
+        assert_eq!(hidden, 0);
+        assert_eq!(glosses(&entries[0]), ["g0", "g1", "g2"]);
+        assert_eq!(entries[0].definition.as_deref(), Some("original"));
+        assert_eq!(entries[0].hidden_sense_count, None);
+
+        assert_eq!(filter_senses(&mut entries, &hiding(&["vulgar", "colloquial"]), false), 2);
+        assert_eq!(glosses(&entries[0]), ["g1"]);
+    }
+
+    #[test]
+    fn preferred_region_senses_come_first_and_other_regions_last() {
+        let mut entry = labeled_entry(&[&["Germany"], &[], &["austria"], &["colloquial"], &["Switzerland"]]);
+        let filter = SenseFilter { hidden_labels: Vec::new(), preferred_region: Some("Austria".to_string()) };
+        let hidden = apply_sense_filter(&mut entry, &filter);
+
+        assert_eq!(hidden, 0);
+        assert_eq!(glosses(&entry), ["g2", "g1", "g3", "g0", "g4"]);
+        assert_eq!(entry.definition.as_deref(), Some("g2 | g1 | g3 | g0 | g4"));
+
+        // A promoted sense keeps the front alongside the preferred region
+        let mut entry = labeled_entry(&[&["Austria"], &["Germany"], &[]]);
+        entry.senses.as_mut().unwrap()[1].promoted = true;
+        apply_sense_filter(&mut entry, &filter);
+        assert_eq!(glosses(&entry), ["g0", "g1", "g2"]);
+    }
+
+    #[test]
+    fn an_entry_with_only_hidden_senses_keeps_its_first_as_filtered() {
+        let mut entry = labeled_entry(&[&["vulgar"], &["vulgar", "dated"], &["dated"]]);
+        let hidden = apply_sense_filter(&mut entry, &hiding(&["vulgar", "dated"]));
+
+        assert_eq!(hidden, 2);
+        assert_eq!(glosses(&entry), ["g0"]);
+        assert!(entry.senses.as_ref().unwrap()[0].filtered);
+        assert_eq!(entry.definition.as_deref(), Some("g0"));
+        assert_eq!(entry.hidden_sense_count, Some(2));
+    }
 }
@@ -0,0 +1,452 @@
+//! Kaikki JSONL → SQLite conversion, producing the schema the lookups in
+//! `db` read. Replaces scripts/convert_jsonl_to_sqlite.py for uploads.
+
+use rusqlite::{params, Transaction};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use crate::normalize::normalize_word;
+
+/// Entries inserted per transaction
+const IMPORT_BATCH: usize = 5_000;
+/// Lines between progress reports and cancellation checks
+const PROGRESS_LINES: usize = 1_000;
+
+/// Error prefix for an import stopped through its cancel flag
+pub const IMPORT_CANCELLED: &str = "import_cancelled";
+
+/// Called with the stage ("importing" or "indexing"), bytes read so far
+/// and entries imported so far
+pub type ProgressFn<'a> = &'a mut dyn FnMut(&str, u64, usize);
+
+/// Sense tags describing the entry's structure rather than its usage
+const SENSE_STRUCTURAL_TAGS: &[&str] = &["form-of", "alt-of", "no-gloss", "empty-gloss"];
+
+pub(super) const SCHEMA: &str = "
+    CREATE TABLE dictionary (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        word TEXT NOT NULL,
+        normalized_word TEXT NOT NULL,
+        lang TEXT,
+        lang_code TEXT NOT NULL,
+        pos TEXT,
+        etymology_text TEXT,
+        pronunciation TEXT,
+        details TEXT,
+        synonyms TEXT,
+        antonyms TEXT,
+        created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+    );
+    CREATE TABLE senses (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        dictionary_id INTEGER NOT NULL,
+        sense_index INTEGER NOT NULL,
+        gloss TEXT NOT NULL,
+        example TEXT,
+        examples TEXT,
+        tags TEXT,
+        FOREIGN KEY (dictionary_id) REFERENCES dictionary(id) ON DELETE CASCADE
+    );
+    CREATE TABLE forms (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        dictionary_id INTEGER NOT NULL,
+        form TEXT NOT NULL,
+        normalized_form TEXT NOT NULL,
+        tags TEXT,
+        FOREIGN KEY (dictionary_id) REFERENCES dictionary(id) ON DELETE CASCADE
+    );
+    CREATE TABLE sounds (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        dictionary_id INTEGER NOT NULL,
+        ipa TEXT,
+        audio_url TEXT,
+        tags TEXT,
+        FOREIGN KEY (dictionary_id) REFERENCES dictionary(id) ON DELETE CASCADE
+    );
+    CREATE TABLE relations (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        dictionary_id INTEGER NOT NULL,
+        rel_type TEXT NOT NULL,
+        word TEXT NOT NULL,
+        sense_index INTEGER,
+        FOREIGN KEY (dictionary_id) REFERENCES dictionary(id) ON DELETE CASCADE
+    );
+";
+
+/// Created after the rows are in, which is much faster than maintaining them
+pub(super) const INDEXES: &str = "
+    CREATE INDEX idx_dictionary_word ON dictionary(word);
+    CREATE INDEX idx_dictionary_normalized ON dictionary(normalized_word);
+    CREATE INDEX idx_dictionary_lang ON dictionary(lang_code);
+    CREATE INDEX idx_senses_dictionary ON senses(dictionary_id);
+    CREATE INDEX idx_forms_form ON forms(form);
+    CREATE INDEX idx_forms_normalized ON forms(normalized_form);
+    CREATE INDEX idx_forms_dictionary ON forms(dictionary_id);
+    CREATE INDEX idx_relations_dictionary ON relations(dictionary_id);
+";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportStats {
+    pub entries: usize,
+    pub senses: usize,
+    pub forms: usize,
+    /// Synonyms, antonyms and related words
+    pub relations: usize,
+    /// Valid JSON lines without a word or any gloss
+    pub skipped: usize,
+    /// Lines that weren't valid JSON (or UTF-8)
+    pub malformed: usize,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct KaikkiEntry {
+    word: Option<String>,
+    pos: Option<String>,
+    lang: Option<String>,
+    senses: Vec<KaikkiSense>,
+    forms: Vec<KaikkiForm>,
+    sounds: Vec<KaikkiSound>,
+    etymology_text: Option<String>,
+    etymology_templates: Option<serde_json::Value>,
+    synonyms: Vec<KaikkiRelation>,
+    antonyms: Vec<KaikkiRelation>,
+    related: Vec<KaikkiRelation>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct KaikkiSense {
+    glosses: Vec<String>,
+    tags: Vec<String>,
+    raw_tags: Vec<String>,
+    examples: Vec<KaikkiExample>,
+    synonyms: Vec<KaikkiRelation>,
+    antonyms: Vec<KaikkiRelation>,
+    related: Vec<KaikkiRelation>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct KaikkiRelation {
+    word: String,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct KaikkiExample {
+    text: Option<String>,
+    english: Option<String>,
+    translation: Option<String>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct KaikkiForm {
+    form: String,
+    tags: Vec<String>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct KaikkiSound {
+    ipa: Option<String>,
+    tags: Vec<String>,
+    raw_tags: Vec<String>,
+    audio_url: Option<String>,
+    ogg_url: Option<String>,
+    mp3_url: Option<String>,
+}
+
+impl KaikkiEntry {
+    /// The headword, or the canonical form when `word` is missing
+    fn headword(&self) -> Option<String> {
+        self.word.clone().filter(|w| !w.is_empty()).or_else(|| {
+            self.forms
+                .iter()
+                .find(|f| f.tags.iter().any(|t| t == "canonical"))
+                .or_else(|| self.forms.first())
+                .map(|f| f.form.clone())
+                .filter(|w| !w.is_empty())
+        })
+    }
+}
+
+/// Stored as `relations.rel_type`
+const RELATION_TYPES: &[&str] = &["synonym", "antonym", "related"];
+
+/// The headword a relation points to. Kaikki words are plain text, but
+/// some link a thesaurus page ("Thesaurus:big") or keep wiki brackets.
+pub(super) fn relation_headword(raw: &str) -> Option<String> {
+    let word = raw.trim();
+    let word = word.strip_prefix("Thesaurus:").unwrap_or(word);
+    let word = word.trim_start_matches("[[").trim_end_matches("]]");
+    // "[[target|shown]]" links to target
+    let word = word.split('|').next().unwrap_or(word).trim();
+    (!word.is_empty()).then(|| word.to_string())
+}
+
+fn json_or_null<T: Serialize>(items: &[T]) -> Option<String> {
+    if items.is_empty() {
+        None
+    } else {
+        serde_json::to_string(items).ok()
+    }
+}
+
+fn insert_entry(
+    tx: &Transaction,
+    entry: &KaikkiEntry,
+    word: &str,
+    lang_code: &str,
+    lang_name: &str,
+    stats: &mut ImportStats,
+) -> rusqlite::Result<()> {
+    let pronunciation = entry.sounds.iter().find_map(|s| s.ipa.clone().filter(|i| !i.is_empty()));
+    let details = entry
+        .etymology_templates
+        .as_ref()
+        .map(|t| serde_json::json!({ "etymology_templates": t }).to_string());
+    tx.prepare_cached(
+        "INSERT INTO dictionary (word, normalized_word, lang, lang_code, pos, etymology_text, pronunciation, details)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+    )?
+    .execute(params![
+        word,
+        normalize_word(word, lang_code),
+        entry.lang.as_deref().unwrap_or(lang_name),
+        lang_code,
+        entry.pos.as_deref().unwrap_or(""),
+        entry.etymology_text,
+        pronunciation,
+        details,
+    ])?;
+    let dictionary_id = tx.last_insert_rowid();
+
+    let mut insert_sense = tx.prepare_cached(
+        "INSERT INTO senses (dictionary_id, sense_index, gloss, example, examples, tags)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+    )?;
+    for (index, sense) in entry.senses.iter().enumerate() {
+        let Some(gloss) = sense.glosses.first().filter(|g| !g.is_empty()) else {
+            continue;
+        };
+        let examples: Vec<serde_json::Value> = sense
+            .examples
+            .iter()
+            .filter_map(|e| {
+                let text = e.text.as_deref().filter(|t| !t.is_empty())?;
+                Some(serde_json::json!({
+                    "text": text,
+                    "translation": e.english.as_ref().or(e.translation.as_ref()),
+                }))
+            })
+            .collect();
+        let example = examples.first().and_then(|e| e["text"].as_str()).unwrap_or("");
+        let mut labels: Vec<&str> = Vec::new();
+        for tag in sense.tags.iter().chain(&sense.raw_tags) {
+            if !SENSE_STRUCTURAL_TAGS.contains(&tag.as_str()) && !labels.contains(&tag.as_str()) {
+                labels.push(tag);
+            }
+        }
+        insert_sense.execute(params![
+            dictionary_id,
+            index as i64,
+            gloss,
+            example,
+            json_or_null(&examples),
+            json_or_null(&labels),
+        ])?;
+        stats.senses += 1;
+    }
+
+    let mut insert_form = tx.prepare_cached(
+        "INSERT INTO forms (dictionary_id, form, normalized_form, tags) VALUES (?1, ?2, ?3, ?4)",
+    )?;
+    for form in entry.forms.iter().filter(|f| !f.form.is_empty()) {
+        insert_form.execute(params![
+            dictionary_id,
+            form.form,
+            normalize_word(&form.form, lang_code),
+            json_or_null(&form.tags),
+        ])?;
+        stats.forms += 1;
+    }
+
+    let mut insert_relation = tx.prepare_cached(
+        "INSERT INTO relations (dictionary_id, rel_type, word, sense_index) VALUES (?1, ?2, ?3, ?4)",
+    )?;
+    let groups = std::iter::once((None, &entry.synonyms, &entry.antonyms, &entry.related)).chain(
+        entry
+            .senses
+            .iter()
+            .enumerate()
+            .map(|(index, s)| (Some(index as i64), &s.synonyms, &s.antonyms, &s.related)),
+    );
+    let mut seen: Vec<(&str, String, Option<i64>)> = Vec::new();
+    for (sense_index, synonyms, antonyms, related) in groups {
+        for (rel_type, relations) in RELATION_TYPES.iter().zip([synonyms, antonyms, related]) {
+            for word in relations.iter().filter_map(|r| relation_headword(&r.word)) {
+                let key = (*rel_type, word, sense_index);
+                if seen.contains(&key) {
+                    continue;
+                }
+                insert_relation.execute(params![dictionary_id, key.0, key.1, sense_index])?;
+                stats.relations += 1;
+                seen.push(key);
+            }
+        }
+    }
+
+    let mut insert_sound = tx.prepare_cached(
+        "INSERT INTO sounds (dictionary_id, ipa, audio_url, tags) VALUES (?1, ?2, ?3, ?4)",
+    )?;
+    for sound in &entry.sounds {
+        let audio = sound
+            .audio_url
+            .as_ref()
+            .or(sound.ogg_url.as_ref())
+            .or(sound.mp3_url.as_ref());
+        if sound.ipa.is_some() || audio.is_some() {
+            let tags: Vec<&String> = sound.tags.iter().chain(&sound.raw_tags).collect();
+            insert_sound.execute(params![dictionary_id, sound.ipa, audio, json_or_null(&tags)])?;
+        }
+    }
+    Ok(())
+}
+
+/// Run `build` against a scratch file next to `target` and move the result
+/// into place only if it succeeded; a failed build's file is removed
+pub(super) fn build_and_install<T>(
+    target: &Path,
+    build: impl FnOnce(&Path) -> Result<T, String>,
+) -> Result<T, String> {
+    let building: PathBuf = target.with_extension("db.importing");
+    let _ = fs::remove_file(&building);
+    let value = match build(&building) {
+        Ok(value) => value,
+        Err(e) => {
+            let _ = fs::remove_file(&building);
+            return Err(e);
+        }
+    };
+    if target.exists() {
+        fs::remove_file(target).map_err(|e| format!("Failed to remove existing file: {}", e))?;
+    }
+    fs::rename(&building, target).map_err(|e| format!("Failed to move database into place: {}", e))?;
+    Ok(value)
+}
+
+/// Convert the Kaikki JSONL file at `source` into a dictionary database at
+/// `target`. The database is built next to the target and moved into place
+/// only when the import finished, so a failed import leaves any existing
+/// dictionary untouched. Malformed lines are skipped and counted. Setting
+/// `cancel` stops the import and removes the partial database.
+pub fn import_jsonl(
+    source: &Path,
+    target: &Path,
+    lang_code: &str,
+    lang_name: &str,
+    cancel: &AtomicBool,
+    on_progress: ProgressFn,
+) -> Result<ImportStats, String> {
+    let stats = build_and_install(target, |building| {
+        build_database(source, building, lang_code, lang_name, cancel, on_progress)
+    })?;
+    eprintln!(
+        "[IMPORT] Done: {} entries, {} senses, {} forms, {} relations, {} skipped, {} malformed",
+        stats.entries, stats.senses, stats.forms, stats.relations, stats.skipped, stats.malformed
+    );
+    Ok(stats)
+}
+
+fn build_database(
+    source: &Path,
+    building: &Path,
+    lang_code: &str,
+    lang_name: &str,
+    cancel: &AtomicBool,
+    on_progress: ProgressFn,
+) -> Result<ImportStats, String> {
+    let file = File::open(source).map_err(|e| format!("Failed to open {}: {}", source.display(), e))?;
+    let mut reader = BufReader::new(file);
+
+    let mut conn = super::open_for_write(building)?;
+    // Nothing reads the file until it is renamed, so durability can wait
+    conn.execute_batch("PRAGMA journal_mode = OFF; PRAGMA synchronous = OFF;")
+        .map_err(|e| e.to_string())?;
+    conn.execute_batch(SCHEMA).map_err(|e| format!("Failed to create schema: {}", e))?;
+
+    let mut stats = ImportStats::default();
+    let mut line = Vec::new();
+    let mut line_number = 0usize;
+    let mut bytes_read = 0u64;
+    let mut tx = conn.transaction().map_err(|e| e.to_string())?;
+    let mut in_batch = 0usize;
+
+    loop {
+        line.clear();
+        let read = reader
+            .read_until(b'\n', &mut line)
+            .map_err(|e| format!("Failed to read {}: {}", source.display(), e))?;
+        if read == 0 {
+            break;
+        }
+        line_number += 1;
+        bytes_read += read as u64;
+        if line_number.is_multiple_of(PROGRESS_LINES) {
+            if cancel.load(Ordering::Relaxed) {
+                return Err(format!("{}: stopped after {} entries", IMPORT_CANCELLED, stats.entries));
+            }
+            on_progress("importing", bytes_read, stats.entries);
+        }
+        if line.iter().all(|b| b.is_ascii_whitespace()) {
+            continue;
+        }
+
+        let entry: KaikkiEntry = match serde_json::from_slice(&line) {
+            Ok(entry) => entry,
+            Err(e) => {
+                if stats.malformed < 10 {
+                    eprintln!("[IMPORT] Line {} skipped: {}", line_number, e);
+                }
+                stats.malformed += 1;
+                continue;
+            }
+        };
+        let Some(word) = entry.headword() else {
+            stats.skipped += 1;
+            continue;
+        };
+        if !entry.senses.iter().any(|s| s.glosses.first().is_some_and(|g| !g.is_empty())) {
+            stats.skipped += 1;
+            continue;
+        }
+
+        insert_entry(&tx, &entry, &word, lang_code, lang_name, &mut stats)
+            .map_err(|e| format!("Failed to insert line {}: {}", line_number, e))?;
+        stats.entries += 1;
+        in_batch += 1;
+
+        if in_batch >= IMPORT_BATCH {
+            tx.commit().map_err(|e| e.to_string())?;
+            tx = conn.transaction().map_err(|e| e.to_string())?;
+            in_batch = 0;
+            eprintln!("[IMPORT] {} entries imported...", stats.entries);
+        }
+    }
+    tx.commit().map_err(|e| e.to_string())?;
+
+    if stats.entries == 0 {
+        return Err(format!(
+            "No dictionary entries found ({} malformed, {} skipped lines)",
+            stats.malformed, stats.skipped
+        ));
+    }
+    on_progress("indexing", bytes_read, stats.entries);
+    conn.execute_batch(INDEXES).map_err(|e| format!("Failed to create indexes: {}", e))?;
+    Ok(stats)
+}
@@ -0,0 +1,208 @@
+//! Several dictionaries per language. `manifest.json` in the language
+//! directory names each database file and gives it a priority; lookups go
+//! through the dictionaries in that order. Database files the manifest
+//! doesn't list are still used, after the listed ones, so directories from
+//! before the manifest keep working.
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const MANIFEST_FILE: &str = "manifest.json";
+/// Wiktionary lookups kept for offline use; not a dictionary of its own
+pub const ONLINE_CACHE_FILE: &str = "online_cache.db";
+
+/// Single-dictionary file names used before the manifest existed
+const LEGACY_FILE_SUFFIXES: &[&str] = &["_dict.db", "_dict.sqlite", "dictionary.db", "dict.db"];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    /// File name inside the language directory
+    pub file: String,
+    /// Display name, unique within the language
+    pub name: String,
+    /// Lower numbers are searched first
+    #[serde(default)]
+    pub priority: i64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Manifest {
+    #[serde(default)]
+    pub dictionaries: Vec<ManifestEntry>,
+}
+
+/// A dictionary database in a language directory
+#[derive(Debug, Clone)]
+pub struct DictionaryFile {
+    pub name: String,
+    pub path: PathBuf,
+    pub priority: i64,
+}
+
+pub fn load(dir: &Path) -> Manifest {
+    let path = dir.join(MANIFEST_FILE);
+    let Ok(content) = fs::read_to_string(&path) else {
+        return Manifest::default();
+    };
+    serde_json::from_str(&content).unwrap_or_else(|e| {
+        eprintln!("[DICT] Ignoring unreadable manifest {:?}: {}", path, e);
+        Manifest::default()
+    })
+}
+
+fn save(dir: &Path, manifest: &Manifest) -> Result<(), String> {
+    let content = serde_json::to_string_pretty(manifest).map_err(|e| e.to_string())?;
+    fs::write(dir.join(MANIFEST_FILE), content)
+        .map_err(|e| format!("Failed to write dictionary manifest: {}", e))
+}
+
+fn is_database(path: &Path) -> bool {
+    path.is_file()
+        && path.file_name().and_then(|n| n.to_str()) != Some(ONLINE_CACHE_FILE)
+        && matches!(
+            path.extension().and_then(|e| e.to_str()),
+            Some("db") | Some("sqlite")
+        )
+}
+
+fn is_legacy_file(path: &Path) -> bool {
+    let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+    LEGACY_FILE_SUFFIXES.iter().any(|suffix| name.ends_with(suffix))
+}
+
+/// Dictionaries in a language directory, highest priority first. Listed
+/// files come in manifest order, then unlisted ones named after their file,
+/// old single-dictionary files before anything else.
+pub fn scan(dir: &Path) -> Vec<DictionaryFile> {
+    let manifest = load(dir);
+    let mut files: Vec<DictionaryFile> = manifest
+        .dictionaries
+        .iter()
+        .map(|entry| DictionaryFile {
+            name: entry.name.clone(),
+            path: dir.join(&entry.file),
+            priority: entry.priority,
+        })
+        .filter(|file| is_database(&file.path))
+        .collect();
+    // Stable, so equal priorities keep their manifest order
+    files.sort_by_key(|file| file.priority);
+
+    let mut unlisted: Vec<PathBuf> = fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| is_database(path))
+        .filter(|path| !manifest.dictionaries.iter().any(|e| dir.join(&e.file) == *path))
+        .collect();
+    unlisted.sort_by_key(|path| (!is_legacy_file(path), path.clone()));
+
+    let first_unlisted = files.last().map_or(0, |file| file.priority + 1);
+    for (path, priority) in unlisted.into_iter().zip(first_unlisted..) {
+        let name = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("dictionary")
+            .to_string();
+        files.push(DictionaryFile { name, path, priority });
+    }
+    files
+}
+
+/// The manifest as `scan` sees the directory, unlisted files included, so
+/// that writing it back keeps their current order
+fn materialize(dir: &Path) -> Manifest {
+    Manifest {
+        dictionaries: scan(dir)
+            .into_iter()
+            .map(|file| ManifestEntry {
+                file: file
+                    .path
+                    .file_name()
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or_default(),
+                name: file.name,
+                priority: file.priority,
+            })
+            .collect(),
+    }
+}
+
+/// File an upload named `name` should be written to: the file already
+/// registered under that name, or a new one derived from it
+pub fn target_file(dir: &Path, lang_code: &str, name: &str) -> String {
+    let manifest = materialize(dir);
+    if let Some(entry) = manifest.dictionaries.iter().find(|e| e.name == name) {
+        return entry.file.clone();
+    }
+
+    let mut slug = String::new();
+    for c in name.to_lowercase().chars() {
+        if c.is_alphanumeric() {
+            slug.push(c);
+        } else if !slug.ends_with('_') {
+            slug.push('_');
+        }
+    }
+    let slug = slug.trim_matches('_');
+    let base = if slug.is_empty() { "dict" } else { slug };
+
+    let taken = |file: &str| {
+        dir.join(file).exists() || manifest.dictionaries.iter().any(|e| e.file == file)
+    };
+    let mut file = format!("{}_{}.db", lang_code, base);
+    let mut n = 2;
+    while taken(&file) {
+        file = format!("{}_{}_{}.db", lang_code, base, n);
+        n += 1;
+    }
+    file
+}
+
+/// Record `file` under `name`. A name already in the manifest keeps its
+/// priority unless one is given; a new name goes last by default.
+pub fn register(dir: &Path, file: &str, name: &str, priority: Option<i64>) -> Result<(), String> {
+    let mut manifest = materialize(dir);
+    // The file was just written, so it shows up unlisted under its file name
+    manifest.dictionaries.retain(|e| e.file != file || e.name == name);
+    let next = manifest
+        .dictionaries
+        .iter()
+        .map(|e| e.priority + 1)
+        .max()
+        .unwrap_or(0);
+
+    match manifest.dictionaries.iter_mut().find(|e| e.name == name) {
+        Some(entry) => {
+            entry.file = file.to_string();
+            if let Some(priority) = priority {
+                entry.priority = priority;
+            }
+        }
+        None => manifest.dictionaries.push(ManifestEntry {
+            file: file.to_string(),
+            name: name.to_string(),
+            priority: priority.unwrap_or(next),
+        }),
+    }
+    manifest.dictionaries.sort_by_key(|e| e.priority);
+    save(dir, &manifest)
+}
+
+/// Delete the dictionary called `name` and drop it from the manifest.
+/// Returns the removed file.
+pub fn unregister(dir: &Path, name: &str) -> Result<PathBuf, String> {
+    let mut manifest = materialize(dir);
+    let position = manifest
+        .dictionaries
+        .iter()
+        .position(|e| e.name == name)
+        .ok_or_else(|| format!("Dictionary '{}' not found", name))?;
+    let entry = manifest.dictionaries.remove(position);
+    let path = dir.join(&entry.file);
+    fs::remove_file(&path).map_err(|e| format!("Failed to remove dictionary file: {}", e))?;
+    save(dir, &manifest)?;
+    Ok(path)
+}
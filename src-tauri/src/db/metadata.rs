@@ -0,0 +1,169 @@
+//! Where a dictionary came from, stored as key/value rows in its own
+//! `metadata` table. Databases imported before the table existed get a
+//! description built from the file itself.
+
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Written as `importer_version`
+pub const IMPORTER_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// License of Wiktionary content, and so of every Kaikki extract
+pub const WIKTIONARY_LICENSE: &str = "CC BY-SA 4.0 and GFDL (Wiktionary)";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DictionaryMetadata {
+    /// Download URL or the name of the uploaded file
+    pub source: Option<String>,
+    /// RFC 3339 time of the import
+    pub imported_at: Option<String>,
+    /// Date of the Wiktionary dump, `YYYY-MM-DD`, when the source name has one
+    pub dump_date: Option<String>,
+    pub license: Option<String>,
+    pub importer_version: Option<String>,
+    /// False when the database has no metadata table and the fields were
+    /// guessed from the file
+    pub recorded: bool,
+}
+
+const KEYS: &[&str] = &["source", "imported_at", "dump_date", "license", "importer_version"];
+
+impl DictionaryMetadata {
+    /// Metadata for an import happening now from `source`
+    pub fn for_import(source: &str, license: Option<String>) -> Self {
+        Self {
+            source: Some(source.to_string()),
+            imported_at: Some(chrono::Utc::now().to_rfc3339()),
+            dump_date: dump_date_from_name(source),
+            license,
+            importer_version: Some(IMPORTER_VERSION.to_string()),
+            recorded: true,
+        }
+    }
+
+    fn field(&self, key: &str) -> Option<&String> {
+        match key {
+            "source" => self.source.as_ref(),
+            "imported_at" => self.imported_at.as_ref(),
+            "dump_date" => self.dump_date.as_ref(),
+            "license" => self.license.as_ref(),
+            "importer_version" => self.importer_version.as_ref(),
+            _ => None,
+        }
+    }
+
+    fn set(&mut self, key: &str, value: String) {
+        match key {
+            "source" => self.source = Some(value),
+            "imported_at" => self.imported_at = Some(value),
+            "dump_date" => self.dump_date = Some(value),
+            "license" => self.license = Some(value),
+            "importer_version" => self.importer_version = Some(value),
+            _ => {}
+        }
+    }
+}
+
+/// Store the fields that are set; absent ones keep whatever the database
+/// already has, so an uploaded file's own license survives
+pub fn write(conn: &Connection, metadata: &DictionaryMetadata) -> Result<(), String> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS metadata (key TEXT PRIMARY KEY, value TEXT NOT NULL)",
+        [],
+    )
+    .map_err(|e| format!("Failed to create metadata table: {}", e))?;
+    for key in KEYS {
+        if let Some(value) = metadata.field(key) {
+            conn.execute(
+                "INSERT OR REPLACE INTO metadata (key, value) VALUES (?1, ?2)",
+                params![key, value],
+            )
+            .map_err(|e| format!("Failed to write metadata: {}", e))?;
+        }
+    }
+    Ok(())
+}
+
+/// Open the database at `path` and store `metadata` in it
+pub fn write_file(path: &Path, metadata: &DictionaryMetadata) -> Result<(), String> {
+    let conn = super::open_for_write(path)?;
+    write(&conn, metadata)
+}
+
+/// The database's metadata, or for one without the table the file name as
+/// source and its modification time as import date
+pub fn read(conn: &Connection, path: &Path) -> DictionaryMetadata {
+    let rows: Option<Vec<(String, String)>> = conn
+        .prepare("SELECT key, value FROM metadata")
+        .and_then(|mut stmt| {
+            stmt.query_map([], |r| Ok((r.get(0)?, r.get(1)?)))
+                .map(|rows| rows.filter_map(|r| r.ok()).collect())
+        })
+        .ok();
+
+    match rows {
+        Some(rows) => {
+            let mut metadata = DictionaryMetadata {
+                recorded: true,
+                ..Default::default()
+            };
+            for (key, value) in rows {
+                metadata.set(&key, value);
+            }
+            metadata
+        }
+        None => {
+            let source = path
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string());
+            let modified = std::fs::metadata(path)
+                .and_then(|m| m.modified())
+                .ok()
+                .map(|t| chrono::DateTime::<chrono::Utc>::from(t).to_rfc3339());
+            DictionaryMetadata {
+                dump_date: source.as_deref().and_then(dump_date_from_name),
+                source,
+                imported_at: modified,
+                ..Default::default()
+            }
+        }
+    }
+}
+
+/// A date in a dump or extract name: `20240301` or `2024-03-01`
+pub fn dump_date_from_name(name: &str) -> Option<String> {
+    let chars: Vec<char> = name.chars().collect();
+    let digits_at = |start: usize, len: usize| {
+        chars.get(start..start + len).filter(|s| s.iter().all(|c| c.is_ascii_digit()))
+            .map(|s| s.iter().collect::<String>())
+    };
+    let plausible = |y: &str, m: &str, d: &str| {
+        let (y, m, d): (u32, u32, u32) = (y.parse().ok()?, m.parse().ok()?, d.parse().ok()?);
+        ((2000..=2100).contains(&y) && (1..=12).contains(&m) && (1..=31).contains(&d))
+            .then(|| format!("{:04}-{:02}-{:02}", y, m, d))
+    };
+
+    for start in 0..chars.len() {
+        // Only at the start of a digit run, so "120240301" isn't read as a date
+        if start > 0 && chars[start - 1].is_ascii_digit() {
+            continue;
+        }
+        if let Some(run) = digits_at(start, 8) {
+            if !chars.get(start + 8).is_some_and(|c| c.is_ascii_digit()) {
+                if let Some(date) = plausible(&run[..4], &run[4..6], &run[6..]) {
+                    return Some(date);
+                }
+            }
+        }
+        if let (Some(y), Some(m), Some(d)) = (digits_at(start, 4), digits_at(start + 5, 2), digits_at(start + 8, 2)) {
+            if chars[start + 4] == '-' && chars[start + 7] == '-' {
+                if let Some(date) = plausible(&y, &m, &d) {
+                    return Some(date);
+                }
+            }
+        }
+    }
+    None
+}
@@ -0,0 +1,320 @@
+//! StarDict (.ifo / .idx / .dict[.dz]) → SQLite conversion into the same
+//! schema as the Kaikki importer. Each headword becomes one entry with its
+//! whole definition as a single sense and no part of speech.
+
+use flate2::read::{DeflateDecoder, GzDecoder};
+use rusqlite::params;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use crate::normalize::normalize_word;
+use super::import::{build_and_install, ImportStats, ProgressFn, IMPORT_CANCELLED, INDEXES, SCHEMA};
+
+const IMPORT_BATCH: usize = 5_000;
+
+/// The three parts of a StarDict dictionary, decompressed
+struct StarDictFiles {
+    ifo: String,
+    idx: Vec<u8>,
+    dict: Vec<u8>,
+}
+
+/// Settings from the .ifo file that affect parsing
+struct IfoInfo {
+    bookname: Option<String>,
+    offset_bits: u32,
+    same_type_sequence: Option<String>,
+}
+
+fn parse_ifo(ifo: &str) -> Result<IfoInfo, String> {
+    if !ifo.trim_start().starts_with("StarDict's dict ifo file") {
+        return Err("Not a StarDict .ifo file".to_string());
+    }
+    let value = |key: &str| {
+        ifo.lines()
+            .find_map(|line| line.strip_prefix(key)?.strip_prefix('='))
+            .map(|v| v.trim().to_string())
+    };
+    Ok(IfoInfo {
+        bookname: value("bookname"),
+        offset_bits: value("idxoffsetbits").and_then(|v| v.parse().ok()).unwrap_or(32),
+        same_type_sequence: value("sametypesequence").filter(|v| !v.is_empty()),
+    })
+}
+
+fn gunzip(data: Vec<u8>, name: &str) -> Result<Vec<u8>, String> {
+    // .dict.dz is dictzip, which is plain gzip with an extra header field
+    let mut out = Vec::new();
+    GzDecoder::new(&data[..])
+        .read_to_end(&mut out)
+        .map_err(|e| format!("Failed to decompress {}: {}", name, e))?;
+    Ok(out)
+}
+
+/// Load the bundle next to an .ifo file: `<base>.idx[.gz]` and `<base>.dict[.dz]`
+fn load_from_ifo(ifo_path: &Path) -> Result<StarDictFiles, String> {
+    let ifo = fs::read_to_string(ifo_path).map_err(|e| format!("Failed to read .ifo: {}", e))?;
+    let base: PathBuf = ifo_path.with_extension("");
+    let sibling = |suffix: &str| PathBuf::from(format!("{}{}", base.to_string_lossy(), suffix));
+
+    let idx = if sibling(".idx").exists() {
+        fs::read(sibling(".idx")).map_err(|e| format!("Failed to read .idx: {}", e))?
+    } else if sibling(".idx.gz").exists() {
+        gunzip(fs::read(sibling(".idx.gz")).map_err(|e| format!("Failed to read .idx.gz: {}", e))?, ".idx.gz")?
+    } else {
+        return Err(format!("Missing {}.idx next to the .ifo file", base.display()));
+    };
+    let dict = if sibling(".dict").exists() {
+        fs::read(sibling(".dict")).map_err(|e| format!("Failed to read .dict: {}", e))?
+    } else if sibling(".dict.dz").exists() {
+        gunzip(fs::read(sibling(".dict.dz")).map_err(|e| format!("Failed to read .dict.dz: {}", e))?, ".dict.dz")?
+    } else {
+        return Err(format!("Missing {}.dict or .dict.dz next to the .ifo file", base.display()));
+    };
+    Ok(StarDictFiles { ifo, idx, dict })
+}
+
+fn read_u16(data: &[u8], at: usize) -> Option<usize> {
+    Some(u16::from_le_bytes(data.get(at..at + 2)?.try_into().ok()?) as usize)
+}
+
+fn read_u32_le(data: &[u8], at: usize) -> Option<usize> {
+    Some(u32::from_le_bytes(data.get(at..at + 4)?.try_into().ok()?) as usize)
+}
+
+/// Files of a .zip archive whose names end with one of `suffixes`. Only
+/// what StarDict archives use is supported: stored or deflated entries,
+/// no zip64, no encryption.
+fn unzip_matching(archive: &[u8], suffixes: &[&str]) -> Result<Vec<(String, Vec<u8>)>, String> {
+    const END_SIGNATURE: &[u8] = b"PK\x05\x06";
+    let end = archive
+        .windows(4)
+        .rposition(|w| w == END_SIGNATURE)
+        .ok_or("Not a zip archive (no end of central directory)")?;
+    let count = read_u16(archive, end + 10).ok_or("Truncated zip archive")?;
+    let mut at = read_u32_le(archive, end + 16).ok_or("Truncated zip archive")?;
+
+    let mut files = Vec::new();
+    for _ in 0..count {
+        if archive.get(at..at + 4) != Some(b"PK\x01\x02") {
+            return Err(format!("Corrupt zip central directory at offset {}", at));
+        }
+        let header = || format!("Truncated zip central directory at offset {}", at);
+        let method = read_u16(archive, at + 10).ok_or_else(header)?;
+        let compressed = read_u32_le(archive, at + 20).ok_or_else(header)?;
+        let name_len = read_u16(archive, at + 28).ok_or_else(header)?;
+        let extra_len = read_u16(archive, at + 30).ok_or_else(header)?;
+        let comment_len = read_u16(archive, at + 32).ok_or_else(header)?;
+        let local = read_u32_le(archive, at + 42).ok_or_else(header)?;
+        let name = String::from_utf8_lossy(archive.get(at + 46..at + 46 + name_len).ok_or_else(header)?).to_string();
+        at += 46 + name_len + extra_len + comment_len;
+
+        if !suffixes.iter().any(|s| name.to_lowercase().ends_with(s)) {
+            continue;
+        }
+        let local_name_len = read_u16(archive, local + 26).ok_or("Truncated zip entry")?;
+        let local_extra_len = read_u16(archive, local + 28).ok_or("Truncated zip entry")?;
+        let start = local + 30 + local_name_len + local_extra_len;
+        let data = archive
+            .get(start..start + compressed)
+            .ok_or_else(|| format!("Truncated zip entry {}", name))?;
+        let content = match method {
+            0 => data.to_vec(),
+            8 => {
+                let mut out = Vec::new();
+                DeflateDecoder::new(data)
+                    .read_to_end(&mut out)
+                    .map_err(|e| format!("Failed to inflate {}: {}", name, e))?;
+                out
+            }
+            other => return Err(format!("Unsupported zip compression method {} for {}", other, name)),
+        };
+        files.push((name, content));
+    }
+    Ok(files)
+}
+
+fn load_from_zip(zip_path: &Path) -> Result<StarDictFiles, String> {
+    let archive = fs::read(zip_path).map_err(|e| format!("Failed to read archive: {}", e))?;
+    let files = unzip_matching(&archive, &[".ifo", ".idx", ".idx.gz", ".dict", ".dict.dz"])?;
+    let take = |suffix: &str| {
+        files
+            .iter()
+            .find(|(name, _)| name.to_lowercase().ends_with(suffix))
+            .map(|(_, data)| data.clone())
+    };
+
+    let ifo = take(".ifo").ok_or("Archive has no .ifo file")?;
+    let idx = match take(".idx") {
+        Some(idx) => idx,
+        None => gunzip(take(".idx.gz").ok_or("Archive has no .idx file")?, ".idx.gz")?,
+    };
+    let dict = match take(".dict") {
+        Some(dict) => dict,
+        None => gunzip(take(".dict.dz").ok_or("Archive has no .dict or .dict.dz file")?, ".dict.dz")?,
+    };
+    Ok(StarDictFiles {
+        ifo: String::from_utf8_lossy(&ifo).to_string(),
+        idx,
+        dict,
+    })
+}
+
+/// (headword, offset, size) for every .idx record
+fn parse_idx(idx: &[u8], offset_bits: u32) -> Result<Vec<(String, usize, usize)>, String> {
+    let offset_len = if offset_bits == 64 { 8 } else { 4 };
+    let mut records = Vec::new();
+    let mut at = 0;
+    while at < idx.len() {
+        let nul = idx[at..]
+            .iter()
+            .position(|b| *b == 0)
+            .ok_or_else(|| format!("Malformed StarDict index at byte offset {}: headword is not terminated", at))?;
+        let word = String::from_utf8_lossy(&idx[at..at + nul]).to_string();
+        let fields = at + nul + 1;
+        let record = idx
+            .get(fields..fields + offset_len + 4)
+            .ok_or_else(|| format!("Malformed StarDict index at byte offset {}: record is truncated", at))?;
+        let offset = if offset_len == 8 {
+            u64::from_be_bytes(record[..8].try_into().unwrap()) as usize
+        } else {
+            u32::from_be_bytes(record[..4].try_into().unwrap()) as usize
+        };
+        let size = u32::from_be_bytes(record[offset_len..].try_into().unwrap()) as usize;
+        records.push((word, offset, size));
+        at = fields + offset_len + 4;
+    }
+    Ok(records)
+}
+
+/// Whether a StarDict field type holds text (lowercase types are text,
+/// uppercase ones binary)
+fn is_text_type(kind: u8) -> bool {
+    kind.is_ascii_lowercase()
+}
+
+/// The text fields of one definition, joined by newlines
+fn definition_text(data: &[u8], same_type_sequence: Option<&str>) -> String {
+    let mut parts = Vec::new();
+    let mut at = 0;
+    let mut take_field = |kind: u8, at: &mut usize, last: bool| {
+        if is_text_type(kind) {
+            let end = if last {
+                data.len()
+            } else {
+                data[*at..].iter().position(|b| *b == 0).map(|p| *at + p).unwrap_or(data.len())
+            };
+            let text = String::from_utf8_lossy(&data[*at..end]).trim().to_string();
+            if !text.is_empty() {
+                parts.push(text);
+            }
+            *at = (end + 1).min(data.len());
+        } else {
+            let size = if last {
+                data.len() - *at
+            } else {
+                data.get(*at..*at + 4)
+                    .map(|b| u32::from_be_bytes(b.try_into().unwrap()) as usize + 4)
+                    .unwrap_or(data.len() - *at)
+            };
+            *at = (*at + size).min(data.len());
+        }
+    };
+
+    match same_type_sequence {
+        Some(types) => {
+            let types = types.as_bytes();
+            for (i, kind) in types.iter().enumerate() {
+                take_field(*kind, &mut at, i == types.len() - 1);
+            }
+        }
+        None => {
+            while at < data.len() {
+                let kind = data[at];
+                at += 1;
+                take_field(kind, &mut at, false);
+            }
+        }
+    }
+    parts.join("\n")
+}
+
+/// Convert a StarDict dictionary into a database at `target`. `source` is
+/// either the .ifo file (with .idx and .dict[.dz] beside it) or a .zip
+/// containing all three.
+pub fn import_stardict(
+    source: &Path,
+    target: &Path,
+    lang_code: &str,
+    lang_name: &str,
+    cancel: &AtomicBool,
+    on_progress: ProgressFn,
+) -> Result<ImportStats, String> {
+    let is_zip = source
+        .extension()
+        .is_some_and(|e| e.eq_ignore_ascii_case("zip"));
+    let files = if is_zip { load_from_zip(source)? } else { load_from_ifo(source)? };
+    let info = parse_ifo(&files.ifo)?;
+    let records = parse_idx(&files.idx, info.offset_bits)?;
+    eprintln!(
+        "[IMPORT] StarDict '{}': {} headwords",
+        info.bookname.as_deref().unwrap_or("?"),
+        records.len()
+    );
+
+    let stats = build_and_install(target, |building| {
+        let mut conn = super::open_for_write(building)?;
+        conn.execute_batch("PRAGMA journal_mode = OFF; PRAGMA synchronous = OFF;")
+            .map_err(|e| e.to_string())?;
+        conn.execute_batch(SCHEMA).map_err(|e| format!("Failed to create schema: {}", e))?;
+
+        let mut stats = ImportStats::default();
+        for (batch_index, batch) in records.chunks(IMPORT_BATCH).enumerate() {
+            if cancel.load(Ordering::Relaxed) {
+                return Err(format!("{}: stopped after {} entries", IMPORT_CANCELLED, stats.entries));
+            }
+            let tx = conn.transaction().map_err(|e| e.to_string())?;
+            for (i, (word, offset, size)) in batch.iter().enumerate() {
+                let data = files.dict.get(*offset..offset + size).ok_or_else(|| {
+                    format!(
+                        "Malformed StarDict index: entry {} ('{}') points to bytes {}..{} but the .dict has {}",
+                        batch_index * IMPORT_BATCH + i,
+                        word,
+                        offset,
+                        offset + size,
+                        files.dict.len()
+                    )
+                })?;
+                let definition = definition_text(data, info.same_type_sequence.as_deref());
+                if word.is_empty() || definition.is_empty() {
+                    stats.skipped += 1;
+                    continue;
+                }
+                tx.prepare_cached(
+                    "INSERT INTO dictionary (word, normalized_word, lang, lang_code, pos)
+                     VALUES (?1, ?2, ?3, ?4, NULL)",
+                )
+                .and_then(|mut stmt| stmt.execute(params![word, normalize_word(word, lang_code), lang_name, lang_code]))
+                .map_err(|e| e.to_string())?;
+                let dictionary_id = tx.last_insert_rowid();
+                tx.prepare_cached("INSERT INTO senses (dictionary_id, sense_index, gloss) VALUES (?1, 0, ?2)")
+                    .and_then(|mut stmt| stmt.execute(params![dictionary_id, definition]))
+                    .map_err(|e| e.to_string())?;
+                stats.entries += 1;
+                stats.senses += 1;
+            }
+            tx.commit().map_err(|e| e.to_string())?;
+            let done = ((batch_index + 1) * IMPORT_BATCH).min(records.len());
+            on_progress("importing", (done as u64 * files.idx.len() as u64) / records.len().max(1) as u64, stats.entries);
+        }
+        if stats.entries == 0 {
+            return Err("StarDict dictionary has no entries".to_string());
+        }
+        on_progress("indexing", files.idx.len() as u64, stats.entries);
+        conn.execute_batch(INDEXES).map_err(|e| format!("Failed to create indexes: {}", e))?;
+        Ok(stats)
+    })?;
+    eprintln!("[IMPORT] StarDict done: {} entries, {} skipped", stats.entries, stats.skipped);
+    Ok(stats)
+}
@@ -0,0 +1,64 @@
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use tauri::{AppHandle, Emitter};
+use crate::db::{self, manifest::ONLINE_CACHE_FILE};
+
+/// How often `dict/` is checked for dictionaries added or removed by hand
+const POLL_INTERVAL: Duration = Duration::from_secs(3);
+
+/// Files whose changes affect the language list
+fn is_watched(path: &Path) -> bool {
+    let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+    name != ONLINE_CACHE_FILE
+        && (name.ends_with(".db") || name.ends_with(".sqlite") || name.ends_with(".json"))
+}
+
+/// Name, size and modification time of every watched file in `dict/` and
+/// its language directories
+fn snapshot(dict_dir: &Path) -> Vec<(PathBuf, u64, Option<SystemTime>)> {
+    let mut files = Vec::new();
+    let visit = |dir: &Path, files: &mut Vec<_>| -> Vec<PathBuf> {
+        let mut subdirs = Vec::new();
+        for entry in std::fs::read_dir(dir).into_iter().flatten().flatten() {
+            let path = entry.path();
+            let Ok(meta) = entry.metadata() else { continue };
+            if meta.is_dir() {
+                subdirs.push(path);
+            } else if is_watched(&path) {
+                files.push((path, meta.len(), meta.modified().ok()));
+            }
+        }
+        subdirs
+    };
+    for subdir in visit(dict_dir, &mut files) {
+        visit(&subdir, &mut files);
+    }
+    files.sort();
+    files
+}
+
+/// Watch `dict/` from a background thread. When a dictionary file appears,
+/// changes or disappears the caches are dropped, the language list is
+/// rebuilt and `dictionaries-changed` is emitted with it.
+pub fn start(app: AppHandle) {
+    std::thread::spawn(move || {
+        let dict_dir = db::get_dict_dir();
+        let mut last = snapshot(&dict_dir);
+        loop {
+            std::thread::sleep(POLL_INTERVAL);
+            let current = snapshot(&dict_dir);
+            if current == last {
+                continue;
+            }
+            last = current;
+            eprintln!("[DICT] Dictionary directory changed, rescanning");
+            db::invalidate_connections(None);
+            match db::get_available_languages() {
+                Ok(languages) => {
+                    let _ = app.emit("dictionaries-changed", languages);
+                }
+                Err(e) => eprintln!("[DICT] Rescan after change failed: {}", e),
+            }
+        }
+    });
+}
@@ -0,0 +1,183 @@
+use serde::{Deserialize, Serialize};
+use crate::capabilities::Capabilities;
+use crate::commands::sanskrit::Segment;
+use crate::commands::vocabulary::TermIndex;
+use crate::db::DictionaryEntry;
+
+/// One quick action for a lookup result. The frontend renders these as-is;
+/// `label_key` is an i18n key and `reason` explains why it's disabled.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EntryAction {
+    pub id: String,
+    pub label_key: String,
+    pub enabled: bool,
+    pub reason: Option<String>,
+}
+
+fn action(id: &str, enabled: bool, reason: &str) -> EntryAction {
+    EntryAction {
+        id: id.to_string(),
+        label_key: format!("entry.action.{}", id),
+        enabled,
+        reason: if enabled { None } else { Some(reason.to_string()) },
+    }
+}
+
+/// Actions for a dictionary entry. Uses only data the entry already carries
+/// plus the in-memory term index, so it adds no queries.
+pub fn for_entry(entry: &DictionaryEntry, saved: bool, caps: &Capabilities) -> Vec<EntryAction> {
+    let has_inflections = entry.inflections.as_ref().is_some_and(|i| !i.is_empty());
+    let mut actions = vec![
+        action("save", !saved, "already_saved"),
+        action("copy", true, ""),
+        action("speak", caps.tts, "tts_unavailable"),
+        action("play_audio", entry.has_audio == Some(true), "no_recording"),
+        action("inflections", has_inflections, "no_inflections"),
+        action("detach", entry.entry_id.is_some(), "no_entry_id"),
+    ];
+    if entry.language == "sa" {
+        actions.push(action("split", caps.sanskrit_tools, "sanskrit_tools_missing"));
+    }
+    actions
+}
+
+/// Fill in `actions` for each entry
+pub fn annotate(entries: &mut [DictionaryEntry], index: &TermIndex, caps: &Capabilities) {
+    for entry in entries.iter_mut() {
+        let saved = index.status_for(&entry.language, &entry.text).is_some();
+        entry.actions = Some(for_entry(entry, saved, caps));
+    }
+}
+
+/// Actions for a segment of analysed text in `language`
+pub fn for_segment(segment: &Segment, language: &str, index: &TermIndex, caps: &Capabilities) -> Vec<EntryAction> {
+    let headword = segment.lemma.as_deref().unwrap_or(&segment.original);
+    let saved = segment.term_id.is_some() || index.status_for(language, headword).is_some();
+    let has_split = segment.split.as_ref().is_some_and(|s| s.len() > 1);
+    vec![
+        action("save", !saved, "already_saved"),
+        action("copy", true, ""),
+        action("speak", caps.tts, "tts_unavailable"),
+        action("split", caps.sanskrit_tools && has_split, if caps.sanskrit_tools { "no_split" } else { "sanskrit_tools_missing" }),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::vocabulary::{Term, TermsData};
+
+    const NO_TTS: Capabilities = Capabilities { sanskrit_tools: false, tts: false };
+    const TTS: Capabilities = Capabilities { sanskrit_tools: false, tts: true };
+
+    fn entry(text: &str, language: &str) -> DictionaryEntry {
+        serde_json::from_value(serde_json::json!({
+            "entry_id": format!("{}:{}", language, text),
+            "text": text,
+            "language": language,
+            "has_audio": true,
+        }))
+        .unwrap()
+    }
+
+    fn index(saved: &[(&str, &str)]) -> TermIndex {
+        let terms = saved
+            .iter()
+            .map(|(language, text)| {
+                serde_json::from_value::<Term>(serde_json::json!({
+                    "id": format!("{}:{}", language, text),
+                    "text": text,
+                    "languageId": language,
+                    "translation": "",
+                    "notes": "",
+                    "status": 1,
+                }))
+                .unwrap()
+            })
+            .collect();
+        TermIndex::build(&TermsData { terms, version: String::new(), updatedAt: 0 })
+    }
+
+    /// (id, enabled, reason) of each action
+    fn summary(actions: &[EntryAction]) -> Vec<(&str, bool, Option<&str>)> {
+        actions.iter().map(|a| (a.id.as_str(), a.enabled, a.reason.as_deref())).collect()
+    }
+
+    #[test]
+    fn unsaved_word_without_tts() {
+        let mut entries = vec![entry("Haus", "de")];
+        annotate(&mut entries, &index(&[]), &NO_TTS);
+        assert_eq!(
+            summary(entries[0].actions.as_ref().unwrap()),
+            vec![
+                ("save", true, None),
+                ("copy", true, None),
+                ("speak", false, Some("tts_unavailable")),
+                ("play_audio", true, None),
+                ("inflections", false, Some("no_inflections")),
+                ("detach", true, None),
+            ]
+        );
+        assert_eq!(entries[0].actions.as_ref().unwrap()[0].label_key, "entry.action.save");
+    }
+
+    #[test]
+    fn saved_word_with_tts() {
+        // Saved-status ignores case, like the term index
+        let mut entries = vec![entry("Haus", "de"), entry("Baum", "de")];
+        annotate(&mut entries, &index(&[("de", "haus")]), &TTS);
+        let saved = summary(entries[0].actions.as_ref().unwrap());
+        assert_eq!(saved[0], ("save", false, Some("already_saved")));
+        assert_eq!(saved[2], ("speak", true, None));
+        assert_eq!(summary(entries[1].actions.as_ref().unwrap())[0], ("save", true, None));
+    }
+
+    #[test]
+    fn saved_in_another_language_is_not_saved() {
+        let mut entries = vec![entry("Rat", "de")];
+        annotate(&mut entries, &index(&[("en", "rat")]), &TTS);
+        assert!(entries[0].actions.as_ref().unwrap()[0].enabled);
+    }
+
+    #[test]
+    fn only_sanskrit_entries_offer_split() {
+        let caps = Capabilities { sanskrit_tools: true, tts: true };
+        let sanskrit = for_entry(&entry("dharma", "sa"), false, &caps);
+        assert_eq!(summary(&sanskrit).last(), Some(&("split", true, None)));
+        assert!(for_entry(&entry("Haus", "de"), false, &caps).iter().all(|a| a.id != "split"));
+        let missing = for_entry(&entry("dharma", "sa"), false, &TTS);
+        assert_eq!(summary(&missing).last(), Some(&("split", false, Some("sanskrit_tools_missing"))));
+    }
+
+    #[test]
+    fn segment_actions_follow_saved_lemma_and_split() {
+        let segment = |lemma: Option<&str>, split: &[&str]| Segment {
+            original: "rāmo'pi".to_string(),
+            split: Some(split.iter().map(|s| s.to_string()).collect()),
+            lemma: lemma.map(str::to_string),
+            morphology: None,
+            actions: Vec::new(),
+            dictionary: None,
+            term_status: None,
+            term_id: None,
+            offset: None,
+        };
+        let caps = Capabilities { sanskrit_tools: true, tts: false };
+        let saved = index(&[("sa", "rāma")]);
+
+        let actions = for_segment(&segment(Some("rāma"), &["rāmaḥ", "api"]), "sa", &saved, &caps);
+        assert_eq!(
+            summary(&actions),
+            vec![
+                ("save", false, Some("already_saved")),
+                ("copy", true, None),
+                ("speak", false, Some("tts_unavailable")),
+                ("split", true, None),
+            ]
+        );
+
+        let actions = for_segment(&segment(None, &["rāmo'pi"]), "sa", &saved, &caps);
+        assert_eq!(summary(&actions)[0], ("save", true, None));
+        assert_eq!(summary(&actions)[3], ("split", false, Some("no_split")));
+    }
+}
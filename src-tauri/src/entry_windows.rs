@@ -0,0 +1,80 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, WebviewUrl, WebviewWindowBuilder, WindowEvent};
+
+/// Detached entry cards open at the same time
+pub const MAX_ENTRY_WINDOWS: usize = 6;
+pub const ENTRY_WINDOW_PREFIX: &str = "entry-";
+
+/// Tracks detached `entry-<n>` windows. Labels are never reused within a
+/// session so late events for a closed card can't reach a new one.
+pub struct EntryWindows {
+    next_id: AtomicUsize,
+    open: Mutex<Vec<String>>,
+}
+
+impl EntryWindows {
+    pub fn new() -> Self {
+        Self {
+            next_id: AtomicUsize::new(1),
+            open: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub fn labels(&self) -> Vec<String> {
+        self.open.lock().unwrap().clone()
+    }
+
+    /// Open a small always-on-top card showing one dictionary entry
+    pub fn open(&self, app: &AppHandle, entry_id: i64, language: &str) -> Result<String, String> {
+        if !language.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+            return Err(format!("Invalid language '{}'", language));
+        }
+
+        let mut open = self.open.lock().unwrap();
+        if open.len() >= MAX_ENTRY_WINDOWS {
+            return Err(format!("At most {} entry windows can be open", MAX_ENTRY_WINDOWS));
+        }
+
+        let label = format!("{}{}", ENTRY_WINDOW_PREFIX, self.next_id.fetch_add(1, Ordering::SeqCst));
+        let url = format!("floating.html?entry={}&language={}", entry_id, language);
+        let window = WebviewWindowBuilder::new(app, &label, WebviewUrl::App(url.into()))
+            .title("Lumina Entry")
+            .inner_size(340.0, 420.0)
+            .always_on_top(true)
+            .skip_taskbar(true)
+            .resizable(true)
+            .build()
+            .map_err(|e| format!("Failed to open entry window: {}", e))?;
+
+        let app_handle = app.clone();
+        let closed_label = label.clone();
+        window.on_window_event(move |event| {
+            if let WindowEvent::Destroyed = event {
+                if let Some(state) = app_handle.try_state::<EntryWindows>() {
+                    state.open.lock().unwrap().retain(|l| l != &closed_label);
+                }
+            }
+        });
+
+        open.push(label.clone());
+        Ok(label)
+    }
+
+    /// Close every entry window; tracking is cleaned up by their destroy events
+    pub fn close_all(&self, app: &AppHandle) -> usize {
+        let labels = self.labels();
+        for label in &labels {
+            if let Some(window) = app.get_webview_window(label) {
+                let _ = window.close();
+            }
+        }
+        labels.len()
+    }
+}
+
+impl Default for EntryWindows {
+    fn default() -> Self {
+        Self::new()
+    }
+}
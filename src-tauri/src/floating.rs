@@ -39,7 +39,7 @@ impl FloatingWindowManager {
         if let Some(window) = app.get_webview_window("floating") {
             window.show().map_err(|e| e.to_string())?;
             window.set_focus().map_err(|e| e.to_string())?;
-            window.emit("new-query", query).map_err(|e| e.to_string())?;
+            window.emit_to("floating", "new-query", query).map_err(|e| e.to_string())?;
         }
         Ok(())
     }
@@ -0,0 +1,32 @@
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// Dictionary imports, downloads and Sanskrit analyses running in the
+/// background, by job id, with the flag that cancels them
+static JOBS: Lazy<Mutex<HashMap<String, Arc<AtomicBool>>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Register a job and return its id ("import-3", "download-4") and cancel flag
+pub fn start(kind: &str) -> (String, Arc<AtomicBool>) {
+    let id = format!("{}-{}", kind, NEXT_ID.fetch_add(1, Ordering::SeqCst));
+    let flag = Arc::new(AtomicBool::new(false));
+    JOBS.lock().unwrap().insert(id.clone(), flag.clone());
+    (id, flag)
+}
+
+/// Ask a running job to stop; false when no such job is running
+pub fn cancel(id: &str) -> bool {
+    match JOBS.lock().unwrap().get(id) {
+        Some(flag) => {
+            flag.store(true, Ordering::Relaxed);
+            true
+        }
+        None => false,
+    }
+}
+
+pub fn finish(id: &str) {
+    JOBS.lock().unwrap().remove(id);
+}
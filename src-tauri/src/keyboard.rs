@@ -0,0 +1,172 @@
+/// Characters learners of a language need but can't easily type, in the
+/// order they should appear on the on-screen keyboard
+const SPECIAL_CHARACTERS: &[(&str, &[&str])] = &[
+    ("de", &["ä", "ö", "ü", "ß", "Ä", "Ö", "Ü"]),
+    ("fr", &["é", "è", "ê", "ë", "à", "â", "ç", "î", "ï", "ô", "û", "ù", "ü", "ÿ", "œ", "æ"]),
+    ("es", &["á", "é", "í", "ó", "ú", "ñ", "ü", "¿", "¡"]),
+    ("it", &["à", "è", "é", "ì", "ò", "ù"]),
+    ("pt", &["á", "â", "ã", "à", "é", "ê", "í", "ó", "ô", "õ", "ú", "ç"]),
+    ("la", &["ā", "ē", "ī", "ō", "ū", "ȳ"]),
+    ("pl", &["ą", "ć", "ę", "ł", "ń", "ó", "ś", "ź", "ż"]),
+    ("sv", &["å", "ä", "ö"]),
+    ("da", &["æ", "ø", "å"]),
+    ("no", &["æ", "ø", "å"]),
+    ("fi", &["ä", "ö", "å"]),
+    ("tr", &["ç", "ğ", "ı", "İ", "ö", "ş", "ü"]),
+    ("vi", &["ă", "â", "đ", "ê", "ô", "ơ", "ư"]),
+    // IAST
+    ("sa", &["ā", "ī", "ū", "ṛ", "ṝ", "ḷ", "ṅ", "ñ", "ṭ", "ḍ", "ṇ", "ś", "ṣ", "ṃ", "ḥ"]),
+];
+
+/// An ASCII digraph and the characters it may stand for
+type Digraph = (&'static str, &'static [&'static str]);
+
+/// ASCII digraphs people type in place of a special character. A digraph
+/// may stand for several characters (IAST "sh" is either ś or ṣ).
+const DIGRAPHS: &[(&str, &[Digraph])] = &[
+    ("de", &[("ae", &["ä"]), ("oe", &["ö"]), ("ue", &["ü"]), ("ss", &["ß"])]),
+    ("sv", &[("ae", &["ä"]), ("oe", &["ö"]), ("aa", &["å"])]),
+    ("da", &[("ae", &["æ"]), ("oe", &["ø"]), ("aa", &["å"])]),
+    ("no", &[("ae", &["æ"]), ("oe", &["ø"]), ("aa", &["å"])]),
+    ("es", &[("n~", &["ñ"])]),
+    ("la", &[("aa", &["ā"]), ("ee", &["ē"]), ("ii", &["ī"]), ("oo", &["ō"]), ("uu", &["ū"])]),
+    (
+        "sa",
+        &[
+            ("aa", &["ā"]),
+            ("ii", &["ī"]),
+            ("uu", &["ū"]),
+            ("sh", &["ś", "ṣ"]),
+            ("~n", &["ñ"]),
+            (".m", &["ṃ"]),
+            (".h", &["ḥ"]),
+        ],
+    ),
+];
+
+/// Variants generated per query; digraph combinations grow exponentially
+const MAX_CANDIDATES: usize = 16;
+
+pub fn builtin_special_characters(language: &str) -> Vec<String> {
+    SPECIAL_CHARACTERS
+        .iter()
+        .find(|(code, _)| *code == language)
+        .map(|(_, chars)| chars.iter().map(|c| c.to_string()).collect())
+        .unwrap_or_default()
+}
+
+/// Built-in characters followed by user additions, without duplicates
+pub fn special_characters(language: &str, custom: &[String]) -> Vec<String> {
+    let mut characters = builtin_special_characters(language);
+    for c in custom {
+        if !c.is_empty() && !characters.contains(c) {
+            characters.push(c.clone());
+        }
+    }
+    characters
+}
+
+/// A spelling of the query with digraphs replaced
+#[derive(Debug, Clone, PartialEq)]
+pub struct InputVariant {
+    pub text: String,
+    /// The replacements that produced it, e.g. "ae→ä"
+    pub transformation: String,
+}
+
+/// Alternative spellings of `query` with the language's digraphs converted,
+/// fewest replacements first. The literal query itself is never included;
+/// callers try it first and only fall back to these when it misses.
+pub fn digraph_variants(language: &str, query: &str) -> Vec<InputVariant> {
+    let Some((_, rules)) = DIGRAPHS.iter().find(|(code, _)| *code == language) else {
+        return Vec::new();
+    };
+    let lower = query.to_lowercase();
+
+    // (text, replacements applied, next byte offset to scan from)
+    let mut frontier: Vec<(String, Vec<String>, usize)> = vec![(lower.clone(), Vec::new(), 0)];
+    let mut variants: Vec<InputVariant> = Vec::new();
+
+    while !frontier.is_empty() && variants.len() < MAX_CANDIDATES {
+        let mut next = Vec::new();
+        for (text, applied, from) in &frontier {
+            for (digraph, replacements) in rules.iter() {
+                let mut search_from = *from;
+                while let Some(pos) = text[search_from..].find(digraph).map(|p| p + search_from) {
+                    for replacement in replacements.iter() {
+                        let candidate = format!("{}{}{}", &text[..pos], replacement, &text[pos + digraph.len()..]);
+                        let mut steps = applied.clone();
+                        steps.push(format!("{}→{}", digraph, replacement));
+                        if candidate != lower && !variants.iter().any(|v| v.text == candidate) {
+                            variants.push(InputVariant {
+                                text: candidate.clone(),
+                                transformation: steps.join(", "),
+                            });
+                        }
+                        next.push((candidate, steps, pos + replacement.len()));
+                    }
+                    search_from = pos + digraph.len();
+                }
+            }
+        }
+        frontier = next;
+    }
+
+    variants.truncate(MAX_CANDIDATES);
+    variants
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(variants: &[InputVariant]) -> Vec<&str> {
+        variants.iter().map(|v| v.text.as_str()).collect()
+    }
+
+    #[test]
+    fn german_digraphs_become_umlauts_and_eszett() {
+        let variants = digraph_variants("de", "Baer");
+        assert_eq!(variants, vec![InputVariant { text: "bär".to_string(), transformation: "ae→ä".to_string() }]);
+
+        assert_eq!(texts(&digraph_variants("de", "strasse")), vec!["straße"]);
+        let both = digraph_variants("de", "gruesse");
+        assert_eq!(texts(&both), vec!["grüsse", "grueße", "grüße"]);
+        assert_eq!(both[2].transformation, "ue→ü, ss→ß");
+    }
+
+    #[test]
+    fn sanskrit_sh_may_be_either_sibilant() {
+        assert_eq!(texts(&digraph_variants("sa", "shiva")), vec!["śiva", "ṣiva"]);
+        assert_eq!(texts(&digraph_variants("sa", "kaama")), vec!["kāma"]);
+    }
+
+    #[test]
+    fn variants_are_capped() {
+        // Six "sh" with two readings each would be 728 spellings
+        let variants = digraph_variants("sa", "shshshshshsh");
+        assert_eq!(variants.len(), MAX_CANDIDATES);
+        // Single replacements come before combinations
+        assert!(variants[..6].iter().all(|v| !v.transformation.contains(',')));
+    }
+
+    #[test]
+    fn the_literal_query_is_never_a_variant() {
+        for (language, query) in [("de", "Haus"), ("de", "Bär"), ("sa", "śiva"), ("fr", "soeur"), ("xx", "aa")] {
+            assert!(digraph_variants(language, query).is_empty(), "{} {}", language, query);
+        }
+        for variants in [digraph_variants("de", "Baer"), digraph_variants("sa", "shaa")] {
+            assert!(variants.iter().all(|v| v.text != "baer" && v.text != "shaa"));
+        }
+    }
+
+    #[test]
+    fn custom_characters_follow_the_builtin_ones_once() {
+        let custom = ["ß", "ẞ", "", "ẞ", "€"].map(str::to_string);
+        assert_eq!(
+            special_characters("de", &custom),
+            vec!["ä", "ö", "ü", "ß", "Ä", "Ö", "Ü", "ẞ", "€"]
+        );
+        assert_eq!(special_characters("xx", &custom), vec!["ß", "ẞ", "€"]);
+    }
+}
@@ -0,0 +1,196 @@
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::RwLock;
+use crate::db;
+
+/// Languages Lumina knows by name, keyed by ISO code. Names match the
+/// dictionary titles used by kaikki.org, so each of these has a downloadable
+/// Wiktionary extract.
+pub const KNOWN_LANGUAGES: &[(&str, &str)] = &[
+    ("ar", "Arabic"),
+    ("da", "Danish"),
+    ("de", "German"),
+    ("el", "Greek"),
+    ("en", "English"),
+    ("es", "Spanish"),
+    ("fi", "Finnish"),
+    ("fr", "French"),
+    ("he", "Hebrew"),
+    ("hi", "Hindi"),
+    ("it", "Italian"),
+    ("ja", "Japanese"),
+    ("ko", "Korean"),
+    ("la", "Latin"),
+    ("nl", "Dutch"),
+    ("no", "Norwegian"),
+    ("pl", "Polish"),
+    ("pt", "Portuguese"),
+    ("ru", "Russian"),
+    ("sa", "Sanskrit"),
+    ("sv", "Swedish"),
+    ("th", "Thai"),
+    ("tr", "Turkish"),
+    ("vi", "Vietnamese"),
+    ("zh", "Chinese"),
+];
+
+pub fn name_for_code(code: &str) -> Option<&'static str> {
+    KNOWN_LANGUAGES
+        .iter()
+        .find(|(c, _)| c.eq_ignore_ascii_case(code))
+        .map(|(_, name)| *name)
+}
+
+pub fn has_download_pack(code: &str) -> bool {
+    name_for_code(code).is_some()
+}
+
+/// Registry of the languages dictionaries are installed for, in `dict/`.
+/// Language directories are named by code or by the registered name.
+const REGISTRY_FILE: &str = "languages.json";
+
+/// What the registry starts with, the languages recognised before it existed
+const BUILT_IN_LANGUAGES: &[(&str, &str)] = &[
+    ("de", "german"),
+    ("sa", "sanskrit"),
+    ("en", "english"),
+    ("fr", "french"),
+    ("es", "spanish"),
+    ("it", "italian"),
+    ("pt", "portuguese"),
+    ("ru", "russian"),
+    ("zh", "chinese"),
+    ("ja", "japanese"),
+    ("ko", "korean"),
+    ("ar", "arabic"),
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegisteredLanguage {
+    pub code: String,
+    pub name: String,
+}
+
+static REGISTRY: Lazy<RwLock<Option<Vec<RegisteredLanguage>>>> = Lazy::new(|| RwLock::new(None));
+
+fn registry_path() -> PathBuf {
+    db::get_dict_dir().join(REGISTRY_FILE)
+}
+
+fn built_in() -> Vec<RegisteredLanguage> {
+    BUILT_IN_LANGUAGES
+        .iter()
+        .map(|(code, name)| RegisteredLanguage {
+            code: code.to_string(),
+            name: name.to_string(),
+        })
+        .collect()
+}
+
+fn load() -> Vec<RegisteredLanguage> {
+    let path = registry_path();
+    match fs::read_to_string(&path) {
+        Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
+            eprintln!("[LANG] Ignoring unreadable {:?}: {}", path, e);
+            built_in()
+        }),
+        Err(_) => {
+            let languages = built_in();
+            // Seed the file, but don't create `dict/` just to hold it
+            let dict_exists = path.parent().is_some_and(|dir| dir.exists());
+            if dict_exists {
+                if let Err(e) = save(&languages) {
+                    eprintln!("[LANG] Could not create {:?}: {}", path, e);
+                }
+            }
+            languages
+        }
+    }
+}
+
+fn save(languages: &[RegisteredLanguage]) -> Result<(), String> {
+    let path = registry_path();
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create dictionary directory: {}", e))?;
+    }
+    let content = serde_json::to_string_pretty(languages).map_err(|e| e.to_string())?;
+    fs::write(&path, content).map_err(|e| format!("Failed to write language registry: {}", e))
+}
+
+/// Every registered language, loaded from disk on first use
+pub fn registered() -> Vec<RegisteredLanguage> {
+    if let Some(languages) = REGISTRY.read().unwrap().as_ref() {
+        return languages.clone();
+    }
+    let mut registry = REGISTRY.write().unwrap();
+    registry.get_or_insert_with(load).clone()
+}
+
+/// Code and name for a language directory; unregistered directories use
+/// their own name for both
+pub fn resolve_dir(dir_name: &str) -> (String, String) {
+    registered()
+        .into_iter()
+        .find(|l| l.code.eq_ignore_ascii_case(dir_name) || l.name.eq_ignore_ascii_case(dir_name))
+        .map(|l| (l.code, l.name))
+        .unwrap_or_else(|| (dir_name.to_string(), dir_name.to_string()))
+}
+
+/// Whether a directory holds the dictionaries for `code`
+pub fn dir_matches(dir_name: &str, code: &str) -> bool {
+    dir_name.eq_ignore_ascii_case(code)
+        || registered()
+            .iter()
+            .any(|l| l.code == code && l.name.eq_ignore_ascii_case(dir_name))
+}
+
+fn update(f: impl FnOnce(&mut Vec<RegisteredLanguage>)) -> Result<Vec<RegisteredLanguage>, String> {
+    let mut registry = REGISTRY.write().unwrap();
+    let languages = registry.get_or_insert_with(load);
+    let mut updated = languages.clone();
+    f(&mut updated);
+    save(&updated)?;
+    *languages = updated.clone();
+    Ok(updated)
+}
+
+/// Add `code`, or rename it when already registered
+pub fn register(code: &str, name: &str) -> Result<Vec<RegisteredLanguage>, String> {
+    let (code, name) = (code.trim(), name.trim());
+    if code.is_empty() || code.len() > 12 || !code.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+        return Err(format!("Invalid language code '{}'", code));
+    }
+    if name.is_empty() {
+        return Err("Language name is required".to_string());
+    }
+    let languages = update(|languages| match languages.iter_mut().find(|l| l.code == code) {
+        Some(language) => language.name = name.to_string(),
+        None => languages.push(RegisteredLanguage {
+            code: code.to_string(),
+            name: name.to_string(),
+        }),
+    })?;
+    db::invalidate_connections(Some(code));
+    Ok(languages)
+}
+
+/// Register `code` under `name` unless it is already known, so an upload
+/// doesn't rename a language the user set up
+pub fn ensure_registered(code: &str, name: &str) -> Result<(), String> {
+    if registered().iter().any(|l| l.code == code) {
+        return Ok(());
+    }
+    register(code, name).map(|_| ())
+}
+
+/// Remove `code` from the registry; its dictionaries stay on disk
+pub fn unregister(code: &str) -> Result<Vec<RegisteredLanguage>, String> {
+    if !registered().iter().any(|l| l.code == code) {
+        return Err(format!("Language '{}' is not registered", code));
+    }
+    let languages = update(|languages| languages.retain(|l| l.code != code))?;
+    db::invalidate_connections(Some(code));
+    Ok(languages)
+}
@@ -0,0 +1,233 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Error prefix the UI matches on to show "input too large" messages
+pub const INPUT_TOO_LARGE: &str = "input_too_large";
+
+/// Caps on what a single command accepts, checked before anything reaches
+/// SQL or Python. Lengths are in characters.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Limits {
+    /// Dictionary lookups and single-word Sanskrit commands
+    pub max_query_length: usize,
+    /// Suggestion prefixes; longer prefixes can't match a headword anyway
+    pub max_prefix_length: usize,
+    /// Words per batch lookup
+    pub max_batch_size: usize,
+    /// Text sent to process_text and transliteration
+    pub max_text_length: usize,
+    /// Items per batch split or transliteration
+    pub max_sanskrit_batch_size: usize,
+    /// Dictionary files imported through upload (bytes)
+    pub max_import_file_size: u64,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            max_query_length: 200,
+            max_prefix_length: 64,
+            max_batch_size: 2_000,
+            max_text_length: 20_000,
+            max_sanskrit_batch_size: 500,
+            max_import_file_size: 512 * 1024 * 1024,
+        }
+    }
+}
+
+/// An input exceeded its configured limit
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InputTooLarge {
+    pub input: String,
+    pub limit: u64,
+    pub actual: u64,
+}
+
+impl fmt::Display for InputTooLarge {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {} is {} but the limit is {}",
+            INPUT_TOO_LARGE, self.input, self.actual, self.limit
+        )
+    }
+}
+
+impl From<InputTooLarge> for String {
+    fn from(e: InputTooLarge) -> Self {
+        e.to_string()
+    }
+}
+
+pub fn check(input: &str, actual: usize, limit: usize) -> Result<(), InputTooLarge> {
+    check_u64(input, actual as u64, limit as u64)
+}
+
+pub fn check_u64(input: &str, actual: u64, limit: u64) -> Result<(), InputTooLarge> {
+    if actual > limit {
+        Err(InputTooLarge {
+            input: input.to_string(),
+            limit,
+            actual,
+        })
+    } else {
+        Ok(())
+    }
+}
+
+/// Length check for text, counted in characters
+pub fn check_text(input: &str, text: &str, limit: usize) -> Result<(), InputTooLarge> {
+    check(input, text.chars().count(), limit)
+}
+
+/// A batch of lookups: the number of words, then each word's length
+pub fn check_batch(words: &[String], limits: &Limits) -> Result<(), InputTooLarge> {
+    check("batch", words.len(), limits.max_batch_size)?;
+    if let Some(word) = words.iter().find(|w| w.chars().count() > limits.max_query_length) {
+        check_text("batch word", word, limits.max_query_length)?;
+    }
+    Ok(())
+}
+
+/// How a text was shortened to fit
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Truncation {
+    pub limit: usize,
+    pub original_length: usize,
+    pub processed_length: usize,
+    pub processed_tokens: usize,
+}
+
+/// The longest prefix of `text` made of whole whitespace-separated tokens
+/// that fits in `limit` characters. A first token longer than the limit
+/// is cut at the limit.
+pub fn truncate_tokens(text: &str, limit: usize) -> (String, Option<Truncation>) {
+    let original_length = text.chars().count();
+    if original_length <= limit {
+        return (text.to_string(), None);
+    }
+
+    // Byte offset of the first character past the limit
+    let cut = text.char_indices().nth(limit).map(|(i, _)| i).unwrap_or(text.len());
+    let at_boundary = text[cut..].starts_with(char::is_whitespace);
+    let processed = if at_boundary {
+        text[..cut].trim_end()
+    } else {
+        match text[..cut].rfind(char::is_whitespace) {
+            Some(pos) => text[..pos].trim_end(),
+            None => &text[..cut],
+        }
+    };
+
+    let truncation = Truncation {
+        limit,
+        original_length,
+        processed_length: processed.chars().count(),
+        processed_tokens: processed.split_whitespace().count(),
+    };
+    (processed.to_string(), Some(truncation))
+}
+
+/// `text` if it fits in `limit`; otherwise shortened with `truncate_tokens`
+/// when the caller accepts that, or an error
+pub fn fit_text(text: String, limit: usize, truncate: bool) -> Result<(String, Option<Truncation>), InputTooLarge> {
+    if truncate {
+        Ok(truncate_tokens(&text, limit))
+    } else {
+        check_text("text", &text, limit)?;
+        Ok((text, None))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(chars: usize) -> String {
+        // Multi-byte on purpose: limits count characters, not bytes
+        "ā".repeat(chars)
+    }
+
+    fn words(n: usize, len: usize) -> Vec<String> {
+        vec!["w".repeat(len); n]
+    }
+
+    #[test]
+    fn text_limits_allow_the_limit_and_reject_one_more() {
+        let limits = Limits::default();
+        for (input, limit) in [
+            ("query", limits.max_query_length),
+            ("prefix", limits.max_prefix_length),
+            ("text", limits.max_text_length),
+        ] {
+            assert!(check_text(input, &text(limit), limit).is_ok(), "{} at its limit", input);
+            let err = check_text(input, &text(limit + 1), limit).unwrap_err();
+            assert_eq!((err.limit, err.actual), (limit as u64, limit as u64 + 1));
+            assert_eq!(
+                String::from(err),
+                format!("input_too_large: {} is {} but the limit is {}", input, limit + 1, limit)
+            );
+        }
+    }
+
+    #[test]
+    fn batch_limits_count_words_then_word_length() {
+        let limits = Limits::default();
+        let (max, len) = (limits.max_batch_size, limits.max_query_length);
+        assert!(check_batch(&words(max, len), &limits).is_ok());
+
+        let err = check_batch(&words(max + 1, len), &limits).unwrap_err();
+        assert_eq!((err.input.as_str(), err.actual), ("batch", max as u64 + 1));
+
+        let mut long_word = words(3, 5);
+        long_word[1] = text(len + 1);
+        let err = check_batch(&long_word, &limits).unwrap_err();
+        assert_eq!((err.input.as_str(), err.actual), ("batch word", len as u64 + 1));
+
+        let sanskrit = limits.max_sanskrit_batch_size;
+        assert!(check("batch", sanskrit, sanskrit).is_ok());
+        assert!(check("batch", sanskrit + 1, sanskrit).is_err());
+    }
+
+    #[test]
+    fn file_size_limit_is_in_bytes() {
+        let limit = Limits::default().max_import_file_size;
+        assert!(check_u64("dictionary file", limit, limit).is_ok());
+        assert_eq!(check_u64("dictionary file", limit + 1, limit).unwrap_err().actual, limit + 1);
+    }
+
+    #[test]
+    fn truncation_keeps_whole_tokens() {
+        // At the limit nothing is cut
+        let (kept, truncation) = fit_text("dharma kṣetre kuru".to_string(), 18, true).unwrap();
+        assert_eq!((kept.as_str(), truncation.is_none()), ("dharma kṣetre kuru", true));
+
+        // One over: the partial last token is dropped, not split
+        let (kept, truncation) = fit_text("dharma kṣetre kuru".to_string(), 17, true).unwrap();
+        assert_eq!(kept, "dharma kṣetre");
+        let truncation = truncation.unwrap();
+        assert_eq!(
+            (truncation.limit, truncation.original_length, truncation.processed_length, truncation.processed_tokens),
+            (17, 18, 13, 2)
+        );
+
+        // A cut that lands on whitespace keeps everything before it
+        let (kept, _) = fit_text("dharma kṣetre kuru".to_string(), 13, true).unwrap();
+        assert_eq!(kept, "dharma kṣetre");
+
+        // A single over-long token is cut at the limit
+        let (kept, truncation) = truncate_tokens(&text(10), 4);
+        assert_eq!((kept, truncation.unwrap().processed_tokens), (text(4), 1));
+    }
+
+    #[test]
+    fn without_truncate_over_long_text_is_an_error() {
+        let limit = Limits::default().max_text_length;
+        assert!(fit_text(text(limit), limit, false).unwrap().1.is_none());
+        let err = fit_text(text(limit + 1), limit, false).unwrap_err();
+        assert_eq!((err.input.as_str(), err.actual), ("text", limit as u64 + 1));
+    }
+}
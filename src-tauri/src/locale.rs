@@ -0,0 +1,149 @@
+use once_cell::sync::OnceCell;
+
+static DETECTED: OnceCell<Vec<String>> = OnceCell::new();
+
+/// Reduce a locale string to its base language: "en_US.UTF-8", "en-GB" and
+/// "en" all become "en". Returns None for "C"/"POSIX" and unparseable input.
+pub fn normalize_locale(locale: &str) -> Option<String> {
+    let base = locale
+        .trim()
+        .split(['.', '@'])
+        .next()
+        .unwrap_or("")
+        .split(['_', '-'])
+        .next()
+        .unwrap_or("")
+        .to_lowercase();
+
+    if base.is_empty() || base == "c" || base == "posix" {
+        return None;
+    }
+    if !(2..=3).contains(&base.len()) || !base.chars().all(|c| c.is_ascii_alphabetic()) {
+        return None;
+    }
+    Some(base)
+}
+
+/// Rank a list of raw locale strings into distinct base languages, keeping
+/// the first occurrence's position.
+pub fn rank_languages<S: AsRef<str>>(locales: &[S]) -> Vec<String> {
+    let mut ranked: Vec<String> = Vec::new();
+    for locale in locales {
+        if let Some(code) = normalize_locale(locale.as_ref()) {
+            if !ranked.contains(&code) {
+                ranked.push(code);
+            }
+        }
+    }
+    ranked
+}
+
+#[cfg(windows)]
+fn platform_locales() -> Vec<String> {
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn GetUserPreferredUILanguages(
+            flags: u32,
+            num_languages: *mut u32,
+            buffer: *mut u16,
+            buffer_len: *mut u32,
+        ) -> i32;
+    }
+    const MUI_LANGUAGE_NAME: u32 = 0x8;
+
+    let mut count: u32 = 0;
+    let mut len: u32 = 0;
+    // SAFETY: first call only queries the required buffer length
+    let ok = unsafe {
+        GetUserPreferredUILanguages(MUI_LANGUAGE_NAME, &mut count, std::ptr::null_mut(), &mut len)
+    };
+    if ok == 0 || len == 0 {
+        return Vec::new();
+    }
+
+    let mut buffer = vec![0u16; len as usize];
+    // SAFETY: buffer holds exactly `len` UTF-16 units as requested by the API
+    let ok = unsafe {
+        GetUserPreferredUILanguages(MUI_LANGUAGE_NAME, &mut count, buffer.as_mut_ptr(), &mut len)
+    };
+    if ok == 0 {
+        return Vec::new();
+    }
+
+    // Double-NUL-terminated list of NUL-separated names
+    buffer
+        .split(|c| *c == 0)
+        .filter(|s| !s.is_empty())
+        .map(String::from_utf16_lossy)
+        .collect()
+}
+
+#[cfg(not(windows))]
+fn platform_locales() -> Vec<String> {
+    let mut locales = Vec::new();
+    // LANGUAGE is a colon-separated preference list and outranks the rest
+    if let Ok(list) = std::env::var("LANGUAGE") {
+        locales.extend(list.split(':').filter(|s| !s.is_empty()).map(|s| s.to_string()));
+    }
+    for var in ["LC_ALL", "LC_MESSAGES", "LANG"] {
+        if let Ok(value) = std::env::var(var) {
+            if !value.is_empty() {
+                locales.push(value);
+            }
+        }
+    }
+    locales
+}
+
+/// Base language codes of the OS locale preferences, most preferred first.
+/// Detected once per process.
+pub fn detected_languages() -> Vec<String> {
+    DETECTED
+        .get_or_init(|| {
+            let raw = platform_locales();
+            eprintln!("[LOCALE] System locales: {:?}", raw);
+            rank_languages(&raw)
+        })
+        .clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn regional_variants_share_the_base_language() {
+        for locale in ["en-US", "en-GB", "en_US.UTF-8", "en_GB.ISO-8859-1", "EN", "en"] {
+            assert_eq!(normalize_locale(locale).as_deref(), Some("en"), "{}", locale);
+        }
+        assert_eq!(normalize_locale("de_DE@euro").as_deref(), Some("de"));
+        assert_eq!(normalize_locale("zh-Hans-CN").as_deref(), Some("zh"));
+        assert_eq!(normalize_locale("fil_PH").as_deref(), Some("fil"));
+    }
+
+    #[test]
+    fn placeholder_locales_are_ignored() {
+        for locale in ["C", "C.UTF-8", "POSIX", "", "  ", "english", "e1_US"] {
+            assert_eq!(normalize_locale(locale), None, "{}", locale);
+        }
+    }
+
+    #[test]
+    fn posix_preferences_rank_without_duplicates() {
+        // LANGUAGE, then LC_ALL, LC_MESSAGES and LANG as platform_locales reads them
+        let raw = ["en_GB", "en_US", "de_DE", "C.UTF-8", "en_US.UTF-8", "fr_FR.UTF-8"];
+        assert_eq!(rank_languages(&raw), vec!["en", "de", "fr"]);
+    }
+
+    #[test]
+    fn windows_preferences_rank_in_order() {
+        let raw = ["ru-RU", "en-US", "uk-UA", "en-GB"];
+        assert_eq!(rank_languages(&raw), vec!["ru", "en", "uk"]);
+    }
+
+    #[test]
+    fn nothing_usable_ranks_empty() {
+        assert!(rank_languages(&["C", "POSIX"]).is_empty());
+        assert!(rank_languages::<&str>(&[]).is_empty());
+    }
+}
@@ -0,0 +1,256 @@
+use chrono::SecondsFormat;
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::Mutex;
+
+/// A log file is rotated once it reaches this size
+const MAX_LOG_BYTES: u64 = 5 * 1024 * 1024;
+/// Rotated files kept next to the live one (lumina.log.1 … .3)
+const ROTATED_FILES: usize = 3;
+/// Lines the diagnostics panel may ask for at once
+pub const MAX_TAIL_LINES: usize = 5000;
+
+/// Entries below this level are dropped
+static THRESHOLD: AtomicU8 = AtomicU8::new(Level::Info as u8);
+/// Serialises appends so rotation never races a write
+static WRITE_LOCK: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+impl Level {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "debug" => Some(Level::Debug),
+            "info" => Some(Level::Info),
+            "warn" | "warning" => Some(Level::Warn),
+            "error" => Some(Level::Error),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Level::Debug => "debug",
+            Level::Info => "info",
+            Level::Warn => "warn",
+            Level::Error => "error",
+        }
+    }
+
+    fn from_u8(value: u8) -> Self {
+        match value {
+            0 => Level::Debug,
+            1 => Level::Info,
+            2 => Level::Warn,
+            _ => Level::Error,
+        }
+    }
+}
+
+pub fn set_level(level: Level) {
+    THRESHOLD.store(level as u8, Ordering::Relaxed);
+}
+
+pub fn level() -> Level {
+    Level::from_u8(THRESHOLD.load(Ordering::Relaxed))
+}
+
+/// logs/ next to the executable
+pub fn log_dir() -> PathBuf {
+    let dir = std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(|dir| dir.join("logs")))
+        .unwrap_or_else(|| PathBuf::from("logs"));
+    if !dir.exists() {
+        let _ = fs::create_dir_all(&dir);
+    }
+    dir
+}
+
+pub fn log_path() -> PathBuf {
+    log_dir().join("lumina.log")
+}
+
+/// Output of the Python services and dependency installs
+pub fn service_log_path() -> PathBuf {
+    log_dir().join("services.log")
+}
+
+fn timestamp() -> String {
+    chrono::Local::now().to_rfc3339_opts(SecondsFormat::Millis, false)
+}
+
+/// Write `msg` to lumina.log (and stdout) if `level` passes the threshold
+pub fn log(level: Level, msg: &str) {
+    if level < self::level() {
+        return;
+    }
+    let label = level.as_str().to_ascii_uppercase();
+    append(&log_path(), &format!("[{}] [{}] {}", timestamp(), label, msg));
+    println!("{}", msg);
+}
+
+pub fn warn(msg: &str) {
+    log(Level::Warn, msg);
+}
+
+pub fn error(msg: &str) {
+    log(Level::Error, msg);
+}
+
+/// A line of service output; services.log has no levels
+pub fn service(msg: &str) {
+    append(&service_log_path(), &format!("[{}] {}", timestamp(), msg));
+}
+
+fn append(path: &Path, line: &str) {
+    let _guard = WRITE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    if fs::metadata(path).map(|m| m.len() >= MAX_LOG_BYTES).unwrap_or(false) {
+        rotate(path);
+    }
+    if let Ok(mut file) = OpenOptions::new().create(true).append(true).open(path) {
+        let _ = writeln!(file, "{}", line);
+    }
+}
+
+/// lumina.log → lumina.log.1 → … → lumina.log.3, the oldest dropped
+fn rotate(path: &Path) {
+    let _ = fs::remove_file(rotated_path(path, ROTATED_FILES));
+    for n in (1..ROTATED_FILES).rev() {
+        let _ = fs::rename(rotated_path(path, n), rotated_path(path, n + 1));
+    }
+    let _ = fs::rename(path, rotated_path(path, 1));
+}
+
+fn rotated_path(path: &Path, n: usize) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(format!(".{}", n));
+    PathBuf::from(name)
+}
+
+/// One entry of lumina.log
+#[derive(Debug, Clone, Serialize)]
+pub struct LogEntry {
+    pub timestamp: String,
+    pub level: String,
+    pub message: String,
+}
+
+/// `[timestamp] [LEVEL] message`; lines from before levels existed are info
+fn parse_line(line: &str) -> Option<LogEntry> {
+    let rest = line.strip_prefix('[')?;
+    let (timestamp, rest) = rest.split_once("] ")?;
+    let (level, message) = rest
+        .strip_prefix('[')
+        .and_then(|r| r.split_once("] "))
+        .and_then(|(level, message)| Level::parse(level).map(|level| (level, message)))
+        .unwrap_or((Level::Info, rest));
+    Some(LogEntry {
+        timestamp: timestamp.to_string(),
+        level: level.as_str().to_string(),
+        message: message.to_string(),
+    })
+}
+
+/// The last `lines` entries at or above `min_level`, oldest first, reaching
+/// into the rotated files when the live one is too short
+pub fn recent(lines: usize, min_level: Option<Level>) -> Result<Vec<LogEntry>, String> {
+    let lines = lines.min(MAX_TAIL_LINES);
+    let path = log_path();
+    let mut entries: Vec<LogEntry> = Vec::new();
+    for n in 0..=ROTATED_FILES {
+        if entries.len() >= lines {
+            break;
+        }
+        let file = if n == 0 { path.clone() } else { rotated_path(&path, n) };
+        if !file.exists() {
+            break;
+        }
+        // Filtering can discard any number of lines, so read the whole file
+        // (at most MAX_LOG_BYTES) rather than guess how far back to go.
+        // Unfiltered, multi-line messages make `lines` a lower bound.
+        let wanted = if min_level.is_some() { usize::MAX } else { lines };
+        let raw = tail_lines(&file, wanted).map_err(|e| format!("Failed to read {:?}: {}", file, e))?;
+        let mut parsed: Vec<LogEntry> = Vec::new();
+        for line in raw {
+            match parse_line(&line) {
+                Some(entry) => parsed.push(entry),
+                // Continuation of a multi-line message
+                None => match parsed.last_mut() {
+                    Some(last) => {
+                        last.message.push('\n');
+                        last.message.push_str(&line);
+                    }
+                    None => continue,
+                },
+            }
+        }
+        parsed.retain(|entry| {
+            min_level.is_none_or(|min| Level::parse(&entry.level).is_some_and(|level| level >= min))
+        });
+        parsed.append(&mut entries);
+        entries = parsed;
+    }
+    let skip = entries.len().saturating_sub(lines);
+    Ok(entries.split_off(skip))
+}
+
+/// Read backwards from the end in blocks until `count` lines are found, so
+/// a large log isn't read whole
+pub fn tail_lines(path: &Path, count: usize) -> std::io::Result<Vec<String>> {
+    use std::io::{Read, Seek, SeekFrom};
+    const BLOCK: u64 = 64 * 1024;
+    let mut file = fs::File::open(path)?;
+    let mut end = file.metadata()?.len();
+    let mut buffer: Vec<u8> = Vec::new();
+    // One extra newline: the file ends with one, and the first line found
+    // may be partial
+    while end > 0 && buffer.iter().filter(|&&b| b == b'\n').count() <= count {
+        let start = end.saturating_sub(BLOCK);
+        let mut block = vec![0; (end - start) as usize];
+        file.seek(SeekFrom::Start(start))?;
+        file.read_exact(&mut block)?;
+        block.extend_from_slice(&buffer);
+        buffer = block;
+        end = start;
+    }
+    let text = String::from_utf8_lossy(&buffer);
+    let mut lines: Vec<String> = text.lines().map(str::to_string).collect();
+    if end > 0 && !lines.is_empty() {
+        lines.remove(0);
+    }
+    let skip = lines.len().saturating_sub(count);
+    Ok(lines.split_off(skip))
+}
+
+/// Show the logs folder in the system file manager
+pub fn open_dir() -> Result<(), String> {
+    let dir = log_dir();
+    reveal(&dir).map_err(|e| format!("Failed to open {:?}: {}", dir, e))
+}
+
+#[cfg(target_os = "windows")]
+fn reveal(dir: &Path) -> std::io::Result<()> {
+    // explorer exits with 1 even when the window opened, so only spawn it
+    std::process::Command::new("explorer").arg(dir).spawn().map(|_| ())
+}
+
+#[cfg(target_os = "macos")]
+fn reveal(dir: &Path) -> std::io::Result<()> {
+    std::process::Command::new("open").arg(dir).status().map(|_| ())
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn reveal(dir: &Path) -> std::io::Result<()> {
+    std::process::Command::new("xdg-open").arg(dir).status().map(|_| ())
+}
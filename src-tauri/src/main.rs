@@ -1,172 +1,127 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
-use std::fs::{self, OpenOptions};
-use std::io::Write;
+use serde::Serialize;
 use std::path::PathBuf;
-use std::process::{Command, Stdio};
-use std::sync::{Arc, Mutex, atomic::{AtomicBool, Ordering}};
-use std::thread;
-use std::time::Duration;
-use tauri::{Manager, Emitter, menu::{Menu, MenuItem}, tray::{TrayIconBuilder, MouseButton, MouseButtonState, TrayIconEvent}};
-use tauri_plugin_global_shortcut::{Code, GlobalShortcutExt, Modifiers, Shortcut, ShortcutState};
+use std::sync::{Arc, Mutex, atomic::AtomicBool};
+use std::time::{Duration, Instant};
+use tauri::{Manager, Emitter, menu::{CheckMenuItem, IsMenuItem, Menu, MenuItem, Submenu}, tray::{TrayIconBuilder, MouseButton, MouseButtonState, TrayIconEvent}};
 use tauri_plugin_clipboard_manager::ClipboardExt;
 
+mod actions;
+mod analysis_cache;
+mod anki;
+mod audio_cache;
+mod autostart;
+mod backend;
+mod capabilities;
+mod clipboard;
 mod floating;
+mod import_jobs;
 mod db;
+mod dict_watcher;
 mod commands;
-
+mod entry_actions;
+mod entry_windows;
+mod keyboard;
+mod languages;
+mod limits;
+mod locale;
+mod logging;
+mod normalize;
+mod offline_queue;
+mod online;
+mod paths;
+mod prefetch;
+mod python;
+mod query_metrics;
+mod recovery;
+mod review_log;
+mod sanskrit_cache;
+mod sanskrit_worker;
+mod script;
+mod search_history;
+mod selection;
+mod services;
+mod settings;
+mod shortcut;
+mod startup;
+mod translit;
+mod truetype;
+mod usage;
+
+use actions::{ActionRegistry, AppAction};
+use entry_windows::EntryWindows;
 use floating::FloatingWindowManager;
-use commands::{dictionary::*, sanskrit::*, vocabulary::*};
+use sanskrit_worker::SanskritWorker;
+use script::{LookupTarget, NewQueryPayload};
+use settings::SettingsState;
+use startup::{StartupReadyEvent, StartupTimings, STARTUP};
+use commands::{actions::*, analysis::*, dictionary::*, flashcards::*, history::*, maintenance::*, queue::*, sanskrit::*, texts::*, usage::*, vocabulary::*};
+use offline_queue::OfflineQueue;
 
 struct AppState {
     floating_manager: Mutex<Option<FloatingWindowManager>>,
-    clipboard_monitoring: Mutex<Arc<AtomicBool>>,
-    vocabulary_state: VocabularyState,
+    clipboard_monitor: clipboard::ClipboardMonitor,
+    sanskrit_worker: SanskritWorker,
+    sanskrit_health: commands::sanskrit::HealthCache,
+    /// Cancel flag of the running Sanskrit package install, if any
+    python_install: Mutex<Option<Arc<AtomicBool>>>,
+    backend_services: backend::BackendServices,
 }
 
-fn get_log_path() -> PathBuf {
-    if let Ok(exe_path) = std::env::current_exe() {
-        if let Some(exe_dir) = exe_path.parent() {
-            let log_dir = exe_dir.join("logs");
-            if !log_dir.exists() {
-                let _ = fs::create_dir_all(&log_dir);
-            }
-            return log_dir.join("lumina.log");
-        }
-    }
-    PathBuf::from("lumina.log")
+/// Info-level entry in lumina.log
+fn write_log(msg: &str) {
+    logging::log(logging::Level::Info, msg);
 }
 
-fn get_service_log_path() -> PathBuf {
-    if let Ok(exe_path) = std::env::current_exe() {
-        if let Some(exe_dir) = exe_path.parent() {
-            let log_dir = exe_dir.join("logs");
-            if !log_dir.exists() {
-                let _ = fs::create_dir_all(&log_dir);
-            }
-            return log_dir.join("services.log");
-        }
-    }
-    PathBuf::from("services.log")
+/// Output of the Python services and dependency installs
+fn write_service_log(msg: &str) {
+    logging::service(msg);
 }
 
-fn write_log(msg: &str) {
-    let log_path = get_log_path();
-    if let Ok(mut file) = OpenOptions::new().create(true).append(true).open(&log_path) {
-        let timestamp = chrono_lite_timestamp();
-        let _ = writeln!(file, "[{}] {}", timestamp, msg);
-    }
-    println!("{}", msg);
-}
-
-fn chrono_lite_timestamp() -> String {
-    use std::time::{SystemTime, UNIX_EPOCH};
-    let duration = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap_or_default();
-    let secs = duration.as_secs();
-    let hours = (secs / 3600) % 24;
-    let mins = (secs / 60) % 60;
-    let secs = secs % 60;
-    format!("{:02}:{:02}:{:02}", hours, mins, secs)
-}
-
-fn find_base_path() -> PathBuf {
-    if let Ok(exe_path) = std::env::current_exe() {
-        if let Some(exe_dir) = exe_path.parent() {
-            let scripts_path = exe_dir.join("scripts");
-            if scripts_path.exists() {
-                return exe_dir.to_path_buf();
-            }
-
-            let up_scripts_path = exe_dir.join("_up_").join("scripts");
-            if up_scripts_path.exists() {
-                return exe_dir.join("_up_");
-            }
-        }
-    }
-
-    let current_dir = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
-
-    // In dev mode CWD is src-tauri/, check parent (project root)
-    if current_dir.join("scripts").exists() {
-        return current_dir;
-    }
-    let parent = current_dir.parent().unwrap_or(&current_dir).to_path_buf();
-    if parent.join("scripts").exists() {
-        write_log(&format!("使用项目根目录: {:?}", parent));
-        return parent;
-    }
-
-    write_log(&format!("回退到当前目录: {:?}", current_dir));
-    current_dir
+#[tauri::command]
+fn start_backend_services(app: tauri::AppHandle) -> Result<String, String> {
+    start_backend_services_at(&app, &paths::find_base_path())
 }
 
+/// Stop whatever is running and start the services again, e.g. after
+/// auto-restart gave up
 #[tauri::command]
-fn start_backend_services() -> Result<String, String> {
-    let base_path = find_base_path();
+fn restart_backend_services(app: tauri::AppHandle) -> Result<String, String> {
+    app.state::<AppState>().backend_services.stop_all();
+    start_backend_services_at(&app, &paths::find_base_path())
+}
+
+/// Start the Python services; ones from an earlier call are stopped first
+/// so they don't keep holding the ports
+fn start_backend_services_at(app: &tauri::AppHandle, base_path: &std::path::Path) -> Result<String, String> {
+    let services = &app.state::<AppState>().backend_services;
     let scripts_dir = base_path.join("scripts");
 
+    if services.any_running() {
+        write_log("后端服务已在运行，先停止旧实例");
+        services.stop_all();
+    }
+
     write_log("========== 后端服务启动 ==========");
     write_log(&format!("基础路径：{:?}", base_path));
 
-    let python_cmd = if Command::new("uv").arg("--version").output().is_ok() {
-        write_log("✓ uv detected");
-        "uv"
-    } else if Command::new("python").arg("--version").output().map(|o| o.status.success()).unwrap_or(false) {
-        write_log("✓ python detected");
-        "python"
-    } else if Command::new("python3").arg("--version").output().map(|o| o.status.success()).unwrap_or(false) {
-        write_log("✓ python3 detected");
-        "python3"
-    } else {
-        write_log("✗ No Python interpreter found");
-        return Err("Python not found".to_string());
-    };
+    STARTUP.phase("python_detection", true, python::resolve)?;
 
     let python_services = [
-        ("enhanced_sanskrit_api.py", "Sanskrit API (3008)"),
-        ("dictionary_download_api.py", "Dictionary API (3011)"),
-        ("nagisa_api.py", "Nagisa Tokenizer (3010)"),
+        ("enhanced_sanskrit_api.py", "Sanskrit API (3008)", "http://127.0.0.1:3008/health"),
+        ("dictionary_download_api.py", "Dictionary API (3011)", "http://127.0.0.1:3011/health"),
+        ("nagisa_api.py", "Nagisa Tokenizer (3010)", "http://127.0.0.1:3010/api/health"),
     ];
 
-    for (script_name, label) in &python_services {
-        let script_path = scripts_dir.join(script_name);
-        if script_path.exists() {
-            let mut cmd = Command::new(python_cmd);
-            if python_cmd == "uv" {
-                cmd.arg("run").arg("python");
-            }
-            let spawn_result = cmd
-                .arg(&script_path)
-                .current_dir(&scripts_dir)
-                .stdout(Stdio::piped())
-                .stderr(Stdio::piped())
-                .spawn();
-
-            match spawn_result {
-                Ok(child) => {
-                    write_log(&format!("✓ {} started (PID: {})", label, child.id()));
-                    let label_owned = label.to_string();
-                    std::thread::spawn(move || {
-                        if let Ok(output) = child.wait_with_output() {
-                            let stdout = String::from_utf8_lossy(&output.stdout);
-                            let stderr = String::from_utf8_lossy(&output.stderr);
-                            for line in stdout.lines() {
-                                write_log(&format!("[{}] {}", label_owned, line));
-                            }
-                            for line in stderr.lines() {
-                                write_log(&format!("[{} err] {}", label_owned, line));
-                            }
-                        }
-                    });
-                }
-                Err(e) => {
-                    write_log(&format!("✗ Failed to start {}: {}", label, e));
-                }
-            }
-        } else {
+    for (script, label, health_url) in python_services {
+        if !scripts_dir.join(script).exists() {
             write_log(&format!("⚠ {} not found, skipping", label));
+            continue;
+        }
+        let spec = backend::ServiceSpec { label, script, health_url, scripts_dir: scripts_dir.clone() };
+        if let Err(e) = services.start(app, spec) {
+            write_log(&format!("✗ Failed to start {}: {}", label, e));
         }
     }
 
@@ -175,7 +130,9 @@ fn start_backend_services() -> Result<String, String> {
 }
 
 #[tauri::command]
-fn stop_backend_services() -> Result<String, String> {
+fn stop_backend_services(state: tauri::State<'_, AppState>) -> Result<String, String> {
+    let stopped = state.backend_services.stop_all();
+    write_log(&format!("已停止 {} 个后端服务", stopped));
     Ok("服务已停止".to_string())
 }
 
@@ -194,9 +151,108 @@ fn is_likely_word(text: &str) -> bool {
     trimmed.chars().all(|c| c.is_alphabetic())
 }
 
+/// Languages a copied run can be routed to: the Sanskrit pipeline when its
+/// script is present, then every installed dictionary. The default language
+/// goes first, so it wins over others written in the same script.
+fn lookup_targets(app: &tauri::AppHandle) -> Vec<LookupTarget> {
+    let mut targets = Vec::new();
+    if paths::script_path("sanskrit_cli.py").is_ok() {
+        targets.push(LookupTarget { language: "sa".to_string(), script: script::Script::Devanagari });
+    }
+    if let Ok(languages) = db::get_available_languages() {
+        for lang in languages.into_iter().filter(|l| l.has_local) {
+            if let Some(script) = script::script_for_language(&lang.code) {
+                targets.push(LookupTarget { language: lang.code, script });
+            }
+        }
+    }
+    if let Some(preferred) = app.state::<SettingsState>().get().default_language {
+        targets.sort_by_key(|t| t.language != preferred);
+    }
+    targets
+}
+
+/// The last `lines` lines of services.log, oldest first
+#[tauri::command]
+fn get_service_log(lines: usize) -> Result<Vec<String>, String> {
+    let lines = lines.min(logging::MAX_TAIL_LINES);
+    let path = logging::service_log_path();
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    logging::tail_lines(&path, lines).map_err(|e| format!("Failed to read {:?}: {}", path, e))
+}
+
+/// The last `lines` entries of lumina.log, oldest first; with `level`, only
+/// entries at or above it
 #[tauri::command]
-fn get_service_status() -> Result<String, String> {
-    Ok("运行中".to_string())
+fn get_recent_logs(lines: usize, level: Option<String>) -> Result<Vec<logging::LogEntry>, String> {
+    let level = level
+        .map(|l| logging::Level::parse(&l).ok_or_else(|| format!("Unknown log level: {}", l)))
+        .transpose()?;
+    logging::recent(lines, level)
+}
+
+/// Lowest level written to lumina.log: "debug", "info", "warn" or "error"
+#[tauri::command]
+fn set_log_level(settings: tauri::State<'_, SettingsState>, level: String) -> Result<(), String> {
+    let parsed = logging::Level::parse(&level).ok_or_else(|| format!("Unknown log level: {}", level))?;
+    settings.update(|s| s.log_level = parsed.as_str().to_string())?;
+    logging::set_level(parsed);
+    Ok(())
+}
+
+#[tauri::command]
+fn open_log_directory() -> Result<(), String> {
+    logging::open_dir()
+}
+
+/// Each backend service's process state, /health answer and uptime
+#[tauri::command]
+async fn get_service_status(state: tauri::State<'_, AppState>) -> Result<Vec<services::ServiceStatus>, String> {
+    Ok(services::current(&state.backend_services).await)
+}
+
+#[tauri::command]
+fn get_last_session_info() -> recovery::LastSessionInfo {
+    recovery::last_session_info()
+}
+
+/// Leave safe mode; the UI then starts the backend and clipboard monitor
+/// through their usual commands
+#[tauri::command]
+fn leave_safe_mode() {
+    recovery::clear_safe_mode();
+}
+
+#[tauri::command]
+fn get_input_limits(settings: tauri::State<'_, SettingsState>) -> limits::Limits {
+    settings.get().limits
+}
+
+#[tauri::command]
+fn set_input_limits(settings: tauri::State<'_, SettingsState>, limits: limits::Limits) -> Result<(), String> {
+    settings.update(|s| s.limits = limits).map(|_| ())
+}
+
+#[tauri::command]
+fn get_startup_timings() -> StartupTimings {
+    STARTUP.snapshot()
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PerformanceStats {
+    startup: StartupTimings,
+    prefetch: prefetch::PrefetchStats,
+}
+
+#[tauri::command]
+fn get_performance_stats() -> PerformanceStats {
+    PerformanceStats {
+        startup: STARTUP.snapshot(),
+        prefetch: prefetch::stats(),
+    }
 }
 
 #[tauri::command]
@@ -264,12 +320,46 @@ async fn toggle_floating_window(app: tauri::AppHandle) -> Result<(), String> {
     Ok(())
 }
 
+/// Tear a dictionary entry out into its own always-on-top card
+#[tauri::command]
+async fn open_entry_window(
+    app: tauri::AppHandle,
+    windows: tauri::State<'_, EntryWindows>,
+    entry_id: String,
+    language: String,
+) -> Result<String, String> {
+    let id = entry_id
+        .parse::<i64>()
+        .map_err(|_| format!("Invalid entry id '{}'", entry_id))?;
+    let label = windows.open(&app, id, &language)?;
+    write_log(&format!("[Window] Opened {} for entry {} ({})", label, id, language));
+    Ok(label)
+}
+
+#[tauri::command]
+async fn close_all_entry_windows(app: tauri::AppHandle, windows: tauri::State<'_, EntryWindows>) -> Result<usize, String> {
+    Ok(windows.close_all(&app))
+}
+
 #[tauri::command]
 async fn send_query_to_floating(app: tauri::AppHandle, query: String) -> Result<(), String> {
+    // Explicit queries are sent even if no run is routable
+    let mut payload = script::route_query(&query, &lookup_targets(&app)).unwrap_or_else(|| NewQueryPayload {
+        query: query.trim().to_string(),
+        original: query.clone(),
+        script: script::Script::Other,
+        language: None,
+        segments: script::segment(&query),
+        prefetched: false,
+        context: None,
+        source: None,
+    });
+    payload.source = Some("floating".to_string());
     if let Some(window) = app.get_webview_window("floating") {
         window.show().map_err(|e| e.to_string())?;
         window.set_focus().map_err(|e| e.to_string())?;
-        window.emit("new-query", query).map_err(|e| e.to_string())?;
+        // Targeted so detached entry windows never pick up floating queries
+        window.emit_to("floating", "new-query", payload).map_err(|e| e.to_string())?;
     }
     Ok(())
 }
@@ -281,58 +371,420 @@ async fn read_clipboard_text(app: tauri::AppHandle) -> Result<String, String> {
 
 #[tauri::command]
 async fn start_clipboard_monitor(app: tauri::AppHandle, state: tauri::State<'_, AppState>) -> Result<(), String> {
-    let monitoring = state.clipboard_monitoring.lock().unwrap().clone();
-    monitoring.store(true, Ordering::SeqCst);
+    state.clipboard_monitor.start(app);
+    Ok(())
+}
+
+#[tauri::command]
+fn get_clipboard_config(state: tauri::State<'_, AppState>) -> clipboard::ClipboardMonitorConfig {
+    state.clipboard_monitor.config()
+}
+
+/// Save the monitor settings; a running monitor picks them up on its next poll
+#[tauri::command]
+fn set_clipboard_config(
+    state: tauri::State<'_, AppState>,
+    settings: tauri::State<'_, SettingsState>,
+    config: clipboard::ClipboardMonitorConfig,
+) -> Result<clipboard::ClipboardMonitorConfig, String> {
+    config.validate()?;
+    settings.update(|s| s.clipboard_monitor = config.clone())?;
+    state.clipboard_monitor.set_config(config.clone());
+    Ok(config)
+}
+
+/// Tray menu choice of trigger mode
+fn set_clipboard_trigger_mode(app: &tauri::AppHandle, mode: clipboard::TriggerMode) {
+    let saved = app.state::<SettingsState>().update(|s| s.clipboard_monitor.trigger_mode = mode);
+    match saved {
+        Ok(settings) => app.state::<AppState>().clipboard_monitor.set_config(settings.clipboard_monitor),
+        Err(e) => {
+            logging::error(&format!("✗ Failed to save clipboard trigger mode: {}", e));
+            // Put the checkmark back on the mode still in effect
+            let monitor = &app.state::<AppState>().clipboard_monitor;
+            monitor.set_config(monitor.config());
+        }
+    }
+}
+
+#[tauri::command]
+async fn stop_clipboard_monitor(state: tauri::State<'_, AppState>) -> Result<(), String> {
+    state.clipboard_monitor.stop();
+    Ok(())
+}
+
+/// Copies seen, lookups triggered and copies filtered out, so the monitor
+/// can be seen working while it stays quiet
+#[tauri::command]
+fn get_clipboard_monitor_stats(state: tauri::State<'_, AppState>) -> clipboard::ClipboardMonitorStats {
+    state.clipboard_monitor.stats()
+}
+
+#[tauri::command]
+fn is_clipboard_monitoring(state: tauri::State<'_, AppState>) -> bool {
+    state.clipboard_monitor.is_running()
+}
+
+/// Command palette entries for window, clipboard and review control
+fn register_app_actions(registry: &ActionRegistry) {
+    registry.register(
+        AppAction::new(
+            "window.toggle_floating",
+            "Toggle Lumina Quick",
+            "Window",
+            Arc::new(|app, _args| {
+                if let Some(window) = app.get_webview_window("floating") {
+                    if window.is_visible().unwrap_or(false) {
+                        window.hide().map_err(|e| e.to_string())?;
+                    } else {
+                        window.show().map_err(|e| e.to_string())?;
+                        window.set_focus().map_err(|e| e.to_string())?;
+                    }
+                }
+                Ok(serde_json::Value::Null)
+            }),
+        )
+        .keywords(&["floating", "popup", "quick lookup"]),
+    );
+
+    registry.register(
+        AppAction::new(
+            "settings.open",
+            "Open settings",
+            "Settings",
+            Arc::new(|app, args| {
+                let section = args
+                    .get("section")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("general")
+                    .to_string();
+                app.emit_to("main", "open-settings-section", &section)
+                    .map_err(|e| e.to_string())?;
+                Ok(serde_json::json!({ "section": section }))
+            }),
+        )
+        .keywords(&["preferences", "options", "configure"]),
+    );
+
+    registry.register(
+        AppAction::new(
+            "clipboard.start",
+            "Start clipboard monitoring",
+            "Clipboard",
+            Arc::new(|app, _args| {
+                app.state::<AppState>().clipboard_monitor.start(app.clone());
+                Ok(serde_json::Value::Null)
+            }),
+        )
+        .keywords(&["monitor", "watch clipboard", "copy"])
+        .enabled_when(Arc::new(|app| {
+            !app.state::<AppState>().clipboard_monitor.is_running()
+        })),
+    );
+
+    registry.register(
+        AppAction::new(
+            "clipboard.stop",
+            "Stop clipboard monitoring",
+            "Clipboard",
+            Arc::new(|app, _args| {
+                app.state::<AppState>().clipboard_monitor.stop();
+                Ok(serde_json::Value::Null)
+            }),
+        )
+        .keywords(&["monitor", "pause clipboard"])
+        .enabled_when(Arc::new(|app| {
+            app.state::<AppState>().clipboard_monitor.is_running()
+        })),
+    );
+
+    registry.register(
+        AppAction::new(
+            "review.start",
+            "Start review session",
+            "Vocabulary",
+            Arc::new(|app, _args| {
+                if let Some(window) = app.get_webview_window("main") {
+                    window.show().map_err(|e| e.to_string())?;
+                    window.set_focus().map_err(|e| e.to_string())?;
+                }
+                app.emit_to("main", "start-review", ()).map_err(|e| e.to_string())?;
+                Ok(serde_json::Value::Null)
+            }),
+        )
+        .keywords(&["srs", "flashcards", "study"]),
+    );
+}
+
+/// Managed state built before the window exists, so nothing here may
+/// probe the environment
+fn initial_state() -> AppState {
+    AppState {
+        floating_manager: Mutex::new(None),
+        clipboard_monitor: clipboard::ClipboardMonitor::default(),
+        sanskrit_worker: SanskritWorker::new(),
+        sanskrit_health: Default::default(),
+        python_install: Mutex::new(None),
+        backend_services: Default::default(),
+    }
+}
+
+/// Files setup loads from the app data folder before the window shows
+struct LocalDataPaths {
+    terms: PathBuf,
+    texts: PathBuf,
+    queue: PathBuf,
+    usage: PathBuf,
+    history: PathBuf,
+    review_log: PathBuf,
+    sanskrit_cache: PathBuf,
+}
+
+impl LocalDataPaths {
+    fn of(app: &tauri::AppHandle) -> Self {
+        Self {
+            terms: get_terms_path(app),
+            texts: get_texts_path(app),
+            queue: offline_queue::get_queue_path(app),
+            usage: usage::get_usage_path(app),
+            history: search_history::get_history_path(app),
+            review_log: review_log::get_review_log_path(app),
+            sanskrit_cache: sanskrit_cache::get_cache_path(app),
+        }
+    }
+}
+
+/// Load the vocabulary, texts and queued operations and open the usage,
+/// history, review log and Sanskrit caches. Takes paths rather than the
+/// app so the startup test can run it; it runs before the window shows,
+/// so it must not start processes.
+fn load_local_data(paths: &LocalDataPaths, privacy_mode: bool) -> (VocabularyState, TextsState, OfflineQueue) {
+    let vocabulary = vocabulary_state_at(paths.terms.clone());
+    let texts = texts_state_at(paths.texts.clone());
+    let queue = OfflineQueue::load(paths.queue.clone());
+    usage::init(paths.usage.clone(), privacy_mode);
+    search_history::init(paths.history.clone());
+    review_log::init(paths.review_log.clone());
+    sanskrit_cache::init(paths.sanskrit_cache.clone());
+    (vocabulary, texts, queue)
+}
+
+/// Manage the app's state, build the tray and show the main window, then
+/// probe the environment on a background thread
+fn setup(app: &mut tauri::App) -> Result<(), Box<dyn std::error::Error>> {
+    // Everything up to showing the main window must stay free of
+    // subprocess calls
+    let setup_start = Instant::now();
+    write_log("执行应用设置...");
+
+    let _app_handle = app.handle().clone();
+
+    app.manage(SettingsState::load(app.handle(), settings::get_settings_path(app.handle())));
+    let startup_settings = app.state::<SettingsState>().get();
+    if let Some(level) = logging::Level::parse(&startup_settings.log_level) {
+        logging::set_level(level);
+    }
+    let (vocabulary, texts, queue) = load_local_data(&LocalDataPaths::of(app.handle()), startup_settings.privacy_mode);
+    app.manage(vocabulary);
+    app.manage(texts);
+    app.manage(queue);
+    offline_queue::register_handler(DOWNLOAD_OPERATION, retry_download);
+    offline_queue::register_handler(AUDIO_OPERATION, retry_audio);
+    offline_queue::spawn_retry_worker(app.handle().clone());
+    watch_term_updates(app.handle());
+    app.state::<AppState>()
+        .clipboard_monitor
+        .set_config(startup_settings.clipboard_monitor);
+    dict_watcher::start(app.handle().clone());
+    services::watch(app.handle().clone());
+
+    let registry = app.state::<ActionRegistry>();
+    register_app_actions(&registry);
+    commands::dictionary::register_actions(&registry);
     
-    let app_handle = app.clone();
-    thread::spawn(move || {
-        let mut last_clipboard = String::new();
-        let mut last_ignored_log = String::new();
-        
-        while monitoring.load(Ordering::SeqCst) {
-            if let Ok(text) = app_handle.clipboard().read_text() {
-                if !text.is_empty() && text != last_clipboard && text.len() < 200 {
-                    // 单词检查：只处理有效单词
-                    if !is_likely_word(&text) {
-                        // 只在剪贴板内容变化时记录一次日志
-                        if text != last_ignored_log {
-                            write_log(&format!("[Clipboard] Ignored non-word: '{}'", text));
-                            last_ignored_log = text.clone();
-                        }
-                        thread::sleep(Duration::from_millis(800));
-                        continue;
+    app.manage(shortcut::GlobalShortcut::default());
+    shortcut::init(app.handle());
+    let accelerator = app.state::<shortcut::GlobalShortcut>().accelerator();
+
+    let show_main_item = MenuItem::with_id(app, "show_main", "Show Main Window", true, None::<&str>)?;
+    let show_item = MenuItem::with_id(app, "show", "Show Lumina Quick", true, None::<&str>)?;
+    let toggle_item = MenuItem::with_id(app, "toggle", shortcut::toggle_label(accelerator.as_deref()), true, None::<&str>)?;
+    let trigger_mode = app.state::<SettingsState>().get().clipboard_monitor.trigger_mode;
+    let trigger_items = clipboard::TriggerMode::ALL
+        .into_iter()
+        .map(|mode| {
+            CheckMenuItem::with_id(app, mode.menu_id(), mode.label(), mode.is_available(), mode == trigger_mode, None::<&str>)
+                .map(|item| (mode, item))
+        })
+        .collect::<tauri::Result<Vec<_>>>()?;
+    let trigger_menu = Submenu::with_items(
+        app,
+        "Clipboard Trigger",
+        true,
+        &trigger_items.iter().map(|(_, item)| item as &dyn IsMenuItem<_>).collect::<Vec<_>>(),
+    )?;
+    app.state::<AppState>().clipboard_monitor.set_trigger_items(trigger_items);
+    let separator = MenuItem::with_id(app, "separator", "Separator", true, None::<&str>)?;
+    let quit_item = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?;
+    let menu = Menu::with_items(app, &[&show_main_item, &show_item, &toggle_item, &trigger_menu, &separator, &quit_item])?;
+    app.state::<shortcut::GlobalShortcut>().set_toggle_item(toggle_item.clone());
+
+    let _tray = TrayIconBuilder::with_id(services::TRAY_ID)
+        .icon(app.default_window_icon().cloned().unwrap())
+        .menu(&menu)
+        .tooltip(shortcut::tray_tooltip(accelerator.as_deref()))
+        .on_menu_event(move |app, event| {
+            match event.id.as_ref() {
+                "show_main" => {
+                    if let Some(window) = app.get_webview_window("main") {
+                        let _ = window.show();
+                        let _ = window.set_focus();
                     }
-                    
-                    last_clipboard = text.clone();
-                    last_ignored_log = String::new();
-                    write_log(&format!("[Clipboard] Detected word: '{}'", text));
-                    
-                    if let Some(window) = app_handle.get_webview_window("floating") {
+                }
+                "show" => {
+                    if let Some(window) = app.get_webview_window("floating") {
                         let _ = window.show();
                         let _ = window.set_focus();
-                        let _ = window.emit("new-query", text);
+                    }
+                }
+                "toggle" => {
+                    if let Some(window) = app.get_webview_window("floating") {
+                        if window.is_visible().unwrap_or(false) {
+                            let _ = window.hide();
+                        } else {
+                            let _ = window.show();
+                            let _ = window.set_focus();
+                        }
+                    }
+                }
+                "quit" => {
+                    app.exit(0);
+                }
+                id => {
+                    if let Some(mode) = clipboard::TriggerMode::from_menu_id(id) {
+                        set_clipboard_trigger_mode(app, mode);
                     }
                 }
             }
-            thread::sleep(Duration::from_millis(800));
+        })
+        .on_tray_icon_event(|tray, event| {
+            if let TrayIconEvent::Click { button: MouseButton::Left, button_state: MouseButtonState::Up, .. } = event {
+                shortcut::toggle_floating(tray.app_handle());
+            }
+        })
+        .build(app)?;
+    
+    write_log("系统托盘已创建");
+
+    // The main window starts hidden; a login launch stays in the tray,
+    // which is then all there is to show
+    if autostart::launched_hidden() {
+        write_log("[Autostart] Started hidden");
+        STARTUP.mark_window_ready();
+    } else if let Some(window) = app.get_webview_window("main") {
+        match window.show() {
+            Ok(()) => STARTUP.mark_window_ready(),
+            Err(e) => logging::error(&format!("✗ Failed to show the main window: {}", e)),
         }
-        write_log("[Clipboard] Monitor stopped");
+    }
+
+    // Environment probing runs off the critical path; each phase
+    // announces itself with a `startup-ready` event when done.
+    let app_handle_for_startup = app.handle().clone();
+    std::thread::spawn(move || {
+        let app = app_handle_for_startup;
+        let emit_ready = |phase: &str| {
+            let _ = app.emit("startup-ready", StartupReadyEvent {
+                phase: phase.to_string(),
+                elapsed_ms: STARTUP.elapsed_ms(),
+            });
+        };
+
+        let session = STARTUP.phase("recovery", true, || recovery::run(&app));
+        if session.safe_mode {
+            let _ = app.emit("safe-mode", &session);
+        }
+
+        let base_path = STARTUP.phase("resolve_base_path", true, paths::find_base_path);
+        emit_ready("scripts-resolved");
+
+        let detected = STARTUP.phase("locale_detection", true, locale::detected_languages);
+        settings::apply_first_run_defaults(&app.state::<SettingsState>(), &detected);
+        let loaded = app.state::<SettingsState>().get();
+        python::set_custom_path(loaded.python_path.map(PathBuf::from));
+        python::set_override(loaded.python_strategy.as_deref().and_then(|s| python::PythonStrategy::parse(s).ok()));
+        emit_ready("settings-loaded");
+        autostart::refresh();
+
+        let report = STARTUP.phase("integrity_check", true, || run_integrity_checks(&app));
+        if report.total_issues > 0 {
+            write_log(&format!(
+                "[Integrity] {} issue(s) found, {} repairable",
+                report.total_issues, report.repairable_issues
+            ));
+            let _ = app.emit("data-integrity-issues", report);
+        }
+
+        let detected_caps = STARTUP.phase("capabilities", true, || capabilities::detect(&base_path));
+        if detected_caps.sanskrit_tools {
+            commands::sanskrit::register_actions(&app.state::<ActionRegistry>());
+            if !session.safe_mode {
+                app.state::<AppState>().sanskrit_worker.warm_up();
+                // First answer for the UI, so it doesn't have to poll
+                let health_app = app.clone();
+                tauri::async_runtime::spawn(async move {
+                    commands::sanskrit::refresh_health(&health_app, Duration::from_secs(60)).await;
+                });
+            }
+        }
+        emit_ready("capabilities");
+
+        if session.safe_mode {
+            write_log("[Recovery] Safe mode: clipboard monitor not started");
+        } else if !app.state::<SettingsState>().get().clipboard_monitor.start_on_launch {
+            write_log("[Clipboard] Monitor not started: turned off in settings");
+        } else if let Some(state) = app.try_state::<AppState>() {
+            write_log("[Clipboard] Starting clipboard monitor...");
+            state.clipboard_monitor.start(app.clone());
+            emit_ready("clipboard-monitor");
+        }
+
+        let scan_app = app.clone();
+        std::thread::spawn(move || {
+            let languages = STARTUP.phase("dictionary_scan", true, db::get_available_languages);
+            write_log(&format!(
+                "词典扫描完成: {} 种语言",
+                languages.map(|l| l.len()).unwrap_or(0)
+            ));
+            let _ = scan_app.emit("startup-ready", StartupReadyEvent {
+                phase: "dictionaries-scanned".to_string(),
+                elapsed_ms: STARTUP.elapsed_ms(),
+            });
+        });
+
+        if session.safe_mode {
+            write_log("[Recovery] Safe mode: backend services not started");
+            return;
+        }
+        if !app.state::<SettingsState>().get().autostart_services {
+            write_log("后端服务自动启动已关闭");
+            return;
+        }
+        write_log("开始启动后端服务...");
+        let _ = STARTUP.phase("backend_start", true, || start_backend_services_at(&app, &base_path));
+        emit_ready("backend-started");
     });
-    
-    Ok(())
-}
 
-#[tauri::command]
-async fn stop_clipboard_monitor(state: tauri::State<'_, AppState>) -> Result<(), String> {
-    let monitoring = state.clipboard_monitoring.lock().unwrap();
-    monitoring.store(false, Ordering::SeqCst);
+    STARTUP.record_since("setup", setup_start, false);
+    write_log("应用设置完成");
     Ok(())
 }
 
 fn main() {
+    once_cell::sync::Lazy::force(&STARTUP);
     write_log("========== Lumina 应用启动 ==========");
 
-    let log_path = get_log_path();
+    let log_path = logging::log_path();
     write_log(&format!("日志文件: {:?}", log_path));
 
     tauri::Builder::default()
@@ -343,17 +795,44 @@ fn main() {
         .plugin(tauri_plugin_updater::Builder::new().build())
         .plugin(tauri_plugin_global_shortcut::Builder::new().build())
         .plugin(tauri_plugin_clipboard_manager::init())
-        .manage(|app: &tauri::AppHandle| AppState {
-            floating_manager: Mutex::new(None),
-            clipboard_monitoring: Mutex::new(Arc::new(AtomicBool::new(false))),
-            vocabulary_state: VocabularyState { 
-                terms_path: Mutex::new(app.path().app_data_dir().unwrap_or_else(|_| std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."))).join("data").join("terms.json"))
-            },
-        })
+        .manage(initial_state())
+        .manage(ActionRegistry::new())
+        .manage(EntryWindows::new())
         .invoke_handler(tauri::generate_handler![
             start_backend_services,
             stop_backend_services,
+            restart_backend_services,
             get_service_status,
+            get_service_log,
+            get_recent_logs,
+            set_log_level,
+            open_log_directory,
+            autostart::get_autostart_enabled,
+            autostart::set_autostart_enabled,
+            settings::get_settings,
+            settings::update_settings,
+            shortcut::get_global_shortcut,
+            shortcut::set_global_shortcut,
+            shortcut::get_selection_shortcut,
+            shortcut::set_selection_shortcut,
+            get_startup_timings,
+            get_performance_stats,
+            get_input_limits,
+            set_input_limits,
+            get_sense_labels,
+            search_by_definition,
+            reverse_lookup,
+            search_pattern,
+            get_search_history,
+            clear_search_history,
+            delete_history_entry,
+            export_analysis,
+            get_last_session_info,
+            leave_safe_mode,
+            get_usage_summary,
+            export_flashcards_pdf,
+            reset_usage_data,
+            set_usage_privacy_mode,
             check_for_updates,
             show_main_window,
             hide_main_window,
@@ -362,158 +841,152 @@ fn main() {
             hide_floating_window,
             toggle_floating_window,
             send_query_to_floating,
+            open_entry_window,
+            close_all_entry_windows,
             read_clipboard_text,
             start_clipboard_monitor,
             stop_clipboard_monitor,
+            is_clipboard_monitoring,
+            get_clipboard_monitor_stats,
+            get_clipboard_config,
+            set_clipboard_config,
             search_dictionary,
+            search_online,
+            get_pronunciation_audio,
+            get_dictionary_entry,
+            get_etymology_chain,
             get_dictionary_stats,
+            get_dictionary_metadata,
+            optimize_dictionary,
             get_available_languages,
+            get_registered_languages,
+            register_language,
+            unregister_language,
             get_dictionary_suggestions,
+            get_special_characters,
+            get_suggested_languages,
             batch_query_dictionary,
+            get_query_metrics,
+            export_entries,
             upload_dictionary_file,
+            cancel_dictionary_import,
+            cancel_download,
             download_dictionary,
+            get_pending_operations,
+            cancel_pending_operation,
             rescan_dictionary,
             remove_dictionary,
             delete_dictionary_file,
             sanskrit_split,
             sanskrit_transliterate,
+            sanskrit_split_batch,
+            sanskrit_transliterate_batch,
+            list_transliteration_schemes,
             sanskrit_health,
+            get_sanskrit_modes,
+            sanskrit_worker_status,
+            cancel_sanskrit_process,
+            clear_sanskrit_cache,
             check_python_environment,
+            reprobe_python,
+            set_python_strategy,
+            set_python_path,
+            install_python_dependencies,
+            install_sanskrit_dependencies,
+            cancel_sanskrit_install,
             process_text,
             save_term,
+            dedupe_terms,
+            export_vocabulary,
+            import_vocabulary,
+            export_terms_csv,
+            import_terms_csv,
+            export_terms_anki,
+            get_most_queried_terms,
             get_all_terms,
+            query_terms,
+            get_vocabulary_health,
+            list_vocabulary_backups,
+            create_vocabulary_backup,
+            restore_vocabulary_backup,
             delete_term,
-            update_term
+            get_term_family,
+            get_term_contexts,
+            get_term_image,
+            cleanup_term_images,
+            update_term,
+            bulk_update_terms,
+            bulk_delete_terms,
+            get_review_card,
+            get_review_log,
+            get_reviews_per_day,
+            get_streak_info,
+            set_daily_goal,
+            get_due_terms,
+            grade_term,
+            add_term_tags,
+            remove_term_tag,
+            get_terms_by_tag,
+            search_terms,
+            get_vocabulary_stats,
+            import_text,
+            list_texts,
+            refresh_coverage,
+            delete_text,
+            verify_data_integrity,
+            repair_data,
+            list_actions,
+            invoke_action
         ])
-        .setup(|app| {
-            write_log("执行应用设置...");
-
-            let _app_handle = app.handle().clone();
-            
-            let shortcut = Shortcut::new(Some(Modifiers::CONTROL | Modifiers::SHIFT), Code::KeyL);
-            let _ = app.global_shortcut().on_shortcut(shortcut, move |_app, _shortcut, event| {
-                if event.state == ShortcutState::Pressed {
-                    write_log("检测到全局快捷键 Ctrl+Shift+L");
-                    if let Some(window) = _app.get_webview_window("floating") {
-                        if window.is_visible().unwrap_or(false) {
-                            let _ = window.hide();
-                        } else {
-                            let _ = window.show();
-                            let _ = window.set_focus();
-                        }
-                    }
-                }
-            });
-            write_log("已注册全局快捷键 Ctrl+Shift+L");
-
-            let show_main_item = MenuItem::with_id(app, "show_main", "Show Main Window", true, None::<&str>)?;
-            let show_item = MenuItem::with_id(app, "show", "Show Lumina Quick", true, None::<&str>)?;
-            let toggle_item = MenuItem::with_id(app, "toggle", "Toggle (Ctrl+Shift+L)", true, None::<&str>)?;
-            let separator = MenuItem::with_id(app, "separator", "Separator", true, None::<&str>)?;
-            let quit_item = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?;
-            let menu = Menu::with_items(app, &[&show_main_item, &show_item, &toggle_item, &separator, &quit_item])?;
-
-            let _tray = TrayIconBuilder::new()
-                .icon(app.default_window_icon().cloned().unwrap())
-                .menu(&menu)
-                .tooltip("Lumina Quick (Ctrl+Shift+L)")
-                .on_menu_event(move |app, event| {
-                    match event.id.as_ref() {
-                        "show_main" => {
-                            if let Some(window) = app.get_webview_window("main") {
-                                let _ = window.show();
-                                let _ = window.set_focus();
-                            }
-                        }
-                        "show" => {
-                            if let Some(window) = app.get_webview_window("floating") {
-                                let _ = window.show();
-                                let _ = window.set_focus();
-                            }
-                        }
-                        "toggle" => {
-                            if let Some(window) = app.get_webview_window("floating") {
-                                if window.is_visible().unwrap_or(false) {
-                                    let _ = window.hide();
-                                } else {
-                                    let _ = window.show();
-                                    let _ = window.set_focus();
-                                }
-                            }
-                        }
-                        "quit" => {
-                            app.exit(0);
-                        }
-                        _ => {}
-                    }
-                })
-                .on_tray_icon_event(|tray, event| {
-                    if let TrayIconEvent::Click { button: MouseButton::Left, button_state: MouseButtonState::Up, .. } = event {
-                        let app = tray.app_handle();
-                        if let Some(window) = app.get_webview_window("floating") {
-                            if window.is_visible().unwrap_or(false) {
-                                let _ = window.hide();
-                            } else {
-                                let _ = window.show();
-                                let _ = window.set_focus();
-                            }
-                        }
-                    }
-                })
-                .build(app)?;
-            
-            write_log("系统托盘已创建");
-
-            std::thread::spawn(move || {
-                std::thread::sleep(std::time::Duration::from_secs(3));
-                write_log("开始启动后端服务...");
-                let _ = start_backend_services();
-            });
-
-            let app_handle_for_clipboard = app.handle().clone();
-            std::thread::spawn(move || {
-                std::thread::sleep(std::time::Duration::from_secs(5));
-                if let Some(state) = app_handle_for_clipboard.try_state::<AppState>() {
-                    let monitoring = state.clipboard_monitoring.lock().unwrap().clone();
-                    monitoring.store(true, Ordering::SeqCst);
-                    
-                    let mut last_clipboard = String::new();
-                    let mut last_ignored_log = String::new();
-                    write_log("[Clipboard] Starting clipboard monitor...");
-                    
-                    while monitoring.load(Ordering::SeqCst) {
-                        if let Ok(text) = app_handle_for_clipboard.clipboard().read_text() {
-                            if !text.is_empty() && text != last_clipboard && text.len() < 200 {
-                                // 单词检查：只处理有效单词
-                                if !is_likely_word(&text) {
-                                    // 只在剪贴板内容变化时记录一次日志
-                                    if text != last_ignored_log {
-                                        write_log(&format!("[Clipboard] Ignored non-word: '{}'", text));
-                                        last_ignored_log = text.clone();
-                                    }
-                                    std::thread::sleep(Duration::from_millis(800));
-                                    continue;
-                                }
-                                
-                                last_clipboard = text.clone();
-                                last_ignored_log = String::new();
-                                write_log(&format!("[Clipboard] Detected word: '{}'", text));
-                                
-                                if let Some(window) = app_handle_for_clipboard.get_webview_window("floating") {
-                                    let _ = window.show();
-                                    let _ = window.set_focus();
-                                    let _ = window.emit("new-query", text);
-                                }
-                            }
-                        }
-                        std::thread::sleep(Duration::from_millis(800));
-                    }
-                }
-            });
+        .setup(setup)
+        .build(tauri::generate_context!())
+        .expect("error while running tauri application")
+        .run(|app, event| {
+            if let tauri::RunEvent::Exit = event {
+                app.state::<AppState>().sanskrit_worker.shutdown();
+                app.state::<AppState>().backend_services.stop_all();
+                search_history::flush();
+                review_log::flush();
+                recovery::mark_clean_exit();
+            }
+        });
+}
 
-            write_log("应用设置完成");
-            Ok(())
-        })
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Builds what `main` builds and loads what setup loads before the main
+    /// window shows, against an empty data folder, and checks the startup
+    /// instrumentation saw no subprocess call
+    #[test]
+    fn no_blocking_call_before_the_window_is_visible() {
+        once_cell::sync::Lazy::force(&STARTUP);
+        let dir = tempfile::TempDir::new().unwrap();
+        let data = dir.path().join("data");
+        let _state = initial_state();
+        let _registry = ActionRegistry::new();
+        let _windows = EntryWindows::new();
+        let (vocabulary, _texts, _queue) = load_local_data(
+            &LocalDataPaths {
+                terms: data.join("terms.json"),
+                texts: data.join("texts.json"),
+                queue: data.join("pending_operations.json"),
+                usage: data.join("usage.json"),
+                history: data.join("search_history.json"),
+                review_log: data.join("review_log.json"),
+                sanskrit_cache: data.join("sanskrit_cache.db"),
+            },
+            false,
+        );
+        assert_eq!(*vocabulary.terms_path.lock().unwrap(), data.join("terms.json"));
+
+        let timings = STARTUP.snapshot();
+        assert_eq!(timings.window_ready_ms, None);
+        assert!(
+            timings.blocking_calls_before_window.is_empty(),
+            "blocking calls before the window: {:?}",
+            timings.blocking_calls_before_window
+        );
+    }
 }
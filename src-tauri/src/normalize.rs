@@ -0,0 +1,318 @@
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+
+// Normalization behind the `normalized_word` / `normalized_form` columns.
+// scripts/convert_jsonl_to_sqlite.py builds those columns with a Python
+// copy of these rules; change both together or lookups silently miss.
+
+/// Punctuation trimmed from both ends of a query
+const EDGE_PUNCTUATION: &[char] = &['.', ',', ';', ':', '!', '?', '"', '\'', '(', ')', '[', ']', '{', '}'];
+
+/// Lookup key for `word` in `lang_code`:
+/// - every language: NFC, lowercase, surrounding punctuation trimmed
+/// - `de`: ä→ae, ö→oe, ü→ue, ß→ss, hyphens and slashes dropped
+/// - `fr`, `es`, `pt`, `it`: accents stripped (Spanish keeps ñ)
+/// - `ru`, `uk`, `be`: stress marks dropped, ё→е
+/// - anything else, `sa` included: no further folding, IAST marks are letters
+pub fn normalize_word(word: &str, lang_code: &str) -> String {
+    let lowered = compose(word).to_lowercase();
+    let trimmed = lowered.trim_matches(|c: char| c.is_whitespace() || EDGE_PUNCTUATION.contains(&c));
+
+    match lang_code {
+        "de" => trimmed
+            .chars()
+            .filter(|c| !matches!(c, '-' | '/'))
+            .fold(String::new(), |mut out, c| {
+                match c {
+                    'ä' => out.push_str("ae"),
+                    'ö' => out.push_str("oe"),
+                    'ü' => out.push_str("ue"),
+                    'ß' => out.push_str("ss"),
+                    other => out.push(other),
+                }
+                out
+            }),
+        "fr" | "es" | "pt" | "it" => trimmed
+            .chars()
+            .map(|c| if lang_code == "es" && c == 'ñ' { c } else { strip_marks(c) })
+            .collect(),
+        "ru" | "uk" | "be" => trimmed
+            .chars()
+            .map(|c| if c == 'ё' { 'е' } else { c })
+            .filter(|c| !matches!(c, '\u{0300}' | '\u{0301}'))
+            .collect(),
+        _ => trimmed.to_string(),
+    }
+}
+
+/// Approximate NFC: canonical composition over the table below, not full
+/// Unicode normalization (no unicode-normalization dependency). Marks are
+/// combined with the character before them one at a time, which covers text
+/// typed or pasted from a dictionary in the scripts the table lists. Two
+/// things real NFC does are missing: canonical reordering of stacked marks
+/// (`ạ` + U+0302 composes, `a` + U+0302 + U+0323 does not) and composition
+/// outside Latin and Cyrillic (Greek, Hangul, Devanagari nukta forms).
+pub fn compose(text: &str) -> String {
+    let mut out: Vec<char> = Vec::with_capacity(text.len());
+    for c in text.chars() {
+        if let Some(last) = out.last_mut() {
+            if let Some(&composed) = COMPOSE.get(&(*last, c)) {
+                *last = composed;
+                continue;
+            }
+        }
+        out.push(c);
+    }
+    out.into_iter().collect()
+}
+
+/// `text` lowercased with every diacritic removed, whatever the language,
+/// for loose matching where "uber" should find "über"
+pub fn fold_marks(text: &str) -> String {
+    compose(text)
+        .to_lowercase()
+        .chars()
+        .map(strip_marks)
+        .filter(|c| !('\u{0300}'..='\u{036F}').contains(c))
+        .collect()
+}
+
+/// Base letter of `c` with all combining marks removed
+fn strip_marks(c: char) -> char {
+    let mut current = c;
+    while let Some(&(base, _)) = DECOMPOSE.get(&current) {
+        current = base;
+    }
+    current
+}
+
+static COMPOSE: Lazy<HashMap<(char, char), char>> =
+    Lazy::new(|| COMPOSITIONS.iter().map(|&(base, mark, composed)| ((base, mark), composed)).collect());
+
+static DECOMPOSE: Lazy<HashMap<char, (char, char)>> =
+    Lazy::new(|| COMPOSITIONS.iter().map(|&(base, mark, composed)| (composed, (base, mark))).collect());
+
+/// (base, combining mark, composed) for every two-part canonical
+/// decomposition in Latin-1, Latin Extended-A/B, Latin Extended Additional
+/// and Cyrillic, generated from Python's `unicodedata`
+const COMPOSITIONS: &[(char, char, char)] = &[
+    ('A', '\u{0300}', 'À'), ('A', '\u{0301}', 'Á'), ('A', '\u{0302}', 'Â'), ('A', '\u{0303}', 'Ã'),
+    ('A', '\u{0304}', 'Ā'), ('A', '\u{0306}', 'Ă'), ('A', '\u{0307}', 'Ȧ'), ('A', '\u{0308}', 'Ä'),
+    ('A', '\u{0309}', 'Ả'), ('A', '\u{030A}', 'Å'), ('A', '\u{030C}', 'Ǎ'), ('A', '\u{030F}', 'Ȁ'),
+    ('A', '\u{0311}', 'Ȃ'), ('A', '\u{0323}', 'Ạ'), ('A', '\u{0325}', 'Ḁ'), ('A', '\u{0328}', 'Ą'),
+    ('B', '\u{0307}', 'Ḃ'), ('B', '\u{0323}', 'Ḅ'), ('B', '\u{0331}', 'Ḇ'), ('C', '\u{0301}', 'Ć'),
+    ('C', '\u{0302}', 'Ĉ'), ('C', '\u{0307}', 'Ċ'), ('C', '\u{030C}', 'Č'), ('C', '\u{0327}', 'Ç'),
+    ('D', '\u{0307}', 'Ḋ'), ('D', '\u{030C}', 'Ď'), ('D', '\u{0323}', 'Ḍ'), ('D', '\u{0327}', 'Ḑ'),
+    ('D', '\u{032D}', 'Ḓ'), ('D', '\u{0331}', 'Ḏ'), ('E', '\u{0300}', 'È'), ('E', '\u{0301}', 'É'),
+    ('E', '\u{0302}', 'Ê'), ('E', '\u{0303}', 'Ẽ'), ('E', '\u{0304}', 'Ē'), ('E', '\u{0306}', 'Ĕ'),
+    ('E', '\u{0307}', 'Ė'), ('E', '\u{0308}', 'Ë'), ('E', '\u{0309}', 'Ẻ'), ('E', '\u{030C}', 'Ě'),
+    ('E', '\u{030F}', 'Ȅ'), ('E', '\u{0311}', 'Ȇ'), ('E', '\u{0323}', 'Ẹ'), ('E', '\u{0327}', 'Ȩ'),
+    ('E', '\u{0328}', 'Ę'), ('E', '\u{032D}', 'Ḙ'), ('E', '\u{0330}', 'Ḛ'), ('F', '\u{0307}', 'Ḟ'),
+    ('G', '\u{0301}', 'Ǵ'), ('G', '\u{0302}', 'Ĝ'), ('G', '\u{0304}', 'Ḡ'), ('G', '\u{0306}', 'Ğ'),
+    ('G', '\u{0307}', 'Ġ'), ('G', '\u{030C}', 'Ǧ'), ('G', '\u{0327}', 'Ģ'), ('H', '\u{0302}', 'Ĥ'),
+    ('H', '\u{0307}', 'Ḣ'), ('H', '\u{0308}', 'Ḧ'), ('H', '\u{030C}', 'Ȟ'), ('H', '\u{0323}', 'Ḥ'),
+    ('H', '\u{0327}', 'Ḩ'), ('H', '\u{032E}', 'Ḫ'), ('I', '\u{0300}', 'Ì'), ('I', '\u{0301}', 'Í'),
+    ('I', '\u{0302}', 'Î'), ('I', '\u{0303}', 'Ĩ'), ('I', '\u{0304}', 'Ī'), ('I', '\u{0306}', 'Ĭ'),
+    ('I', '\u{0307}', 'İ'), ('I', '\u{0308}', 'Ï'), ('I', '\u{0309}', 'Ỉ'), ('I', '\u{030C}', 'Ǐ'),
+    ('I', '\u{030F}', 'Ȉ'), ('I', '\u{0311}', 'Ȋ'), ('I', '\u{0323}', 'Ị'), ('I', '\u{0328}', 'Į'),
+    ('I', '\u{0330}', 'Ḭ'), ('J', '\u{0302}', 'Ĵ'), ('K', '\u{0301}', 'Ḱ'), ('K', '\u{030C}', 'Ǩ'),
+    ('K', '\u{0323}', 'Ḳ'), ('K', '\u{0327}', 'Ķ'), ('K', '\u{0331}', 'Ḵ'), ('L', '\u{0301}', 'Ĺ'),
+    ('L', '\u{030C}', 'Ľ'), ('L', '\u{0323}', 'Ḷ'), ('L', '\u{0327}', 'Ļ'), ('L', '\u{032D}', 'Ḽ'),
+    ('L', '\u{0331}', 'Ḻ'), ('M', '\u{0301}', 'Ḿ'), ('M', '\u{0307}', 'Ṁ'), ('M', '\u{0323}', 'Ṃ'),
+    ('N', '\u{0300}', 'Ǹ'), ('N', '\u{0301}', 'Ń'), ('N', '\u{0303}', 'Ñ'), ('N', '\u{0307}', 'Ṅ'),
+    ('N', '\u{030C}', 'Ň'), ('N', '\u{0323}', 'Ṇ'), ('N', '\u{0327}', 'Ņ'), ('N', '\u{032D}', 'Ṋ'),
+    ('N', '\u{0331}', 'Ṉ'), ('O', '\u{0300}', 'Ò'), ('O', '\u{0301}', 'Ó'), ('O', '\u{0302}', 'Ô'),
+    ('O', '\u{0303}', 'Õ'), ('O', '\u{0304}', 'Ō'), ('O', '\u{0306}', 'Ŏ'), ('O', '\u{0307}', 'Ȯ'),
+    ('O', '\u{0308}', 'Ö'), ('O', '\u{0309}', 'Ỏ'), ('O', '\u{030B}', 'Ő'), ('O', '\u{030C}', 'Ǒ'),
+    ('O', '\u{030F}', 'Ȍ'), ('O', '\u{0311}', 'Ȏ'), ('O', '\u{031B}', 'Ơ'), ('O', '\u{0323}', 'Ọ'),
+    ('O', '\u{0328}', 'Ǫ'), ('P', '\u{0301}', 'Ṕ'), ('P', '\u{0307}', 'Ṗ'), ('R', '\u{0301}', 'Ŕ'),
+    ('R', '\u{0307}', 'Ṙ'), ('R', '\u{030C}', 'Ř'), ('R', '\u{030F}', 'Ȑ'), ('R', '\u{0311}', 'Ȓ'),
+    ('R', '\u{0323}', 'Ṛ'), ('R', '\u{0327}', 'Ŗ'), ('R', '\u{0331}', 'Ṟ'), ('S', '\u{0301}', 'Ś'),
+    ('S', '\u{0302}', 'Ŝ'), ('S', '\u{0307}', 'Ṡ'), ('S', '\u{030C}', 'Š'), ('S', '\u{0323}', 'Ṣ'),
+    ('S', '\u{0326}', 'Ș'), ('S', '\u{0327}', 'Ş'), ('T', '\u{0307}', 'Ṫ'), ('T', '\u{030C}', 'Ť'),
+    ('T', '\u{0323}', 'Ṭ'), ('T', '\u{0326}', 'Ț'), ('T', '\u{0327}', 'Ţ'), ('T', '\u{032D}', 'Ṱ'),
+    ('T', '\u{0331}', 'Ṯ'), ('U', '\u{0300}', 'Ù'), ('U', '\u{0301}', 'Ú'), ('U', '\u{0302}', 'Û'),
+    ('U', '\u{0303}', 'Ũ'), ('U', '\u{0304}', 'Ū'), ('U', '\u{0306}', 'Ŭ'), ('U', '\u{0308}', 'Ü'),
+    ('U', '\u{0309}', 'Ủ'), ('U', '\u{030A}', 'Ů'), ('U', '\u{030B}', 'Ű'), ('U', '\u{030C}', 'Ǔ'),
+    ('U', '\u{030F}', 'Ȕ'), ('U', '\u{0311}', 'Ȗ'), ('U', '\u{031B}', 'Ư'), ('U', '\u{0323}', 'Ụ'),
+    ('U', '\u{0324}', 'Ṳ'), ('U', '\u{0328}', 'Ų'), ('U', '\u{032D}', 'Ṷ'), ('U', '\u{0330}', 'Ṵ'),
+    ('V', '\u{0303}', 'Ṽ'), ('V', '\u{0323}', 'Ṿ'), ('W', '\u{0300}', 'Ẁ'), ('W', '\u{0301}', 'Ẃ'),
+    ('W', '\u{0302}', 'Ŵ'), ('W', '\u{0307}', 'Ẇ'), ('W', '\u{0308}', 'Ẅ'), ('W', '\u{0323}', 'Ẉ'),
+    ('X', '\u{0307}', 'Ẋ'), ('X', '\u{0308}', 'Ẍ'), ('Y', '\u{0300}', 'Ỳ'), ('Y', '\u{0301}', 'Ý'),
+    ('Y', '\u{0302}', 'Ŷ'), ('Y', '\u{0303}', 'Ỹ'), ('Y', '\u{0304}', 'Ȳ'), ('Y', '\u{0307}', 'Ẏ'),
+    ('Y', '\u{0308}', 'Ÿ'), ('Y', '\u{0309}', 'Ỷ'), ('Y', '\u{0323}', 'Ỵ'), ('Z', '\u{0301}', 'Ź'),
+    ('Z', '\u{0302}', 'Ẑ'), ('Z', '\u{0307}', 'Ż'), ('Z', '\u{030C}', 'Ž'), ('Z', '\u{0323}', 'Ẓ'),
+    ('Z', '\u{0331}', 'Ẕ'), ('a', '\u{0300}', 'à'), ('a', '\u{0301}', 'á'), ('a', '\u{0302}', 'â'),
+    ('a', '\u{0303}', 'ã'), ('a', '\u{0304}', 'ā'), ('a', '\u{0306}', 'ă'), ('a', '\u{0307}', 'ȧ'),
+    ('a', '\u{0308}', 'ä'), ('a', '\u{0309}', 'ả'), ('a', '\u{030A}', 'å'), ('a', '\u{030C}', 'ǎ'),
+    ('a', '\u{030F}', 'ȁ'), ('a', '\u{0311}', 'ȃ'), ('a', '\u{0323}', 'ạ'), ('a', '\u{0325}', 'ḁ'),
+    ('a', '\u{0328}', 'ą'), ('b', '\u{0307}', 'ḃ'), ('b', '\u{0323}', 'ḅ'), ('b', '\u{0331}', 'ḇ'),
+    ('c', '\u{0301}', 'ć'), ('c', '\u{0302}', 'ĉ'), ('c', '\u{0307}', 'ċ'), ('c', '\u{030C}', 'č'),
+    ('c', '\u{0327}', 'ç'), ('d', '\u{0307}', 'ḋ'), ('d', '\u{030C}', 'ď'), ('d', '\u{0323}', 'ḍ'),
+    ('d', '\u{0327}', 'ḑ'), ('d', '\u{032D}', 'ḓ'), ('d', '\u{0331}', 'ḏ'), ('e', '\u{0300}', 'è'),
+    ('e', '\u{0301}', 'é'), ('e', '\u{0302}', 'ê'), ('e', '\u{0303}', 'ẽ'), ('e', '\u{0304}', 'ē'),
+    ('e', '\u{0306}', 'ĕ'), ('e', '\u{0307}', 'ė'), ('e', '\u{0308}', 'ë'), ('e', '\u{0309}', 'ẻ'),
+    ('e', '\u{030C}', 'ě'), ('e', '\u{030F}', 'ȅ'), ('e', '\u{0311}', 'ȇ'), ('e', '\u{0323}', 'ẹ'),
+    ('e', '\u{0327}', 'ȩ'), ('e', '\u{0328}', 'ę'), ('e', '\u{032D}', 'ḙ'), ('e', '\u{0330}', 'ḛ'),
+    ('f', '\u{0307}', 'ḟ'), ('g', '\u{0301}', 'ǵ'), ('g', '\u{0302}', 'ĝ'), ('g', '\u{0304}', 'ḡ'),
+    ('g', '\u{0306}', 'ğ'), ('g', '\u{0307}', 'ġ'), ('g', '\u{030C}', 'ǧ'), ('g', '\u{0327}', 'ģ'),
+    ('h', '\u{0302}', 'ĥ'), ('h', '\u{0307}', 'ḣ'), ('h', '\u{0308}', 'ḧ'), ('h', '\u{030C}', 'ȟ'),
+    ('h', '\u{0323}', 'ḥ'), ('h', '\u{0327}', 'ḩ'), ('h', '\u{032E}', 'ḫ'), ('h', '\u{0331}', 'ẖ'),
+    ('i', '\u{0300}', 'ì'), ('i', '\u{0301}', 'í'), ('i', '\u{0302}', 'î'), ('i', '\u{0303}', 'ĩ'),
+    ('i', '\u{0304}', 'ī'), ('i', '\u{0306}', 'ĭ'), ('i', '\u{0308}', 'ï'), ('i', '\u{0309}', 'ỉ'),
+    ('i', '\u{030C}', 'ǐ'), ('i', '\u{030F}', 'ȉ'), ('i', '\u{0311}', 'ȋ'), ('i', '\u{0323}', 'ị'),
+    ('i', '\u{0328}', 'į'), ('i', '\u{0330}', 'ḭ'), ('j', '\u{0302}', 'ĵ'), ('j', '\u{030C}', 'ǰ'),
+    ('k', '\u{0301}', 'ḱ'), ('k', '\u{030C}', 'ǩ'), ('k', '\u{0323}', 'ḳ'), ('k', '\u{0327}', 'ķ'),
+    ('k', '\u{0331}', 'ḵ'), ('l', '\u{0301}', 'ĺ'), ('l', '\u{030C}', 'ľ'), ('l', '\u{0323}', 'ḷ'),
+    ('l', '\u{0327}', 'ļ'), ('l', '\u{032D}', 'ḽ'), ('l', '\u{0331}', 'ḻ'), ('m', '\u{0301}', 'ḿ'),
+    ('m', '\u{0307}', 'ṁ'), ('m', '\u{0323}', 'ṃ'), ('n', '\u{0300}', 'ǹ'), ('n', '\u{0301}', 'ń'),
+    ('n', '\u{0303}', 'ñ'), ('n', '\u{0307}', 'ṅ'), ('n', '\u{030C}', 'ň'), ('n', '\u{0323}', 'ṇ'),
+    ('n', '\u{0327}', 'ņ'), ('n', '\u{032D}', 'ṋ'), ('n', '\u{0331}', 'ṉ'), ('o', '\u{0300}', 'ò'),
+    ('o', '\u{0301}', 'ó'), ('o', '\u{0302}', 'ô'), ('o', '\u{0303}', 'õ'), ('o', '\u{0304}', 'ō'),
+    ('o', '\u{0306}', 'ŏ'), ('o', '\u{0307}', 'ȯ'), ('o', '\u{0308}', 'ö'), ('o', '\u{0309}', 'ỏ'),
+    ('o', '\u{030B}', 'ő'), ('o', '\u{030C}', 'ǒ'), ('o', '\u{030F}', 'ȍ'), ('o', '\u{0311}', 'ȏ'),
+    ('o', '\u{031B}', 'ơ'), ('o', '\u{0323}', 'ọ'), ('o', '\u{0328}', 'ǫ'), ('p', '\u{0301}', 'ṕ'),
+    ('p', '\u{0307}', 'ṗ'), ('r', '\u{0301}', 'ŕ'), ('r', '\u{0307}', 'ṙ'), ('r', '\u{030C}', 'ř'),
+    ('r', '\u{030F}', 'ȑ'), ('r', '\u{0311}', 'ȓ'), ('r', '\u{0323}', 'ṛ'), ('r', '\u{0327}', 'ŗ'),
+    ('r', '\u{0331}', 'ṟ'), ('s', '\u{0301}', 'ś'), ('s', '\u{0302}', 'ŝ'), ('s', '\u{0307}', 'ṡ'),
+    ('s', '\u{030C}', 'š'), ('s', '\u{0323}', 'ṣ'), ('s', '\u{0326}', 'ș'), ('s', '\u{0327}', 'ş'),
+    ('t', '\u{0307}', 'ṫ'), ('t', '\u{0308}', 'ẗ'), ('t', '\u{030C}', 'ť'), ('t', '\u{0323}', 'ṭ'),
+    ('t', '\u{0326}', 'ț'), ('t', '\u{0327}', 'ţ'), ('t', '\u{032D}', 'ṱ'), ('t', '\u{0331}', 'ṯ'),
+    ('u', '\u{0300}', 'ù'), ('u', '\u{0301}', 'ú'), ('u', '\u{0302}', 'û'), ('u', '\u{0303}', 'ũ'),
+    ('u', '\u{0304}', 'ū'), ('u', '\u{0306}', 'ŭ'), ('u', '\u{0308}', 'ü'), ('u', '\u{0309}', 'ủ'),
+    ('u', '\u{030A}', 'ů'), ('u', '\u{030B}', 'ű'), ('u', '\u{030C}', 'ǔ'), ('u', '\u{030F}', 'ȕ'),
+    ('u', '\u{0311}', 'ȗ'), ('u', '\u{031B}', 'ư'), ('u', '\u{0323}', 'ụ'), ('u', '\u{0324}', 'ṳ'),
+    ('u', '\u{0328}', 'ų'), ('u', '\u{032D}', 'ṷ'), ('u', '\u{0330}', 'ṵ'), ('v', '\u{0303}', 'ṽ'),
+    ('v', '\u{0323}', 'ṿ'), ('w', '\u{0300}', 'ẁ'), ('w', '\u{0301}', 'ẃ'), ('w', '\u{0302}', 'ŵ'),
+    ('w', '\u{0307}', 'ẇ'), ('w', '\u{0308}', 'ẅ'), ('w', '\u{030A}', 'ẘ'), ('w', '\u{0323}', 'ẉ'),
+    ('x', '\u{0307}', 'ẋ'), ('x', '\u{0308}', 'ẍ'), ('y', '\u{0300}', 'ỳ'), ('y', '\u{0301}', 'ý'),
+    ('y', '\u{0302}', 'ŷ'), ('y', '\u{0303}', 'ỹ'), ('y', '\u{0304}', 'ȳ'), ('y', '\u{0307}', 'ẏ'),
+    ('y', '\u{0308}', 'ÿ'), ('y', '\u{0309}', 'ỷ'), ('y', '\u{030A}', 'ẙ'), ('y', '\u{0323}', 'ỵ'),
+    ('z', '\u{0301}', 'ź'), ('z', '\u{0302}', 'ẑ'), ('z', '\u{0307}', 'ż'), ('z', '\u{030C}', 'ž'),
+    ('z', '\u{0323}', 'ẓ'), ('z', '\u{0331}', 'ẕ'), ('Â', '\u{0300}', 'Ầ'), ('Â', '\u{0301}', 'Ấ'),
+    ('Â', '\u{0303}', 'Ẫ'), ('Â', '\u{0309}', 'Ẩ'), ('Ä', '\u{0304}', 'Ǟ'), ('Å', '\u{0301}', 'Ǻ'),
+    ('Æ', '\u{0301}', 'Ǽ'), ('Æ', '\u{0304}', 'Ǣ'), ('Ç', '\u{0301}', 'Ḉ'), ('Ê', '\u{0300}', 'Ề'),
+    ('Ê', '\u{0301}', 'Ế'), ('Ê', '\u{0303}', 'Ễ'), ('Ê', '\u{0309}', 'Ể'), ('Ï', '\u{0301}', 'Ḯ'),
+    ('Ô', '\u{0300}', 'Ồ'), ('Ô', '\u{0301}', 'Ố'), ('Ô', '\u{0303}', 'Ỗ'), ('Ô', '\u{0309}', 'Ổ'),
+    ('Õ', '\u{0301}', 'Ṍ'), ('Õ', '\u{0304}', 'Ȭ'), ('Õ', '\u{0308}', 'Ṏ'), ('Ö', '\u{0304}', 'Ȫ'),
+    ('Ø', '\u{0301}', 'Ǿ'), ('Ü', '\u{0300}', 'Ǜ'), ('Ü', '\u{0301}', 'Ǘ'), ('Ü', '\u{0304}', 'Ǖ'),
+    ('Ü', '\u{030C}', 'Ǚ'), ('â', '\u{0300}', 'ầ'), ('â', '\u{0301}', 'ấ'), ('â', '\u{0303}', 'ẫ'),
+    ('â', '\u{0309}', 'ẩ'), ('ä', '\u{0304}', 'ǟ'), ('å', '\u{0301}', 'ǻ'), ('æ', '\u{0301}', 'ǽ'),
+    ('æ', '\u{0304}', 'ǣ'), ('ç', '\u{0301}', 'ḉ'), ('ê', '\u{0300}', 'ề'), ('ê', '\u{0301}', 'ế'),
+    ('ê', '\u{0303}', 'ễ'), ('ê', '\u{0309}', 'ể'), ('ï', '\u{0301}', 'ḯ'), ('ô', '\u{0300}', 'ồ'),
+    ('ô', '\u{0301}', 'ố'), ('ô', '\u{0303}', 'ỗ'), ('ô', '\u{0309}', 'ổ'), ('õ', '\u{0301}', 'ṍ'),
+    ('õ', '\u{0304}', 'ȭ'), ('õ', '\u{0308}', 'ṏ'), ('ö', '\u{0304}', 'ȫ'), ('ø', '\u{0301}', 'ǿ'),
+    ('ü', '\u{0300}', 'ǜ'), ('ü', '\u{0301}', 'ǘ'), ('ü', '\u{0304}', 'ǖ'), ('ü', '\u{030C}', 'ǚ'),
+    ('Ă', '\u{0300}', 'Ằ'), ('Ă', '\u{0301}', 'Ắ'), ('Ă', '\u{0303}', 'Ẵ'), ('Ă', '\u{0309}', 'Ẳ'),
+    ('ă', '\u{0300}', 'ằ'), ('ă', '\u{0301}', 'ắ'), ('ă', '\u{0303}', 'ẵ'), ('ă', '\u{0309}', 'ẳ'),
+    ('Ē', '\u{0300}', 'Ḕ'), ('Ē', '\u{0301}', 'Ḗ'), ('ē', '\u{0300}', 'ḕ'), ('ē', '\u{0301}', 'ḗ'),
+    ('Ō', '\u{0300}', 'Ṑ'), ('Ō', '\u{0301}', 'Ṓ'), ('ō', '\u{0300}', 'ṑ'), ('ō', '\u{0301}', 'ṓ'),
+    ('Ś', '\u{0307}', 'Ṥ'), ('ś', '\u{0307}', 'ṥ'), ('Š', '\u{0307}', 'Ṧ'), ('š', '\u{0307}', 'ṧ'),
+    ('Ũ', '\u{0301}', 'Ṹ'), ('ũ', '\u{0301}', 'ṹ'), ('Ū', '\u{0308}', 'Ṻ'), ('ū', '\u{0308}', 'ṻ'),
+    ('ſ', '\u{0307}', 'ẛ'), ('Ơ', '\u{0300}', 'Ờ'), ('Ơ', '\u{0301}', 'Ớ'), ('Ơ', '\u{0303}', 'Ỡ'),
+    ('Ơ', '\u{0309}', 'Ở'), ('Ơ', '\u{0323}', 'Ợ'), ('ơ', '\u{0300}', 'ờ'), ('ơ', '\u{0301}', 'ớ'),
+    ('ơ', '\u{0303}', 'ỡ'), ('ơ', '\u{0309}', 'ở'), ('ơ', '\u{0323}', 'ợ'), ('Ư', '\u{0300}', 'Ừ'),
+    ('Ư', '\u{0301}', 'Ứ'), ('Ư', '\u{0303}', 'Ữ'), ('Ư', '\u{0309}', 'Ử'), ('Ư', '\u{0323}', 'Ự'),
+    ('ư', '\u{0300}', 'ừ'), ('ư', '\u{0301}', 'ứ'), ('ư', '\u{0303}', 'ữ'), ('ư', '\u{0309}', 'ử'),
+    ('ư', '\u{0323}', 'ự'), ('Ʒ', '\u{030C}', 'Ǯ'), ('Ǫ', '\u{0304}', 'Ǭ'), ('ǫ', '\u{0304}', 'ǭ'),
+    ('Ȧ', '\u{0304}', 'Ǡ'), ('ȧ', '\u{0304}', 'ǡ'), ('Ȩ', '\u{0306}', 'Ḝ'), ('ȩ', '\u{0306}', 'ḝ'),
+    ('Ȯ', '\u{0304}', 'Ȱ'), ('ȯ', '\u{0304}', 'ȱ'), ('ʒ', '\u{030C}', 'ǯ'), ('І', '\u{0308}', 'Ї'),
+    ('А', '\u{0306}', 'Ӑ'), ('А', '\u{0308}', 'Ӓ'), ('Г', '\u{0301}', 'Ѓ'), ('Е', '\u{0300}', 'Ѐ'),
+    ('Е', '\u{0306}', 'Ӗ'), ('Е', '\u{0308}', 'Ё'), ('Ж', '\u{0306}', 'Ӂ'), ('Ж', '\u{0308}', 'Ӝ'),
+    ('З', '\u{0308}', 'Ӟ'), ('И', '\u{0300}', 'Ѝ'), ('И', '\u{0304}', 'Ӣ'), ('И', '\u{0306}', 'Й'),
+    ('И', '\u{0308}', 'Ӥ'), ('К', '\u{0301}', 'Ќ'), ('О', '\u{0308}', 'Ӧ'), ('У', '\u{0304}', 'Ӯ'),
+    ('У', '\u{0306}', 'Ў'), ('У', '\u{0308}', 'Ӱ'), ('У', '\u{030B}', 'Ӳ'), ('Ч', '\u{0308}', 'Ӵ'),
+    ('Ы', '\u{0308}', 'Ӹ'), ('Э', '\u{0308}', 'Ӭ'), ('а', '\u{0306}', 'ӑ'), ('а', '\u{0308}', 'ӓ'),
+    ('г', '\u{0301}', 'ѓ'), ('е', '\u{0300}', 'ѐ'), ('е', '\u{0306}', 'ӗ'), ('е', '\u{0308}', 'ё'),
+    ('ж', '\u{0306}', 'ӂ'), ('ж', '\u{0308}', 'ӝ'), ('з', '\u{0308}', 'ӟ'), ('и', '\u{0300}', 'ѝ'),
+    ('и', '\u{0304}', 'ӣ'), ('и', '\u{0306}', 'й'), ('и', '\u{0308}', 'ӥ'), ('к', '\u{0301}', 'ќ'),
+    ('о', '\u{0308}', 'ӧ'), ('у', '\u{0304}', 'ӯ'), ('у', '\u{0306}', 'ў'), ('у', '\u{0308}', 'ӱ'),
+    ('у', '\u{030B}', 'ӳ'), ('ч', '\u{0308}', 'ӵ'), ('ы', '\u{0308}', 'ӹ'), ('э', '\u{0308}', 'ӭ'),
+    ('і', '\u{0308}', 'ї'), ('Ѵ', '\u{030F}', 'Ѷ'), ('ѵ', '\u{030F}', 'ѷ'), ('Ә', '\u{0308}', 'Ӛ'),
+    ('ә', '\u{0308}', 'ӛ'), ('Ө', '\u{0308}', 'Ӫ'), ('ө', '\u{0308}', 'ӫ'), ('Ḷ', '\u{0304}', 'Ḹ'),
+    ('ḷ', '\u{0304}', 'ḹ'), ('Ṛ', '\u{0304}', 'Ṝ'), ('ṛ', '\u{0304}', 'ṝ'), ('Ṣ', '\u{0307}', 'Ṩ'),
+    ('ṣ', '\u{0307}', 'ṩ'), ('Ạ', '\u{0302}', 'Ậ'), ('Ạ', '\u{0306}', 'Ặ'), ('ạ', '\u{0302}', 'ậ'),
+    ('ạ', '\u{0306}', 'ặ'), ('Ẹ', '\u{0302}', 'Ệ'), ('ẹ', '\u{0302}', 'ệ'), ('Ọ', '\u{0302}', 'Ộ'),
+    ('ọ', '\u{0302}', 'ộ'),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn german_folds_umlauts_and_eszett() {
+        assert_eq!(normalize_word("Straße", "de"), "strasse");
+        assert_eq!(normalize_word("Ärger", "de"), "aerger");
+        assert_eq!(normalize_word("Öl", "de"), "oel");
+        assert_eq!(normalize_word("Tür.", "de"), "tuer");
+        assert_eq!(normalize_word("E-Mail", "de"), "email");
+        // Decomposed input folds the same as precomposed
+        assert_eq!(normalize_word("A\u{0308}rger", "de"), "aerger");
+    }
+
+    #[test]
+    fn german_folding_applies_to_german_only() {
+        assert_eq!(normalize_word("Straße", "en"), "straße");
+        assert_eq!(normalize_word("Müller", "fr"), "muller");
+        assert_eq!(normalize_word("Müller", "sa"), "müller");
+    }
+
+    #[test]
+    fn romance_languages_strip_accents() {
+        assert_eq!(normalize_word("Élève", "fr"), "eleve");
+        assert_eq!(normalize_word("garçon", "fr"), "garcon");
+        assert_eq!(normalize_word("naïve", "fr"), "naive");
+        assert_eq!(normalize_word("e\u{0301}te\u{0301}", "fr"), "ete");
+        assert_eq!(normalize_word("canción", "es"), "cancion");
+        assert_eq!(normalize_word("Niño", "es"), "niño");
+        assert_eq!(normalize_word("Niño", "pt"), "nino");
+        assert_eq!(normalize_word("perché", "it"), "perche");
+    }
+
+    #[test]
+    fn russian_folds_yo_and_drops_stress() {
+        assert_eq!(normalize_word("Ёлка", "ru"), "елка");
+        assert_eq!(normalize_word("е\u{0308}лка", "ru"), "елка");
+        assert_eq!(normalize_word("за\u{0301}мок", "ru"), "замок");
+        assert_eq!(normalize_word("моло\u{0300}ко", "uk"), "молоко");
+        // й is a letter of its own, not и with a mark
+        assert_eq!(normalize_word("Й", "ru"), "й");
+    }
+
+    #[test]
+    fn sanskrit_keeps_iast_marks() {
+        assert_eq!(normalize_word("Kṛṣṇa", "sa"), "kṛṣṇa");
+        assert_eq!(normalize_word("ātman", "sa"), "ātman");
+        assert_eq!(normalize_word("śāntiḥ", "sa"), "śāntiḥ");
+        assert_eq!(normalize_word("pitṝn", "sa"), "pitṝn");
+        // Decomposed IAST composes to the same key
+        assert_eq!(normalize_word("Kr\u{0323}s\u{0323}n\u{0323}a", "sa"), "kṛṣṇa");
+        assert_eq!(normalize_word("pitr\u{0323}\u{0304}n", "sa"), "pitṝn");
+        assert_eq!(normalize_word("कृष्ण", "sa"), "कृष्ण");
+    }
+
+    #[test]
+    fn every_language_trims_edge_punctuation() {
+        for lang in ["de", "fr", "ru", "sa", "en"] {
+            assert_eq!(normalize_word("  (Word)!\" ", lang), "word");
+        }
+    }
+
+    #[test]
+    fn compose_matches_precomposed_forms() {
+        assert_eq!(compose("a\u{0304}"), "ā");
+        assert_eq!(compose("e\u{0302}\u{0301}"), "ế");
+        assert_eq!(compose("u\u{0308}ber"), "über");
+        assert_eq!(compose("already ā"), "already ā");
+        // Documented gap: marks out of canonical order are left alone
+        assert_eq!(compose("a\u{0302}\u{0323}"), "â\u{0323}");
+    }
+
+    #[test]
+    fn fold_marks_ignores_language() {
+        assert_eq!(fold_marks("Über"), "uber");
+        assert_eq!(fold_marks("kṛṣṇa"), "krsna");
+        assert_eq!(fold_marks("a\u{0361}b"), "ab");
+    }
+}
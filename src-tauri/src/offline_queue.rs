@@ -0,0 +1,368 @@
+use futures_util::future::BoxFuture;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, RwLock};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+
+/// Operations older than this are expired instead of retried
+const MAX_AGE_MS: i64 = 7 * 24 * 60 * 60 * 1000;
+const BASE_BACKOFF_MS: i64 = 30 * 1000;
+const MAX_BACKOFF_MS: i64 = 60 * 60 * 1000;
+const RETRY_POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Failure of a network-dependent operation. Only `Offline` failures are
+/// queued; everything else is reported to the caller as usual.
+#[derive(Debug, Clone)]
+pub enum OperationError {
+    Offline(String),
+    Failed(String),
+}
+
+impl fmt::Display for OperationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OperationError::Offline(e) | OperationError::Failed(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+/// Lets plain `String` errors propagate with `?` as non-retryable failures
+impl From<String> for OperationError {
+    fn from(e: String) -> Self {
+        OperationError::Failed(e)
+    }
+}
+
+impl From<&str> for OperationError {
+    fn from(e: &str) -> Self {
+        OperationError::Failed(e.to_string())
+    }
+}
+
+/// Classify a reqwest error: no connection, DNS failure or timeout means
+/// "try again later"; HTTP errors and bad bodies don't.
+pub fn classify(context: &str, err: reqwest::Error) -> OperationError {
+    let message = format!("{}: {}", context, err);
+    if err.is_connect() || err.is_timeout() || (err.is_request() && err.status().is_none()) {
+        OperationError::Offline(message)
+    } else {
+        OperationError::Failed(message)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum OperationStatus {
+    Pending,
+    Running,
+    Expired,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PendingOperation {
+    pub id: String,
+    pub kind: String,
+    pub args: Value,
+    pub status: OperationStatus,
+    pub attempts: u32,
+    pub created_at: i64,
+    pub next_attempt_at: i64,
+    pub last_error: Option<String>,
+}
+
+/// Retry handler for one kind of operation. Handlers must be idempotent or
+/// check before writing, since a retry may follow a partial success.
+pub type OperationHandler = fn(AppHandle, Value) -> BoxFuture<'static, Result<(), OperationError>>;
+
+static HANDLERS: Lazy<RwLock<HashMap<&'static str, OperationHandler>>> = Lazy::new(|| RwLock::new(HashMap::new()));
+
+pub fn register_handler(kind: &'static str, handler: OperationHandler) {
+    HANDLERS.write().unwrap().insert(kind, handler);
+}
+
+pub struct OfflineQueue {
+    path: PathBuf,
+    operations: Mutex<Vec<PendingOperation>>,
+}
+
+fn now_ms() -> i64 {
+    chrono::Utc::now().timestamp_millis()
+}
+
+fn backoff_ms(attempts: u32) -> i64 {
+    (BASE_BACKOFF_MS << attempts.min(16)).min(MAX_BACKOFF_MS)
+}
+
+fn write_atomic(path: &Path, operations: &[PendingOperation]) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create queue directory: {}", e))?;
+    }
+    let content = serde_json::to_string_pretty(operations)
+        .map_err(|e| format!("Failed to serialize queue: {}", e))?;
+    let tmp_path = path.with_extension("json.tmp");
+    fs::write(&tmp_path, content).map_err(|e| format!("Failed to write queue: {}", e))?;
+    fs::rename(&tmp_path, path).map_err(|e| format!("Failed to replace queue file: {}", e))
+}
+
+impl OfflineQueue {
+    pub fn load(path: PathBuf) -> Self {
+        let mut operations: Vec<PendingOperation> = fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        // A crash mid-retry leaves items marked running
+        for op in operations.iter_mut().filter(|op| op.status == OperationStatus::Running) {
+            op.status = OperationStatus::Pending;
+        }
+        Self {
+            path,
+            operations: Mutex::new(operations),
+        }
+    }
+
+    fn persist(&self, operations: &[PendingOperation]) {
+        if let Err(e) = write_atomic(&self.path, operations) {
+            eprintln!("[QUEUE] {}", e);
+        }
+    }
+
+    pub fn list(&self) -> Vec<PendingOperation> {
+        self.operations.lock().unwrap().clone()
+    }
+
+    /// Queue `kind` with `args` unless the same operation is already waiting.
+    /// Returns the id of the queued (or existing) operation.
+    pub fn enqueue(&self, kind: &str, args: Value, error: &str) -> String {
+        let mut operations = self.operations.lock().unwrap();
+        if let Some(existing) = operations
+            .iter_mut()
+            .find(|op| op.kind == kind && op.args == args && op.status != OperationStatus::Expired)
+        {
+            existing.last_error = Some(error.to_string());
+            let id = existing.id.clone();
+            self.persist(&operations);
+            return id;
+        }
+
+        let now = now_ms();
+        let mut id = format!("{}:{}", kind, now);
+        // Several failures can land in the same millisecond
+        let mut n = 1;
+        while operations.iter().any(|op| op.id == id) {
+            n += 1;
+            id = format!("{}:{}-{}", kind, now, n);
+        }
+        operations.push(PendingOperation {
+            id: id.clone(),
+            kind: kind.to_string(),
+            args,
+            status: OperationStatus::Pending,
+            attempts: 0,
+            created_at: now,
+            next_attempt_at: now + backoff_ms(0),
+            last_error: Some(error.to_string()),
+        });
+        self.persist(&operations);
+        eprintln!("[QUEUE] Queued {} ({})", id, error);
+        id
+    }
+
+    pub fn cancel(&self, id: &str) -> bool {
+        let mut operations = self.operations.lock().unwrap();
+        let before = operations.len();
+        operations.retain(|op| op.id != id || op.status == OperationStatus::Running);
+        let removed = operations.len() != before;
+        if removed {
+            self.persist(&operations);
+        }
+        removed
+    }
+
+    /// Expire old items and claim the ones due for a retry
+    fn take_due(&self) -> Vec<PendingOperation> {
+        let now = now_ms();
+        let mut operations = self.operations.lock().unwrap();
+        let mut due = Vec::new();
+        for op in operations.iter_mut() {
+            if op.status != OperationStatus::Pending {
+                continue;
+            }
+            if now - op.created_at > MAX_AGE_MS {
+                op.status = OperationStatus::Expired;
+            } else if op.next_attempt_at <= now {
+                op.status = OperationStatus::Running;
+                due.push(op.clone());
+            }
+        }
+        self.persist(&operations);
+        due
+    }
+
+    fn finish(&self, id: &str, result: Result<(), OperationError>) {
+        let mut operations = self.operations.lock().unwrap();
+        match result {
+            Ok(()) => operations.retain(|op| op.id != id),
+            Err(OperationError::Offline(e)) => {
+                if let Some(op) = operations.iter_mut().find(|op| op.id == id) {
+                    op.status = OperationStatus::Pending;
+                    op.attempts += 1;
+                    op.next_attempt_at = now_ms() + backoff_ms(op.attempts);
+                    op.last_error = Some(e);
+                }
+            }
+            // Online but failed for real: retrying won't help, drop it
+            Err(OperationError::Failed(e)) => {
+                eprintln!("[QUEUE] {} failed permanently: {}", id, e);
+                operations.retain(|op| op.id != id);
+            }
+        }
+        self.persist(&operations);
+    }
+}
+
+pub fn get_queue_path(app: &AppHandle) -> PathBuf {
+    let base_dir = app.path()
+        .app_data_dir()
+        .unwrap_or_else(|_| std::env::current_dir().unwrap_or_else(|_| PathBuf::from(".")));
+    base_dir.join("data").join("pending_operations.json")
+}
+
+/// Retry due operations in the background until the app exits
+pub fn spawn_retry_worker(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(RETRY_POLL_INTERVAL).await;
+            let queue = app.state::<OfflineQueue>();
+            let due = queue.take_due();
+            if due.is_empty() {
+                continue;
+            }
+
+            for op in due {
+                let handler = HANDLERS.read().unwrap().get(op.kind.as_str()).copied();
+                let result = match handler {
+                    Some(handler) => handler(app.clone(), op.args.clone()).await,
+                    None => Err(OperationError::Failed(format!("No handler for '{}'", op.kind))),
+                };
+                let succeeded = result.is_ok();
+                queue.finish(&op.id, result);
+                if succeeded {
+                    let _ = app.emit("pending-operation-completed", &op.id);
+                }
+            }
+            let _ = app.emit("pending-operations-changed", queue.list().len());
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    fn queue() -> (TempDir, OfflineQueue) {
+        let dir = TempDir::new().unwrap();
+        let queue = OfflineQueue::load(dir.path().join("pending_operations.json"));
+        (dir, queue)
+    }
+
+    /// Make every pending operation due now
+    fn make_due(queue: &OfflineQueue) {
+        for op in queue.operations.lock().unwrap().iter_mut() {
+            op.next_attempt_at = 0;
+        }
+    }
+
+    #[test]
+    fn same_operation_is_queued_once() {
+        let (_dir, queue) = queue();
+        let first = queue.enqueue("audio.download", json!({ "url": "a.ogg" }), "no route");
+        let again = queue.enqueue("audio.download", json!({ "url": "a.ogg" }), "timed out");
+        let other = queue.enqueue("audio.download", json!({ "url": "b.ogg" }), "no route");
+
+        assert_eq!(first, again);
+        assert_ne!(first, other);
+        let list = queue.list();
+        assert_eq!(list.len(), 2);
+        assert_eq!(list[0].last_error.as_deref(), Some("timed out"));
+    }
+
+    #[test]
+    fn offline_failures_back_off_and_real_failures_drop() {
+        let (_dir, queue) = queue();
+        let id = queue.enqueue("audio.download", json!({ "url": "a.ogg" }), "no route");
+        assert!(queue.take_due().is_empty(), "not due before the first backoff");
+
+        make_due(&queue);
+        let due = queue.take_due();
+        assert_eq!(due.len(), 1);
+        assert_eq!(queue.list()[0].status, OperationStatus::Running);
+        // Claimed items aren't handed out twice
+        assert!(queue.take_due().is_empty());
+
+        queue.finish(&id, Err(OperationError::Offline("still offline".to_string())));
+        let op = &queue.list()[0];
+        assert_eq!((op.status.clone(), op.attempts), (OperationStatus::Pending, 1));
+        assert!(op.next_attempt_at - now_ms() > BASE_BACKOFF_MS);
+
+        make_due(&queue);
+        queue.take_due();
+        queue.finish(&id, Err(OperationError::Failed("HTTP 404".to_string())));
+        assert!(queue.list().is_empty());
+    }
+
+    #[test]
+    fn success_removes_the_operation() {
+        let (_dir, queue) = queue();
+        let id = queue.enqueue("dictionary.download", json!({ "url": "de.jsonl" }), "no route");
+        make_due(&queue);
+        queue.take_due();
+        queue.finish(&id, Ok(()));
+        assert!(queue.list().is_empty());
+    }
+
+    #[test]
+    fn old_operations_expire_instead_of_retrying() {
+        let (_dir, queue) = queue();
+        queue.enqueue("audio.download", json!({ "url": "a.ogg" }), "no route");
+        queue.operations.lock().unwrap()[0].created_at -= MAX_AGE_MS + 1;
+        make_due(&queue);
+        assert!(queue.take_due().is_empty());
+        assert_eq!(queue.list()[0].status, OperationStatus::Expired);
+
+        // An expired item doesn't suppress queueing the operation anew
+        queue.enqueue("audio.download", json!({ "url": "a.ogg" }), "no route");
+        assert_eq!(queue.list().len(), 2);
+    }
+
+    #[test]
+    fn running_operations_are_pending_again_after_a_restart() {
+        let (dir, queue) = queue();
+        queue.enqueue("audio.download", json!({ "url": "a.ogg" }), "no route");
+        make_due(&queue);
+        queue.take_due();
+        drop(queue);
+
+        let reloaded = OfflineQueue::load(dir.path().join("pending_operations.json"));
+        assert_eq!(reloaded.list()[0].status, OperationStatus::Pending);
+        // A running item can't be cancelled from under its handler; a pending one can
+        let id = reloaded.list()[0].id.clone();
+        assert!(reloaded.cancel(&id));
+        assert!(!reloaded.cancel(&id));
+    }
+
+    #[test]
+    fn backoff_doubles_up_to_the_cap() {
+        assert_eq!(backoff_ms(0), BASE_BACKOFF_MS);
+        assert_eq!(backoff_ms(1), 2 * BASE_BACKOFF_MS);
+        assert_eq!(backoff_ms(30), MAX_BACKOFF_MS);
+    }
+}
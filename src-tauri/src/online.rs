@@ -0,0 +1,260 @@
+use once_cell::sync::Lazy;
+use rusqlite::{params, Connection};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use crate::db::{self, manifest::ONLINE_CACHE_FILE, DictionaryEntry, Example, Sense};
+use crate::offline_queue::{self, OperationError};
+
+/// Per-word definitions from English Wiktionary, keyed by language code
+const WIKTIONARY_DEFINITION_URL: &str = "https://en.wiktionary.org/api/rest_v1/page/definition/";
+const ONLINE_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Error prefix the UI matches on to show "you're offline"
+pub const OFFLINE: &str = "offline";
+
+/// Lookups remembered in memory, misses included
+const MEMORY_CAPACITY: usize = 256;
+const MEMORY_TTL: Duration = Duration::from_secs(30 * 60);
+
+/// (word, language) → when it was fetched and what came back
+type MemoryCache = HashMap<(String, String), (Instant, Vec<DictionaryEntry>)>;
+
+static MEMORY: Lazy<Mutex<MemoryCache>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+static CLIENT: Lazy<reqwest::Client> = Lazy::new(|| {
+    reqwest::Client::builder()
+        .user_agent("LuminousLute/1.5.0")
+        .timeout(ONLINE_TIMEOUT)
+        .build()
+        .unwrap_or_default()
+});
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct WiktionaryUsage {
+    part_of_speech: Option<String>,
+    #[serde(default)]
+    definitions: Vec<WiktionaryDefinition>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct WiktionaryDefinition {
+    definition: String,
+    #[serde(default)]
+    parsed_examples: Vec<WiktionaryExample>,
+}
+
+#[derive(Debug, Deserialize)]
+struct WiktionaryExample {
+    example: String,
+    translation: Option<String>,
+}
+
+/// Look `word` up on Wiktionary: memory first, then the on-disk cache when
+/// `persist` is set, then the network. An empty result is a real miss; a
+/// network failure is an error, `Offline` when there's no connection.
+pub async fn lookup(word: &str, lang_code: &str, persist: bool) -> Result<Vec<DictionaryEntry>, OperationError> {
+    let key = (word.to_string(), lang_code.to_string());
+    if let Some((at, entries)) = MEMORY.lock().unwrap().get(&key) {
+        if at.elapsed() < MEMORY_TTL {
+            return Ok(entries.clone());
+        }
+    }
+    if persist {
+        if let Some(entries) = read_persisted(word, lang_code) {
+            remember(key, entries.clone());
+            return Ok(entries);
+        }
+    }
+
+    let entries = fetch(word, lang_code).await?;
+    eprintln!("[ONLINE] {} ({}): {} entries", word, lang_code, entries.len());
+    if persist && !entries.is_empty() {
+        if let Err(e) = write_persisted(word, lang_code, &entries) {
+            eprintln!("[ONLINE] Could not cache {}: {}", word, e);
+        }
+    }
+    remember(key, entries.clone());
+    Ok(entries)
+}
+
+fn remember(key: (String, String), entries: Vec<DictionaryEntry>) {
+    let mut memory = MEMORY.lock().unwrap();
+    memory.retain(|_, (at, _)| at.elapsed() < MEMORY_TTL);
+    if memory.len() >= MEMORY_CAPACITY {
+        if let Some(oldest) = memory.iter().min_by_key(|(_, (at, _))| *at).map(|(k, _)| k.clone()) {
+            memory.remove(&oldest);
+        }
+    }
+    memory.insert(key, (Instant::now(), entries));
+}
+
+async fn fetch(word: &str, lang_code: &str) -> Result<Vec<DictionaryEntry>, OperationError> {
+    let mut url = reqwest::Url::parse(WIKTIONARY_DEFINITION_URL).map_err(|e| e.to_string())?;
+    url.path_segments_mut()
+        .map_err(|_| "Invalid Wiktionary URL".to_string())?
+        .pop_if_empty()
+        .push(word);
+
+    let response = CLIENT
+        .get(url)
+        .send()
+        .await
+        .map_err(|e| offline_queue::classify("Wiktionary lookup failed", e))?;
+    if response.status() == reqwest::StatusCode::NOT_FOUND {
+        return Ok(Vec::new());
+    }
+    if !response.status().is_success() {
+        return Err(format!("Wiktionary lookup failed: HTTP {}", response.status()).into());
+    }
+    let bytes = response
+        .bytes()
+        .await
+        .map_err(|e| offline_queue::classify("Wiktionary lookup failed", e))?;
+    let body: HashMap<String, Vec<WiktionaryUsage>> = serde_json::from_slice(&bytes)
+        .map_err(|e| format!("Unexpected Wiktionary response: {}", e))?;
+
+    Ok(body
+        .get(lang_code)
+        .map(|usages| usages.iter().filter_map(|usage| to_entry(word, lang_code, usage)).collect())
+        .unwrap_or_default())
+}
+
+fn to_entry(word: &str, lang_code: &str, usage: &WiktionaryUsage) -> Option<DictionaryEntry> {
+    let mut senses = Vec::new();
+    let mut examples = Vec::new();
+    for definition in &usage.definitions {
+        let gloss = strip_html(&definition.definition);
+        if gloss.is_empty() {
+            continue;
+        }
+        let index = senses.len() as i64;
+        for example in &definition.parsed_examples {
+            examples.push(Example {
+                text: strip_html(&example.example),
+                translation: example.translation.as_deref().map(strip_html),
+                sense_index: index,
+            });
+        }
+        senses.push(Sense {
+            id: format!("online-{}", index),
+            index,
+            fingerprint: db::gloss_fingerprint(&gloss),
+            labels: db::parse_gloss_labels(&gloss),
+            gloss,
+            promoted: false,
+            filtered: false,
+        });
+    }
+    if senses.is_empty() {
+        return None;
+    }
+
+    Some(DictionaryEntry {
+        entry_id: None,
+        text: word.to_string(),
+        language: lang_code.to_string(),
+        translation: None,
+        root_form: None,
+        grammar: usage.part_of_speech.as_ref().map(|p| p.to_lowercase()),
+        definition: Some(senses.iter().map(|s| s.gloss.as_str()).collect::<Vec<_>>().join(" | ")),
+        details: None,
+        link_part: None,
+        inflections: None,
+        etymology: None,
+        ipa: None,
+        etymology_chain: None,
+        matched_form_tags: None,
+        senses: Some(senses),
+        examples: if examples.is_empty() { None } else { Some(examples) },
+        hint_applied: None,
+        has_audio: None,
+        pronunciations: None,
+        hidden_sense_count: None,
+        actions: None,
+        dictionary_name: Some("Wiktionary".to_string()),
+        relations: None,
+        matched_via: Some("online".to_string()),
+    })
+}
+
+/// Text content of a Wiktionary HTML fragment
+fn strip_html(html: &str) -> String {
+    let mut text = String::with_capacity(html.len());
+    let mut in_tag = false;
+    for c in html.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => in_tag = false,
+            _ if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    let text = text
+        .replace("&nbsp;", " ")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&");
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn cache_path(lang_code: &str) -> PathBuf {
+    db::find_language_dir(lang_code)
+        .unwrap_or_else(|| db::get_dict_dir().join(lang_code))
+        .join(ONLINE_CACHE_FILE)
+}
+
+fn open_cache(lang_code: &str) -> Result<Connection, String> {
+    let path = cache_path(lang_code);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let conn = Connection::open(&path).map_err(|e| e.to_string())?;
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS online_entries (
+            word TEXT PRIMARY KEY,
+            fetched_at TEXT NOT NULL,
+            entries TEXT NOT NULL
+        )",
+        [],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(conn)
+}
+
+fn read_persisted(word: &str, lang_code: &str) -> Option<Vec<DictionaryEntry>> {
+    if !cache_path(lang_code).exists() {
+        return None;
+    }
+    let conn = open_cache(lang_code).ok()?;
+    let json: String = conn
+        .query_row("SELECT entries FROM online_entries WHERE word = ?1", params![word], |r| r.get(0))
+        .ok()?;
+    serde_json::from_str(&json).ok()
+}
+
+fn write_persisted(word: &str, lang_code: &str, entries: &[DictionaryEntry]) -> Result<(), String> {
+    let conn = open_cache(lang_code)?;
+    let json = serde_json::to_string(entries).map_err(|e| e.to_string())?;
+    conn.execute(
+        "INSERT OR REPLACE INTO online_entries (word, fetched_at, entries) VALUES (?1, ?2, ?3)",
+        params![word, chrono::Utc::now().to_rfc3339(), json],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Error string for a failed online lookup; offline failures carry the
+/// `offline` prefix
+pub fn error_message(e: OperationError) -> String {
+    match e {
+        OperationError::Offline(message) => format!("{}: {}", OFFLINE, message),
+        OperationError::Failed(message) => message,
+    }
+}
@@ -0,0 +1,49 @@
+use std::path::PathBuf;
+
+use crate::write_log;
+
+/// Directory holding `scripts/`: next to the executable, under `_up_` in
+/// packaged builds (where Tauri puts `../` resources), or the project root
+/// in dev mode
+pub fn find_base_path() -> PathBuf {
+    if let Ok(exe_path) = std::env::current_exe() {
+        if let Some(exe_dir) = exe_path.parent() {
+            let scripts_path = exe_dir.join("scripts");
+            if scripts_path.exists() {
+                return exe_dir.to_path_buf();
+            }
+
+            let up_scripts_path = exe_dir.join("_up_").join("scripts");
+            if up_scripts_path.exists() {
+                return exe_dir.join("_up_");
+            }
+        }
+    }
+
+    let current_dir = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+
+    // In dev mode CWD is src-tauri/, check parent (project root)
+    if current_dir.join("scripts").exists() {
+        return current_dir;
+    }
+    let parent = current_dir.parent().unwrap_or(&current_dir).to_path_buf();
+    if parent.join("scripts").exists() {
+        write_log(&format!("使用项目根目录: {:?}", parent));
+        return parent;
+    }
+
+    write_log(&format!("回退到当前目录: {:?}", current_dir));
+    current_dir
+}
+
+/// Absolute path of `scripts/<name>`, or an error naming where it was looked for
+pub fn script_path(name: &str) -> Result<PathBuf, String> {
+    let base_path = find_base_path();
+    let path = std::path::absolute(base_path.join("scripts").join(name))
+        .unwrap_or_else(|_| base_path.join("scripts").join(name));
+    if path.exists() {
+        Ok(path)
+    } else {
+        Err(format!("Script not found: {}", path.display()))
+    }
+}
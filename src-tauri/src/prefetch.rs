@@ -0,0 +1,111 @@
+use once_cell::sync::{Lazy, OnceCell};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use crate::db::{self, DictionaryEntry, SearchOptions};
+
+const PREFETCH_TTL: Duration = Duration::from_secs(60);
+const PREFETCH_CAPACITY: usize = 64;
+
+type LookupCell = Arc<OnceCell<Result<Vec<DictionaryEntry>, String>>>;
+
+/// Short-lived lookups started by the clipboard monitor before the popup asks
+/// for them. Keyed by (text, language) with default search options only.
+static CACHE: Lazy<PrefetchCache> = Lazy::new(PrefetchCache::new);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PrefetchStats {
+    pub started: u64,
+    pub hits: u64,
+    pub misses: u64,
+    pub hit_rate: f64,
+    pub cached: usize,
+}
+
+struct PrefetchCache {
+    entries: Mutex<HashMap<(String, String), (Instant, LookupCell)>>,
+    started: AtomicU64,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl PrefetchCache {
+    fn new() -> Self {
+        Self {
+            entries: Mutex::new(HashMap::new()),
+            started: AtomicU64::new(0),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+}
+
+fn lookup(word: &str, language: &str) -> Result<Vec<DictionaryEntry>, String> {
+    db::search_dictionary_with(word, language, &SearchOptions::default())
+}
+
+/// Start looking `word` up in the background. A later `take` with the same
+/// key returns the result, waiting for the lookup if it is still running.
+pub fn start(word: &str, language: &str) {
+    let key = (word.to_string(), language.to_string());
+    let cell: LookupCell = {
+        let mut entries = CACHE.entries.lock().unwrap();
+        entries.retain(|_, (at, _)| at.elapsed() < PREFETCH_TTL);
+        if entries.contains_key(&key) {
+            return;
+        }
+        if entries.len() >= PREFETCH_CAPACITY {
+            if let Some(oldest) = entries.iter().min_by_key(|(_, (at, _))| *at).map(|(k, _)| k.clone()) {
+                entries.remove(&oldest);
+            }
+        }
+        let cell: LookupCell = Arc::new(OnceCell::new());
+        entries.insert(key.clone(), (Instant::now(), cell.clone()));
+        cell
+    };
+
+    CACHE.started.fetch_add(1, Ordering::Relaxed);
+    std::thread::spawn(move || {
+        cell.get_or_init(|| lookup(&key.0, &key.1));
+    });
+}
+
+/// Cached result for (word, language), if one was prefetched within the TTL.
+/// Counts towards the hit rate either way.
+pub fn take(word: &str, language: &str) -> Option<Result<Vec<DictionaryEntry>, String>> {
+    let key = (word.to_string(), language.to_string());
+    let cell = {
+        let entries = CACHE.entries.lock().unwrap();
+        entries
+            .get(&key)
+            .filter(|(at, _)| at.elapsed() < PREFETCH_TTL)
+            .map(|(_, cell)| cell.clone())
+    };
+
+    match cell {
+        Some(cell) => {
+            CACHE.hits.fetch_add(1, Ordering::Relaxed);
+            Some(cell.get_or_init(|| lookup(word, language)).clone())
+        }
+        None => {
+            CACHE.misses.fetch_add(1, Ordering::Relaxed);
+            None
+        }
+    }
+}
+
+pub fn stats() -> PrefetchStats {
+    let hits = CACHE.hits.load(Ordering::Relaxed);
+    let misses = CACHE.misses.load(Ordering::Relaxed);
+    let lookups = hits + misses;
+    PrefetchStats {
+        started: CACHE.started.load(Ordering::Relaxed),
+        hits,
+        misses,
+        hit_rate: if lookups == 0 { 0.0 } else { hits as f64 / lookups as f64 },
+        cached: CACHE.entries.lock().unwrap().len(),
+    }
+}
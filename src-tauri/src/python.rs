@@ -0,0 +1,210 @@
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::{Arc, RwLock};
+use crate::startup::STARTUP;
+
+/// How Python scripts are launched, in probe order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PythonStrategy {
+    /// `uv run --project <scripts_dir> python`, using the scripts' pyproject
+    Uv,
+    /// The interpreter of `<scripts_dir>/.venv`
+    Venv,
+    /// `python` / `python3` from PATH
+    System,
+    /// The interpreter path set in settings
+    Custom,
+}
+
+impl PythonStrategy {
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value {
+            "uv" => Ok(PythonStrategy::Uv),
+            "venv" => Ok(PythonStrategy::Venv),
+            "system" => Ok(PythonStrategy::System),
+            "custom" => Ok(PythonStrategy::Custom),
+            other => Err(format!("Unknown Python strategy '{}'", other)),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PythonStrategy::Uv => "uv",
+            PythonStrategy::Venv => "venv",
+            PythonStrategy::System => "system",
+            PythonStrategy::Custom => "custom",
+        }
+    }
+}
+
+/// A resolved way to run Python with the scripts' dependencies available
+#[derive(Debug, Clone)]
+pub struct PythonLauncher {
+    pub strategy: PythonStrategy,
+    program: PathBuf,
+    prefix: Vec<OsString>,
+    base_path: PathBuf,
+}
+
+impl PythonLauncher {
+    pub fn scripts_dir(&self) -> PathBuf {
+        self.base_path.join("scripts")
+    }
+
+    /// The interpreter, run from the base path
+    pub fn python(&self) -> Command {
+        let mut cmd = Command::new(&self.program);
+        cmd.args(&self.prefix).current_dir(&self.base_path);
+        cmd
+    }
+
+    /// The interpreter with `scripts/<name>` as its first argument
+    pub fn script(&self, name: &str) -> Command {
+        let mut cmd = self.python();
+        cmd.arg(self.scripts_dir().join(name));
+        cmd
+    }
+
+    pub fn describe(&self) -> String {
+        let mut parts = vec![self.program.to_string_lossy().to_string()];
+        parts.extend(self.prefix.iter().map(|p| p.to_string_lossy().to_string()));
+        parts.join(" ")
+    }
+}
+
+static RESOLVED: Lazy<RwLock<Option<Arc<PythonLauncher>>>> = Lazy::new(|| RwLock::new(None));
+static OVERRIDE: Lazy<RwLock<Option<PythonStrategy>>> = Lazy::new(|| RwLock::new(None));
+static CUSTOM_PATH: Lazy<RwLock<Option<PathBuf>>> = Lazy::new(|| RwLock::new(None));
+
+fn succeeds(cmd: &mut Command) -> bool {
+    cmd.output().map(|o| o.status.success()).unwrap_or(false)
+}
+
+fn probe_uv(base_path: &Path) -> Option<PythonLauncher> {
+    let scripts_dir = base_path.join("scripts");
+    // Without a project file `uv run --project` has nothing to sync against
+    if !scripts_dir.join("pyproject.toml").exists() {
+        return None;
+    }
+    if !succeeds(Command::new("uv").arg("--version")) {
+        return None;
+    }
+    Some(PythonLauncher {
+        strategy: PythonStrategy::Uv,
+        program: PathBuf::from("uv"),
+        prefix: vec!["run".into(), "--project".into(), scripts_dir.into_os_string(), "python".into()],
+        base_path: base_path.to_path_buf(),
+    })
+}
+
+pub fn venv_python(scripts_dir: &Path) -> Option<PathBuf> {
+    let venv = scripts_dir.join(".venv");
+    [venv.join("bin").join("python"), venv.join("Scripts").join("python.exe")]
+        .into_iter()
+        .find(|p| p.exists())
+}
+
+fn probe_venv(base_path: &Path) -> Option<PythonLauncher> {
+    let python = venv_python(&base_path.join("scripts"))?;
+    Some(PythonLauncher {
+        strategy: PythonStrategy::Venv,
+        program: python,
+        prefix: Vec::new(),
+        base_path: base_path.to_path_buf(),
+    })
+}
+
+fn probe_system(base_path: &Path) -> Option<PythonLauncher> {
+    ["python", "python3"]
+        .into_iter()
+        .find(|name| succeeds(Command::new(name).arg("--version")))
+        .map(|name| PythonLauncher {
+            strategy: PythonStrategy::System,
+            program: PathBuf::from(name),
+            prefix: Vec::new(),
+            base_path: base_path.to_path_buf(),
+        })
+}
+
+fn probe_custom(base_path: &Path) -> Option<PythonLauncher> {
+    let python = CUSTOM_PATH.read().unwrap().clone()?;
+    if !succeeds(Command::new(&python).arg("--version")) {
+        return None;
+    }
+    Some(PythonLauncher {
+        strategy: PythonStrategy::Custom,
+        program: python,
+        prefix: Vec::new(),
+        base_path: base_path.to_path_buf(),
+    })
+}
+
+fn probe(base_path: &Path, strategy: PythonStrategy) -> Option<PythonLauncher> {
+    match strategy {
+        PythonStrategy::Uv => probe_uv(base_path),
+        PythonStrategy::Venv => probe_venv(base_path),
+        PythonStrategy::System => probe_system(base_path),
+        PythonStrategy::Custom => probe_custom(base_path),
+    }
+}
+
+/// Probe the custom path and the override (if any), then uv, .venv and the
+/// system interpreter
+fn probe_all(base_path: &Path) -> Option<PythonLauncher> {
+    STARTUP.note_blocking_call("python interpreter probe");
+
+    if let Some(path) = CUSTOM_PATH.read().unwrap().as_ref() {
+        if let Some(launcher) = probe_custom(base_path) {
+            return Some(launcher);
+        }
+        eprintln!("[PYTHON] Configured interpreter {} does not run, probing defaults", path.display());
+    }
+
+    if let Some(strategy) = *OVERRIDE.read().unwrap() {
+        if let Some(launcher) = probe(base_path, strategy) {
+            return Some(launcher);
+        }
+        eprintln!("[PYTHON] Preferred strategy '{}' unavailable, probing defaults", strategy.as_str());
+    }
+
+    [PythonStrategy::Uv, PythonStrategy::Venv, PythonStrategy::System]
+        .into_iter()
+        .find_map(|strategy| probe(base_path, strategy))
+}
+
+/// The cached launcher, probing on first use
+pub fn resolve() -> Result<Arc<PythonLauncher>, String> {
+    if let Some(launcher) = RESOLVED.read().unwrap().clone() {
+        return Ok(launcher);
+    }
+    reprobe()
+}
+
+/// Forget the cached launcher and probe again (e.g. after installing uv)
+pub fn reprobe() -> Result<Arc<PythonLauncher>, String> {
+    let base_path = crate::paths::find_base_path();
+    let launcher = probe_all(&base_path)
+        .map(Arc::new)
+        .ok_or_else(|| "Python not found".to_string());
+
+    match &launcher {
+        Ok(l) => crate::write_log(&format!("✓ Python strategy: {} ({})", l.strategy.as_str(), l.describe())),
+        Err(_) => crate::write_log("✗ No Python interpreter found"),
+    }
+    *RESOLVED.write().unwrap() = launcher.as_ref().ok().cloned();
+    launcher
+}
+
+/// Strategy to try before the default order; None restores the default
+pub fn set_override(strategy: Option<PythonStrategy>) {
+    *OVERRIDE.write().unwrap() = strategy;
+}
+
+/// Interpreter to try before any strategy; None goes back to probing
+pub fn set_custom_path(path: Option<PathBuf>) {
+    *CUSTOM_PATH.write().unwrap() = path;
+}
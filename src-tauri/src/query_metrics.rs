@@ -0,0 +1,172 @@
+//! Where lookup time goes. The db layer records named phases while a lookup
+//! is being timed on the current thread; the command finishing the lookup
+//! turns them into `QueryTimings`, logs slow lookups and keeps recent
+//! totals per language for `get_query_metrics`.
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Lookups slower than this are logged with their phase breakdown
+const SLOW_QUERY: Duration = Duration::from_millis(200);
+/// Recent lookups kept per language and kind for averages and p95
+const RING_SIZE: usize = 200;
+
+thread_local! {
+    static PHASES: RefCell<Option<Vec<(&'static str, Duration)>>> = const { RefCell::new(None) };
+}
+
+static METRICS: Lazy<Mutex<HashMap<(String, String), Ring>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Default)]
+struct Ring {
+    lookups: u64,
+    slow: u64,
+    recent: VecDeque<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PhaseTiming {
+    pub phase: String,
+    pub ms: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueryTimings {
+    pub total_ms: f64,
+    /// Phases in the order they first ran; repeated phases are summed
+    pub phases: Vec<PhaseTiming>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueryMetrics {
+    pub language: String,
+    /// "search" or "batch"
+    pub kind: String,
+    /// Lookups since the app started
+    pub lookups: u64,
+    /// Of those, slower than the slow-query threshold
+    pub slow_lookups: u64,
+    /// Over the most recent lookups
+    pub average_ms: f64,
+    pub p95_ms: f64,
+}
+
+/// Start collecting phases for a lookup on this thread
+pub fn begin() {
+    PHASES.with(|phases| *phases.borrow_mut() = Some(Vec::new()));
+}
+
+/// Add `elapsed` to `phase` if a lookup is being timed on this thread
+pub fn record(phase: &'static str, elapsed: Duration) {
+    PHASES.with(|phases| {
+        if let Some(phases) = phases.borrow_mut().as_mut() {
+            match phases.iter_mut().find(|(name, _)| *name == phase) {
+                Some((_, total)) => *total += elapsed,
+                None => phases.push((phase, elapsed)),
+            }
+        }
+    });
+}
+
+/// Run `f` as `phase`
+pub fn time<T>(phase: &'static str, f: impl FnOnce() -> T) -> T {
+    let start = Instant::now();
+    let result = f();
+    record(phase, start.elapsed());
+    result
+}
+
+/// Stop collecting on this thread and return what was recorded. Call before
+/// awaiting: the rest of an async command may run on another thread.
+pub fn take() -> Vec<(&'static str, Duration)> {
+    PHASES.with(|phases| phases.borrow_mut().take()).unwrap_or_default()
+}
+
+/// Record a finished lookup: keep its total for the language's metrics and
+/// log it when it was slow
+pub fn finish(
+    kind: &str,
+    language: &str,
+    query: &str,
+    started: Instant,
+    phases: Vec<(&'static str, Duration)>,
+) -> QueryTimings {
+    let total = started.elapsed();
+    let timings = QueryTimings {
+        total_ms: total.as_secs_f64() * 1000.0,
+        phases: phases
+            .into_iter()
+            .map(|(phase, elapsed)| PhaseTiming {
+                phase: phase.to_string(),
+                ms: elapsed.as_secs_f64() * 1000.0,
+            })
+            .collect(),
+    };
+
+    let slow = total >= SLOW_QUERY;
+    if slow {
+        let breakdown = timings
+            .phases
+            .iter()
+            .map(|p| format!("{} {:.1}", p.phase, p.ms))
+            .collect::<Vec<_>>()
+            .join(", ");
+        eprintln!(
+            "[SLOW-QUERY] {} {} '{}': {:.1} ms ({})",
+            kind,
+            language,
+            query.chars().take(80).collect::<String>(),
+            timings.total_ms,
+            breakdown
+        );
+    }
+
+    let mut metrics = METRICS.lock().unwrap();
+    let ring = metrics.entry((language.to_string(), kind.to_string())).or_default();
+    ring.lookups += 1;
+    if slow {
+        ring.slow += 1;
+    }
+    if ring.recent.len() >= RING_SIZE {
+        ring.recent.pop_front();
+    }
+    ring.recent.push_back(timings.total_ms);
+    timings
+}
+
+/// Lookup times per language and kind since the app started
+pub fn summary() -> Vec<QueryMetrics> {
+    let metrics = METRICS.lock().unwrap();
+    let mut summary: Vec<QueryMetrics> = metrics
+        .iter()
+        .map(|((language, kind), ring)| {
+            let mut sorted: Vec<f64> = ring.recent.iter().copied().collect();
+            sorted.sort_by(|a, b| a.total_cmp(b));
+            let average_ms = if sorted.is_empty() {
+                0.0
+            } else {
+                sorted.iter().sum::<f64>() / sorted.len() as f64
+            };
+            // Nearest rank
+            let p95_ms = if sorted.is_empty() {
+                0.0
+            } else {
+                sorted[((sorted.len() as f64 * 0.95).ceil() as usize).clamp(1, sorted.len()) - 1]
+            };
+            QueryMetrics {
+                language: language.clone(),
+                kind: kind.clone(),
+                lookups: ring.lookups,
+                slow_lookups: ring.slow,
+                average_ms,
+                p95_ms,
+            }
+        })
+        .collect();
+    summary.sort_by(|a, b| (&a.language, &a.kind).cmp(&(&b.language, &b.kind)));
+    summary
+}
@@ -0,0 +1,548 @@
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use crate::write_log;
+
+/// Unclean exits in a row before the next launch starts in safe mode
+pub const SAFE_MODE_THRESHOLD: u32 = 3;
+
+/// What the previous launch left behind. The file exists while the app runs
+/// and doubles as the instance lock; a clean exit marks it closed.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SessionMarker {
+    pid: u32,
+    exe: Option<String>,
+    started_at: i64,
+    clean_exit: bool,
+    consecutive_unclean: u32,
+    last_crash_at: Option<i64>,
+}
+
+/// A backend service started by this app, recorded so a crashed session's
+/// orphans can be found again
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackendProcess {
+    pub pid: u32,
+    pub script: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LastSessionInfo {
+    /// False when the previous session crashed or was killed
+    pub clean: bool,
+    /// When the crashed session started; `None` after a clean exit
+    pub crash_at: Option<i64>,
+    pub consecutive_unclean: u32,
+    pub safe_mode: bool,
+    /// Another live instance holds the session lock
+    pub other_instance: bool,
+    /// One line per artifact that was cleaned up
+    pub recovered: Vec<String>,
+}
+
+struct RecoveryState {
+    marker_path: Option<PathBuf>,
+    pids_path: Option<PathBuf>,
+    marker: SessionMarker,
+    info: LastSessionInfo,
+}
+
+static RECOVERY: Lazy<Mutex<RecoveryState>> = Lazy::new(|| {
+    Mutex::new(RecoveryState {
+        marker_path: None,
+        pids_path: None,
+        marker: SessionMarker::default(),
+        info: LastSessionInfo {
+            clean: true,
+            ..Default::default()
+        },
+    })
+});
+
+fn current_exe() -> Option<String> {
+    std::env::current_exe().ok().map(|p| p.to_string_lossy().to_string())
+}
+
+fn read_json<T: for<'de> Deserialize<'de>>(path: &Path) -> Option<T> {
+    fs::read_to_string(path).ok().and_then(|content| serde_json::from_str(&content).ok())
+}
+
+fn write_json<T: Serialize>(path: &Path, value: &T) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create directory: {}", e))?;
+    }
+    let content = serde_json::to_string_pretty(value).map_err(|e| format!("Failed to serialize: {}", e))?;
+    let tmp_path = path.with_extension("json.tmp");
+    fs::write(&tmp_path, content).map_err(|e| format!("Failed to write {:?}: {}", tmp_path, e))?;
+    fs::rename(&tmp_path, path).map_err(|e| format!("Failed to replace {:?}: {}", path, e))
+}
+
+/// Command line of a running process, or `None` if it isn't running.
+/// Windows only reports the image name, so backend scripts can't be told
+/// apart there and their stale PIDs are dropped rather than killed.
+#[cfg(target_os = "linux")]
+fn process_cmdline(pid: u32) -> Option<String> {
+    fs::read(format!("/proc/{}/cmdline", pid))
+        .ok()
+        .map(|raw| String::from_utf8_lossy(&raw).replace('\0', " "))
+}
+
+#[cfg(all(unix, not(target_os = "linux")))]
+fn process_cmdline(pid: u32) -> Option<String> {
+    let output = std::process::Command::new("ps")
+        .args(["-p", &pid.to_string(), "-o", "command="])
+        .output()
+        .ok()?;
+    let command = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (output.status.success() && !command.is_empty()).then_some(command)
+}
+
+#[cfg(windows)]
+fn process_cmdline(pid: u32) -> Option<String> {
+    use std::os::windows::process::CommandExt;
+    const CREATE_NO_WINDOW: u32 = 0x08000000;
+    let output = std::process::Command::new("tasklist")
+        .args(["/FI", &format!("PID eq {}", pid), "/NH", "/FO", "CSV"])
+        .creation_flags(CREATE_NO_WINDOW)
+        .output()
+        .ok()?;
+    let listing = String::from_utf8_lossy(&output.stdout).to_string();
+    // tasklist prints an informational line instead of a row when nothing matches
+    listing.contains(&format!("\"{}\"", pid)).then_some(listing)
+}
+
+/// The recorded PID is alive and still runs `exe` (PIDs get reused)
+fn is_our_process(pid: u32, exe: Option<&str>) -> bool {
+    let Some(cmdline) = process_cmdline(pid) else {
+        return false;
+    };
+    match exe.and_then(|e| Path::new(e).file_name()) {
+        Some(name) => cmdline.contains(&*name.to_string_lossy()),
+        None => true,
+    }
+}
+
+#[cfg(unix)]
+fn kill_process(pid: u32) -> bool {
+    std::process::Command::new("kill")
+        .arg(pid.to_string())
+        .status()
+        .map(|s| s.success())
+        .unwrap_or(false)
+}
+
+#[cfg(windows)]
+fn kill_process(pid: u32) -> bool {
+    use std::os::windows::process::CommandExt;
+    const CREATE_NO_WINDOW: u32 = 0x08000000;
+    std::process::Command::new("taskkill")
+        .args(["/PID", &pid.to_string(), "/F"])
+        .creation_flags(CREATE_NO_WINDOW)
+        .status()
+        .map(|s| s.success())
+        .unwrap_or(false)
+}
+
+fn data_dir(app: &tauri::AppHandle) -> PathBuf {
+    use tauri::Manager;
+    app.path()
+        .app_data_dir()
+        .unwrap_or_else(|_| std::env::current_dir().unwrap_or_else(|_| PathBuf::from(".")))
+        .join("data")
+}
+
+fn get_session_path(app: &tauri::AppHandle) -> PathBuf {
+    data_dir(app).join("session.json")
+}
+
+fn get_backend_pids_path(app: &tauri::AppHandle) -> PathBuf {
+    data_dir(app).join("backend_pids.json")
+}
+
+/// Backend services still running from a crashed session hold their ports
+/// and would make the new ones fail to bind. Kill the ones that are still
+/// our scripts and forget the rest.
+fn reconcile_backend_pids(path: &Path, recovered: &mut Vec<String>) {
+    let Some(processes) = read_json::<Vec<BackendProcess>>(path) else {
+        return;
+    };
+    for process in processes {
+        match process_cmdline(process.pid) {
+            Some(cmdline) if cmdline.contains(&process.script) => {
+                let killed = kill_process(process.pid);
+                recovered.push(format!(
+                    "{} orphaned backend {} (PID {})",
+                    if killed { "Stopped" } else { "Could not stop" },
+                    process.script,
+                    process.pid
+                ));
+            }
+            _ => recovered.push(format!("Dropped stale PID {} for {}", process.pid, process.script)),
+        }
+    }
+    let _ = fs::remove_file(path);
+}
+
+/// Temp files of atomic writes (`*.json.tmp`) are only left behind when the
+/// process died between write and rename; the original is still intact.
+fn discard_partial_writes(dir: &Path, recovered: &mut Vec<String>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.to_string_lossy().ends_with(".json.tmp") && fs::remove_file(&path).is_ok() {
+            recovered.push(format!("Discarded partial write {:?}", path.file_name().unwrap_or_default()));
+        }
+    }
+}
+
+/// Dictionary downloads extract into a temp directory and only then import
+/// into the target database, so leftovers there are never a finished import
+fn discard_interrupted_imports(temp_dir: &Path, recovered: &mut Vec<String>) {
+    let Ok(entries) = fs::read_dir(temp_dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        if fs::remove_file(entry.path()).is_ok() {
+            recovered.push(format!(
+                "Discarded interrupted import {:?}",
+                entry.file_name()
+            ));
+        }
+    }
+    let _ = fs::remove_dir(temp_dir);
+}
+
+/// Inspect what the previous session left behind, clean it up and claim the
+/// session marker for this process. Must run before the backend and the
+/// clipboard monitor start, since it decides whether they may.
+pub fn run(app: &tauri::AppHandle) -> LastSessionInfo {
+    let marker_path = get_session_path(app);
+    let pids_path = get_backend_pids_path(app);
+    let (info, marker) = recover(&marker_path, &pids_path, &std::env::temp_dir().join("luminous_lute_dict"));
+
+    for line in &info.recovered {
+        write_log(&format!("[Recovery] {}", line));
+    }
+    if info.other_instance {
+        write_log(&format!("[Recovery] Another instance is running (PID {})", marker.pid));
+        RECOVERY.lock().unwrap().info = info.clone();
+        return info;
+    }
+    if info.safe_mode {
+        write_log(&format!(
+            "[Recovery] {} unclean exits in a row, starting in safe mode",
+            info.consecutive_unclean
+        ));
+    }
+
+    let mut state = RECOVERY.lock().unwrap();
+    state.marker_path = Some(marker_path);
+    state.pids_path = Some(pids_path);
+    state.marker = marker;
+    state.info = info.clone();
+    info
+}
+
+/// The recovery pass on explicit paths. Returns what was found and the
+/// marker now on disk; when another instance holds the lock, that
+/// instance's marker, untouched.
+fn recover(marker_path: &Path, pids_path: &Path, import_dir: &Path) -> (LastSessionInfo, SessionMarker) {
+    let exe = current_exe();
+    let now = chrono::Utc::now().timestamp_millis();
+    let mut info = LastSessionInfo {
+        clean: true,
+        ..Default::default()
+    };
+
+    let previous = read_json::<SessionMarker>(marker_path);
+    let mut consecutive_unclean = 0;
+    if let Some(previous) = &previous {
+        consecutive_unclean = previous.consecutive_unclean;
+        if !previous.clean_exit && previous.pid != std::process::id() {
+            if is_our_process(previous.pid, previous.exe.as_deref()) {
+                // A second instance: leave its marker and PIDs alone
+                info.other_instance = true;
+                info.consecutive_unclean = consecutive_unclean;
+                return (info, previous.clone());
+            }
+            consecutive_unclean += 1;
+            info.clean = false;
+            info.crash_at = Some(previous.started_at);
+            info.recovered.push(format!("Cleared stale session lock (PID {})", previous.pid));
+        } else if previous.clean_exit {
+            consecutive_unclean = 0;
+        }
+    }
+
+    reconcile_backend_pids(pids_path, &mut info.recovered);
+    if let Some(dir) = marker_path.parent() {
+        discard_partial_writes(dir, &mut info.recovered);
+    }
+    discard_interrupted_imports(import_dir, &mut info.recovered);
+
+    info.consecutive_unclean = consecutive_unclean;
+    info.safe_mode = consecutive_unclean >= SAFE_MODE_THRESHOLD;
+
+    let marker = SessionMarker {
+        pid: std::process::id(),
+        exe,
+        started_at: now,
+        clean_exit: false,
+        consecutive_unclean,
+        last_crash_at: info.crash_at.or_else(|| previous.and_then(|p| p.last_crash_at)),
+    };
+    if let Err(e) = write_json(marker_path, &marker) {
+        write_log(&format!("[Recovery] Failed to write session marker: {}", e));
+    }
+    (info, marker)
+}
+
+pub fn last_session_info() -> LastSessionInfo {
+    RECOVERY.lock().unwrap().info.clone()
+}
+
+fn save_marker(state: &RecoveryState) {
+    if let Some(path) = &state.marker_path {
+        if let Err(e) = write_json(path, &state.marker) {
+            eprintln!("[Recovery] Failed to update session marker: {}", e);
+        }
+    }
+}
+
+/// Leave safe mode for this session and stop counting past crashes
+pub fn clear_safe_mode() {
+    let mut state = RECOVERY.lock().unwrap();
+    state.info.safe_mode = false;
+    state.marker.consecutive_unclean = 0;
+    save_marker(&state);
+}
+
+/// Mark the session as ended cleanly; called on a normal exit only
+pub fn mark_clean_exit() {
+    let mut state = RECOVERY.lock().unwrap();
+    if state.marker_path.is_none() {
+        return;
+    }
+    state.marker.clean_exit = true;
+    state.marker.consecutive_unclean = 0;
+    save_marker(&state);
+}
+
+/// Remember a spawned backend so the next launch can clean it up if this
+/// one crashes
+pub fn record_backend_process(pid: u32, script: &str) {
+    let Some(path) = RECOVERY.lock().unwrap().pids_path.clone() else {
+        return;
+    };
+    let mut processes = read_json::<Vec<BackendProcess>>(&path).unwrap_or_default();
+    processes.retain(|p| p.script != script);
+    processes.push(BackendProcess {
+        pid,
+        script: script.to_string(),
+    });
+    if let Err(e) = write_json(&path, &processes) {
+        write_log(&format!("[Recovery] Failed to record backend PID: {}", e));
+    }
+}
+
+/// A backend was stopped on purpose; drop it from the orphan list
+pub fn forget_backend_process(script: &str) {
+    let Some(path) = RECOVERY.lock().unwrap().pids_path.clone() else {
+        return;
+    };
+    let mut processes = read_json::<Vec<BackendProcess>>(&path).unwrap_or_default();
+    processes.retain(|p| p.script != script);
+    if let Err(e) = write_json(&path, &processes) {
+        write_log(&format!("[Recovery] Failed to record backend PID: {}", e));
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::process::{Child, Command};
+    use tempfile::TempDir;
+
+    struct Fixture {
+        _dir: TempDir,
+        marker: PathBuf,
+        pids: PathBuf,
+        imports: PathBuf,
+    }
+
+    impl Fixture {
+        fn new() -> Self {
+            let dir = TempDir::new().unwrap();
+            let data = dir.path().join("data");
+            fs::create_dir_all(&data).unwrap();
+            Self {
+                marker: data.join("session.json"),
+                pids: data.join("backend_pids.json"),
+                imports: dir.path().join("luminous_lute_dict"),
+                _dir: dir,
+            }
+        }
+
+        fn previous(&self, marker: SessionMarker) {
+            write_json(&self.marker, &marker).unwrap();
+        }
+
+        fn recover(&self) -> (LastSessionInfo, SessionMarker) {
+            recover(&self.marker, &self.pids, &self.imports)
+        }
+    }
+
+    /// A PID that was just in use and now belongs to nobody
+    fn dead_pid() -> u32 {
+        let mut child = Command::new("true").spawn().unwrap();
+        let pid = child.id();
+        child.wait().unwrap();
+        pid
+    }
+
+    fn sleeper() -> Child {
+        let child = Command::new("sleep").arg("30").spawn().unwrap();
+        // The command line reads empty until the exec has gone through
+        while !process_cmdline(child.id()).is_some_and(|c| c.contains("sleep")) {
+            std::thread::sleep(std::time::Duration::from_millis(5));
+        }
+        child
+    }
+
+    fn crashed(pid: u32, exe: &str, consecutive_unclean: u32) -> SessionMarker {
+        SessionMarker {
+            pid,
+            exe: Some(exe.to_string()),
+            started_at: 1_000,
+            clean_exit: false,
+            consecutive_unclean,
+            last_crash_at: None,
+        }
+    }
+
+    #[test]
+    fn first_launch_claims_the_lock() {
+        let fx = Fixture::new();
+        let (info, marker) = fx.recover();
+        assert!(info.clean && !info.safe_mode && info.recovered.is_empty());
+        assert_eq!(marker.pid, std::process::id());
+        let on_disk: SessionMarker = read_json(&fx.marker).unwrap();
+        assert!(!on_disk.clean_exit);
+    }
+
+    #[test]
+    fn stale_lock_of_a_dead_process_is_cleared() {
+        let fx = Fixture::new();
+        let pid = dead_pid();
+        fx.previous(crashed(pid, "/opt/lumina/lumina", 0));
+
+        let (info, marker) = fx.recover();
+        assert!(!info.clean);
+        assert_eq!((info.crash_at, info.consecutive_unclean), (Some(1_000), 1));
+        assert_eq!(info.recovered, vec![format!("Cleared stale session lock (PID {})", pid)]);
+        assert_eq!((marker.pid, marker.last_crash_at), (std::process::id(), Some(1_000)));
+    }
+
+    #[test]
+    fn reused_pid_running_another_program_is_stale() {
+        let fx = Fixture::new();
+        let mut other = sleeper();
+        fx.previous(crashed(other.id(), "/opt/lumina/lumina", 0));
+
+        let (info, _) = fx.recover();
+        other.kill().unwrap();
+        other.wait().unwrap();
+        assert!(!info.clean && !info.other_instance);
+    }
+
+    #[test]
+    fn live_instance_keeps_its_lock() {
+        let fx = Fixture::new();
+        let mut instance = sleeper();
+        fx.previous(crashed(instance.id(), "/usr/bin/sleep", 2));
+
+        let (info, marker) = fx.recover();
+        instance.kill().unwrap();
+        instance.wait().unwrap();
+        assert!(info.other_instance && info.clean);
+        assert_eq!(marker.pid, instance.id());
+        let on_disk: SessionMarker = read_json(&fx.marker).unwrap();
+        assert_eq!(on_disk.pid, instance.id());
+    }
+
+    #[test]
+    fn repeated_crashes_start_safe_mode_and_a_clean_exit_resets() {
+        let fx = Fixture::new();
+        fx.previous(crashed(dead_pid(), "/opt/lumina/lumina", SAFE_MODE_THRESHOLD - 2));
+        assert!(!fx.recover().0.safe_mode);
+
+        fx.previous(crashed(dead_pid(), "/opt/lumina/lumina", SAFE_MODE_THRESHOLD - 1));
+        let (info, marker) = fx.recover();
+        assert!(info.safe_mode);
+        assert_eq!(marker.consecutive_unclean, SAFE_MODE_THRESHOLD);
+
+        fx.previous(SessionMarker { clean_exit: true, ..crashed(dead_pid(), "/opt/lumina/lumina", 0) });
+        let (info, marker) = fx.recover();
+        assert!(info.clean && !info.safe_mode);
+        assert_eq!(marker.consecutive_unclean, 0);
+    }
+
+    #[test]
+    fn orphaned_backends_are_stopped_and_dead_ones_dropped() {
+        let fx = Fixture::new();
+        let mut orphan = sleeper();
+        let dead = dead_pid();
+        write_json(
+            &fx.pids,
+            &vec![
+                BackendProcess { pid: orphan.id(), script: "sleep".to_string() },
+                BackendProcess { pid: dead, script: "tts_api.py".to_string() },
+            ],
+        )
+        .unwrap();
+
+        let (info, _) = fx.recover();
+        // Killed by the recovery pass, so this returns at once
+        let status = orphan.wait().unwrap();
+        assert!(!status.success());
+        assert_eq!(
+            info.recovered,
+            vec![
+                format!("Stopped orphaned backend sleep (PID {})", orphan.id()),
+                format!("Dropped stale PID {} for tts_api.py", dead),
+            ]
+        );
+        assert!(!fx.pids.exists());
+    }
+
+    #[test]
+    fn partial_writes_are_discarded_and_originals_kept() {
+        let fx = Fixture::new();
+        let data = fx.marker.parent().unwrap();
+        fs::write(data.join("settings.json"), "{}").unwrap();
+        fs::write(data.join("settings.json.tmp"), "{\"ver").unwrap();
+
+        let (info, _) = fx.recover();
+        assert_eq!(info.recovered, vec!["Discarded partial write \"settings.json.tmp\"".to_string()]);
+        assert!(data.join("settings.json").exists());
+        assert!(!data.join("settings.json.tmp").exists());
+    }
+
+    #[test]
+    fn interrupted_imports_are_discarded() {
+        let fx = Fixture::new();
+        fs::create_dir_all(&fx.imports).unwrap();
+        fs::write(fx.imports.join("de.jsonl"), "{}\n{\"word").unwrap();
+
+        let (info, _) = fx.recover();
+        assert_eq!(info.recovered, vec!["Discarded interrupted import \"de.jsonl\"".to_string()]);
+        assert!(!fx.imports.exists());
+    }
+}
@@ -0,0 +1,300 @@
+use chrono::{Local, NaiveDate, TimeZone};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Every change to a term's review schedule, newest last, plus per-day
+/// totals. Raw events are kept for a year; the daily totals are kept for
+/// good, so charts over older periods still work. Recording only touches
+/// memory; a background thread writes the file when something changed.
+static REVIEW_LOG: Lazy<ReviewLogStore> = Lazy::new(ReviewLogStore::new);
+
+const FLUSH_INTERVAL: Duration = Duration::from_secs(10);
+const RAW_RETENTION_DAYS: i64 = 365;
+/// Grades from this up count as a correct answer
+const CORRECT_GRADE: u8 = 3;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReviewEvent {
+    pub term_id: String,
+    pub language: String,
+    pub timestamp: i64,
+    /// 0-5 for graded reviews; None when the schedule was set directly
+    pub grade: Option<u8>,
+    pub previous_interval: i32,
+    pub interval: i32,
+    pub ease_factor: f64,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DayTotals {
+    reviews: u64,
+    graded: u64,
+    correct: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DailyReviews {
+    /// Local calendar day, "YYYY-MM-DD"
+    pub date: String,
+    pub reviews: u64,
+    /// Share of graded reviews answered correctly, None without any
+    pub accuracy: Option<f64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ReviewLogData {
+    events: Vec<ReviewEvent>,
+    /// "YYYY-MM-DD" -> totals
+    daily: BTreeMap<String, DayTotals>,
+}
+
+struct ReviewLogStore {
+    path: Mutex<Option<PathBuf>>,
+    data: Mutex<ReviewLogData>,
+    dirty: AtomicBool,
+}
+
+impl ReviewLogStore {
+    fn new() -> Self {
+        Self {
+            path: Mutex::new(None),
+            data: Mutex::new(ReviewLogData::default()),
+            dirty: AtomicBool::new(false),
+        }
+    }
+}
+
+fn day_key(timestamp: i64) -> String {
+    chrono::DateTime::from_timestamp_millis(timestamp)
+        .map(|t| t.with_timezone(&Local).format("%Y-%m-%d").to_string())
+        .unwrap_or_default()
+}
+
+/// Drop raw events past the retention window
+fn compact(data: &mut ReviewLogData, now: i64) {
+    let cutoff = now - RAW_RETENTION_DAYS * 24 * 60 * 60 * 1000;
+    let expired = data.events.iter().take_while(|e| e.timestamp < cutoff).count();
+    data.events.drain(..expired);
+}
+
+pub fn record(event: ReviewEvent) {
+    let mut data = REVIEW_LOG.data.lock().unwrap();
+    let totals = data.daily.entry(day_key(event.timestamp)).or_default();
+    totals.reviews += 1;
+    if let Some(grade) = event.grade {
+        totals.graded += 1;
+        if grade >= CORRECT_GRADE {
+            totals.correct += 1;
+        }
+    }
+    let now = event.timestamp;
+    data.events.push(event);
+    compact(&mut data, now);
+    REVIEW_LOG.dirty.store(true, Ordering::Relaxed);
+}
+
+/// Newest first, optionally for one term and from `since` (ms) on
+pub fn list(term_id: Option<&str>, since: Option<i64>, limit: usize) -> Vec<ReviewEvent> {
+    let data = REVIEW_LOG.data.lock().unwrap();
+    data.events
+        .iter()
+        .rev()
+        .take_while(|e| since.is_none_or(|since| e.timestamp >= since))
+        .filter(|e| term_id.is_none_or(|id| e.term_id == id))
+        .take(limit)
+        .cloned()
+        .collect()
+}
+
+/// Reviews and accuracy for each of the last `days` local days, oldest
+/// first, days without reviews included
+pub fn per_day(days: u32) -> Vec<DailyReviews> {
+    let data = REVIEW_LOG.data.lock().unwrap();
+    let today = Local::now().date_naive();
+    (0..days as i64)
+        .rev()
+        .map(|ago| {
+            let date = (today - chrono::Duration::days(ago)).format("%Y-%m-%d").to_string();
+            let totals = data.daily.get(&date).copied().unwrap_or_default();
+            DailyReviews {
+                date,
+                reviews: totals.reviews,
+                accuracy: (totals.graded > 0).then(|| totals.correct as f64 / totals.graded as f64),
+            }
+        })
+        .collect()
+}
+
+/// Review count per local calendar day. Days still covered by raw events
+/// are counted from them in the current timezone, so they follow a
+/// timezone change; older days come from the stored totals.
+pub fn reviews_by_day() -> BTreeMap<NaiveDate, u64> {
+    reviews_by_day_in(&REVIEW_LOG.data.lock().unwrap(), &Local)
+}
+
+/// `reviews_by_day` with raw events counted on their day in `tz`
+fn reviews_by_day_in<Tz: TimeZone>(data: &ReviewLogData, tz: &Tz) -> BTreeMap<NaiveDate, u64> {
+    let local_date = |timestamp: i64| {
+        chrono::DateTime::from_timestamp_millis(timestamp).map(|t| t.with_timezone(tz).date_naive())
+    };
+    // Totals are keyed by the day in the timezone at record time, which can
+    // be a day before the first raw event's day here; leave that day to the
+    // raw events too rather than count them twice
+    let first_raw_day = data
+        .events
+        .first()
+        .and_then(|e| local_date(e.timestamp))
+        .and_then(|day| day.pred_opt());
+    let mut days: BTreeMap<NaiveDate, u64> = data
+        .daily
+        .iter()
+        .filter(|(_, totals)| totals.reviews > 0)
+        .filter_map(|(day, totals)| Some((NaiveDate::parse_from_str(day, "%Y-%m-%d").ok()?, totals.reviews)))
+        .filter(|(day, _)| first_raw_day.is_none_or(|first| *day < first))
+        .collect();
+    for day in data.events.iter().filter_map(|e| local_date(e.timestamp)) {
+        *days.entry(day).or_insert(0) += 1;
+    }
+    days
+}
+
+/// Handle a deleted term's events: drop them, or with `keep` keep them
+/// under an id that no longer names the term
+pub fn forget_term(term_id: &str, keep: bool) {
+    let mut data = REVIEW_LOG.data.lock().unwrap();
+    if keep {
+        let anonymous = format!("deleted:{:016x}", {
+            use std::hash::{Hash, Hasher};
+            let mut hasher = std::collections::hash_map::DefaultHasher::new();
+            term_id.hash(&mut hasher);
+            hasher.finish()
+        });
+        for event in data.events.iter_mut().filter(|e| e.term_id == term_id) {
+            event.term_id = anonymous.clone();
+        }
+    } else {
+        data.events.retain(|e| e.term_id != term_id);
+    }
+    REVIEW_LOG.dirty.store(true, Ordering::Relaxed);
+}
+
+fn write_atomic(path: &Path, data: &ReviewLogData) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create review log directory: {}", e))?;
+    }
+    let content = serde_json::to_string(data).map_err(|e| format!("Failed to serialize review log: {}", e))?;
+    let tmp_path = path.with_extension("json.tmp");
+    fs::write(&tmp_path, content).map_err(|e| format!("Failed to write review log: {}", e))?;
+    fs::rename(&tmp_path, path).map_err(|e| format!("Failed to replace review log: {}", e))
+}
+
+/// Write the file if anything changed since the last flush
+pub fn flush() {
+    let Some(path) = REVIEW_LOG.path.lock().unwrap().clone() else {
+        return;
+    };
+    if !REVIEW_LOG.dirty.swap(false, Ordering::Relaxed) {
+        return;
+    }
+    let snapshot = REVIEW_LOG.data.lock().unwrap().clone();
+    if let Err(e) = write_atomic(&path, &snapshot) {
+        eprintln!("[REVIEWS] {}", e);
+        REVIEW_LOG.dirty.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Load the log from `path`, keep anything recorded before startup, and
+/// flush periodically
+pub fn init(path: PathBuf) {
+    let mut stored: ReviewLogData = fs::read_to_string(&path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default();
+    {
+        let mut data = REVIEW_LOG.data.lock().unwrap();
+        let early = std::mem::take(&mut *data);
+        stored.events.extend(early.events);
+        for (day, totals) in early.daily {
+            let merged = stored.daily.entry(day).or_default();
+            merged.reviews += totals.reviews;
+            merged.graded += totals.graded;
+            merged.correct += totals.correct;
+        }
+        compact(&mut stored, chrono::Utc::now().timestamp_millis());
+        *data = stored;
+    }
+    *REVIEW_LOG.path.lock().unwrap() = Some(path);
+    REVIEW_LOG.dirty.store(true, Ordering::Relaxed);
+    flush();
+
+    std::thread::spawn(|| loop {
+        std::thread::sleep(FLUSH_INTERVAL);
+        flush();
+    });
+}
+
+pub fn get_review_log_path(app: &tauri::AppHandle) -> PathBuf {
+    use tauri::Manager;
+    let base_dir = app.path()
+        .app_data_dir()
+        .unwrap_or_else(|_| std::env::current_dir().unwrap_or_else(|_| PathBuf::from(".")));
+    base_dir.join("data").join("review_log.json")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(timestamp: i64) -> ReviewEvent {
+        ReviewEvent {
+            term_id: "t".to_string(),
+            language: "de".to_string(),
+            timestamp,
+            grade: Some(4),
+            previous_interval: 1,
+            interval: 3,
+            ease_factor: 2.5,
+        }
+    }
+
+    fn utc_ms(time: &str) -> i64 {
+        chrono::NaiveDateTime::parse_from_str(time, "%Y-%m-%d %H:%M:%S").unwrap().and_utc().timestamp_millis()
+    }
+
+    fn day(date: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(date, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn raw_reviews_are_counted_in_the_current_timezone() {
+        let mut data = ReviewLogData::default();
+        // Only totals remain for the 1st; the rest are raw events
+        data.daily.insert("2026-03-01".to_string(), DayTotals { reviews: 4, graded: 4, correct: 3 });
+        data.daily.insert("2026-03-09".to_string(), DayTotals { reviews: 2, graded: 2, correct: 2 });
+        data.events = vec![event(utc_ms("2026-03-09 22:30:00")), event(utc_ms("2026-03-09 23:30:00"))];
+
+        let utc = chrono::FixedOffset::east_opt(0).unwrap();
+        let by_day = reviews_by_day_in(&data, &utc);
+        assert_eq!(by_day, BTreeMap::from([(day("2026-03-01"), 4), (day("2026-03-09"), 2)]));
+
+        // An hour east, the second review moved past midnight
+        let berlin = chrono::FixedOffset::east_opt(3600).unwrap();
+        let by_day = reviews_by_day_in(&data, &berlin);
+        assert_eq!(by_day, BTreeMap::from([(day("2026-03-01"), 4), (day("2026-03-09"), 1), (day("2026-03-10"), 1)]));
+
+        // Stored totals for the day the raw events started on in the old
+        // timezone are not counted on top of them
+        let tokyo = chrono::FixedOffset::east_opt(9 * 3600).unwrap();
+        let by_day = reviews_by_day_in(&data, &tokyo);
+        assert_eq!(by_day, BTreeMap::from([(day("2026-03-01"), 4), (day("2026-03-10"), 2)]));
+    }
+}
@@ -0,0 +1,166 @@
+use once_cell::sync::Lazy;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, RwLock};
+
+/// Results kept in memory; the least recently used go first
+const MEMORY_CAPACITY: usize = 4_000;
+/// Results kept on disk; the oldest go first
+const DISK_CAPACITY: i64 = 50_000;
+/// Disk pruning runs every this many writes
+const PRUNE_EVERY: u64 = 200;
+/// Longer inputs aren't worth keeping (whole passages rarely repeat)
+const MAX_KEY_CHARS: usize = 1_000;
+
+/// Split and transliteration results by request. The worker's answers are
+/// deterministic, so a hit skips the Python round trip entirely.
+static MEMORY: Lazy<Mutex<Lru>> = Lazy::new(|| Mutex::new(Lru::default()));
+static DISK_PATH: Lazy<RwLock<Option<PathBuf>>> = Lazy::new(|| RwLock::new(None));
+static DISK_WRITES: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Default)]
+struct Lru {
+    entries: HashMap<String, (u64, Value)>,
+    /// Last use → key, oldest first
+    order: BTreeMap<u64, String>,
+    tick: u64,
+}
+
+impl Lru {
+    fn get(&mut self, key: &str) -> Option<Value> {
+        self.tick += 1;
+        let tick = self.tick;
+        let (used, value) = self.entries.get_mut(key)?;
+        self.order.remove(used);
+        self.order.insert(tick, key.to_string());
+        *used = tick;
+        Some(value.clone())
+    }
+
+    fn put(&mut self, key: String, value: Value) {
+        self.tick += 1;
+        if let Some((used, _)) = self.entries.remove(&key) {
+            self.order.remove(&used);
+        }
+        while self.entries.len() >= MEMORY_CAPACITY {
+            let Some((_, oldest)) = self.order.pop_first() else { break };
+            self.entries.remove(&oldest);
+        }
+        self.order.insert(self.tick, key.clone());
+        self.entries.insert(key, (self.tick, value));
+    }
+
+    fn clear(&mut self) -> usize {
+        let count = self.entries.len();
+        self.entries.clear();
+        self.order.clear();
+        count
+    }
+}
+
+/// Cache key for a request. Every parameter that changes the answer is
+/// part of it, so "slp1→iast" and "hk→iast" of the same text stay apart.
+pub fn key(action: &str, parts: &[&str]) -> String {
+    // U+001F can't occur in the inputs, so ("a", "bc") and ("ab", "c") differ
+    std::iter::once(action).chain(parts.iter().copied()).collect::<Vec<_>>().join("\u{1f}")
+}
+
+pub fn get_cache_path(app: &tauri::AppHandle) -> PathBuf {
+    use tauri::Manager;
+    let base_dir = app.path()
+        .app_data_dir()
+        .unwrap_or_else(|_| std::env::current_dir().unwrap_or_else(|_| PathBuf::from(".")));
+    base_dir.join("data").join("sanskrit_cache.db")
+}
+
+pub fn init(path: PathBuf) {
+    *DISK_PATH.write().unwrap() = Some(path);
+}
+
+fn open_disk() -> Option<Connection> {
+    let path = DISK_PATH.read().unwrap().clone()?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).ok()?;
+    }
+    let conn = Connection::open(&path)
+        .map_err(|e| eprintln!("[SANSKRIT] Cache unavailable: {}", e))
+        .ok()?;
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS results (
+            key TEXT PRIMARY KEY,
+            stored_at INTEGER NOT NULL,
+            value TEXT NOT NULL
+        )",
+        [],
+    )
+    .ok()?;
+    Some(conn)
+}
+
+/// The cached result for `key`. With `persist`, a memory miss falls back to
+/// the disk cache and the hit is kept in memory.
+pub fn get(key: &str, persist: bool) -> Option<Value> {
+    if let Some(value) = MEMORY.lock().unwrap().get(key) {
+        return Some(value);
+    }
+    if !persist {
+        return None;
+    }
+    let conn = open_disk()?;
+    let json: String = conn
+        .query_row("SELECT value FROM results WHERE key = ?1", params![key], |r| r.get(0))
+        .optional()
+        .ok()??;
+    let value: Value = serde_json::from_str(&json).ok()?;
+    MEMORY.lock().unwrap().put(key.to_string(), value.clone());
+    Some(value)
+}
+
+/// Remember a successful result; with `persist` it is written to disk too
+pub fn put(key: String, value: Value, persist: bool) {
+    if key.chars().count() > MAX_KEY_CHARS {
+        return;
+    }
+    if persist {
+        if let Err(e) = write_disk(&key, &value) {
+            eprintln!("[SANSKRIT] Cache write failed: {}", e);
+        }
+    }
+    MEMORY.lock().unwrap().put(key, value);
+}
+
+fn write_disk(key: &str, value: &Value) -> Result<(), String> {
+    let Some(conn) = open_disk() else { return Ok(()) };
+    conn.execute(
+        "INSERT OR REPLACE INTO results (key, stored_at, value) VALUES (?1, ?2, ?3)",
+        params![key, chrono::Utc::now().timestamp_millis(), value.to_string()],
+    )
+    .map_err(|e| e.to_string())?;
+    if DISK_WRITES.fetch_add(1, Ordering::Relaxed).is_multiple_of(PRUNE_EVERY) {
+        conn.execute(
+            "DELETE FROM results WHERE key IN (
+                SELECT key FROM results ORDER BY stored_at DESC LIMIT -1 OFFSET ?1
+            )",
+            params![DISK_CAPACITY],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Drop every cached result; returns how many were in memory and on disk
+pub fn clear() -> Result<(usize, usize), String> {
+    let memory = MEMORY.lock().unwrap().clear();
+    let path = DISK_PATH.read().unwrap().clone();
+    let disk = match path {
+        Some(path) if path.exists() => open_disk()
+            .ok_or("Sanskrit cache could not be opened")?
+            .execute("DELETE FROM results", [])
+            .map_err(|e| e.to_string())?,
+        _ => 0,
+    };
+    Ok((memory, disk))
+}
@@ -0,0 +1,459 @@
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::io::{BufRead, BufReader, ErrorKind, Write};
+use std::process::{Child, ChildStdin, Stdio};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::oneshot;
+use crate::{paths, python};
+
+/// How often a waiting request checks its cancel flag
+const CANCEL_POLL: Duration = Duration::from_millis(100);
+/// Wait before the first respawn after a crash; doubles per crash in a row
+const MIN_RESPAWN_DELAY: Duration = Duration::from_millis(500);
+const MAX_RESPAWN_DELAY: Duration = Duration::from_secs(60);
+/// Grace period for the worker to exit once its stdin is closed
+const SHUTDOWN_GRACE: Duration = Duration::from_secs(1);
+/// Stderr lines kept for crash reports
+const STDERR_TAIL_LINES: usize = 20;
+
+/// Why a worker request produced no answer
+#[derive(Debug, Clone)]
+pub enum WorkerError {
+    /// No answer in time; the worker was killed so the next request gets a fresh one
+    Timeout(Duration),
+    /// Cancelled by the caller; the worker was killed
+    Cancelled,
+    PythonNotFound(String),
+    /// The process died; `stderr` is the tail of what it printed
+    Crashed { status: String, stderr: String },
+    /// The worker was killed over someone else's request
+    Interrupted(String),
+    /// The script answered with `success: false`
+    Failed(String),
+    /// Not running and can't be started right now (backoff, shutdown)
+    Unavailable(String),
+}
+
+impl WorkerError {
+    /// Stable name for the frontend: "timeout", "cancelled", "python_not_found",
+    /// "crashed", "interrupted", "failed" or "unavailable"
+    pub fn kind(&self) -> &'static str {
+        match self {
+            WorkerError::Timeout(_) => "timeout",
+            WorkerError::Cancelled => "cancelled",
+            WorkerError::PythonNotFound(_) => "python_not_found",
+            WorkerError::Crashed { .. } => "crashed",
+            WorkerError::Interrupted(_) => "interrupted",
+            WorkerError::Failed(_) => "failed",
+            WorkerError::Unavailable(_) => "unavailable",
+        }
+    }
+}
+
+impl fmt::Display for WorkerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WorkerError::Timeout(after) => write!(f, "Sanskrit worker timed out after {}ms", after.as_millis()),
+            WorkerError::Cancelled => write!(f, "Cancelled"),
+            WorkerError::PythonNotFound(e) => write!(f, "Python not found: {}", e),
+            WorkerError::Crashed { status, stderr } if stderr.is_empty() => {
+                write!(f, "Sanskrit script crashed ({}) without output", status)
+            }
+            WorkerError::Crashed { status, stderr } => {
+                write!(f, "Sanskrit script crashed ({}) with stderr:\n{}", status, stderr)
+            }
+            WorkerError::Interrupted(why) => write!(f, "Sanskrit worker was restarted: {}", why),
+            WorkerError::Failed(e) | WorkerError::Unavailable(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+/// One long-running `sanskrit_cli.py --server` process, so split,
+/// transliterate and process calls don't pay for a Python cold start each.
+/// Requests and responses are newline-delimited JSON matched by id. The
+/// process is spawned on first use and respawned after a crash, waiting
+/// longer after each crash in a row.
+pub struct SanskritWorker {
+    shared: Arc<Shared>,
+}
+
+struct Shared {
+    process: Mutex<Option<WorkerProcess>>,
+    pending: Mutex<HashMap<u64, oneshot::Sender<Result<Value, WorkerError>>>>,
+    stderr_tail: Mutex<VecDeque<String>>,
+    next_id: AtomicU64,
+    served: AtomicU64,
+    spawns: AtomicU64,
+    crashes: Mutex<CrashState>,
+    shutting_down: AtomicBool,
+}
+
+struct WorkerProcess {
+    child: Child,
+    stdin: ChildStdin,
+    started_at: Instant,
+    /// Which start this is, so a stale reader thread leaves a newer process alone
+    generation: u64,
+}
+
+#[derive(Default)]
+struct CrashState {
+    /// Crashes since the last answered request
+    in_a_row: u32,
+    last_error: Option<String>,
+    /// No respawn before this
+    respawn_at: Option<Instant>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SanskritWorkerStatus {
+    pub alive: bool,
+    pub pid: Option<u32>,
+    /// Requests answered since the app started
+    pub served: u64,
+    /// Times the process was (re)started
+    pub spawns: u64,
+    pub pending: usize,
+    pub uptime_ms: Option<u64>,
+    pub last_error: Option<String>,
+    /// Set while a respawn is held back after a crash
+    pub respawn_in_ms: Option<u64>,
+}
+
+impl SanskritWorker {
+    pub fn new() -> Self {
+        Self {
+            shared: Arc::new(Shared {
+                process: Mutex::new(None),
+                pending: Mutex::new(HashMap::new()),
+                stderr_tail: Mutex::new(VecDeque::new()),
+                next_id: AtomicU64::new(1),
+                served: AtomicU64::new(0),
+                spawns: AtomicU64::new(0),
+                crashes: Mutex::new(CrashState::default()),
+                shutting_down: AtomicBool::new(false),
+            }),
+        }
+    }
+
+    /// Start the process now so the first request doesn't wait for it
+    pub fn warm_up(&self) {
+        if let Err(e) = self.shared.ensure_running() {
+            eprintln!("[SANSKRIT] Worker not started: {}", e);
+        }
+    }
+
+    /// Send `request` (an object with "action" and its arguments) and wait
+    /// for the worker's answer. Past `timeout`, or once `cancel` is set, the
+    /// worker is killed: Python can't abandon a call halfway, and a stuck
+    /// process would hold up every request queued behind it.
+    pub async fn request(
+        &self,
+        mut request: Value,
+        timeout: Duration,
+        cancel: Option<&AtomicBool>,
+    ) -> Result<Value, WorkerError> {
+        let deadline = tokio::time::Instant::now() + timeout;
+        if let Some(wait) = self.shared.respawn_wait() {
+            if wait > timeout {
+                return Err(WorkerError::Unavailable(format!(
+                    "Sanskrit worker keeps crashing; next restart in {}s",
+                    wait.as_secs()
+                )));
+            }
+            tokio::time::sleep(wait).await;
+        }
+
+        let id = self.shared.next_id.fetch_add(1, Ordering::Relaxed);
+        request["id"] = Value::from(id);
+        let (sender, mut receiver) = oneshot::channel();
+        self.shared.pending.lock().unwrap().insert(id, sender);
+        if let Err(e) = self.shared.send(&request) {
+            self.shared.pending.lock().unwrap().remove(&id);
+            return Err(e);
+        }
+
+        loop {
+            let tick = tokio::time::sleep_until(deadline.min(tokio::time::Instant::now() + CANCEL_POLL));
+            tokio::select! {
+                answer = &mut receiver => {
+                    return answer.unwrap_or_else(|_| {
+                        Err(WorkerError::Interrupted("the worker went away".to_string()))
+                    });
+                }
+                _ = tick => {
+                    if cancel.is_some_and(|c| c.load(Ordering::Relaxed)) {
+                        self.shared.abandon(id, "a request was cancelled");
+                        return Err(WorkerError::Cancelled);
+                    }
+                    if tokio::time::Instant::now() >= deadline {
+                        self.shared.abandon(id, "a request timed out");
+                        return Err(WorkerError::Timeout(timeout));
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn status(&self) -> SanskritWorkerStatus {
+        let mut process = self.shared.process.lock().unwrap();
+        let alive = process.as_mut().is_some_and(|p| matches!(p.child.try_wait(), Ok(None)));
+        let running = process.as_ref().filter(|_| alive);
+        let crashes = self.shared.crashes.lock().unwrap();
+        SanskritWorkerStatus {
+            alive: running.is_some(),
+            pid: running.as_ref().map(|p| p.child.id()),
+            served: self.shared.served.load(Ordering::Relaxed),
+            spawns: self.shared.spawns.load(Ordering::Relaxed),
+            pending: self.shared.pending.lock().unwrap().len(),
+            uptime_ms: running.as_ref().map(|p| p.started_at.elapsed().as_millis() as u64),
+            last_error: crashes.last_error.clone(),
+            respawn_in_ms: crashes
+                .respawn_at
+                .and_then(|at| at.checked_duration_since(Instant::now()))
+                .map(|d| d.as_millis() as u64),
+        }
+    }
+
+    /// Stop the current process, e.g. after the interpreter changed; the
+    /// next request starts a new one
+    pub fn restart(&self, why: &str) {
+        let mut process = self.shared.process.lock().unwrap();
+        if process.is_some() {
+            eprintln!("[SANSKRIT] Restarting worker: {}", why);
+            self.shared.retire(&mut process, WorkerError::Interrupted(why.to_string()), false);
+        }
+    }
+
+    /// Close the worker's stdin so it exits on its own, killing it if it
+    /// doesn't within the grace period
+    pub fn shutdown(&self) {
+        self.shared.shutting_down.store(true, Ordering::SeqCst);
+        let Some(WorkerProcess { mut child, stdin, .. }) = self.shared.process.lock().unwrap().take() else {
+            return;
+        };
+        drop(stdin);
+        let deadline = Instant::now() + SHUTDOWN_GRACE;
+        while Instant::now() < deadline {
+            if matches!(child.try_wait(), Ok(Some(_))) {
+                return;
+            }
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        let _ = child.kill();
+        let _ = child.wait();
+    }
+}
+
+impl Shared {
+    /// Time left before a crashed worker may be respawned
+    fn respawn_wait(&self) -> Option<Duration> {
+        self.crashes
+            .lock()
+            .unwrap()
+            .respawn_at
+            .and_then(|at| at.checked_duration_since(Instant::now()))
+    }
+
+    fn record_crash(&self, error: String) {
+        if self.shutting_down.load(Ordering::SeqCst) {
+            return;
+        }
+        let mut crashes = self.crashes.lock().unwrap();
+        let delay = MIN_RESPAWN_DELAY
+            .saturating_mul(2u32.saturating_pow(crashes.in_a_row))
+            .min(MAX_RESPAWN_DELAY);
+        crashes.in_a_row += 1;
+        crashes.respawn_at = Some(Instant::now() + delay);
+        eprintln!("[SANSKRIT] {}; restarting in {}ms", error, delay.as_millis());
+        crashes.last_error = Some(error);
+    }
+
+    fn stderr_tail(&self) -> String {
+        self.stderr_tail.lock().unwrap().iter().cloned().collect::<Vec<_>>().join("\n")
+    }
+
+    /// Kill the current process and fail everything still waiting on it.
+    /// Only `crashed` retirements count towards the respawn backoff.
+    fn retire(&self, process: &mut Option<WorkerProcess>, error: WorkerError, crashed: bool) {
+        if let Some(mut running) = process.take() {
+            let _ = running.child.kill();
+            let _ = running.child.wait();
+        }
+        let waiting = std::mem::take(&mut *self.pending.lock().unwrap());
+        if !waiting.is_empty() {
+            eprintln!("[SANSKRIT] {} request(s) failed: {}", waiting.len(), error);
+        }
+        for sender in waiting.into_values() {
+            let _ = sender.send(Err(error.clone()));
+        }
+        if crashed {
+            self.record_crash(error.to_string());
+        }
+    }
+
+    /// Give up on request `id`. If it is still unanswered the worker is busy
+    /// with it, so the process is killed and respawned on the next request.
+    fn abandon(&self, id: u64, why: &str) {
+        if self.pending.lock().unwrap().remove(&id).is_none() {
+            return;
+        }
+        let mut process = self.process.lock().unwrap();
+        eprintln!("[SANSKRIT] Killing worker: {}", why);
+        self.retire(&mut process, WorkerError::Interrupted(why.to_string()), false);
+    }
+
+    /// Start the process unless it is running
+    fn ensure_running(self: &Arc<Self>) -> Result<(), WorkerError> {
+        let mut process = self.process.lock().unwrap();
+        if let Some(running) = process.as_mut() {
+            match running.child.try_wait() {
+                Ok(None) => return Ok(()),
+                Ok(Some(status)) => {
+                    let error = WorkerError::Crashed { status: status.to_string(), stderr: self.stderr_tail() };
+                    self.retire(&mut process, error, true);
+                }
+                Err(e) => {
+                    let error = WorkerError::Crashed { status: e.to_string(), stderr: self.stderr_tail() };
+                    self.retire(&mut process, error, true);
+                }
+            }
+        }
+        if self.shutting_down.load(Ordering::SeqCst) {
+            return Err(WorkerError::Unavailable("Shutting down".to_string()));
+        }
+        if let Some(wait) = self.respawn_wait() {
+            return Err(WorkerError::Unavailable(format!(
+                "Sanskrit worker restarts in {}ms",
+                wait.as_millis()
+            )));
+        }
+        *process = Some(self.spawn()?);
+        Ok(())
+    }
+
+    fn spawn(self: &Arc<Self>) -> Result<WorkerProcess, WorkerError> {
+        let script = paths::script_path("sanskrit_cli.py").map_err(WorkerError::Unavailable)?;
+        let mut cmd = python::resolve().map_err(WorkerError::PythonNotFound)?.python();
+        cmd.arg(&script)
+            .arg("--server")
+            .env("PYTHONUNBUFFERED", "1")
+            .env("PYTHONIOENCODING", "utf-8")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        let mut child = cmd.spawn().map_err(|e| match e.kind() {
+            ErrorKind::NotFound => WorkerError::PythonNotFound(e.to_string()),
+            _ => WorkerError::Unavailable(format!("Failed to start Sanskrit worker ({}): {}", script.display(), e)),
+        })?;
+        let unavailable = |what: &str| WorkerError::Unavailable(format!("Sanskrit worker has no {}", what));
+        let stdin = child.stdin.take().ok_or_else(|| unavailable("stdin"))?;
+        let stdout = child.stdout.take().ok_or_else(|| unavailable("stdout"))?;
+        let stderr = child.stderr.take();
+        let generation = self.spawns.fetch_add(1, Ordering::Relaxed) + 1;
+        eprintln!(
+            "[SANSKRIT] Worker started (pid {}, start #{}, {})",
+            child.id(),
+            generation,
+            script.display()
+        );
+        self.stderr_tail.lock().unwrap().clear();
+
+        let shared = Arc::clone(self);
+        let stderr_thread = stderr.map(|stderr| {
+            std::thread::spawn(move || {
+                for line in BufReader::new(stderr).lines().map_while(Result::ok) {
+                    eprintln!("[SANSKRIT] {}", line);
+                    let mut tail = shared.stderr_tail.lock().unwrap();
+                    if tail.len() == STDERR_TAIL_LINES {
+                        tail.pop_front();
+                    }
+                    tail.push_back(line);
+                }
+            })
+        });
+
+        let shared = Arc::clone(self);
+        std::thread::spawn(move || {
+            for line in BufReader::new(stdout).lines() {
+                let Ok(line) = line else { break };
+                let Ok(response) = serde_json::from_str::<Value>(&line) else {
+                    eprintln!("[SANSKRIT] {}", line);
+                    continue;
+                };
+                let Some(id) = response.get("id").and_then(Value::as_u64) else {
+                    eprintln!("[SANSKRIT] Response without id: {}", line);
+                    continue;
+                };
+                let sender = shared.pending.lock().unwrap().remove(&id);
+                if let Some(sender) = sender {
+                    shared.served.fetch_add(1, Ordering::Relaxed);
+                    let mut crashes = shared.crashes.lock().unwrap();
+                    crashes.in_a_row = 0;
+                    crashes.respawn_at = None;
+                    drop(crashes);
+                    let _ = sender.send(Ok(response));
+                }
+            }
+
+            // stdout closed: the process is gone. Unless it was killed on
+            // purpose (then it is no longer current), fail whatever was
+            // waiting with its stderr; the next request respawns it.
+            if let Some(handle) = stderr_thread {
+                let _ = handle.join();
+            }
+            let mut process = shared.process.lock().unwrap();
+            let Some(running) = process.as_mut().filter(|p| p.generation == generation) else {
+                return;
+            };
+            let mut status = None;
+            // Give the exit status a moment to become visible
+            for _ in 0..50 {
+                if let Ok(Some(exit)) = running.child.try_wait() {
+                    status = Some(exit.to_string());
+                    break;
+                }
+                std::thread::sleep(Duration::from_millis(10));
+            }
+            let error = WorkerError::Crashed {
+                status: status.unwrap_or_else(|| "output closed".to_string()),
+                stderr: shared.stderr_tail(),
+            };
+            shared.retire(&mut process, error, true);
+        });
+
+        Ok(WorkerProcess {
+            child,
+            stdin,
+            started_at: Instant::now(),
+            generation,
+        })
+    }
+
+    fn send(self: &Arc<Self>, request: &Value) -> Result<(), WorkerError> {
+        self.ensure_running()?;
+        let mut process = self.process.lock().unwrap();
+        let running = process
+            .as_mut()
+            .ok_or_else(|| WorkerError::Unavailable("Sanskrit worker is not running".to_string()))?;
+        let mut line = serde_json::to_string(request).map_err(|e| WorkerError::Failed(e.to_string()))?;
+        line.push('\n');
+        if let Err(e) = running.stdin.write_all(line.as_bytes()).and_then(|()| running.stdin.flush()) {
+            let status = running
+                .child
+                .try_wait()
+                .ok()
+                .flatten()
+                .map(|s| s.to_string())
+                .unwrap_or_else(|| format!("write failed: {}", e));
+            let error = WorkerError::Crashed { status, stderr: self.stderr_tail() };
+            self.retire(&mut process, error.clone(), true);
+            return Err(error);
+        }
+        Ok(())
+    }
+}
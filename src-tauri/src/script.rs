@@ -0,0 +1,338 @@
+use serde::{Deserialize, Serialize};
+
+/// Unicode script of a character, coarse enough to route lookups
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Script {
+    Latin,
+    Cyrillic,
+    Greek,
+    Devanagari,
+    Arabic,
+    Hebrew,
+    Thai,
+    Hangul,
+    Kana,
+    Han,
+    /// Whitespace, punctuation and digits: joins runs, never dominant
+    Common,
+    /// Emoji, symbols and anything else we can't look up
+    Other,
+}
+
+pub fn script_of(c: char) -> Script {
+    let cp = c as u32;
+    if c.is_whitespace() || c.is_ascii_punctuation() || c.is_numeric() {
+        return Script::Common;
+    }
+    match cp {
+        0x0041..=0x005A | 0x0061..=0x007A | 0x00C0..=0x024F | 0x1E00..=0x1EFF => Script::Latin,
+        0x0370..=0x03FF | 0x1F00..=0x1FFF => Script::Greek,
+        0x0400..=0x052F => Script::Cyrillic,
+        0x0590..=0x05FF => Script::Hebrew,
+        0x0600..=0x06FF | 0x0750..=0x077F => Script::Arabic,
+        0x0900..=0x097F | 0xA8E0..=0xA8FF => Script::Devanagari,
+        0x0E00..=0x0E7F => Script::Thai,
+        0x1100..=0x11FF | 0xAC00..=0xD7AF => Script::Hangul,
+        0x3040..=0x30FF => Script::Kana,
+        0x4E00..=0x9FFF | 0x3400..=0x4DBF => Script::Han,
+        // Combining diacritics belong to whatever they follow
+        0x0300..=0x036F => Script::Common,
+        // General punctuation and CJK punctuation
+        0x2000..=0x206F | 0x3000..=0x303F | 0x00A0..=0x00BF => Script::Common,
+        _ => Script::Other,
+    }
+}
+
+/// Primary script a dictionary language is written in
+pub fn script_for_language(code: &str) -> Option<Script> {
+    match code {
+        "ru" | "uk" | "bg" | "sr" => Some(Script::Cyrillic),
+        "el" | "grc" => Some(Script::Greek),
+        "sa" | "hi" | "mr" | "ne" => Some(Script::Devanagari),
+        "ar" | "fa" | "ur" => Some(Script::Arabic),
+        "he" | "yi" => Some(Script::Hebrew),
+        "th" => Some(Script::Thai),
+        "ko" => Some(Script::Hangul),
+        "ja" => Some(Script::Kana),
+        "zh" => Some(Script::Han),
+        _ if code.len() <= 3 && code.chars().all(|c| c.is_ascii_lowercase()) => Some(Script::Latin),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScriptRun {
+    pub script: Script,
+    pub text: String,
+    /// Char offsets into the original string
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Split text into runs of a single script. Common characters between two
+/// runs of the same script stay inside that run, so "liebe Grüße" is one run.
+pub fn segment(text: &str) -> Vec<ScriptRun> {
+    let mut runs: Vec<ScriptRun> = Vec::new();
+
+    for (i, c) in text.chars().enumerate() {
+        let script = script_of(c);
+        // Japanese mixes kana and kanji within a word
+        let script = match (runs.last().map(|r| r.script), script) {
+            (Some(Script::Kana), Script::Han) => Script::Kana,
+            _ => script,
+        };
+
+        match runs.last_mut() {
+            Some(run) if run.script == script => {
+                run.text.push(c);
+                run.end = i + 1;
+            }
+            _ => runs.push(ScriptRun {
+                script,
+                text: c.to_string(),
+                start: i,
+                end: i + 1,
+            }),
+        }
+    }
+
+    // Absorb Common runs sandwiched between runs of the same script
+    let mut merged: Vec<ScriptRun> = Vec::new();
+    let mut i = 0;
+    while i < runs.len() {
+        let run = runs[i].clone();
+        if run.script == Script::Common {
+            let prev = merged.last().map(|r| r.script);
+            let next = runs.get(i + 1).map(|r| r.script);
+            if let (Some(p), Some(n)) = (prev, next) {
+                if p == n && p != Script::Common {
+                    let next_run = runs[i + 1].clone();
+                    let last = merged.last_mut().unwrap();
+                    last.text.push_str(&run.text);
+                    last.text.push_str(&next_run.text);
+                    last.end = next_run.end;
+                    i += 2;
+                    continue;
+                }
+            }
+        }
+        merged.push(run);
+        i += 1;
+    }
+
+    merged
+}
+
+/// Pick the run to look up: the longest run (by letters) whose script has a
+/// dictionary or pipeline available. Common/Other runs never qualify.
+pub fn dominant_run<'a>(runs: &'a [ScriptRun], available: &[Script]) -> Option<&'a ScriptRun> {
+    runs.iter()
+        .filter(|r| r.script != Script::Common && r.script != Script::Other)
+        .filter(|r| available.contains(&r.script))
+        .fold(None, |best: Option<&ScriptRun>, run| {
+            let letters = |r: &ScriptRun| r.text.chars().filter(|c| c.is_alphabetic()).count();
+            match best {
+                Some(b) if letters(b) >= letters(run) => Some(b),
+                _ => Some(run),
+            }
+        })
+}
+
+/// Payload of the `new-query` event sent to the floating window
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewQueryPayload {
+    /// The run that should be looked up
+    pub query: String,
+    /// Full text as copied, kept as context
+    pub original: String,
+    pub script: Script,
+    /// Language the run should be routed to, if one could be picked
+    pub language: Option<String>,
+    pub segments: Vec<ScriptRun>,
+    /// The lookup for (query, language) was already started in the background
+    #[serde(default)]
+    pub prefetched: bool,
+    /// Sentence of `original` the query was taken from, when there is more
+    /// to it than the query itself
+    #[serde(default)]
+    pub context: Option<String>,
+    /// What sent the query: "clipboard" or "floating"
+    #[serde(default)]
+    pub source: Option<String>,
+}
+
+/// A language Lumina can look words up in, with the script it is written in
+#[derive(Debug, Clone)]
+pub struct LookupTarget {
+    pub language: String,
+    pub script: Script,
+}
+
+fn ends_sentence(c: char) -> bool {
+    matches!(c, '.' | '!' | '?' | ';' | '\n' | '。' | '！' | '？' | '；' | '।' | '॥')
+}
+
+/// The script most of `text`'s letters are written in
+pub fn dominant_script(text: &str) -> Option<Script> {
+    let mut counts: Vec<(Script, usize)> = Vec::new();
+    for c in text.chars().filter(|c| c.is_alphabetic()) {
+        let script = script_of(c);
+        if script == Script::Common || script == Script::Other {
+            continue;
+        }
+        match counts.iter_mut().find(|(s, _)| *s == script) {
+            Some((_, n)) => *n += 1,
+            None => counts.push((script, 1)),
+        }
+    }
+    counts.into_iter().max_by_key(|(_, n)| *n).map(|(script, _)| script)
+}
+
+/// The sentence of `text` containing `word`, or None when `word` isn't in
+/// it or the sentence is just the word
+pub fn context_sentence(text: &str, word: &str) -> Option<String> {
+    let word = word.trim();
+    if word.is_empty() {
+        return None;
+    }
+    let start = text.find(word)?;
+    let end = start + word.len();
+    let from = text[..start]
+        .char_indices()
+        .rev()
+        .find(|(_, c)| ends_sentence(*c))
+        .map_or(0, |(i, c)| i + c.len_utf8());
+    let to = text[end..]
+        .char_indices()
+        .find(|(_, c)| ends_sentence(*c))
+        .map_or(text.len(), |(i, c)| end + i + c.len_utf8());
+    let sentence = text[from..to].split_whitespace().collect::<Vec<_>>().join(" ");
+    (sentence.trim_matches(|c: char| script_of(c) == Script::Common) != word).then_some(sentence)
+}
+
+/// Segment `text` and pick the run to look up. Returns None when no run is
+/// written in a script any target can handle (e.g. pure emoji).
+pub fn route_query(text: &str, targets: &[LookupTarget]) -> Option<NewQueryPayload> {
+    let segments = segment(text.trim());
+    let available: Vec<Script> = targets.iter().map(|t| t.script).collect();
+    let run = dominant_run(&segments, &available)?;
+
+    let query = run
+        .text
+        .trim_matches(|c: char| script_of(c) == Script::Common)
+        .to_string();
+    let language = targets
+        .iter()
+        .find(|t| t.script == run.script)
+        .map(|t| t.language.clone());
+
+    let script = run.script;
+    let context = context_sentence(text, &query);
+
+    Some(NewQueryPayload {
+        query,
+        original: text.to_string(),
+        script,
+        language,
+        segments,
+        prefetched: false,
+        context,
+        source: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn target(language: &str) -> LookupTarget {
+        LookupTarget {
+            language: language.to_string(),
+            script: script_for_language(language).unwrap(),
+        }
+    }
+
+    fn scripts(runs: &[ScriptRun]) -> Vec<(Script, &str)> {
+        runs.iter().map(|r| (r.script, r.text.as_str())).collect()
+    }
+
+    #[test]
+    fn latin_and_devanagari_are_separate_runs() {
+        let runs = segment("the word धर्म means duty");
+        assert_eq!(
+            scripts(&runs),
+            vec![
+                (Script::Latin, "the word"),
+                (Script::Common, " "),
+                (Script::Devanagari, "धर्म"),
+                (Script::Common, " "),
+                (Script::Latin, "means duty"),
+            ]
+        );
+        assert_eq!((runs[2].start, runs[2].end), (9, 13));
+    }
+
+    #[test]
+    fn devanagari_run_goes_to_the_sanskrit_pipeline() {
+        let text = "the word धर्म means duty";
+        let payload = route_query(text, &[target("sa")]).unwrap();
+        assert_eq!(payload.query, "धर्म");
+        assert_eq!(payload.script, Script::Devanagari);
+        assert_eq!(payload.language.as_deref(), Some("sa"));
+        assert_eq!(payload.original, text);
+        assert_eq!(payload.context.as_deref(), Some(text));
+        assert_eq!(payload.segments.len(), 5);
+    }
+
+    #[test]
+    fn longest_available_run_wins() {
+        // With English installed too, the longest Latin run is the lookup
+        let payload = route_query("the word धर्म means duty", &[target("en"), target("sa")]).unwrap();
+        assert_eq!(payload.query, "means duty");
+        assert_eq!(payload.language.as_deref(), Some("en"));
+    }
+
+    #[test]
+    fn latin_and_cyrillic_route_to_the_cyrillic_dictionary() {
+        let payload = route_query("love — любовь", &[target("de"), target("ru")]).unwrap();
+        assert_eq!(payload.query, "любовь");
+        assert_eq!(payload.script, Script::Cyrillic);
+        assert_eq!(payload.language.as_deref(), Some("ru"));
+        assert_eq!(
+            scripts(&payload.segments),
+            vec![(Script::Latin, "love"), (Script::Common, " — "), (Script::Cyrillic, "любовь")]
+        );
+
+        // Without a Cyrillic dictionary the Latin run is all that's left
+        let payload = route_query("love — любовь", &[target("de")]).unwrap();
+        assert_eq!(payload.query, "love");
+    }
+
+    #[test]
+    fn same_script_runs_absorb_punctuation_between_them() {
+        let runs = segment("liebe Grüße, Anna!");
+        assert_eq!(scripts(&runs), vec![(Script::Latin, "liebe Grüße, Anna"), (Script::Common, "!")]);
+    }
+
+    #[test]
+    fn digits_and_punctuation_never_dominate() {
+        let payload = route_query("2024-01-01: 12345678 ok", &[target("en")]).unwrap();
+        assert_eq!(payload.query, "ok");
+        assert!(route_query("2024-01-01 !!!", &[target("en")]).is_none());
+    }
+
+    #[test]
+    fn pure_emoji_is_rejected() {
+        let targets = [target("en"), target("sa"), target("ru")];
+        assert!(route_query("😀🎉👍", &targets).is_none());
+        assert!(route_query("🔥 🔥 🔥", &targets).is_none());
+        assert_eq!(dominant_script("😀🎉"), None);
+    }
+
+    #[test]
+    fn dominant_script_counts_letters() {
+        assert_eq!(dominant_script("धर्म is dharma"), Some(Script::Latin));
+        assert_eq!(dominant_script("धर्मक्षेत्रे कुरुक्षेत्रे x"), Some(Script::Devanagari));
+    }
+}
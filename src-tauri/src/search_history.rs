@@ -0,0 +1,175 @@
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Dictionary lookups the user made, newest last. Recording only touches
+/// memory; a background thread writes the file when something changed.
+static HISTORY: Lazy<HistoryStore> = Lazy::new(HistoryStore::new);
+
+const FLUSH_INTERVAL: Duration = Duration::from_secs(10);
+/// Lookups of the same word closer together than this are one record
+const COLLAPSE_WINDOW_MS: i64 = 5_000;
+const MAX_ENTRIES: usize = 5_000;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryEntry {
+    pub id: u64,
+    pub word: String,
+    pub language: String,
+    /// First lookup of this record, milliseconds since the epoch
+    pub timestamp: i64,
+    pub last_seen_at: i64,
+    pub found: bool,
+    /// Lookups collapsed into this record
+    pub count: u32,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct HistoryData {
+    next_id: u64,
+    entries: Vec<HistoryEntry>,
+}
+
+struct HistoryStore {
+    path: Mutex<Option<PathBuf>>,
+    data: Mutex<HistoryData>,
+    dirty: AtomicBool,
+}
+
+impl HistoryStore {
+    fn new() -> Self {
+        Self {
+            path: Mutex::new(None),
+            data: Mutex::new(HistoryData::default()),
+            dirty: AtomicBool::new(false),
+        }
+    }
+}
+
+/// Remember a lookup. A repeat of the latest record within a few seconds
+/// (the clipboard monitor can fire twice) bumps its count instead.
+pub fn record(word: &str, language: &str, found: bool) {
+    let now = chrono::Utc::now().timestamp_millis();
+    let mut data = HISTORY.data.lock().unwrap();
+    let repeat = data.entries.last_mut().filter(|last| {
+        last.word == word && last.language == language && now - last.last_seen_at < COLLAPSE_WINDOW_MS
+    });
+    match repeat {
+        Some(last) => {
+            last.count += 1;
+            last.last_seen_at = now;
+            last.found |= found;
+        }
+        None => {
+            data.next_id += 1;
+            let id = data.next_id;
+            data.entries.push(HistoryEntry {
+                id,
+                word: word.to_string(),
+                language: language.to_string(),
+                timestamp: now,
+                last_seen_at: now,
+                found,
+                count: 1,
+            });
+            let excess = data.entries.len().saturating_sub(MAX_ENTRIES);
+            data.entries.drain(..excess);
+        }
+    }
+    HISTORY.dirty.store(true, Ordering::Relaxed);
+}
+
+/// Newest first, optionally for one language
+pub fn list(language: Option<&str>, limit: usize, offset: usize) -> Vec<HistoryEntry> {
+    let data = HISTORY.data.lock().unwrap();
+    data.entries
+        .iter()
+        .rev()
+        .filter(|e| language.is_none_or(|lang| e.language == lang))
+        .skip(offset)
+        .take(limit)
+        .cloned()
+        .collect()
+}
+
+/// Returns whether an entry with `id` existed
+pub fn delete(id: u64) -> bool {
+    let mut data = HISTORY.data.lock().unwrap();
+    let before = data.entries.len();
+    data.entries.retain(|e| e.id != id);
+    let removed = data.entries.len() != before;
+    if removed {
+        HISTORY.dirty.store(true, Ordering::Relaxed);
+    }
+    removed
+}
+
+pub fn clear() {
+    HISTORY.data.lock().unwrap().entries.clear();
+    HISTORY.dirty.store(true, Ordering::Relaxed);
+    flush();
+}
+
+fn write_atomic(path: &Path, data: &HistoryData) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create history directory: {}", e))?;
+    }
+    let content = serde_json::to_string(data).map_err(|e| format!("Failed to serialize history: {}", e))?;
+    let tmp_path = path.with_extension("json.tmp");
+    fs::write(&tmp_path, content).map_err(|e| format!("Failed to write history: {}", e))?;
+    fs::rename(&tmp_path, path).map_err(|e| format!("Failed to replace history file: {}", e))
+}
+
+/// Write the file if anything changed since the last flush
+pub fn flush() {
+    let Some(path) = HISTORY.path.lock().unwrap().clone() else {
+        return;
+    };
+    if !HISTORY.dirty.swap(false, Ordering::Relaxed) {
+        return;
+    }
+    let snapshot = HISTORY.data.lock().unwrap().clone();
+    if let Err(e) = write_atomic(&path, &snapshot) {
+        eprintln!("[HISTORY] {}", e);
+        HISTORY.dirty.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Load the history from `path`, keep anything recorded before startup,
+/// and flush periodically
+pub fn init(path: PathBuf) {
+    let stored: HistoryData = fs::read_to_string(&path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default();
+    {
+        let mut data = HISTORY.data.lock().unwrap();
+        let early = std::mem::replace(&mut *data, stored);
+        for mut entry in early.entries {
+            data.next_id += 1;
+            entry.id = data.next_id;
+            data.entries.push(entry);
+        }
+    }
+    *HISTORY.path.lock().unwrap() = Some(path);
+    flush();
+
+    std::thread::spawn(|| loop {
+        std::thread::sleep(FLUSH_INTERVAL);
+        flush();
+    });
+}
+
+pub fn get_history_path(app: &tauri::AppHandle) -> PathBuf {
+    use tauri::Manager;
+    let base_dir = app.path()
+        .app_data_dir()
+        .unwrap_or_else(|_| std::env::current_dir().unwrap_or_else(|_| PathBuf::from(".")));
+    base_dir.join("data").join("search_history.json")
+}
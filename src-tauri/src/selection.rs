@@ -0,0 +1,242 @@
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_clipboard_manager::ClipboardExt;
+use crate::{clipboard, logging, lookup_targets, prefetch, script, write_log, AppState};
+
+/// How long the other app gets to answer the copy keystroke
+const COPY_TIMEOUT: Duration = Duration::from_millis(400);
+const COPY_POLL: Duration = Duration::from_millis(20);
+/// Lets the shortcut's own keys come up before the copy keystroke
+const SETTLE: Duration = Duration::from_millis(60);
+
+/// Why selection lookup can't work here, if it can't
+pub fn support() -> Result<(), String> {
+    platform::support()
+}
+
+/// Look up the text selected in whatever app has focus: copy it with a
+/// synthetic Ctrl+C (Cmd+C on macOS), send it to the floating window and
+/// put the clipboard back. Runs on its own thread.
+pub fn lookup(app: &AppHandle) {
+    let app = app.clone();
+    std::thread::spawn(move || match capture(&app) {
+        Ok(Some(text)) => send(&app, &text),
+        Ok(None) => write_log("[Selection] Nothing selected"),
+        Err(e) => logging::warn(&format!("⚠ [Selection] {}", e)),
+    });
+}
+
+/// The selected text, or None when the copy didn't change the clipboard.
+/// Only text is restored afterwards; an image on the clipboard is replaced
+/// by the selection.
+fn capture(app: &AppHandle) -> Result<Option<String>, String> {
+    support()?;
+    let monitor = &app.state::<AppState>().clipboard_monitor;
+    let original = app.clipboard().read_text().ok();
+    let before = clipboard::sequence_number();
+    std::thread::sleep(SETTLE);
+
+    monitor.begin_synthetic_copy();
+    if let Err(e) = platform::send_copy() {
+        monitor.end_synthetic_copy(Vec::new());
+        return Err(e);
+    }
+    let deadline = Instant::now() + COPY_TIMEOUT;
+    let mut selected = None;
+    while Instant::now() < deadline {
+        std::thread::sleep(COPY_POLL);
+        let text = app.clipboard().read_text().ok();
+        if text != original || (before.is_some() && clipboard::sequence_number() != before) {
+            selected = text;
+            break;
+        }
+    }
+    if selected.is_some() {
+        if let Some(original) = &original {
+            let _ = app.clipboard().write_text(original.clone());
+        }
+    }
+    monitor.end_synthetic_copy(selected.iter().chain(original.iter()).cloned().collect());
+    Ok(selected.filter(|text| !text.trim().is_empty()))
+}
+
+fn send(app: &AppHandle, text: &str) {
+    let Some(mut payload) = script::route_query(text, &lookup_targets(app)) else {
+        write_log("[Selection] Nothing to look up in the selection");
+        return;
+    };
+    write_log(&format!("[Selection] Looking up '{}'", payload.query));
+    payload.source = Some("selection".to_string());
+    if let Some(language) = payload.language.as_deref().filter(|l| *l != "sa") {
+        prefetch::start(&payload.query, language);
+        payload.prefetched = true;
+    }
+    if let Some(window) = app.get_webview_window("floating") {
+        let _ = window.show();
+        let _ = window.set_focus();
+        let _ = window.emit_to("floating", "new-query", payload);
+    }
+}
+
+#[cfg(windows)]
+mod platform {
+    #[repr(C)]
+    struct KeybdInput {
+        vk: u16,
+        scan: u16,
+        flags: u32,
+        time: u32,
+        extra_info: usize,
+    }
+
+    /// INPUT with the keyboard member; the padding covers the larger
+    /// MOUSEINPUT so the size matches what SendInput expects
+    #[repr(C)]
+    struct Input {
+        kind: u32,
+        ki: KeybdInput,
+        _pad: [u8; 8],
+    }
+
+    #[link(name = "user32")]
+    extern "system" {
+        fn SendInput(count: u32, inputs: *const Input, size: i32) -> u32;
+    }
+
+    const INPUT_KEYBOARD: u32 = 1;
+    const KEYEVENTF_KEYUP: u32 = 0x0002;
+    const VK_SHIFT: u16 = 0x10;
+    const VK_CONTROL: u16 = 0x11;
+    const VK_MENU: u16 = 0x12;
+    const VK_LWIN: u16 = 0x5B;
+    const VK_RWIN: u16 = 0x5C;
+    const VK_C: u16 = 0x43;
+
+    fn key(vk: u16, up: bool) -> Input {
+        Input {
+            kind: INPUT_KEYBOARD,
+            ki: KeybdInput {
+                vk,
+                scan: 0,
+                flags: if up { KEYEVENTF_KEYUP } else { 0 },
+                time: 0,
+                extra_info: 0,
+            },
+            _pad: [0; 8],
+        }
+    }
+
+    pub fn support() -> Result<(), String> {
+        Ok(())
+    }
+
+    /// Release the shortcut's other modifiers, which would otherwise turn
+    /// Ctrl+C into e.g. Ctrl+Alt+C, then press Ctrl+C
+    pub fn send_copy() -> Result<(), String> {
+        let inputs = [
+            key(VK_SHIFT, true),
+            key(VK_MENU, true),
+            key(VK_LWIN, true),
+            key(VK_RWIN, true),
+            key(VK_CONTROL, false),
+            key(VK_C, false),
+            key(VK_C, true),
+            key(VK_CONTROL, true),
+        ];
+        // SAFETY: `inputs` is a valid array of INPUT-layout structs
+        let sent = unsafe { SendInput(inputs.len() as u32, inputs.as_ptr(), std::mem::size_of::<Input>() as i32) };
+        if sent as usize != inputs.len() {
+            return Err("The copy keystroke was blocked (the focused app may run as administrator)".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use std::ffi::c_void;
+
+    #[link(name = "CoreGraphics", kind = "framework")]
+    extern "C" {
+        fn CGEventSourceCreate(state: i32) -> *mut c_void;
+        fn CGEventCreateKeyboardEvent(source: *mut c_void, key: u16, down: bool) -> *mut c_void;
+        fn CGEventSetFlags(event: *mut c_void, flags: u64);
+        fn CGEventPost(tap: u32, event: *mut c_void);
+    }
+
+    #[link(name = "CoreFoundation", kind = "framework")]
+    extern "C" {
+        fn CFRelease(object: *const c_void);
+    }
+
+    #[link(name = "ApplicationServices", kind = "framework")]
+    extern "C" {
+        fn AXIsProcessTrusted() -> bool;
+    }
+
+    const HID_SYSTEM_STATE: i32 = 1;
+    const HID_EVENT_TAP: u32 = 0;
+    const KEY_C: u16 = 8;
+    const FLAG_COMMAND: u64 = 0x0010_0000;
+
+    pub fn support() -> Result<(), String> {
+        // SAFETY: no arguments, only reads the permission state
+        if unsafe { AXIsProcessTrusted() } {
+            Ok(())
+        } else {
+            Err("Lumina needs the Accessibility permission (System Settings → Privacy & Security) to copy the selection".to_string())
+        }
+    }
+
+    /// Cmd+C with only the Command flag set, whatever else is still held
+    pub fn send_copy() -> Result<(), String> {
+        // SAFETY: every object created here is released before returning
+        unsafe {
+            let source = CGEventSourceCreate(HID_SYSTEM_STATE);
+            for down in [true, false] {
+                let event = CGEventCreateKeyboardEvent(source, KEY_C, down);
+                if event.is_null() {
+                    if !source.is_null() {
+                        CFRelease(source);
+                    }
+                    return Err("Could not create the copy keystroke".to_string());
+                }
+                CGEventSetFlags(event, FLAG_COMMAND);
+                CGEventPost(HID_EVENT_TAP, event);
+                CFRelease(event);
+            }
+            if !source.is_null() {
+                CFRelease(source);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+mod platform {
+    pub fn support() -> Result<(), String> {
+        let wayland = std::env::var("XDG_SESSION_TYPE").is_ok_and(|t| t.eq_ignore_ascii_case("wayland"))
+            || std::env::var_os("WAYLAND_DISPLAY").is_some();
+        if wayland {
+            return Err("Selection lookup isn't available on Wayland, which doesn't let apps send keystrokes to other windows; copy the text instead".to_string());
+        }
+        let xdotool = std::env::var_os("PATH")
+            .is_some_and(|path| std::env::split_paths(&path).any(|dir| dir.join("xdotool").is_file()));
+        if !xdotool {
+            return Err("Selection lookup needs xdotool; install it with your package manager".to_string());
+        }
+        Ok(())
+    }
+
+    pub fn send_copy() -> Result<(), String> {
+        let status = std::process::Command::new("xdotool")
+            .args(["key", "--clearmodifiers", "ctrl+c"])
+            .status()
+            .map_err(|e| format!("Failed to run xdotool: {}", e))?;
+        if !status.success() {
+            return Err(format!("xdotool exited with {}", status));
+        }
+        Ok(())
+    }
+}
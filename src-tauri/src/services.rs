@@ -0,0 +1,149 @@
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+use crate::backend::{BackendServices, ServiceProcess};
+use crate::shortcut::{self, GlobalShortcut};
+use crate::AppState;
+
+pub const TRAY_ID: &str = "main";
+
+/// A service's /health gets this long to answer
+const HEALTH_TIMEOUT: Duration = Duration::from_millis(1500);
+/// How often the watcher looks for services that exited
+const WATCH_INTERVAL: Duration = Duration::from_secs(5);
+
+static CLIENT: Lazy<reqwest::Client> = Lazy::new(|| {
+    reqwest::Client::builder()
+        .timeout(HEALTH_TIMEOUT)
+        .build()
+        .unwrap_or_default()
+});
+
+/// One Python backend service, for the settings page and the tray
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServiceStatus {
+    pub label: String,
+    pub script: String,
+    pub pid: u32,
+    /// "running", "exited" (cleanly), "crashed", or "stopped" once
+    /// `stop_backend_services` took it down
+    pub state: String,
+    /// Whether /health answered; None when it wasn't asked (not running,
+    /// no endpoint, or a watcher event)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub healthy: Option<bool>,
+    pub uptime_secs: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exit_code: Option<i32>,
+    /// Last stderr lines, once the process has exited
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub stderr_tail: Vec<String>,
+}
+
+fn status_of(process: ServiceProcess) -> ServiceStatus {
+    let state = match process.exit {
+        None => "running",
+        Some(status) if status.success() => "exited",
+        Some(_) => "crashed",
+    };
+    ServiceStatus {
+        label: process.label,
+        script: process.script,
+        pid: process.pid,
+        state: state.to_string(),
+        healthy: None,
+        uptime_secs: process.uptime.as_secs(),
+        exit_code: process.exit.and_then(|status| status.code()),
+        stderr_tail: if process.exit.is_some() { process.stderr_tail } else { Vec::new() },
+    }
+}
+
+/// Status of every started service, asking each running one's /health
+pub async fn current(services: &BackendServices) -> Vec<ServiceStatus> {
+    let mut statuses = Vec::new();
+    for process in services.processes() {
+        let health_url = process.health_url.clone();
+        let mut status = status_of(process);
+        if let Some(url) = health_url.filter(|_| status.state == "running") {
+            let answer = CLIENT.get(&url).send().await;
+            status.healthy = Some(answer.is_ok_and(|r| r.status().is_success()));
+        }
+        statuses.push(status);
+    }
+    statuses
+}
+
+/// Tray tooltip: the usual text, plus a line per service that went down
+fn tooltip(app: &AppHandle, statuses: &[ServiceStatus]) -> String {
+    let accelerator = app.state::<GlobalShortcut>().accelerator();
+    statuses
+        .iter()
+        .filter(|s| s.state == "crashed")
+        .fold(shortcut::tray_tooltip(accelerator.as_deref()), |text, s| format!("{}\n{}: crashed", text, s.label))
+}
+
+fn set_tray_tooltip(app: &AppHandle, statuses: &[ServiceStatus]) {
+    if let Some(tray) = app.tray_by_id(TRAY_ID) {
+        let _ = tray.set_tooltip(Some(tooltip(app, statuses)));
+    }
+}
+
+/// Rebuild the tray tooltip, e.g. after the global shortcut changed
+pub fn update_tray_tooltip(app: &AppHandle) {
+    let statuses: Vec<ServiceStatus> = app
+        .state::<AppState>()
+        .backend_services
+        .processes()
+        .into_iter()
+        .map(status_of)
+        .collect();
+    set_tray_tooltip(app, &statuses);
+}
+
+/// Poll the services' processes (no HTTP) and emit `service-status-changed`
+/// for each one whose state changed, e.g. running → crashed. The tray
+/// tooltip is updated along with it.
+pub fn watch(app: AppHandle) {
+    std::thread::spawn(move || {
+        let mut previous: HashMap<String, ServiceStatus> = HashMap::new();
+        loop {
+            std::thread::sleep(WATCH_INTERVAL);
+            let statuses: Vec<ServiceStatus> = app
+                .state::<AppState>()
+                .backend_services
+                .processes()
+                .into_iter()
+                .map(status_of)
+                .collect();
+
+            let mut changed: Vec<ServiceStatus> = statuses
+                .iter()
+                .filter(|s| previous.get(&s.script).is_none_or(|p| (p.pid, &p.state) != (s.pid, &s.state)))
+                .cloned()
+                .collect();
+            for (script, last) in &previous {
+                if last.state != "stopped" && !statuses.iter().any(|s| &s.script == script) {
+                    changed.push(ServiceStatus {
+                        state: "stopped".to_string(),
+                        stderr_tail: Vec::new(),
+                        ..last.clone()
+                    });
+                }
+            }
+            if changed.is_empty() {
+                continue;
+            }
+
+            for status in &changed {
+                if status.state == "crashed" {
+                    crate::write_log(&format!("✗ {} (PID: {}) crashed", status.label, status.pid));
+                }
+                let _ = app.emit("service-status-changed", status);
+                previous.insert(status.script.clone(), status.clone());
+            }
+            set_tray_tooltip(&app, &statuses);
+        }
+    });
+}
@@ -0,0 +1,390 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::RwLock;
+use tauri::{AppHandle, Emitter, Manager};
+use crate::clipboard::ClipboardMonitorConfig;
+use crate::limits::Limits;
+use crate::{logging, shortcut, usage, AppState};
+
+/// Schema of settings.json. Bump it and add a step to `migrate` when a
+/// field is renamed or changes meaning.
+pub const SETTINGS_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Settings {
+    pub version: u32,
+    pub ui_language: Option<String>,
+    /// Language dictionary definitions should be shown in
+    pub gloss_language: Option<String>,
+    /// Rank well-known vocabulary (status 5) behind unseen dictionary words in suggestions
+    pub prioritize_learning: bool,
+    /// Python launch strategy tried first ("uv", "venv", "system")
+    pub python_strategy: Option<String>,
+    /// Interpreter to use instead of probing; wins over `python_strategy`
+    pub python_path: Option<String>,
+    /// Retry missed lookups with ASCII digraphs converted (ae→ä, aa→ā)
+    pub smart_input: bool,
+    /// Extra on-screen keyboard characters per language code
+    pub special_characters: HashMap<String, Vec<String>>,
+    /// Keep local usage counters free of language codes
+    pub privacy_mode: bool,
+    /// Dictionary senses with any of these labels are hidden
+    pub hidden_sense_labels: Vec<String>,
+    /// Regional variety whose senses are listed first, e.g. "Austria"
+    pub preferred_region: Option<String>,
+    /// Input size caps enforced by the commands
+    pub limits: Limits,
+    /// Keep Wiktionary results in the language's online_cache.db for offline use
+    pub persist_online_lookups: bool,
+    /// Keep Sanskrit split and transliteration results on disk across restarts
+    pub persist_sanskrit_results: bool,
+    pub clipboard_monitor: ClipboardMonitorConfig,
+    /// Dictionary language preferred when several share a script, and for
+    /// the clipboard's script filter when the UI hasn't chosen one
+    pub default_language: Option<String>,
+    /// Start the Python backend services at launch
+    pub autostart_services: bool,
+    /// Start a crashed backend service again, with backoff
+    pub auto_restart_services: bool,
+    /// Accelerator that toggles the floating window; None means the default
+    pub global_shortcut: Option<String>,
+    /// Accelerator that looks up the selection in any app; None turns it off
+    pub selection_shortcut: Option<String>,
+    /// Lowest level written to lumina.log ("debug", "info", "warn", "error")
+    pub log_level: String,
+    /// Vocabulary backups kept in the backups folder
+    pub vocabulary_backup_count: usize,
+    pub daily_goal: DailyGoal,
+    /// TrueType font embedded in flashcard PDFs; None picks a system font
+    pub flashcard_font: Option<String>,
+}
+
+/// Per local calendar day; 0 turns that half of the goal off
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct DailyGoal {
+    pub new_terms: u32,
+    pub reviews: u32,
+}
+
+impl Default for DailyGoal {
+    fn default() -> Self {
+        Self {
+            new_terms: 5,
+            reviews: 20,
+        }
+    }
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            version: SETTINGS_VERSION,
+            ui_language: None,
+            gloss_language: None,
+            prioritize_learning: false,
+            python_strategy: None,
+            python_path: None,
+            smart_input: false,
+            special_characters: HashMap::new(),
+            privacy_mode: false,
+            hidden_sense_labels: vec!["archaic".to_string(), "obsolete".to_string(), "vulgar".to_string()],
+            preferred_region: None,
+            limits: Limits::default(),
+            persist_online_lookups: false,
+            persist_sanskrit_results: true,
+            clipboard_monitor: ClipboardMonitorConfig::default(),
+            default_language: None,
+            autostart_services: true,
+            auto_restart_services: true,
+            global_shortcut: None,
+            selection_shortcut: None,
+            log_level: "info".to_string(),
+            vocabulary_backup_count: 20,
+            daily_goal: DailyGoal::default(),
+            flashcard_font: None,
+        }
+    }
+}
+
+impl Settings {
+    /// Reject values the features reading them can't work with
+    pub fn validate(&self) -> Result<(), String> {
+        self.clipboard_monitor.validate()?;
+        if logging::Level::parse(&self.log_level).is_none() {
+            return Err(format!("Unknown log level: {}", self.log_level));
+        }
+        for accelerator in [&self.global_shortcut, &self.selection_shortcut].into_iter().flatten() {
+            shortcut::parse(accelerator)?;
+        }
+        if self.global_shortcut.is_some() && self.global_shortcut == self.selection_shortcut {
+            return Err("The two global shortcuts must differ".to_string());
+        }
+        if self.vocabulary_backup_count == 0 {
+            return Err("Keep at least one vocabulary backup".to_string());
+        }
+        if let Some(path) = &self.flashcard_font {
+            crate::truetype::Font::load(Path::new(path))?;
+        }
+        Ok(())
+    }
+}
+
+/// Payload of `settings-changed`, sent to every window after each save
+#[derive(Debug, Clone, Serialize)]
+pub struct SettingsChangedEvent {
+    pub settings: Settings,
+}
+
+pub struct SettingsState {
+    path: PathBuf,
+    settings: RwLock<Settings>,
+    first_run: AtomicBool,
+    app: AppHandle,
+}
+
+impl SettingsState {
+    pub fn load(app: &AppHandle, path: PathBuf) -> Self {
+        let first_run = !path.exists();
+        let settings = read_settings(&path);
+
+        Self {
+            path,
+            settings: RwLock::new(settings),
+            first_run: AtomicBool::new(first_run),
+            app: app.clone(),
+        }
+    }
+
+    pub fn get(&self) -> Settings {
+        self.settings.read().unwrap().clone()
+    }
+
+    /// True until the first settings file has been written
+    pub fn is_first_run(&self) -> bool {
+        self.first_run.load(Ordering::SeqCst)
+    }
+
+    /// Apply `f` to the settings and persist the result
+    pub fn update(&self, f: impl FnOnce(&mut Settings)) -> Result<Settings, String> {
+        let snapshot = {
+            let mut settings = self.settings.write().unwrap();
+            f(&mut settings);
+            settings.clone()
+        };
+        write_settings(&self.path, &snapshot)?;
+        self.first_run.store(false, Ordering::SeqCst);
+        let _ = self.app.emit("settings-changed", SettingsChangedEvent { settings: snapshot.clone() });
+        Ok(snapshot)
+    }
+}
+
+/// Settings stored at `path`, or the defaults when there are none. A file
+/// that doesn't parse is moved to `settings.json.bad` so the next save
+/// can't overwrite what the user had.
+fn read_settings(path: &Path) -> Settings {
+    let Ok(content) = fs::read_to_string(path) else {
+        return Settings::default();
+    };
+    let parsed = serde_json::from_str::<Value>(&content)
+        .and_then(|value| serde_json::from_value::<Settings>(migrate(value)));
+    match parsed {
+        Ok(settings) => settings,
+        Err(e) => {
+            let bad = path.with_extension("json.bad");
+            logging::error(&format!(
+                "✗ settings.json does not parse ({}); moved to {} and using defaults",
+                e,
+                bad.display()
+            ));
+            if let Err(e) = fs::rename(path, &bad) {
+                logging::error(&format!("✗ Could not move unparseable settings aside: {}", e));
+            }
+            Settings::default()
+        }
+    }
+}
+
+/// Bring settings.json written by an older build up to `SETTINGS_VERSION`,
+/// one version at a time. Files from a newer build are left alone; fields
+/// this build doesn't know are dropped when it next saves.
+fn migrate(mut value: Value) -> Value {
+    let Some(object) = value.as_object_mut() else {
+        return value;
+    };
+    // Files from before the field existed are version 1 in all but name
+    let mut version = object.get("version").and_then(Value::as_u64).unwrap_or(1) as u32;
+    while version < SETTINGS_VERSION {
+        // One step per schema change, keyed by the version it upgrades
+        // from, e.g. `if version == 1 { rename(object, "old", "new") }`
+        version += 1;
+        object.insert("version".to_string(), version.into());
+    }
+    value
+}
+
+/// Overlay `patch` on `base`: objects merge key by key, anything else
+/// (including arrays and null) replaces
+fn merge(base: &mut Value, patch: Value) {
+    match (base, patch) {
+        (Value::Object(base), Value::Object(patch)) => {
+            for (key, value) in patch {
+                merge(base.entry(key).or_insert(Value::Null), value);
+            }
+        }
+        (base, patch) => *base = patch,
+    }
+}
+
+fn write_settings(path: &Path, settings: &Settings) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create settings directory: {}", e))?;
+    }
+    let content = serde_json::to_string_pretty(settings)
+        .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+
+    let tmp_path = path.with_extension("json.tmp");
+    fs::write(&tmp_path, content)
+        .map_err(|e| format!("Failed to write settings: {}", e))?;
+    fs::rename(&tmp_path, path)
+        .map_err(|e| format!("Failed to replace settings file: {}", e))?;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_settings(settings: tauri::State<'_, SettingsState>) -> Settings {
+    settings.get()
+}
+
+/// Merge a partial update, e.g. `{"clipboardMonitor": {"pollIntervalMs": 500}}`,
+/// validate the result, save it and apply it to the running features.
+/// Returns the new settings, which also go out as `settings-changed`.
+#[tauri::command]
+pub fn update_settings(app: AppHandle, patch: Value) -> Result<Settings, String> {
+    let state = app.state::<SettingsState>();
+    let old = state.get();
+    let mut value = serde_json::to_value(&old).map_err(|e| e.to_string())?;
+    merge(&mut value, patch);
+    let mut new: Settings = serde_json::from_value(value).map_err(|e| format!("Invalid settings: {}", e))?;
+    new.version = old.version;
+    new.validate()?;
+
+    // Shortcuts can be refused by the OS, so they go first and nothing is
+    // saved when they fail
+    if new.global_shortcut != old.global_shortcut {
+        let accelerator = new.global_shortcut.clone().unwrap_or_else(|| shortcut::DEFAULT_SHORTCUT.to_string());
+        let result = shortcut::set_global_shortcut(app.clone(), accelerator);
+        if let Some(error) = result.error.filter(|_| !result.success) {
+            return Err(error);
+        }
+    }
+    if new.selection_shortcut != old.selection_shortcut {
+        let result = shortcut::set_selection_shortcut(app.clone(), new.selection_shortcut.clone());
+        if let Some(error) = result.error.filter(|_| !result.success) {
+            return Err(error);
+        }
+    }
+
+    let saved = state.update(|s| *s = new)?;
+    if let Some(level) = logging::Level::parse(&saved.log_level) {
+        logging::set_level(level);
+    }
+    app.state::<AppState>().clipboard_monitor.set_config(saved.clipboard_monitor.clone());
+    if saved.privacy_mode != old.privacy_mode {
+        usage::set_privacy_mode(saved.privacy_mode);
+    }
+    Ok(saved)
+}
+
+pub fn get_settings_path(app: &AppHandle) -> PathBuf {
+    let base_dir = app.path()
+        .app_data_dir()
+        .unwrap_or_else(|_| std::env::current_dir().unwrap_or_else(|_| PathBuf::from(".")));
+
+    base_dir.join("settings.json")
+}
+
+/// Fill in locale-derived defaults. Only runs before the first settings file exists.
+pub fn apply_first_run_defaults(state: &SettingsState, detected_languages: &[String]) {
+    if !state.is_first_run() {
+        return;
+    }
+    if detected_languages.is_empty() {
+        return;
+    }
+
+    let result = state.update(|settings| fill_locale_defaults(settings, detected_languages));
+    if let Err(e) = result {
+        eprintln!("[SETTINGS] Failed to write first-run defaults: {}", e);
+    }
+}
+
+/// Use the primary detected language for languages the user hasn't chosen
+fn fill_locale_defaults(settings: &mut Settings, detected_languages: &[String]) {
+    let Some(primary) = detected_languages.first() else {
+        return;
+    };
+    if settings.ui_language.is_none() {
+        settings.ui_language = Some(primary.clone());
+    }
+    if settings.gloss_language.is_none() {
+        settings.gloss_language = Some(primary.clone());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unparseable_settings_are_moved_aside_before_falling_back() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("settings.json");
+        fs::write(&path, "{\"smart_input\": fals").unwrap();
+
+        let settings = read_settings(&path);
+        assert_eq!(settings.smart_input, Settings::default().smart_input);
+        assert!(!path.exists());
+        assert_eq!(fs::read_to_string(dir.path().join("settings.json.bad")).unwrap(), "{\"smart_input\": fals");
+    }
+
+    #[test]
+    fn readable_settings_stay_in_place() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("settings.json");
+        fs::write(&path, serde_json::to_string(&Settings { vocabulary_backup_count: 3, ..Default::default() }).unwrap())
+            .unwrap();
+
+        assert_eq!(read_settings(&path).vocabulary_backup_count, 3);
+        assert!(path.exists());
+        assert!(!dir.path().join("settings.json.bad").exists());
+        let missing = read_settings(&dir.path().join("missing.json"));
+        assert_eq!(missing.vocabulary_backup_count, Settings::default().vocabulary_backup_count);
+    }
+
+    #[test]
+    fn first_run_defaults_come_from_the_primary_locale() {
+        let mut settings = Settings::default();
+        fill_locale_defaults(&mut settings, &["de".to_string(), "en".to_string()]);
+        assert_eq!(settings.ui_language.as_deref(), Some("de"));
+        assert_eq!(settings.gloss_language.as_deref(), Some("de"));
+    }
+
+    #[test]
+    fn first_run_defaults_keep_chosen_languages() {
+        let mut settings = Settings { gloss_language: Some("en".to_string()), ..Default::default() };
+        fill_locale_defaults(&mut settings, &["fr".to_string()]);
+        assert_eq!(settings.ui_language.as_deref(), Some("fr"));
+        assert_eq!(settings.gloss_language.as_deref(), Some("en"));
+
+        let mut untouched = Settings::default();
+        fill_locale_defaults(&mut untouched, &[]);
+        assert_eq!(untouched.ui_language, None);
+    }
+}
@@ -0,0 +1,344 @@
+use serde::Serialize;
+use std::sync::Mutex;
+use tauri::menu::MenuItem;
+use tauri::{AppHandle, Manager, Wry};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutState};
+use crate::settings::SettingsState;
+use crate::{logging, selection, services, write_log, AppState};
+
+pub const DEFAULT_SHORTCUT: &str = "Ctrl+Shift+L";
+
+/// The registered global shortcuts (toggling the floating window, and
+/// looking up the selection) and the tray item that shows the first
+#[derive(Default)]
+pub struct GlobalShortcut {
+    /// The accelerator as the user wrote it, and its parsed form
+    current: Mutex<Option<(String, Shortcut)>>,
+    /// None while selection lookup is turned off
+    selection: Mutex<Option<(String, Shortcut)>>,
+    toggle_item: Mutex<Option<MenuItem<Wry>>>,
+    /// Why the saved shortcut wasn't used at startup
+    startup_error: Mutex<Option<String>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GlobalShortcutInfo {
+    /// None when nothing could be registered
+    pub accelerator: Option<String>,
+    pub default: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub startup_error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SelectionShortcutInfo {
+    /// None while selection lookup is turned off
+    pub accelerator: Option<String>,
+    /// False where keystrokes can't be sent to other apps (e.g. Wayland)
+    pub supported: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limitation: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SetGlobalShortcutResult {
+    pub success: bool,
+    /// The shortcut in effect afterwards
+    pub accelerator: Option<String>,
+    pub error: Option<String>,
+    /// "invalid", "conflict", "unavailable", "unsupported" or "settings"
+    pub error_kind: Option<String>,
+}
+
+impl SetGlobalShortcutResult {
+    fn ok(accelerator: Option<String>) -> Self {
+        SetGlobalShortcutResult {
+            success: true,
+            accelerator,
+            error: None,
+            error_kind: None,
+        }
+    }
+
+    fn failed(accelerator: Option<String>, error: String, kind: &str) -> Self {
+        SetGlobalShortcutResult {
+            success: false,
+            accelerator,
+            error: Some(error),
+            error_kind: Some(kind.to_string()),
+        }
+    }
+}
+
+/// What a global shortcut does
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Action {
+    ToggleFloating,
+    LookupSelection,
+}
+
+impl GlobalShortcut {
+    /// The accelerator in effect, e.g. "Ctrl+Shift+L"
+    pub fn accelerator(&self) -> Option<String> {
+        self.current.lock().unwrap().as_ref().map(|(accelerator, _)| accelerator.clone())
+    }
+
+    fn slot(&self, action: Action) -> &Mutex<Option<(String, Shortcut)>> {
+        match action {
+            Action::ToggleFloating => &self.current,
+            Action::LookupSelection => &self.selection,
+        }
+    }
+
+    /// Whether the other shortcut already uses `shortcut`
+    fn taken_by_other(&self, action: Action, shortcut: &Shortcut) -> bool {
+        let other = match action {
+            Action::ToggleFloating => Action::LookupSelection,
+            Action::LookupSelection => Action::ToggleFloating,
+        };
+        self.slot(other).lock().unwrap().as_ref().is_some_and(|(_, s)| s.id() == shortcut.id())
+    }
+
+    /// Remember the tray item whose label names the shortcut
+    pub fn set_toggle_item(&self, item: MenuItem<Wry>) {
+        *self.toggle_item.lock().unwrap() = Some(item);
+    }
+}
+
+/// Parse an accelerator such as "Ctrl+Alt+Space". A global shortcut without
+/// a modifier would swallow that key in every app, so one is required.
+pub fn parse(accelerator: &str) -> Result<Shortcut, String> {
+    let shortcut: Shortcut = accelerator
+        .trim()
+        .parse()
+        .map_err(|e| format!("Invalid shortcut \"{}\": {}", accelerator, e))?;
+    if shortcut.mods.is_empty() {
+        return Err(format!("Shortcut \"{}\" needs a modifier such as Ctrl or Alt", accelerator));
+    }
+    Ok(shortcut)
+}
+
+pub fn toggle_label(accelerator: Option<&str>) -> String {
+    match accelerator {
+        Some(accelerator) => format!("Toggle ({})", accelerator),
+        None => "Toggle".to_string(),
+    }
+}
+
+pub fn tray_tooltip(accelerator: Option<&str>) -> String {
+    match accelerator {
+        Some(accelerator) => format!("Lumina Quick ({})", accelerator),
+        None => "Lumina Quick".to_string(),
+    }
+}
+
+fn register(app: &AppHandle, accelerator: &str, shortcut: Shortcut, action: Action) -> Result<(), String> {
+    let label = accelerator.to_string();
+    app.global_shortcut()
+        .on_shortcut(shortcut, move |app, _shortcut, event| match (action, event.state) {
+            (Action::ToggleFloating, ShortcutState::Pressed) => {
+                write_log(&format!("检测到全局快捷键 {}", label));
+                toggle_floating(app);
+            }
+            // On release, so fewer of the shortcut's keys are still down
+            // when the copy keystroke goes out
+            (Action::LookupSelection, ShortcutState::Released) => selection::lookup(app),
+            _ => {}
+        })
+        .map_err(|e| format!("Could not register {}: {}", accelerator, e))
+}
+
+/// Put `next` in `action`'s slot in place of the shortcut there. If `next`
+/// can't be registered the previous one is put back and the error returned.
+fn replace(app: &AppHandle, action: Action, next: Option<(String, Shortcut)>) -> Result<(), String> {
+    let slot = app.state::<GlobalShortcut>().inner().slot(action);
+    let previous = slot.lock().unwrap().clone();
+    let same = match (&previous, &next) {
+        (Some((_, old)), Some((_, new))) => old.id() == new.id(),
+        (None, None) => true,
+        _ => false,
+    };
+    if !same {
+        if let Some((_, old)) = &previous {
+            let _ = app.global_shortcut().unregister(*old);
+        }
+        if let Some((accelerator, shortcut)) = &next {
+            if let Err(e) = register(app, accelerator, *shortcut, action) {
+                logging::warn(&format!("⚠ {}", e));
+                if let Some((old_accelerator, old)) = &previous {
+                    if let Err(e) = register(app, old_accelerator, *old, action) {
+                        logging::error(&format!("✗ Could not restore {}: {}", old_accelerator, e));
+                        *slot.lock().unwrap() = None;
+                    }
+                }
+                return Err(e);
+            }
+        }
+    }
+    *slot.lock().unwrap() = next;
+    Ok(())
+}
+
+/// Send a copy held back in manual trigger mode, or else show or hide the
+/// floating window
+pub fn toggle_floating(app: &AppHandle) {
+    if app.state::<AppState>().clipboard_monitor.send_pending(app) {
+        return;
+    }
+    if let Some(window) = app.get_webview_window("floating") {
+        if window.is_visible().unwrap_or(false) {
+            let _ = window.hide();
+        } else {
+            let _ = window.show();
+            let _ = window.set_focus();
+        }
+    }
+}
+
+/// Register the saved shortcuts. The toggle shortcut falls back to the
+/// default when it is invalid or taken by another app; the selection
+/// shortcut is just left off. Call before the tray is built.
+pub fn init(app: &AppHandle) {
+    let state = app.state::<GlobalShortcut>();
+    let settings = app.state::<SettingsState>().get();
+    init_toggle(app, &state, settings.global_shortcut);
+    if let Some(saved) = settings.selection_shortcut {
+        let registered = selection::support().and_then(|_| parse(&saved)).and_then(|shortcut| {
+            if state.taken_by_other(Action::LookupSelection, &shortcut) {
+                return Err(format!("{} is already the floating-window shortcut", saved));
+            }
+            replace(app, Action::LookupSelection, Some((saved.clone(), shortcut)))
+        });
+        match registered {
+            Ok(()) => write_log(&format!("已注册选区查词快捷键 {}", saved)),
+            Err(e) => logging::warn(&format!("⚠ Selection lookup shortcut not registered: {}", e)),
+        }
+    }
+}
+
+fn init_toggle(app: &AppHandle, state: &GlobalShortcut, saved: Option<String>) {
+    if let Some(saved) = saved.filter(|s| s != DEFAULT_SHORTCUT) {
+        let registered = parse(&saved)
+            .and_then(|shortcut| register(app, &saved, shortcut, Action::ToggleFloating).map(|_| shortcut));
+        match registered {
+            Ok(shortcut) => {
+                *state.current.lock().unwrap() = Some((saved.clone(), shortcut));
+                write_log(&format!("已注册全局快捷键 {}", saved));
+                return;
+            }
+            Err(e) => {
+                logging::warn(&format!("⚠ Saved shortcut not used, falling back to {}: {}", DEFAULT_SHORTCUT, e));
+                *state.startup_error.lock().unwrap() = Some(e);
+            }
+        }
+    }
+    let registered = parse(DEFAULT_SHORTCUT)
+        .and_then(|shortcut| register(app, DEFAULT_SHORTCUT, shortcut, Action::ToggleFloating).map(|_| shortcut));
+    match registered {
+        Ok(shortcut) => {
+            *state.current.lock().unwrap() = Some((DEFAULT_SHORTCUT.to_string(), shortcut));
+            write_log(&format!("已注册全局快捷键 {}", DEFAULT_SHORTCUT));
+        }
+        Err(e) => {
+            logging::warn(&format!("⚠ No global shortcut: {}", e));
+            state.startup_error.lock().unwrap().get_or_insert(e);
+        }
+    }
+}
+
+/// Point the tray's toggle item and tooltip at the current shortcut
+fn update_tray(app: &AppHandle, accelerator: Option<&str>) {
+    if let Some(item) = app.state::<GlobalShortcut>().toggle_item.lock().unwrap().as_ref() {
+        let _ = item.set_text(toggle_label(accelerator));
+    }
+    services::update_tray_tooltip(app);
+}
+
+#[tauri::command]
+pub fn get_global_shortcut(state: tauri::State<'_, GlobalShortcut>) -> GlobalShortcutInfo {
+    GlobalShortcutInfo {
+        accelerator: state.accelerator(),
+        default: DEFAULT_SHORTCUT.to_string(),
+        startup_error: state.startup_error.lock().unwrap().clone(),
+    }
+}
+
+/// Switch the floating-window shortcut. If the new one can't be registered
+/// the old one stays in effect.
+#[tauri::command]
+pub fn set_global_shortcut(app: AppHandle, accelerator: String) -> SetGlobalShortcutResult {
+    let state = app.state::<GlobalShortcut>();
+    let accelerator = accelerator.trim().to_string();
+    let shortcut = match parse(&accelerator) {
+        Ok(shortcut) => shortcut,
+        Err(e) => return SetGlobalShortcutResult::failed(state.accelerator(), e, "invalid"),
+    };
+    if state.taken_by_other(Action::ToggleFloating, &shortcut) {
+        let error = format!("{} is already the selection lookup shortcut", accelerator);
+        return SetGlobalShortcutResult::failed(state.accelerator(), error, "conflict");
+    }
+    if let Err(e) = replace(&app, Action::ToggleFloating, Some((accelerator.clone(), shortcut))) {
+        let current = state.accelerator();
+        update_tray(&app, current.as_deref());
+        return SetGlobalShortcutResult::failed(current, e, "unavailable");
+    }
+    *state.startup_error.lock().unwrap() = None;
+    update_tray(&app, Some(&accelerator));
+    write_log(&format!("已注册全局快捷键 {}", accelerator));
+
+    let saved = app.state::<SettingsState>().update(|s| s.global_shortcut = Some(accelerator.clone()));
+    match saved {
+        Ok(_) => SetGlobalShortcutResult::ok(Some(accelerator)),
+        Err(e) => SetGlobalShortcutResult::failed(Some(accelerator), e, "settings"),
+    }
+}
+
+#[tauri::command]
+pub fn get_selection_shortcut(state: tauri::State<'_, GlobalShortcut>) -> SelectionShortcutInfo {
+    let limitation = selection::support().err();
+    SelectionShortcutInfo {
+        accelerator: state.selection.lock().unwrap().as_ref().map(|(accelerator, _)| accelerator.clone()),
+        supported: limitation.is_none(),
+        limitation,
+    }
+}
+
+/// Set or (with None) turn off the shortcut that looks up the text selected
+/// in any app. Where keystrokes can't be sent to other apps the result is
+/// an "unsupported" error explaining why.
+#[tauri::command]
+pub fn set_selection_shortcut(app: AppHandle, accelerator: Option<String>) -> SetGlobalShortcutResult {
+    let state = app.state::<GlobalShortcut>();
+    let current = || state.selection.lock().unwrap().as_ref().map(|(accelerator, _)| accelerator.clone());
+    let accelerator = accelerator.map(|a| a.trim().to_string()).filter(|a| !a.is_empty());
+    let next = match &accelerator {
+        Some(accelerator) => {
+            if let Err(e) = selection::support() {
+                return SetGlobalShortcutResult::failed(current(), e, "unsupported");
+            }
+            let shortcut = match parse(accelerator) {
+                Ok(shortcut) => shortcut,
+                Err(e) => return SetGlobalShortcutResult::failed(current(), e, "invalid"),
+            };
+            if state.taken_by_other(Action::LookupSelection, &shortcut) {
+                let error = format!("{} is already the floating-window shortcut", accelerator);
+                return SetGlobalShortcutResult::failed(current(), error, "conflict");
+            }
+            Some((accelerator.clone(), shortcut))
+        }
+        None => None,
+    };
+    if let Err(e) = replace(&app, Action::LookupSelection, next) {
+        return SetGlobalShortcutResult::failed(current(), e, "unavailable");
+    }
+    match &accelerator {
+        Some(accelerator) => write_log(&format!("已注册选区查词快捷键 {}", accelerator)),
+        None => write_log("选区查词快捷键已关闭"),
+    }
+
+    let saved = app.state::<SettingsState>().update(|s| s.selection_shortcut = accelerator.clone());
+    match saved {
+        Ok(_) => SetGlobalShortcutResult::ok(accelerator),
+        Err(e) => SetGlobalShortcutResult::failed(accelerator, e, "settings"),
+    }
+}
@@ -0,0 +1,142 @@
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Instant;
+
+/// Startup instrumentation, created when `main` begins
+pub static STARTUP: Lazy<StartupTracker> = Lazy::new(StartupTracker::new);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PhaseTiming {
+    pub phase: String,
+    pub started_ms: u64,
+    pub duration_ms: u64,
+    pub background: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StartupTimings {
+    pub phases: Vec<PhaseTiming>,
+    pub window_ready_ms: Option<u64>,
+    /// Subprocess calls made before the first window was visible; should stay empty
+    pub blocking_calls_before_window: Vec<String>,
+    pub elapsed_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StartupReadyEvent {
+    pub phase: String,
+    pub elapsed_ms: u64,
+}
+
+pub struct StartupTracker {
+    launched_at: Instant,
+    phases: Mutex<Vec<PhaseTiming>>,
+    window_ready: AtomicBool,
+    window_ready_ms: Mutex<Option<u64>>,
+    early_blocking_calls: Mutex<Vec<String>>,
+}
+
+impl StartupTracker {
+    fn new() -> Self {
+        Self {
+            launched_at: Instant::now(),
+            phases: Mutex::new(Vec::new()),
+            window_ready: AtomicBool::new(false),
+            window_ready_ms: Mutex::new(None),
+            early_blocking_calls: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub fn elapsed_ms(&self) -> u64 {
+        self.launched_at.elapsed().as_millis() as u64
+    }
+
+    /// Run `f` and record how long it took
+    pub fn phase<T>(&self, name: &str, background: bool, f: impl FnOnce() -> T) -> T {
+        let start = Instant::now();
+        let result = f();
+        self.record_since(name, start, background);
+        result
+    }
+
+    /// Record a phase that started at `start` and ends now
+    pub fn record_since(&self, name: &str, start: Instant, background: bool) {
+        self.phases.lock().unwrap().push(PhaseTiming {
+            phase: name.to_string(),
+            started_ms: start.saturating_duration_since(self.launched_at).as_millis() as u64,
+            duration_ms: start.elapsed().as_millis() as u64,
+            background,
+        });
+    }
+
+    pub fn mark_window_ready(&self) {
+        if !self.window_ready.swap(true, Ordering::SeqCst) {
+            *self.window_ready_ms.lock().unwrap() = Some(self.elapsed_ms());
+        }
+    }
+
+    /// Called before spawning a blocking subprocess. Anything recorded here
+    /// before the window is up is a startup regression.
+    pub fn note_blocking_call(&self, what: &str) {
+        if !self.window_ready.load(Ordering::SeqCst) {
+            eprintln!("[STARTUP] Blocking call before window ready: {}", what);
+            self.early_blocking_calls.lock().unwrap().push(what.to_string());
+        }
+    }
+
+    pub fn snapshot(&self) -> StartupTimings {
+        StartupTimings {
+            phases: self.phases.lock().unwrap().clone(),
+            window_ready_ms: *self.window_ready_ms.lock().unwrap(),
+            blocking_calls_before_window: self.early_blocking_calls.lock().unwrap().clone(),
+            elapsed_ms: self.elapsed_ms(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_calls_before_the_window_are_flagged() {
+        let tracker = StartupTracker::new();
+        tracker.note_blocking_call("python --version");
+        tracker.mark_window_ready();
+        tracker.note_blocking_call("dictionary scan");
+
+        let timings = tracker.snapshot();
+        assert_eq!(timings.blocking_calls_before_window, vec!["python --version"]);
+        assert!(timings.window_ready_ms.is_some());
+    }
+
+    #[test]
+    fn window_ready_is_recorded_once() {
+        let tracker = StartupTracker::new();
+        tracker.mark_window_ready();
+        let first = tracker.snapshot().window_ready_ms;
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        tracker.mark_window_ready();
+        assert_eq!(tracker.snapshot().window_ready_ms, first);
+    }
+
+    #[test]
+    fn phases_are_recorded_in_order() {
+        let tracker = StartupTracker::new();
+        let value = tracker.phase("settings", false, || 42);
+        tracker.phase("dictionary_scan", true, || std::thread::sleep(std::time::Duration::from_millis(5)));
+
+        assert_eq!(value, 42);
+        let phases = tracker.snapshot().phases;
+        let names: Vec<&str> = phases.iter().map(|p| p.phase.as_str()).collect();
+        assert_eq!(names, vec!["settings", "dictionary_scan"]);
+        assert!(!phases[0].background && phases[1].background);
+        assert!(phases[1].duration_ms >= 5);
+        assert!(phases[1].started_ms >= phases[0].started_ms);
+    }
+}
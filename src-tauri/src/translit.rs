@@ -0,0 +1,437 @@
+//! Native transliteration between Devanagari, IAST, Harvard-Kyoto and SLP1,
+//! so a Sanskrit dictionary is searchable in whichever script it was built
+//! in, and `sanskrit_transliterate` works, without going through the Python
+//! sidecar.
+
+/// How a Sanskrit string is written
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scheme {
+    Devanagari,
+    Iast,
+    HarvardKyoto,
+    Slp1,
+}
+
+impl Scheme {
+    pub const ALL: [Scheme; 4] = [Scheme::Devanagari, Scheme::Iast, Scheme::HarvardKyoto, Scheme::Slp1];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Scheme::Devanagari => "devanagari",
+            Scheme::Iast => "iast",
+            Scheme::HarvardKyoto => "hk",
+            Scheme::Slp1 => "slp1",
+        }
+    }
+
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            Scheme::Devanagari => "Devanagari",
+            Scheme::Iast => "IAST",
+            Scheme::HarvardKyoto => "Harvard-Kyoto",
+            Scheme::Slp1 => "SLP1",
+        }
+    }
+
+    /// The scheme called `name`, as the Sanskrit commands spell it
+    pub fn parse(name: &str) -> Option<Scheme> {
+        match name.trim().to_lowercase().as_str() {
+            "devanagari" => Some(Scheme::Devanagari),
+            "iast" => Some(Scheme::Iast),
+            "hk" | "harvard-kyoto" | "harvardkyoto" => Some(Scheme::HarvardKyoto),
+            "slp1" => Some(Scheme::Slp1),
+            _ => None,
+        }
+    }
+}
+
+/// Languages whose dictionaries come in more than one script
+const MULTI_SCRIPT_LANGUAGES: &[&str] = &["sa", "pi"];
+
+pub fn is_multi_script(language: &str) -> bool {
+    MULTI_SCRIPT_LANGUAGES.contains(&language)
+}
+
+const IAST_VOWELS: &[(&str, char, Option<char>)] = &[
+    // (IAST, independent vowel, vowel sign after a consonant)
+    ("ai", 'ऐ', Some('ै')),
+    ("au", 'औ', Some('ौ')),
+    ("a", 'अ', None),
+    ("ā", 'आ', Some('ा')),
+    ("i", 'इ', Some('ि')),
+    ("ī", 'ई', Some('ी')),
+    ("u", 'उ', Some('ु')),
+    ("ū", 'ऊ', Some('ू')),
+    ("ṛ", 'ऋ', Some('ृ')),
+    ("ṝ", 'ॠ', Some('ॄ')),
+    ("ḷ", 'ऌ', Some('ॢ')),
+    ("ḹ", 'ॡ', Some('ॣ')),
+    ("e", 'ए', Some('े')),
+    ("o", 'ओ', Some('ो')),
+];
+
+/// Aspirates before their plain consonant so matching is longest-first
+const IAST_CONSONANTS: &[(&str, char)] = &[
+    ("kh", 'ख'), ("gh", 'घ'), ("ch", 'छ'), ("jh", 'झ'), ("ṭh", 'ठ'),
+    ("ḍh", 'ढ'), ("th", 'थ'), ("dh", 'ध'), ("ph", 'फ'), ("bh", 'भ'),
+    ("k", 'क'), ("g", 'ग'), ("ṅ", 'ङ'), ("c", 'च'), ("j", 'ज'),
+    ("ñ", 'ञ'), ("ṭ", 'ट'), ("ḍ", 'ड'), ("ṇ", 'ण'), ("t", 'त'),
+    ("d", 'द'), ("n", 'न'), ("p", 'प'), ("b", 'ब'), ("m", 'म'),
+    ("y", 'य'), ("r", 'र'), ("l", 'ल'), ("v", 'व'), ("ś", 'श'),
+    ("ṣ", 'ष'), ("s", 'स'), ("h", 'ह'), ("ḻ", 'ळ'),
+];
+
+const IAST_OTHER: &[(&str, char)] = &[
+    ("ṃ", 'ं'), ("ḥ", 'ः'), ("'", 'ऽ'), ("oṃ", 'ॐ'), ("||", '॥'), ("|", '।'),
+];
+
+/// SLP1 spells every sound with one ASCII letter, case-sensitively
+const SLP1_VOWELS: &[(&str, char, Option<char>)] = &[
+    ("E", 'ऐ', Some('ै')),
+    ("O", 'औ', Some('ौ')),
+    ("a", 'अ', None),
+    ("A", 'आ', Some('ा')),
+    ("i", 'इ', Some('ि')),
+    ("I", 'ई', Some('ी')),
+    ("u", 'उ', Some('ु')),
+    ("U", 'ऊ', Some('ू')),
+    ("f", 'ऋ', Some('ृ')),
+    ("F", 'ॠ', Some('ॄ')),
+    ("x", 'ऌ', Some('ॢ')),
+    ("X", 'ॡ', Some('ॣ')),
+    ("e", 'ए', Some('े')),
+    ("o", 'ओ', Some('ो')),
+];
+
+const SLP1_CONSONANTS: &[(&str, char)] = &[
+    ("k", 'क'), ("K", 'ख'), ("g", 'ग'), ("G", 'घ'), ("N", 'ङ'),
+    ("c", 'च'), ("C", 'छ'), ("j", 'ज'), ("J", 'झ'), ("Y", 'ञ'),
+    ("w", 'ट'), ("W", 'ठ'), ("q", 'ड'), ("Q", 'ढ'), ("R", 'ण'),
+    ("t", 'त'), ("T", 'थ'), ("d", 'द'), ("D", 'ध'), ("n", 'न'),
+    ("p", 'प'), ("P", 'फ'), ("b", 'ब'), ("B", 'भ'), ("m", 'म'),
+    ("y", 'य'), ("r", 'र'), ("l", 'ल'), ("v", 'व'), ("S", 'श'),
+    ("z", 'ष'), ("s", 'स'), ("h", 'ह'), ("L", 'ळ'),
+];
+
+const SLP1_OTHER: &[(&str, char)] = &[
+    ("M", 'ं'), ("H", 'ः'), ("'", 'ऽ'), ("oM", 'ॐ'), ("~", 'ँ'), ("||", '॥'), ("|", '।'),
+];
+
+/// A romanization's letters, for converting to and from Devanagari
+struct Romanization {
+    vowels: &'static [(&'static str, char, Option<char>)],
+    consonants: &'static [(&'static str, char)],
+    other: &'static [(&'static str, char)],
+}
+
+const IAST: Romanization = Romanization {
+    vowels: IAST_VOWELS,
+    consonants: IAST_CONSONANTS,
+    other: IAST_OTHER,
+};
+
+const SLP1: Romanization = Romanization {
+    vowels: SLP1_VOWELS,
+    consonants: SLP1_CONSONANTS,
+    other: SLP1_OTHER,
+};
+
+const VIRAMA: char = '्';
+const NUKTA: char = '़';
+
+/// Harvard-Kyoto spellings that differ from IAST, longest first; read
+/// backwards they give the Harvard-Kyoto spelling of an IAST letter
+const HK_TO_IAST: &[(&str, &str)] = &[
+    ("lRR", "ḹ"), ("lR", "ḷ"), ("RR", "ṝ"), ("R", "ṛ"),
+    ("A", "ā"), ("I", "ī"), ("U", "ū"), ("M", "ṃ"), ("H", "ḥ"),
+    ("G", "ṅ"), ("J", "ñ"), ("T", "ṭ"), ("D", "ḍ"), ("N", "ṇ"),
+    ("z", "ś"), ("S", "ṣ"),
+];
+
+/// Combining marks that decomposed IAST input uses, with the precomposed
+/// letters they form
+const IAST_COMPOSE: &[(char, char, char)] = &[
+    ('a', '\u{304}', 'ā'), ('i', '\u{304}', 'ī'), ('u', '\u{304}', 'ū'),
+    ('r', '\u{323}', 'ṛ'), ('ṛ', '\u{304}', 'ṝ'), ('l', '\u{323}', 'ḷ'),
+    ('ḷ', '\u{304}', 'ḹ'), ('t', '\u{323}', 'ṭ'), ('d', '\u{323}', 'ḍ'),
+    ('n', '\u{323}', 'ṇ'), ('s', '\u{323}', 'ṣ'), ('m', '\u{323}', 'ṃ'),
+    ('h', '\u{323}', 'ḥ'), ('n', '\u{307}', 'ṅ'), ('n', '\u{303}', 'ñ'),
+    ('s', '\u{301}', 'ś'), ('m', '\u{307}', 'ṃ'), ('l', '\u{331}', 'ḻ'),
+];
+
+const IAST_MARKS: &str = "āīūṛṝḷḹṅñṭḍṇśṣṃḥ";
+
+/// The scheme `text` is most likely written in, None when it has no
+/// letters. Plain ASCII counts as IAST unless it uses Harvard-Kyoto's
+/// capitals or `z`.
+pub fn detect(text: &str) -> Option<Scheme> {
+    if text.chars().any(|c| ('\u{900}'..='\u{97F}').contains(&c)) {
+        return Some(Scheme::Devanagari);
+    }
+    let lower = text.to_lowercase();
+    if lower.chars().any(|c| IAST_MARKS.contains(c)) || lower.chars().any(|c| ('\u{300}'..='\u{36F}').contains(&c)) {
+        return Some(Scheme::Iast);
+    }
+    if !text.chars().any(|c| c.is_alphabetic()) {
+        return None;
+    }
+    let hk = text.is_ascii()
+        && (text.contains('z')
+            || text
+                .split_whitespace()
+                .any(|word| word.chars().skip(1).any(|c| c.is_ascii_uppercase())));
+    Some(if hk { Scheme::HarvardKyoto } else { Scheme::Iast })
+}
+
+/// `text` converted from `from` to `to`. IAST is the pivot between the
+/// Latin schemes, except that SLP1 goes through Devanagari: it tells `kh`
+/// (K) from `k` + `h` (kh), which IAST can't.
+pub fn convert(text: &str, from: Scheme, to: Scheme) -> String {
+    if from == to {
+        return text.to_string();
+    }
+    if to == Scheme::Slp1 {
+        let devanagari = convert(text, from, Scheme::Devanagari);
+        return from_devanagari(&devanagari, &SLP1);
+    }
+    if from == Scheme::Slp1 {
+        return convert(&to_devanagari(text, &SLP1), Scheme::Devanagari, to);
+    }
+    let iast = match from {
+        Scheme::Devanagari => devanagari_to_iast(text),
+        Scheme::Iast => compose_iast(&text.to_lowercase()),
+        Scheme::HarvardKyoto => hk_to_iast(text),
+        Scheme::Slp1 => unreachable!("converted through Devanagari above"),
+    };
+    match to {
+        Scheme::Devanagari => iast_to_devanagari(&iast),
+        Scheme::Iast => iast,
+        Scheme::HarvardKyoto => iast_to_hk(&iast),
+        Scheme::Slp1 => unreachable!("converted through Devanagari above"),
+    }
+}
+
+fn compose_iast(text: &str) -> String {
+    let mut out: Vec<char> = Vec::with_capacity(text.len());
+    for c in text.chars() {
+        let composed = out
+            .last()
+            .and_then(|&base| IAST_COMPOSE.iter().find(|(b, mark, _)| *b == base && *mark == c))
+            .map(|(_, _, composed)| *composed);
+        match composed {
+            Some(composed) => {
+                out.pop();
+                out.push(composed);
+            }
+            None => out.push(c),
+        }
+    }
+    out.into_iter().collect()
+}
+
+fn hk_to_iast(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    'outer: while let Some(c) = rest.chars().next() {
+        for (hk, iast) in HK_TO_IAST {
+            if let Some(after) = rest.strip_prefix(hk) {
+                out.push_str(iast);
+                rest = after;
+                continue 'outer;
+            }
+        }
+        out.extend(c.to_lowercase());
+        rest = &rest[c.len_utf8()..];
+    }
+    out
+}
+
+fn iast_to_hk(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        let mut buf = [0u8; 4];
+        match HK_TO_IAST.iter().find(|(_, iast)| *iast == c.encode_utf8(&mut buf)) {
+            Some((hk, _)) => out.push_str(hk),
+            None => out.push(c),
+        }
+    }
+    out
+}
+
+pub fn devanagari_to_iast(text: &str) -> String {
+    from_devanagari(text, &IAST)
+}
+
+/// Devanagari spelled out in `scheme`. ळ and candrabindu get IAST's ḻ and
+/// m̐ where the scheme has no letter for them.
+fn from_devanagari(text: &str, scheme: &Romanization) -> String {
+    let chars: Vec<char> = text.chars().filter(|&c| c != NUKTA).collect();
+    let inherent = scheme.vowels.iter().find(|(_, _, sign)| sign.is_none()).map(|(v, _, _)| *v).unwrap_or("a");
+    let mut out = String::with_capacity(text.len() * 2);
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        i += 1;
+        let consonant = scheme
+            .consonants
+            .iter()
+            .find(|(_, d)| *d == c)
+            .map(|(roman, _)| roman.to_string())
+            .or_else(|| (c == 'ळ').then(|| "ḻ".to_string()));
+        if let Some(roman) = consonant {
+            out.push_str(&roman);
+            match chars.get(i) {
+                Some(&VIRAMA) => i += 1,
+                Some(next) => match scheme.vowels.iter().find(|(_, _, sign)| *sign == Some(*next)) {
+                    Some((vowel, _, _)) => {
+                        out.push_str(vowel);
+                        i += 1;
+                    }
+                    None => out.push_str(inherent),
+                },
+                None => out.push_str(inherent),
+            }
+        } else if let Some((vowel, _, _)) = scheme.vowels.iter().find(|(_, d, _)| *d == c) {
+            out.push_str(vowel);
+        } else if let Some((roman, _)) = scheme.other.iter().find(|(_, d)| *d == c) {
+            out.push_str(roman);
+        } else if c == 'ँ' {
+            out.push_str("m̐");
+        } else if ('०'..='९').contains(&c) {
+            out.push(char::from(b'0' + (c as u32 - '०' as u32) as u8));
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+pub fn iast_to_devanagari(text: &str) -> String {
+    to_devanagari(&compose_iast(&text.to_lowercase()), &IAST)
+}
+
+/// `text` in `scheme` written in Devanagari, matching longest spellings
+/// first. `oṃ` outside a word is ॐ rather than ओं.
+fn to_devanagari(text: &str, scheme: &Romanization) -> String {
+    let mut out = String::with_capacity(text.len() * 3);
+    let mut after_consonant = false;
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        let om = scheme.other.iter().find(|(roman, d)| *d == 'ॐ' && rest.starts_with(roman));
+        if let (Some((roman, d)), false) = (om, after_consonant || out.ends_with(char::is_alphabetic)) {
+            out.push(*d);
+            rest = &rest[roman.len()..];
+        } else if let Some((roman, d)) = scheme.consonants.iter().find(|(roman, _)| rest.starts_with(roman)) {
+            if after_consonant {
+                out.push(VIRAMA);
+            }
+            out.push(*d);
+            after_consonant = true;
+            rest = &rest[roman.len()..];
+        } else if let Some((roman, independent, sign)) = scheme.vowels.iter().find(|(roman, _, _)| rest.starts_with(roman)) {
+            if after_consonant {
+                // The inherent vowel has no sign
+                if let Some(sign) = sign {
+                    out.push(*sign);
+                }
+            } else {
+                out.push(*independent);
+            }
+            after_consonant = false;
+            rest = &rest[roman.len()..];
+        } else {
+            if after_consonant {
+                out.push(VIRAMA);
+            }
+            after_consonant = false;
+            match scheme.other.iter().find(|(roman, _)| rest.starts_with(roman)) {
+                Some((roman, d)) => {
+                    out.push(*d);
+                    rest = &rest[roman.len()..];
+                }
+                None if c.is_ascii_digit() => {
+                    out.push(char::from_u32('०' as u32 + c.to_digit(10).unwrap_or(0)).unwrap_or(c));
+                    rest = &rest[1..];
+                }
+                None => {
+                    out.push(c);
+                    rest = &rest[c.len_utf8()..];
+                }
+            }
+        }
+    }
+    if after_consonant {
+        out.push(VIRAMA);
+    }
+    out
+}
+
+/// The scheme most of `headwords` are written in: Devanagari or IAST
+pub fn dominant_scheme(headwords: &[String]) -> Option<Scheme> {
+    let devanagari = headwords
+        .iter()
+        .filter(|w| detect(w) == Some(Scheme::Devanagari))
+        .count();
+    let latin = headwords
+        .iter()
+        .filter(|w| matches!(detect(w), Some(Scheme::Iast) | Some(Scheme::HarvardKyoto)))
+        .count();
+    match (devanagari, latin) {
+        (0, 0) => None,
+        (d, l) if d >= l => Some(Scheme::Devanagari),
+        _ => Some(Scheme::Iast),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verses in Devanagari with their IAST
+    const VERSES: &[(&str, &str)] = &[
+        // Bhagavad Gītā 1.1
+        (
+            "धर्मक्षेत्रे कुरुक्षेत्रे समवेता युयुत्सवः ।\nमामकाः पाण्डवाश्चैव किमकुर्वत सञ्जय ॥ १ ॥",
+            "dharmakṣetre kurukṣetre samavetā yuyutsavaḥ |\nmāmakāḥ pāṇḍavāścaiva kimakurvata sañjaya || 1 ||",
+        ),
+        // Gāyatrī mantra
+        (
+            "ॐ भूर्भुवः स्वः तत्सवितुर्वरेण्यं भर्गो देवस्य धीमहि धियो यो नः प्रचोदयात् ॥",
+            "oṃ bhūrbhuvaḥ svaḥ tatsaviturvareṇyaṃ bhargo devasya dhīmahi dhiyo yo naḥ pracodayāt ||",
+        ),
+        // Īśa Upaniṣad 1
+        (
+            "ईशावास्यमिदं सर्वं यत्किञ्च जगत्यां जगत् ।\nतेन त्यक्तेन भुञ्जीथा मा गृधः कस्यस्विद्धनम् ॥",
+            "īśāvāsyamidaṃ sarvaṃ yatkiñca jagatyāṃ jagat |\ntena tyaktena bhuñjīthā mā gṛdhaḥ kasyasviddhanam ||",
+        ),
+        // Ṛgveda 1.1.1, with ळ
+        (
+            "अग्निमीळे पुरोहितं यज्ञस्य देवमृत्विजम् । होतारं रत्नधातमम् ॥",
+            "agnimīḻe purohitaṃ yajñasya devamṛtvijam | hotāraṃ ratnadhātamam ||",
+        ),
+        // Avagraha, ॠ and ऌ
+        (
+            "सोऽहं पितॄन् कॢप्तं नमामि ॥ २३ ॥",
+            "so'haṃ pitṝn kḷptaṃ namāmi || 23 ||",
+        ),
+    ];
+
+    #[test]
+    fn verses_round_trip_through_iast() {
+        for (devanagari, iast) in VERSES {
+            let romanized = convert(devanagari, Scheme::Devanagari, Scheme::Iast);
+            assert_eq!(&romanized, iast);
+            assert_eq!(&convert(&romanized, Scheme::Iast, Scheme::Devanagari), devanagari);
+        }
+    }
+
+    #[test]
+    fn verses_round_trip_through_every_scheme() {
+        for (devanagari, _) in VERSES {
+            for scheme in [Scheme::HarvardKyoto, Scheme::Slp1] {
+                let romanized = convert(devanagari, Scheme::Devanagari, scheme);
+                assert_eq!(&convert(&romanized, scheme, Scheme::Devanagari), devanagari, "{}", scheme.as_str());
+            }
+        }
+    }
+}
@@ -0,0 +1,296 @@
+//! Just enough TrueType reading to embed a font in a PDF: the cmap for
+//! character-to-glyph lookup, horizontal metrics for layout, and the header
+//! values a PDF font descriptor needs. The font file is embedded whole;
+//! there is no subsetter and no shaping.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+pub struct Font {
+    /// The font file, embedded as is
+    pub data: Vec<u8>,
+    /// PostScript-safe name for the PDF's BaseFont
+    pub name: String,
+    units_per_em: u16,
+    /// xMin, yMin, xMax, yMax in font units
+    bbox: [i16; 4],
+    ascent: i16,
+    descent: i16,
+    advances: Vec<u16>,
+    glyphs: HashMap<u32, u16>,
+}
+
+fn u16_at(data: &[u8], offset: usize) -> Result<u16, String> {
+    data.get(offset..offset + 2)
+        .map(|b| u16::from_be_bytes([b[0], b[1]]))
+        .ok_or_else(|| "Font file is truncated".to_string())
+}
+
+fn i16_at(data: &[u8], offset: usize) -> Result<i16, String> {
+    u16_at(data, offset).map(|v| v as i16)
+}
+
+fn u32_at(data: &[u8], offset: usize) -> Result<u32, String> {
+    data.get(offset..offset + 4)
+        .map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+        .ok_or_else(|| "Font file is truncated".to_string())
+}
+
+/// Offsets of the tables in the font's table directory, by tag
+fn tables(data: &[u8]) -> Result<HashMap<[u8; 4], usize>, String> {
+    match data.get(..4) {
+        Some(b"OTTO") => return Err("CFF-based OpenType fonts aren't supported; choose a TrueType font".to_string()),
+        Some(b"ttcf") => return Err("Font collections (.ttc) aren't supported; choose a single .ttf font".to_string()),
+        Some([0, 1, 0, 0]) | Some(b"true") => {}
+        _ => return Err("Not a TrueType font".to_string()),
+    }
+    let count = u16_at(data, 4)? as usize;
+    let mut found = HashMap::with_capacity(count);
+    for i in 0..count {
+        let record = 12 + i * 16;
+        let tag = data.get(record..record + 4).ok_or("Font file is truncated")?;
+        found.insert([tag[0], tag[1], tag[2], tag[3]], u32_at(data, record + 8)? as usize);
+    }
+    Ok(found)
+}
+
+/// Character-to-glyph map from a format 4 (BMP) cmap subtable
+fn cmap_format4(data: &[u8], table: usize, glyphs: &mut HashMap<u32, u16>) -> Result<(), String> {
+    let segments = u16_at(data, table + 6)? as usize / 2;
+    let ends = table + 14;
+    let starts = ends + segments * 2 + 2;
+    let deltas = starts + segments * 2;
+    let range_offsets = deltas + segments * 2;
+    for segment in 0..segments {
+        let end = u16_at(data, ends + segment * 2)?;
+        let start = u16_at(data, starts + segment * 2)?;
+        let delta = u16_at(data, deltas + segment * 2)?;
+        let range_offset_at = range_offsets + segment * 2;
+        let range_offset = u16_at(data, range_offset_at)? as usize;
+        for c in start..=end {
+            if c == 0xFFFF {
+                break;
+            }
+            let glyph = if range_offset == 0 {
+                c.wrapping_add(delta)
+            } else {
+                // Offset from the idRangeOffset entry itself into glyphIdArray
+                let at = range_offset_at + range_offset + (c - start) as usize * 2;
+                match u16_at(data, at)? {
+                    0 => 0,
+                    g => g.wrapping_add(delta),
+                }
+            };
+            if glyph != 0 {
+                glyphs.insert(c as u32, glyph);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Character-to-glyph map from a format 12 (full Unicode) cmap subtable
+fn cmap_format12(data: &[u8], table: usize, glyphs: &mut HashMap<u32, u16>) -> Result<(), String> {
+    let groups = u32_at(data, table + 12)? as usize;
+    for group in 0..groups {
+        let at = table + 16 + group * 12;
+        let (start, end, first) = (u32_at(data, at)?, u32_at(data, at + 4)?, u32_at(data, at + 8)?);
+        for c in start..=end.min(0x10FFFF) {
+            let glyph = first + (c - start);
+            if glyph != 0 && glyph <= u16::MAX as u32 {
+                glyphs.insert(c, glyph as u16);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The Unicode cmap subtable, preferring full Unicode (format 12) to BMP
+/// only (format 4)
+fn cmap(data: &[u8], table: usize) -> Result<HashMap<u32, u16>, String> {
+    let count = u16_at(data, table + 2)? as usize;
+    let mut best: Option<(u16, usize)> = None;
+    for i in 0..count {
+        let record = table + 4 + i * 8;
+        let (platform, encoding) = (u16_at(data, record)?, u16_at(data, record + 2)?);
+        if !(platform == 0 || (platform == 3 && (encoding == 1 || encoding == 10))) {
+            continue;
+        }
+        let subtable = table + u32_at(data, record + 4)? as usize;
+        let format = u16_at(data, subtable)?;
+        if (format == 4 || format == 12) && best.is_none_or(|(f, _)| format > f) {
+            best = Some((format, subtable));
+        }
+    }
+    let mut glyphs = HashMap::new();
+    match best {
+        Some((12, subtable)) => cmap_format12(data, subtable, &mut glyphs)?,
+        Some((_, subtable)) => cmap_format4(data, subtable, &mut glyphs)?,
+        None => return Err("Font has no Unicode character map".to_string()),
+    }
+    Ok(glyphs)
+}
+
+impl Font {
+    pub fn load(path: &Path) -> Result<Font, String> {
+        let data = fs::read(path).map_err(|e| format!("Failed to read font {:?}: {}", path, e))?;
+        let stem = path.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+        Font::parse(data, &stem).map_err(|e| format!("{:?}: {}", path, e))
+    }
+
+    pub fn parse(data: Vec<u8>, name: &str) -> Result<Font, String> {
+        let tables = tables(&data)?;
+        let table = |tag: &[u8; 4]| {
+            tables
+                .get(tag)
+                .copied()
+                .ok_or_else(|| format!("Font has no {} table", String::from_utf8_lossy(tag)))
+        };
+        // Without glyf the outlines are CFF, which FontFile2 can't carry
+        table(b"glyf")?;
+        let head = table(b"head")?;
+        let hhea = table(b"hhea")?;
+        let hmtx = table(b"hmtx")?;
+        let glyph_count = u16_at(&data, table(b"maxp")? + 4)? as usize;
+        let metrics = (u16_at(&data, hhea + 34)? as usize).clamp(1, glyph_count.max(1));
+        let mut advances = Vec::with_capacity(glyph_count);
+        for glyph in 0..metrics {
+            advances.push(u16_at(&data, hmtx + glyph * 4)?);
+        }
+        // Glyphs past numberOfHMetrics share the last advance
+        let last = advances[metrics - 1];
+        advances.resize(glyph_count.max(metrics), last);
+
+        let name: String = name.chars().filter(char::is_ascii_alphanumeric).collect();
+        Ok(Font {
+            name: if name.is_empty() { "EmbeddedFont".to_string() } else { name },
+            units_per_em: u16_at(&data, head + 18)?.max(1),
+            bbox: [
+                i16_at(&data, head + 36)?,
+                i16_at(&data, head + 38)?,
+                i16_at(&data, head + 40)?,
+                i16_at(&data, head + 42)?,
+            ],
+            ascent: i16_at(&data, hhea + 4)?,
+            descent: i16_at(&data, hhea + 6)?,
+            advances,
+            glyphs: cmap(&data, table(b"cmap")?)?,
+            data,
+        })
+    }
+
+    /// Glyph for `c`, None when the font has none
+    pub fn glyph(&self, c: char) -> Option<u16> {
+        self.glyphs.get(&(c as u32)).copied()
+    }
+
+    /// Font units scaled to the 1/1000 em PDF metrics use
+    fn scale(&self, units: i16) -> i64 {
+        (units as i64 * 1000) / self.units_per_em as i64
+    }
+
+    /// Advance width of `glyph` in 1/1000 em
+    pub fn advance(&self, glyph: u16) -> f64 {
+        let units = self.advances.get(glyph as usize).copied().unwrap_or(0);
+        units as f64 * 1000.0 / self.units_per_em as f64
+    }
+
+    /// xMin, yMin, xMax, yMax in 1/1000 em
+    pub fn bbox(&self) -> [i64; 4] {
+        self.bbox.map(|v| self.scale(v))
+    }
+
+    /// Ascent and descent in 1/1000 em
+    pub fn vertical_metrics(&self) -> (i64, i64) {
+        (self.scale(self.ascent), self.scale(self.descent))
+    }
+}
+
+/// A minimal TrueType font mapping `chars` to glyphs 1, 2, ... (glyph 0 is
+/// .notdef), every glyph 500 units wide on a 1000-unit em and without
+/// outlines, for tests
+#[cfg(test)]
+pub fn test_font(chars: &[char]) -> Vec<u8> {
+    let be16 = |out: &mut Vec<u8>, v: u16| out.extend_from_slice(&v.to_be_bytes());
+    let glyphs = chars.len() as u16 + 1;
+
+    let mut mapped: Vec<(u16, u16)> = chars.iter().enumerate().map(|(i, &c)| (c as u16, i as u16 + 1)).collect();
+    mapped.sort();
+    let segments = mapped.len() as u16 + 1;
+    let mut cmap = Vec::new();
+    for v in [0, 1, 3, 1] {
+        be16(&mut cmap, v);
+    }
+    cmap.extend_from_slice(&12u32.to_be_bytes());
+    for v in [4, 16 + segments * 8, 0, segments * 2, 0, 0, 0] {
+        be16(&mut cmap, v);
+    }
+    mapped.iter().for_each(|(c, _)| be16(&mut cmap, *c));
+    be16(&mut cmap, 0xFFFF);
+    be16(&mut cmap, 0);
+    mapped.iter().for_each(|(c, _)| be16(&mut cmap, *c));
+    be16(&mut cmap, 0xFFFF);
+    mapped.iter().for_each(|(c, g)| be16(&mut cmap, g.wrapping_sub(*c)));
+    be16(&mut cmap, 1);
+    (0..segments).for_each(|_| be16(&mut cmap, 0));
+
+    let mut head = vec![0u8; 54];
+    head[18..20].copy_from_slice(&1000u16.to_be_bytes());
+    head[40..42].copy_from_slice(&1000u16.to_be_bytes());
+    head[42..44].copy_from_slice(&800u16.to_be_bytes());
+    let mut hhea = vec![0u8; 36];
+    hhea[4..6].copy_from_slice(&800u16.to_be_bytes());
+    hhea[6..8].copy_from_slice(&(-200i16).to_be_bytes());
+    hhea[34..36].copy_from_slice(&glyphs.to_be_bytes());
+    let mut maxp = vec![0, 0, 0x50, 0];
+    be16(&mut maxp, glyphs);
+    let mut hmtx = Vec::new();
+    (0..glyphs).for_each(|_| hmtx.extend_from_slice(&[0x01, 0xF4, 0, 0]));
+
+    let tables: [(&[u8; 4], Vec<u8>); 6] =
+        [(b"cmap", cmap), (b"glyf", Vec::new()), (b"head", head), (b"hhea", hhea), (b"hmtx", hmtx), (b"maxp", maxp)];
+    let mut out = vec![0, 1, 0, 0];
+    for v in [tables.len() as u16, 0, 0, 0] {
+        be16(&mut out, v);
+    }
+    let mut offset = 12 + tables.len() * 16;
+    for (tag, body) in &tables {
+        out.extend_from_slice(*tag);
+        out.extend_from_slice(&[0; 4]);
+        out.extend_from_slice(&(offset as u32).to_be_bytes());
+        out.extend_from_slice(&(body.len() as u32).to_be_bytes());
+        offset += body.len();
+    }
+    for (_, body) in tables {
+        out.extend_from_slice(&body);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_characters_to_glyphs_with_their_widths() {
+        let font = Font::parse(test_font(&['a', 'ā', 'क']), "Test Sans-Regular").unwrap();
+        assert_eq!(font.name, "TestSansRegular");
+        assert_eq!((font.glyph('a'), font.glyph('ā'), font.glyph('क')), (Some(1), Some(2), Some(3)));
+        assert_eq!(font.glyph('b'), None);
+        assert_eq!(font.advance(3), 500.0);
+        assert_eq!(font.vertical_metrics(), (800, -200));
+        assert_eq!(font.bbox(), [0, 0, 1000, 800]);
+    }
+
+    #[test]
+    fn refuses_fonts_it_cannot_embed() {
+        let error = |data: Vec<u8>| Font::parse(data, "x").err().unwrap_or_default();
+        let mut cff = test_font(&['a']);
+        cff[..4].copy_from_slice(b"OTTO");
+        assert!(error(cff).contains("CFF"));
+        assert!(error(b"ttcf\0\0\0\0".to_vec()).contains(".ttc"));
+        assert_eq!(error(b"%PDF-1.4".to_vec()), "Not a TrueType font");
+        assert_eq!(error(vec![0, 1, 0, 0, 0, 9]), "Font file is truncated");
+    }
+}
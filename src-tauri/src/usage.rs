@@ -0,0 +1,378 @@
+use chrono::{Datelike, Local, NaiveDate};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Local-only usage counters. Only counts are stored, keyed by day, counter
+/// name and (unless privacy mode is on) language code; never query text.
+/// Nothing here is sent anywhere.
+static USAGE: Lazy<UsageStore> = Lazy::new(UsageStore::new);
+
+const FLUSH_INTERVAL: Duration = Duration::from_secs(60);
+const RETENTION_DAYS: i64 = 400;
+const MAX_SESSIONS: usize = 500;
+
+pub const LOOKUP: &str = "lookup";
+pub const CLIPBOARD_TRIGGER: &str = "clipboard.trigger";
+pub const REVIEW_ANSWERED: &str = "review.answered";
+pub const TERM_SAVED: &str = "term.saved";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Session {
+    started_at: i64,
+    last_seen_at: i64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct UsageData {
+    /// "YYYY-MM-DD" -> "counter" or "counter:lang" -> count
+    days: BTreeMap<String, HashMap<String, u64>>,
+    sessions: Vec<Session>,
+}
+
+struct UsageStore {
+    path: Mutex<Option<PathBuf>>,
+    data: Mutex<UsageData>,
+    privacy_mode: AtomicBool,
+    session_started_at: i64,
+}
+
+impl UsageStore {
+    fn new() -> Self {
+        Self {
+            path: Mutex::new(None),
+            data: Mutex::new(UsageData::default()),
+            privacy_mode: AtomicBool::new(false),
+            session_started_at: chrono::Utc::now().timestamp_millis(),
+        }
+    }
+
+    fn record(&self, counter: &str, language: Option<&str>) {
+        let key = match language {
+            Some(lang) if !self.privacy_mode.load(Ordering::Relaxed) => format!("{}:{}", counter, lang),
+            _ => counter.to_string(),
+        };
+        let mut data = self.data.lock().unwrap();
+        *data.days.entry(today()).or_default().entry(key).or_insert(0) += 1;
+    }
+
+    fn flush(&self) {
+        let Some(path) = self.path.lock().unwrap().clone() else {
+            return;
+        };
+        let snapshot = {
+            let mut data = self.data.lock().unwrap();
+            let now = chrono::Utc::now().timestamp_millis();
+            match data.sessions.iter_mut().find(|s| s.started_at == self.session_started_at) {
+                Some(session) => session.last_seen_at = now,
+                None => data.sessions.push(Session {
+                    started_at: self.session_started_at,
+                    last_seen_at: now,
+                }),
+            }
+            let excess = data.sessions.len().saturating_sub(MAX_SESSIONS);
+            data.sessions.drain(..excess);
+
+            let cutoff = (Local::now().date_naive() - chrono::Duration::days(RETENTION_DAYS))
+                .format("%Y-%m-%d")
+                .to_string();
+            data.days.retain(|day, _| *day >= cutoff);
+            data.clone()
+        };
+        if let Err(e) = write_atomic(&path, &snapshot) {
+            eprintln!("[USAGE] {}", e);
+        }
+    }
+
+    fn reset(&self) {
+        *self.data.lock().unwrap() = UsageData::default();
+        self.flush();
+    }
+}
+
+fn today() -> String {
+    Local::now().format("%Y-%m-%d").to_string()
+}
+
+/// Count one occurrence of `counter`, optionally split by language. The one
+/// call every instrumentation point uses.
+pub fn record(counter: &str, language: Option<&str>) {
+    USAGE.record(counter, language);
+}
+
+/// Drop the language dimension from counts recorded from now on
+pub fn set_privacy_mode(enabled: bool) {
+    USAGE.privacy_mode.store(enabled, Ordering::Relaxed);
+}
+
+fn write_atomic(path: &Path, data: &UsageData) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create usage directory: {}", e))?;
+    }
+    let content = serde_json::to_string(data).map_err(|e| format!("Failed to serialize usage: {}", e))?;
+    let tmp_path = path.with_extension("json.tmp");
+    fs::write(&tmp_path, content).map_err(|e| format!("Failed to write usage: {}", e))?;
+    fs::rename(&tmp_path, path).map_err(|e| format!("Failed to replace usage file: {}", e))
+}
+
+/// Record the current session's heartbeat, trim old days and write the file
+pub fn flush() {
+    USAGE.flush();
+}
+
+/// Load counters from `path`, merge anything recorded before startup, and
+/// flush periodically
+pub fn init(path: PathBuf, privacy_mode: bool) {
+    set_privacy_mode(privacy_mode);
+    let stored: UsageData = fs::read_to_string(&path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default();
+    {
+        let mut data = USAGE.data.lock().unwrap();
+        let early = std::mem::replace(&mut *data, stored);
+        for (day, counters) in early.days {
+            let bucket = data.days.entry(day).or_default();
+            for (key, count) in counters {
+                *bucket.entry(key).or_insert(0) += count;
+            }
+        }
+    }
+    *USAGE.path.lock().unwrap() = Some(path);
+    flush();
+
+    std::thread::spawn(|| loop {
+        std::thread::sleep(FLUSH_INTERVAL);
+        // Always flush: the session heartbeat changes even with no new counts
+        flush();
+    });
+}
+
+/// Forget every counter and session, including the file on disk
+pub fn reset() {
+    USAGE.reset();
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DailyLookups {
+    pub date: String,
+    pub language: Option<String>,
+    pub count: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageSummary {
+    pub since_days: u32,
+    pub total_lookups: u64,
+    pub lookups_per_day: Vec<DailyLookups>,
+    pub lookups_by_language: BTreeMap<String, u64>,
+    pub clipboard_triggers: u64,
+    /// Clipboard-triggered share of lookups, 0.0–1.0
+    pub clipboard_ratio: f64,
+    pub terms_saved: u64,
+    /// Monday first
+    pub reviews_by_weekday: [u64; 7],
+    pub sessions: usize,
+    pub average_session_seconds: f64,
+}
+
+/// Aggregate the last `since_days` days (today included)
+pub fn summary(since_days: u32) -> UsageSummary {
+    let data = USAGE.data.lock().unwrap().clone();
+    aggregate(&data, Local::now().date_naive(), since_days)
+}
+
+/// Aggregate the `since_days` days up to and including `today`
+fn aggregate(data: &UsageData, today: NaiveDate, since_days: u32) -> UsageSummary {
+    let since_date = today - chrono::Duration::days(since_days.saturating_sub(1) as i64);
+    let since_key = since_date.format("%Y-%m-%d").to_string();
+
+    let mut summary = UsageSummary {
+        since_days,
+        total_lookups: 0,
+        lookups_per_day: Vec::new(),
+        lookups_by_language: BTreeMap::new(),
+        clipboard_triggers: 0,
+        clipboard_ratio: 0.0,
+        terms_saved: 0,
+        reviews_by_weekday: [0; 7],
+        sessions: 0,
+        average_session_seconds: 0.0,
+    };
+
+    for (day, counters) in data.days.range(since_key..) {
+        let weekday = NaiveDate::parse_from_str(day, "%Y-%m-%d")
+            .map(|d| d.weekday().num_days_from_monday() as usize)
+            .ok();
+        for (key, count) in counters {
+            let (counter, language) = match key.split_once(':') {
+                Some((c, l)) => (c, Some(l.to_string())),
+                None => (key.as_str(), None),
+            };
+            match counter {
+                LOOKUP => {
+                    summary.total_lookups += count;
+                    if let Some(lang) = &language {
+                        *summary.lookups_by_language.entry(lang.clone()).or_insert(0) += count;
+                    }
+                    summary.lookups_per_day.push(DailyLookups {
+                        date: day.clone(),
+                        language,
+                        count: *count,
+                    });
+                }
+                CLIPBOARD_TRIGGER => summary.clipboard_triggers += count,
+                TERM_SAVED => summary.terms_saved += count,
+                REVIEW_ANSWERED => {
+                    if let Some(w) = weekday {
+                        summary.reviews_by_weekday[w] += count;
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+    if summary.total_lookups > 0 {
+        summary.clipboard_ratio = (summary.clipboard_triggers as f64 / summary.total_lookups as f64).min(1.0);
+    }
+
+    let since_ms = since_date
+        .and_hms_opt(0, 0, 0)
+        .and_then(|dt| dt.and_local_timezone(Local).single())
+        .map(|dt| dt.timestamp_millis())
+        .unwrap_or(0);
+    let sessions: Vec<&Session> = data.sessions.iter().filter(|s| s.started_at >= since_ms).collect();
+    summary.sessions = sessions.len();
+    if !sessions.is_empty() {
+        let total_ms: i64 = sessions.iter().map(|s| (s.last_seen_at - s.started_at).max(0)).sum();
+        summary.average_session_seconds = total_ms as f64 / 1000.0 / sessions.len() as f64;
+    }
+    summary
+}
+
+pub fn get_usage_path(app: &tauri::AppHandle) -> PathBuf {
+    use tauri::Manager;
+    let base_dir = app.path()
+        .app_data_dir()
+        .unwrap_or_else(|_| std::env::current_dir().unwrap_or_else(|_| PathBuf::from(".")));
+    base_dir.join("data").join("usage.json")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn date(day: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(day, "%Y-%m-%d").unwrap()
+    }
+
+    fn day(data: &mut UsageData, day: &str, counters: &[(&str, u64)]) {
+        let bucket = data.days.entry(day.to_string()).or_default();
+        for (key, count) in counters {
+            bucket.insert(key.to_string(), *count);
+        }
+    }
+
+    /// 2026-10-15 is a Thursday
+    const TODAY: &str = "2026-10-15";
+
+    fn week() -> UsageData {
+        let mut data = UsageData::default();
+        day(&mut data, "2026-10-08", &[("lookup:de", 100), ("review.answered:de", 100)]);
+        day(&mut data, "2026-10-09", &[("lookup:de", 3), ("lookup:sa", 1), ("review.answered:de", 4)]);
+        day(&mut data, "2026-10-12", &[("lookup", 2), ("clipboard.trigger", 3), ("review.answered:sa", 5)]);
+        day(&mut data, TODAY, &[("lookup:de", 4), ("clipboard.trigger:de", 1), ("term.saved:de", 2)]);
+        data
+    }
+
+    #[test]
+    fn window_covers_exactly_the_last_days() {
+        let summary = aggregate(&week(), date(TODAY), 7);
+        // 2026-10-08 is eight days back and falls outside
+        assert_eq!(summary.total_lookups, 10);
+        assert_eq!(summary.lookups_by_language, BTreeMap::from([("de".to_string(), 7), ("sa".to_string(), 1)]));
+        assert_eq!(summary.lookups_per_day.len(), 4);
+        assert_eq!(summary.clipboard_triggers, 4);
+        assert_eq!(summary.clipboard_ratio, 0.4);
+        assert_eq!(summary.terms_saved, 2);
+        // Friday 2026-10-09 and Monday 2026-10-12
+        assert_eq!(summary.reviews_by_weekday, [5, 0, 0, 0, 4, 0, 0]);
+
+        let today = aggregate(&week(), date(TODAY), 1);
+        assert_eq!((today.total_lookups, today.clipboard_triggers), (4, 1));
+        assert_eq!(today.reviews_by_weekday, [0; 7]);
+
+        let all = aggregate(&week(), date(TODAY), 8);
+        assert_eq!(all.total_lookups, 110);
+        assert_eq!(all.reviews_by_weekday[3], 100);
+    }
+
+    #[test]
+    fn only_sessions_in_the_window_are_averaged() {
+        let mut data = UsageData::default();
+        let start = |day: &str| {
+            date(day).and_hms_opt(12, 0, 0).unwrap().and_local_timezone(Local).single().unwrap().timestamp_millis()
+        };
+        for (day, minutes) in [("2026-10-01", 600), ("2026-10-14", 10), (TODAY, 20)] {
+            data.sessions.push(Session {
+                started_at: start(day),
+                last_seen_at: start(day) + minutes * 60 * 1000,
+            });
+        }
+        let summary = aggregate(&data, date(TODAY), 7);
+        assert_eq!(summary.sessions, 2);
+        assert_eq!(summary.average_session_seconds, 15.0 * 60.0);
+
+        let empty = aggregate(&UsageData::default(), date(TODAY), 7);
+        assert_eq!((empty.sessions, empty.average_session_seconds, empty.clipboard_ratio), (0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn privacy_mode_records_counts_without_languages() {
+        let store = UsageStore::new();
+        store.record(LOOKUP, Some("de"));
+        store.privacy_mode.store(true, Ordering::Relaxed);
+        store.record(LOOKUP, Some("sa"));
+        store.record(LOOKUP, None);
+
+        let data = store.data.lock().unwrap();
+        let counters = &data.days[&today()];
+        assert_eq!(counters.get("lookup:de"), Some(&1));
+        assert_eq!(counters.get("lookup"), Some(&2));
+        assert!(counters.keys().all(|k| !k.contains("sa")));
+    }
+
+    #[test]
+    fn reset_clears_memory_and_the_file() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("usage.json");
+        let store = UsageStore::new();
+        *store.path.lock().unwrap() = Some(path.clone());
+        store.record(LOOKUP, Some("de"));
+        store.record(TERM_SAVED, Some("de"));
+        store.flush();
+
+        let written: UsageData = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(written.days[&today()].len(), 2);
+        assert_eq!(written.sessions.len(), 1);
+        assert!(!path.with_extension("json.tmp").exists());
+
+        store.reset();
+        let written: UsageData = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert!(written.days.is_empty());
+        // The running session starts counting again from the reset
+        assert_eq!(written.sessions.len(), 1);
+        let summary = aggregate(&store.data.lock().unwrap(), Local::now().date_naive(), 30);
+        assert_eq!((summary.total_lookups, summary.terms_saved), (0, 0));
+    }
+}